@@ -238,6 +238,100 @@ pub enum SyscallNumber {
 }
 
 impl SyscallNumber {
+    /// Every assigned syscall, in ascending numeric order.
+    pub const ALL: [Self; 90] = [
+        Self::GetPid,
+        Self::Spawn,
+        Self::SendIpc,
+        Self::ReceiveIpc,
+        Self::BlockForIpc,
+        Self::EnumerateDevices,
+        Self::DeviceRead,
+        Self::DeviceWrite,
+        Self::Mmap,
+        Self::Munmap,
+        Self::Malloc,
+        Self::Free,
+        Self::ReceiveOrBlockIpc,
+        Self::Realloc,
+        Self::MallocAligned,
+        Self::DeviceInfo,
+        Self::OpenAt,
+        Self::Close,
+        Self::Read,
+        Self::Write,
+        Self::Pread64,
+        Self::Pwrite64,
+        Self::Lseek,
+        Self::Statx,
+        Self::NewFstatAt,
+        Self::Getdents64,
+        Self::MkdirAt,
+        Self::UnlinkAt,
+        Self::RenameAt2,
+        Self::Ftruncate,
+        Self::Fsync,
+        Self::Mount,
+        Self::Chdir,
+        Self::Fchdir,
+        Self::Getcwd,
+        Self::Faccessat,
+        Self::Fchmodat,
+        Self::Fchownat,
+        Self::Symlinkat,
+        Self::Readlinkat,
+        Self::Linkat,
+        Self::RegisterService,
+        Self::SendServiceIpc,
+        Self::ClaimDevice,
+        Self::ReleaseDevice,
+        Self::Fork,
+        Self::Execve,
+        Self::Exit,
+        Self::Wait4,
+        Self::GetPpid,
+        Self::SetPgid,
+        Self::Setsid,
+        Self::GetUid,
+        Self::GetEuid,
+        Self::SetUid,
+        Self::GetGid,
+        Self::SetGid,
+        Self::GetGroups,
+        Self::SetGroups,
+        Self::RtSigaction,
+        Self::RtSigprocmask,
+        Self::Kill,
+        Self::RtSigreturn,
+        Self::ClockGettime,
+        Self::Nanosleep,
+        Self::TimerCreate,
+        Self::TimerSettime,
+        Self::TimerGettime,
+        Self::TimerDelete,
+        Self::Dup,
+        Self::Dup2,
+        Self::Dup3,
+        Self::Fcntl,
+        Self::Ioctl,
+        Self::Pipe2,
+        Self::Poll,
+        Self::Pselect,
+        Self::Eventfd,
+        Self::Socket,
+        Self::Bind,
+        Self::Listen,
+        Self::Accept,
+        Self::Connect,
+        Self::Sendmsg,
+        Self::Recvmsg,
+        Self::Clone,
+        Self::Futex,
+        Self::SetThreadArea,
+        Self::ArchPrctl,
+        Self::Yield,
+    ];
+
     pub const fn raw(self) -> u64 {
         self as u64
     }
@@ -471,4 +565,121 @@ mod tests {
         assert_eq!(MIRAGE_SYSCALL_WAIT, 103);
         assert_eq!(MIRAGE_SYSCALL_WAIT4, 103);
     }
+
+    /// Numeric assignments are ABI. Append new syscalls here; never edit an
+    /// existing row.
+    const GOLDEN_SYSCALL_NUMBERS: [(SyscallNumber, u64); 90] = [
+        (SyscallNumber::GetPid, 0),
+        (SyscallNumber::Spawn, 1),
+        (SyscallNumber::SendIpc, 2),
+        (SyscallNumber::ReceiveIpc, 3),
+        (SyscallNumber::BlockForIpc, 4),
+        (SyscallNumber::EnumerateDevices, 5),
+        (SyscallNumber::DeviceRead, 6),
+        (SyscallNumber::DeviceWrite, 7),
+        (SyscallNumber::Mmap, 8),
+        (SyscallNumber::Munmap, 9),
+        (SyscallNumber::Malloc, 10),
+        (SyscallNumber::Free, 11),
+        (SyscallNumber::ReceiveOrBlockIpc, 12),
+        (SyscallNumber::Realloc, 13),
+        (SyscallNumber::MallocAligned, 14),
+        (SyscallNumber::DeviceInfo, 15),
+        (SyscallNumber::OpenAt, 16),
+        (SyscallNumber::Close, 17),
+        (SyscallNumber::Read, 18),
+        (SyscallNumber::Write, 19),
+        (SyscallNumber::Pread64, 20),
+        (SyscallNumber::Pwrite64, 21),
+        (SyscallNumber::Lseek, 22),
+        (SyscallNumber::Statx, 23),
+        (SyscallNumber::NewFstatAt, 24),
+        (SyscallNumber::Getdents64, 25),
+        (SyscallNumber::MkdirAt, 26),
+        (SyscallNumber::UnlinkAt, 27),
+        (SyscallNumber::RenameAt2, 28),
+        (SyscallNumber::Ftruncate, 29),
+        (SyscallNumber::Fsync, 30),
+        (SyscallNumber::Mount, 31),
+        (SyscallNumber::Chdir, 32),
+        (SyscallNumber::Fchdir, 33),
+        (SyscallNumber::Getcwd, 34),
+        (SyscallNumber::Faccessat, 35),
+        (SyscallNumber::Fchmodat, 36),
+        (SyscallNumber::Fchownat, 37),
+        (SyscallNumber::Symlinkat, 38),
+        (SyscallNumber::Readlinkat, 39),
+        (SyscallNumber::Linkat, 40),
+        (SyscallNumber::RegisterService, 41),
+        (SyscallNumber::SendServiceIpc, 42),
+        (SyscallNumber::ClaimDevice, 43),
+        (SyscallNumber::ReleaseDevice, 44),
+        (SyscallNumber::Fork, 100),
+        (SyscallNumber::Execve, 101),
+        (SyscallNumber::Exit, 102),
+        (SyscallNumber::Wait4, 103),
+        (SyscallNumber::GetPpid, 104),
+        (SyscallNumber::SetPgid, 105),
+        (SyscallNumber::Setsid, 106),
+        (SyscallNumber::GetUid, 120),
+        (SyscallNumber::GetEuid, 121),
+        (SyscallNumber::SetUid, 122),
+        (SyscallNumber::GetGid, 123),
+        (SyscallNumber::SetGid, 124),
+        (SyscallNumber::GetGroups, 125),
+        (SyscallNumber::SetGroups, 126),
+        (SyscallNumber::RtSigaction, 140),
+        (SyscallNumber::RtSigprocmask, 141),
+        (SyscallNumber::Kill, 142),
+        (SyscallNumber::RtSigreturn, 143),
+        (SyscallNumber::ClockGettime, 160),
+        (SyscallNumber::Nanosleep, 161),
+        (SyscallNumber::TimerCreate, 162),
+        (SyscallNumber::TimerSettime, 163),
+        (SyscallNumber::TimerGettime, 164),
+        (SyscallNumber::TimerDelete, 165),
+        (SyscallNumber::Dup, 180),
+        (SyscallNumber::Dup2, 181),
+        (SyscallNumber::Dup3, 182),
+        (SyscallNumber::Fcntl, 183),
+        (SyscallNumber::Ioctl, 184),
+        (SyscallNumber::Pipe2, 200),
+        (SyscallNumber::Poll, 201),
+        (SyscallNumber::Pselect, 202),
+        (SyscallNumber::Eventfd, 203),
+        (SyscallNumber::Socket, 220),
+        (SyscallNumber::Bind, 221),
+        (SyscallNumber::Listen, 222),
+        (SyscallNumber::Accept, 223),
+        (SyscallNumber::Connect, 224),
+        (SyscallNumber::Sendmsg, 225),
+        (SyscallNumber::Recvmsg, 226),
+        (SyscallNumber::Clone, 240),
+        (SyscallNumber::Futex, 241),
+        (SyscallNumber::SetThreadArea, 242),
+        (SyscallNumber::ArchPrctl, 243),
+        (SyscallNumber::Yield, 244),
+    ];
+
+    #[test]
+    fn syscall_numbers_match_golden_table() {
+        assert_eq!(SyscallNumber::ALL.len(), GOLDEN_SYSCALL_NUMBERS.len());
+        for (index, (syscall, raw)) in GOLDEN_SYSCALL_NUMBERS.iter().enumerate() {
+            assert_eq!(SyscallNumber::ALL[index], *syscall);
+            assert_eq!(syscall.raw(), *raw, "{syscall:?} was renumbered");
+            assert_eq!(SyscallNumber::from_raw(*raw), Some(*syscall));
+        }
+    }
+
+    #[test]
+    fn unassigned_numbers_do_not_decode() {
+        let mut raw = 0;
+        while raw < 512 {
+            let assigned = GOLDEN_SYSCALL_NUMBERS
+                .iter()
+                .any(|(_, number)| *number == raw);
+            assert_eq!(SyscallNumber::from_raw(raw).is_some(), assigned);
+            raw += 1;
+        }
+    }
 }
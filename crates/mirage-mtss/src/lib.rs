@@ -584,6 +584,61 @@ pub mod types {
         }
     }
 
+    /// Scheduling discipline applied to a thread.
+    ///
+    /// Classed dispatch drains the band of each thread's priority in order,
+    /// except that interactive and batch threads that keep getting passed over
+    /// age up one band per [`SchedClass::AGING_THRESHOLD`] picks so they
+    /// cannot starve. An aged thread can therefore run ahead of interactive
+    /// work of higher priority; only realtime threads are drained strictly.
+    /// Realtime threads always stay in their priority's band, queue ahead of
+    /// other threads in it, and are never overtaken by aging. Interactive
+    /// threads queue one band higher right after waking. Batch threads queue
+    /// in the lowest band but run with longer slices.
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+    pub enum SchedClass {
+        Realtime,
+        #[default]
+        Interactive,
+        Batch,
+    }
+
+    impl SchedClass {
+        /// Dispatch decisions a queued thread may be passed over before it is
+        /// ranked one band higher.
+        pub const AGING_THRESHOLD: u8 = 8;
+        /// Batch slices are this many times the configured default.
+        pub const BATCH_SLICE_FACTOR: u64 = 4;
+
+        pub const fn timeslice(self, default: Timeslice) -> Timeslice {
+            match self {
+                SchedClass::Batch => {
+                    Timeslice::from_ticks(default.ticks().saturating_mul(Self::BATCH_SLICE_FACTOR))
+                }
+                SchedClass::Realtime | SchedClass::Interactive => default,
+            }
+        }
+    }
+
+    /// How MTSS orders queued threads.
+    ///
+    /// `Classed` dispatches by [`SchedClass`] and priority. `Stride` ignores
+    /// classes and shares the CPU in proportion to priority weight: each
+    /// dispatch advances a thread's pass by its stride, and the queued thread
+    /// with the lowest pass runs next. `ProcessFair` serves runnable tasks
+    /// round-robin and runs one of the task's threads per turn, so a task gets
+    /// the same CPU share however many threads it has.
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+    pub enum SchedulingMode {
+        #[default]
+        Classed,
+        Stride,
+        ProcessFair,
+    }
+
+    /// Pass distance covered by a weight-one thread per dispatch.
+    pub const STRIDE_BASE: u64 = 1 << 20;
+
     /// Scheduler-visible micro-thread state.
     #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
     pub enum ThreadState {
@@ -765,6 +820,12 @@ pub mod types {
         pub const fn raw(self) -> u8 {
             self.0
         }
+
+        /// CPU share weight under [`SchedulingMode::Stride`]: 8 for the
+        /// critical quarter of the range down to 2 for the low quarter.
+        pub const fn stride_weight(self) -> u64 {
+            8 - 2 * (self.0 as u64 / 64)
+        }
     }
 
     /// Portable MTSS task descriptor.  It intentionally omits credentials,
@@ -778,6 +839,9 @@ pub mod types {
         pub priority: Priority,
         pub thread_count: u16,
         pub cpu_time_ticks: u128,
+        /// Turn at which one of this task's threads last dispatched under
+        /// [`SchedulingMode::ProcessFair`]; lowest runs first.
+        pub last_turn: u64,
     }
 
     impl Task {
@@ -795,6 +859,7 @@ pub mod types {
                 priority,
                 thread_count: 0,
                 cpu_time_ticks: 0,
+                last_turn: 0,
             }
         }
 
@@ -863,6 +928,14 @@ pub mod types {
         pub priority: Priority,
         pub timeslice: Timeslice,
         pub cpu_time_ticks: u128,
+        pub class: SchedClass,
+        /// Set when an interactive thread wakes; cleared once it dispatches.
+        pub wake_boost: bool,
+        /// Bands below its priority's band an interactive thread queues in
+        /// after using up whole slices under classed dispatch.
+        pub demotion: u8,
+        /// Stride-scheduling virtual time; lowest runs first.
+        pub pass: u64,
     }
 
     impl Thread {
@@ -879,9 +952,17 @@ pub mod types {
                 priority,
                 timeslice,
                 cpu_time_ticks: 0,
+                class: SchedClass::Interactive,
+                wake_boost: false,
+                demotion: 0,
+                pass: 0,
             }
         }
 
+        pub const fn stride(&self) -> u64 {
+            STRIDE_BASE / self.priority.stride_weight()
+        }
+
         pub fn admit(&mut self) -> Result<ThreadState, MtssError> {
             self.transition(ThreadState::Ready)
         }
@@ -1027,6 +1108,8 @@ pub mod types {
         ProcessRecordFull,
         BackendUnavailable,
         CapabilityDenied,
+        /// The installed admission policy refused the thread.
+        AdmissionRejected,
     }
 }
 
@@ -1035,10 +1118,12 @@ pub use backend::{
 };
 pub use lifecycle::{LifecycleEvent, LifecycleReason, MtssEvent, MtssEventKind, MtssEventSink};
 pub use mtss::{
-    Mtss, MtssConfig, MtssHandle, DEFAULT_EVENT_QUEUE_DEPTH, DEFAULT_MAX_TASKS,
-    DEFAULT_MAX_THREADS, DEFAULT_RUN_QUEUE_DEPTH,
+    AdmissionDecision, AdmissionPolicy, Mtss, MtssConfig, MtssHandle, ADMISSION_HOLD_SLOTS,
+    DEFAULT_EVENT_QUEUE_DEPTH, DEFAULT_MAX_TASKS, DEFAULT_MAX_THREADS, DEFAULT_RUN_QUEUE_DEPTH,
+};
+pub use run_queue::{
+    BandedRunQueue, MtssThreadScheduleRecord, PriorityBand, RunQueue, RUN_QUEUE_BANDS,
 };
-pub use run_queue::{MtssThreadScheduleRecord, RunQueue};
 pub use scheduler::{ScheduleDecision, SchedulerCore};
 pub use stats::{MtssStats, SchedulerStats};
 pub use task_core::{
@@ -1050,8 +1135,8 @@ pub use task_core::{
 pub use types::{
     valid_process_transition, valid_task_transition, valid_thread_transition, AddressSpaceId,
     CpuId, CredentialHandle, GrantHandle, MtssError, Priority, ProcessId, ProcessRecord,
-    ProcessState, RunQueueId, Task, TaskId, TaskState, Thread, ThreadDescriptor, ThreadId,
-    ThreadState, TimeSlice, Timeslice, Timestamp,
+    ProcessState, RunQueueId, SchedClass, SchedulingMode, Task, TaskId, TaskState, Thread,
+    ThreadDescriptor, ThreadId, ThreadState, TimeSlice, Timeslice, Timestamp, STRIDE_BASE,
 };
 
 #[cfg(test)]
@@ -1198,6 +1283,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn position_of_matches_dispatch_order() {
+        const THREAD_C: ThreadId = ThreadId::new(12);
+        let mut mtss = mtss::<16>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        create_thread(&mut mtss, THREAD_B);
+        create_thread(&mut mtss, THREAD_C);
+        assert_eq!(mtss.position_of(THREAD_A), None);
+
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        mtss.enqueue_thread(THREAD_C).unwrap();
+        assert_eq!(mtss.position_of(THREAD_A), Some(0));
+        assert_eq!(mtss.position_of(THREAD_B), Some(1));
+        assert_eq!(mtss.position_of(THREAD_C), Some(2));
+
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_A);
+        assert_eq!(mtss.position_of(THREAD_A), None);
+        assert_eq!(mtss.position_of(THREAD_B), Some(0));
+        assert_eq!(mtss.position_of(THREAD_C), Some(1));
+
+        mtss.block_thread(THREAD_B).unwrap();
+        assert_eq!(mtss.position_of(THREAD_B), None);
+        assert_eq!(mtss.position_of(THREAD_C), Some(0));
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_C);
+    }
+
     #[test]
     fn timer_tick_without_expiry_only_accounts_cpu_time() {
         let mut mtss = mtss::<8>();
@@ -1339,6 +1452,55 @@ mod tests {
         assert_eq!(mtss.yield_current().unwrap().unwrap().next, THREAD_A);
     }
 
+    #[test]
+    fn pass_current_hands_the_cpu_to_another_thread_or_keeps_it() {
+        let mut mtss = mtss::<16>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_A);
+
+        assert_eq!(mtss.pass_current(), Ok(None));
+        assert_eq!(mtss.current(), Some(THREAD_A));
+
+        create_thread(&mut mtss, THREAD_B);
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        let decision = mtss.pass_current().unwrap().unwrap();
+        assert_eq!(
+            (decision.previous, decision.next),
+            (Some(THREAD_A), THREAD_B)
+        );
+        assert_eq!(mtss.position_of(THREAD_A), Some(0));
+    }
+
+    #[test]
+    fn threads_that_use_up_slices_sink_and_early_blockers_climb_back() {
+        let mut mtss = mtss::<32>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        create_thread(&mut mtss, THREAD_B);
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_A);
+
+        // A yield keeps what is left of the slice, so A uses it up across
+        // two turns.
+        mtss.on_timer_tick().unwrap();
+        assert_eq!(mtss.yield_current().unwrap().unwrap().next, THREAD_B);
+        mtss.on_timer_tick().unwrap();
+        assert_eq!(mtss.yield_current().unwrap().unwrap().next, THREAD_A);
+        assert_eq!(mtss.on_timer_tick().unwrap().unwrap().next, THREAD_B);
+        assert_eq!(mtss.queue_depth_for_priority(Priority::NORMAL), 0);
+        assert_eq!(mtss.queue_depth_for_priority(Priority::LOW), 1);
+
+        // Blocking on a fresh slice lifts A back to its own band, and the
+        // wake boost queues it one band above that.
+        mtss.block_thread(THREAD_A).unwrap();
+        mtss.wake_thread(THREAD_A).unwrap();
+        assert_eq!(mtss.queue_depth_for_priority(Priority::LOW), 0);
+        assert_eq!(mtss.queue_depth_for_priority(Priority::HIGH), 1);
+    }
+
     #[test]
     fn block_removes_thread_from_run_queue_and_updates_stats() {
         let mut mtss = mtss::<8>();
@@ -1511,6 +1673,333 @@ mod tests {
         );
     }
 
+    #[test]
+    fn realtime_thread_preempts_running_batch_thread() {
+        let mut mtss = mtss::<32>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        mtss.set_thread_class(THREAD_A, SchedClass::Batch).unwrap();
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_A);
+
+        create_thread(&mut mtss, THREAD_B);
+        mtss.set_thread_class(THREAD_B, SchedClass::Realtime)
+            .unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        assert!(mtss.need_resched());
+
+        // The batch slice is far from expired, but the realtime arrival still
+        // takes the CPU on the next tick.
+        assert_eq!(mtss.on_timer_tick().unwrap().unwrap().next, THREAD_B);
+        assert_eq!(mtss.position_of(THREAD_A), Some(0));
+    }
+
+    #[test]
+    fn higher_band_arrival_preempts_running_thread() {
+        const THREAD_C: ThreadId = ThreadId::new(12);
+        let mut mtss = mtss::<32>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_A);
+
+        // An arrival in the running thread's own band waits its turn.
+        create_thread(&mut mtss, THREAD_B);
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        assert!(!mtss.need_resched());
+
+        mtss.create_thread(TASK, THREAD_C, Priority::CRITICAL)
+            .unwrap();
+        mtss.enqueue_thread(THREAD_C).unwrap();
+        assert!(mtss.need_resched());
+        assert_eq!(mtss.on_timer_tick().unwrap().unwrap().next, THREAD_C);
+    }
+
+    #[test]
+    fn lowered_priority_reorders_queued_threads() {
+        let mut mtss = mtss::<32>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        create_thread(&mut mtss, THREAD_B);
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        assert_eq!(mtss.position_of(THREAD_A), Some(0));
+
+        mtss.set_thread_priority(THREAD_A, Priority::LOW).unwrap();
+        assert_eq!(mtss.position_of(THREAD_A), Some(1));
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_B);
+    }
+
+    fn admit_by_priority(thread: &Thread) -> AdmissionDecision {
+        match thread.priority {
+            Priority::LOW => AdmissionDecision::Reject,
+            Priority::NORMAL => AdmissionDecision::Defer(2),
+            _ => AdmissionDecision::Admit,
+        }
+    }
+
+    #[test]
+    fn admission_policy_rejects_defers_and_admits() {
+        const THREAD_C: ThreadId = ThreadId::new(12);
+        let mut mtss = mtss::<32>();
+        create_task(&mut mtss);
+        mtss.create_thread(TASK, THREAD_A, Priority::LOW).unwrap();
+        create_thread(&mut mtss, THREAD_B);
+        mtss.create_thread(TASK, THREAD_C, Priority::HIGH).unwrap();
+        mtss.set_admission_policy(Some(admit_by_priority));
+
+        assert_eq!(
+            mtss.enqueue_thread(THREAD_A),
+            Err(MtssError::AdmissionRejected)
+        );
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        mtss.enqueue_thread(THREAD_C).unwrap();
+        assert_eq!(mtss.queued_threads(), 1);
+        assert_eq!(mtss.held_threads(), 1);
+        assert_eq!(mtss.position_of(THREAD_B), None);
+
+        mtss.on_timer_tick().unwrap();
+        assert_eq!(mtss.held_threads(), 1);
+        mtss.on_timer_tick().unwrap();
+        assert_eq!(mtss.held_threads(), 0);
+        assert_eq!(mtss.queued_threads(), 2);
+        assert_eq!(mtss.position_of(THREAD_C), Some(0));
+        assert_eq!(mtss.position_of(THREAD_B), Some(1));
+
+        mtss.set_admission_policy(None);
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        assert_eq!(mtss.queued_threads(), 3);
+    }
+
+    #[test]
+    fn interactive_threads_get_wake_boosts_and_batch_threads_do_not() {
+        for (class, first) in [
+            (SchedClass::Interactive, THREAD_B),
+            (SchedClass::Batch, THREAD_A),
+        ] {
+            let mut mtss = mtss::<32>();
+            create_task(&mut mtss);
+            create_thread(&mut mtss, THREAD_A);
+            create_thread(&mut mtss, THREAD_B);
+            mtss.set_thread_class(THREAD_A, class).unwrap();
+            mtss.set_thread_class(THREAD_B, class).unwrap();
+            mtss.enqueue_thread(THREAD_A).unwrap();
+            mtss.enqueue_thread(THREAD_B).unwrap();
+
+            mtss.block_thread(THREAD_B).unwrap();
+            mtss.wake_thread(THREAD_B).unwrap();
+
+            assert_eq!(mtss.position_of(first), Some(0));
+            assert_eq!(mtss.pick_next().unwrap().unwrap().next, first);
+        }
+    }
+
+    #[test]
+    fn default_class_threads_jump_the_queue_after_waking() {
+        let mut mtss = mtss::<32>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        create_thread(&mut mtss, THREAD_B);
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        assert_eq!(mtss.position_of(THREAD_A), Some(0));
+
+        // New threads are interactive, so waking is not FIFO.
+        mtss.block_thread(THREAD_A).unwrap();
+        mtss.wake_thread(THREAD_A).unwrap();
+        assert_eq!(mtss.position_of(THREAD_A), Some(0));
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_A);
+        mtss.requeue_current().unwrap();
+        assert_eq!(mtss.position_of(THREAD_A), Some(1));
+    }
+
+    #[test]
+    fn batch_threads_age_past_fresh_interactive_work() {
+        let mut mtss = mtss::<64>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        create_thread(&mut mtss, THREAD_B);
+        mtss.set_thread_class(THREAD_A, SchedClass::Batch).unwrap();
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+
+        let mut passes = 0u8;
+        while mtss.pick_next().unwrap().unwrap().next == THREAD_B {
+            mtss.requeue_current().unwrap();
+            passes += 1;
+        }
+        assert_eq!(passes, SchedClass::AGING_THRESHOLD);
+    }
+
+    #[test]
+    fn aged_batch_threads_never_outrank_realtime_threads() {
+        const THREAD_C: ThreadId = ThreadId::new(12);
+        let mut mtss = mtss::<128>();
+        create_task(&mut mtss);
+        mtss.create_thread(TASK, THREAD_A, Priority::CRITICAL)
+            .unwrap();
+        create_thread(&mut mtss, THREAD_B);
+        mtss.create_thread(TASK, THREAD_C, Priority::CRITICAL)
+            .unwrap();
+        mtss.set_thread_class(THREAD_B, SchedClass::Batch).unwrap();
+        mtss.set_thread_class(THREAD_C, SchedClass::Realtime)
+            .unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        mtss.enqueue_thread(THREAD_A).unwrap();
+
+        // Three aging steps lift B from the lowest band to the top one.
+        for _ in 0..3 * SchedClass::AGING_THRESHOLD {
+            assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_A);
+            mtss.requeue_current().unwrap();
+        }
+        mtss.enqueue_thread(THREAD_C).unwrap();
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_C);
+        mtss.requeue_current().unwrap();
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_C);
+
+        mtss.block_thread(THREAD_C).unwrap();
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_B);
+    }
+
+    #[test]
+    fn position_of_counts_aged_threads_ahead_of_higher_bands() {
+        let mut mtss = mtss::<128>();
+        create_task(&mut mtss);
+        mtss.create_thread(TASK, THREAD_A, Priority::CRITICAL)
+            .unwrap();
+        create_thread(&mut mtss, THREAD_B);
+        mtss.set_thread_class(THREAD_B, SchedClass::Batch).unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        assert_eq!(mtss.position_of(THREAD_A), Some(0));
+        assert_eq!(mtss.position_of(THREAD_B), Some(1));
+
+        // B has aged from the lowest band up to A's, and was queued first.
+        for _ in 0..3 * SchedClass::AGING_THRESHOLD {
+            assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_A);
+            mtss.requeue_current().unwrap();
+        }
+        assert_eq!(mtss.position_of(THREAD_B), Some(0));
+        assert_eq!(mtss.position_of(THREAD_A), Some(1));
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_B);
+    }
+
+    #[test]
+    fn stride_mode_shares_cpu_by_priority_weight() {
+        let mut mtss = mtss::<0>();
+        mtss.set_scheduling_mode(SchedulingMode::Stride);
+        create_task(&mut mtss);
+        mtss.create_thread(TASK, THREAD_A, Priority::CRITICAL)
+            .unwrap();
+        mtss.create_thread(TASK, THREAD_B, Priority::LOW).unwrap();
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        mtss.pick_next().unwrap();
+
+        let mut ticks = [0u32; 2];
+        for _ in 0..1000 {
+            match mtss.current() {
+                Some(THREAD_A) => ticks[0] += 1,
+                Some(THREAD_B) => ticks[1] += 1,
+                other => panic!("unexpected current thread {other:?}"),
+            }
+            mtss.on_timer_tick().unwrap();
+        }
+        assert_eq!(ticks[0] + ticks[1], 1000);
+        assert!((790..=810).contains(&ticks[0]), "{ticks:?}");
+    }
+
+    #[test]
+    fn process_fair_mode_shares_cpu_per_task_not_per_thread() {
+        let mut mtss = mtss::<0>();
+        mtss.set_scheduling_mode(SchedulingMode::ProcessFair);
+        create_task(&mut mtss);
+        mtss.create_task(OTHER_TASK, None, AddressSpaceId::new(2), Priority::NORMAL)
+            .unwrap();
+        let crowd = [
+            ThreadId::new(10),
+            ThreadId::new(11),
+            ThreadId::new(12),
+            ThreadId::new(13),
+        ];
+        const LONER: ThreadId = ThreadId::new(20);
+        for thread in crowd {
+            create_thread(&mut mtss, thread);
+            mtss.enqueue_thread(thread).unwrap();
+        }
+        mtss.create_thread(OTHER_TASK, LONER, Priority::NORMAL)
+            .unwrap();
+        mtss.enqueue_thread(LONER).unwrap();
+        mtss.pick_next().unwrap();
+
+        let mut crowd_ticks = [0u32; 4];
+        let mut loner_ticks = 0u32;
+        for _ in 0..1000 {
+            match mtss.current() {
+                Some(LONER) => loner_ticks += 1,
+                Some(thread) => {
+                    let slot = crowd.iter().position(|&t| t == thread).unwrap();
+                    crowd_ticks[slot] += 1;
+                }
+                None => panic!("no thread running"),
+            }
+            mtss.on_timer_tick().unwrap();
+        }
+        let crowd_total: u32 = crowd_ticks.iter().sum();
+        assert_eq!(crowd_total + loner_ticks, 1000);
+        assert!((490..=510).contains(&loner_ticks), "{loner_ticks}");
+        // The crowd's threads rotate through its turns evenly.
+        assert!(
+            crowd_ticks.iter().all(|&t| (120..=130).contains(&t)),
+            "{crowd_ticks:?}"
+        );
+    }
+
+    #[test]
+    fn stride_arrivals_start_at_the_current_pass() {
+        let mut mtss = mtss::<0>();
+        mtss.set_scheduling_mode(SchedulingMode::Stride);
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+        create_thread(&mut mtss, THREAD_B);
+        mtss.enqueue_thread(THREAD_A).unwrap();
+        for _ in 0..20 {
+            mtss.yield_current().unwrap();
+        }
+
+        // B was never queued, but must not get twenty dispatches in a row.
+        mtss.enqueue_thread(THREAD_B).unwrap();
+        let mut order = [ThreadId::IDLE; 4];
+        for slot in order.iter_mut() {
+            *slot = mtss.yield_current().unwrap().unwrap().next;
+        }
+        assert_eq!(order, [THREAD_B, THREAD_A, THREAD_B, THREAD_A]);
+    }
+
+    #[test]
+    fn reap_thread_releases_only_dead_threads() {
+        let mut mtss = mtss::<16>();
+        create_task(&mut mtss);
+        create_thread(&mut mtss, THREAD_A);
+
+        assert_eq!(
+            mtss.reap_thread(THREAD_A),
+            Err(MtssError::InvalidThreadTransition {
+                from: ThreadState::New,
+                to: ThreadState::Dead,
+            })
+        );
+
+        mtss.exit_thread(THREAD_A).unwrap();
+        mtss.reap_thread(THREAD_A).unwrap();
+
+        assert_eq!(mtss.reap_thread(THREAD_A), Err(MtssError::InvalidThread));
+        assert_eq!(
+            mtss.create_thread(TASK, THREAD_A, Priority::NORMAL),
+            Ok(MtssHandle::thread(TASK, THREAD_A)),
+        );
+    }
+
     #[test]
     fn invalid_transition_is_denied_without_mutating_stats_or_emitting_events() {
         let mut mtss = mtss::<16>();
@@ -1729,4 +2218,175 @@ mod tests {
             Err(MtssError::ProcessRecordFull)
         );
     }
+
+    type BandedTestQueue = BandedRunQueue<MtssThreadScheduleRecord<ThreadId, TaskId, Priority>, 4>;
+
+    fn banded_record(
+        thread: u64,
+        task: TaskId,
+        priority: Priority,
+    ) -> MtssThreadScheduleRecord<ThreadId, TaskId, Priority> {
+        MtssThreadScheduleRecord::new(ThreadId::new(thread), task, priority, 2)
+    }
+
+    #[test]
+    fn banded_run_queue_dispatches_highest_band_first_and_fifo_within() {
+        let mut queue = BandedTestQueue::new();
+        queue
+            .enqueue(banded_record(1, TASK, Priority::LOW))
+            .unwrap();
+        queue
+            .enqueue(banded_record(2, TASK, Priority::NORMAL))
+            .unwrap();
+        queue
+            .enqueue(banded_record(3, TASK, Priority::CRITICAL))
+            .unwrap();
+        queue
+            .enqueue(banded_record(4, TASK, Priority::NORMAL))
+            .unwrap();
+        queue
+            .enqueue(banded_record(5, TASK, Priority::HIGH))
+            .unwrap();
+
+        assert_eq!(queue.len(), 5);
+        assert_eq!(queue.position_of_thread(ThreadId::new(1)), Some(4));
+        assert_eq!(queue.position_of_thread(ThreadId::new(4)), Some(3));
+        let order: [u64; 5] = core::array::from_fn(|_| queue.pop_next().unwrap().thread.raw());
+        assert_eq!(order, [3, 5, 2, 4, 1]);
+        assert!(queue.pop_next().is_none());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn banded_run_queue_removes_from_the_owning_band() {
+        let mut queue = BandedTestQueue::new();
+        queue
+            .enqueue(banded_record(1, TASK, Priority::HIGH))
+            .unwrap();
+        queue
+            .enqueue(banded_record(2, OTHER_TASK, Priority::HIGH))
+            .unwrap();
+        queue
+            .enqueue(banded_record(3, OTHER_TASK, Priority::LOW))
+            .unwrap();
+        queue
+            .enqueue(banded_record(4, TASK, Priority::LOW))
+            .unwrap();
+
+        assert_eq!(queue.remove_thread(ThreadId::new(1)), 1);
+        assert_eq!(queue.band_len(Priority::HIGH.band()), 1);
+        assert_eq!(queue.band_len(Priority::LOW.band()), 2);
+
+        assert_eq!(queue.remove_process(OTHER_TASK), 2);
+        assert!(!queue.contains_process(OTHER_TASK));
+        assert_eq!(queue.band_len(Priority::HIGH.band()), 0);
+        assert_eq!(queue.pop_next().unwrap().thread, ThreadId::new(4));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn banded_run_queue_demotes_slice_hogs_and_promotes_early_blockers() {
+        let mut queue = BandedTestQueue::new();
+        let mut hog = banded_record(1, TASK, Priority::HIGH);
+        queue
+            .enqueue(banded_record(2, TASK, Priority::NORMAL))
+            .unwrap();
+
+        for _ in 0..hog.slice_budget() {
+            hog.consume_time_slice();
+        }
+        queue.requeue(hog).unwrap();
+        assert_eq!(queue.queue_depth_for_priority(Priority::HIGH), 0);
+        assert_eq!(queue.queue_depth_for_priority(Priority::NORMAL), 2);
+
+        let mut hog = queue.pop_next().unwrap();
+        assert_eq!(hog.thread, ThreadId::new(2));
+        hog = queue.pop_next().unwrap();
+        assert_eq!((hog.thread, hog.demotion()), (ThreadId::new(1), 1));
+        assert_eq!(hog.remaining_slice(), hog.slice_budget());
+
+        for _ in 0..3 {
+            for _ in 0..hog.slice_budget() {
+                hog.consume_time_slice();
+            }
+            queue.requeue(hog).unwrap();
+            hog = queue.pop_next().unwrap();
+        }
+        assert_eq!(hog.demotion(), 2);
+
+        hog.consume_time_slice();
+        queue.requeue(hog).unwrap();
+        assert_eq!(queue.queue_depth_for_priority(Priority::LOW), 1);
+        hog = queue.pop_next().unwrap();
+        assert!(hog.remaining_slice() > 0);
+
+        queue.enqueue_woken(hog).unwrap();
+        assert_eq!(queue.queue_depth_for_priority(Priority::NORMAL), 1);
+        hog = queue.pop_next().unwrap();
+        assert_eq!(hog.remaining_slice(), hog.slice_budget());
+        queue.enqueue_woken(hog).unwrap();
+        hog = queue.pop_next().unwrap();
+        queue.enqueue_woken(hog).unwrap();
+        assert_eq!(queue.queue_depth_for_priority(Priority::HIGH), 1);
+        assert_eq!(queue.pop_next().unwrap().demotion(), 0);
+    }
+
+    #[test]
+    fn run_queue_removal_keeps_a_wrapped_queue_intact() {
+        let mut queue = RunQueue::<MtssThreadScheduleRecord<ThreadId, TaskId, Priority>, 4>::new();
+        for thread in 0..4 {
+            queue
+                .enqueue(banded_record(thread, TASK, Priority::NORMAL))
+                .unwrap();
+        }
+        assert_eq!(queue.remove_thread(ThreadId::new(1)), 1);
+        queue
+            .enqueue(banded_record(4, TASK, Priority::NORMAL))
+            .unwrap();
+
+        let order: [u64; 4] = core::array::from_fn(|_| queue.pop_next().unwrap().thread.raw());
+        assert_eq!(order, [0, 2, 3, 4]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn classed_dispatch_drains_higher_priority_bands_first() {
+        const THREAD_C: ThreadId = ThreadId::new(12);
+        let mut mtss = mtss::<32>();
+        create_task(&mut mtss);
+        mtss.create_thread(TASK, THREAD_A, Priority::LOW).unwrap();
+        create_thread(&mut mtss, THREAD_B);
+        mtss.create_thread(TASK, THREAD_C, Priority::CRITICAL)
+            .unwrap();
+        for thread in [THREAD_A, THREAD_B, THREAD_C] {
+            mtss.enqueue_thread(thread).unwrap();
+        }
+        assert_eq!(mtss.position_of(THREAD_C), Some(0));
+        assert_eq!(mtss.position_of(THREAD_A), Some(2));
+
+        let order: [ThreadId; 3] =
+            core::array::from_fn(|_| mtss.yield_current().unwrap().unwrap().next);
+        assert_eq!(order, [THREAD_C, THREAD_C, THREAD_C]);
+        mtss.block_thread(THREAD_C).unwrap();
+        assert_eq!(mtss.pick_next().unwrap().unwrap().next, THREAD_B);
+        assert_eq!(mtss.yield_current().unwrap().unwrap().next, THREAD_B);
+    }
+
+    #[test]
+    fn banded_run_queue_reports_a_full_band() {
+        let mut queue = BandedTestQueue::new();
+        for thread in 0..4 {
+            queue
+                .enqueue(banded_record(thread, TASK, Priority::NORMAL))
+                .unwrap();
+        }
+
+        assert_eq!(
+            queue.enqueue(banded_record(9, TASK, Priority::NORMAL)),
+            Err(MtssError::RunQueueFull)
+        );
+        queue
+            .enqueue(banded_record(9, TASK, Priority::LOW))
+            .unwrap();
+    }
 }
@@ -2,18 +2,22 @@
 //!
 //! The facade in this module is intentionally allocation-free by default. It
 //! keeps task/thread descriptors in caller-sized arrays and uses the portable
-//! run queue from [`crate::run_queue`]. Policy remains outside this crate; MTSS
-//! only validates lifecycle transitions, maintains scheduler-visible state, and
-//! emits minimal scheduling decisions.
+//! banded run queue from [`crate::run_queue`]. Apart from ordering dispatch by
+//! each thread's priority band and [`SchedClass`], with aging that lets a
+//! starved non-realtime thread overtake higher bands, by stride pass under
+//! [`SchedulingMode::Stride`], or by task turn under
+//! [`SchedulingMode::ProcessFair`], policy remains outside this crate; MTSS
+//! only validates lifecycle transitions, maintains scheduler-visible state,
+//! and emits minimal scheduling decisions.
 
 use crate::{
     lifecycle::{LifecycleReason, MtssEvent, MtssEventKind, MtssEventSink},
-    run_queue::{MtssThreadScheduleRecord, RunQueue},
+    run_queue::{BandedRunQueue, MtssThreadScheduleRecord, PriorityBand, RUN_QUEUE_BANDS},
     scheduler::ScheduleDecision,
     stats::MtssStats,
     types::{
-        AddressSpaceId, CpuId, MtssError, Priority, Task, TaskId, TaskState, Thread, ThreadId,
-        ThreadState, Timeslice, Timestamp,
+        AddressSpaceId, CpuId, MtssError, Priority, SchedClass, SchedulingMode, Task, TaskId,
+        TaskState, Thread, ThreadId, ThreadState, Timeslice, Timestamp,
     },
 };
 
@@ -25,6 +29,43 @@ pub const DEFAULT_MAX_THREADS: usize = 256;
 pub const DEFAULT_RUN_QUEUE_DEPTH: usize = 256;
 /// Default number of scheduler events retained by [`Mtss`].
 pub const DEFAULT_EVENT_QUEUE_DEPTH: usize = 256;
+/// Threads an admission policy can hold back at once.
+pub const ADMISSION_HOLD_SLOTS: usize = 16;
+
+/// Verdict of an admission policy on a thread about to be enqueued.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdmissionDecision {
+    Admit,
+    /// Refuse the thread; `enqueue_thread` fails with
+    /// [`MtssError::AdmissionRejected`].
+    Reject,
+    /// Hold the thread back for this many timer ticks before it joins the
+    /// run queue.
+    Defer(u64),
+}
+
+/// Policy consulted by [`Mtss::enqueue_thread`] before a thread is queued.
+pub type AdmissionPolicy = fn(&Thread) -> AdmissionDecision;
+
+/// Installed policy; compared by address so [`Mtss`] can stay `PartialEq`.
+#[derive(Clone, Copy, Debug)]
+struct AdmissionHook(AdmissionPolicy);
+
+impl PartialEq for AdmissionHook {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for AdmissionHook {}
+
+type ScheduleRecord = MtssThreadScheduleRecord<ThreadId, TaskId, Priority>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct HeldThread {
+    thread: ThreadId,
+    release_at: Timestamp,
+}
 
 /// Configuration for an MTSS scheduler instance.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -35,6 +76,8 @@ pub struct MtssConfig {
     pub initial_time: Timestamp,
     /// Default time slice assigned to newly-created threads.
     pub default_timeslice: Timeslice,
+    /// Dispatch ordering for queued threads.
+    pub scheduling_mode: SchedulingMode,
 }
 
 impl MtssConfig {
@@ -44,6 +87,7 @@ impl MtssConfig {
             cpu,
             initial_time: Timestamp::from_ticks(0),
             default_timeslice: Timeslice::from_ticks(4),
+            scheduling_mode: SchedulingMode::Classed,
         }
     }
 
@@ -58,6 +102,12 @@ impl MtssConfig {
         self.default_timeslice = default_timeslice;
         self
     }
+
+    /// Override the dispatch ordering.
+    pub const fn with_scheduling_mode(mut self, scheduling_mode: SchedulingMode) -> Self {
+        self.scheduling_mode = scheduling_mode;
+        self
+    }
 }
 
 impl Default for MtssConfig {
@@ -99,13 +149,23 @@ pub struct Mtss<
     current: Option<ThreadId>,
     tasks: [Option<Task>; MAX_TASKS],
     threads: [Option<Thread>; MAX_THREADS],
-    run_queue: RunQueue<MtssThreadScheduleRecord<ThreadId, TaskId, Priority>, RUN_QUEUE_DEPTH>,
+    run_queue: BandedRunQueue<ScheduleRecord, RUN_QUEUE_DEPTH>,
     stats: MtssStats,
     need_resched: bool,
+    /// Pass of the most recent stride dispatch; arriving threads start no
+    /// earlier so time spent off the queue does not bank CPU share.
+    stride_floor: u64,
+    /// Turns handed out under process-fair dispatch.
+    turn_clock: u64,
+    /// Classed picks made so far; queued records age against it.
+    picks: u64,
     events: [Option<MtssEvent>; EVENT_QUEUE_DEPTH],
     event_head: usize,
     event_len: usize,
     dropped_events: u64,
+    admission: Option<AdmissionHook>,
+    /// Threads deferred by the admission policy, released by the timer tick.
+    held: [Option<HeldThread>; ADMISSION_HOLD_SLOTS],
 }
 
 impl<
@@ -123,13 +183,18 @@ impl<
             current: None,
             tasks: [None; MAX_TASKS],
             threads: [None; MAX_THREADS],
-            run_queue: RunQueue::new(),
+            run_queue: BandedRunQueue::new(),
             stats: MtssStats::new(),
             need_resched: false,
+            stride_floor: 0,
+            turn_clock: 0,
+            picks: 0,
             events: [None; EVENT_QUEUE_DEPTH],
             event_head: 0,
             event_len: 0,
             dropped_events: 0,
+            admission: None,
+            held: [None; ADMISSION_HOLD_SLOTS],
         }
     }
 
@@ -138,11 +203,192 @@ impl<
         self.stats
     }
 
+    /// Return the number of threads waiting in the run queue.
+    pub const fn queued_threads(&self) -> usize {
+        self.run_queue.len()
+    }
+
+    /// Return the number of threads waiting in `priority`'s band, including
+    /// threads of higher priority demoted into it.
+    pub fn queue_depth_for_priority(&self, priority: Priority) -> usize {
+        self.run_queue.queue_depth_for_priority(priority)
+    }
+
+    /// Install or remove the policy consulted before each enqueue. Threads
+    /// already held keep their release time.
+    pub fn set_admission_policy(&mut self, policy: Option<AdmissionPolicy>) {
+        self.admission = policy.map(AdmissionHook);
+    }
+
+    /// Return the number of threads held back by the admission policy.
+    pub fn held_threads(&self) -> usize {
+        self.held.iter().flatten().count()
+    }
+
     /// Return the currently running thread, if any.
     pub const fn current(&self) -> Option<ThreadId> {
         self.current
     }
 
+    /// Return the dispatch ordering in effect.
+    pub const fn scheduling_mode(&self) -> SchedulingMode {
+        self.config.scheduling_mode
+    }
+
+    /// Switch dispatch ordering. Stride passes and task turns restart from
+    /// zero so history from an earlier period does not carry over.
+    pub fn set_scheduling_mode(&mut self, mode: SchedulingMode) {
+        self.config.scheduling_mode = mode;
+        self.stride_floor = 0;
+        self.turn_clock = 0;
+        let mut idx = 0;
+        while idx < MAX_THREADS {
+            if let Some(thread) = self.threads[idx].as_mut() {
+                thread.pass = 0;
+            }
+            idx += 1;
+        }
+        let mut idx = 0;
+        while idx < MAX_TASKS {
+            if let Some(task) = self.tasks[idx].as_mut() {
+                task.last_turn = 0;
+            }
+            idx += 1;
+        }
+        let mut idx = 0;
+        while idx < MAX_THREADS {
+            if let Some(thread) = self.threads[idx] {
+                if thread.state == ThreadState::Ready {
+                    self.rekey_queued(thread);
+                }
+            }
+            idx += 1;
+        }
+        if self.current.is_some() {
+            self.need_resched = true;
+        }
+    }
+
+    /// Return how many threads `pick_next` would dispatch before `thread`, or
+    /// `None` if it is not queued. Under classed dispatch this replays
+    /// [`Self::classed_choice`] over the band fronts, aging them as the picks
+    /// go by, so aged threads count ahead of the bands they overtake.
+    /// Otherwise threads dispatch by key and in queue order within a key, so
+    /// this counts earlier threads of equal or smaller key and later threads of
+    /// strictly smaller key.
+    pub fn position_of(&self, thread: ThreadId) -> Option<usize> {
+        if self.config.scheduling_mode == SchedulingMode::Classed {
+            self.run_queue.position_of_thread(thread)?;
+            let mut bands: [_; RUN_QUEUE_BANDS] =
+                core::array::from_fn(|band| self.run_queue.band_iter(band).peekable());
+            let mut picks = self.picks;
+            let mut ahead = 0usize;
+            loop {
+                let fronts = core::array::from_fn(|band| bands[band].peek().copied());
+                let band = Self::classed_choice(picks, &fronts)?;
+                if bands[band].next()?.thread == thread {
+                    return Some(ahead);
+                }
+                ahead += 1;
+                picks = picks.saturating_add(1);
+            }
+        }
+        let key = self
+            .run_queue
+            .iter()
+            .find(|record| record.thread == thread)?
+            .key();
+        let mut ahead = 0usize;
+        let mut passed = false;
+        for record in self.run_queue.iter() {
+            if record.thread == thread {
+                passed = true;
+                continue;
+            }
+            if record.key() < key || (!passed && record.key() == key) {
+                ahead += 1;
+            }
+        }
+        Some(ahead)
+    }
+
+    /// Change the scheduling class of `thread`. A pending wake boost, any
+    /// demotion and the thread's accumulated age are discarded.
+    pub fn set_thread_class(
+        &mut self,
+        thread: ThreadId,
+        class: SchedClass,
+    ) -> Result<(), MtssError> {
+        let current = self.current == Some(thread);
+        let updated = {
+            let thread = self.thread_mut(thread)?;
+            thread.class = class;
+            thread.wake_boost = false;
+            thread.demotion = 0;
+            *thread
+        };
+        self.reband_queued(updated, self.picks)?;
+        if current {
+            self.need_resched = true;
+        }
+        Ok(())
+    }
+
+    /// Change the priority of `thread`. A queued thread moves to the back of
+    /// its new band and keeps its age.
+    pub fn set_thread_priority(
+        &mut self,
+        thread: ThreadId,
+        priority: Priority,
+    ) -> Result<(), MtssError> {
+        let current = self.current == Some(thread);
+        let updated = {
+            let thread = self.thread_mut(thread)?;
+            thread.priority = priority;
+            *thread
+        };
+        let Some(queued_at) = self
+            .run_queue
+            .iter()
+            .find(|record| record.thread == thread)
+            .map(|record| record.queued_at())
+        else {
+            self.rekey_queued(updated);
+            if current {
+                self.need_resched = true;
+            }
+            return Ok(());
+        };
+        self.reband_queued(updated, queued_at)?;
+        if current {
+            self.need_resched = true;
+        }
+        Ok(())
+    }
+
+    /// Return the first queued thread, in dispatch order, for which `matches`
+    /// holds.
+    pub fn find_queued_thread(
+        &self,
+        mut matches: impl FnMut(ThreadId) -> bool,
+    ) -> Option<ThreadId> {
+        let mut found = None;
+        self.run_queue.position_of(|entry| {
+            if matches(entry.thread) {
+                found = Some(entry.thread);
+                true
+            } else {
+                false
+            }
+        });
+        found
+    }
+
+    /// Return every queued thread in run queue order, duplicates included.
+    pub fn queued_thread_ids(&self) -> impl Iterator<Item = ThreadId> + '_ {
+        self.run_queue.iter().map(|entry| entry.thread)
+    }
+
     /// Return whether the current CPU owes a reschedule after a deferred preemption.
     pub const fn need_resched(&self) -> bool {
         self.need_resched
@@ -235,18 +481,85 @@ impl<
         Ok(MtssHandle::thread(task, thread))
     }
 
-    /// Validate a thread transition into `Ready` and append it to the run queue.
+    /// Validate a thread transition into `Ready` and append it to the run
+    /// queue, unless the admission policy rejects or defers it. A deferred
+    /// thread is `Ready` but stays off the run queue until its hold expires.
     pub fn enqueue_thread(&mut self, thread: ThreadId) -> Result<(), MtssError> {
         self.ensure_run_queue_capacity()?;
-        let (record, task) = {
+        let decision = match self.admission {
+            Some(AdmissionHook(policy)) => policy(&self.thread(thread)?),
+            None => AdmissionDecision::Admit,
+        };
+        match decision {
+            AdmissionDecision::Admit => {}
+            AdmissionDecision::Reject => return Err(MtssError::AdmissionRejected),
+            AdmissionDecision::Defer(ticks) => return self.hold_thread(thread, ticks),
+        }
+        self.release_ready_thread(thread)
+    }
+
+    fn hold_thread(&mut self, thread: ThreadId, ticks: u64) -> Result<(), MtssError> {
+        let slot = self
+            .held
+            .iter()
+            .position(|held| held.is_some_and(|held| held.thread == thread))
+            .or_else(|| self.held.iter().position(Option::is_none))
+            .ok_or(MtssError::RunQueueFull)?;
+        {
+            let thread = self.thread_mut(thread)?;
+            if thread.state != ThreadState::Ready {
+                thread.transition(ThreadState::Ready)?;
+            }
+        }
+        self.held[slot] = Some(HeldThread {
+            thread,
+            release_at: Timestamp::from_ticks(self.now.ticks().saturating_add(ticks)),
+        });
+        Ok(())
+    }
+
+    /// Queue every held thread whose hold has expired. Threads that do not
+    /// fit stay held until a later tick.
+    fn release_held_threads(&mut self) -> Result<(), MtssError> {
+        let mut idx = 0;
+        while idx < ADMISSION_HOLD_SLOTS {
+            if let Some(held) = self.held[idx] {
+                if held.release_at <= self.now && self.ensure_run_queue_capacity().is_ok() {
+                    self.held[idx] = None;
+                    self.release_ready_thread(held.thread)?;
+                }
+            }
+            idx += 1;
+        }
+        Ok(())
+    }
+
+    fn release_ready_thread(&mut self, thread: ThreadId) -> Result<(), MtssError> {
+        let stride_floor = self.stride_floor;
+        let (ready, task) = {
             let thread = self.thread_mut(thread)?;
             let previous = thread.state;
             if previous != ThreadState::Ready {
                 thread.transition(ThreadState::Ready)?;
             }
-            (Self::schedule_record(*thread), thread.task)
+            thread.pass = thread.pass.max(stride_floor);
+            (*thread, thread.task)
         };
-        self.run_queue.enqueue(record)?;
+        let record = self.schedule_record(ready);
+        if ready.wake_boost && self.config.scheduling_mode == SchedulingMode::Classed {
+            let demotion = self.run_queue.enqueue_woken(record)?;
+            let default_timeslice = self.config.default_timeslice;
+            let thread = self.thread_mut(thread)?;
+            thread.demotion = demotion;
+            thread.reset_timeslice(Self::slice_for(
+                thread,
+                default_timeslice,
+                SchedulingMode::Classed,
+            ));
+        } else {
+            self.run_queue.enqueue(record)?;
+        }
+        self.note_preempting_arrival(thread);
         self.stats = self.stats.with_admission();
         self.emit(MtssEvent::thread(
             MtssEventKind::ThreadRunnable,
@@ -260,15 +573,21 @@ impl<
 
     /// Pick the next runnable thread and mark it running.
     pub fn pick_next(&mut self) -> Result<Option<ScheduleDecision>, MtssError> {
-        let record = match self.run_queue.next() {
-            Some(record) => record,
-            None => return Ok(None),
+        let record = match self.config.scheduling_mode {
+            SchedulingMode::Classed => self.take_classed(),
+            SchedulingMode::Stride | SchedulingMode::ProcessFair => {
+                self.run_queue.take_first_min_by_key(|record| record.key())
+            }
+        };
+        let Some(record) = record else {
+            return Ok(None);
         };
         self.dispatch(record.thread, LifecycleReason::Scheduled)
             .map(Some)
     }
 
-    /// Account one timer tick and request/perform preemption only when a slice expires.
+    /// Account one timer tick and preempt when a slice expires or an arrival
+    /// outranks the running thread.
     pub fn on_timer_tick(&mut self) -> Result<Option<ScheduleDecision>, MtssError> {
         self.on_timer_tick_with_preemption_disabled(false)
     }
@@ -277,7 +596,8 @@ impl<
     ///
     /// The tick advances MTSS time, charges the running thread and its task, and
     /// consumes one tick of the running thread's time slice.  When the slice
-    /// expires, MTSS sets `need_resched`. If preemption is disabled, rescheduling
+    /// expires, MTSS sets `need_resched`; an outranking arrival has already set
+    /// it. If preemption is disabled, rescheduling
     /// is deferred; otherwise MTSS requeues the current thread and picks a
     /// replacement immediately.
     pub fn on_timer_tick_with_preemption_disabled(
@@ -285,6 +605,7 @@ impl<
         preemption_disabled: bool,
     ) -> Result<Option<ScheduleDecision>, MtssError> {
         self.now = Timestamp::from_ticks(self.now.ticks().saturating_add(1));
+        self.release_held_threads()?;
         let Some(current) = self.current else {
             return Ok(None);
         };
@@ -302,22 +623,20 @@ impl<
         };
         self.with_task_mut_for_thread(current, |task| task.accumulate_cpu_time(1))?;
 
-        if !expired {
-            return Ok(None);
+        if expired {
+            self.need_resched = true;
+            self.stats = self.stats.with_preemption();
+            let task = self.thread(current)?.task;
+            self.emit(MtssEvent::thread(
+                MtssEventKind::TimesliceExpired,
+                task,
+                current,
+                Some(self.config.cpu),
+                self.now,
+            ));
         }
 
-        self.need_resched = true;
-        self.stats = self.stats.with_preemption();
-        let task = self.thread(current)?.task;
-        self.emit(MtssEvent::thread(
-            MtssEventKind::TimesliceExpired,
-            task,
-            current,
-            Some(self.config.cpu),
-            self.now,
-        ));
-
-        if preemption_disabled {
+        if !self.need_resched || preemption_disabled {
             return Ok(None);
         }
 
@@ -334,6 +653,8 @@ impl<
     }
 
     /// Voluntarily yield the current thread and pick another runnable thread.
+    /// Under classed dispatch the yielding thread keeps what is left of its
+    /// slice, and drops a band once a whole slice is used up.
     pub fn yield_current(&mut self) -> Result<Option<ScheduleDecision>, MtssError> {
         let Some(current) = self.current else {
             return self.pick_next();
@@ -342,6 +663,20 @@ impl<
         self.pick_next()
     }
 
+    /// Dispatch the next queued thread and only then requeue the current
+    /// thread, so the current thread cannot be picked again. With nothing
+    /// else queued the current thread keeps running and `None` is returned.
+    pub fn pass_current(&mut self) -> Result<Option<ScheduleDecision>, MtssError> {
+        let Some(current) = self.current else {
+            return self.pick_next();
+        };
+        let Some(decision) = self.pick_next()? else {
+            return Ok(None);
+        };
+        self.ready_current_for_requeue(current, LifecycleReason::Yielded)?;
+        Ok(Some(decision))
+    }
+
     /// Return the current thread to the runnable queue without selecting a replacement.
     pub fn requeue_current(&mut self) -> Result<(), MtssError> {
         let Some(current) = self.current.take() else {
@@ -408,6 +743,7 @@ impl<
         let task = {
             let thread = self.thread_mut(thread)?;
             thread.wake()?;
+            thread.wake_boost = thread.class == SchedClass::Interactive;
             thread.task
         };
         self.wake_task_if_waiting(task)?;
@@ -564,6 +900,23 @@ impl<
         Ok(())
     }
 
+    /// Release the table entry of an exited thread so its slot can be reused
+    /// while the owning task is still alive.
+    pub fn reap_thread(&mut self, thread: ThreadId) -> Result<(), MtssError> {
+        let index = self
+            .find_thread_index(thread)
+            .ok_or(MtssError::InvalidThread)?;
+        let state = self.threads[index].ok_or(MtssError::InvalidThread)?.state;
+        if state != ThreadState::Dead {
+            return Err(MtssError::InvalidThreadTransition {
+                from: state,
+                to: ThreadState::Dead,
+            });
+        }
+        self.threads[index] = None;
+        Ok(())
+    }
+
     fn dispatch(
         &mut self,
         thread: ThreadId,
@@ -571,17 +924,42 @@ impl<
     ) -> Result<ScheduleDecision, MtssError> {
         let previous = self.current;
         let default_timeslice = self.config.default_timeslice;
-        let task = {
+        let mode = self.config.scheduling_mode;
+        let stride = mode == SchedulingMode::Stride;
+        let (task, pass) = {
             let thread = self.thread_mut(thread)?;
             thread.mark_running()?;
-            thread.reset_timeslice(default_timeslice);
-            thread.task
+            if mode != SchedulingMode::Classed || thread.timeslice.is_expired() {
+                thread.reset_timeslice(Self::slice_for(thread, default_timeslice, mode));
+            }
+            thread.wake_boost = false;
+            let pass = thread.pass;
+            if stride {
+                thread.pass = pass.saturating_add(thread.stride());
+            }
+            (thread.task, pass)
         };
+        if stride {
+            self.stride_floor = pass;
+        }
+        if mode == SchedulingMode::ProcessFair {
+            self.turn_clock = self.turn_clock.saturating_add(1);
+        }
+        let turn = self.turn_clock;
         self.with_task_mut(task, |task| {
             if task.state == TaskState::Runnable {
                 let _ = task.mark_running();
             }
+            if mode == SchedulingMode::ProcessFair {
+                task.last_turn = turn;
+            }
         })?;
+        if mode == SchedulingMode::ProcessFair {
+            self.run_queue.update_matching(
+                |record| record.process == task,
+                |record| record.set_key(turn),
+            );
+        }
         self.current = Some(thread);
         self.stats = self.stats.with_context_switch();
         self.emit(MtssEvent::thread(
@@ -606,13 +984,29 @@ impl<
     ) -> Result<(), MtssError> {
         self.ensure_run_queue_capacity()?;
         let default_timeslice = self.config.default_timeslice;
-        let (record, task) = {
+        let mode = self.config.scheduling_mode;
+        let classed = mode == SchedulingMode::Classed;
+        let (ready, task) = {
             let thread = self.thread_mut(thread)?;
             thread.mark_ready()?;
-            thread.reset_timeslice(default_timeslice);
-            (Self::schedule_record(*thread), thread.task)
+            if !classed {
+                thread.reset_timeslice(Self::slice_for(thread, default_timeslice, mode));
+            }
+            (*thread, thread.task)
         };
-        self.run_queue.requeue(record)?;
+        let record = self.schedule_record(ready);
+        if classed {
+            let demotion = self.run_queue.requeue(record)?;
+            let thread = self.thread_mut(thread)?;
+            if thread.class == SchedClass::Interactive {
+                thread.demotion = demotion;
+            }
+            if thread.timeslice.is_expired() {
+                thread.reset_timeslice(Self::slice_for(thread, default_timeslice, mode));
+            }
+        } else {
+            self.run_queue.enqueue(record)?;
+        }
         self.emit(MtssEvent::thread(
             MtssEventKind::ThreadRunnable,
             task,
@@ -623,13 +1017,153 @@ impl<
         Ok(())
     }
 
-    fn schedule_record(thread: Thread) -> MtssThreadScheduleRecord<ThreadId, TaskId, Priority> {
-        MtssThreadScheduleRecord::new(
+    /// Stride and process-fair shares assume equal quanta, so class slice
+    /// scaling only applies to classed dispatch.
+    fn slice_for(thread: &Thread, default: Timeslice, mode: SchedulingMode) -> Timeslice {
+        match mode {
+            SchedulingMode::Classed => thread.class.timeslice(default),
+            SchedulingMode::Stride | SchedulingMode::ProcessFair => default,
+        }
+    }
+
+    /// Take the front record of the band [`Self::classed_choice`] picks.
+    fn take_classed(&mut self) -> Option<ScheduleRecord> {
+        let fronts = core::array::from_fn(|band| self.run_queue.front(band));
+        let band = Self::classed_choice(self.picks, &fronts)?;
+        self.picks = self.picks.saturating_add(1);
+        self.run_queue.pop_band(band)
+    }
+
+    /// Band whose front classed dispatch takes next, after `picks` picks. A
+    /// front whose feedback is enabled climbs one band for every
+    /// [`SchedClass::AGING_THRESHOLD`] picks it has waited, but never past a
+    /// realtime front in its own or a higher band, and realtime fronts win
+    /// ties. Between other fronts competing for the same band the one queued
+    /// earlier wins. Only band fronts are examined, so the cost does not grow
+    /// with the queue.
+    fn classed_choice(
+        picks: u64,
+        fronts: &[Option<ScheduleRecord>; RUN_QUEUE_BANDS],
+    ) -> Option<usize> {
+        let threshold = u64::from(SchedClass::AGING_THRESHOLD);
+        let mut realtime_floor: Option<usize> = None;
+        let mut best: Option<(usize, bool, u64, usize)> = None;
+        for (band, front) in fronts.iter().enumerate() {
+            let Some(front) = front else {
+                continue;
+            };
+            let effective = if front.feedback() {
+                let age = picks.saturating_sub(front.queued_at());
+                let lift = usize::try_from(age / threshold).unwrap_or(usize::MAX);
+                band.saturating_sub(lift).max(realtime_floor.unwrap_or(0))
+            } else {
+                realtime_floor = Some(band);
+                band
+            };
+            let candidate = (effective, front.feedback(), front.queued_at(), band);
+            if best.is_none_or(|best| candidate < best) {
+                best = Some(candidate);
+            }
+        }
+        best.map(|(_, _, _, band)| band)
+    }
+
+    /// Band a thread queues in under classed dispatch: its priority's band,
+    /// lowered by any demotion of an interactive thread. Batch work always
+    /// waits in the lowest band.
+    fn classed_band(thread: &Thread) -> usize {
+        let band = match thread.class {
+            SchedClass::Batch => RUN_QUEUE_BANDS - 1,
+            SchedClass::Realtime => thread.priority.band(),
+            SchedClass::Interactive => thread.priority.band() + thread.demotion as usize,
+        };
+        band.min(RUN_QUEUE_BANDS - 1)
+    }
+
+    /// Key a thread queues under in the current mode. Classed dispatch orders
+    /// by band instead. Under process-fair dispatch every thread of a task
+    /// shares the task's turn, so the queue's FIFO order rotates threads
+    /// within the task.
+    fn order_key(&self, thread: &Thread) -> u64 {
+        match self.config.scheduling_mode {
+            SchedulingMode::Classed => 0,
+            SchedulingMode::Stride => thread.pass,
+            SchedulingMode::ProcessFair => self
+                .find_task_index(thread.task)
+                .and_then(|idx| self.tasks[idx])
+                .map_or(u64::MAX, |task| task.last_turn),
+        }
+    }
+
+    /// Recompute the key of `thread`'s queued record, if it has one.
+    fn rekey_queued(&mut self, thread: Thread) {
+        let key = self.order_key(&thread);
+        self.run_queue.update_matching(
+            |record| record.thread == thread.id,
+            |record| record.set_key(key),
+        );
+    }
+
+    /// Move `thread`'s queued record, if any, to the band its class and
+    /// priority now call for.
+    fn reband_queued(&mut self, thread: Thread, queued_at: u64) -> Result<(), MtssError> {
+        if self.run_queue.remove_thread(thread.id) == 0 {
+            return Ok(());
+        }
+        let record = self.schedule_record(thread);
+        self.run_queue
+            .enqueue(record.with_key(record.key(), queued_at))
+    }
+
+    /// A thread arriving in a higher band than the running thread, or a
+    /// realtime thread arriving in its band ahead of a non-realtime one,
+    /// forces a reschedule on the next tick instead of waiting for the slice
+    /// to expire.
+    fn note_preempting_arrival(&mut self, thread: ThreadId) {
+        if self.config.scheduling_mode != SchedulingMode::Classed {
+            return;
+        }
+        let Some(current) = self.current else {
+            return;
+        };
+        let Ok(arrival) = self.thread(thread) else {
+            return;
+        };
+        let Ok(current) = self.thread(current) else {
+            return;
+        };
+        let (arrival_band, current_band) =
+            (Self::classed_band(&arrival), Self::classed_band(&current));
+        if arrival_band < current_band
+            || (arrival_band == current_band
+                && arrival.class == SchedClass::Realtime
+                && current.class != SchedClass::Realtime)
+        {
+            self.need_resched = true;
+        }
+    }
+
+    fn schedule_record(&self, thread: Thread) -> ScheduleRecord {
+        let demotion =
+            Self::classed_band(&thread) - thread.priority.band().min(RUN_QUEUE_BANDS - 1);
+        let record = MtssThreadScheduleRecord::new(
             thread.id,
             thread.task,
             thread.priority,
-            timeslice_budget_u8(thread.timeslice),
+            timeslice_budget_u8(Self::slice_for(
+                &thread,
+                self.config.default_timeslice,
+                self.config.scheduling_mode,
+            )),
         )
+        .with_remaining_slice(thread.timeslice.ticks().min(u8::MAX as u64) as u8)
+        .with_demotion(demotion as u8)
+        .with_key(self.order_key(&thread), self.picks);
+        match thread.class {
+            SchedClass::Realtime => record.without_feedback(),
+            SchedClass::Interactive if thread.wake_boost => record.with_boost(),
+            SchedClass::Interactive | SchedClass::Batch => record,
+        }
     }
 
     fn refresh_task_wait_state(
@@ -701,6 +1235,11 @@ impl<
 
     fn unschedule_thread(&mut self, thread: ThreadId) {
         self.run_queue.remove_thread(thread);
+        for held in self.held.iter_mut() {
+            if held.is_some_and(|held| held.thread == thread) {
+                *held = None;
+            }
+        }
         if self.current == Some(thread) {
             self.current = None;
         }
@@ -5,7 +5,24 @@
 //! thread, process, and priority identifiers generically so kernel-side types do
 //! not leak into the MTSS crate.
 
-use crate::MtssError;
+use crate::{MtssError, Priority};
+
+/// Number of priority bands kept by [`BandedRunQueue`].
+pub const RUN_QUEUE_BANDS: usize = 4;
+
+/// Maps a record's priority onto a [`BandedRunQueue`] band; band 0 is
+/// dispatched first.
+pub trait PriorityBand {
+    fn band(&self) -> usize;
+}
+
+impl PriorityBand for Priority {
+    /// One band per quarter of the raw range, so [`Priority::CRITICAL`]
+    /// through [`Priority::LOW`] each get their own band.
+    fn band(&self) -> usize {
+        self.raw() as usize / (256 / RUN_QUEUE_BANDS)
+    }
+}
 
 /// MTSS scheduling record for one runnable micro-thread.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -15,6 +32,18 @@ pub struct MtssThreadScheduleRecord<Thread, Process, Priority> {
     pub priority: Priority,
     remaining_slice: u8,
     slice_budget: u8,
+    /// Bands below its priority's own band that [`BandedRunQueue`]
+    /// currently queues this record in.
+    demotion: u8,
+    /// Whether the band may move under feedback and aging; realtime records
+    /// stay in their priority's band.
+    feedback: bool,
+    /// Queue one band above where the record would otherwise go.
+    boosted: bool,
+    /// Order the owning scheduler dispatches by; lower runs first.
+    key: u64,
+    /// Owning scheduler's pick count when the record was queued.
+    queued_at: u64,
 }
 
 impl<Thread, Process, Priority> MtssThreadScheduleRecord<Thread, Process, Priority> {
@@ -30,9 +59,45 @@ impl<Thread, Process, Priority> MtssThreadScheduleRecord<Thread, Process, Priori
             priority,
             remaining_slice: slice_budget,
             slice_budget,
+            demotion: 0,
+            feedback: true,
+            boosted: false,
+            key: 0,
+            queued_at: 0,
         }
     }
 
+    /// Start with `remaining` ticks of the slice left, at most the budget.
+    pub const fn with_remaining_slice(mut self, remaining: u8) -> Self {
+        self.remaining_slice = if remaining < self.slice_budget {
+            remaining
+        } else {
+            self.slice_budget
+        };
+        self
+    }
+
+    pub const fn with_demotion(mut self, demotion: u8) -> Self {
+        self.demotion = demotion;
+        self
+    }
+
+    pub const fn without_feedback(mut self) -> Self {
+        self.feedback = false;
+        self
+    }
+
+    pub const fn with_boost(mut self) -> Self {
+        self.boosted = true;
+        self
+    }
+
+    pub const fn with_key(mut self, key: u64, queued_at: u64) -> Self {
+        self.key = key;
+        self.queued_at = queued_at;
+        self
+    }
+
     pub const fn remaining_slice(&self) -> u8 {
         self.remaining_slice
     }
@@ -41,6 +106,30 @@ impl<Thread, Process, Priority> MtssThreadScheduleRecord<Thread, Process, Priori
         self.slice_budget
     }
 
+    pub const fn demotion(&self) -> u8 {
+        self.demotion
+    }
+
+    pub const fn feedback(&self) -> bool {
+        self.feedback
+    }
+
+    pub const fn is_boosted(&self) -> bool {
+        self.boosted
+    }
+
+    pub const fn key(&self) -> u64 {
+        self.key
+    }
+
+    pub const fn queued_at(&self) -> u64 {
+        self.queued_at
+    }
+
+    pub fn set_key(&mut self, key: u64) {
+        self.key = key;
+    }
+
     pub fn consume_time_slice(&mut self) -> bool {
         if self.remaining_slice > 0 {
             self.remaining_slice -= 1;
@@ -93,11 +182,36 @@ impl<Record: Copy, const MAX: usize> RunQueue<Record, MAX> {
         Ok(())
     }
 
+    /// Queue `record` behind the run of records at the front for which
+    /// `leads` holds, ahead of everything after them. Only that run moves.
+    pub fn enqueue_behind_leading(
+        &mut self,
+        record: Record,
+        mut leads: impl FnMut(Record) -> bool,
+    ) -> Result<(), MtssError> {
+        if self.len == MAX {
+            return Err(MtssError::RunQueueFull);
+        }
+        let leading = self.iter().take_while(|entry| leads(*entry)).count();
+        self.head = (self.head + MAX - 1) % MAX;
+        for step in 0..leading {
+            self.queue[(self.head + step) % MAX] = self.queue[(self.head + step + 1) % MAX];
+        }
+        self.queue[(self.head + leading) % MAX] = Some(record);
+        self.len += 1;
+        Ok(())
+    }
+
     pub fn requeue(&mut self, record: Record) -> Result<(), MtssError> {
         self.enqueue(record)
     }
 
-    pub fn next(&mut self) -> Option<Record> {
+    /// The record `pop_next()` would return.
+    pub fn front(&self) -> Option<Record> {
+        self.iter().next()
+    }
+
+    pub fn pop_next(&mut self) -> Option<Record> {
         if self.len == 0 {
             return None;
         }
@@ -128,22 +242,105 @@ impl<Record: Copy, const MAX: usize> RunQueue<Record, MAX> {
         self.len
     }
 
-    pub fn remove_matching(&mut self, mut matches: impl FnMut(Record) -> bool) -> usize {
-        let mut removed = 0usize;
-        let mut idx = 0;
-        while idx < MAX {
-            if let Some(entry) = self.queue[idx] {
+    /// Number of records `pop_next()` would return before the first record that
+    /// matches, or `None` when nothing queued matches.
+    pub fn position_of(&self, mut matches: impl FnMut(Record) -> bool) -> Option<usize> {
+        let mut position = 0usize;
+        let mut steps = 0;
+        while steps < MAX && position < self.len {
+            if let Some(entry) = self.queue[(self.head + steps) % MAX] {
                 if matches(entry) {
-                    self.queue[idx] = None;
-                    if self.len > 0 {
-                        self.len -= 1;
-                    }
-                    removed += 1;
+                    return Some(position);
                 }
+                position += 1;
+            }
+            steps += 1;
+        }
+        None
+    }
+
+    /// Remove and return the earliest queued record with the smallest key.
+    /// Later records shift forward so the queue keeps its order without
+    /// leaving a hole.
+    pub fn take_first_min_by_key<K: Ord>(
+        &mut self,
+        mut key: impl FnMut(Record) -> K,
+    ) -> Option<Record> {
+        let mut best: Option<(usize, K)> = None;
+        let mut seen = 0usize;
+        let mut steps = 0;
+        while steps < MAX && seen < self.len {
+            let idx = (self.head + steps) % MAX;
+            if let Some(entry) = self.queue[idx] {
+                let entry_key = key(entry);
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, best_key)| entry_key < *best_key)
+                {
+                    best = Some((idx, entry_key));
+                }
+                seen += 1;
+            }
+            steps += 1;
+        }
+
+        let (idx, _) = best?;
+        let entry = self.queue[idx].take();
+        self.len -= 1;
+        if idx == self.head {
+            self.head = (idx + 1) % MAX;
+            return entry;
+        }
+        let mut slot = idx;
+        loop {
+            let next = (slot + 1) % MAX;
+            if next == self.tail {
+                break;
+            }
+            self.queue[slot] = self.queue[next].take();
+            slot = next;
+        }
+        self.tail = slot;
+        entry
+    }
+
+    /// Queued records in FIFO order.
+    pub fn iter(&self) -> impl Iterator<Item = Record> + '_ {
+        (0..MAX).filter_map(move |step| self.queue[(self.head + step) % MAX])
+    }
+
+    /// Remove every matching record. The rest close up behind the head, so
+    /// the queue never holds a gap that a later enqueue could land on.
+    pub fn remove_matching(&mut self, mut matches: impl FnMut(Record) -> bool) -> usize {
+        let queued = self.len;
+        let mut kept = 0usize;
+        let mut steps = 0;
+        while steps < queued {
+            let idx = (self.head + steps) % MAX;
+            if let Some(entry) = self.queue[idx].take() {
+                if !matches(entry) {
+                    self.queue[(self.head + kept) % MAX] = Some(entry);
+                    kept += 1;
+                }
+            }
+            steps += 1;
+        }
+        self.len = kept;
+        self.tail = (self.head + kept) % MAX;
+        queued - kept
+    }
+
+    /// Apply `update` in place to every queued record that matches.
+    pub fn update_matching(
+        &mut self,
+        mut matches: impl FnMut(Record) -> bool,
+        mut update: impl FnMut(&mut Record),
+    ) {
+        for entry in self.queue.iter_mut().flatten() {
+            if matches(*entry) {
+                update(entry);
             }
-            idx += 1;
         }
-        removed
     }
 }
 
@@ -167,6 +364,10 @@ where
         false
     }
 
+    pub fn position_of_thread(&self, thread: Thread) -> Option<usize> {
+        self.position_of(|entry| entry.thread == thread)
+    }
+
     pub fn remove_thread(&mut self, thread: Thread) -> usize {
         self.remove_matching(|entry| entry.thread == thread)
     }
@@ -175,3 +376,239 @@ where
         self.remove_matching(|entry| entry.process == process)
     }
 }
+
+/// Run queue split into one FIFO ring per priority band.
+///
+/// `pop_next()` takes from the highest-priority non-empty band without
+/// scanning the others' records. Each band holds up to `MAX` records.
+///
+/// Bands also give multi-level feedback: a record requeued with its slice
+/// used up drops one band, and one woken after blocking with slice left
+/// climbs back one band, never above the band of its own priority. Records
+/// built [`without_feedback`](MtssThreadScheduleRecord::without_feedback)
+/// keep their band.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BandedRunQueue<Record, const MAX: usize> {
+    bands: [RunQueue<Record, MAX>; RUN_QUEUE_BANDS],
+}
+
+impl<Thread, Process, Priority, const MAX: usize>
+    BandedRunQueue<MtssThreadScheduleRecord<Thread, Process, Priority>, MAX>
+where
+    Thread: Copy + PartialEq,
+    Process: Copy + PartialEq,
+    Priority: Copy + PriorityBand,
+{
+    pub const fn new() -> Self {
+        Self {
+            bands: [RunQueue::new(); RUN_QUEUE_BANDS],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for band in self.bands.iter_mut() {
+            band.reset();
+        }
+    }
+
+    fn base_band(record: &MtssThreadScheduleRecord<Thread, Process, Priority>) -> usize {
+        record.priority.band().min(RUN_QUEUE_BANDS - 1)
+    }
+
+    fn band_of(record: &MtssThreadScheduleRecord<Thread, Process, Priority>) -> usize {
+        (Self::base_band(record) + record.demotion as usize)
+            .min(RUN_QUEUE_BANDS - 1)
+            .saturating_sub(usize::from(record.boosted))
+    }
+
+    /// Append `record` to the band of its priority, lowered by its
+    /// demotion and raised by a boost. A record without feedback queues
+    /// ahead of the band's records with feedback.
+    pub fn enqueue(
+        &mut self,
+        record: MtssThreadScheduleRecord<Thread, Process, Priority>,
+    ) -> Result<(), MtssError> {
+        let band = &mut self.bands[Self::band_of(&record)];
+        if record.feedback {
+            band.enqueue(record)
+        } else {
+            band.enqueue_behind_leading(record, |entry| !entry.feedback)
+        }
+    }
+
+    /// Put back a record that has just run. One that used up its slice is
+    /// demoted a band and given a fresh slice; one with slice left keeps
+    /// its band. Returns the demotion the record was queued with.
+    pub fn requeue(
+        &mut self,
+        mut record: MtssThreadScheduleRecord<Thread, Process, Priority>,
+    ) -> Result<u8, MtssError> {
+        if record.remaining_slice == 0 && record.feedback {
+            let floor = (RUN_QUEUE_BANDS - 1 - Self::base_band(&record)) as u8;
+            record.demotion = (record.demotion + 1).min(floor);
+            record.reset_time_slice();
+        }
+        self.enqueue(record).map(|()| record.demotion)
+    }
+
+    /// Queue a record whose thread blocked and is runnable again. Blocking
+    /// before the slice ran out promotes it a band, up to its priority's
+    /// own band. Returns the demotion the record was queued with.
+    pub fn enqueue_woken(
+        &mut self,
+        mut record: MtssThreadScheduleRecord<Thread, Process, Priority>,
+    ) -> Result<u8, MtssError> {
+        if record.remaining_slice > 0 && record.feedback {
+            record.demotion = record.demotion.saturating_sub(1);
+        }
+        record.reset_time_slice();
+        self.enqueue(record).map(|()| record.demotion)
+    }
+
+    pub fn pop_next(&mut self) -> Option<MtssThreadScheduleRecord<Thread, Process, Priority>> {
+        self.bands
+            .iter_mut()
+            .find(|band| !band.is_empty())
+            .and_then(RunQueue::pop_next)
+    }
+
+    /// The record at the front of `band`.
+    pub fn front(
+        &self,
+        band: usize,
+    ) -> Option<MtssThreadScheduleRecord<Thread, Process, Priority>> {
+        self.bands.get(band).and_then(RunQueue::front)
+    }
+
+    /// Take the record at the front of `band`, skipping any higher band.
+    pub fn pop_band(
+        &mut self,
+        band: usize,
+    ) -> Option<MtssThreadScheduleRecord<Thread, Process, Priority>> {
+        self.bands.get_mut(band).and_then(RunQueue::pop_next)
+    }
+
+    /// Remove and return the first record, in dispatch order, with the
+    /// smallest key.
+    pub fn take_first_min_by_key<K: Ord>(
+        &mut self,
+        mut key: impl FnMut(MtssThreadScheduleRecord<Thread, Process, Priority>) -> K,
+    ) -> Option<MtssThreadScheduleRecord<Thread, Process, Priority>> {
+        let mut best: Option<(usize, K)> = None;
+        for (band, queue) in self.bands.iter().enumerate() {
+            for record in queue.iter() {
+                let record_key = key(record);
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, best_key)| record_key < *best_key)
+                {
+                    best = Some((band, record_key));
+                }
+            }
+        }
+        let (band, _) = best?;
+        self.bands[band].take_first_min_by_key(key)
+    }
+
+    /// Queued records in dispatch order: band by band, FIFO within each.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = MtssThreadScheduleRecord<Thread, Process, Priority>> + '_ {
+        self.bands.iter().flat_map(RunQueue::iter)
+    }
+
+    /// Records queued in `band`, front first.
+    pub fn band_iter(
+        &self,
+        band: usize,
+    ) -> impl Iterator<Item = MtssThreadScheduleRecord<Thread, Process, Priority>> + '_ {
+        self.bands.get(band).into_iter().flat_map(RunQueue::iter)
+    }
+
+    /// Number of records `pop_next()` would return before the first record
+    /// that matches.
+    pub fn position_of(
+        &self,
+        matches: impl FnMut(MtssThreadScheduleRecord<Thread, Process, Priority>) -> bool,
+    ) -> Option<usize> {
+        self.iter().position(matches)
+    }
+
+    /// Apply `update` in place to every queued record that matches. The
+    /// update must leave each record's band unchanged.
+    pub fn update_matching(
+        &mut self,
+        mut matches: impl FnMut(MtssThreadScheduleRecord<Thread, Process, Priority>) -> bool,
+        mut update: impl FnMut(&mut MtssThreadScheduleRecord<Thread, Process, Priority>),
+    ) {
+        for band in self.bands.iter_mut() {
+            band.update_matching(&mut matches, &mut update);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub const fn len(&self) -> usize {
+        let mut len = 0;
+        let mut band = 0;
+        while band < RUN_QUEUE_BANDS {
+            len += self.bands[band].len();
+            band += 1;
+        }
+        len
+    }
+
+    pub fn band_len(&self, band: usize) -> usize {
+        self.bands.get(band).map_or(0, RunQueue::len)
+    }
+
+    /// Records queued in `priority`'s band, including any demoted into it
+    /// from higher priorities.
+    pub fn queue_depth_for_priority(&self, priority: Priority) -> usize {
+        self.band_len(priority.band().min(RUN_QUEUE_BANDS - 1))
+    }
+
+    /// Number of records `pop_next()` would return before `thread`.
+    pub fn position_of_thread(&self, thread: Thread) -> Option<usize> {
+        let mut ahead = 0usize;
+        for band in self.bands.iter() {
+            if let Some(position) = band.position_of_thread(thread) {
+                return Some(ahead + position);
+            }
+            ahead += band.len();
+        }
+        None
+    }
+
+    pub fn contains_process(&self, process: Process) -> bool {
+        self.bands.iter().any(|band| band.contains_process(process))
+    }
+
+    pub fn remove_thread(&mut self, thread: Thread) -> usize {
+        self.bands
+            .iter_mut()
+            .map(|band| band.remove_thread(thread))
+            .sum()
+    }
+
+    pub fn remove_process(&mut self, process: Process) -> usize {
+        self.bands
+            .iter_mut()
+            .map(|band| band.remove_process(process))
+            .sum()
+    }
+}
+
+impl<Thread, Process, Priority, const MAX: usize> Default
+    for BandedRunQueue<MtssThreadScheduleRecord<Thread, Process, Priority>, MAX>
+where
+    Thread: Copy + PartialEq,
+    Process: Copy + PartialEq,
+    Priority: Copy + PriorityBand,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
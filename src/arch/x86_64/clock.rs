@@ -32,6 +32,11 @@ impl HardwareClock {
         self.counter.store(0, Ordering::SeqCst);
     }
 
+    /// Set the tick counter to `ticks`, forwards or backwards.
+    pub fn set(&self, ticks: u64) {
+        self.counter.store(ticks, Ordering::SeqCst);
+    }
+
     /// Configure the expected tick frequency. The clock keeps running while
     /// the frequency changes, mirroring how a real kernel would adjust the PIT
     /// or HPET divisor at runtime.
@@ -578,3 +578,9 @@ pub fn timer_ticks() -> u64 {
 pub fn last_page_fault_address() -> u64 {
     LAST_PAGE_FAULT_ADDRESS.load(Ordering::SeqCst)
 }
+
+/// Stands in for the trap stub's CR2 read so host tests can stage a fault.
+#[cfg(test)]
+pub(crate) fn record_page_fault_address(address: u64) {
+    LAST_PAGE_FAULT_ADDRESS.store(address, Ordering::SeqCst);
+}
@@ -77,6 +77,10 @@ pub enum ThreadRunOutcome {
     TimerPreempted,
     UserEntryInvalid,
     Syscall(SyscallTrap),
+    /// A user-mode page fault at `address`, as read from CR2 by the trap stub.
+    PageFault {
+        address: u64,
+    },
 }
 
 /// Explicit kernel-to-architecture handoff for a single MTSS-selected slice.
@@ -234,6 +238,12 @@ pub fn run_thread_slice(mut run_context: ThreadSliceRunContext<'_>) -> ThreadRun
             run_context.context.clear_trap();
             ThreadRunOutcome::TimerPreempted
         }
+        vector if vector == u64::from(idt::PAGE_FAULT_VECTOR) => {
+            run_context.context.clear_trap();
+            ThreadRunOutcome::PageFault {
+                address: idt::last_page_fault_address(),
+            }
+        }
         _ if idt::timer_ticks() != timer_epoch => ThreadRunOutcome::TimerPreempted,
         _ => ThreadRunOutcome::TimeSliceComplete,
     }
@@ -1,7 +1,7 @@
 //! UART 16550 serial console driver for the legacy COM1 port.
 
 use crate::arch::x86_64::io::{inb, outb};
-use crate::kernel::device::{DeviceDriver, DeviceError, DeviceKind};
+use crate::kernel::device::{DeviceDriver, DeviceError, DeviceKind, SerialDevice};
 use crate::kernel::sync::SpinLock;
 use core::fmt::{self, Write};
 use core::sync::atomic::{AtomicBool, Ordering};
@@ -19,6 +19,7 @@ const DLAB: u8 = 0x80;
 const LCR_8N1: u8 = 0x03;
 const LSR_DATA_READY: u8 = 0x01;
 const LSR_TRANSMIT_EMPTY: u8 = 0x20;
+const LSR_TRANSMITTER_IDLE: u8 = 0x40;
 
 /// Early, allocation-free serial console for boot diagnostics.
 ///
@@ -185,6 +186,25 @@ impl DeviceDriver for Uart16550Driver {
         }
         Ok(data.len())
     }
+
+    fn as_serial(&self) -> Option<&dyn SerialDevice> {
+        Some(self)
+    }
+}
+
+impl SerialDevice for Uart16550Driver {
+    fn flush(&self) -> Result<(), DeviceError> {
+        self.ensure_initialised();
+        let mut spins = 0usize;
+        while self.line_status() & LSR_TRANSMITTER_IDLE == 0 {
+            if spins >= 100_000 {
+                return Err(DeviceError::Busy);
+            }
+            core::hint::spin_loop();
+            spins += 1;
+        }
+        Ok(())
+    }
 }
 
 pub static UART16550_COM1_DRIVER: Uart16550Driver = Uart16550Driver::new();
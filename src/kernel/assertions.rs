@@ -0,0 +1,164 @@
+//! Recoverable assertions.
+//!
+//! Invariant checks that find the kernel in an inconsistent but survivable
+//! state record what they saw here instead of panicking. The ring keeps the
+//! most recent reports; older ones are overwritten and counted.
+
+use crate::kernel::process::ProcessId;
+use crate::kernel::thread::{ThreadId, ThreadState};
+
+pub const ASSERTION_RING_CAPACITY: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssertionCode {
+    /// A run queue entry names a thread with no thread table entry.
+    QueuedThreadMissing,
+    /// A run queue entry names a thread that is not `Ready`.
+    QueuedThreadNotReady,
+    /// A thread appears in the run queue more than once.
+    QueuedThreadDuplicated,
+    /// A `Ready` thread is not queued, running or pending dispatch.
+    ReadyThreadUnscheduled,
+    /// A process's `thread_count` disagrees with its thread table entries.
+    ThreadCountMismatch,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecoverableAssertion {
+    pub code: AssertionCode,
+    /// Scheduler tick the check ran on.
+    pub tick: u64,
+    pub thread: Option<ThreadId>,
+    pub process: Option<ProcessId>,
+    /// State of the thread's table entry, if it has one.
+    pub found: Option<ThreadState>,
+    /// Where the thread or count was expected to be.
+    pub expected: &'static str,
+    /// `(recorded, counted)` for [`AssertionCode::ThreadCountMismatch`].
+    pub counts: Option<(u32, u32)>,
+}
+
+impl RecoverableAssertion {
+    pub const fn thread(
+        code: AssertionCode,
+        tick: u64,
+        thread: ThreadId,
+        process: Option<ProcessId>,
+        found: Option<ThreadState>,
+        expected: &'static str,
+    ) -> Self {
+        Self {
+            code,
+            tick,
+            thread: Some(thread),
+            process,
+            found,
+            expected,
+            counts: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AssertionRing<const N: usize> {
+    entries: [Option<RecoverableAssertion>; N],
+    next: usize,
+    len: usize,
+    overwritten: u64,
+}
+
+impl<const N: usize> AssertionRing<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; N],
+            next: 0,
+            len: 0,
+            overwritten: 0,
+        }
+    }
+
+    pub fn push(&mut self, assertion: RecoverableAssertion) {
+        if N == 0 {
+            self.overwritten = self.overwritten.saturating_add(1);
+            return;
+        }
+        if self.len == N {
+            self.overwritten = self.overwritten.saturating_add(1);
+        } else {
+            self.len += 1;
+        }
+        self.entries[self.next] = Some(assertion);
+        self.next = (self.next + 1) % N;
+    }
+
+    /// Removes and returns the oldest report.
+    pub fn take(&mut self) -> Option<RecoverableAssertion> {
+        if self.len == 0 {
+            return None;
+        }
+        let oldest = (self.next + N - self.len) % N;
+        self.len -= 1;
+        self.entries[oldest].take()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RecoverableAssertion> + '_ {
+        let start = (self.next + N - self.len) % N.max(1);
+        (0..self.len).filter_map(move |offset| self.entries[(start + offset) % N].as_ref())
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Reports lost because the ring was full.
+    pub const fn overwritten(&self) -> u64 {
+        self.overwritten
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const N: usize> Default for AssertionRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(tick: u64) -> RecoverableAssertion {
+        RecoverableAssertion::thread(
+            AssertionCode::ReadyThreadUnscheduled,
+            tick,
+            ThreadId::new(1),
+            None,
+            Some(ThreadState::Ready),
+            "run queue",
+        )
+    }
+
+    #[test]
+    fn ring_keeps_the_newest_reports_oldest_first() {
+        let mut ring = AssertionRing::<3>::new();
+        for tick in 0..5 {
+            ring.push(report(tick));
+        }
+
+        assert_eq!(ring.len(), 3);
+        assert_eq!(ring.overwritten(), 2);
+        assert!(ring.iter().map(|entry| entry.tick).eq([2, 3, 4]));
+        assert_eq!(ring.take().map(|entry| entry.tick), Some(2));
+        ring.push(report(5));
+        assert!(ring.iter().map(|entry| entry.tick).eq([3, 4, 5]));
+        ring.clear();
+        assert!(ring.take().is_none());
+    }
+}
@@ -0,0 +1,269 @@
+//! Declarative boot task table.
+//!
+//! Images list the processes they start at boot as [`BootTask`] entries. Each
+//! task names a role instead of carrying credentials; the [`SecurityPolicy`]
+//! handed to [`Kernel::spawn_boot_tasks`] resolves roles to credentials so the
+//! table itself cannot grant authority. The first task becomes init and every
+//! later task is spawned as its child, in table order.
+
+use crate::kernel::exec::SpawnTaskRequest;
+use crate::kernel::process::{ProcessId, ProcessPriority};
+use crate::kernel::{Kernel, KernelError};
+use crate::subkernel::{CapabilitySet, Credentials, IsolationLevel, SecurityLabel};
+
+pub const MAX_BOOT_TASKS: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BootTask {
+    pub name: &'static str,
+    pub entry_point: u64,
+    pub priority: ProcessPriority,
+    /// Role looked up in the [`SecurityPolicy`] for the task's credentials.
+    pub role: &'static str,
+    /// Arguments for the task once it is loaded from an image; tasks started
+    /// at a bare entry point do not receive them.
+    pub args: &'static [&'static str],
+    /// A failed critical task stops the boot; other failures are reported
+    /// and skipped.
+    pub critical: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BootRole {
+    pub name: &'static str,
+    pub credentials: Credentials,
+}
+
+impl BootRole {
+    pub const fn new(name: &'static str, credentials: Credentials) -> Self {
+        Self { name, credentials }
+    }
+}
+
+const DEFAULT_BOOT_ROLES: [BootRole; 3] = [
+    BootRole::new("system", Credentials::system()),
+    BootRole::new(
+        "service",
+        Credentials::new(
+            SecurityLabel::internal(),
+            CapabilitySet::ipc_io(),
+            IsolationLevel::Process,
+        ),
+    ),
+    BootRole::new("user", Credentials::user()),
+];
+
+/// Maps boot task roles to the credentials they run with.
+#[derive(Clone, Copy, Debug)]
+pub struct SecurityPolicy<'a> {
+    roles: &'a [BootRole],
+}
+
+impl<'a> SecurityPolicy<'a> {
+    pub const fn new(roles: &'a [BootRole]) -> Self {
+        Self { roles }
+    }
+
+    pub fn credentials_for(&self, role: &str) -> Option<Credentials> {
+        self.roles
+            .iter()
+            .find(|entry| entry.name == role)
+            .map(|entry| entry.credentials)
+    }
+}
+
+impl SecurityPolicy<'static> {
+    /// `system`, `service` (internal label, IPC and I/O) and `user` roles.
+    pub const fn default_roles() -> Self {
+        Self::new(&DEFAULT_BOOT_ROLES)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BootTaskOutcome {
+    pub name: &'static str,
+    pub result: Result<ProcessId, KernelError>,
+}
+
+/// Per-task results of [`Kernel::spawn_boot_tasks`], in table order.
+#[derive(Clone, Copy, Debug)]
+pub struct BootReport {
+    outcomes: [Option<BootTaskOutcome>; MAX_BOOT_TASKS],
+    len: usize,
+    /// Index of the critical task whose failure stopped the boot.
+    pub aborted_at: Option<usize>,
+}
+
+impl BootReport {
+    const fn new() -> Self {
+        Self {
+            outcomes: [None; MAX_BOOT_TASKS],
+            len: 0,
+            aborted_at: None,
+        }
+    }
+
+    fn record(&mut self, name: &'static str, result: Result<ProcessId, KernelError>) {
+        self.outcomes[self.len] = Some(BootTaskOutcome { name, result });
+        self.len += 1;
+    }
+
+    /// Outcomes of the tasks that were attempted.
+    pub fn outcomes(&self) -> impl Iterator<Item = BootTaskOutcome> + '_ {
+        self.outcomes[..self.len].iter().flatten().copied()
+    }
+
+    pub fn outcome(&self, index: usize) -> Option<BootTaskOutcome> {
+        self.outcomes[..self.len].get(index).copied().flatten()
+    }
+
+    pub fn init(&self) -> Option<ProcessId> {
+        self.outcome(0).and_then(|outcome| outcome.result.ok())
+    }
+
+    pub fn pid_of(&self, name: &str) -> Option<ProcessId> {
+        self.outcomes()
+            .find(|outcome| outcome.name == name)
+            .and_then(|outcome| outcome.result.ok())
+    }
+
+    pub fn failures(&self) -> usize {
+        self.outcomes()
+            .filter(|outcome| outcome.result.is_err())
+            .count()
+    }
+}
+
+impl<
+        const NPROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > Kernel<NPROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
+    /// Spawn `tasks` in order. The first task is init and cannot be skipped;
+    /// the rest are its children. Unknown roles fail with `InvalidArgument`.
+    /// Only the first [`MAX_BOOT_TASKS`] entries are considered.
+    pub fn spawn_boot_tasks(&mut self, tasks: &[BootTask], policy: &SecurityPolicy) -> BootReport {
+        let mut report = BootReport::new();
+        let mut init = None;
+        for (index, task) in tasks.iter().take(MAX_BOOT_TASKS).enumerate() {
+            let result = match policy.credentials_for(task.role) {
+                None => Err(KernelError::InvalidArgument),
+                Some(credentials) => match init {
+                    None => self.spawn_task(SpawnTaskRequest {
+                        parent: None,
+                        entry_point: task.entry_point,
+                        priority: task.priority,
+                        credentials,
+                    }),
+                    Some(parent) => self.spawn_child_process(
+                        parent,
+                        task.entry_point,
+                        task.priority,
+                        credentials,
+                    ),
+                },
+            };
+            report.record(task.name, result);
+            match result {
+                Ok(pid) if init.is_none() => init = Some(pid),
+                Ok(_) => {}
+                Err(_) if task.critical || init.is_none() => {
+                    report.aborted_at = Some(index);
+                    break;
+                }
+                Err(_) => {}
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn task(name: &'static str, role: &'static str, critical: bool) -> BootTask {
+        BootTask {
+            name,
+            entry_point: 0,
+            priority: ProcessPriority::Normal,
+            role,
+            args: &[],
+            critical,
+        }
+    }
+
+    fn boot_kernel() -> Kernel<16, 4> {
+        let mut kernel = Kernel::<16, 4>::new();
+        kernel.bootstrap();
+        kernel
+    }
+
+    fn parent_of(kernel: &Kernel<16, 4>, pid: ProcessId) -> Option<ProcessId> {
+        let index = kernel.locate_process(pid).unwrap();
+        kernel.process_table[index].unwrap().parent
+    }
+
+    #[test]
+    fn failed_optional_tasks_are_reported_and_skipped() {
+        let mut kernel = boot_kernel();
+        let tasks = [
+            task("init", "system", true),
+            task("logd", "service", false),
+            task("ghost", "missing-role", false),
+            task("shell", "user", false),
+        ];
+
+        let report = kernel.spawn_boot_tasks(&tasks, &SecurityPolicy::default_roles());
+
+        assert_eq!(report.aborted_at, None);
+        assert_eq!(report.outcomes().count(), 4);
+        assert_eq!(report.failures(), 1);
+        assert!(matches!(
+            report.outcome(2),
+            Some(BootTaskOutcome {
+                name: "ghost",
+                result: Err(KernelError::InvalidArgument),
+            })
+        ));
+        let init = report.init().unwrap();
+        let logd = report.pid_of("logd").unwrap();
+        let shell = report.pid_of("shell").unwrap();
+        assert_eq!(parent_of(&kernel, init), None);
+        assert_eq!(parent_of(&kernel, logd), Some(init));
+        assert_eq!(parent_of(&kernel, shell), Some(init));
+    }
+
+    #[test]
+    fn failed_critical_task_aborts_the_boot() {
+        let mut kernel = boot_kernel();
+        let tasks = [
+            task("init", "system", true),
+            task("storaged", "missing-role", true),
+            task("shell", "user", false),
+        ];
+
+        let report = kernel.spawn_boot_tasks(&tasks, &SecurityPolicy::default_roles());
+
+        assert_eq!(report.aborted_at, Some(1));
+        assert_eq!(report.outcomes().count(), 2);
+        assert!(report.init().is_some());
+        assert_eq!(report.pid_of("shell"), None);
+    }
+
+    #[test]
+    fn boot_stops_when_init_cannot_start() {
+        let mut kernel = boot_kernel();
+        let tasks = [
+            task("init", "missing-role", false),
+            task("shell", "user", false),
+        ];
+
+        let report = kernel.spawn_boot_tasks(&tasks, &SecurityPolicy::default_roles());
+
+        assert_eq!(report.aborted_at, Some(0));
+        assert_eq!(report.init(), None);
+    }
+}
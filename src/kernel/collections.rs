@@ -0,0 +1,464 @@
+//! Fixed-capacity containers for kernel tables.
+//!
+//! All of these live entirely inline, never allocate and are `Copy`, so they
+//! can sit in statics and in `Copy` kernel structures. Running out of room is
+//! reported as [`CapacityError`] rather than a panic.
+
+use core::fmt;
+use core::mem::MaybeUninit;
+
+/// The container had no room for the requested elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityError;
+
+/// Vector of at most `N` `Copy` elements, stored contiguously.
+#[derive(Clone, Copy)]
+pub struct FixedVec<T: Copy, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> FixedVec<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            items: [MaybeUninit::uninit(); N],
+            len: 0,
+        }
+    }
+
+    /// Copies `items` into a new vector, failing if there are more than `N`.
+    pub fn from_slice(items: &[T]) -> Result<Self, CapacityError> {
+        let mut vec = Self::new();
+        vec.extend_from_slice(items)?;
+        Ok(vec)
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn push(&mut self, value: T) -> Result<(), CapacityError> {
+        if self.len == N {
+            return Err(CapacityError);
+        }
+        self.items[self.len] = MaybeUninit::new(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Appends all of `items`, or none of them if they do not fit.
+    pub fn extend_from_slice(&mut self, items: &[T]) -> Result<(), CapacityError> {
+        if items.len() > N - self.len {
+            return Err(CapacityError);
+        }
+        for item in items {
+            self.items[self.len] = MaybeUninit::new(*item);
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: every slot below the old `len` was initialized by a push.
+        Some(unsafe { self.items[self.len].assume_init() })
+    }
+
+    /// Removes the element at `index` in O(1) by moving the last element
+    /// into its place, so order is not preserved.
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        let removed = self.as_slice()[index];
+        let last = self.pop()?;
+        if index < self.len {
+            self.items[index] = MaybeUninit::new(last);
+        }
+        Some(removed)
+    }
+
+    /// Keeps only the elements for which `keep` returns true, in order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        let mut kept = 0usize;
+        let mut index = 0usize;
+        while index < self.len {
+            let item = self.as_slice()[index];
+            if keep(&item) {
+                self.items[kept] = MaybeUninit::new(item);
+                kept += 1;
+            }
+            index += 1;
+        }
+        self.len = kept;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` slots are initialized and `MaybeUninit<T>`
+        // has the same layout as `T`.
+        unsafe { core::slice::from_raw_parts(self.items.as_ptr().cast::<T>(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: as for `as_slice`.
+        unsafe { core::slice::from_raw_parts_mut(self.items.as_mut_ptr().cast::<T>(), self.len) }
+    }
+}
+
+impl<T: Copy, const N: usize> Default for FixedVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + fmt::Debug, const N: usize> fmt::Debug for FixedVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Copy + PartialEq, const N: usize> PartialEq for FixedVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Copy + Eq, const N: usize> Eq for FixedVec<T, N> {}
+
+impl<T: Copy, const N: usize> TryFrom<&[T]> for FixedVec<T, N> {
+    type Error = CapacityError;
+
+    fn try_from(items: &[T]) -> Result<Self, Self::Error> {
+        Self::from_slice(items)
+    }
+}
+
+impl<'a, T: Copy, const N: usize> IntoIterator for &'a FixedVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// UTF-8 string of at most `N` bytes.
+#[derive(Clone, Copy)]
+pub struct FixedString<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedString<N> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Copies as much of `text` as fits, cutting at a character boundary.
+    pub const fn from_str_truncated(text: &str) -> Self {
+        let mut string = Self::new();
+        let _ = string.push_str(text);
+        string
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Appends `text`. When it does not fit, the longest prefix ending on a
+    /// character boundary is appended and `CapacityError` reports the cut.
+    pub const fn push_str(&mut self, text: &str) -> Result<(), CapacityError> {
+        let room = N - self.len;
+        let mut take = if text.len() < room { text.len() } else { room };
+        while !text.is_char_boundary(take) {
+            take -= 1;
+        }
+        let source = text.as_bytes();
+        let mut index = 0usize;
+        while index < take {
+            self.bytes[self.len + index] = source[index];
+            index += 1;
+        }
+        self.len += take;
+        if take == text.len() {
+            Ok(())
+        } else {
+            Err(CapacityError)
+        }
+    }
+
+    /// Appends `ch`, or nothing if its encoding does not fit.
+    pub fn push(&mut self, ch: char) -> Result<(), CapacityError> {
+        let mut encoded = [0u8; 4];
+        let encoded = ch.encode_utf8(&mut encoded);
+        if encoded.len() > N - self.len {
+            return Err(CapacityError);
+        }
+        self.push_str(encoded)
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes[..self.len].fill(0);
+        self.len = 0;
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: bytes are only ever appended from `&str`s, cut at
+        // character boundaries.
+        unsafe { core::str::from_utf8_unchecked(self.as_bytes()) }
+    }
+}
+
+impl<const N: usize> Default for FixedString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Write for FixedString<N> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        self.push_str(text).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize> PartialEq for FixedString<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<const N: usize> Eq for FixedString<N> {}
+
+impl<const N: usize> TryFrom<&str> for FixedString<N> {
+    type Error = CapacityError;
+
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        if text.len() > N {
+            return Err(CapacityError);
+        }
+        let mut string = Self::new();
+        string.push_str(text)?;
+        Ok(string)
+    }
+}
+
+/// Largest table a [`SlotBitmap`] can track.
+pub const SLOT_BITMAP_CAPACITY: usize = 512;
+const SLOT_BITMAP_WORDS: usize = SLOT_BITMAP_CAPACITY / u64::BITS as usize;
+
+/// Occupancy bits for an `N`-entry table, kept next to the table so finding
+/// the lowest free index costs a trailing-zeros lookup per 64 entries
+/// instead of a scan. The table's owner sets and clears bits as it fills
+/// and empties entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotBitmap<const N: usize> {
+    words: [u64; SLOT_BITMAP_WORDS],
+}
+
+impl<const N: usize> SlotBitmap<N> {
+    const FITS: () = assert!(N <= SLOT_BITMAP_CAPACITY, "table too large for SlotBitmap");
+
+    pub const fn new() -> Self {
+        let () = Self::FITS;
+        Self {
+            words: [0; SLOT_BITMAP_WORDS],
+        }
+    }
+
+    pub fn set(&mut self, index: usize) {
+        debug_assert!(index < N);
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        debug_assert!(index < N);
+        self.words[index / 64] &= !(1 << (index % 64));
+    }
+
+    pub fn is_set(&self, index: usize) -> bool {
+        index < N && self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Lowest index whose bit is clear, if any is below `N`.
+    pub fn first_clear(&self) -> Option<usize> {
+        for (word_index, word) in self.words.iter().enumerate() {
+            let free = !word;
+            if free != 0 {
+                let index = word_index * 64 + free.trailing_zeros() as usize;
+                return (index < N).then_some(index);
+            }
+        }
+        None
+    }
+
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn clear_all(&mut self) {
+        self.words = [0; SLOT_BITMAP_WORDS];
+    }
+}
+
+impl<const N: usize> Default for SlotBitmap<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn slot_bitmap_finds_the_lowest_clear_index() {
+        let mut bits = SlotBitmap::<70>::new();
+        assert_eq!(bits.first_clear(), Some(0));
+        for index in 0..66 {
+            bits.set(index);
+        }
+        assert_eq!(bits.first_clear(), Some(66));
+        bits.clear(3);
+        bits.clear(65);
+        assert_eq!(bits.first_clear(), Some(3));
+        bits.set(3);
+        assert_eq!(bits.first_clear(), Some(65));
+        for index in 65..70 {
+            bits.set(index);
+        }
+        assert_eq!(bits.count(), 70);
+        assert_eq!(bits.first_clear(), None);
+        assert!(!bits.is_set(70));
+        bits.clear_all();
+        assert_eq!(bits.first_clear(), Some(0));
+        assert_eq!(SlotBitmap::<0>::new().first_clear(), None);
+    }
+
+    #[test]
+    fn fixed_vec_rejects_pushes_past_capacity() {
+        let mut vec = FixedVec::<u32, 3>::new();
+        for value in 1..=3 {
+            vec.push(value).unwrap();
+        }
+        assert!(vec.is_full());
+        assert_eq!(vec.push(4), Err(CapacityError));
+        assert_eq!(vec.as_slice(), &[1, 2, 3]);
+
+        assert_eq!(vec.pop(), Some(3));
+        assert_eq!(vec.extend_from_slice(&[7, 8]), Err(CapacityError));
+        assert_eq!(vec.as_slice(), &[1, 2]);
+        assert_eq!(
+            FixedVec::<u32, 2>::try_from(&[1, 2, 3][..]),
+            Err(CapacityError)
+        );
+        assert_eq!(
+            FixedVec::<u32, 2>::from_slice(&[5, 6]).unwrap().as_slice(),
+            &[5, 6]
+        );
+    }
+
+    #[test]
+    fn retain_keeps_matching_elements_in_order() {
+        let mut vec = FixedVec::<u32, 8>::from_slice(&[1, 2, 3, 4, 5, 6]).unwrap();
+        vec.retain(|value| value % 2 == 0);
+        assert_eq!(vec.as_slice(), &[2, 4, 6]);
+        vec.retain(|_| false);
+        assert!(vec.is_empty());
+        assert_eq!(vec.pop(), None);
+    }
+
+    #[test]
+    fn iteration_after_swap_remove_sees_the_moved_tail() {
+        let mut vec = FixedVec::<u32, 4>::from_slice(&[10, 20, 30, 40]).unwrap();
+        assert_eq!(vec.swap_remove(1), Some(20));
+        assert!(vec.iter().copied().eq([10, 40, 30]));
+        assert_eq!(vec.swap_remove(2), Some(30));
+        assert!(vec.iter().copied().eq([10, 40]));
+        assert_eq!(vec.swap_remove(2), None);
+        let mut sum = 0;
+        for value in &vec {
+            sum += value;
+        }
+        assert_eq!(sum, 50);
+    }
+
+    #[test]
+    fn fixed_string_truncates_on_utf8_boundaries() {
+        let mut name = FixedString::<5>::new();
+        name.push_str("ab").unwrap();
+        // "é" is two bytes and "€" three; only "é" fits.
+        assert_eq!(name.push_str("é€"), Err(CapacityError));
+        assert_eq!(name.as_str(), "abé");
+        assert_eq!(name.push('€'), Err(CapacityError));
+        name.push('!').unwrap();
+        assert_eq!(name.as_str(), "abé!");
+        assert_eq!(name.push_str("x"), Err(CapacityError));
+
+        assert_eq!(FixedString::<4>::from_str_truncated("€€").as_str(), "€");
+        assert_eq!(FixedString::<4>::try_from("hello"), Err(CapacityError));
+
+        let mut formatted = FixedString::<16>::new();
+        write!(formatted, "pid {}", 42).unwrap();
+        assert_eq!(formatted.as_str(), "pid 42");
+        formatted.clear();
+        assert!(formatted.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 //! CPU topology helpers for the Mirage kernel. The simulated environment keeps
 //! track of a handful of virtual cores so the scheduler can distribute work.
 
+use crate::kernel::metrics::{Histogram, LATENCY_BUCKETS};
 use crate::kernel::thread::ThreadId;
 
 pub const MAX_CORES: usize = 4;
@@ -9,9 +10,15 @@ pub const MAX_CORES: usize = 4;
 pub struct CpuCoreState {
     pub online: bool,
     pub current_thread: Option<ThreadId>,
+    /// Most recent thread dispatched here; kept after the slice ends.
+    pub last_thread: Option<ThreadId>,
+    /// Thread dispatched here since the current tick began.
+    pub started_this_tick: Option<ThreadId>,
     pub local_ticks: u64,
     pub idle_ticks: u64,
     pub kernel_stack_top: u64,
+    /// Ticks threads dispatched here spent runnable before they ran.
+    pub dispatch_latency: Histogram<LATENCY_BUCKETS>,
 }
 
 impl CpuCoreState {
@@ -19,9 +26,12 @@ impl CpuCoreState {
         Self {
             online: false,
             current_thread: None,
+            last_thread: None,
+            started_this_tick: None,
             local_ticks: 0,
             idle_ticks: 0,
             kernel_stack_top: 0,
+            dispatch_latency: Histogram::new(),
         }
     }
 
@@ -29,6 +39,11 @@ impl CpuCoreState {
         self.online = true;
     }
 
+    pub fn offline(&mut self) {
+        self.online = false;
+        self.current_thread = None;
+    }
+
     pub fn set_kernel_stack_top(&mut self, stack_top: u64) {
         self.kernel_stack_top = stack_top;
     }
@@ -36,6 +51,12 @@ impl CpuCoreState {
     pub fn start_thread(&mut self, thread: ThreadId) {
         self.online = true;
         self.current_thread = Some(thread);
+        self.last_thread = Some(thread);
+        self.started_this_tick = Some(thread);
+    }
+
+    pub fn begin_tick(&mut self) {
+        self.started_this_tick = None;
     }
 
     pub fn finish_cycle(&mut self) {
@@ -3,8 +3,13 @@ use core::fmt;
 use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use crate::arch::x86_64::boot::{BootInfo, FramebufferInfo};
+use crate::kernel::collections::FixedVec;
+use crate::kernel::partition::crc32;
+use crate::kernel::process::ProcessId;
 use crate::kernel::sync::SpinLock;
-use crate::subkernel::{DeviceSecurity, SecurityClass};
+use crate::subkernel::{
+    CapabilityObject, CapabilityRight, DeviceSecurity, SecurityClass, SecurityKernel,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct DeviceId(u16);
@@ -125,6 +130,29 @@ pub enum DeviceKind {
     SubkernelControl,
 }
 
+impl DeviceKind {
+    /// The last declared kind. The match is exhaustive, so adding a kind
+    /// fails to build until this is revisited.
+    const LAST: Self = match Self::SubkernelControl {
+        Self::SerialConsole
+        | Self::SystemTimer
+        | Self::BlockStorage
+        | Self::Framebuffer
+        | Self::GpuCapability
+        | Self::NetworkInterface
+        | Self::InputController
+        | Self::SubkernelControl => Self::SubkernelControl,
+    };
+
+    /// Number of device kinds, used to size per-kind tables.
+    pub const COUNT: usize = Self::LAST as usize + 1;
+
+    /// Position in declaration order, below [`Self::COUNT`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
 /// Stable C ABI framebuffer metadata returned by the built-in framebuffer driver.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -314,6 +342,25 @@ pub(crate) fn copy_input_event_to_bytes(
     copy_c_abi_metadata(event, buffer)
 }
 
+/// Result of a bounded device read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceReadOutcome {
+    /// This many bytes were copied into the caller's buffer.
+    Ready(usize),
+    /// Nothing was available; the caller is blocked and should retry the
+    /// read once it is woken.
+    Pending,
+    /// The timeout elapsed without input.
+    TimedOut,
+}
+
+/// A process blocked in a bounded device read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingDeviceRead {
+    pub device: DeviceId,
+    pub remaining_ticks: u64,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct DeviceDescriptor {
     pub id: DeviceId,
@@ -353,6 +400,10 @@ pub enum DeviceError {
     BufferTooSmall,
     Unsupported,
     Busy,
+    /// Block data no longer matches the checksum stored when it was written.
+    CorruptBlock,
+    /// The caller lacks the capability or clearance for this device.
+    PermissionDenied,
 }
 
 impl DeviceError {
@@ -412,12 +463,25 @@ pub trait DeviceDriver {
     fn read(&self, _buffer: &mut [u8]) -> Result<usize, DeviceError> {
         Err(DeviceError::Unsupported)
     }
+    /// Non-blocking read: `None` when the device has nothing buffered.
+    fn try_read(&self, buffer: &mut [u8]) -> Result<Option<usize>, DeviceError> {
+        match self.read(buffer)? {
+            0 if !buffer.is_empty() => Ok(None),
+            count => Ok(Some(count)),
+        }
+    }
     fn write(&self, _data: &[u8]) -> Result<usize, DeviceError> {
         Err(DeviceError::Unsupported)
     }
     fn as_block_storage(&self) -> Option<&dyn BlockStorageDevice> {
         None
     }
+    fn as_serial(&self) -> Option<&dyn SerialDevice> {
+        None
+    }
+    fn as_timer(&self) -> Option<&dyn TimerDevice> {
+        None
+    }
 }
 
 /// Sector-addressed interface implemented by block storage devices.
@@ -437,6 +501,66 @@ pub trait BlockStorageDevice {
     }
 }
 
+/// Operations specific to serial consoles.
+pub trait SerialDevice {
+    /// Wait until every byte accepted by `write` has left the device.
+    fn flush(&self) -> Result<(), DeviceError>;
+}
+
+/// Operations specific to system timers.
+pub trait TimerDevice {
+    /// Ticks counted since the timer was brought up.
+    fn ticks(&self) -> u64;
+}
+
+/// A registered serial console, resolved by [`DeviceManager::serial`].
+#[derive(Clone, Copy)]
+pub struct SerialHandle {
+    id: DeviceId,
+    driver: &'static dyn DeviceDriver,
+    serial: &'static dyn SerialDevice,
+}
+
+impl SerialHandle {
+    pub const fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.driver.name()
+    }
+
+    pub fn write(&self, data: &[u8]) -> Result<usize, DeviceError> {
+        self.driver.write(data)
+    }
+
+    pub fn flush(&self) -> Result<(), DeviceError> {
+        self.serial.flush()
+    }
+}
+
+/// A registered system timer, resolved by [`DeviceManager::timer`].
+#[derive(Clone, Copy)]
+pub struct TimerHandle {
+    id: DeviceId,
+    driver: &'static dyn DeviceDriver,
+    timer: &'static dyn TimerDevice,
+}
+
+impl TimerHandle {
+    pub const fn id(&self) -> DeviceId {
+        self.id
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.driver.name()
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.timer.ticks()
+    }
+}
+
 #[derive(Clone, Copy)]
 struct DeviceEntry {
     id: DeviceId,
@@ -456,6 +580,7 @@ impl DeviceEntry {
 
 pub struct DeviceManager<const MAX: usize> {
     devices: [Option<DeviceEntry>; MAX],
+    defaults: [Option<DeviceId>; DeviceKind::COUNT],
     next_id: u16,
 }
 
@@ -463,6 +588,7 @@ impl<const MAX: usize> DeviceManager<MAX> {
     pub const fn new() -> Self {
         Self {
             devices: [None; MAX],
+            defaults: [None; DeviceKind::COUNT],
             next_id: 1,
         }
     }
@@ -474,6 +600,7 @@ impl<const MAX: usize> DeviceManager<MAX> {
             self.devices[idx] = None;
             idx += 1;
         }
+        self.defaults = [None; DeviceKind::COUNT];
     }
 
     pub fn install_core_devices(&mut self) -> Result<(), DeviceError> {
@@ -639,16 +766,53 @@ impl<const MAX: usize> DeviceManager<MAX> {
         self.find_device(id).map(|entry| entry.descriptor())
     }
 
+    /// Descriptors of every registered device, in table order.
+    pub fn descriptors(&self) -> FixedVec<DeviceDescriptor, MAX> {
+        let mut descriptors = FixedVec::new();
+        for entry in self.devices.iter().flatten() {
+            // At most MAX entries, so this always fits.
+            let _ = descriptors.push(entry.descriptor());
+        }
+        descriptors
+    }
+
     pub fn enumerate(&self, out: &mut [DeviceDescriptor]) -> usize {
-        let mut count = 0usize;
+        let descriptors = self.descriptors();
+        let count = min(out.len(), descriptors.len());
+        out[..count].copy_from_slice(&descriptors.as_slice()[..count]);
+        count
+    }
+
+    /// Remove a registered device. Any default selection pointing at it is
+    /// cleared so `default_for` falls back to the next device of that kind.
+    pub fn unregister(&mut self, id: DeviceId) -> Result<DeviceDescriptor, DeviceError> {
         let mut idx = 0usize;
         while idx < MAX {
             if let Some(entry) = self.devices[idx] {
-                if count < out.len() {
+                if entry.id.raw() == id.raw() {
+                    let descriptor = entry.descriptor();
+                    self.devices[idx] = None;
+                    let default = &mut self.defaults[descriptor.kind.index()];
+                    if default.is_some_and(|current| current.raw() == id.raw()) {
+                        *default = None;
+                    }
+                    return Ok(descriptor);
+                }
+            }
+            idx += 1;
+        }
+        Err(DeviceError::NotFound)
+    }
+
+    /// Like `enumerate`, but only reports devices of `kind`.
+    pub fn find_by_kind(&self, kind: DeviceKind, out: &mut [DeviceDescriptor]) -> usize {
+        let mut count = 0usize;
+        let mut idx = 0usize;
+        while idx < MAX && count < out.len() {
+            if let Some(entry) = self.devices[idx] {
+                if entry.driver.kind() == kind {
                     out[count] = entry.descriptor();
                     count += 1;
-                } else {
-                    break;
                 }
             }
             idx += 1;
@@ -656,16 +820,105 @@ impl<const MAX: usize> DeviceManager<MAX> {
         count
     }
 
-    pub fn read(&self, id: DeviceId, buffer: &mut [u8]) -> Result<usize, DeviceError> {
+    /// Registered device whose driver reports `name`, independent of the
+    /// order ids were handed out in. The first match wins if names repeat.
+    pub fn find_by_name(&self, name: &str) -> Option<DeviceDescriptor> {
+        let mut idx = 0usize;
+        while idx < MAX {
+            if let Some(entry) = self.devices[idx] {
+                if entry.driver.name() == name {
+                    return Some(entry.descriptor());
+                }
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    /// Select the default device for `kind`. The device must be registered
+    /// and report the same kind, otherwise `Unsupported` is returned.
+    pub fn set_default(&mut self, kind: DeviceKind, id: DeviceId) -> Result<(), DeviceError> {
         let entry = self.find_device(id).ok_or(DeviceError::NotFound)?;
+        if entry.driver.kind() != kind {
+            return Err(DeviceError::Unsupported);
+        }
+        self.defaults[kind.index()] = Some(id);
+        Ok(())
+    }
+
+    /// Default device for `kind`, falling back to the first registered device
+    /// of that kind when no explicit default has been selected.
+    pub fn default_for(&self, kind: DeviceKind) -> Option<DeviceId> {
+        if let Some(id) = self.defaults[kind.index()] {
+            return Some(id);
+        }
+        let mut idx = 0usize;
+        while idx < MAX {
+            if let Some(entry) = self.devices[idx] {
+                if entry.driver.kind() == kind {
+                    return Some(entry.id);
+                }
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    pub fn read<const PROCS: usize>(
+        &self,
+        id: DeviceId,
+        caller: ProcessId,
+        security: &SecurityKernel<PROCS>,
+        buffer: &mut [u8],
+    ) -> Result<usize, DeviceError> {
+        let entry = self.authorized_device(id, caller, security, CapabilityRight::Read)?;
         entry.driver.read(buffer)
     }
 
-    pub fn write(&self, id: DeviceId, data: &[u8]) -> Result<usize, DeviceError> {
-        let entry = self.find_device(id).ok_or(DeviceError::NotFound)?;
+    pub fn try_read<const PROCS: usize>(
+        &self,
+        id: DeviceId,
+        caller: ProcessId,
+        security: &SecurityKernel<PROCS>,
+        buffer: &mut [u8],
+    ) -> Result<Option<usize>, DeviceError> {
+        let entry = self.authorized_device(id, caller, security, CapabilityRight::Read)?;
+        entry.driver.try_read(buffer)
+    }
+
+    pub fn write<const PROCS: usize>(
+        &self,
+        id: DeviceId,
+        caller: ProcessId,
+        security: &SecurityKernel<PROCS>,
+        data: &[u8],
+    ) -> Result<usize, DeviceError> {
+        let entry = self.authorized_device(id, caller, security, CapabilityRight::Write)?;
         entry.driver.write(data)
     }
 
+    /// Look up `id` and check that `caller` may use it with `right` before
+    /// the driver is touched. Missing capabilities and clearance failures
+    /// both surface as `PermissionDenied`.
+    fn authorized_device<const PROCS: usize>(
+        &self,
+        id: DeviceId,
+        caller: ProcessId,
+        security: &SecurityKernel<PROCS>,
+        right: CapabilityRight,
+    ) -> Result<DeviceEntry, DeviceError> {
+        let entry = self.find_device(id).ok_or(DeviceError::NotFound)?;
+        security
+            .authorize_device_access(
+                caller,
+                CapabilityObject::PciDevice(id.raw() as u64),
+                right,
+                entry.driver.security(),
+            )
+            .map_err(|_| DeviceError::PermissionDenied)?;
+        Ok(entry)
+    }
+
     pub fn block_storage(&self, id: DeviceId) -> Result<&dyn BlockStorageDevice, DeviceError> {
         self.block_storage_static(id)
     }
@@ -684,6 +937,35 @@ impl<const MAX: usize> DeviceManager<MAX> {
             .ok_or(DeviceError::Unsupported)
     }
 
+    /// Serial console registered as `id`. `None` when there is no such
+    /// device or it is not a serial console. Like `block_storage`, this is a
+    /// kernel-side accessor and performs no capability check.
+    pub fn serial(&self, id: DeviceId) -> Option<SerialHandle> {
+        let entry = self.find_device(id)?;
+        if entry.driver.kind() != DeviceKind::SerialConsole {
+            return None;
+        }
+        Some(SerialHandle {
+            id,
+            driver: entry.driver,
+            serial: entry.driver.as_serial()?,
+        })
+    }
+
+    /// System timer registered as `id`, or `None` if `id` names anything
+    /// else.
+    pub fn timer(&self, id: DeviceId) -> Option<TimerHandle> {
+        let entry = self.find_device(id)?;
+        if entry.driver.kind() != DeviceKind::SystemTimer {
+            return None;
+        }
+        Some(TimerHandle {
+            id,
+            driver: entry.driver,
+            timer: entry.driver.as_timer()?,
+        })
+    }
+
     pub fn sector_size(&self, id: DeviceId) -> Result<usize, DeviceError> {
         Ok(self.block_storage(id)?.sector_size())
     }
@@ -758,9 +1040,34 @@ impl<const MAX: usize> DeviceManager<MAX> {
     }
 }
 
+/// Byte framing for the buffered serial console. Each write becomes one
+/// frame terminated by `delimiter`; literal `delimiter` and `escape` bytes in
+/// the payload are sent as `escape` followed by the byte, so any data can be
+/// framed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SerialFraming {
+    pub delimiter: u8,
+    pub escape: u8,
+}
+
+impl SerialFraming {
+    /// `None` if the two bytes are equal, which would make frames ambiguous.
+    pub const fn new(delimiter: u8, escape: u8) -> Option<Self> {
+        if delimiter == escape {
+            return None;
+        }
+        Some(Self { delimiter, escape })
+    }
+
+    const fn needs_escape(&self, byte: u8) -> bool {
+        byte == self.delimiter || byte == self.escape
+    }
+}
+
 struct SerialBuffer {
     data: [u8; SerialConsoleDriver::CAPACITY],
     len: usize,
+    framing: Option<SerialFraming>,
 }
 
 impl SerialBuffer {
@@ -768,6 +1075,7 @@ impl SerialBuffer {
         Self {
             data: [0; SerialConsoleDriver::CAPACITY],
             len: 0,
+            framing: None,
         }
     }
 
@@ -788,14 +1096,86 @@ impl SerialBuffer {
             return 0;
         }
         buffer[..to_copy].copy_from_slice(&self.data[..to_copy]);
-        let remaining = self.len - to_copy;
+        self.discard(to_copy);
+        to_copy
+    }
+
+    fn discard(&mut self, count: usize) {
+        let remaining = self.len - count;
         let mut idx = 0usize;
         while idx < remaining {
-            self.data[idx] = self.data[idx + to_copy];
+            self.data[idx] = self.data[idx + count];
             idx += 1;
         }
         self.len = remaining;
-        to_copy
+    }
+
+    /// Queue `payload` as one escaped frame. Frames are never split: if the
+    /// whole frame does not fit, nothing is queued and 0 is returned.
+    fn push_frame(&mut self, framing: SerialFraming, payload: &[u8]) -> usize {
+        let escapes = payload
+            .iter()
+            .filter(|&&byte| framing.needs_escape(byte))
+            .count();
+        if payload.len() + escapes + 1 > SerialConsoleDriver::CAPACITY - self.len {
+            return 0;
+        }
+        for &byte in payload {
+            if framing.needs_escape(byte) {
+                self.data[self.len] = framing.escape;
+                self.len += 1;
+            }
+            self.data[self.len] = byte;
+            self.len += 1;
+        }
+        self.data[self.len] = framing.delimiter;
+        self.len += 1;
+        payload.len()
+    }
+
+    /// Copy the payload of the oldest complete frame into `buffer`,
+    /// un-escaped. Returns 0 while no frame is complete; a frame too large
+    /// for `buffer` is left queued.
+    fn pop_frame(
+        &mut self,
+        framing: SerialFraming,
+        buffer: &mut [u8],
+    ) -> Result<usize, DeviceError> {
+        let mut payload_len = 0usize;
+        let mut escaped = false;
+        let mut end = None;
+        for (idx, &byte) in self.data[..self.len].iter().enumerate() {
+            if escaped {
+                escaped = false;
+                payload_len += 1;
+            } else if byte == framing.escape {
+                escaped = true;
+            } else if byte == framing.delimiter {
+                end = Some(idx);
+                break;
+            } else {
+                payload_len += 1;
+            }
+        }
+        let Some(end) = end else {
+            return Ok(0);
+        };
+        if payload_len > buffer.len() {
+            return Err(DeviceError::BufferTooSmall);
+        }
+        let mut out = 0usize;
+        let mut escaped = false;
+        for &byte in &self.data[..end] {
+            if !escaped && byte == framing.escape {
+                escaped = true;
+                continue;
+            }
+            escaped = false;
+            buffer[out] = byte;
+            out += 1;
+        }
+        self.discard(end + 1);
+        Ok(out)
     }
 }
 
@@ -804,13 +1184,23 @@ pub struct SerialConsoleDriver {
 }
 
 impl SerialConsoleDriver {
-    const CAPACITY: usize = 256;
+    const CAPACITY: usize = 1024;
 
     pub const fn new() -> Self {
         Self {
             buffer: SpinLock::new(SerialBuffer::new()),
         }
     }
+
+    /// Switch between framed and raw byte-stream mode. Bytes already
+    /// buffered are kept as they are.
+    pub fn set_framing(&self, framing: Option<SerialFraming>) {
+        self.buffer.lock().framing = framing;
+    }
+
+    pub fn framing(&self) -> Option<SerialFraming> {
+        self.buffer.lock().framing
+    }
 }
 
 impl DeviceDriver for SerialConsoleDriver {
@@ -826,14 +1216,33 @@ impl DeviceDriver for SerialConsoleDriver {
         DeviceSecurity::new(SecurityClass::Internal, false)
     }
 
+    /// In framed mode, returns one whole frame's payload.
     fn read(&self, buffer: &mut [u8]) -> Result<usize, DeviceError> {
         let mut state = self.buffer.lock();
-        Ok(state.pop(buffer))
+        match state.framing {
+            Some(framing) => state.pop_frame(framing, buffer),
+            None => Ok(state.pop(buffer)),
+        }
     }
 
+    /// In framed mode, `data` is queued as a single frame or not at all.
     fn write(&self, data: &[u8]) -> Result<usize, DeviceError> {
         let mut state = self.buffer.lock();
-        Ok(state.push(data))
+        match state.framing {
+            Some(framing) => Ok(state.push_frame(framing, data)),
+            None => Ok(state.push(data)),
+        }
+    }
+
+    fn as_serial(&self) -> Option<&dyn SerialDevice> {
+        Some(self)
+    }
+}
+
+impl SerialDevice for SerialConsoleDriver {
+    fn flush(&self) -> Result<(), DeviceError> {
+        // Writes land in the buffer synchronously; nothing is in flight.
+        Ok(())
     }
 }
 
@@ -870,23 +1279,50 @@ impl DeviceDriver for SystemTimerDriver {
         if buffer.len() < core::mem::size_of::<u64>() {
             return Err(DeviceError::BufferTooSmall);
         }
-        let value = self.ticks.load(Ordering::Relaxed);
-        let bytes = value.to_le_bytes();
+        let bytes = self.ticks().to_le_bytes();
         buffer[..bytes.len()].copy_from_slice(&bytes);
         Ok(bytes.len())
     }
+
+    fn as_timer(&self) -> Option<&dyn TimerDevice> {
+        Some(self)
+    }
+}
+
+impl TimerDevice for SystemTimerDriver {
+    fn ticks(&self) -> u64 {
+        self.ticks.load(Ordering::Relaxed)
+    }
 }
 
 struct BlockStorageState {
     sectors: [[u8; BlockStorageDriver::SECTOR_SIZE]; BlockStorageDriver::SECTOR_COUNT],
+    /// Trailing CRC32 of each sector, maintained only while `checksummed`.
+    checksums: [u32; BlockStorageDriver::SECTOR_COUNT],
+    checksummed: bool,
 }
 
 impl BlockStorageState {
     const fn new() -> Self {
         Self {
             sectors: [[0; BlockStorageDriver::SECTOR_SIZE]; BlockStorageDriver::SECTOR_COUNT],
+            checksums: [0; BlockStorageDriver::SECTOR_COUNT],
+            checksummed: false,
+        }
+    }
+
+    fn seal(&mut self, sector: usize) {
+        if self.checksummed {
+            self.checksums[sector] = crc32(&self.sectors[sector]);
         }
     }
+
+    fn verify(&self, sector: usize) -> Result<(), DeviceError> {
+        if self.checksummed && crc32(&self.sectors[sector]) != self.checksums[sector] {
+            return Err(DeviceError::CorruptBlock);
+        }
+        Ok(())
+    }
 }
 
 /// Built-in RAM-backed block device used until platform storage drivers register
@@ -905,6 +1341,28 @@ impl BlockStorageDriver {
         }
     }
 
+    /// Store a CRC32 alongside every sector and verify it on read. Enabling
+    /// checksums seals the current contents; they are off by default.
+    pub fn set_checksums(&self, enabled: bool) {
+        let mut state = self.state.lock();
+        state.checksummed = enabled;
+        let mut sector = 0usize;
+        while sector < Self::SECTOR_COUNT {
+            state.seal(sector);
+            sector += 1;
+        }
+    }
+
+    pub fn checksums_enabled(&self) -> bool {
+        self.state.lock().checksummed
+    }
+
+    /// Flip bits of one stored byte without touching its checksum.
+    #[cfg(test)]
+    fn corrupt_byte(&self, sector: usize, offset: usize) {
+        self.state.lock().sectors[sector][offset] ^= 0xff;
+    }
+
     fn validate_transfer(&self, first_sector: u64, byte_len: usize) -> Result<usize, DeviceError> {
         let sector_size = Self::SECTOR_SIZE;
         if byte_len % sector_size != 0 {
@@ -962,6 +1420,7 @@ impl BlockStorageDevice for BlockStorageDriver {
         let mut idx = 0usize;
         while idx < sectors {
             let sector = first_sector as usize + idx;
+            state.verify(sector)?;
             let start = idx * Self::SECTOR_SIZE;
             let end = start + Self::SECTOR_SIZE;
             buffer[start..end].copy_from_slice(&state.sectors[sector]);
@@ -979,6 +1438,7 @@ impl BlockStorageDevice for BlockStorageDriver {
             let start = idx * Self::SECTOR_SIZE;
             let end = start + Self::SECTOR_SIZE;
             state.sectors[sector].copy_from_slice(&data[start..end]);
+            state.seal(sector);
             idx += 1;
         }
         Ok(data.len())
@@ -996,7 +1456,9 @@ impl BlockStorageDevice for BlockStorageDriver {
         let mut state = self.state.lock();
         let mut idx = 0usize;
         while idx < sectors {
-            state.sectors[first_sector as usize + idx].fill(0);
+            let sector = first_sector as usize + idx;
+            state.sectors[sector].fill(0);
+            state.seal(sector);
             idx += 1;
         }
         Ok(())
@@ -1379,4 +1841,247 @@ mod tests {
         let _held_descriptor = driver.descriptor.lock();
         assert_eq!(driver.configure(Some(test_framebuffer())), Ok(()));
     }
+
+    struct SecondaryConsole;
+
+    impl DeviceDriver for SecondaryConsole {
+        fn kind(&self) -> DeviceKind {
+            DeviceKind::SerialConsole
+        }
+
+        fn name(&self) -> &'static str {
+            "serial1"
+        }
+
+        fn security(&self) -> DeviceSecurity {
+            SERIAL_CONSOLE_DRIVER.security()
+        }
+    }
+
+    static SECONDARY_CONSOLE: SecondaryConsole = SecondaryConsole;
+
+    #[test]
+    fn find_by_kind_reports_only_matching_devices() {
+        let mut manager = DeviceManager::<8>::new();
+        let serial0 = manager.register_driver(&SERIAL_CONSOLE_DRIVER).unwrap();
+        manager.register_driver(&BLOCK_STORAGE_DRIVER).unwrap();
+        let serial1 = manager.register_driver(&SECONDARY_CONSOLE).unwrap();
+
+        let mut out = [serial0; 4];
+        assert_eq!(manager.find_by_kind(DeviceKind::SerialConsole, &mut out), 2);
+        assert_eq!(out[0].id, serial0.id);
+        assert_eq!(out[1].id, serial1.id);
+        assert_eq!(manager.find_by_kind(DeviceKind::Framebuffer, &mut out), 0);
+        assert_eq!(
+            manager.find_by_kind(DeviceKind::SerialConsole, &mut out[..1]),
+            1
+        );
+    }
+
+    #[test]
+    fn find_by_name_resolves_core_devices_regardless_of_id() {
+        let mut manager = DeviceManager::<16>::new();
+        manager.install_core_devices().unwrap();
+
+        let timer = manager.find_by_name("system-timer").unwrap();
+        assert_eq!(timer.kind, DeviceKind::SystemTimer);
+        let mut installed = [timer; 16];
+        let count = manager.enumerate(&mut installed);
+        assert!(count > 1);
+        for descriptor in &installed[..count] {
+            assert_eq!(manager.find_by_name(descriptor.name), Some(*descriptor));
+        }
+        assert_eq!(manager.find_by_name("no-such-device"), None);
+    }
+
+    #[test]
+    fn default_device_falls_back_and_clears_on_unregister() {
+        let mut manager = DeviceManager::<8>::new();
+        assert_eq!(manager.default_for(DeviceKind::SerialConsole), None);
+
+        let serial0 = manager.register_driver(&SERIAL_CONSOLE_DRIVER).unwrap();
+        let serial1 = manager.register_driver(&SECONDARY_CONSOLE).unwrap();
+        assert_eq!(
+            manager.default_for(DeviceKind::SerialConsole),
+            Some(serial0.id)
+        );
+
+        manager
+            .set_default(DeviceKind::SerialConsole, serial1.id)
+            .unwrap();
+        assert_eq!(
+            manager.default_for(DeviceKind::SerialConsole),
+            Some(serial1.id)
+        );
+
+        assert_eq!(manager.unregister(serial1.id), Ok(serial1));
+        assert_eq!(
+            manager.default_for(DeviceKind::SerialConsole),
+            Some(serial0.id)
+        );
+        assert_eq!(manager.unregister(serial1.id), Err(DeviceError::NotFound));
+
+        manager.unregister(serial0.id).unwrap();
+        assert_eq!(manager.default_for(DeviceKind::SerialConsole), None);
+    }
+
+    #[test]
+    fn set_default_rejects_kind_mismatch_and_unknown_devices() {
+        let mut manager = DeviceManager::<8>::new();
+        let storage = manager.register_driver(&BLOCK_STORAGE_DRIVER).unwrap();
+
+        assert_eq!(
+            manager.set_default(DeviceKind::SerialConsole, storage.id),
+            Err(DeviceError::Unsupported)
+        );
+        assert_eq!(
+            manager.set_default(DeviceKind::BlockStorage, DeviceId::new(99)),
+            Err(DeviceError::NotFound)
+        );
+        assert_eq!(manager.default_for(DeviceKind::SerialConsole), None);
+        assert_eq!(
+            manager.default_for(DeviceKind::BlockStorage),
+            Some(storage.id)
+        );
+    }
+
+    #[test]
+    fn typed_handles_resolve_only_for_matching_kinds() {
+        let mut manager = DeviceManager::<8>::new();
+        let timer = manager.register_driver(&SYSTEM_TIMER_DRIVER).unwrap();
+        let serial = manager.register_driver(&SERIAL_CONSOLE_DRIVER).unwrap();
+
+        let handle = manager.timer(timer.id).unwrap();
+        assert_eq!(handle.id(), timer.id);
+        assert_eq!(handle.name(), "system-timer");
+        let before = handle.ticks();
+        SYSTEM_TIMER_DRIVER.tick();
+        assert!(handle.ticks() > before);
+        assert!(manager.serial(timer.id).is_none());
+
+        let console = manager.serial(serial.id).unwrap();
+        assert_eq!(console.flush(), Ok(()));
+        assert!(manager.timer(serial.id).is_none());
+        assert!(manager.timer(DeviceId::new(99)).is_none());
+
+        // A console driver without serial operations never yields a handle.
+        let bare = manager.register_driver(&SECONDARY_CONSOLE).unwrap();
+        assert!(manager.serial(bare.id).is_none());
+    }
+
+    #[test]
+    fn framed_serial_writes_escape_delimiters_and_reads_restore_them() {
+        let serial = SerialConsoleDriver::new();
+        assert_eq!(SerialFraming::new(b'\n', b'\n'), None);
+        let framing = SerialFraming::new(b'\n', 0x1b).unwrap();
+        serial.set_framing(Some(framing));
+
+        let first = b"line\none\x1bdone";
+        assert_eq!(serial.write(first), Ok(first.len()));
+        assert_eq!(serial.write(b"\n"), Ok(1));
+
+        let mut out = [0u8; 32];
+        assert_eq!(serial.read(&mut out[..4]), Err(DeviceError::BufferTooSmall));
+        assert_eq!(serial.read(&mut out), Ok(first.len()));
+        assert_eq!(&out[..first.len()], first);
+        assert_eq!(serial.read(&mut out), Ok(1));
+        assert_eq!(out[0], b'\n');
+        assert_eq!(serial.try_read(&mut out), Ok(None));
+
+        // On the wire, each literal delimiter or escape is prefixed.
+        serial.write(b"a\nb").unwrap();
+        serial.set_framing(None);
+        let count = serial.read(&mut out).unwrap();
+        assert_eq!(&out[..count], b"a\x1b\nb\n");
+    }
+
+    #[test]
+    fn checksummed_block_read_detects_corruption() {
+        let storage = BlockStorageDriver::new();
+        assert!(!storage.checksums_enabled());
+        storage.set_checksums(true);
+
+        let mut block = [0u8; BlockStorageDriver::SECTOR_SIZE];
+        block[..6].copy_from_slice(b"mirage");
+        storage.write_sectors(3, &block).unwrap();
+        let mut out = [0u8; BlockStorageDriver::SECTOR_SIZE];
+        assert_eq!(storage.read_sectors(3, &mut out).unwrap(), out.len());
+        assert_eq!(out, block);
+
+        storage.corrupt_byte(3, 2);
+        assert!(matches!(
+            storage.read_sectors(3, &mut out),
+            Err(DeviceError::CorruptBlock)
+        ));
+        let mut span = [0u8; BlockStorageDriver::SECTOR_SIZE * 2];
+        assert!(matches!(
+            storage.read_sectors(2, &mut span),
+            Err(DeviceError::CorruptBlock)
+        ));
+        storage.read_sectors(4, &mut out).unwrap();
+
+        storage.write_sectors(3, &block).unwrap();
+        storage.read_sectors(3, &mut out).unwrap();
+    }
+
+    #[test]
+    fn corruption_goes_unnoticed_with_checksums_off() {
+        let storage = BlockStorageDriver::new();
+        storage
+            .write_sectors(0, &[0x5a; BlockStorageDriver::SECTOR_SIZE])
+            .unwrap();
+        storage.corrupt_byte(0, 0);
+
+        let mut out = [0u8; BlockStorageDriver::SECTOR_SIZE];
+        storage.read_sectors(0, &mut out).unwrap();
+        assert_eq!(out[0], 0xa5);
+
+        storage.set_checksums(true);
+        storage.read_sectors(0, &mut out).unwrap();
+    }
+
+    #[test]
+    fn device_access_is_checked_against_caller_clearance() {
+        use crate::subkernel::{CapabilitySet, Credentials, IsolationLevel, SecurityLabel};
+
+        let mut manager = DeviceManager::<8>::new();
+        let storage = manager.register_driver(&BLOCK_STORAGE_DRIVER).unwrap().id;
+        let mut security = SecurityKernel::<4>::new();
+        let public = ProcessId::new(10);
+        let unprivileged = ProcessId::new(11);
+        let system = ProcessId::new(12);
+        security
+            .register_task(
+                public,
+                Credentials::new(
+                    SecurityLabel::public(),
+                    CapabilitySet::ipc_io(),
+                    IsolationLevel::Process,
+                ),
+            )
+            .unwrap();
+        security
+            .register_task(unprivileged, Credentials::user())
+            .unwrap();
+        security
+            .register_task(system, Credentials::system())
+            .unwrap();
+
+        let mut buffer = [0u8; BlockStorageDriver::SECTOR_SIZE];
+        assert_eq!(
+            manager.read(storage, public, &security, &mut buffer),
+            Err(DeviceError::PermissionDenied)
+        );
+        assert_eq!(
+            manager.write(storage, public, &security, &buffer),
+            Err(DeviceError::PermissionDenied)
+        );
+        assert_eq!(
+            manager.read(storage, unprivileged, &security, &mut buffer),
+            Err(DeviceError::PermissionDenied)
+        );
+        assert!(manager
+            .read(storage, system, &security, &mut buffer)
+            .is_ok());
+    }
 }
@@ -49,7 +49,13 @@ struct ProgramHeader {
     align: u64,
 }
 
-impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH> {
+impl<
+        const MAX_PROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > Kernel<MAX_PROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
     pub(super) fn load_exec_image(
         &mut self,
         caller: ProcessId,
@@ -11,7 +11,7 @@ use crate::kernel::process::{
 };
 use crate::kernel::thread::{CpuContext, ThreadControlBlock, ThreadId};
 use crate::kernel::{memory, Kernel, KernelError, KernelResult};
-use crate::subkernel::Credentials;
+use crate::subkernel::{Credentials, IsolationError};
 use crate::supervisor::SupervisorExecPolicy;
 
 /// Linux-compatible clone bits that Mirage currently models.
@@ -102,7 +102,13 @@ impl CloneTaskRequest {
     }
 }
 
-impl<const NPROC: usize, const MSG_DEPTH: usize> SupervisorExecPolicy for Kernel<NPROC, MSG_DEPTH> {
+impl<
+        const NPROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > SupervisorExecPolicy for Kernel<NPROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
     fn supervisor_authorize_exec(&self, request: &ExecRequest) -> KernelResult<()> {
         self.security
             .authorize_exec(request)
@@ -110,7 +116,13 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> SupervisorExecPolicy for Kernel
     }
 }
 
-impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
+impl<
+        const NPROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > Kernel<NPROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
     /// Create a new process task after L2 authorizes domain creation.
     pub fn spawn_task(&mut self, request: SpawnTaskRequest) -> KernelResult<ProcessId> {
         if let Some(parent_pid) = request.parent {
@@ -161,7 +173,7 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
             .first_thread_for_process(child)
             .ok_or(KernelError::UnknownThread)?;
         let thread_index = self.locate_thread(thread)?;
-        if let Some(tcb) = self.thread_table[thread_index].as_mut() {
+        if let Some(tcb) = self.thread_table.entry_mut(thread_index) {
             tcb.replace_exec_image(image.entry_point, image.stack_pointer);
         }
         Ok(child)
@@ -221,9 +233,7 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
                 crate::arch::x86_64::paging::switch_address_space(request.image.address_space_root);
             memory::destroy_user_address_space(old_root);
         }
-        self.security
-            .register_task(request.caller, request.requested_credentials)
-            .map_err(KernelError::SecurityViolation)?;
+        self.register_security_domain(request.caller, request.requested_credentials)?;
         if let Some(pcb) = self.process_table[self.locate_process(request.caller)?].as_mut() {
             pcb.update_credentials(request.requested_credentials);
         }
@@ -306,6 +316,21 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         self.wait_for_child(parent, None, selector, status_ptr, options)
     }
 
+    /// Registers `pid` with the security kernel, reporting a full domain table
+    /// as [`KernelError::SecurityTableFull`] rather than a policy violation.
+    pub(super) fn register_security_domain(
+        &mut self,
+        pid: ProcessId,
+        creds: Credentials,
+    ) -> KernelResult<()> {
+        self.security
+            .register_task(pid, creds)
+            .map_err(|err| match err {
+                IsolationError::DomainTableFull => KernelError::SecurityTableFull,
+                other => KernelError::SecurityViolation(other),
+            })
+    }
+
     fn authorize_task_creation(
         &self,
         parent: ProcessId,
@@ -339,7 +364,7 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         ))
     }
 
-    fn create_process_task(
+    pub(super) fn create_process_task(
         &mut self,
         entry_point: u64,
         priority: ProcessPriority,
@@ -348,7 +373,7 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         context_template: Option<CpuContext>,
     ) -> KernelResult<ProcessId> {
         let slot = self.find_free_slot().ok_or(KernelError::ProcessTableFull)?;
-        let pid = self.allocate_pid();
+        let pid = self.allocate_pid()?;
         let mut pcb = ProcessControlBlock::new(pid, entry_point, priority, parent);
         pcb.update_credentials(creds);
         if let Some(parent_pid) = parent {
@@ -370,14 +395,19 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
             return Err(error);
         }
 
-        self.security.register_task(pid, creds).map_err(|err| {
+        if let Err(err) = self.register_security_domain(pid, creds) {
             self.release_process_file_table(&mut pcb.files);
-            KernelError::SecurityViolation(err)
-        })?;
+            return Err(err);
+        }
         if let Some(parent_pid) = parent {
             if let Err(err) = self
                 .security
-                .derive_inherited_child_capabilities(parent_pid, pid)
+                .ipc_namespace(parent_pid)
+                .and_then(|namespace| self.security.set_ipc_namespace(pid, namespace))
+                .and_then(|()| {
+                    self.security
+                        .derive_inherited_child_capabilities(parent_pid, pid)
+                })
             {
                 self.release_process_file_table(&mut pcb.files);
                 self.security.revoke_task(pid);
@@ -385,7 +415,7 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
             }
         }
 
-        self.process_table[slot] = Some(pcb);
+        self.install_process(slot, pcb);
 
         let thread_id = match context_template {
             Some(context) => self.create_initial_thread_from_context(pid, priority, context),
@@ -394,7 +424,7 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         let thread_id = match thread_id {
             Ok(id) => id,
             Err(err) => {
-                if let Some(mut failed) = self.process_table[slot].take() {
+                if let Some(mut failed) = self.vacate_process(slot) {
                     if failed.address_space_root != 0 {
                         memory::destroy_user_address_space(failed.address_space_root);
                     }
@@ -423,7 +453,7 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
             .and_then(|_| self.mtss_enqueue_thread(thread_id))
         {
             self.rollback_thread_creation(thread_id);
-            if let Some(mut failed) = self.process_table[slot].take() {
+            if let Some(mut failed) = self.vacate_process(slot) {
                 self.release_process_file_table(&mut failed.files);
             }
             self.security.revoke_task(pid);
@@ -440,7 +470,7 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         creds: Credentials,
     ) -> KernelResult<ProcessId> {
         let slot = self.find_free_slot().ok_or(KernelError::ProcessTableFull)?;
-        let pid = self.allocate_pid();
+        let pid = self.allocate_pid()?;
         let parent_index = self.locate_process(request.caller)?;
         let parent_pcb = self.process_table[parent_index]
             .as_ref()
@@ -471,10 +501,10 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
                     .ok_or(KernelError::AllocationFailed)?;
         }
 
-        self.security.register_task(pid, creds).map_err(|err| {
+        if let Err(err) = self.register_security_domain(pid, creds) {
             self.release_process_file_table(&mut pcb.files);
-            KernelError::SecurityViolation(err)
-        })?;
+            return Err(err);
+        }
         if let Err(err) = self
             .security
             .derive_inherited_child_capabilities(request.caller, pid)
@@ -483,13 +513,13 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
             self.security.revoke_task(pid);
             return Err(KernelError::SecurityViolation(err));
         }
-        self.process_table[slot] = Some(pcb);
+        self.install_process(slot, pcb);
         Ok(pid)
     }
 
     fn rollback_process_shell(&mut self, pid: ProcessId) {
         if let Ok(index) = self.locate_process(pid) {
-            if let Some(mut failed) = self.process_table[index].take() {
+            if let Some(mut failed) = self.vacate_process(index) {
                 if failed.address_space_root != 0 {
                     memory::destroy_user_address_space(failed.address_space_root);
                 }
@@ -508,9 +538,9 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         let slot = self
             .find_free_thread_slot()
             .ok_or(KernelError::ThreadTableFull)?;
-        let id = self.allocate_thread_id();
+        let id = self.allocate_thread_id(slot);
         if context.rsp == 0 {
-            context.rsp = self.allocate_stack_pointer(slot, id);
+            context.rsp = self.allocate_stack_pointer(slot);
         }
         let mut tcb = ThreadControlBlock::new(id, pid, context.rip, priority, context.rsp);
         tcb.context = context;
@@ -518,7 +548,8 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         tcb.gs_base = tcb.context.gs_base;
         tcb.tls_base = tcb.context.fs_base;
         tcb.thread_group = pid;
-        self.thread_table[slot] = Some(tcb);
+        tcb.ready_tick = Some(self.mtss_ticks);
+        self.thread_table.insert_at(slot, tcb);
         self.update_process_thread_count(pid, true);
         Ok(id)
     }
@@ -533,12 +564,12 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         let slot = self
             .find_free_thread_slot()
             .ok_or(KernelError::ThreadTableFull)?;
-        let id = self.allocate_thread_id();
+        let id = self.allocate_thread_id(slot);
         context.rip = request.entry_point;
         context.rax = 0;
         context.rsp = request
             .child_stack
-            .unwrap_or_else(|| self.allocate_stack_pointer(slot, id));
+            .unwrap_or_else(|| self.allocate_stack_pointer(slot));
         if let Some(tls_base) = request.tls_base {
             context.fs = tls_base;
             context.fs_base = tls_base;
@@ -558,7 +589,8 @@ impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
         tcb.set_gs_base(context.gs_base);
         tcb.shares_address_space = request.shares_address_space();
         tcb.shares_descriptor_table = request.shares_descriptors();
-        self.thread_table[slot] = Some(tcb);
+        tcb.ready_tick = Some(self.mtss_ticks);
+        self.thread_table.insert_at(slot, tcb);
         self.update_process_thread_count(pid, true);
         Ok(id)
     }
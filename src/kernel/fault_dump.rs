@@ -0,0 +1,270 @@
+//! Post-mortem snapshots of processes killed by isolation faults.
+//!
+//! Before the kernel terminates a process for an isolation fault it copies the
+//! PCB, up to [`FAULT_DUMP_MAX_THREADS`] TCBs (including their saved
+//! `CpuContext`), a summary of the head of its IPC queue and its security
+//! domain into a [`FaultDump`]. Dumps are fixed-size and land in a small ring
+//! owned by the kernel; once the configured number of dumps is buffered, the oldest is
+//! overwritten.
+//!
+//! Message payload bytes are only copied when both the message class and the
+//! victim's label are at most `Internal`. Anything more sensitive keeps its
+//! class and length but has its data zeroed.
+
+use crate::kernel::ipc::{Message, MessagePriority};
+use crate::kernel::process::{ProcessControlBlock, ProcessId};
+use crate::kernel::thread::ThreadControlBlock;
+use crate::kernel::MAX_OPEN_FILES;
+use crate::subkernel::{IsolationError, SecurityClass, SecurityLabel, TaskDomain};
+
+pub const FAULT_DUMP_CAPACITY: usize = 4;
+pub const FAULT_DUMP_MAX_THREADS: usize = 4;
+pub const FAULT_DUMP_MAX_MESSAGES: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageSummary {
+    pub sender: ProcessId,
+    pub sequence: u64,
+    pub security_class: SecurityClass,
+    pub priority: MessagePriority,
+    pub length: usize,
+    /// Payload bytes; all zero when `redacted`.
+    pub data: [u8; 64],
+    pub redacted: bool,
+}
+
+impl MessageSummary {
+    /// Summarize `message` as queued for a process labelled `victim`.
+    pub fn capture(message: &Message, victim: SecurityLabel) -> Self {
+        let redacted = !exposes_payload(message.payload.security_class, victim);
+        Self {
+            sender: message.origin(),
+            sequence: message.sequence,
+            security_class: message.payload.security_class,
+            priority: message.payload.priority,
+            length: message.payload.length,
+            data: if redacted {
+                [0; 64]
+            } else {
+                message.payload.data
+            },
+            redacted,
+        }
+    }
+}
+
+/// Whether a dump may carry payload bytes of `class` queued for `victim`.
+pub fn exposes_payload(class: SecurityClass, victim: SecurityLabel) -> bool {
+    let ceiling = SecurityLabel::internal();
+    ceiling.dominates(&class.as_label()) && ceiling.dominates(&victim)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FaultDump {
+    /// Uptime tick the fault was handled at.
+    pub tick: u64,
+    pub reason: IsolationError,
+    pub process: ProcessControlBlock<MAX_OPEN_FILES>,
+    pub threads: [Option<ThreadControlBlock>; FAULT_DUMP_MAX_THREADS],
+    /// Threads the process owned; may exceed the number captured.
+    pub thread_count: usize,
+    /// Messages waiting in the IPC queue; may exceed the number summarized.
+    pub queue_depth: usize,
+    pub messages: [Option<MessageSummary>; FAULT_DUMP_MAX_MESSAGES],
+    /// `None` if the process had already lost its domain.
+    pub domain: Option<TaskDomain>,
+}
+
+impl FaultDump {
+    pub const fn new(
+        tick: u64,
+        reason: IsolationError,
+        process: ProcessControlBlock<MAX_OPEN_FILES>,
+        domain: Option<TaskDomain>,
+    ) -> Self {
+        Self {
+            tick,
+            reason,
+            process,
+            threads: [None; FAULT_DUMP_MAX_THREADS],
+            thread_count: 0,
+            queue_depth: 0,
+            messages: [None; FAULT_DUMP_MAX_MESSAGES],
+            domain,
+        }
+    }
+
+    /// Label payload exposure is judged against: the domain's if the process
+    /// still has one, otherwise the PCB's.
+    pub fn victim_label(&self) -> SecurityLabel {
+        self.domain
+            .map(|domain| domain.label())
+            .unwrap_or(self.process.security_label)
+    }
+
+    pub fn record_thread(&mut self, tcb: ThreadControlBlock) {
+        if self.thread_count < FAULT_DUMP_MAX_THREADS {
+            self.threads[self.thread_count] = Some(tcb);
+        }
+        self.thread_count += 1;
+    }
+
+    pub fn record_message(&mut self, message: &Message) {
+        if self.queue_depth < FAULT_DUMP_MAX_MESSAGES {
+            self.messages[self.queue_depth] =
+                Some(MessageSummary::capture(message, self.victim_label()));
+        }
+        self.queue_depth += 1;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultDumpError {
+    InvalidLimit,
+}
+
+/// Ring of the most recent fault dumps, holding at most `limit` of them.
+#[derive(Clone, Copy, Debug)]
+pub struct FaultDumpRing<const N: usize> {
+    dumps: [Option<FaultDump>; N],
+    head: usize,
+    len: usize,
+    limit: usize,
+    overwritten: u64,
+}
+
+impl<const N: usize> FaultDumpRing<N> {
+    pub const fn new() -> Self {
+        Self {
+            dumps: [None; N],
+            head: 0,
+            len: 0,
+            limit: N,
+            overwritten: 0,
+        }
+    }
+
+    /// Keep at most `limit` dumps, discarding the oldest ones beyond it.
+    pub fn set_limit(&mut self, limit: usize) -> Result<(), FaultDumpError> {
+        if limit == 0 || limit > N {
+            return Err(FaultDumpError::InvalidLimit);
+        }
+        while self.len > limit {
+            self.discard_oldest();
+        }
+        self.limit = limit;
+        Ok(())
+    }
+
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn push(&mut self, dump: FaultDump) {
+        if self.len == self.limit {
+            self.discard_oldest();
+        }
+        let slot = (self.head + self.len) % N;
+        self.dumps[slot] = Some(dump);
+        self.len += 1;
+    }
+
+    /// Remove and return the oldest dump.
+    pub fn take(&mut self) -> Option<FaultDump> {
+        if self.len == 0 {
+            return None;
+        }
+        let dump = self.dumps[self.head].take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        dump
+    }
+
+    /// Copy of the most recent dump, left in the ring.
+    pub fn newest(&self) -> Option<FaultDump> {
+        if self.len == 0 {
+            return None;
+        }
+        self.dumps[(self.head + self.len - 1) % N]
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Dumps discarded before anyone took them.
+    pub const fn overwritten(&self) -> u64 {
+        self.overwritten
+    }
+
+    fn discard_oldest(&mut self) {
+        self.take();
+        self.overwritten = self.overwritten.saturating_add(1);
+    }
+}
+
+impl<const N: usize> Default for FaultDumpRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::ipc::MessagePayload;
+    use crate::kernel::process::ProcessPriority;
+
+    fn dump(tick: u64) -> FaultDump {
+        let pcb =
+            ProcessControlBlock::new(ProcessId::new(7), 0x4000, ProcessPriority::Normal, None);
+        FaultDump::new(tick, IsolationError::PolicyViolation, pcb, None)
+    }
+
+    #[test]
+    fn ring_overwrites_oldest_dump_within_limit() {
+        let mut ring: FaultDumpRing<3> = FaultDumpRing::new();
+        assert_eq!(ring.set_limit(4), Err(FaultDumpError::InvalidLimit));
+        ring.set_limit(2).unwrap();
+        ring.push(dump(1));
+        ring.push(dump(2));
+        ring.push(dump(3));
+        assert_eq!(ring.len(), 2);
+        assert_eq!(ring.overwritten(), 1);
+
+        assert_eq!(ring.take().unwrap().tick, 2);
+        assert_eq!(ring.take().unwrap().tick, 3);
+        assert!(ring.take().is_none());
+    }
+
+    #[test]
+    fn payload_is_exposed_only_up_to_internal() {
+        let public = Message::new(
+            ProcessId::new(2),
+            ProcessId::new(7),
+            1,
+            MessagePayload::from_slice(SecurityClass::Public, b"hello"),
+        );
+        let secret = Message::new(
+            ProcessId::new(2),
+            ProcessId::new(7),
+            2,
+            MessagePayload::from_slice(SecurityClass::Confidential, b"secret"),
+        );
+
+        let shown = MessageSummary::capture(&public, SecurityLabel::public());
+        assert!(!shown.redacted);
+        assert_eq!(&shown.data[..5], b"hello");
+
+        let hidden = MessageSummary::capture(&secret, SecurityLabel::public());
+        assert!(hidden.redacted);
+        assert_eq!(hidden.length, 6);
+        assert_eq!(hidden.data, [0; 64]);
+
+        assert!(MessageSummary::capture(&public, SecurityLabel::confidential()).redacted);
+    }
+}
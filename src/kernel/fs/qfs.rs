@@ -1348,6 +1348,8 @@ fn map_device_error(error: DeviceError) -> FsError {
     match error {
         DeviceError::NotFound | DeviceError::RegistryFull => FsError::NoSpace,
         DeviceError::Busy => FsError::Busy,
+        DeviceError::CorruptBlock => FsError::ChecksumMismatch,
+        DeviceError::PermissionDenied => FsError::PermissionDenied,
         DeviceError::Unsupported | DeviceError::BufferTooSmall => FsError::Unsupported,
     }
 }
@@ -737,6 +737,8 @@ fn map_device_error(error: crate::kernel::device::DeviceError) -> FsError {
         crate::kernel::device::DeviceError::NotFound
         | crate::kernel::device::DeviceError::RegistryFull => FsError::NoSpace,
         crate::kernel::device::DeviceError::Busy => FsError::Busy,
+        crate::kernel::device::DeviceError::CorruptBlock => FsError::ChecksumMismatch,
+        crate::kernel::device::DeviceError::PermissionDenied => FsError::PermissionDenied,
         crate::kernel::device::DeviceError::Unsupported
         | crate::kernel::device::DeviceError::BufferTooSmall => FsError::Unsupported,
     }
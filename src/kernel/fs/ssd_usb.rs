@@ -375,6 +375,8 @@ fn fs_error_from_device(error: DeviceError) -> FsError {
         DeviceError::BufferTooSmall => FsError::InvalidArgument,
         DeviceError::Unsupported => FsError::Unsupported,
         DeviceError::Busy => FsError::Busy,
+        DeviceError::CorruptBlock => FsError::ChecksumMismatch,
+        DeviceError::PermissionDenied => FsError::PermissionDenied,
     }
 }
 
@@ -1,9 +1,24 @@
 //! Fixed-capacity futex wait queues keyed by user address and address space.
 
 use crate::kernel::thread::ThreadId;
+use crate::kernel::time::rebase_deadline;
 
 pub const MAX_FUTEX_WAITERS: usize = crate::kernel::thread::MAX_THREADS;
 
+/// Key owner for waits on kernel-mapped memory. Those mappings sit at the
+/// same address in every process, so the address alone names the word and
+/// waiters and wakers may belong to different processes.
+pub const SHARED_FUTEX_OWNER: u64 = u64::MAX;
+
+/// Result of a shared-memory futex wait.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FutexWaitOutcome {
+    /// The word held the expected value and the caller is now blocked.
+    Blocked,
+    /// The word no longer held the expected value; nothing was blocked.
+    ValueChanged,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct FutexKey {
     pub owner: u64,
@@ -118,6 +133,25 @@ impl<const MAX: usize> FutexTable<MAX> {
         count
     }
 
+    /// Move every timeout across a clock jump from `old_ns` to `new_ns`;
+    /// see [`rebase_deadline`].
+    pub fn rebase(&mut self, old_ns: u128, new_ns: u128) {
+        for waiter in self.waiters.iter_mut().flatten() {
+            if let Some(deadline) = waiter.deadline_ns.as_mut() {
+                *deadline = rebase_deadline(*deadline, old_ns, new_ns);
+            }
+        }
+    }
+
+    /// Earliest timeout among waiters that have one.
+    pub fn next_deadline(&self) -> Option<u128> {
+        self.waiters
+            .iter()
+            .flatten()
+            .filter_map(|waiter| waiter.deadline_ns)
+            .min()
+    }
+
     pub fn remove_thread(&mut self, thread: ThreadId) {
         let mut idx = 0usize;
         while idx < MAX {
@@ -0,0 +1,207 @@
+//! Generation-checked slot storage for kernel object tables.
+//!
+//! A handle packs a slot index with the slot's generation. Freeing a slot bumps
+//! its generation, so a handle kept past the object's lifetime stops resolving
+//! instead of aliasing whatever object reuses the slot next.
+
+use core::ops::Index;
+
+use crate::kernel::collections::SlotBitmap;
+
+/// Encoded (index, generation) pair. Index 0 encodes as raw 1 so that a zero
+/// handle never names a live object. The whole encoding fits in 32 bits, so a
+/// thread handle can be returned to userspace as a TID unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Handle(u64);
+
+impl Handle {
+    const INDEX_BITS: u32 = 16;
+    const INDEX_MASK: u64 = (1 << Self::INDEX_BITS) - 1;
+
+    pub const fn new(index: usize, generation: u16) -> Self {
+        Self(((generation as u64) << Self::INDEX_BITS) | (index as u64 + 1))
+    }
+
+    pub const fn from_raw(raw: u64) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(&self) -> u64 {
+        self.0
+    }
+
+    /// Slot index, or `None` for the reserved zero encoding.
+    pub const fn index(&self) -> Option<usize> {
+        match self.0 & Self::INDEX_MASK {
+            0 => None,
+            encoded => Some((encoded - 1) as usize),
+        }
+    }
+
+    pub const fn generation(&self) -> u16 {
+        (self.0 >> Self::INDEX_BITS) as u16
+    }
+}
+
+/// Fixed-capacity table whose entries are addressed by generation-checked
+/// handles. Reads by raw index are allowed for table scans; all insertions and
+/// removals go through methods so generations stay accurate.
+#[derive(Clone, Copy)]
+pub struct Slot<T: Copy, const N: usize> {
+    entries: [Option<T>; N],
+    generations: [u16; N],
+    occupied: SlotBitmap<N>,
+}
+
+impl<T: Copy, const N: usize> Slot<T, N> {
+    pub const fn new() -> Self {
+        assert!(
+            N < Handle::INDEX_MASK as usize,
+            "slot table too large to encode"
+        );
+        Self {
+            entries: [None; N],
+            generations: [0; N],
+            occupied: SlotBitmap::new(),
+        }
+    }
+
+    /// Handle the next object stored at `index` will have.
+    pub const fn handle_for(&self, index: usize) -> Handle {
+        Handle::new(index, self.generations[index])
+    }
+
+    /// Lowest vacant index.
+    pub fn find_free(&self) -> Option<usize> {
+        self.occupied.first_clear()
+    }
+
+    /// Store `value` in a vacant slot. Returns false if the slot is occupied.
+    pub fn insert_at(&mut self, index: usize, value: T) -> bool {
+        if self.entries[index].is_some() {
+            return false;
+        }
+        self.entries[index] = Some(value);
+        self.occupied.set(index);
+        true
+    }
+
+    /// Slot index for `handle` if it still names a live object.
+    pub fn index_of(&self, handle: Handle) -> Option<usize> {
+        let index = handle.index()?;
+        if index < N
+            && self.entries[index].is_some()
+            && self.generations[index] == handle.generation()
+        {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        let index = self.index_of(handle)?;
+        self.entries[index].as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        let index = self.index_of(handle)?;
+        self.entries[index].as_mut()
+    }
+
+    pub fn entry_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.entries.get_mut(index)?.as_mut()
+    }
+
+    /// Free the slot at `index`, invalidating every handle issued for it.
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        let value = self.entries[index].take()?;
+        self.occupied.clear(index);
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        Some(value)
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let index = self.index_of(handle)?;
+        self.remove_at(index)
+    }
+
+    /// Free every slot; outstanding handles are invalidated.
+    pub fn clear(&mut self) {
+        let mut idx = 0;
+        while idx < N {
+            self.remove_at(idx);
+            idx += 1;
+        }
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, Option<T>> {
+        self.entries.iter()
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T: Copy, const N: usize> Default for Slot<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy, const N: usize> Index<usize> for Slot<T, N> {
+    type Output = Option<T>;
+
+    fn index(&self, index: usize) -> &Option<T> {
+        &self.entries[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_handle_is_rejected_after_slot_reuse() {
+        let mut table: Slot<u32, 2> = Slot::new();
+        let first = table.handle_for(0);
+        assert!(table.insert_at(0, 7));
+        assert_eq!(table.get(first), Some(&7));
+
+        assert_eq!(table.remove(first), Some(7));
+        let second = table.handle_for(0);
+        assert!(table.insert_at(0, 9));
+
+        assert_ne!(first, second);
+        assert_eq!(second.index(), first.index());
+        assert_eq!(table.get(first), None);
+        assert_eq!(table.remove(first), None);
+        assert_eq!(table.get(second), Some(&9));
+    }
+
+    #[test]
+    fn generation_wraps_without_panicking() {
+        let mut table: Slot<u8, 1> = Slot::new();
+        let mut round = 0u32;
+        while round <= u16::MAX as u32 {
+            assert!(table.insert_at(0, 1));
+            table.remove_at(0);
+            round += 1;
+        }
+        assert_eq!(table.handle_for(0).generation(), 0);
+        assert!(Handle::new(0, u16::MAX).raw() <= u64::from(u32::MAX));
+        assert!(table.insert_at(0, 2));
+        assert_eq!(table.get(Handle::new(0, 0)), Some(&2));
+        assert_eq!(table.get(Handle::new(0, 1)), None);
+    }
+
+    #[test]
+    fn zero_and_out_of_range_handles_do_not_resolve() {
+        let mut table: Slot<u8, 1> = Slot::new();
+        assert!(table.insert_at(0, 1));
+        assert_eq!(table.get(Handle::from_raw(0)), None);
+        assert_eq!(table.get(Handle::new(5, 0)), None);
+        assert_eq!(Handle::new(0, 0).raw(), 1);
+    }
+}
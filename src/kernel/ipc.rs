@@ -1,11 +1,23 @@
 //! Inter-process communication primitives.
 
+use core::sync::atomic::{AtomicU64, Ordering};
+
 use crate::kernel::process::ProcessId;
 use crate::subkernel::SecurityClass;
 
+/// Delivery order within a receiver's queue. Independent of
+/// `SecurityClass`: an urgent public message overtakes bulk system traffic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MessagePriority {
+    Urgent,
+    Normal,
+    Bulk,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct MessagePayload {
     pub security_class: SecurityClass,
+    pub priority: MessagePriority,
     pub data: [u8; 64],
     pub length: usize,
 }
@@ -14,6 +26,7 @@ impl MessagePayload {
     pub const fn empty(security_class: SecurityClass) -> Self {
         Self {
             security_class,
+            priority: MessagePriority::Normal,
             data: [0; 64],
             length: 0,
         }
@@ -29,6 +42,11 @@ impl MessagePayload {
         payload.length = idx;
         payload
     }
+
+    pub const fn with_priority(mut self, priority: MessagePriority) -> Self {
+        self.priority = priority;
+        self
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -37,6 +55,12 @@ pub struct Message {
     pub receiver: ProcessId,
     pub sequence: u64,
     pub payload: MessagePayload,
+    /// Process that first sent the payload when it reached `receiver` through
+    /// one or more forwarders; `None` for direct messages.
+    pub original_sender: Option<ProcessId>,
+    /// Kernel tick at which the message was queued, for delivery latency.
+    #[cfg(feature = "latency-metrics")]
+    pub sent_tick: u64,
 }
 
 impl Message {
@@ -51,21 +75,216 @@ impl Message {
             receiver,
             sequence,
             payload,
+            original_sender: None,
+            #[cfg(feature = "latency-metrics")]
+            sent_tick: 0,
+        }
+    }
+
+    /// Process that originally produced this payload.
+    pub fn origin(&self) -> ProcessId {
+        self.original_sender.unwrap_or(self.sender)
+    }
+
+    /// Write the canonical wire form into the front of `out`, returning
+    /// [`MESSAGE_WIRE_SIZE`]. `original_sender` and the payload priority are
+    /// not carried; decoded messages are `Normal` priority.
+    pub fn encode(&self, out: &mut [u8]) -> Result<usize, CodecError> {
+        if out.len() < MESSAGE_WIRE_SIZE || self.payload.length > self.payload.data.len() {
+            return Err(CodecError::BufferSize);
+        }
+        let out = &mut out[..MESSAGE_WIRE_SIZE];
+        out[0..4].copy_from_slice(&MESSAGE_WIRE_MAGIC);
+        out[4..6].copy_from_slice(&MESSAGE_WIRE_VERSION.to_le_bytes());
+        out[6] = encode_security_class(self.payload.security_class);
+        out[7] = self.payload.length as u8;
+        out[8..16].copy_from_slice(&self.sender.raw().to_le_bytes());
+        out[16..24].copy_from_slice(&self.receiver.raw().to_le_bytes());
+        out[24..32].copy_from_slice(&self.sequence.to_le_bytes());
+        out[32..].copy_from_slice(&self.payload.data);
+        Ok(MESSAGE_WIRE_SIZE)
+    }
+
+    /// Parse exactly one wire-format message.
+    pub fn decode(bytes: &[u8]) -> Result<Message, CodecError> {
+        if bytes.len() != MESSAGE_WIRE_SIZE {
+            return Err(CodecError::BufferSize);
+        }
+        if bytes[0..4] != MESSAGE_WIRE_MAGIC {
+            return Err(CodecError::BadMagic);
         }
+        if u16::from_le_bytes([bytes[4], bytes[5]]) != MESSAGE_WIRE_VERSION {
+            return Err(CodecError::UnsupportedVersion);
+        }
+        let security_class = decode_security_class(bytes[6])?;
+        let length = bytes[7] as usize;
+        if length > MESSAGE_WIRE_DATA_LEN {
+            return Err(CodecError::LengthTooLarge);
+        }
+        let payload = MessagePayload::from_slice(security_class, &bytes[32..32 + length]);
+        Ok(Message::new(
+            ProcessId::new(read_u64_le(&bytes[8..16])),
+            ProcessId::new(read_u64_le(&bytes[16..24])),
+            read_u64_le(&bytes[24..32]),
+            payload,
+        ))
     }
 }
 
+/// Wire layout, all integers little-endian:
+///
+/// | offset | size | field                                   |
+/// |--------|------|-----------------------------------------|
+/// | 0      | 4    | magic `b"MIPC"`                         |
+/// | 4      | 2    | version (`MESSAGE_WIRE_VERSION`)        |
+/// | 6      | 1    | class: 0 public, 1 internal, 2 confidential, 3 system |
+/// | 7      | 1    | payload length (at most 64)             |
+/// | 8      | 8    | sender pid                              |
+/// | 16     | 8    | receiver pid                            |
+/// | 24     | 8    | sequence                                |
+/// | 32     | 64   | payload data, zero padded               |
+pub const MESSAGE_WIRE_SIZE: usize = 96;
+pub const MESSAGE_WIRE_MAGIC: [u8; 4] = *b"MIPC";
+pub const MESSAGE_WIRE_VERSION: u16 = 1;
+const MESSAGE_WIRE_DATA_LEN: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodecError {
+    BufferSize,
+    BadMagic,
+    UnsupportedVersion,
+    UnknownClass,
+    LengthTooLarge,
+}
+
+const fn encode_security_class(class: SecurityClass) -> u8 {
+    match class {
+        SecurityClass::Public => 0,
+        SecurityClass::Internal => 1,
+        SecurityClass::Confidential => 2,
+        SecurityClass::System => 3,
+    }
+}
+
+const fn decode_security_class(raw: u8) -> Result<SecurityClass, CodecError> {
+    match raw {
+        0 => Ok(SecurityClass::Public),
+        1 => Ok(SecurityClass::Internal),
+        2 => Ok(SecurityClass::Confidential),
+        3 => Ok(SecurityClass::System),
+        _ => Err(CodecError::UnknownClass),
+    }
+}
+
+fn read_u64_le(bytes: &[u8]) -> u64 {
+    let mut raw = [0u8; 8];
+    raw.copy_from_slice(bytes);
+    u64::from_le_bytes(raw)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum MessageQueueError {
     Full,
+    /// The message's `SecurityClass` already holds its quota of slots.
+    ClassQuotaExceeded,
+    /// The head message no longer matches the checksum taken when it was
+    /// queued. It has been dropped.
+    Corrupted {
+        sequence: u64,
+    },
 }
 
+/// What [`MessageQueue::push`] does when every slot is taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Fail the push with [`MessageQueueError::Full`].
+    #[default]
+    RejectNew,
+    /// Evict the head of the queue to make room, like a ring overwrite.
+    DropOldest,
+}
+
+const SECURITY_CLASSES: usize = 4;
+
+/// A queued message and, when the queue checksums, the checksum taken at
+/// push time.
+#[derive(Clone, Copy)]
+struct QueuedMessage {
+    message: Message,
+    checksum: Option<u32>,
+}
+
+/// FNV-1a over every field a receiver can observe. Not carried on the wire.
+fn message_checksum(message: &Message) -> u32 {
+    const FNV_OFFSET: u32 = 0x811c_9dc5;
+    const FNV_PRIME: u32 = 0x0100_0193;
+    let mut hash = FNV_OFFSET;
+    let mut feed = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash = (hash ^ byte as u32).wrapping_mul(FNV_PRIME);
+        }
+    };
+    feed(&message.sender.raw().to_le_bytes());
+    feed(&message.receiver.raw().to_le_bytes());
+    feed(&message.sequence.to_le_bytes());
+    match message.original_sender {
+        Some(origin) => {
+            feed(&[1]);
+            feed(&origin.raw().to_le_bytes());
+        }
+        None => feed(&[0]),
+    }
+    feed(&[
+        encode_security_class(message.payload.security_class),
+        message.payload.priority as u8,
+    ]);
+    feed(&(message.payload.length as u64).to_le_bytes());
+    feed(&message.payload.data);
+    hash
+}
+
+/// Messages of one `SecurityClass` waiting in a queue, and the cap on them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClassOccupancy {
+    pub queued: usize,
+    pub quota: Option<usize>,
+}
+
+/// Cumulative traffic through one [`MessageQueue`] since it was created or
+/// last cleared.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Messages accepted by `push`, less any withdrawn by rollback.
+    pub enqueued: u64,
+    /// Messages handed out by `pop`.
+    pub dequeued: u64,
+    /// Messages lost: pushes rejected as full or over quota, heads evicted
+    /// under [`OverflowPolicy::DropOldest`], and heads that failed their
+    /// checksum.
+    pub dropped: u64,
+}
+
+/// Bounded receive queue ordered by `MessagePriority`, FIFO within a level.
+///
+/// Each `SecurityClass` can be capped to a number of slots; classes without
+/// a quota share whatever capacity is free. A full queue rejects new
+/// messages unless its [`OverflowPolicy`] says to drop the oldest.
+///
+/// With checksums enabled, each message is checksummed as it is queued and
+/// verified as it is popped, so a stray write into queue memory is caught
+/// instead of delivered.
 #[derive(Clone, Copy)]
 pub struct MessageQueue<const N: usize> {
-    buffer: [Option<Message>; N],
+    buffer: [Option<QueuedMessage>; N],
     head: usize,
     tail: usize,
     len: usize,
+    /// Queue position the most recent push landed at, for rollback.
+    last_push: Option<usize>,
+    class_quotas: [Option<usize>; SECURITY_CLASSES],
+    overflow_policy: OverflowPolicy,
+    checksums: bool,
+    stats: QueueStats,
 }
 
 impl<const N: usize> MessageQueue<N> {
@@ -75,45 +294,148 @@ impl<const N: usize> MessageQueue<N> {
             head: 0,
             tail: 0,
             len: 0,
+            last_push: None,
+            class_quotas: [None; SECURITY_CLASSES],
+            overflow_policy: OverflowPolicy::RejectNew,
+            checksums: false,
+            stats: QueueStats {
+                enqueued: 0,
+                dequeued: 0,
+                dropped: 0,
+            },
         }
     }
 
-    pub fn push(&mut self, message: Message) -> Result<(), MessageQueueError> {
-        if self.is_full() {
+    const fn slot(&self, offset: usize) -> usize {
+        (self.head + offset) % N
+    }
+
+    fn priority_at(&self, offset: usize) -> MessagePriority {
+        self.buffer[self.slot(offset)]
+            .map(|queued| queued.message.payload.priority)
+            .unwrap_or(MessagePriority::Bulk)
+    }
+
+    /// Queue `message` behind everything of equal or higher priority. When the
+    /// queue is full and the policy is [`OverflowPolicy::DropOldest`], the
+    /// head is evicted first and returned without checksum verification.
+    pub fn push(&mut self, message: Message) -> Result<Option<Message>, MessageQueueError> {
+        let evicts = self.is_full();
+        if evicts && self.overflow_policy == OverflowPolicy::RejectNew {
+            self.stats.dropped += 1;
             return Err(MessageQueueError::Full);
         }
-        self.buffer[self.tail] = Some(message);
+        let class = message.payload.security_class;
+        if let Some(quota) = self.class_quotas[class as usize] {
+            let mut queued = self.class_len(class);
+            if evicts && self.peek_at(0).map(|head| head.payload.security_class) == Some(class) {
+                queued -= 1;
+            }
+            if queued >= quota {
+                self.stats.dropped += 1;
+                return Err(MessageQueueError::ClassQuotaExceeded);
+            }
+        }
+        let evicted = if evicts {
+            self.stats.dropped += 1;
+            self.take_head().map(|queued| queued.message)
+        } else {
+            None
+        };
+        let mut position = self.len;
+        while position > 0 && self.priority_at(position - 1) > message.payload.priority {
+            self.buffer[self.slot(position)] = self.buffer[self.slot(position - 1)];
+            position -= 1;
+        }
+        let checksum = self.checksums.then(|| message_checksum(&message));
+        self.buffer[self.slot(position)] = Some(QueuedMessage { message, checksum });
         self.tail = (self.tail + 1) % N;
         self.len += 1;
-        Ok(())
+        self.last_push = Some(position);
+        self.stats.enqueued += 1;
+        Ok(evicted)
     }
 
-    pub fn pop(&mut self) -> Option<Message> {
+    /// Dequeue the head message. A head that fails its checksum is dropped
+    /// and reported as [`MessageQueueError::Corrupted`].
+    pub fn pop(&mut self) -> Result<Option<Message>, MessageQueueError> {
+        let Some(queued) = self.take_head() else {
+            return Ok(None);
+        };
+        match queued.checksum {
+            Some(checksum) if checksum != message_checksum(&queued.message) => {
+                self.stats.dropped += 1;
+                Err(MessageQueueError::Corrupted {
+                    sequence: queued.message.sequence,
+                })
+            }
+            _ => {
+                self.stats.dequeued += 1;
+                Ok(Some(queued.message))
+            }
+        }
+    }
+
+    fn take_head(&mut self) -> Option<QueuedMessage> {
         if self.len == 0 {
             return None;
         }
-        let message = self.buffer[self.head];
+        let queued = self.buffer[self.head];
         self.buffer[self.head] = None;
         self.head = (self.head + 1) % N;
         self.len -= 1;
-        message
+        self.last_push = self.last_push.and_then(|position| position.checked_sub(1));
+        queued
     }
 
-    pub fn rollback_last_push(&mut self) -> Option<Message> {
-        if self.len == 0 {
+    /// Message `offset` places behind the head, without dequeuing it.
+    pub fn peek_at(&self, offset: usize) -> Option<&Message> {
+        if offset >= self.len {
             return None;
         }
+        self.buffer[self.slot(offset)]
+            .as_ref()
+            .map(|queued| &queued.message)
+    }
+
+    /// Mutable access to a queued message without refreshing its checksum,
+    /// for simulating stray writes into queue memory.
+    #[cfg(test)]
+    pub(crate) fn message_at_mut(&mut self, offset: usize) -> Option<&mut Message> {
+        if offset >= self.len {
+            return None;
+        }
+        let slot = self.slot(offset);
+        self.buffer[slot].as_mut().map(|queued| &mut queued.message)
+    }
+
+    /// Withdraw the message queued by the most recent `push`, wherever its
+    /// priority placed it.
+    pub fn rollback_last_push(&mut self) -> Option<Message> {
+        let position = self.last_push.take()?;
+        let message = self.buffer[self.slot(position)].map(|queued| queued.message);
+        let mut offset = position;
+        while offset + 1 < self.len {
+            self.buffer[self.slot(offset)] = self.buffer[self.slot(offset + 1)];
+            offset += 1;
+        }
         self.tail = (self.tail + N - 1) % N;
-        let message = self.buffer[self.tail];
         self.buffer[self.tail] = None;
         self.len -= 1;
+        self.stats.enqueued = self.stats.enqueued.saturating_sub(1);
         message
     }
 
+    /// Drop every queued message, class quota and statistic, and go back to
+    /// rejecting pushes into a full queue. Checksumming stays as configured.
     pub fn clear(&mut self) {
+        self.stats = QueueStats::default();
         self.head = 0;
         self.tail = 0;
         self.len = 0;
+        self.last_push = None;
+        self.class_quotas = [None; SECURITY_CLASSES];
+        self.overflow_policy = OverflowPolicy::RejectNew;
         let mut idx = 0;
         while idx < N {
             self.buffer[idx] = None;
@@ -132,4 +454,840 @@ impl<const N: usize> MessageQueue<N> {
     pub fn is_full(&self) -> bool {
         self.len == N
     }
+
+    /// Cap `class` to `max_slots` queued messages, or lift its cap with
+    /// `None`. Messages already queued beyond a lowered cap stay queued.
+    pub fn set_class_quota(&mut self, class: SecurityClass, max_slots: Option<usize>) {
+        self.class_quotas[class as usize] = max_slots;
+    }
+
+    pub fn set_overflow_policy(&mut self, policy: OverflowPolicy) {
+        self.overflow_policy = policy;
+    }
+
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    /// Checksum messages from the next push on. Messages already queued keep
+    /// whatever checksum they were queued with.
+    pub fn set_checksums(&mut self, enabled: bool) {
+        self.checksums = enabled;
+    }
+
+    pub fn checksums(&self) -> bool {
+        self.checksums
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.stats
+    }
+
+    pub fn class_occupancy(&self, class: SecurityClass) -> ClassOccupancy {
+        ClassOccupancy {
+            queued: self.class_len(class),
+            quota: self.class_quotas[class as usize],
+        }
+    }
+
+    fn class_len(&self, class: SecurityClass) -> usize {
+        (0..self.len)
+            .filter_map(|offset| self.peek_at(offset))
+            .filter(|message| message.payload.security_class == class)
+            .count()
+    }
+}
+
+/// Transparent delivery override: messages addressed to `from` are queued for
+/// `to` instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpcRedirect {
+    pub from: ProcessId,
+    pub to: ProcessId,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedirectTableError {
+    Full,
+}
+
+#[derive(Clone, Copy)]
+pub struct RedirectTable<const N: usize> {
+    entries: [Option<IpcRedirect>; N],
+}
+
+impl<const N: usize> RedirectTable<N> {
+    pub const fn new() -> Self {
+        Self { entries: [None; N] }
+    }
+
+    /// Install or replace the redirect for `redirect.from`.
+    pub fn insert(&mut self, redirect: IpcRedirect) -> Result<(), RedirectTableError> {
+        let mut free = None;
+        let mut idx = 0;
+        while idx < N {
+            match self.entries[idx] {
+                Some(entry) if entry.from == redirect.from => {
+                    self.entries[idx] = Some(redirect);
+                    return Ok(());
+                }
+                None if free.is_none() => free = Some(idx),
+                _ => {}
+            }
+            idx += 1;
+        }
+        let slot = free.ok_or(RedirectTableError::Full)?;
+        self.entries[slot] = Some(redirect);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, from: ProcessId) -> Option<IpcRedirect> {
+        let mut idx = 0;
+        while idx < N {
+            if let Some(entry) = self.entries[idx] {
+                if entry.from == from {
+                    self.entries[idx] = None;
+                    return Some(entry);
+                }
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    pub fn lookup(&self, from: ProcessId) -> Option<ProcessId> {
+        let mut idx = 0;
+        while idx < N {
+            if let Some(entry) = self.entries[idx] {
+                if entry.from == from {
+                    return Some(entry.to);
+                }
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    /// Drop every redirect that starts or ends at `pid`.
+    pub fn remove_process(&mut self, pid: ProcessId) {
+        let mut idx = 0;
+        while idx < N {
+            if let Some(entry) = self.entries[idx] {
+                if entry.from == pid || entry.to == pid {
+                    self.entries[idx] = None;
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+    }
+}
+
+impl<const N: usize> Default for RedirectTable<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Receiver-granted send allowance for one sender/receiver pair. Pairs with no
+/// entry are not flow controlled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpcCredits {
+    pub sender: ProcessId,
+    pub receiver: ProcessId,
+    pub balance: u32,
+    /// Return one credit to the sender each time the receiver dequeues one of
+    /// its messages.
+    pub auto_regrant: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CreditTableError {
+    Full,
+}
+
+#[derive(Clone, Copy)]
+pub struct CreditTable<const N: usize> {
+    entries: [Option<IpcCredits>; N],
+}
+
+impl<const N: usize> CreditTable<N> {
+    pub const fn new() -> Self {
+        Self { entries: [None; N] }
+    }
+
+    fn index_of(&self, sender: ProcessId, receiver: ProcessId) -> Option<usize> {
+        let mut idx = 0;
+        while idx < N {
+            if let Some(entry) = self.entries[idx] {
+                if entry.sender == sender && entry.receiver == receiver {
+                    return Some(idx);
+                }
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    pub fn get(&self, sender: ProcessId, receiver: ProcessId) -> Option<IpcCredits> {
+        self.index_of(sender, receiver)
+            .and_then(|idx| self.entries[idx])
+    }
+
+    pub fn get_mut(&mut self, sender: ProcessId, receiver: ProcessId) -> Option<&mut IpcCredits> {
+        let idx = self.index_of(sender, receiver)?;
+        self.entries[idx].as_mut()
+    }
+
+    /// Add `count` credits for the pair, enrolling it in flow control if it
+    /// was not already. Returns the new balance.
+    pub fn grant(
+        &mut self,
+        sender: ProcessId,
+        receiver: ProcessId,
+        count: u32,
+    ) -> Result<u32, CreditTableError> {
+        if let Some(entry) = self.get_mut(sender, receiver) {
+            entry.balance = entry.balance.saturating_add(count);
+            return Ok(entry.balance);
+        }
+        let mut idx = 0;
+        while idx < N {
+            if self.entries[idx].is_none() {
+                self.entries[idx] = Some(IpcCredits {
+                    sender,
+                    receiver,
+                    balance: count,
+                    auto_regrant: false,
+                });
+                return Ok(count);
+            }
+            idx += 1;
+        }
+        Err(CreditTableError::Full)
+    }
+
+    /// Drop every pair that `pid` takes part in.
+    pub fn remove_process(&mut self, pid: ProcessId) {
+        let mut idx = 0;
+        while idx < N {
+            if let Some(entry) = self.entries[idx] {
+                if entry.sender == pid || entry.receiver == pid {
+                    self.entries[idx] = None;
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+    }
+}
+
+impl<const N: usize> Default for CreditTable<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A send that found the receiver's queue full and will be re-attempted
+/// from the scheduler tick. The wait before each retry doubles, starting
+/// at `backoff_ticks`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetrySend {
+    pub receiver: ProcessId,
+    pub payload: MessagePayload,
+    pub max_attempts: u32,
+    /// Attempts made so far, including the first, immediate one.
+    pub attempts: u32,
+    pub backoff_ticks: u64,
+    /// Tick at or after which the next attempt is made.
+    pub next_attempt: u64,
+}
+
+impl RetrySend {
+    /// Record of a send whose first attempt at tick `now` found the queue full.
+    pub const fn after_first_attempt(
+        receiver: ProcessId,
+        payload: MessagePayload,
+        max_attempts: u32,
+        backoff_ticks: u64,
+        now: u64,
+    ) -> Self {
+        Self {
+            receiver,
+            payload,
+            max_attempts,
+            attempts: 1,
+            backoff_ticks,
+            next_attempt: now.saturating_add(backoff_ticks),
+        }
+    }
+
+    pub const fn is_due(&self, now: u64) -> bool {
+        now >= self.next_attempt
+    }
+
+    /// Count a failed attempt made at `now` and schedule the next one.
+    /// Returns `false` once the attempt budget is spent.
+    pub fn record_failure(&mut self, now: u64) -> bool {
+        self.attempts = self.attempts.saturating_add(1);
+        if self.attempts >= self.max_attempts {
+            return false;
+        }
+        let shift = (self.attempts - 1).min(63);
+        let delay = self.backoff_ticks.saturating_mul(1 << shift);
+        self.next_attempt = now.saturating_add(delay);
+        true
+    }
+}
+
+pub const MAX_ENDPOINT_NAME_LEN: usize = 32;
+
+/// Name a process registered so clients can reach it without its PID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpcEndpoint {
+    name: [u8; MAX_ENDPOINT_NAME_LEN],
+    name_len: usize,
+    pub owner: ProcessId,
+}
+
+impl IpcEndpoint {
+    pub fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndpointTableError {
+    Full,
+    /// Empty, or longer than [`MAX_ENDPOINT_NAME_LEN`] bytes.
+    InvalidName,
+    /// Another registration already holds the name.
+    NameTaken,
+}
+
+#[derive(Clone, Copy)]
+pub struct EndpointTable<const N: usize> {
+    entries: [Option<IpcEndpoint>; N],
+}
+
+impl<const N: usize> EndpointTable<N> {
+    pub const fn new() -> Self {
+        Self { entries: [None; N] }
+    }
+
+    pub fn register(&mut self, name: &str, owner: ProcessId) -> Result<(), EndpointTableError> {
+        if name.is_empty() || name.len() > MAX_ENDPOINT_NAME_LEN {
+            return Err(EndpointTableError::InvalidName);
+        }
+        if self.resolve(name).is_some() {
+            return Err(EndpointTableError::NameTaken);
+        }
+        let slot = self
+            .entries
+            .iter()
+            .position(Option::is_none)
+            .ok_or(EndpointTableError::Full)?;
+        let mut bytes = [0u8; MAX_ENDPOINT_NAME_LEN];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        self.entries[slot] = Some(IpcEndpoint {
+            name: bytes,
+            name_len: name.len(),
+            owner,
+        });
+        Ok(())
+    }
+
+    pub fn resolve(&self, name: &str) -> Option<ProcessId> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|entry| entry.name() == name)
+            .map(|entry| entry.owner)
+    }
+
+    /// Drop every name `pid` registered.
+    pub fn remove_process(&mut self, pid: ProcessId) {
+        let mut idx = 0;
+        while idx < N {
+            if let Some(entry) = self.entries[idx] {
+                if entry.owner == pid {
+                    self.entries[idx] = None;
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+    }
+}
+
+impl<const N: usize> Default for EndpointTable<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SpinStats {
+    /// Waits a message ended while the receiver was still spinning.
+    pub hits: u64,
+    /// Spins that ran out and fell back to blocking.
+    pub misses: u64,
+}
+
+/// Spin-before-block state for one receiver waiting on its message queue.
+///
+/// While spinning the receiver stays runnable and is re-checked once per
+/// scheduler tick; only when the window runs out does it block. The window
+/// follows a running average of how long recent waits took: twice that
+/// average, capped at the configured maximum, and zero once waits routinely
+/// outlast the maximum, since spinning would then only delay the block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AdaptiveSpin {
+    max_cycles: u32,
+    window: u32,
+    /// Running average wait in ticks, weighted 3:1 toward history.
+    average_wait: u64,
+    waiting_since: Option<u64>,
+    remaining: u32,
+    stats: SpinStats,
+}
+
+impl AdaptiveSpin {
+    pub const fn new() -> Self {
+        Self {
+            max_cycles: 0,
+            window: 0,
+            average_wait: 0,
+            waiting_since: None,
+            remaining: 0,
+            stats: SpinStats { hits: 0, misses: 0 },
+        }
+    }
+
+    /// Allow spins of up to `max_cycles` ticks; zero turns spinning off.
+    /// Tuning restarts from the full window.
+    pub fn configure(&mut self, max_cycles: u32) {
+        *self = Self {
+            max_cycles,
+            window: max_cycles,
+            stats: self.stats,
+            ..Self::new()
+        };
+    }
+
+    pub const fn max_cycles(&self) -> u32 {
+        self.max_cycles
+    }
+
+    /// Ticks the next wait will spin for.
+    pub const fn window(&self) -> u32 {
+        self.window
+    }
+
+    pub const fn stats(&self) -> SpinStats {
+        self.stats
+    }
+
+    pub const fn is_spinning(&self) -> bool {
+        self.remaining != 0
+    }
+
+    /// Start waiting at tick `now`. Returns whether to spin rather than
+    /// block straight away.
+    pub fn begin(&mut self, now: u64) -> bool {
+        if self.max_cycles == 0 {
+            return false;
+        }
+        self.waiting_since = Some(now);
+        self.remaining = self.window;
+        self.remaining != 0
+    }
+
+    /// Spend one spin cycle. Returns true when the window has just run out
+    /// and the receiver should block.
+    pub fn spin_cycle(&mut self) -> bool {
+        if self.remaining == 0 {
+            return false;
+        }
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.stats.misses = self.stats.misses.saturating_add(1);
+            return true;
+        }
+        false
+    }
+
+    /// A message arrived at tick `now`; end the wait and retune the window.
+    pub fn finish(&mut self, now: u64) {
+        let Some(since) = self.waiting_since.take() else {
+            return;
+        };
+        if self.remaining != 0 {
+            self.stats.hits = self.stats.hits.saturating_add(1);
+            self.remaining = 0;
+        }
+        let wait = now.saturating_sub(since);
+        self.average_wait = (self.average_wait * 3 + wait).div_ceil(4);
+        let max = u64::from(self.max_cycles);
+        self.window = if self.average_wait > max {
+            0
+        } else {
+            (self.average_wait * 2).clamp(1, max) as u32
+        };
+    }
+}
+
+/// Address of the installed `fn(&Message) -> bool` filter, or 0 when none is
+/// set. Stored as an integer so the hook can be swapped without a lock.
+static IPC_HOOK: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_message_filter_hook(hook: fn(&Message) -> bool) {
+    IPC_HOOK.store(hook as usize as u64, Ordering::Release);
+}
+
+pub fn clear_message_filter_hook() {
+    IPC_HOOK.store(0, Ordering::Release);
+}
+
+/// Run the installed filter against a fully built message. Messages pass when
+/// no hook is installed.
+pub fn message_filter_allows(message: &Message) -> bool {
+    let raw = IPC_HOOK.load(Ordering::Acquire);
+    if raw == 0 {
+        return true;
+    }
+    // SAFETY: non-zero values are only ever stored by `set_message_filter_hook`
+    // from a valid `fn(&Message) -> bool`.
+    let hook = unsafe { core::mem::transmute::<usize, fn(&Message) -> bool>(raw as usize) };
+    hook(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Message {
+        Message::new(
+            ProcessId::new(7),
+            ProcessId::new(0x1122_3344_5566_7788),
+            42,
+            MessagePayload::from_slice(SecurityClass::Confidential, b"wire"),
+        )
+    }
+
+    fn encoded() -> [u8; MESSAGE_WIRE_SIZE] {
+        let mut bytes = [0u8; MESSAGE_WIRE_SIZE];
+        assert_eq!(sample().encode(&mut bytes), Ok(MESSAGE_WIRE_SIZE));
+        bytes
+    }
+
+    #[test]
+    fn queue_orders_by_priority_and_rolls_back_inserted_message() {
+        let mut queue: MessageQueue<4> = MessageQueue::new();
+        let message = |sequence, priority| {
+            let payload = MessagePayload::empty(SecurityClass::Public).with_priority(priority);
+            Message::new(ProcessId::new(1), ProcessId::new(2), sequence, payload)
+        };
+        queue.push(message(0, MessagePriority::Bulk)).unwrap();
+        queue.push(message(1, MessagePriority::Normal)).unwrap();
+        queue.push(message(2, MessagePriority::Bulk)).unwrap();
+        queue.push(message(3, MessagePriority::Urgent)).unwrap();
+
+        assert_eq!(queue.rollback_last_push().map(|m| m.sequence), Some(3));
+        assert_eq!(queue.rollback_last_push(), None);
+        queue.push(message(4, MessagePriority::Normal)).unwrap();
+
+        let mut order = [0u64; 4];
+        let mut idx = 0;
+        while let Some(next) = queue.pop().unwrap() {
+            order[idx] = next.sequence;
+            idx += 1;
+        }
+        assert_eq!(order, [1, 4, 0, 2]);
+    }
+
+    fn sequenced(sequence: u64) -> Message {
+        Message::new(
+            ProcessId::new(1),
+            ProcessId::new(2),
+            sequence,
+            MessagePayload::empty(SecurityClass::Public),
+        )
+    }
+
+    fn sequences<const N: usize>(queue: &mut MessageQueue<N>) -> [Option<u64>; N] {
+        let mut order = [None; N];
+        let mut idx = 0;
+        while let Some(next) = queue.pop().unwrap() {
+            order[idx] = Some(next.sequence);
+            idx += 1;
+        }
+        order
+    }
+
+    #[test]
+    fn full_queue_rejects_new_messages_by_default() {
+        let mut queue: MessageQueue<3> = MessageQueue::new();
+        assert_eq!(queue.overflow_policy(), OverflowPolicy::RejectNew);
+        for sequence in 0..3 {
+            assert_eq!(queue.push(sequenced(sequence)), Ok(None));
+        }
+
+        assert_eq!(queue.push(sequenced(3)), Err(MessageQueueError::Full));
+        assert_eq!(sequences(&mut queue), [Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn drop_oldest_evicts_head_to_make_room() {
+        let mut queue: MessageQueue<3> = MessageQueue::new();
+        queue.set_overflow_policy(OverflowPolicy::DropOldest);
+        for sequence in 0..3 {
+            assert_eq!(queue.push(sequenced(sequence)), Ok(None));
+        }
+
+        let evicted = queue.push(sequenced(3)).unwrap();
+        assert_eq!(evicted.map(|m| m.sequence), Some(0));
+        let evicted = queue.push(sequenced(4)).unwrap();
+        assert_eq!(evicted.map(|m| m.sequence), Some(1));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(sequences(&mut queue), [Some(2), Some(3), Some(4)]);
+
+        queue.clear();
+        assert_eq!(queue.overflow_policy(), OverflowPolicy::RejectNew);
+    }
+
+    #[test]
+    fn drop_oldest_counts_the_evicted_head_against_class_quota() {
+        let mut queue: MessageQueue<2> = MessageQueue::new();
+        queue.set_overflow_policy(OverflowPolicy::DropOldest);
+        queue.set_class_quota(SecurityClass::Public, Some(1));
+        queue.push(sequenced(0)).unwrap();
+        queue
+            .push(Message::new(
+                ProcessId::new(1),
+                ProcessId::new(2),
+                1,
+                MessagePayload::empty(SecurityClass::Internal),
+            ))
+            .unwrap();
+
+        // Evicting the public head frees the class's only slot.
+        assert_eq!(
+            queue.push(sequenced(2)).unwrap().map(|m| m.sequence),
+            Some(0)
+        );
+        // Now the head is internal, so the public quota still blocks.
+        assert_eq!(
+            queue.push(sequenced(3)),
+            Err(MessageQueueError::ClassQuotaExceeded)
+        );
+        assert_eq!(sequences(&mut queue), [Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn stats_count_pushes_pops_and_overflow_drops() {
+        let mut queue: MessageQueue<2> = MessageQueue::new();
+        queue.push(sequenced(0)).unwrap();
+        queue.push(sequenced(1)).unwrap();
+        assert_eq!(queue.push(sequenced(2)), Err(MessageQueueError::Full));
+        queue.pop().unwrap();
+
+        queue.set_overflow_policy(OverflowPolicy::DropOldest);
+        queue.push(sequenced(3)).unwrap();
+        queue.push(sequenced(4)).unwrap();
+        assert_eq!(queue.rollback_last_push().map(|m| m.sequence), Some(4));
+        while queue.pop().unwrap().is_some() {}
+
+        assert_eq!(
+            queue.stats(),
+            QueueStats {
+                enqueued: 3,
+                dequeued: 2,
+                dropped: 2,
+            }
+        );
+        queue.clear();
+        assert_eq!(queue.stats(), QueueStats::default());
+    }
+
+    #[test]
+    fn checksummed_queue_round_trips_messages_unchanged() {
+        let mut queue: MessageQueue<4> = MessageQueue::new();
+        queue.set_checksums(true);
+        let mut forwarded = sequenced(1);
+        forwarded.original_sender = Some(ProcessId::new(9));
+        queue.push(sample()).unwrap();
+        queue.push(forwarded).unwrap();
+
+        let popped = queue.pop().unwrap().unwrap();
+        assert_eq!(popped, sample());
+        let mut bytes = [0u8; MESSAGE_WIRE_SIZE];
+        popped.encode(&mut bytes).unwrap();
+        assert_eq!(bytes, encoded());
+        assert_eq!(queue.pop(), Ok(Some(forwarded)));
+        assert_eq!(queue.pop(), Ok(None));
+
+        queue.clear();
+        assert!(queue.checksums());
+    }
+
+    #[test]
+    fn checksummed_queue_detects_in_place_corruption() {
+        let mut queue: MessageQueue<4> = MessageQueue::new();
+        queue.set_checksums(true);
+        for sequence in 0..3 {
+            queue.push(sequenced(sequence)).unwrap();
+        }
+        queue.message_at_mut(1).unwrap().payload.data[17] ^= 0x40;
+
+        assert_eq!(queue.pop().unwrap().map(|m| m.sequence), Some(0));
+        assert_eq!(
+            queue.pop(),
+            Err(MessageQueueError::Corrupted { sequence: 1 })
+        );
+        // The corrupted message is gone; delivery carries on behind it.
+        assert_eq!(queue.pop().unwrap().map(|m| m.sequence), Some(2));
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn unchecksummed_queue_delivers_messages_as_stored() {
+        let mut queue: MessageQueue<2> = MessageQueue::new();
+        assert!(!queue.checksums());
+        queue.push(sample()).unwrap();
+        queue.message_at_mut(0).unwrap().payload.data[0] = b'W';
+
+        let mut expected = sample();
+        expected.payload.data[0] = b'W';
+        assert_eq!(queue.pop(), Ok(Some(expected)));
+    }
+
+    #[test]
+    fn class_quota_caps_one_class_and_tracks_occupancy_across_pops() {
+        let mut queue: MessageQueue<4> = MessageQueue::new();
+        let message = |class| {
+            Message::new(
+                ProcessId::new(1),
+                ProcessId::new(2),
+                0,
+                MessagePayload::empty(class),
+            )
+        };
+        queue.set_class_quota(SecurityClass::Confidential, Some(2));
+
+        queue.push(message(SecurityClass::Confidential)).unwrap();
+        queue.push(message(SecurityClass::Confidential)).unwrap();
+        assert_eq!(
+            queue.push(message(SecurityClass::Confidential)),
+            Err(MessageQueueError::ClassQuotaExceeded)
+        );
+        queue.push(message(SecurityClass::Public)).unwrap();
+        assert_eq!(
+            queue.class_occupancy(SecurityClass::Confidential),
+            ClassOccupancy {
+                queued: 2,
+                quota: Some(2),
+            }
+        );
+
+        queue.pop().unwrap();
+        queue.push(message(SecurityClass::Confidential)).unwrap();
+        queue.push(message(SecurityClass::Public)).unwrap();
+        assert_eq!(
+            queue.push(message(SecurityClass::Public)),
+            Err(MessageQueueError::Full)
+        );
+
+        queue.clear();
+        assert_eq!(
+            queue.class_occupancy(SecurityClass::Confidential),
+            ClassOccupancy {
+                queued: 0,
+                quota: None,
+            }
+        );
+    }
+
+    #[test]
+    fn message_wire_format_round_trips() {
+        let bytes = encoded();
+        assert_eq!(&bytes[0..4], b"MIPC");
+        assert_eq!(bytes[6], 2);
+        assert_eq!(bytes[7], 4);
+        assert_eq!(&bytes[16..24], &0x1122_3344_5566_7788u64.to_le_bytes());
+        assert_eq!(Message::decode(&bytes), Ok(sample()));
+
+        let mut full = sample();
+        full.payload = MessagePayload::from_slice(SecurityClass::System, &[0xab; 64]);
+        let mut bytes = [0u8; MESSAGE_WIRE_SIZE + 8];
+        assert_eq!(full.encode(&mut bytes), Ok(MESSAGE_WIRE_SIZE));
+        assert_eq!(Message::decode(&bytes[..MESSAGE_WIRE_SIZE]), Ok(full));
+    }
+
+    #[test]
+    fn message_wire_format_rejects_malformed_fields() {
+        let mut short = [0u8; MESSAGE_WIRE_SIZE - 1];
+        assert_eq!(sample().encode(&mut short), Err(CodecError::BufferSize));
+
+        let good = encoded();
+        assert_eq!(
+            Message::decode(&good[..MESSAGE_WIRE_SIZE - 1]),
+            Err(CodecError::BufferSize)
+        );
+        let mut long = [0u8; MESSAGE_WIRE_SIZE + 1];
+        long[..MESSAGE_WIRE_SIZE].copy_from_slice(&good);
+        assert_eq!(Message::decode(&long), Err(CodecError::BufferSize));
+
+        let mut bytes = good;
+        bytes[0] = b'X';
+        assert_eq!(Message::decode(&bytes), Err(CodecError::BadMagic));
+
+        let mut bytes = good;
+        bytes[4] = 2;
+        assert_eq!(Message::decode(&bytes), Err(CodecError::UnsupportedVersion));
+
+        let mut bytes = good;
+        bytes[6] = 4;
+        assert_eq!(Message::decode(&bytes), Err(CodecError::UnknownClass));
+
+        let mut bytes = good;
+        bytes[7] = 65;
+        assert_eq!(Message::decode(&bytes), Err(CodecError::LengthTooLarge));
+    }
+
+    #[test]
+    fn spin_window_follows_recent_wait_times() {
+        let mut spin = AdaptiveSpin::new();
+        assert!(!spin.begin(0));
+        spin.configure(4);
+        assert_eq!(spin.window(), 4);
+
+        assert!(spin.begin(0));
+        spin.finish(1);
+        assert_eq!(spin.window(), 2);
+        assert!(!spin.is_spinning());
+
+        assert!(spin.begin(10));
+        assert!(!spin.spin_cycle());
+        assert!(spin.spin_cycle());
+        spin.finish(30);
+        // Waits now average past the maximum, so spinning stops paying off.
+        assert_eq!(spin.window(), 0);
+        assert!(!spin.begin(40));
+        spin.finish(41);
+        assert_eq!(spin.window(), 0);
+        assert!(!spin.begin(50));
+        spin.finish(51);
+        assert_eq!(spin.window(), 4);
+        assert_eq!(spin.stats(), SpinStats { hits: 1, misses: 1 });
+    }
 }
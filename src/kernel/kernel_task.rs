@@ -0,0 +1,399 @@
+//! Kernel tasks: maintenance work run as ordinary scheduled threads.
+//!
+//! A kernel task is a function pointer and a state word. It runs in-kernel
+//! each time MTSS dispatches its thread, so it is subject to the same
+//! priorities and time slices as user threads, and says what happens next
+//! through [`KernelTaskStep`]. Every kernel task thread belongs to one
+//! reserved process with system credentials, created on first use.
+//!
+//! Tasks only see a [`KernelTaskCtx`], never the kernel itself, so a task
+//! cannot re-enter the scheduler that is running it.
+
+use crate::kernel::collections::FixedString;
+use crate::kernel::device::DeviceManager;
+use crate::kernel::memory::{self, FullMemoryStats};
+use crate::kernel::process::{ProcessId, ProcessPriority, ProcessState};
+use crate::kernel::thread::{ThreadId, ThreadState};
+use crate::kernel::time::{MonotonicTimestamp, KERNEL_TIME};
+use crate::kernel::{
+    Kernel, KernelError, KernelResult, KernelThreadScheduleRecord, ResourceSnapshot, MAX_DEVICES,
+};
+use crate::subkernel::Credentials;
+
+pub const MAX_KERNEL_TASKS: usize = 8;
+pub const KERNEL_TASK_NAME_BYTES: usize = 16;
+
+/// What a kernel task wants after a slice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KernelTaskStep {
+    /// Stay runnable and go to the back of the run queue.
+    Continue,
+    /// Block for this many scheduler ticks. `Sleep(0)` acts as `Continue`.
+    Sleep(u64),
+    /// Finish; the task's thread is removed.
+    Exit,
+}
+
+pub type KernelTaskEntry = fn(&mut KernelTaskCtx<'_>, &mut u64) -> KernelTaskStep;
+
+/// Entry point and state word of a kernel task. The state word is handed to
+/// every call and kept between slices.
+#[derive(Clone, Copy, Debug)]
+pub struct KernelTaskFn {
+    pub entry: KernelTaskEntry,
+    pub state: u64,
+}
+
+impl KernelTaskFn {
+    pub const fn new(entry: KernelTaskEntry, state: u64) -> Self {
+        Self { entry, state }
+    }
+}
+
+/// What a kernel task may touch while it runs.
+pub struct KernelTaskCtx<'a> {
+    thread: ThreadId,
+    core: usize,
+    resources: ResourceSnapshot,
+    devices: &'a mut DeviceManager<MAX_DEVICES>,
+}
+
+impl KernelTaskCtx<'_> {
+    pub const fn thread(&self) -> ThreadId {
+        self.thread
+    }
+
+    pub const fn core(&self) -> usize {
+        self.core
+    }
+
+    /// Kernel resource figures taken just before this slice.
+    pub const fn resources(&self) -> &ResourceSnapshot {
+        &self.resources
+    }
+
+    pub fn devices(&mut self) -> &mut DeviceManager<MAX_DEVICES> {
+        self.devices
+    }
+
+    pub fn memory_stats(&self) -> FullMemoryStats {
+        memory::full_stats()
+    }
+
+    /// Scrub up to `budget` bytes of freed heap; returns the bytes scrubbed.
+    pub fn scrub(&mut self, budget: usize) -> usize {
+        memory::scrub_step(budget)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct KernelTaskSlot {
+    thread: ThreadId,
+    name: FixedString<KERNEL_TASK_NAME_BYTES>,
+    task: KernelTaskFn,
+}
+
+impl<
+        const NPROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > Kernel<NPROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
+    /// Start `task` as a thread of the kernel task process, creating that
+    /// process on first use. Names longer than [`KERNEL_TASK_NAME_BYTES`]
+    /// are truncated.
+    pub fn spawn_kernel_task(
+        &mut self,
+        name: &str,
+        priority: ProcessPriority,
+        task: KernelTaskFn,
+    ) -> KernelResult<ThreadId> {
+        let slot = self
+            .kernel_tasks
+            .iter()
+            .position(Option::is_none)
+            .ok_or(KernelError::ThreadTableFull)?;
+        let thread = match self
+            .kernel_task_pid
+            .filter(|pid| self.locate_process(*pid).is_ok())
+        {
+            Some(pid) => self.spawn_thread(pid, 0, priority)?,
+            None => {
+                let pid = self.create_kernel_task_process(priority)?;
+                self.first_thread_for_process(pid)
+                    .ok_or(KernelError::UnknownThread)?
+            }
+        };
+        self.kernel_tasks[slot] = Some(KernelTaskSlot {
+            thread,
+            name: FixedString::from_str_truncated(name),
+            task,
+        });
+        Ok(thread)
+    }
+
+    /// The reserved process kernel tasks run in, once one has been spawned.
+    pub fn kernel_task_process(&self) -> Option<ProcessId> {
+        self.kernel_task_pid
+    }
+
+    pub fn is_kernel_task(&self, thread: ThreadId) -> bool {
+        self.kernel_task_slot(thread).is_some()
+    }
+
+    pub fn kernel_task_name(&self, thread: ThreadId) -> Option<&str> {
+        let slot = self.kernel_task_slot(thread)?;
+        self.kernel_tasks[slot]
+            .as_ref()
+            .map(|task| task.name.as_str())
+    }
+
+    /// The kernel task process does not take PID 1, which stays reserved for
+    /// the first user process.
+    fn create_kernel_task_process(&mut self, priority: ProcessPriority) -> KernelResult<ProcessId> {
+        let init_pending = !self.init_pid_issued;
+        self.init_pid_issued = true;
+        let created = self.create_process_task(0, priority, None, Credentials::system(), None);
+        if init_pending {
+            self.init_pid_issued = false;
+        }
+        let pid = created?;
+        let index = self.locate_process(pid)?;
+        if let Some(pcb) = self.process_table[index].as_mut() {
+            pcb.kernel_tasks = true;
+        }
+        self.kernel_task_pid = Some(pid);
+        Ok(pid)
+    }
+
+    pub(super) fn kernel_task_slot(&self, thread: ThreadId) -> Option<usize> {
+        self.kernel_tasks
+            .iter()
+            .position(|task| task.is_some_and(|task| task.thread == thread))
+    }
+
+    pub(super) fn release_kernel_tasks(&mut self) {
+        self.kernel_tasks = [None; MAX_KERNEL_TASKS];
+        self.kernel_task_pid = None;
+    }
+
+    /// Run one slice of the kernel task in `slot`, dispatched on `core_index`
+    /// by `scheduled`.
+    pub(super) fn run_kernel_task(
+        &mut self,
+        core_index: usize,
+        slot: usize,
+        scheduled: KernelThreadScheduleRecord,
+        thread_index: usize,
+        process_index: usize,
+    ) {
+        let Some(mut task) = self.kernel_tasks[slot] else {
+            return;
+        };
+        self.core_states[core_index].start_thread(scheduled.thread);
+        self.record_dispatch_wait(core_index, thread_index);
+        if let Some(tcb) = self.thread_table.entry_mut(thread_index) {
+            tcb.mark_running();
+            tcb.accumulate_cpu_time(1);
+        }
+        let _ = self.set_process_running_via_mtss(scheduled.process, process_index);
+        if let Some(pcb) = self.process_table[process_index].as_mut() {
+            pcb.cpu_time = pcb.cpu_time.saturating_add(1);
+        }
+
+        let resources = self.resource_accounting_snapshot();
+        let mut ctx = KernelTaskCtx {
+            thread: scheduled.thread,
+            core: core_index,
+            resources,
+            devices: &mut self.devices,
+        };
+        let step = (task.task.entry)(&mut ctx, &mut task.task.state);
+        self.kernel_tasks[slot] = Some(task);
+
+        let step = match step {
+            KernelTaskStep::Sleep(ticks) if ticks > 0 => {
+                let now = KERNEL_TIME.now();
+                let wake_deadline = now
+                    .as_nanos()
+                    .saturating_add(MonotonicTimestamp::new(ticks, now.frequency()).as_nanos());
+                match self.timers.add_sleep(
+                    scheduled.process,
+                    Some(scheduled.thread),
+                    wake_deadline,
+                ) {
+                    Ok(()) => KernelTaskStep::Sleep(ticks),
+                    Err(_) => KernelTaskStep::Continue,
+                }
+            }
+            KernelTaskStep::Sleep(_) => KernelTaskStep::Continue,
+            step => step,
+        };
+        match step {
+            KernelTaskStep::Exit => {
+                self.kernel_tasks[slot] = None;
+                self.terminate_thread(scheduled.thread);
+            }
+            KernelTaskStep::Sleep(_) => {
+                let _ = self.block_thread(scheduled.thread);
+            }
+            KernelTaskStep::Continue => {
+                if let Some(tcb) = self.thread_table.entry_mut(thread_index) {
+                    if tcb.state == ThreadState::Running {
+                        tcb.mark_ready_at(self.mtss_ticks);
+                    }
+                }
+            }
+        }
+
+        let runnable = self.has_runnable_thread(scheduled.process);
+        if let Some(pcb) = self.process_table[process_index].as_ref() {
+            if pcb.state == ProcessState::Running {
+                if runnable {
+                    let _ = self.set_process_ready_via_mtss(scheduled.process, process_index);
+                } else {
+                    let _ = self.set_process_blocked_via_mtss(scheduled.process, process_index);
+                }
+            }
+        }
+        self.core_states[core_index].finish_cycle();
+
+        if step == KernelTaskStep::Continue {
+            match self.kernel_yield_current(scheduled) {
+                Ok(Some(next)) => self.pending_mtss_decision = Some(next),
+                Ok(None) => {}
+                Err(_) => self.core_states[core_index].idle_cycle(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counting_task(ctx: &mut KernelTaskCtx<'_>, slices: &mut u64) -> KernelTaskStep {
+        ctx.scrub(64);
+        *slices += 1;
+        KernelTaskStep::Continue
+    }
+
+    fn napping_task(_ctx: &mut KernelTaskCtx<'_>, slices: &mut u64) -> KernelTaskStep {
+        *slices += 1;
+        KernelTaskStep::Sleep(3)
+    }
+
+    fn one_shot_task(_ctx: &mut KernelTaskCtx<'_>, slices: &mut u64) -> KernelTaskStep {
+        *slices += 1;
+        KernelTaskStep::Exit
+    }
+
+    fn booted() -> Kernel<16, 4> {
+        let mut kernel = Kernel::<16, 4>::new();
+        kernel.bootstrap();
+        kernel.kernel_mtss_init().unwrap();
+        kernel
+    }
+
+    fn slices(kernel: &Kernel<16, 4>, thread: ThreadId) -> u64 {
+        let slot = kernel.kernel_task_slot(thread).unwrap();
+        kernel.kernel_tasks[slot].unwrap().task.state
+    }
+
+    fn thread_state(kernel: &Kernel<16, 4>, thread: ThreadId) -> ThreadState {
+        let index = kernel.locate_thread(thread).unwrap();
+        kernel.thread_table[index].unwrap().state
+    }
+
+    #[test]
+    fn kernel_task_shares_the_cpu_with_a_user_thread() {
+        let mut kernel = booted();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let index = kernel.locate_process(init).unwrap();
+        kernel.process_table[index]
+            .as_mut()
+            .unwrap()
+            .address_space_root = 0x4b54_0000_0000;
+        let scrubber = kernel
+            .spawn_kernel_task(
+                "scrubber",
+                ProcessPriority::Critical,
+                KernelTaskFn::new(counting_task, 0),
+            )
+            .unwrap();
+
+        assert_eq!(init, ProcessId::new(1));
+        let host = kernel.kernel_task_process().unwrap();
+        assert_ne!(host, init);
+        let host_index = kernel.locate_process(host).unwrap();
+        assert!(kernel.process_table[host_index].unwrap().kernel_tasks);
+        assert!(!kernel.process_table[index].unwrap().kernel_tasks);
+        assert_eq!(kernel.kernel_task_name(scrubber), Some("scrubber"));
+
+        kernel.run_ticks(40);
+        let task_slices = slices(&kernel, scrubber);
+        let user_slices = kernel.process_table[index].unwrap().cpu_time as u64;
+        assert!(task_slices >= 10, "kernel task ran {task_slices} slices");
+        assert!(user_slices >= 10, "user thread ran {user_slices} slices");
+        assert!(task_slices.abs_diff(user_slices) <= 2);
+    }
+
+    #[test]
+    fn sleeping_kernel_task_waits_on_the_timer_wheel() {
+        let mut kernel = booted();
+        let napper = kernel
+            .spawn_kernel_task(
+                "napper",
+                ProcessPriority::Normal,
+                KernelTaskFn::new(napping_task, 0),
+            )
+            .unwrap();
+
+        kernel.tick();
+        assert_eq!(slices(&kernel, napper), 1);
+        assert_eq!(thread_state(&kernel, napper), ThreadState::Blocked);
+        let host = kernel.kernel_task_process().unwrap();
+        assert_eq!(kernel.timers.expired_for(host, u128::MAX), 1);
+
+        kernel.run_ticks(8);
+        assert!(slices(&kernel, napper) >= 2);
+    }
+
+    #[test]
+    fn exiting_kernel_task_removes_its_thread() {
+        let mut kernel = booted();
+        let first = kernel
+            .spawn_kernel_task(
+                "one-shot",
+                ProcessPriority::Normal,
+                KernelTaskFn::new(one_shot_task, 0),
+            )
+            .unwrap();
+        let second = kernel
+            .spawn_kernel_task(
+                "one-shot",
+                ProcessPriority::Normal,
+                KernelTaskFn::new(one_shot_task, 0),
+            )
+            .unwrap();
+        let host = kernel.kernel_task_process().unwrap();
+        let host_index = kernel.locate_process(host).unwrap();
+        assert_eq!(kernel.process_table[host_index].unwrap().thread_count, 2);
+
+        kernel.run_ticks(4);
+
+        assert!(!kernel.is_kernel_task(first));
+        assert!(!kernel.is_kernel_task(second));
+        assert!(matches!(
+            kernel.locate_thread(first),
+            Err(KernelError::UnknownThread)
+        ));
+        assert!(kernel.locate_thread(second).is_err());
+        assert_eq!(kernel.process_table[host_index].unwrap().thread_count, 0);
+        assert_eq!(
+            kernel.process_table[host_index].unwrap().state,
+            ProcessState::Blocked
+        );
+    }
+}
@@ -13,6 +13,7 @@ use core::{
 use core::alloc::{GlobalAlloc, Layout};
 
 use crate::arch::x86_64::{boot::BootInfo, paging};
+use crate::kernel::collections::SlotBitmap;
 use crate::kernel::process::ProcessId;
 use crate::kernel::sync::SpinLock;
 
@@ -23,6 +24,7 @@ pub const MAX_ALLOCATION_RECORDS: usize = 512;
 pub const MAX_PHYSICAL_REGIONS: usize = 128;
 pub const MAX_ADDRESS_SPACES: usize = 64;
 pub const MAX_USER_MAPPINGS: usize = 2048;
+pub const SCRATCH_ARENA_BYTES: usize = 2048;
 pub const EARLY_HEAP_BASE: usize = 0xffff_9000_0000_0000;
 pub const KERNEL_PROCESS_ID: ProcessId = ProcessId::new(0);
 
@@ -30,6 +32,10 @@ pub const PROT_READ: u32 = 0x1;
 pub const PROT_WRITE: u32 = 0x2;
 pub const PROT_EXECUTE: u32 = 0x4;
 
+pub const MAP_FIXED: u32 = 0x10;
+pub const MAP_ANONYMOUS: u32 = 0x20;
+pub const MAP_POPULATE: u32 = 0x8000;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AllocationKind {
     Heap,
@@ -79,6 +85,53 @@ impl MemoryProtection {
     }
 }
 
+/// Heap-free mapping flags understood by [`MemoryManager::mmap_with_flags`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MmapFlags(u32);
+
+impl MmapFlags {
+    pub const EMPTY: Self = Self(0);
+    pub const ANONYMOUS: Self = Self(1);
+    pub const POPULATE: Self = Self(2);
+    pub const FIXED: Self = Self(4);
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Translates Linux `MAP_*` bits from the C ABI into Mirage mapping flags.
+    /// Unknown bits are ignored.
+    pub const fn from_linux_bits(bits: u32) -> Self {
+        let mut flags = Self::EMPTY;
+        if (bits & MAP_ANONYMOUS) != 0 {
+            flags = flags.union(Self::ANONYMOUS);
+        }
+        if (bits & MAP_POPULATE) != 0 {
+            flags = flags.union(Self::POPULATE);
+        }
+        if (bits & MAP_FIXED) != 0 {
+            flags = flags.union(Self::FIXED);
+        }
+        flags
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn contains(self, flag: Self) -> bool {
+        (self.0 & flag.0) == flag.0
+    }
+
+    pub const fn union(self, flag: Self) -> Self {
+        Self(self.0 | flag.0)
+    }
+
+    pub const fn without(self, flag: Self) -> Self {
+        Self(self.0 & !flag.0)
+    }
+}
+
 pub mod frame_allocator;
 pub mod heap;
 
@@ -128,6 +181,8 @@ struct AllocationRecord {
     size: usize,
     kind: AllocationKind,
     protection: MemoryProtection,
+    /// log2 of the alignment the block was reserved with.
+    align: u16,
 }
 
 impl AllocationRecord {
@@ -137,6 +192,7 @@ impl AllocationRecord {
         size: usize,
         kind: AllocationKind,
         protection: MemoryProtection,
+        align: usize,
     ) -> Self {
         Self {
             owner,
@@ -144,8 +200,23 @@ impl AllocationRecord {
             size,
             kind,
             protection,
+            align: align.trailing_zeros() as u16,
         }
     }
+
+    const fn alignment(&self) -> usize {
+        1 << self.align
+    }
+}
+
+/// What the allocator recorded for a live allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocationInfo {
+    pub owner: ProcessId,
+    pub size: usize,
+    pub align: usize,
+    pub kind: AllocationKind,
+    pub protection: MemoryProtection,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -168,6 +239,10 @@ impl FreeRegion {
 pub struct AllocationStats {
     pub allocated_bytes: usize,
     pub peak_allocated_bytes: usize,
+    /// Requests the heap had no room for.
+    pub failed_allocations: u64,
+    /// Total size of those requests, after rounding up to their alignment.
+    pub failed_bytes: u64,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -178,26 +253,254 @@ pub struct HeapStats {
     pub reserved_bytes: usize,
 }
 
+/// Every heap figure captured under a single lock acquisition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FullMemoryStats {
+    pub allocated_bytes: usize,
+    pub peak_allocated_bytes: usize,
+    pub failed_allocations: u64,
+    pub failed_bytes: u64,
+    /// Bytes still available: the untouched bump tail plus all free regions.
+    pub free_bytes: usize,
+    pub largest_free_block: usize,
+    pub free_region_count: usize,
+    pub heap: HeapStats,
+}
+
+impl FullMemoryStats {
+    /// Share of free bytes unusable for an allocation of `free_bytes`, in
+    /// percent. Zero when all free space is contiguous.
+    pub const fn fragmentation_percent(&self) -> usize {
+        if self.free_bytes == 0 {
+            return 0;
+        }
+        100 - self.largest_free_block * 100 / self.free_bytes
+    }
+}
+
+/// Heap pressure watermarks, in allocated bytes. The handler fires when an
+/// allocation takes the heap to `high_water` or above and is re-armed only
+/// once usage falls below `low_water`, so hovering near the threshold does
+/// not fire it repeatedly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PressureThreshold {
+    pub high_water: usize,
+    pub low_water: usize,
+}
+
+impl PressureThreshold {
+    /// `low_water` is clamped to `high_water`.
+    pub const fn new(high_water: usize, low_water: usize) -> Self {
+        Self {
+            high_water,
+            low_water: if low_water < high_water {
+                low_water
+            } else {
+                high_water
+            },
+        }
+    }
+}
+
+/// When freed heap bytes are zeroed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrubPolicy {
+    /// Freed bytes are left as they are.
+    Off,
+    /// Freed bytes join a dirty list that idle cores scrub, at most
+    /// `bytes_per_idle` per idle pass, before moving them to the clean list.
+    Deferred { bytes_per_idle: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct ScrubStats {
+    /// Bytes queued for scrubbing since boot.
+    pub freed_bytes: usize,
+    /// Bytes zeroed by idle passes, flushes, or on-demand zeroing.
+    pub scrubbed_bytes: usize,
+    /// Freed bytes still waiting to be scrubbed.
+    pub backlog_bytes: usize,
+    /// Bytes zeroed by the most recent idle pass.
+    pub last_pass_bytes: usize,
+}
+
+/// Byte ranges tracked on top of the free list, e.g. freed-but-dirty bytes or
+/// scrubbed bytes known to read as zero. Ranges are merged with neighbours.
+#[derive(Clone, Copy)]
+struct RegionList<const N: usize> {
+    regions: [Option<FreeRegion>; N],
+}
+
+impl<const N: usize> RegionList<N> {
+    const fn new() -> Self {
+        Self { regions: [None; N] }
+    }
+
+    /// Track `region`. Hands it back if the list has no room for it.
+    fn insert(&mut self, region: FreeRegion) -> Option<FreeRegion> {
+        if region.size == 0 {
+            return None;
+        }
+        let mut merged = region;
+        let mut idx = 0;
+        while idx < N {
+            if let Some(existing) = self.regions[idx] {
+                if existing.end() == merged.offset {
+                    merged = FreeRegion::new(existing.offset, existing.size + merged.size);
+                    self.regions[idx] = None;
+                } else if merged.end() == existing.offset {
+                    merged = FreeRegion::new(merged.offset, merged.size + existing.size);
+                    self.regions[idx] = None;
+                }
+            }
+            idx += 1;
+        }
+        self.place(merged)
+    }
+
+    fn place(&mut self, region: FreeRegion) -> Option<FreeRegion> {
+        let mut idx = 0;
+        while idx < N {
+            if self.regions[idx].is_none() {
+                self.regions[idx] = Some(region);
+                return None;
+            }
+            idx += 1;
+        }
+        Some(region)
+    }
+
+    /// Stop tracking `[offset, offset + size)`. Returns how many of those
+    /// bytes were tracked, plus a leftover piece the list had no room to keep
+    /// after splitting a range.
+    fn remove_range(&mut self, offset: usize, size: usize) -> (usize, Option<FreeRegion>) {
+        let end = offset + size;
+        let mut overlap = 0;
+        let mut lost = None;
+        let mut idx = 0;
+        while idx < N {
+            if let Some(region) = self.regions[idx] {
+                let start = region.offset.max(offset);
+                let stop = region.end().min(end);
+                if start < stop {
+                    overlap += stop - start;
+                    self.regions[idx] = None;
+                    if start > region.offset {
+                        lost = lost
+                            .or(self.place(FreeRegion::new(region.offset, start - region.offset)));
+                    }
+                    if stop < region.end() {
+                        lost = lost.or(self.place(FreeRegion::new(stop, region.end() - stop)));
+                    }
+                }
+            }
+            idx += 1;
+        }
+        (overlap, lost)
+    }
+
+    fn covers(&self, offset: usize, size: usize) -> bool {
+        let mut idx = 0;
+        while idx < N {
+            if let Some(region) = self.regions[idx] {
+                if region.offset <= offset && offset + size <= region.end() {
+                    return true;
+                }
+            }
+            idx += 1;
+        }
+        false
+    }
+
+    /// Detach up to `max` bytes from the front of the first tracked range.
+    fn take_front(&mut self, max: usize) -> Option<FreeRegion> {
+        let mut idx = 0;
+        while idx < N {
+            if let Some(region) = self.regions[idx] {
+                if region.size <= max {
+                    self.regions[idx] = None;
+                    return Some(region);
+                }
+                self.regions[idx] = Some(FreeRegion::new(region.offset + max, region.size - max));
+                return Some(FreeRegion::new(region.offset, max));
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    fn total(&self) -> usize {
+        let mut total = 0;
+        let mut idx = 0;
+        while idx < N {
+            if let Some(region) = self.regions[idx] {
+                total += region.size;
+            }
+            idx += 1;
+        }
+        total
+    }
+}
+
+/// Static heap storage aligned to `PAGE_SIZE` so offset 0 already satisfies
+/// page-aligned requests and the first mapping pays no alignment padding.
+#[repr(C, align(4096))]
+struct PageAlignedHeap<const HEAP_SIZE: usize>([u8; HEAP_SIZE]);
+
+impl<const HEAP_SIZE: usize> PageAlignedHeap<HEAP_SIZE> {
+    const fn as_ptr(&self) -> *const u8 {
+        self.0.as_ptr()
+    }
+}
+
 pub struct MemoryManager<const HEAP_SIZE: usize, const MAX_AREAS: usize> {
-    heap: [u8; HEAP_SIZE],
+    heap: PageAlignedHeap<HEAP_SIZE>,
     bump_offset: usize,
     allocations: [Option<AllocationRecord>; MAX_AREAS],
+    /// Occupied `allocations` entries.
+    allocation_slots: SlotBitmap<MAX_AREAS>,
     free_regions: [Option<FreeRegion>; MAX_AREAS],
     allocated_bytes: usize,
     peak_bytes: usize,
+    failed_allocations: u64,
+    failed_bytes: u64,
     backing: BackingStore,
+    scrub_policy: ScrubPolicy,
+    dirty_regions: RegionList<MAX_AREAS>,
+    clean_regions: RegionList<MAX_AREAS>,
+    scrub_stats: ScrubStats,
+    pressure_threshold: Option<PressureThreshold>,
+    pressure_handler: Option<fn()>,
+    under_pressure: bool,
+    pressure_events: u64,
 }
 
 impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MAX_AREAS> {
     pub const fn new() -> Self {
         Self {
-            heap: [0; HEAP_SIZE],
+            heap: PageAlignedHeap([0; HEAP_SIZE]),
             bump_offset: 0,
             allocations: [None; MAX_AREAS],
+            allocation_slots: SlotBitmap::new(),
             free_regions: [None; MAX_AREAS],
             allocated_bytes: 0,
             peak_bytes: 0,
+            failed_allocations: 0,
+            failed_bytes: 0,
             backing: BackingStore::Static,
+            scrub_policy: ScrubPolicy::Off,
+            dirty_regions: RegionList::new(),
+            clean_regions: RegionList::new(),
+            scrub_stats: ScrubStats {
+                freed_bytes: 0,
+                scrubbed_bytes: 0,
+                backlog_bytes: 0,
+                last_pass_bytes: 0,
+            },
+            pressure_threshold: None,
+            pressure_handler: None,
+            under_pressure: false,
+            pressure_events: 0,
         }
     }
 
@@ -267,6 +570,28 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
     }
 
     pub fn malloc_for(&mut self, owner: ProcessId, size: usize) -> Option<NonNull<u8>> {
+        self.claim_heap(owner, size).map(|(ptr, _)| ptr)
+    }
+
+    pub fn malloc_zeroed(&mut self, size: usize) -> Option<NonNull<u8>> {
+        self.malloc_zeroed_for(KERNEL_PROCESS_ID, size)
+    }
+
+    /// Allocate `size` zeroed bytes. The memset is skipped only when the
+    /// allocation lies entirely in bytes the scrubber already zeroed.
+    pub fn malloc_zeroed_for(&mut self, owner: ProcessId, size: usize) -> Option<NonNull<u8>> {
+        let (ptr, clean) = self.claim_heap(owner, size)?;
+        if !clean {
+            unsafe {
+                ptr::write_bytes(ptr.as_ptr(), 0, size);
+            }
+        }
+        Some(ptr)
+    }
+
+    /// Heap allocation shared by `malloc_for` and `malloc_zeroed_for`; the
+    /// flag reports whether every returned byte is known to be zero.
+    fn claim_heap(&mut self, owner: ProcessId, size: usize) -> Option<(NonNull<u8>, bool)> {
         if size == 0 {
             return None;
         }
@@ -287,13 +612,14 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
             actual_size,
             AllocationKind::Heap,
             MemoryProtection::read_write(),
+            align,
         );
-        if self.record_allocation(record).is_none() {
+        let Some(clean) = self.record_allocation(record) else {
             self.insert_free_region(FreeRegion::new(offset, actual_size));
             return None;
-        }
+        };
         self.update_stats_on_alloc(actual_size);
-        Some(self.ptr_for_offset(offset))
+        Some((self.ptr_for_offset(offset), clean))
     }
 
     pub fn malloc_aligned(&mut self, size: usize, align: usize) -> Option<NonNull<u8>> {
@@ -327,6 +653,7 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
             actual_size,
             AllocationKind::Heap,
             MemoryProtection::read_write(),
+            actual_align,
         );
         if self.record_allocation(record).is_none() {
             self.insert_free_region(FreeRegion::new(offset, actual_size));
@@ -371,8 +698,7 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
                     let leftover = record.size.saturating_sub(aligned_new);
                     if leftover > 0 {
                         let free_offset = record.offset + aligned_new;
-                        self.insert_free_region(FreeRegion::new(free_offset, leftover));
-                        self.update_stats_on_free(leftover);
+                        self.free_range(FreeRegion::new(free_offset, leftover));
                     }
                     record.size = aligned_new;
                     self.allocations[idx] = Some(record);
@@ -380,7 +706,7 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
                 }
 
                 let copy_len = cmp::min(record.size, size);
-                let new_ptr = self.malloc_for(owner, size)?;
+                let new_ptr = self.malloc_aligned_for(owner, size, record.alignment())?;
                 unsafe {
                     ptr::copy_nonoverlapping(p.as_ptr(), new_ptr.as_ptr(), copy_len);
                 }
@@ -398,6 +724,71 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         self.release(owner, ptr, Some(AllocationKind::Heap), None)
     }
 
+    pub fn free_with_align(&mut self, ptr: NonNull<u8>, align: usize) -> bool {
+        self.free_with_align_for(KERNEL_PROCESS_ID, ptr, align)
+    }
+
+    /// Free a heap block only if it was allocated with `align`. Alignments
+    /// below a machine word match any block, as every block is at least
+    /// word-aligned.
+    pub fn free_with_align_for(
+        &mut self,
+        owner: ProcessId,
+        ptr: NonNull<u8>,
+        align: usize,
+    ) -> bool {
+        if !Self::valid_alignment(align) {
+            return false;
+        }
+        match self.allocation_info_for(owner, ptr) {
+            Some(info)
+                if info.kind == AllocationKind::Heap
+                    && info.align == align.max(core::mem::size_of::<usize>()) =>
+            {
+                self.free_for(owner, ptr)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn allocation_info(&self, ptr: NonNull<u8>) -> Option<AllocationInfo> {
+        self.allocation_info_for(KERNEL_PROCESS_ID, ptr)
+    }
+
+    pub fn allocation_info_for(
+        &self,
+        owner: ProcessId,
+        ptr: NonNull<u8>,
+    ) -> Option<AllocationInfo> {
+        let offset = self.offset_for_ptr(ptr)?;
+        let record = self.allocations[self.find_allocation_index(owner, offset)?]?;
+        Some(AllocationInfo {
+            owner: record.owner,
+            size: record.size,
+            align: record.alignment(),
+            kind: record.kind,
+            protection: record.protection,
+        })
+    }
+
+    /// Whether `len` bytes at `address` lie inside one readable mapping
+    /// owned by `owner`.
+    pub fn mapping_covers(&self, owner: ProcessId, address: usize, len: usize) -> bool {
+        let Some(offset) = address.checked_sub(self.base_address()) else {
+            return false;
+        };
+        let Some(end) = offset.checked_add(len) else {
+            return false;
+        };
+        self.allocations.iter().flatten().any(|record| {
+            record.owner == owner
+                && record.kind == AllocationKind::Mapping
+                && record.protection.read
+                && offset >= record.offset
+                && end <= record.offset + record.size
+        })
+    }
+
     pub fn mmap(&mut self, length: usize, protection: MemoryProtection) -> Option<MappedRegion> {
         self.mmap_for(KERNEL_PROCESS_ID, length, protection)
     }
@@ -407,6 +798,33 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         owner: ProcessId,
         length: usize,
         protection: MemoryProtection,
+    ) -> Option<MappedRegion> {
+        self.mmap_with_flags_for(owner, length, protection, MmapFlags::ANONYMOUS, None)
+    }
+
+    pub fn mmap_with_flags(
+        &mut self,
+        length: usize,
+        protection: MemoryProtection,
+        flags: MmapFlags,
+        addr: Option<u64>,
+    ) -> Option<MappedRegion> {
+        self.mmap_with_flags_for(KERNEL_PROCESS_ID, length, protection, flags, addr)
+    }
+
+    /// Maps `length` bytes for `owner`.
+    ///
+    /// `POPULATE` zeroes the whole region before it is returned, standing in for
+    /// pre-faulting every page. `FIXED` places the mapping at the heap offset
+    /// given by `addr`; the request fails rather than relocating if that range
+    /// is unaligned or already in use.
+    pub fn mmap_with_flags_for(
+        &mut self,
+        owner: ProcessId,
+        length: usize,
+        protection: MemoryProtection,
+        flags: MmapFlags,
+        addr: Option<u64>,
     ) -> Option<MappedRegion> {
         if length == 0 {
             return None;
@@ -414,7 +832,12 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
 
         let align = PAGE_SIZE;
         let actual_size = self.align_up(length, PAGE_SIZE)?;
-        let offset = self.reserve(actual_size, align)?;
+        let offset = if flags.contains(MmapFlags::FIXED) {
+            let fixed = usize::try_from(addr?).ok()?;
+            self.reserve_at(fixed, actual_size, align)?
+        } else {
+            self.reserve(actual_size, align)?
+        };
         if self
             .ensure_backing(offset, actual_size, protection)
             .is_none()
@@ -428,6 +851,7 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
             actual_size,
             AllocationKind::Mapping,
             protection,
+            align,
         );
         if self.record_allocation(record).is_none() {
             self.insert_free_region(FreeRegion::new(offset, actual_size));
@@ -435,6 +859,11 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         }
         self.update_stats_on_alloc(actual_size);
         let ptr = self.ptr_for_offset(offset);
+        if flags.contains(MmapFlags::POPULATE) {
+            unsafe {
+                ptr::write_bytes(ptr.as_ptr(), 0, actual_size);
+            }
+        }
         Some(MappedRegion {
             owner,
             ptr,
@@ -445,6 +874,27 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         })
     }
 
+    /// Change the protection recorded for `region` and return the updated
+    /// handle. Only the allocator's record changes; kernel heap pages keep the
+    /// protection they were committed with.
+    pub fn mprotect(
+        &mut self,
+        region: MappedRegion,
+        protection: MemoryProtection,
+    ) -> Option<MappedRegion> {
+        let offset = self.offset_for_ptr(region.ptr)?;
+        let index = self.find_allocation_index(region.owner, offset)?;
+        let record = self.allocations[index].as_mut()?;
+        if record.kind != AllocationKind::Mapping || record.size < region.length {
+            return None;
+        }
+        record.protection = protection;
+        Some(MappedRegion {
+            protection,
+            ..region
+        })
+    }
+
     pub fn munmap(&mut self, region: MappedRegion) -> bool {
         self.release(
             region.owner,
@@ -468,8 +918,8 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
             if let Some(record) = self.allocations[idx] {
                 if record.owner == owner {
                     self.allocations[idx] = None;
-                    self.insert_free_region(FreeRegion::new(record.offset, record.size));
-                    self.update_stats_on_free(record.size);
+                    self.allocation_slots.clear(idx);
+                    self.free_range(FreeRegion::new(record.offset, record.size));
                 }
             }
             idx += 1;
@@ -480,6 +930,8 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         AllocationStats {
             allocated_bytes: self.allocated_bytes,
             peak_allocated_bytes: self.peak_bytes,
+            failed_allocations: self.failed_allocations,
+            failed_bytes: self.failed_bytes,
         }
     }
 
@@ -499,6 +951,32 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         }
     }
 
+    pub fn full_statistics(&self) -> FullMemoryStats {
+        let tail = self.capacity().saturating_sub(self.bump_offset);
+        let mut free_bytes = tail;
+        let mut largest_free_block = tail;
+        let mut free_region_count = 0usize;
+        let mut idx = 0;
+        while idx < MAX_AREAS {
+            if let Some(region) = self.free_regions[idx] {
+                free_bytes = free_bytes.saturating_add(region.size);
+                largest_free_block = largest_free_block.max(region.size);
+                free_region_count += 1;
+            }
+            idx += 1;
+        }
+        FullMemoryStats {
+            allocated_bytes: self.allocated_bytes,
+            peak_allocated_bytes: self.peak_bytes,
+            failed_allocations: self.failed_allocations,
+            failed_bytes: self.failed_bytes,
+            free_bytes,
+            largest_free_block,
+            free_region_count,
+            heap: self.heap_statistics(),
+        }
+    }
+
     fn base_address(&self) -> usize {
         self.backing.base::<HEAP_SIZE>(self.heap.as_ptr())
     }
@@ -520,11 +998,20 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         Some(addr - base)
     }
 
+    /// Finds room for `size` bytes, counting the request as a failed
+    /// allocation when there is none.
     fn reserve(&mut self, size: usize, align: usize) -> Option<usize> {
-        if let Some(offset) = self.reserve_from_free_list(size, align) {
-            return Some(offset);
+        let offset = self
+            .reserve_from_free_list(size, align)
+            .or_else(|| self.reserve_from_bump(size, align));
+        if offset.is_none() {
+            self.failed_allocations = self.failed_allocations.saturating_add(1);
+            self.failed_bytes = self.failed_bytes.saturating_add(size as u64);
         }
+        offset
+    }
 
+    fn reserve_from_bump(&mut self, size: usize, align: usize) -> Option<usize> {
         let aligned_offset = self.aligned_heap_offset(self.bump_offset, align)?;
         let end = aligned_offset.checked_add(size)?;
         if end > self.capacity() {
@@ -534,6 +1021,47 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         Some(aligned_offset)
     }
 
+    fn reserve_at(&mut self, offset: usize, size: usize, align: usize) -> Option<usize> {
+        if self.aligned_heap_offset(offset, align)? != offset {
+            return None;
+        }
+        let end = offset.checked_add(size)?;
+        if end > self.capacity() {
+            return None;
+        }
+
+        if offset >= self.bump_offset {
+            let gap = FreeRegion::new(self.bump_offset, offset - self.bump_offset);
+            self.bump_offset = end;
+            self.insert_free_region(gap);
+            return Some(offset);
+        }
+
+        let mut idx = 0;
+        while idx < MAX_AREAS {
+            if let Some(region) = self.free_regions[idx] {
+                let reaches_bump = region.end() == self.bump_offset && end > region.end();
+                if region.offset <= offset && (end <= region.end() || reaches_bump) {
+                    self.free_regions[idx] = None;
+                    if offset > region.offset {
+                        self.insert_free_region(FreeRegion::new(
+                            region.offset,
+                            offset - region.offset,
+                        ));
+                    }
+                    if end < region.end() {
+                        self.insert_free_region(FreeRegion::new(end, region.end() - end));
+                    } else {
+                        self.bump_offset = self.bump_offset.max(end);
+                    }
+                    return Some(offset);
+                }
+            }
+            idx += 1;
+        }
+        None
+    }
+
     fn reserve_from_free_list(&mut self, size: usize, align: usize) -> Option<usize> {
         let mut idx = 0;
         while idx < MAX_AREAS {
@@ -558,16 +1086,125 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         None
     }
 
-    fn record_allocation(&mut self, record: AllocationRecord) -> Option<()> {
-        let mut idx = 0;
-        while idx < MAX_AREAS {
-            if self.allocations[idx].is_none() {
-                self.allocations[idx] = Some(record);
-                return Some(());
-            }
-            idx += 1;
+    /// Store `record` and take its bytes off the scrubber lists. Returns
+    /// whether the bytes were all known to be zero.
+    fn record_allocation(&mut self, record: AllocationRecord) -> Option<bool> {
+        let idx = self.allocation_slots.first_clear()?;
+        self.allocations[idx] = Some(record);
+        self.allocation_slots.set(idx);
+        Some(self.claim_scrub_state(record.offset, record.size))
+    }
+
+    fn claim_scrub_state(&mut self, offset: usize, size: usize) -> bool {
+        let (dirty, lost) = self.dirty_regions.remove_range(offset, size);
+        self.scrub_stats.backlog_bytes -= dirty;
+        if let Some(region) = lost {
+            // The split-off remainder is still free; scrub it now rather than
+            // lose track of it.
+            self.scrub_stats.backlog_bytes -= region.size;
+            self.scrub_region(region);
         }
-        None
+        let clean = dirty == 0 && self.clean_regions.covers(offset, size);
+        self.clean_regions.remove_range(offset, size);
+        clean
+    }
+
+    fn free_range(&mut self, region: FreeRegion) {
+        self.insert_free_region(region);
+        self.update_stats_on_free(region.size);
+        if !matches!(self.scrub_policy, ScrubPolicy::Deferred { .. }) {
+            return;
+        }
+        self.scrub_stats.freed_bytes += region.size;
+        match self.dirty_regions.insert(region) {
+            None => self.scrub_stats.backlog_bytes += region.size,
+            Some(untracked) => self.scrub_region(untracked),
+        }
+    }
+
+    fn scrub_region(&mut self, region: FreeRegion) {
+        let ptr = self.ptr_for_offset(region.offset);
+        unsafe {
+            ptr::write_bytes(ptr.as_ptr(), 0, region.size);
+        }
+        self.scrub_stats.scrubbed_bytes += region.size;
+        let _ = self.clean_regions.insert(region);
+    }
+
+    /// Scrub up to `budget` bytes of the dirty backlog, returning how many
+    /// were zeroed.
+    pub fn scrub_step(&mut self, budget: usize) -> usize {
+        let mut scrubbed = 0;
+        while scrubbed < budget {
+            let Some(region) = self.dirty_regions.take_front(budget - scrubbed) else {
+                break;
+            };
+            self.scrub_stats.backlog_bytes -= region.size;
+            self.scrub_region(region);
+            scrubbed += region.size;
+        }
+        scrubbed
+    }
+
+    /// Idle-time maintenance hook: one bounded scrub pass under the deferred
+    /// policy.
+    pub fn scrub_idle(&mut self) -> usize {
+        let ScrubPolicy::Deferred { bytes_per_idle } = self.scrub_policy else {
+            return 0;
+        };
+        let scrubbed = self.scrub_step(bytes_per_idle);
+        self.scrub_stats.last_pass_bytes = scrubbed;
+        scrubbed
+    }
+
+    /// Scrub the whole backlog now.
+    pub fn flush_scrubber(&mut self) -> usize {
+        self.scrub_step(usize::MAX)
+    }
+
+    /// Switch scrub policy. Turning scrubbing off flushes the backlog first so
+    /// no freed bytes are left dirty.
+    pub fn set_scrub_policy(&mut self, policy: ScrubPolicy) {
+        if policy == ScrubPolicy::Off {
+            self.flush_scrubber();
+        }
+        self.scrub_policy = policy;
+    }
+
+    /// Watch for heap pressure, or stop watching with `None`. Usage already
+    /// past the new high-water mark counts as a crossing on the next
+    /// allocation.
+    pub fn set_pressure_threshold(&mut self, threshold: Option<PressureThreshold>) {
+        self.pressure_threshold = threshold;
+        self.under_pressure = false;
+    }
+
+    pub fn pressure_threshold(&self) -> Option<PressureThreshold> {
+        self.pressure_threshold
+    }
+
+    /// Install the callback run once per pressure crossing. It runs inside
+    /// the allocation that crossed the threshold, with the manager borrowed,
+    /// so it must not allocate or free; it should only flag reclaim work,
+    /// such as waking whoever flushes the block cache.
+    pub fn set_pressure_handler(&mut self, handler: Option<fn()>) {
+        self.pressure_handler = handler;
+    }
+
+    /// Whether usage crossed the high-water mark and has not yet fallen
+    /// below the low-water mark.
+    pub fn under_pressure(&self) -> bool {
+        self.under_pressure
+    }
+
+    /// Number of pressure crossings since boot.
+    pub fn pressure_events(&self) -> u64 {
+        self.pressure_events
+    }
+
+    pub fn scrub_stats(&self) -> ScrubStats {
+        debug_assert_eq!(self.scrub_stats.backlog_bytes, self.dirty_regions.total());
+        self.scrub_stats
     }
 
     fn release(
@@ -581,8 +1218,7 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
             return false;
         };
         if let Some(record) = self.remove_allocation(owner, offset, expected_kind, minimum_length) {
-            self.insert_free_region(FreeRegion::new(record.offset, record.size));
-            self.update_stats_on_free(record.size);
+            self.free_range(FreeRegion::new(record.offset, record.size));
             true
         } else {
             false
@@ -624,6 +1260,7 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
                         }
                     }
                     self.allocations[idx] = None;
+                    self.allocation_slots.clear(idx);
                     return Some(record);
                 }
             }
@@ -690,35 +1327,289 @@ impl<const HEAP_SIZE: usize, const MAX_AREAS: usize> MemoryManager<HEAP_SIZE, MA
         value.checked_add(mask).map(|aligned| aligned & !mask)
     }
 
-    const fn valid_alignment(align: usize) -> bool {
-        align != 0 && align.is_power_of_two()
+    const fn valid_alignment(align: usize) -> bool {
+        align != 0 && align.is_power_of_two()
+    }
+
+    fn update_stats_on_alloc(&mut self, size: usize) {
+        self.allocated_bytes = self.allocated_bytes.saturating_add(size);
+        if self.allocated_bytes > self.peak_bytes {
+            self.peak_bytes = self.allocated_bytes;
+        }
+        let Some(threshold) = self.pressure_threshold else {
+            return;
+        };
+        if !self.under_pressure && self.allocated_bytes >= threshold.high_water {
+            self.under_pressure = true;
+            self.pressure_events = self.pressure_events.saturating_add(1);
+            if let Some(handler) = self.pressure_handler {
+                handler();
+            }
+        }
+    }
+
+    fn update_stats_on_free(&mut self, size: usize) {
+        self.allocated_bytes = self.allocated_bytes.saturating_sub(size);
+        if self.under_pressure
+            && self
+                .pressure_threshold
+                .is_none_or(|threshold| self.allocated_bytes < threshold.low_water)
+        {
+            self.under_pressure = false;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MappedRegion {
+    pub owner: ProcessId,
+    pub ptr: NonNull<u8>,
+    pub length: usize,
+    pub requested: usize,
+    pub protection: MemoryProtection,
+    pub kind: AllocationKind,
+}
+
+impl MappedRegion {
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    /// Stable identity of the mapping: the address of its first byte, which
+    /// does not change when the protection does.
+    pub fn id(&self) -> usize {
+        self.ptr.as_ptr() as usize
+    }
+
+    /// Whether both handles name the same mapping, ignoring fields such as
+    /// `protection` that may be stale on one of them.
+    pub fn same_region(&self, other: &MappedRegion) -> bool {
+        self.owner == other.owner && self.id() == other.id()
+    }
+
+    pub fn protection(&self) -> MemoryProtection {
+        self.protection
+    }
+
+    /// The `requested` bytes of the mapping. Page rounding past that is not
+    /// exposed. The handle must not outlive its `munmap`.
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: the mapping owns at least `length >= requested` bytes of the
+        // heap starting at `ptr` until it is unmapped.
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.requested) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: as for `as_slice`; `&mut self` keeps this handle from
+        // handing out a second view while the slice is live.
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.requested) }
+    }
+
+    /// Copy `data` into the mapping at `offset`. Returns `false`, writing
+    /// nothing, if it would run past `requested`.
+    pub fn write_at(&mut self, offset: usize, data: &[u8]) -> bool {
+        match offset.checked_add(data.len()) {
+            Some(end) if end <= self.requested => {
+                self.as_mut_slice()[offset..end].copy_from_slice(data);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Fill `buf` from the mapping at `offset`. Returns `false`, reading
+    /// nothing, if it would run past `requested`.
+    pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> bool {
+        match offset.checked_add(buf.len()) {
+            Some(end) if end <= self.requested => {
+                buf.copy_from_slice(&self.as_slice()[offset..end]);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Per-core bump arena for short-lived kernel scratch buffers.
+///
+/// Allocations are never freed individually; the kernel resets every arena at
+/// the top of each tick, so nothing handed out here may outlive the tick that
+/// requested it. The high-water mark survives resets so arena sizing can be
+/// driven by the worst tick observed.
+#[derive(Clone, Copy, Debug)]
+pub struct ScratchArena<const SIZE: usize> {
+    buffer: [u8; SIZE],
+    offset: usize,
+    high_water: usize,
+    exhausted: u64,
+}
+
+impl<const SIZE: usize> ScratchArena<SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            buffer: [0; SIZE],
+            offset: 0,
+            high_water: 0,
+            exhausted: 0,
+        }
+    }
+
+    /// Carves `len` bytes aligned to `align` from the arena. Returns `None`
+    /// when the arena is exhausted so callers can fall back to the main heap.
+    pub fn alloc(&mut self, len: usize, align: usize) -> Option<&mut [u8]> {
+        if align == 0 || !align.is_power_of_two() {
+            return None;
+        }
+        let base = self.buffer.as_ptr() as usize;
+        let start = match base
+            .checked_add(self.offset)
+            .and_then(|addr| addr.checked_add(align - 1))
+        {
+            Some(addr) => (addr & !(align - 1)) - base,
+            None => return None,
+        };
+        let end = match start.checked_add(len) {
+            Some(end) if end <= SIZE => end,
+            _ => {
+                self.exhausted = self.exhausted.saturating_add(1);
+                return None;
+            }
+        };
+        self.offset = end;
+        if end > self.high_water {
+            self.high_water = end;
+        }
+        Some(&mut self.buffer[start..end])
+    }
+
+    pub fn reset(&mut self) {
+        self.offset = 0;
+    }
+
+    pub const fn used(&self) -> usize {
+        self.offset
+    }
+
+    pub const fn capacity(&self) -> usize {
+        SIZE
+    }
+
+    pub const fn high_water_mark(&self) -> usize {
+        self.high_water
+    }
+
+    pub const fn exhaustion_count(&self) -> u64 {
+        self.exhausted
+    }
+}
+
+impl<const SIZE: usize> Default for ScratchArena<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Names a [`ProcessArena`] in the kernel's arena table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArenaId(u64);
+
+impl ArenaId {
+    pub const fn new(raw: u64) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Bump allocator over a single heap allocation owned by one process.
+///
+/// Objects carved from the arena are not tracked individually and cannot be
+/// freed one at a time; the backing allocation goes back to the heap in one
+/// step when the arena is destroyed or its owner exits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProcessArena {
+    owner: ProcessId,
+    base: usize,
+    size: usize,
+    offset: usize,
+}
+
+impl ProcessArena {
+    pub fn new(owner: ProcessId, base: NonNull<u8>, size: usize) -> Self {
+        Self {
+            owner,
+            base: base.as_ptr() as usize,
+            size,
+            offset: 0,
+        }
+    }
+
+    /// Carves `len` bytes aligned to `align`, or `None` once the arena cannot
+    /// fit them.
+    pub fn alloc(&mut self, len: usize, align: usize) -> Option<NonNull<u8>> {
+        if align == 0 || !align.is_power_of_two() {
+            return None;
+        }
+        let start = self.base.checked_add(self.offset)?.checked_add(align - 1)? & !(align - 1);
+        let end = start.checked_add(len)?;
+        if end > self.base + self.size {
+            return None;
+        }
+        self.offset = end - self.base;
+        NonNull::new(start as *mut u8)
+    }
+
+    /// Forget every object carved so far, keeping the backing allocation.
+    pub fn reset(&mut self) {
+        self.offset = 0;
+    }
+
+    pub const fn owner(&self) -> ProcessId {
+        self.owner
+    }
+
+    pub fn base(&self) -> NonNull<u8> {
+        // Built from a `NonNull` in `new`.
+        unsafe { NonNull::new_unchecked(self.base as *mut u8) }
+    }
+
+    pub const fn size(&self) -> usize {
+        self.size
     }
 
-    fn update_stats_on_alloc(&mut self, size: usize) {
-        self.allocated_bytes = self.allocated_bytes.saturating_add(size);
-        if self.allocated_bytes > self.peak_bytes {
-            self.peak_bytes = self.allocated_bytes;
-        }
+    pub const fn used(&self) -> usize {
+        self.offset
     }
 
-    fn update_stats_on_free(&mut self, size: usize) {
-        self.allocated_bytes = self.allocated_bytes.saturating_sub(size);
+    pub const fn remaining(&self) -> usize {
+        self.size - self.offset
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct MappedRegion {
-    pub owner: ProcessId,
-    pub ptr: NonNull<u8>,
-    pub length: usize,
-    pub requested: usize,
-    pub protection: MemoryProtection,
-    pub kind: AllocationKind,
+/// Borrowed view of one core's scratch arena, passed to kernel paths that need
+/// temporary buffers without touching the main heap.
+pub struct ScratchCtx<'a> {
+    core: usize,
+    arena: &'a mut ScratchArena<SCRATCH_ARENA_BYTES>,
 }
 
-impl MappedRegion {
-    pub fn as_ptr(&self) -> *mut u8 {
-        self.ptr.as_ptr()
+impl<'a> ScratchCtx<'a> {
+    pub fn new(core: usize, arena: &'a mut ScratchArena<SCRATCH_ARENA_BYTES>) -> Self {
+        Self { core, arena }
+    }
+
+    pub const fn core(&self) -> usize {
+        self.core
+    }
+
+    pub fn alloc(&mut self, len: usize, align: usize) -> Option<&mut [u8]> {
+        self.arena.alloc(len, align)
+    }
+
+    pub const fn remaining(&self) -> usize {
+        SCRATCH_ARENA_BYTES - self.arena.used()
     }
 }
 
@@ -909,6 +1800,44 @@ pub fn malloc_for(owner: ProcessId, size: usize) -> Option<NonNull<u8>> {
     MEMORY_MANAGER.lock().malloc_for(owner, size)
 }
 
+pub fn malloc_zeroed(size: usize) -> Option<NonNull<u8>> {
+    MEMORY_MANAGER.lock().malloc_zeroed(size)
+}
+
+pub fn set_scrub_policy(policy: ScrubPolicy) {
+    MEMORY_MANAGER.lock().set_scrub_policy(policy);
+}
+
+/// Run one bounded scrub pass; called when a core has nothing to run.
+pub fn scrub_idle() -> usize {
+    MEMORY_MANAGER.lock().scrub_idle()
+}
+
+/// Scrub up to `budget` bytes of the backlog regardless of policy.
+pub fn scrub_step(budget: usize) -> usize {
+    MEMORY_MANAGER.lock().scrub_step(budget)
+}
+
+pub fn flush_scrubber() -> usize {
+    MEMORY_MANAGER.lock().flush_scrubber()
+}
+
+pub fn scrub_stats() -> ScrubStats {
+    MEMORY_MANAGER.lock().scrub_stats()
+}
+
+pub fn set_pressure_threshold(threshold: Option<PressureThreshold>) {
+    MEMORY_MANAGER.lock().set_pressure_threshold(threshold);
+}
+
+pub fn set_pressure_handler(handler: Option<fn()>) {
+    MEMORY_MANAGER.lock().set_pressure_handler(handler);
+}
+
+pub fn under_memory_pressure() -> bool {
+    MEMORY_MANAGER.lock().under_pressure()
+}
+
 pub fn malloc_aligned(size: usize, align: usize) -> Option<NonNull<u8>> {
     malloc_aligned_for(KERNEL_PROCESS_ID, size, align)
 }
@@ -937,6 +1866,22 @@ pub fn free_for(owner: ProcessId, ptr: NonNull<u8>) -> bool {
     MEMORY_MANAGER.lock().free_for(owner, ptr)
 }
 
+pub fn free_with_align(ptr: NonNull<u8>, align: usize) -> bool {
+    MEMORY_MANAGER.lock().free_with_align(ptr, align)
+}
+
+pub fn allocation_info(ptr: NonNull<u8>) -> Option<AllocationInfo> {
+    MEMORY_MANAGER.lock().allocation_info(ptr)
+}
+
+pub fn allocation_info_for(owner: ProcessId, ptr: NonNull<u8>) -> Option<AllocationInfo> {
+    MEMORY_MANAGER.lock().allocation_info_for(owner, ptr)
+}
+
+pub fn mapping_covers(owner: ProcessId, address: usize, len: usize) -> bool {
+    MEMORY_MANAGER.lock().mapping_covers(owner, address, len)
+}
+
 pub fn mmap(length: usize, protection: MemoryProtection) -> Option<MappedRegion> {
     mmap_for(KERNEL_PROCESS_ID, length, protection)
 }
@@ -949,6 +1894,18 @@ pub fn mmap_for(
     MEMORY_MANAGER.lock().mmap_for(owner, length, protection)
 }
 
+pub fn mmap_with_flags_for(
+    owner: ProcessId,
+    length: usize,
+    protection: MemoryProtection,
+    flags: MmapFlags,
+    addr: Option<u64>,
+) -> Option<MappedRegion> {
+    MEMORY_MANAGER
+        .lock()
+        .mmap_with_flags_for(owner, length, protection, flags, addr)
+}
+
 pub fn create_user_address_space(owner: ProcessId) -> Option<u64> {
     let root = paging::create_user_address_space()?;
     let mut table = ADDRESS_SPACES.lock();
@@ -1115,6 +2072,90 @@ pub fn mmap_user_fixed(
     None
 }
 
+/// Maps one read/write page at `virtual_address` to extend a user stack
+/// downward. Host tests record the mapping without touching page tables.
+pub fn map_user_stack_page(
+    owner: ProcessId,
+    address_space_root: u64,
+    virtual_address: u64,
+) -> Option<MappedRegion> {
+    #[cfg(not(test))]
+    {
+        mmap_user_fixed(
+            owner,
+            address_space_root,
+            virtual_address,
+            PAGE_SIZE,
+            MemoryProtection::read_write(),
+        )
+    }
+
+    #[cfg(test)]
+    {
+        install_test_user_mapping(
+            owner,
+            address_space_root,
+            virtual_address,
+            PAGE_SIZE,
+            MemoryProtection::read_write(),
+        )
+    }
+}
+
+/// Records a kernel-heap-backed user mapping without touching page tables so
+/// host tests can exercise user-copy paths against a synthetic address space.
+#[cfg(test)]
+pub(crate) fn install_test_user_mapping(
+    owner: ProcessId,
+    address_space_root: u64,
+    virtual_address: u64,
+    length: usize,
+    protection: MemoryProtection,
+) -> Option<MappedRegion> {
+    let region = mmap_for(owner, length, protection)?;
+    let mut table = ADDRESS_SPACES.lock();
+    let slot = table.mappings.iter_mut().find(|slot| slot.is_none())?;
+    *slot = Some(UserMappingRecord {
+        owner,
+        root: address_space_root,
+        user_start: virtual_address,
+        kernel_start: region.as_ptr() as usize,
+        length: region.length,
+        protection,
+    });
+    Some(region)
+}
+
+/// Removes every test mapping under its address-space root when dropped,
+/// so a failed assertion cannot leak mappings into tests running alongside.
+#[cfg(test)]
+pub(crate) struct TestUserMappings(pub u64);
+
+#[cfg(test)]
+impl Drop for TestUserMappings {
+    fn drop(&mut self) {
+        remove_test_user_mappings(self.0);
+    }
+}
+
+#[cfg(test)]
+fn remove_test_user_mappings(address_space_root: u64) {
+    let mut table = ADDRESS_SPACES.lock();
+    for slot in table.mappings.iter_mut() {
+        if let Some(mapping) = *slot {
+            if mapping.root == address_space_root {
+                *slot = None;
+                if let Some(ptr) = NonNull::new(mapping.kernel_start as *mut u8) {
+                    let _ =
+                        MEMORY_MANAGER
+                            .lock()
+                            .munmap_ptr_for(mapping.owner, ptr, mapping.length);
+                }
+            }
+        }
+    }
+}
+
 pub fn find_user_mapping(
     address_space_root: u64,
     user_address: u64,
@@ -1159,6 +2200,10 @@ pub fn find_user_mapping_protection(
     None
 }
 
+pub fn mprotect(region: MappedRegion, protection: MemoryProtection) -> Option<MappedRegion> {
+    MEMORY_MANAGER.lock().mprotect(region, protection)
+}
+
 pub fn munmap(region: MappedRegion) -> bool {
     munmap_ptr_for(region.owner, region.ptr, region.length)
 }
@@ -1251,6 +2296,12 @@ pub fn heap_stats() -> HeapStats {
     MEMORY_MANAGER.lock().heap_statistics()
 }
 
+/// Coherent snapshot of allocation, free-space, and heap figures. Prefer this
+/// over separate `stats`/`heap_stats` calls when the figures must agree.
+pub fn full_stats() -> FullMemoryStats {
+    MEMORY_MANAGER.lock().full_statistics()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1393,6 +2444,114 @@ mod tests {
         assert_eq!(offset_of(&manager, reused.ptr), rolled_back.offset);
     }
 
+    #[test]
+    fn mprotected_region_is_still_the_same_region() {
+        let mut manager: MemoryManager<{ PAGE_SIZE * 4 }, 8> = MemoryManager::new();
+        let original = manager
+            .mmap(PAGE_SIZE, MemoryProtection::read_write())
+            .expect("mapping succeeds");
+        let other = manager
+            .mmap(PAGE_SIZE, MemoryProtection::read_write())
+            .expect("second mapping succeeds");
+
+        let protected = manager
+            .mprotect(original, MemoryProtection::read_only())
+            .expect("mapping can be reprotected");
+
+        assert_ne!(protected, original);
+        assert!(protected.same_region(&original));
+        assert_eq!(protected.id(), original.id());
+        assert!(!protected.same_region(&other));
+        assert_eq!(protected.protection(), MemoryProtection::read_only());
+        assert_eq!(
+            manager.allocation_info(protected.ptr).unwrap().protection,
+            MemoryProtection::read_only()
+        );
+        assert!(manager.munmap(protected));
+        assert!(manager
+            .mprotect(original, MemoryProtection::read_exec())
+            .is_none());
+    }
+
+    #[test]
+    fn mapped_region_slices_cover_requested_bytes_only() {
+        let mut manager: MemoryManager<{ PAGE_SIZE * 2 }, 4> = MemoryManager::new();
+        let mut region = manager
+            .mmap(100, MemoryProtection::read_write())
+            .expect("mapping succeeds");
+        assert_eq!(region.length, PAGE_SIZE);
+
+        region.as_mut_slice().fill(0xA5);
+        assert!(region.write_at(96, b"tail"));
+        assert!(!region.write_at(97, b"tail"));
+        assert!(!region.write_at(usize::MAX, b"x"));
+
+        assert_eq!(region.as_slice().len(), region.requested);
+        assert!(region.as_slice()[..96].iter().all(|&byte| byte == 0xA5));
+        assert_eq!(&region.as_slice()[96..], b"tail");
+        let mut buf = [0u8; 4];
+        assert!(region.read_at(96, &mut buf));
+        assert_eq!(&buf, b"tail");
+        assert!(!region.read_at(98, &mut buf));
+        assert!(manager.munmap(region));
+    }
+
+    #[test]
+    fn mprotect_rejects_heap_allocations() {
+        let mut manager: MemoryManager<4096, 8> = MemoryManager::new();
+        let ptr = manager.malloc(64).expect("allocation succeeds");
+        let info = manager.allocation_info(ptr).unwrap();
+        let region = MappedRegion {
+            owner: info.owner,
+            ptr,
+            length: info.size,
+            requested: 64,
+            protection: info.protection,
+            kind: info.kind,
+        };
+
+        assert!(manager
+            .mprotect(region, MemoryProtection::read_only())
+            .is_none());
+        assert!(manager.free(ptr));
+    }
+
+    fn assert_allocation_slots_match<const HEAP_SIZE: usize, const MAX_AREAS: usize>(
+        manager: &MemoryManager<HEAP_SIZE, MAX_AREAS>,
+    ) {
+        for (idx, record) in manager.allocations.iter().enumerate() {
+            assert_eq!(manager.allocation_slots.is_set(idx), record.is_some());
+        }
+    }
+
+    #[test]
+    fn allocation_slots_follow_records_and_reuse_lowest_index() {
+        let mut manager: MemoryManager<{ PAGE_SIZE * 4 }, 8> = MemoryManager::new();
+        let owner = ProcessId::new(5);
+        let first = manager.malloc(32).unwrap();
+        let second = manager.malloc(32).unwrap();
+        let region = manager
+            .mmap_for(owner, PAGE_SIZE, MemoryProtection::read_write())
+            .unwrap();
+        let _third = manager.malloc(32).unwrap();
+        assert_allocation_slots_match(&manager);
+
+        assert!(manager.free(second));
+        assert!(manager.free(first));
+        assert_allocation_slots_match(&manager);
+        assert_eq!(manager.allocation_slots.first_clear(), Some(0));
+        let reused = manager.malloc(16).unwrap();
+        assert_eq!(
+            manager.find_allocation_index(KERNEL_PROCESS_ID, offset_of(&manager, reused)),
+            Some(0)
+        );
+
+        manager.release_process(owner);
+        assert!(manager.allocation_info(region.ptr).is_none());
+        assert_allocation_slots_match(&manager);
+        assert_eq!(manager.allocation_slots.first_clear(), Some(1));
+    }
+
     #[test]
     fn malloc_and_free_cycle() {
         let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
@@ -1414,6 +2573,288 @@ mod tests {
         assert!(manager.free(heap));
     }
 
+    #[test]
+    fn mmap_on_fresh_heap_starts_at_offset_zero() {
+        let mut manager: MemoryManager<PAGE_SIZE, 4> = MemoryManager::new();
+        let region = manager
+            .mmap(PAGE_SIZE, MemoryProtection::read_write())
+            .expect("single page fits exactly");
+        assert_eq!(offset_of(&manager, region.ptr), 0);
+        assert_eq!(manager.statistics().allocated_bytes, PAGE_SIZE);
+    }
+
+    #[test]
+    fn static_heap_base_is_page_aligned() {
+        let manager: Box<MemoryManager<{ PAGE_SIZE * 2 }, 4>> = Box::new(MemoryManager::new());
+        assert!(manager.base_address().is_multiple_of(PAGE_SIZE));
+        let small = MemoryManager::<64, 2>::new();
+        assert!((small.heap.as_ptr() as usize).is_multiple_of(PAGE_SIZE));
+    }
+
+    fn is_zeroed(ptr: NonNull<u8>, len: usize) -> bool {
+        unsafe { core::slice::from_raw_parts(ptr.as_ptr(), len) }
+            .iter()
+            .all(|byte| *byte == 0)
+    }
+
+    #[test]
+    fn malloc_zeroed_clears_region_still_awaiting_scrub() {
+        let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
+        manager.set_scrub_policy(ScrubPolicy::Deferred { bytes_per_idle: 0 });
+        let dirty = manager.malloc(64).expect("allocation succeeds");
+        unsafe {
+            ptr::write_bytes(dirty.as_ptr(), 0xa5, 64);
+        }
+        assert!(manager.free(dirty));
+        assert_eq!(manager.scrub_stats().backlog_bytes, 64);
+
+        let zeroed = manager.malloc_zeroed(64).expect("allocation succeeds");
+        assert_eq!(zeroed, dirty);
+        assert!(is_zeroed(zeroed, 64));
+        assert_eq!(manager.scrub_stats().backlog_bytes, 0);
+    }
+
+    #[test]
+    fn idle_scrub_drains_backlog_at_configured_rate() {
+        let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
+        manager.set_scrub_policy(ScrubPolicy::Deferred { bytes_per_idle: 48 });
+        let block = manager.malloc(128).expect("allocation succeeds");
+        unsafe {
+            ptr::write_bytes(block.as_ptr(), 0x5a, 128);
+        }
+        assert!(manager.free(block));
+
+        assert_eq!(manager.scrub_idle(), 48);
+        assert_eq!(manager.scrub_stats().backlog_bytes, 80);
+        assert_eq!(manager.scrub_stats().last_pass_bytes, 48);
+        assert!(is_zeroed(block, 48));
+        assert_eq!(unsafe { block.as_ptr().add(48).read() }, 0x5a);
+        assert_eq!(manager.scrub_idle(), 48);
+        assert_eq!(manager.scrub_idle(), 32);
+        assert_eq!(manager.scrub_idle(), 0);
+        assert!(is_zeroed(block, 128));
+        assert_eq!(manager.scrub_stats().scrubbed_bytes, 128);
+
+        manager.set_scrub_policy(ScrubPolicy::Off);
+        assert_eq!(manager.scrub_idle(), 0);
+    }
+
+    #[test]
+    fn flush_scrubs_every_freed_byte() {
+        let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
+        manager.set_scrub_policy(ScrubPolicy::Deferred { bytes_per_idle: 16 });
+        let first = manager.malloc(64).expect("allocation succeeds");
+        let keep = manager.malloc(32).expect("allocation succeeds");
+        let second = manager.malloc(96).expect("allocation succeeds");
+        unsafe {
+            ptr::write_bytes(first.as_ptr(), 0x11, 64);
+            ptr::write_bytes(second.as_ptr(), 0x22, 96);
+        }
+        assert!(manager.free(first));
+        assert!(manager.free(second));
+        manager.scrub_idle();
+
+        assert_eq!(manager.flush_scrubber(), 64 + 96 - 16);
+        let stats = manager.scrub_stats();
+        assert_eq!(stats.backlog_bytes, 0);
+        assert_eq!(stats.scrubbed_bytes, stats.freed_bytes);
+        assert_eq!(stats.freed_bytes, 64 + 96);
+        assert!(is_zeroed(first, 64));
+        assert!(is_zeroed(second, 96));
+        assert!(manager.free(keep));
+    }
+
+    #[test]
+    fn pressure_handler_fires_once_per_crossing() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static FIRED: AtomicUsize = AtomicUsize::new(0);
+        fn on_pressure() {
+            FIRED.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
+        manager.set_pressure_threshold(Some(PressureThreshold::new(256, 128)));
+        manager.set_pressure_handler(Some(on_pressure));
+
+        let base = manager.malloc(192).expect("allocation succeeds");
+        assert_eq!(FIRED.load(Ordering::Relaxed), 0);
+        let spike = manager.malloc(64).expect("allocation succeeds");
+        assert_eq!(FIRED.load(Ordering::Relaxed), 1);
+        assert!(manager.under_pressure());
+
+        // Dipping below the high-water mark but not the low one stays armed.
+        assert!(manager.free(spike));
+        let spike = manager.malloc(128).expect("allocation succeeds");
+        assert_eq!(FIRED.load(Ordering::Relaxed), 1);
+
+        assert!(manager.free(spike));
+        assert!(manager.free(base));
+        assert!(!manager.under_pressure());
+        let spike = manager.malloc(320).expect("allocation succeeds");
+        assert_eq!(FIRED.load(Ordering::Relaxed), 2);
+        assert_eq!(manager.pressure_events(), 2);
+        assert!(manager.free(spike));
+    }
+
+    #[test]
+    fn mmap_populate_zero_fills_reused_region() {
+        let mut manager: MemoryManager<{ PAGE_SIZE * 4 }, 16> = MemoryManager::new();
+        let dirty = manager
+            .mmap(PAGE_SIZE, MemoryProtection::read_write())
+            .expect("mapping succeeds");
+        unsafe {
+            ptr::write_bytes(dirty.as_ptr(), 0xa5, PAGE_SIZE);
+        }
+        assert!(manager.munmap(dirty));
+
+        let region = manager
+            .mmap_with_flags(
+                PAGE_SIZE,
+                MemoryProtection::read_write(),
+                MmapFlags::ANONYMOUS.union(MmapFlags::POPULATE),
+                None,
+            )
+            .expect("populated mapping succeeds");
+        assert_eq!(region.ptr, dirty.ptr);
+        unsafe {
+            for index in 0..PAGE_SIZE {
+                assert_eq!(region.as_ptr().add(index).read(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn mmap_fixed_places_mapping_at_requested_offset() {
+        let mut manager: MemoryManager<{ PAGE_SIZE * 8 }, 16> = MemoryManager::new();
+        let probe = manager
+            .mmap(PAGE_SIZE, MemoryProtection::read_only())
+            .expect("probe mapping succeeds");
+        let base = offset_of(&manager, probe.ptr);
+        assert!(manager.munmap(probe));
+
+        let target = (base + PAGE_SIZE * 2) as u64;
+        let fixed = manager
+            .mmap_with_flags(
+                PAGE_SIZE,
+                MemoryProtection::read_write(),
+                MmapFlags::ANONYMOUS.union(MmapFlags::FIXED),
+                Some(target),
+            )
+            .expect("fixed mapping succeeds");
+        assert_eq!(offset_of(&manager, fixed.ptr) as u64, target);
+
+        assert!(manager
+            .mmap_with_flags(
+                PAGE_SIZE,
+                MemoryProtection::read_write(),
+                MmapFlags::FIXED,
+                Some(target),
+            )
+            .is_none());
+        assert!(manager
+            .mmap_with_flags(
+                PAGE_SIZE,
+                MemoryProtection::read_write(),
+                MmapFlags::FIXED,
+                None
+            )
+            .is_none());
+
+        let low = manager
+            .mmap_with_flags(
+                PAGE_SIZE,
+                MemoryProtection::read_write(),
+                MmapFlags::FIXED,
+                Some(base as u64),
+            )
+            .expect("gap before fixed mapping is reusable");
+        assert_eq!(offset_of(&manager, low.ptr), base);
+        assert!(manager.munmap(fixed));
+        assert!(manager.munmap(low));
+    }
+
+    #[test]
+    fn mmap_flags_translate_linux_bits() {
+        let flags = MmapFlags::from_linux_bits(MAP_ANONYMOUS | MAP_POPULATE | 0x2);
+
+        assert!(flags.contains(MmapFlags::ANONYMOUS));
+        assert!(flags.contains(MmapFlags::POPULATE));
+        assert!(!flags.contains(MmapFlags::FIXED));
+        assert!(MmapFlags::from_linux_bits(MAP_FIXED).contains(MmapFlags::FIXED));
+    }
+
+    #[test]
+    fn scratch_arena_allocations_do_not_overlap() {
+        let mut arena: ScratchArena<256> = ScratchArena::new();
+        let first = {
+            let bytes = arena.alloc(10, 1).expect("first scratch allocation");
+            bytes.fill(0x11);
+            (bytes.as_ptr() as usize, bytes.len())
+        };
+        let second = {
+            let bytes = arena.alloc(32, 16).expect("second scratch allocation");
+            bytes.fill(0x22);
+            (bytes.as_ptr() as usize, bytes.len())
+        };
+
+        assert_eq!(second.0 % 16, 0);
+        assert!(second.0 >= first.0 + first.1);
+        unsafe {
+            for index in 0..first.1 {
+                assert_eq!((first.0 as *const u8).add(index).read(), 0x11);
+            }
+        }
+    }
+
+    #[test]
+    fn scratch_arena_reset_reclaims_and_tracks_worst_tick() {
+        let mut arena: ScratchArena<128> = ScratchArena::new();
+        assert!(arena.alloc(96, 1).is_some());
+        assert_eq!(arena.used(), 96);
+        arena.reset();
+        assert_eq!(arena.used(), 0);
+
+        assert!(arena.alloc(128, 1).is_some());
+        arena.reset();
+        assert!(arena.alloc(16, 1).is_some());
+
+        assert_eq!(arena.high_water_mark(), 128);
+        assert_eq!(arena.used(), 16);
+    }
+
+    #[test]
+    fn process_arena_bumps_aligned_objects_until_full() {
+        let mut backing = [0u64; 8];
+        let base = NonNull::new(backing.as_mut_ptr().cast::<u8>()).unwrap();
+        let mut arena = ProcessArena::new(ProcessId::new(3), base, 64);
+
+        let first = arena.alloc(3, 1).unwrap();
+        let second = arena.alloc(8, 8).unwrap();
+        assert_eq!(first, base);
+        assert_eq!(second.as_ptr() as usize, base.as_ptr() as usize + 8);
+        assert_eq!(arena.used(), 16);
+        assert!(arena.alloc(49, 1).is_none());
+        assert_eq!(arena.used(), 16);
+        assert!(arena.alloc(48, 1).is_some());
+        assert_eq!(arena.remaining(), 0);
+
+        arena.reset();
+        assert_eq!(arena.alloc(1, 1), Some(base));
+    }
+
+    #[test]
+    fn scratch_arena_exhaustion_returns_none_without_consuming() {
+        let mut arena: ScratchArena<64> = ScratchArena::new();
+        assert!(arena.alloc(48, 1).is_some());
+
+        assert!(arena.alloc(32, 1).is_none());
+        assert!(arena.alloc(8, 3).is_none());
+        assert_eq!(arena.used(), 48);
+        assert_eq!(arena.exhaustion_count(), 1);
+        assert!(arena.alloc(16, 1).is_some());
+    }
+
     #[test]
     fn freeing_unknown_pointer_fails() {
         let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
@@ -1511,6 +2952,60 @@ mod tests {
         assert_eq!(manager.statistics().allocated_bytes, 0);
     }
 
+    #[test]
+    fn full_statistics_snapshot_is_internally_consistent() {
+        let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
+        let fresh = manager.full_statistics();
+        assert_eq!(fresh.free_bytes, 4096);
+        assert_eq!(fresh.fragmentation_percent(), 0);
+
+        let a = manager.malloc(256).unwrap();
+        let b = manager.malloc(512).unwrap();
+        let c = manager.malloc(128).unwrap();
+        let _d = manager.malloc(64).unwrap();
+        assert!(manager.free(a));
+        assert!(manager.free(c));
+        let e = manager.realloc(Some(b), 1024).unwrap();
+
+        let stats = manager.full_statistics();
+        assert_eq!(stats.allocated_bytes, manager.statistics().allocated_bytes);
+        assert_eq!(stats.heap, manager.heap_statistics());
+        assert!(stats.allocated_bytes + stats.free_bytes <= stats.heap.reserved_bytes);
+        assert!(stats.peak_allocated_bytes >= stats.allocated_bytes);
+        assert!(stats.largest_free_block <= stats.free_bytes);
+        assert!(stats.free_region_count > 0);
+        assert!(stats.fragmentation_percent() > 0);
+
+        assert!(manager.free(e));
+        let after = manager.full_statistics();
+        assert!(after.free_bytes > stats.free_bytes);
+        assert!(after.allocated_bytes + after.free_bytes <= after.heap.reserved_bytes);
+    }
+
+    #[test]
+    fn failed_reservations_are_counted_by_number_and_size() {
+        let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
+        let whole = manager.malloc(4096).unwrap();
+        assert_eq!(manager.statistics().failed_allocations, 0);
+
+        assert!(manager.malloc(100).is_none());
+        assert!(manager.malloc_aligned(200, 64).is_none());
+        assert!(manager.malloc(0).is_none());
+
+        let stats = manager.statistics();
+        assert_eq!(stats.failed_allocations, 2);
+        assert_eq!(stats.failed_bytes, 104 + 200);
+        let full = manager.full_statistics();
+        assert_eq!(full.failed_allocations, stats.failed_allocations);
+        assert_eq!(full.failed_bytes, stats.failed_bytes);
+
+        assert!(manager.free(whole));
+        assert!(manager.malloc(64).is_some());
+        let after = manager.statistics();
+        assert_eq!(after.failed_allocations, 2);
+        assert_eq!(after.failed_bytes, stats.failed_bytes);
+    }
+
     #[test]
     fn allocations_are_owned_by_process() {
         let mut manager: MemoryManager<4096, 16> = MemoryManager::new();
@@ -1539,4 +3034,41 @@ mod tests {
 
         assert_eq!(manager.statistics().allocated_bytes, 0);
     }
+
+    #[test]
+    fn realloc_preserves_alignment_when_moving() {
+        let mut manager: MemoryManager<8192, 16> = MemoryManager::new();
+        let _pad = manager.malloc(8).unwrap();
+        let block = manager.malloc_aligned(32, 256).unwrap();
+        assert_eq!(offset_of(&manager, block) % 256, 0);
+        unsafe { block.as_ptr().write(0x5a) };
+
+        let moved = manager.realloc(Some(block), 1024).unwrap();
+        assert_ne!(moved, block);
+        assert_eq!(offset_of(&manager, moved) % 256, 0);
+        assert_eq!(unsafe { moved.as_ptr().read() }, 0x5a);
+        assert_eq!(manager.allocation_info(moved).unwrap().align, 256);
+    }
+
+    #[test]
+    fn checked_free_validates_recorded_alignment() {
+        let mut manager: MemoryManager<8192, 16> = MemoryManager::new();
+        let plain = manager.malloc(24).unwrap();
+        let aligned = manager.malloc_aligned(64, 128).unwrap();
+
+        let info = manager.allocation_info(aligned).unwrap();
+        assert_eq!(info.align, 128);
+        assert_eq!(info.size, 64);
+        assert_eq!(info.kind, AllocationKind::Heap);
+        assert_eq!(
+            manager.allocation_info(plain).unwrap().align,
+            core::mem::size_of::<usize>()
+        );
+
+        assert!(!manager.free_with_align(aligned, 64));
+        assert!(!manager.free_with_align(aligned, 3));
+        assert!(manager.free_with_align(aligned, 128));
+        assert!(manager.allocation_info(aligned).is_none());
+        assert!(manager.free_with_align(plain, 1));
+    }
 }
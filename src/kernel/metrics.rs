@@ -0,0 +1,184 @@
+//! Fixed-size latency histograms.
+//!
+//! Buckets have power-of-two boundaries in ticks: bucket 0 holds zero, bucket
+//! `i` holds `[2^(i-1), 2^i)`, and the last bucket also takes everything
+//! larger. Recording is a couple of integer operations, so histograms can sit
+//! on hot paths. With the `latency-metrics` feature the kernel keeps one for
+//! IPC delivery and one for wake-to-run latency; see
+//! [`Kernel::latency_report`](crate::kernel::Kernel::latency_report).
+
+/// Buckets in each kernel latency histogram; the last starts at 2^14 ticks.
+pub const LATENCY_BUCKETS: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Histogram<const BUCKETS: usize> {
+    counts: [u64; BUCKETS],
+    total: u64,
+}
+
+impl<const BUCKETS: usize> Histogram<BUCKETS> {
+    pub const fn new() -> Self {
+        Self {
+            counts: [0; BUCKETS],
+            total: 0,
+        }
+    }
+
+    /// Bucket that `value` falls into.
+    pub const fn bucket_for(value: u64) -> usize {
+        let bucket = (u64::BITS - value.leading_zeros()) as usize;
+        if bucket < BUCKETS {
+            bucket
+        } else {
+            BUCKETS - 1
+        }
+    }
+
+    /// Largest value bucket `index` holds.
+    pub const fn bucket_upper_bound(index: usize) -> u64 {
+        if index + 1 >= BUCKETS || index >= u64::BITS as usize {
+            u64::MAX
+        } else {
+            (1u64 << index) - 1
+        }
+    }
+
+    pub fn record(&mut self, value: u64) {
+        let bucket = Self::bucket_for(value);
+        self.counts[bucket] = self.counts[bucket].saturating_add(1);
+        self.total = self.total.saturating_add(1);
+    }
+
+    pub const fn count(&self) -> u64 {
+        self.total
+    }
+
+    pub fn bucket(&self, index: usize) -> u64 {
+        self.counts.get(index).copied().unwrap_or(0)
+    }
+
+    /// Upper bound of the bucket holding the `percent`-th percentile sample,
+    /// so the true value is at most one bucket width smaller. `None` while
+    /// empty; `percent` is clamped to 100.
+    pub fn percentile(&self, percent: u8) -> Option<u64> {
+        if self.total == 0 {
+            return None;
+        }
+        let percent = percent.min(100) as u128;
+        let rank = ((self.total as u128 * percent).div_ceil(100)).max(1);
+        let mut seen = 0u128;
+        let mut index = 0;
+        while index < BUCKETS {
+            seen += self.counts[index] as u128;
+            if seen >= rank {
+                return Some(Self::bucket_upper_bound(index));
+            }
+            index += 1;
+        }
+        Some(Self::bucket_upper_bound(BUCKETS - 1))
+    }
+
+    /// Fold `other`'s samples into this histogram.
+    pub fn merge(&mut self, other: &Self) {
+        let mut index = 0;
+        while index < BUCKETS {
+            self.counts[index] = self.counts[index].saturating_add(other.counts[index]);
+            index += 1;
+        }
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const BUCKETS: usize> Default for Histogram<BUCKETS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Kernel latency distributions, in scheduler ticks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// From `send_message` queueing a message to the receiver dequeuing it.
+    pub ipc_delivery: Histogram<LATENCY_BUCKETS>,
+    /// From a blocked thread being made ready to its first dispatch.
+    pub wake_to_run: Histogram<LATENCY_BUCKETS>,
+}
+
+impl LatencyReport {
+    pub const fn new() -> Self {
+        Self {
+            ipc_delivery: Histogram::new(),
+            wake_to_run: Histogram::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_land_in_power_of_two_buckets() {
+        let mut histogram = Histogram::<6>::new();
+        for value in [0, 1, 2, 3, 4, 7, 8, 15, 16, 1000] {
+            histogram.record(value);
+        }
+
+        // Buckets: {0}, {1}, [2,4), [4,8), [8,16), [16,..).
+        let counts: [u64; 6] = core::array::from_fn(|index| histogram.bucket(index));
+        assert_eq!(counts, [1, 1, 2, 2, 2, 2]);
+        assert_eq!(histogram.count(), 10);
+        assert_eq!(Histogram::<6>::bucket_upper_bound(3), 7);
+        assert_eq!(Histogram::<6>::bucket_upper_bound(5), u64::MAX);
+        assert_eq!(Histogram::<6>::bucket_for(u64::MAX), 5);
+    }
+
+    #[test]
+    fn percentile_reports_the_bucket_holding_that_rank() {
+        let mut histogram = Histogram::<9>::new();
+        assert_eq!(histogram.percentile(50), None);
+        // 90 samples of 1 tick, 9 of 5 ticks, 1 of 100 ticks.
+        for _ in 0..90 {
+            histogram.record(1);
+        }
+        for _ in 0..9 {
+            histogram.record(5);
+        }
+        histogram.record(100);
+
+        assert_eq!(histogram.percentile(0), Some(1));
+        assert_eq!(histogram.percentile(50), Some(1));
+        assert_eq!(histogram.percentile(90), Some(1));
+        assert_eq!(histogram.percentile(91), Some(7));
+        assert_eq!(histogram.percentile(99), Some(7));
+        assert_eq!(histogram.percentile(100), Some(127));
+        assert_eq!(histogram.percentile(200), Some(127));
+    }
+
+    #[test]
+    fn merge_adds_counts_and_reset_clears_them() {
+        let mut left = Histogram::<4>::new();
+        let mut right = Histogram::<4>::new();
+        left.record(0);
+        left.record(3);
+        right.record(3);
+        right.record(50);
+
+        left.merge(&right);
+        let counts: [u64; 4] = core::array::from_fn(|index| left.bucket(index));
+        assert_eq!(counts, [1, 0, 2, 1]);
+        assert_eq!(left.count(), 4);
+        assert_eq!(right.count(), 2);
+
+        left.reset();
+        assert_eq!(left, Histogram::new());
+    }
+}
@@ -1,31 +1,41 @@
 //! Core kernel primitives: process lifecycle, scheduling, IPC routing, and
 //! multi-core orchestration.
 
+pub mod assertions;
 pub mod block;
 pub mod boot_diagnostics;
 pub mod boot_phase;
 pub mod boot_runtime;
 pub mod boot_screen;
 pub mod boot_status;
+pub mod boot_tasks;
+pub mod collections;
 pub mod cpu;
 pub mod debug_shell;
 pub mod device;
 pub mod dispatch;
 pub mod elf;
 pub mod exec;
+pub mod fault_dump;
 pub mod fs;
 pub mod futex;
+pub mod handlegen;
 pub mod input;
 pub mod ipc;
+pub mod kernel_task;
 pub mod kso;
 pub mod memory;
+pub mod metrics;
 pub mod mmio;
 pub mod partition;
 pub mod platform;
 pub mod process;
+pub mod profiler;
 pub mod root;
 pub mod services;
+pub mod softirq;
 pub mod spider_pid1;
+pub mod status;
 pub mod sync;
 pub mod syscall;
 pub mod thread;
@@ -38,16 +48,21 @@ use crate::arch::x86_64::{
     boot::{BootInfo, BootModules, FramebufferInfo},
     clock, ThreadRunOutcome, ThreadSliceRunContext,
 };
+use crate::kernel::assertions::{
+    AssertionCode, AssertionRing, RecoverableAssertion, ASSERTION_RING_CAPACITY,
+};
 use crate::kernel::boot_phase::{
     boot_phase_detected, boot_phase_failed, boot_phase_online, boot_phase_skipped,
     boot_phase_start, BootPhase,
 };
+use crate::kernel::collections::{FixedVec, SlotBitmap};
 use crate::kernel::cpu::CpuCoreState;
 use crate::kernel::device::{
     DeviceDescriptor, DeviceError as DriverError, DeviceId, DeviceKind, DeviceManager,
-    MirageDeviceDescriptor,
+    DeviceReadOutcome, MirageDeviceDescriptor, PendingDeviceRead,
 };
 use crate::kernel::exec::{CloneTaskRequest, SpawnTaskRequest};
+use crate::kernel::fault_dump::{FaultDump, FaultDumpRing, FAULT_DUMP_CAPACITY};
 use crate::kernel::fs::inode::InodeKind;
 use crate::kernel::fs::{
     open_flags_from_libc, permissions_from_libc_mode, syscall_error_code_from_vfs, AccessMode,
@@ -56,15 +71,28 @@ use crate::kernel::fs::{
     PathError, PipeDirection, PipeEndpoint, PipeId, QfsFileSystem, SocketHandle, SsdUsbOptions,
     SuperBlock, VfsError, MAX_PATH_BYTES,
 };
-use crate::kernel::futex::{FutexKey, FutexTable, MAX_FUTEX_WAITERS};
-use crate::kernel::ipc::{Message, MessagePayload, MessageQueue, MessageQueueError};
-use crate::kernel::memory::MemoryProtection;
+use crate::kernel::futex::{
+    FutexKey, FutexTable, FutexWaitOutcome, MAX_FUTEX_WAITERS, SHARED_FUTEX_OWNER,
+};
+use crate::kernel::handlegen::{Handle, Slot};
+use crate::kernel::ipc::{
+    AdaptiveSpin, ClassOccupancy, CreditTable, CreditTableError, EndpointTable, EndpointTableError,
+    IpcRedirect, Message, MessagePayload, MessageQueue, MessageQueueError, QueueStats,
+    RedirectTable, RedirectTableError, RetrySend, MESSAGE_WIRE_SIZE,
+};
+use crate::kernel::kernel_task::{KernelTaskSlot, MAX_KERNEL_TASKS};
+use crate::kernel::memory::{
+    ArenaId, MemoryProtection, MmapFlags, ProcessArena, ScratchArena, ScratchCtx,
+    SCRATCH_ARENA_BYTES,
+};
 use crate::kernel::process::{
-    ChildWaitSelector, ExecRequest, ExecServiceDaemon, ExecSignatureMetadata, ExecVectorMetadata,
-    ExitStatus, ProcessControlBlock, ProcessFileTableError, ProcessGroupId, ProcessId, ProcessPath,
-    ProcessPriority, ProcessState, SessionId, SignalAction, SignalMask, MAX_EXEC_ARGS,
+    ChildWaitSelector, CpuQuota, ExecRequest, ExecServiceDaemon, ExecSignatureMetadata,
+    ExecVectorMetadata, ExitStatus, ExitedPids, ProcessControlBlock, ProcessFileTableError,
+    ProcessGroupId, ProcessId, ProcessPath, ProcessPriority, ProcessSnapshot, ProcessState,
+    ProcessSummary, SchedClass, SchedulingMode, SessionId, SignalAction, SignalMask, MAX_EXEC_ARGS,
     MAX_EXEC_ENVS, MAX_SUPPLEMENTARY_GROUPS, SIGCHLD, SIGKILL, SIGTERM,
 };
+use crate::kernel::profiler::{ProfileSample, PROFILER};
 use crate::kernel::services::network::{
     NetworkIpcRequest, NetworkOpcode, NetworkRecvmsgRequest, NetworkRequestHeader,
     NetworkSendmsgRequest, NetworkSockaddrRequest, NetworkSocketRequest,
@@ -73,25 +101,31 @@ use crate::kernel::services::registry::{
     ServiceId as RegistryServiceId, ServiceRegistry, ServiceRegistryError, MAX_DEVICE_CLAIMS,
     MAX_SERVICE_REGISTRATIONS,
 };
+use crate::kernel::softirq::{SoftirqKind, SoftirqStats, Softirqs, SOFTIRQ_BUDGET};
 use crate::kernel::syscall::{
-    SyscallContext, SyscallErrorCode, SyscallNumber, MIRAGE_SYSCALL_ERROR_BIT,
+    SyscallContext, SyscallErrorCode, SyscallFrame, SyscallNumber, MIRAGE_SYSCALL_ERROR_BIT,
 };
 use crate::kernel::thread::{
-    CpuContext, PrivilegeMode, ThreadControlBlock, ThreadId, ThreadState, MAX_THREADS,
+    CpuContext, PendingThreadSpawn, PrivilegeMode, StackFaultOutcome, StackPolicy,
+    ThreadControlBlock, ThreadId, ThreadState, ThreadWaitlist, SYSCALL_TRAP_VECTOR,
+};
+use crate::kernel::time::{
+    rebase_deadline, ClockAdjustment, ClockDiscontinuity, MonotonicTimestamp, KERNEL_TIME,
 };
-use crate::kernel::time::KERNEL_TIME;
 use crate::kernel::timer::{TimerError, TimerManager, MAX_PROCESS_TIMERS, MAX_SLEEP_ENTRIES};
 use crate::subkernel::{
-    CapabilityId, CapabilityObject, CapabilityRight, CapabilityRights, Credentials, DeviceSecurity,
-    IsolationError, SecurityClass, SecurityKernel,
+    CapabilityId, CapabilityObject, CapabilityRight, CapabilityRights, Credentials, IpcNamespace,
+    IsolationError, SecurityClass, SecurityKernel, SecurityLabel,
 };
 use core::cmp::min;
 use core::ptr::NonNull;
 use mirage_mtss::{
     AddressSpaceId as MtssAddressSpaceId, CoreMtss, CoreMtssError, CoreTask, CoreTaskId,
     CoreThread, CpuId as MtssCpuId, Mtss, MtssConfig, MtssError, MtssThreadScheduleRecord,
-    Priority as MtssPriority, ScheduleDecision, StackRange, TaskId as MtssTaskId,
+    Priority as MtssPriority, SchedClass as MtssSchedClass, ScheduleDecision,
+    SchedulingMode as MtssSchedulingMode, StackRange, TaskId as MtssTaskId,
     ThreadId as MtssThreadId, Timeslice as MtssTimeslice, UserProgramImage, UserThreadPreflight,
+    RUN_QUEUE_BANDS,
 };
 
 pub type KernelThreadScheduleRecord =
@@ -99,6 +133,9 @@ pub type KernelThreadScheduleRecord =
 
 pub const MAX_PROCESSES: usize = 64;
 pub const MESSAGE_DEPTH: usize = 16;
+
+/// Kernel sized with the platform process, core, and thread limits.
+pub type DefaultKernel = Kernel<MAX_PROCESSES, MESSAGE_DEPTH>;
 // Keep enough logical device slots for early architecture drivers (serial, PS/2,
 // optional USB/EC input, Limine module block media, AHCI SATA/ATAPI) plus
 // core kernel service devices registered during BootInfoApplied. A full QEMU
@@ -106,7 +143,21 @@ pub const MESSAGE_DEPTH: usize = 16;
 pub const MAX_DEVICES: usize = 24;
 pub const MAX_OPEN_FILES: usize = 64;
 pub const MAX_KERNEL_PIPES: usize = 32;
+pub const MAX_IPC_REDIRECTS: usize = 16;
+pub const MAX_IPC_CREDIT_PAIRS: usize = 32;
+pub const MAX_IPC_ENDPOINTS: usize = 16;
+/// Exited PIDs remembered so sends to them report `ReceiverTerminated`.
+pub const EXITED_PID_HISTORY: usize = 16;
+pub const MAX_THREAD_WAITLIST: usize = 16;
+/// Mixed into the clock reading that seeds PID generation.
+const PID_SEED_SALT: u64 = 0xDEAD_BEEF_1234_5678;
+/// Candidates tried before PID allocation gives up.
+const PID_ALLOCATION_ATTEMPTS: u32 = 64;
 pub const MAX_KERNEL_EVENTFDS: usize = 32;
+pub const MAX_PROCESS_ARENAS: usize = 16;
+const ARENA_ALIGN: usize = 16;
+/// Number of ticks between stack guard page scans.
+pub const STACK_GUARD_CHECK_INTERVAL: u32 = 10;
 const PIPE_BUFFER_BYTES: usize = 4096;
 
 const AT_FDCWD: i32 = -100;
@@ -693,11 +744,20 @@ pub enum KernelError {
     ProcessTableFull,
     SchedulerFull,
     UnknownProcess,
+    /// The receiver exited; it is a zombie or was reaped recently.
+    ReceiverTerminated,
     UnknownThread,
     ThreadTableFull,
     MessageQueueFull,
     MessageQueueEmpty,
+    /// The sender has used up the credits its receiver granted it.
+    NoCredits,
+    /// The receiver's queue already holds its quota of the message's class.
+    ClassQuotaExceeded,
+    /// A queued message failed its integrity checksum and was dropped.
+    MessageCorrupted,
     SecurityViolation(IsolationError),
+    SecurityTableFull,
     IsolationFault(IsolationError),
     DeviceNotFound,
     DeviceFault(DriverError),
@@ -723,6 +783,71 @@ pub enum MtssReadiness {
     Failed { reason: &'static str },
 }
 
+/// Immediate result of [`Kernel::send_message_retry`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendRetryOutcome {
+    Delivered,
+    /// The queue was full; delivery will be re-attempted from `tick`.
+    Pending,
+}
+
+/// Work a process could act on without waiting, as seen by
+/// [`Kernel::pending_work`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PendingWork {
+    pub messages: usize,
+    /// Sleeps and timers past their deadline that the timer softirq has not
+    /// expired yet.
+    pub expired_timers: usize,
+    /// Queued signals at least one of the process's threads does not mask.
+    pub signals: usize,
+    /// Exited children waiting to be reaped.
+    pub lifecycle_events: usize,
+}
+
+impl PendingWork {
+    pub const fn is_empty(&self) -> bool {
+        self.messages == 0
+            && self.expired_timers == 0
+            && self.signals == 0
+            && self.lifecycle_events == 0
+    }
+}
+
+/// Outcome of [`Kernel::quiesce`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuiesceResult {
+    pub ticks: u64,
+    /// A tick changed nothing and no deferred work was left; `false` means
+    /// the tick budget ran out first.
+    pub fixed_point: bool,
+    pub blocked_processes: usize,
+    pub queued_messages: usize,
+}
+
+impl QuiesceResult {
+    /// Reached a fixed point with nobody blocked and nothing left queued. A
+    /// fixed point that fails this is a stuck protocol, such as a deadlock.
+    pub const fn is_quiesced(&self) -> bool {
+        self.fixed_point && self.blocked_processes == 0 && self.queued_messages == 0
+    }
+}
+
+/// State-changing activity compared across a tick by [`Kernel::quiesce`].
+/// Running and ready threads are not told apart, so a thread that only
+/// yields does not count as work.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ActivitySnapshot {
+    messages_sent: u64,
+    queued_messages: usize,
+    processes: usize,
+    blocked_processes: usize,
+    exited_processes: usize,
+    threads: usize,
+    blocked_threads: usize,
+    pending_spawns: usize,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct MtssInitReport {
     pub core_ready: bool,
@@ -829,45 +954,142 @@ impl MtssInitReport {
     }
 }
 
+/// Outcome of [`Kernel::offline_core`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OfflineReport {
+    pub core: usize,
+    /// Threads that were running on the core and went back to MTSS.
+    pub migrated_threads: usize,
+    pub online_cores: usize,
+}
+
+/// System-wide utilisation figures gathered by
+/// [`Kernel::resource_accounting_snapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResourceSnapshot {
+    pub processes_live: usize,
+    pub processes_max: usize,
+    pub threads_live: usize,
+    pub threads_max: usize,
+    pub memory_used: usize,
+    pub memory_peak: usize,
+    /// Threads waiting in the MTSS run queue.
+    pub scheduler_depth: usize,
+    pub security_domains: usize,
+    pub uptime_ticks: u64,
+    pub online_cores: usize,
+}
+
+impl ResourceSnapshot {
+    /// Process table occupancy, the headline utilisation figure.
+    pub const fn utilization_percent(&self) -> u8 {
+        if self.processes_max == 0 {
+            return 0;
+        }
+        (self.processes_live * 100 / self.processes_max) as u8
+    }
+}
+
+/// Audit record left by [`Kernel::update_credentials`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CredentialAudit {
+    pub requester: ProcessId,
+    pub target: ProcessId,
+    pub old_label: SecurityLabel,
+    pub new_label: SecurityLabel,
+    /// Uptime tick the change took effect at.
+    pub tick: u64,
+    /// Whether the target was sent [`CREDENTIAL_UPDATE_NOTICE`].
+    pub notified: bool,
+}
+
+/// Payload of the IPC notice sent to a process whose credentials changed.
+pub const CREDENTIAL_UPDATE_NOTICE: &[u8] = b"credentials-updated";
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ProcessExitReport {
     pub pid: ProcessId,
     pub status: ExitStatus,
 }
 
-const EMPTY_DEVICE_DESCRIPTOR: DeviceDescriptor = DeviceDescriptor::new(
-    DeviceId::new(0),
-    DeviceKind::SerialConsole,
-    "",
-    DeviceSecurity::new(SecurityClass::Public, false),
-);
-
-pub struct Kernel<const MAX_PROC: usize, const MSG_DEPTH: usize> {
+/// Kernel instance sized at compile time.
+///
+/// `MAX_CORES` and `MAX_THREADS` default to the platform limits so existing
+/// `Kernel<MAX_PROC, MSG_DEPTH>` users are unchanged; host simulations and
+/// unit tests can shrink them to exhaust per-core and thread limits quickly.
+pub struct Kernel<
+    const MAX_PROC: usize,
+    const MSG_DEPTH: usize,
+    const MAX_CORES: usize = { cpu::MAX_CORES },
+    const MAX_THREADS: usize = { thread::MAX_THREADS },
+> {
     process_table: [Option<ProcessControlBlock<MAX_OPEN_FILES>>; MAX_PROC],
+    /// Occupied `process_table` entries; filled and emptied only through
+    /// `install_process` and `vacate_process`.
+    process_slots: SlotBitmap<MAX_PROC>,
     ipc_queues: [MessageQueue<MSG_DEPTH>; MAX_PROC],
-    mtss_scheduler: Mtss<MAX_PROCESSES, MAX_THREADS, MAX_THREADS, MAX_THREADS>,
-    mtss_core: CoreMtss<MAX_PROCESSES, MAX_THREADS, MAX_THREADS>,
+    message_spins: [AdaptiveSpin; MAX_PROC],
+    ipc_redirects: RedirectTable<MAX_IPC_REDIRECTS>,
+    ipc_credits: CreditTable<MAX_IPC_CREDIT_PAIRS>,
+    ipc_endpoints: EndpointTable<MAX_IPC_ENDPOINTS>,
+    exited_pids: ExitedPids<EXITED_PID_HISTORY>,
+    /// Bounded device reads, indexed like `process_table`.
+    device_reads: [Option<PendingDeviceRead>; MAX_PROC],
+    /// Outstanding `send_message_retry` sends, indexed by sender like
+    /// `process_table`.
+    send_retries: [Option<RetrySend>; MAX_PROC],
+    /// Final results of retried sends not yet collected.
+    send_retry_results: [Option<KernelResult<()>>; MAX_PROC],
+    mtss_scheduler: Mtss<MAX_PROC, MAX_THREADS, MAX_THREADS, MAX_THREADS>,
+    mtss_core: CoreMtss<MAX_PROC, MAX_THREADS, MAX_THREADS>,
     mtss_initialized: bool,
     mtss_ticks: u64,
+    /// MTSS run-queue depth per `ProcessPriority` band, sampled each tick.
+    queue_depths: [usize; RUN_QUEUE_BANDS],
+    #[cfg(feature = "latency-metrics")]
+    latency: metrics::LatencyReport,
     pending_mtss_decision: Option<KernelThreadScheduleRecord>,
     security: SecurityKernel<MAX_PROC>,
     devices: DeviceManager<MAX_DEVICES>,
     service_registry: ServiceRegistry<MAX_SERVICE_REGISTRATIONS, MAX_DEVICE_CLAIMS>,
     root_fs: RootFileSystem,
     open_files: FileTable<MAX_OPEN_FILES>,
-    core_states: [CpuCoreState; cpu::MAX_CORES],
-    thread_table: [Option<ThreadControlBlock>; MAX_THREADS],
+    core_states: [CpuCoreState; MAX_CORES],
+    scratch_arenas: [ScratchArena<SCRATCH_ARENA_BYTES>; MAX_CORES],
+    process_arenas: Slot<ProcessArena, MAX_PROCESS_ARENAS>,
+    kernel_tasks: [Option<KernelTaskSlot>; MAX_KERNEL_TASKS],
+    kernel_task_pid: Option<ProcessId>,
+    clock_discontinuities: u64,
+    last_clock_discontinuity: Option<ClockDiscontinuity>,
+    thread_table: Slot<ThreadControlBlock, MAX_THREADS>,
+    thread_waitlist: ThreadWaitlist<MAX_THREAD_WAITLIST>,
     timers: TimerManager<MAX_SLEEP_ENTRIES, MAX_PROCESS_TIMERS>,
     pipes: [Option<PipeObject>; MAX_KERNEL_PIPES],
     eventfds: [Option<EventFdObject>; MAX_KERNEL_EVENTFDS],
     futexes: FutexTable<MAX_FUTEX_WAITERS>,
-    next_pid: u64,
-    next_thread: u64,
+    softirqs: Softirqs,
+    stack_guard_countdown: u32,
+    /// Whether each tick cross-checks the run queue against the thread table.
+    scheduler_cross_check: bool,
+    assertions: AssertionRing<ASSERTION_RING_CAPACITY>,
+    fault_dumps: FaultDumpRing<FAULT_DUMP_CAPACITY>,
+    /// State of the LCG that PIDs are drawn from.
+    pid_seed: u64,
+    /// Whether PID 1 has been handed to the first process since bootstrap.
+    init_pid_issued: bool,
+    pid_collision_count: u32,
     message_sequence: u64,
+    last_credential_audit: Option<CredentialAudit>,
     next_socket_handle: u64,
 }
 
-impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH> {
+impl<
+        const MAX_PROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > Kernel<MAX_PROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
     const THREAD_CAPACITY: usize = MAX_THREADS;
 
     const fn schedule_record(
@@ -878,7 +1100,7 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
         MtssThreadScheduleRecord::new(thread, process, priority, priority.time_slice())
     }
 
-    const fn new_mtss_scheduler() -> Mtss<MAX_PROCESSES, MAX_THREADS, MAX_THREADS, MAX_THREADS> {
+    const fn new_mtss_scheduler() -> Mtss<MAX_PROC, MAX_THREADS, MAX_THREADS, MAX_THREADS> {
         Mtss::new(
             MtssConfig::new(MtssCpuId::new(0)).with_default_timeslice(MtssTimeslice::from_ticks(4)),
         )
@@ -901,6 +1123,14 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
         }
     }
 
+    const fn mtss_sched_class(class: SchedClass) -> MtssSchedClass {
+        match class {
+            SchedClass::Realtime => MtssSchedClass::Realtime,
+            SchedClass::Interactive => MtssSchedClass::Interactive,
+            SchedClass::Batch => MtssSchedClass::Batch,
+        }
+    }
+
     fn schedule_record_from_mtss(
         &self,
         decision: ScheduleDecision,
@@ -939,14 +1169,32 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
         thread: ThreadId,
         priority: ProcessPriority,
     ) -> KernelResult<()> {
+        let pcb = self
+            .locate_process(pid)
+            .ok()
+            .and_then(|index| self.process_table[index].as_ref());
+        let class = pcb.map(|pcb| pcb.sched_class).unwrap_or_default();
+        let priority = if pcb
+            .and_then(|pcb| pcb.cpu_quota)
+            .is_some_and(|quota| quota.is_throttled())
+        {
+            ProcessPriority::Low
+        } else {
+            priority
+        };
         self.mtss_scheduler
             .create_thread(
                 Self::mtss_task_id(pid),
                 Self::mtss_thread_id(thread),
                 Self::mtss_priority(priority),
             )
-            .map(|_| ())
-            .map_err(map_mtss_error)
+            .map_err(map_mtss_error)?;
+        if class != SchedClass::default() {
+            self.mtss_scheduler
+                .set_thread_class(Self::mtss_thread_id(thread), Self::mtss_sched_class(class))
+                .map_err(map_mtss_error)?;
+        }
+        Ok(())
     }
 
     pub(super) fn mtss_enqueue_thread(&mut self, thread: ThreadId) -> KernelResult<()> {
@@ -1006,26 +1254,52 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
     pub const fn new() -> Self {
         Self {
             process_table: [None; MAX_PROC],
+            process_slots: SlotBitmap::new(),
             ipc_queues: [MessageQueue::new(); MAX_PROC],
+            message_spins: [AdaptiveSpin::new(); MAX_PROC],
+            ipc_redirects: RedirectTable::new(),
+            ipc_credits: CreditTable::new(),
+            ipc_endpoints: EndpointTable::new(),
+            exited_pids: ExitedPids::new(),
+            device_reads: [None; MAX_PROC],
+            send_retries: [None; MAX_PROC],
+            send_retry_results: [None; MAX_PROC],
             mtss_scheduler: Self::new_mtss_scheduler(),
             mtss_core: CoreMtss::new(),
             mtss_initialized: false,
             mtss_ticks: 0,
+            queue_depths: [0; RUN_QUEUE_BANDS],
+            #[cfg(feature = "latency-metrics")]
+            latency: metrics::LatencyReport::new(),
             pending_mtss_decision: None,
             security: SecurityKernel::new(),
             devices: DeviceManager::new(),
             service_registry: ServiceRegistry::new(),
             root_fs: RootFileSystem::new(),
             open_files: FileTable::new(),
-            core_states: [CpuCoreState::new(); cpu::MAX_CORES],
-            thread_table: [None; MAX_THREADS],
+            core_states: [CpuCoreState::new(); MAX_CORES],
+            scratch_arenas: [ScratchArena::new(); MAX_CORES],
+            process_arenas: Slot::new(),
+            kernel_tasks: [None; MAX_KERNEL_TASKS],
+            kernel_task_pid: None,
+            clock_discontinuities: 0,
+            last_clock_discontinuity: None,
+            thread_table: Slot::new(),
+            thread_waitlist: ThreadWaitlist::new(),
             timers: TimerManager::new(),
             pipes: [None; MAX_KERNEL_PIPES],
             eventfds: [None; MAX_KERNEL_EVENTFDS],
             futexes: FutexTable::new(),
-            next_pid: 1,
-            next_thread: 1,
+            softirqs: Softirqs::new(),
+            stack_guard_countdown: STACK_GUARD_CHECK_INTERVAL,
+            scheduler_cross_check: cfg!(feature = "scheduler-cross-check"),
+            assertions: AssertionRing::new(),
+            fault_dumps: FaultDumpRing::new(),
+            pid_seed: 0,
+            init_pid_issued: false,
+            pid_collision_count: 0,
             message_sequence: 0,
+            last_credential_audit: None,
             next_socket_handle: 1,
         }
     }
@@ -1051,7 +1325,17 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
         self.mtss_core = CoreMtss::new();
         self.mtss_initialized = false;
         self.mtss_ticks = 0;
+        self.queue_depths = [0; RUN_QUEUE_BANDS];
+        #[cfg(feature = "latency-metrics")]
+        self.latency.reset();
+        for core in self.core_states.iter_mut() {
+            core.dispatch_latency.reset();
+        }
         self.pending_mtss_decision = None;
+        self.release_arenas(|_| true);
+        self.release_kernel_tasks();
+        self.clock_discontinuities = 0;
+        self.last_clock_discontinuity = None;
         self.security.reset();
         self.devices.reset();
         self.service_registry.reset();
@@ -1060,36 +1344,47 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
         self.pipes = [None; MAX_KERNEL_PIPES];
         self.eventfds = [None; MAX_KERNEL_EVENTFDS];
         self.futexes.reset();
-        self.next_pid = 1;
-        self.next_thread = 1;
+        self.softirqs.reset();
+        self.stack_guard_countdown = STACK_GUARD_CHECK_INTERVAL;
+        self.assertions.clear();
+        self.pid_seed = Self::generate_pid_seed();
+        self.init_pid_issued = false;
+        self.pid_collision_count = 0;
         self.message_sequence = 0;
+        self.last_credential_audit = None;
         self.next_socket_handle = 1;
         KERNEL_TIME.init(clock::DEFAULT_FREQUENCY_HZ);
 
         let mut idx = 0;
         while idx < MAX_PROC {
-            self.process_table[idx] = None;
+            self.vacate_process(idx);
             self.ipc_queues[idx].clear();
+            self.message_spins[idx] = AdaptiveSpin::new();
+            self.device_reads[idx] = None;
+            self.send_retries[idx] = None;
+            self.send_retry_results[idx] = None;
             idx += 1;
         }
+        self.ipc_redirects.clear();
+        self.ipc_credits.clear();
+        self.ipc_endpoints.clear();
+        self.exited_pids.clear();
 
-        idx = 0;
-        while idx < Self::THREAD_CAPACITY {
-            self.thread_table[idx] = None;
-            idx += 1;
-        }
+        self.thread_table.clear();
+        self.thread_waitlist.clear();
 
         idx = 0;
-        while idx < cpu::MAX_CORES {
+        while idx < MAX_CORES {
             self.core_states[idx] = CpuCoreState::new();
+            self.scratch_arenas[idx] = ScratchArena::new();
             idx += 1;
         }
         idx = 0;
-        while idx < cpu::MAX_CORES {
+        while idx < MAX_CORES {
             self.core_states[idx].set_kernel_stack_top(x86_64::kernel_stack_top(idx));
             idx += 1;
         }
-        if cpu::MAX_CORES > 0 {
+        if MAX_CORES > 0 {
             self.core_states[0].online();
         }
 
@@ -1146,11 +1441,7 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
             index += 1;
         }
 
-        let mut descriptors = [EMPTY_DEVICE_DESCRIPTOR; MAX_DEVICES];
-        let count = self.devices.enumerate(&mut descriptors);
-        let mut device_index = 0usize;
-        while device_index < count {
-            let descriptor = descriptors[device_index];
+        for descriptor in &self.devices.descriptors() {
             if descriptor.kind == DeviceKind::BlockStorage {
                 if let Ok(device) = self.devices.block_storage_static(descriptor.id) {
                     if self.root_fs.mount_ext4(device).is_ok() {
@@ -1161,7 +1452,6 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
                     }
                 }
             }
-            device_index += 1;
         }
 
         let built_in = crate::kernel::device::built_in_block_storage();
@@ -1215,17 +1505,52 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
     pub fn bring_up_secondary_cores(&mut self, count: usize) {
         let mut brought_online = 0usize;
         let mut idx = 1usize;
-        while idx < cpu::MAX_CORES && brought_online < count {
+        while idx < MAX_CORES && brought_online < count {
             self.core_states[idx].online();
             brought_online += 1;
             idx += 1;
         }
     }
 
+    /// Take `core` out of service. The core stops pulling work from MTSS on
+    /// the next tick; a thread caught mid-slice is handed back so another
+    /// core picks it up. The last online core cannot be taken down.
+    pub fn offline_core(&mut self, core: usize) -> KernelResult<OfflineReport> {
+        if core >= MAX_CORES || !self.core_states[core].online {
+            return Err(KernelError::InvalidArgument);
+        }
+        if self.online_core_count() == 1 {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        let mut migrated_threads = 0usize;
+        if let Some(thread) = self.core_states[core].current_thread {
+            self.core_states[core].evict(thread);
+            migrated_threads += 1;
+        }
+        self.core_states[core].offline();
+        Ok(OfflineReport {
+            core,
+            migrated_threads,
+            online_cores: self.online_core_count(),
+        })
+    }
+
+    /// Bring an offline core back, repeating the bring-up handshake so it
+    /// starts on a fresh kernel stack.
+    pub fn online_core(&mut self, core: usize) -> KernelResult<()> {
+        if core >= MAX_CORES || self.core_states[core].online {
+            return Err(KernelError::InvalidArgument);
+        }
+        self.core_states[core].set_kernel_stack_top(x86_64::kernel_stack_top(core));
+        self.core_states[core].online();
+        Ok(())
+    }
+
     pub fn online_core_count(&self) -> usize {
         let mut count = 0usize;
         let mut idx = 0usize;
-        while idx < cpu::MAX_CORES {
+        while idx < MAX_CORES {
             if self.core_states[idx].online {
                 count += 1;
             }
@@ -1241,6 +1566,12 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
         self.mtss_scheduler = Self::new_mtss_scheduler();
         self.mtss_initialized = false;
         self.mtss_ticks = 0;
+        self.queue_depths = [0; RUN_QUEUE_BANDS];
+        #[cfg(feature = "latency-metrics")]
+        self.latency.reset();
+        for core in self.core_states.iter_mut() {
+            core.dispatch_latency.reset();
+        }
         self.pending_mtss_decision = None;
 
         let kernel_stack_top = x86_64::kernel_stack_top(0);
@@ -1270,14 +1601,254 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
     /// expiry, and avoids blocking or heap allocation.  This milestone has no
     /// architecture preemption-disable counter wired into `Kernel`, so the tick
     /// is reported as preemptible; MTSS still supports deferred rescheduling when
-    /// a backend passes `preemption_disabled = true`.
+    /// a backend passes `preemption_disabled = true`. The per-priority run-queue
+    /// depths read by [`Self::queue_depth_for_priority`] are sampled here.
     pub fn kernel_on_timer_tick(&mut self) {
         if self.mtss_initialized {
             self.mtss_ticks = self.mtss_ticks.saturating_add(1);
-            let _ = self
+            // A slice expiry makes MTSS requeue its current thread and pick
+            // another; that pick replaces the one deferred from last tick.
+            if let Ok(Some(decision)) = self
                 .mtss_scheduler
-                .on_timer_tick_with_preemption_disabled(false);
+                .on_timer_tick_with_preemption_disabled(false)
+            {
+                self.pending_mtss_decision = self.schedule_record_from_mtss(decision);
+            }
+            for priority in [
+                ProcessPriority::Critical,
+                ProcessPriority::High,
+                ProcessPriority::Normal,
+                ProcessPriority::Low,
+            ] {
+                self.queue_depths[priority as usize] = self
+                    .mtss_scheduler
+                    .queue_depth_for_priority(Self::mtss_priority(priority));
+            }
+        }
+    }
+
+    /// Move every thread of `pid`, and any it creates later, to `class`.
+    pub fn set_sched_class(&mut self, pid: ProcessId, class: SchedClass) -> KernelResult<()> {
+        let index = self.locate_process(pid)?;
+        self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?
+            .sched_class = class;
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(tcb) = self.thread_table[idx] {
+                if tcb.process == pid {
+                    self.mtss_scheduler
+                        .set_thread_class(
+                            Self::mtss_thread_id(tcb.id),
+                            Self::mtss_sched_class(class),
+                        )
+                        .map_err(map_mtss_error)?;
+                }
+            }
+            idx += 1;
+        }
+        Ok(())
+    }
+
+    /// Soft-limit `pid` to `ticks_per_window` ticks of CPU per `window`
+    /// scheduler ticks. Past the limit its threads run at `Low` priority
+    /// until the window rolls over. Replaces any earlier quota.
+    pub fn set_cpu_quota(
+        &mut self,
+        pid: ProcessId,
+        ticks_per_window: u64,
+        window: u64,
+    ) -> KernelResult<()> {
+        if window == 0 {
+            return Err(KernelError::InvalidArgument);
+        }
+        self.clear_cpu_quota(pid)?;
+        let index = self.locate_process(pid)?;
+        self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?
+            .cpu_quota = Some(CpuQuota::new(ticks_per_window, window, self.mtss_ticks));
+        Ok(())
+    }
+
+    /// Remove `pid`'s CPU quota, restoring its priority if it was throttled.
+    pub fn clear_cpu_quota(&mut self, pid: ProcessId) -> KernelResult<()> {
+        let index = self.locate_process(pid)?;
+        let pcb = self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?;
+        let priority = pcb.priority;
+        if pcb
+            .cpu_quota
+            .take()
+            .is_some_and(|quota| quota.is_throttled())
+        {
+            self.apply_thread_priority(pid, priority)?;
+        }
+        Ok(())
+    }
+
+    pub fn cpu_quota(&self, pid: ProcessId) -> KernelResult<Option<CpuQuota>> {
+        let index = self.locate_process(pid)?;
+        Ok(self.process_table[index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?
+            .cpu_quota)
+    }
+
+    /// Priority `pid`'s threads are scheduled at: its own, or `Low` while a
+    /// CPU quota throttles it.
+    pub fn effective_priority(&self, pid: ProcessId) -> KernelResult<ProcessPriority> {
+        let index = self.locate_process(pid)?;
+        let pcb = self.process_table[index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?;
+        if pcb.cpu_quota.is_some_and(|quota| quota.is_throttled()) {
+            Ok(ProcessPriority::Low)
+        } else {
+            Ok(pcb.priority)
+        }
+    }
+
+    fn apply_thread_priority(
+        &mut self,
+        pid: ProcessId,
+        priority: ProcessPriority,
+    ) -> KernelResult<()> {
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(tcb) = self.thread_table[idx] {
+                if tcb.process == pid {
+                    self.mtss_scheduler
+                        .set_thread_priority(
+                            Self::mtss_thread_id(tcb.id),
+                            Self::mtss_priority(priority),
+                        )
+                        .map_err(map_mtss_error)?;
+                }
+            }
+            idx += 1;
+        }
+        Ok(())
+    }
+
+    /// Charge the tick `pid` just ran against its quota, throttling it the
+    /// moment it goes over.
+    fn charge_cpu_quota(&mut self, pid: ProcessId, index: usize) {
+        let now = self.mtss_ticks;
+        let Some(quota) = self.process_table[index]
+            .as_mut()
+            .and_then(|pcb| pcb.cpu_quota.as_mut())
+        else {
+            return;
+        };
+        if quota.charge(now) {
+            let _ = self.apply_thread_priority(pid, ProcessPriority::Low);
+        }
+    }
+
+    /// Lift throttles whose window has rolled over.
+    fn roll_over_cpu_quotas(&mut self) {
+        let now = self.mtss_ticks;
+        let mut index = 0usize;
+        while index < MAX_PROC {
+            if let Some(pcb) = self.process_table[index].as_mut() {
+                let (pid, priority) = (pcb.pid, pcb.priority);
+                if pcb
+                    .cpu_quota
+                    .as_mut()
+                    .is_some_and(|quota| quota.roll_over(now))
+                {
+                    let _ = self.apply_thread_priority(pid, priority);
+                }
+            }
+            index += 1;
+        }
+    }
+
+    /// Switch how MTSS orders runnable threads. The running thread is
+    /// rescheduled on the next tick.
+    pub fn set_scheduling_mode(&mut self, mode: SchedulingMode) {
+        self.mtss_scheduler.set_scheduling_mode(match mode {
+            SchedulingMode::Classed => MtssSchedulingMode::Classed,
+            SchedulingMode::Stride => MtssSchedulingMode::Stride,
+            SchedulingMode::ProcessFair => MtssSchedulingMode::ProcessFair,
+        });
+    }
+
+    pub fn sched_class(&self, pid: ProcessId) -> KernelResult<SchedClass> {
+        let index = self.locate_process(pid)?;
+        self.process_table[index]
+            .as_ref()
+            .map(|pcb| pcb.sched_class)
+            .ok_or(KernelError::UnknownProcess)
+    }
+
+    pub fn resource_accounting_snapshot(&self) -> ResourceSnapshot {
+        let memory = memory::stats();
+        ResourceSnapshot {
+            processes_live: self.process_table.iter().flatten().count(),
+            processes_max: MAX_PROC,
+            threads_live: self.thread_table.iter().flatten().count(),
+            threads_max: Self::THREAD_CAPACITY,
+            memory_used: memory.allocated_bytes,
+            memory_peak: memory.peak_allocated_bytes,
+            scheduler_depth: self.mtss_scheduler.queued_threads(),
+            security_domains: self.security.registered_tasks().count(),
+            uptime_ticks: KERNEL_TIME.uptime_ticks(),
+            online_cores: self.online_core_count(),
+        }
+    }
+
+    /// Threads waiting in `priority`'s band of the MTSS run queue at the last
+    /// tick, counting higher-priority threads demoted into it.
+    pub fn queue_depth_for_priority(&self, priority: ProcessPriority) -> usize {
+        self.queue_depths[priority as usize]
+    }
+
+    /// Number of threads MTSS would dispatch before `thread`, for scheduling
+    /// fairness diagnostics. `None` when the thread is not queued.
+    pub fn run_queue_position(&self, thread: ThreadId) -> Option<usize> {
+        self.mtss_scheduler
+            .position_of(Self::mtss_thread_id(thread))
+    }
+
+    /// Sample the running thread on every `interval`-th tick.
+    pub fn profiler_enable(&self, interval: u64) -> KernelResult<()> {
+        if interval == 0 {
+            return Err(KernelError::InvalidArgument);
         }
+        PROFILER.enable(interval);
+        Ok(())
+    }
+
+    pub fn profiler_disable(&self) {
+        PROFILER.disable();
+    }
+
+    /// Ticks threads waited between becoming runnable and being dispatched
+    /// on `core`, since boot or the last `kernel_mtss_init`. `None` for a
+    /// core index past `MAX_CORES`.
+    pub fn core_latency_histogram(
+        &self,
+        core: usize,
+    ) -> Option<metrics::Histogram<{ metrics::LATENCY_BUCKETS }>> {
+        self.core_states
+            .get(core)
+            .map(|state| state.dispatch_latency)
+    }
+
+    /// IPC delivery and wake-to-run latency recorded since boot or the last
+    /// `kernel_mtss_init`, in scheduler ticks.
+    #[cfg(feature = "latency-metrics")]
+    pub fn latency_report(&self) -> metrics::LatencyReport {
+        self.latency
+    }
+
+    /// Copy buffered profiler samples into `out`, oldest first.
+    pub fn profiler_drain(&self, out: &mut [ProfileSample]) -> usize {
+        PROFILER.drain(out)
     }
 
     /// Ask MTSS for the next runnable micro-thread. CPU entry, address-space
@@ -1440,7 +2011,7 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
     ) -> KernelResult<()> {
         let mut idx = 0usize;
         while idx < Self::THREAD_CAPACITY {
-            if let Some(tcb) = self.thread_table[idx].as_mut() {
+            if let Some(tcb) = self.thread_table.entry_mut(idx) {
                 if tcb.process == pid {
                     tcb.entry_point = entry_point;
                     tcb.stack_pointer = stack_pointer;
@@ -1669,6 +2240,68 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
         })
     }
 
+    /// Like `spawn_child_process`, but the child starts in `namespace`
+    /// instead of inheriting its parent's. Requires `CAP_KERNEL`; a process
+    /// cannot change namespace after spawn.
+    pub fn spawn_child_process_in_namespace(
+        &mut self,
+        parent_pid: ProcessId,
+        entry_point: u64,
+        priority: ProcessPriority,
+        requested_creds: Credentials,
+        namespace: IpcNamespace,
+    ) -> KernelResult<ProcessId> {
+        self.security
+            .authorize_kernel_control(parent_pid)
+            .map_err(KernelError::SecurityViolation)?;
+        let child = self.spawn_child_process(parent_pid, entry_point, priority, requested_creds)?;
+        self.security
+            .set_ipc_namespace(child, namespace)
+            .map_err(KernelError::SecurityViolation)?;
+        Ok(child)
+    }
+
+    /// IPC namespace of `pid`. Processes in other namespaces are reported as
+    /// unknown unless `caller` supervises every namespace.
+    pub fn ipc_namespace(&self, caller: ProcessId, pid: ProcessId) -> KernelResult<IpcNamespace> {
+        self.security
+            .ipc_namespace(caller)
+            .map_err(KernelError::SecurityViolation)?;
+        self.ensure_ipc_visible(caller, pid)?;
+        self.security
+            .ipc_namespace(pid)
+            .map_err(|_| KernelError::UnknownProcess)
+    }
+
+    /// A process in another IPC namespace looks like one that does not
+    /// exist, rather than one the caller is forbidden to reach.
+    /// `ReceiverTerminated` if `receiver` has exited and lived in the
+    /// sender's own IPC namespace; other namespaces learn nothing new.
+    fn ensure_receiver_live(&self, sender: ProcessId, receiver: ProcessId) -> KernelResult<()> {
+        let live = self
+            .locate_process(receiver)
+            .ok()
+            .and_then(|index| self.process_table[index].as_ref())
+            .is_some_and(|pcb| pcb.state != ProcessState::Zombie);
+        if live {
+            return Ok(());
+        }
+        match self.exited_pids.namespace_of(receiver) {
+            Some(namespace) if self.security.ipc_namespace(sender) == Ok(namespace) => {
+                Err(KernelError::ReceiverTerminated)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn ensure_ipc_visible(&self, viewer: ProcessId, target: ProcessId) -> KernelResult<()> {
+        if self.security.ipc_visible(viewer, target) {
+            Ok(())
+        } else {
+            Err(KernelError::UnknownProcess)
+        }
+    }
+
     pub fn spawn_thread(
         &mut self,
         pid: ProcessId,
@@ -1683,5618 +2316,10785 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> Kernel<MAX_PROC, MSG_DEPTH>
         ))
     }
 
-    pub fn terminate_process(&mut self, pid: ProcessId) {
-        self.exit_process(pid, ExitStatus::signaled(SIGTERM));
-    }
-
-    pub fn exit_process(
+    /// Like `spawn_thread`, with `policy` deciding what a hit on the new
+    /// thread's stack guard page does. A growth cap must be a non-zero
+    /// multiple of the page size.
+    pub fn spawn_thread_with_stack_policy(
         &mut self,
         pid: ProcessId,
-        status: ExitStatus,
-    ) -> Option<ProcessExitReport> {
-        if let Ok(index) = self.locate_process(pid) {
-            if let Some(pcb) = self.process_table[index].as_ref() {
-                if pcb.state == ProcessState::Zombie {
-                    return None;
-                }
-            }
-            if let Some(mut pcb) = self.process_table[index].take() {
-                self.release_process_file_table(&mut pcb.files);
-                pcb.mark_zombie(status);
-                self.process_table[index] = Some(pcb);
-            }
-            self.ipc_queues[index].clear();
-            if self
-                .mtss_scheduler
-                .terminate_task(Self::mtss_task_id(pid))
-                .is_ok()
-            {
-                let _ = self.mtss_scheduler.reap_task(Self::mtss_task_id(pid));
+        entry_point: u64,
+        priority: ProcessPriority,
+        policy: StackPolicy,
+    ) -> KernelResult<ThreadId> {
+        if let StackPolicy::Grow { max_bytes } = policy {
+            if max_bytes == 0 || !max_bytes.is_multiple_of(memory::PAGE_SIZE as u32) {
+                return Err(KernelError::InvalidArgument);
             }
-            self.remove_threads_for_process(pid);
-            memory::release_process(pid);
-            self.security.revoke_task(pid);
-            self.timers.release_process(pid);
-            self.futexes.remove_owner(self.futex_owner_for_process(pid));
-            let _ = self.queue_signal_to_parent(pid, SIGCHLD);
-            let _ = self.wake_parent_child_waiters(pid);
-            return Some(ProcessExitReport { pid, status });
         }
-        None
+        let thread = self.spawn_thread(pid, entry_point, priority)?;
+        let index = self.locate_thread(thread)?;
+        if let Some(tcb) = self.thread_table.entry_mut(index) {
+            tcb.stack_policy = policy;
+        }
+        Ok(thread)
     }
 
-    pub fn terminate_thread(&mut self, thread: ThreadId) {
-        if let Ok(index) = self.locate_thread(thread) {
-            if let Some(tcb) = self.thread_table[index] {
-                let _ = self
-                    .mtss_scheduler
-                    .exit_thread(Self::mtss_thread_id(thread));
-                self.futexes.remove_thread(thread);
-                self.remove_thread_from_cores(thread);
-                self.thread_table[index] = None;
-                self.update_process_thread_count(tcb.process, false);
+    /// Copy the ids of `pid`'s threads into `out` in thread table order,
+    /// returning how many were written. Threads past `out.len()` are left
+    /// out.
+    pub fn threads_of(&self, pid: ProcessId, out: &mut [ThreadId]) -> KernelResult<usize> {
+        self.ensure_process_exists(pid)?;
+        let mut count = 0usize;
+        for tcb in self.thread_table.iter().flatten() {
+            if count == out.len() {
+                break;
+            }
+            if tcb.process == pid {
+                out[count] = tcb.id;
+                count += 1;
             }
         }
+        Ok(count)
     }
 
-    pub fn register_service(
-        &mut self,
-        authorizer: ProcessId,
-        service: RegistryServiceId,
-        owner: ProcessId,
-    ) -> KernelResult<()> {
-        self.security
-            .authorize_service_control(authorizer)
-            .map_err(KernelError::SecurityViolation)?;
-        self.security
-            .authorize_service_registration(owner, service.security_class())
-            .map_err(KernelError::SecurityViolation)?;
-        self.ensure_process_exists(owner)?;
-        self.service_registry
-            .register(service, owner)
-            .map_err(map_service_registry_error)
+    /// Summaries of every process in the table, copied in one pass. The
+    /// snapshot borrows nothing from the kernel, so monitors can hold it
+    /// across later spawns, exits and reaps.
+    pub fn snapshot_processes(&self) -> ProcessSnapshot<MAX_PROC> {
+        let mut snapshot = ProcessSnapshot::new();
+        for pcb in self.process_table.iter().flatten() {
+            snapshot.push(ProcessSummary::capture(pcb));
+        }
+        snapshot
     }
 
-    pub fn register_endpoint(
+    /// Declares `thread`'s stack as `stack_size` bytes below its initial
+    /// stack pointer with `guard_size` unmapped bytes beneath. Both must be
+    /// page multiples and the guard non-zero. Once set, the thread's stack
+    /// pointer is checked on every dispatch; see [`Kernel::check_stack`].
+    pub fn set_stack_layout(
         &mut self,
-        authorizer: ProcessId,
-        service: RegistryServiceId,
-        owner: ProcessId,
+        thread: ThreadId,
+        stack_size: usize,
+        guard_size: usize,
     ) -> KernelResult<()> {
-        self.register_service(authorizer, service, owner)
+        if !stack_size.is_multiple_of(memory::PAGE_SIZE)
+            || guard_size == 0
+            || !guard_size.is_multiple_of(memory::PAGE_SIZE)
+        {
+            return Err(KernelError::InvalidArgument);
+        }
+        let index = self.locate_thread(thread)?;
+        let tcb = self
+            .thread_table
+            .entry_mut(index)
+            .ok_or(KernelError::UnknownThread)?;
+        let reserved = stack_size
+            .checked_add(tcb.stack_grown as usize)
+            .and_then(|bytes| bytes.checked_add(guard_size))
+            .ok_or(KernelError::InvalidArgument)?;
+        if tcb.stack_pointer < reserved as u64 {
+            return Err(KernelError::InvalidArgument);
+        }
+        tcb.stack_size = stack_size;
+        tcb.stack_guard_size = guard_size;
+        Ok(())
     }
 
-    pub fn revoke_task(&mut self, pid: ProcessId) {
-        self.security.revoke_task(pid);
+    /// Fails with a `StackOverflow` isolation fault when `sp` is outside
+    /// `thread`'s stack: above its initial stack pointer, in the guard below
+    /// it, or further down still.
+    pub fn check_stack(&self, thread: ThreadId, sp: u64) -> KernelResult<()> {
+        let index = self.locate_thread(thread)?;
+        let tcb = self.thread_table[index].ok_or(KernelError::UnknownThread)?;
+        let base = tcb.stack_base().ok_or(KernelError::InvalidArgument)?;
+        if sp < base || sp > tcb.stack_pointer {
+            return Err(KernelError::IsolationFault(IsolationError::StackOverflow));
+        }
+        Ok(())
     }
 
-    pub fn grant_task_capability(
+    /// Like `spawn_thread`, but when the thread table is full the request is
+    /// queued and retried on later ticks as slots free up. Returns `None` when
+    /// the spawn was queued; fails with `ThreadTableFull` only when the
+    /// waitlist is full too.
+    pub fn spawn_thread_waitlisted(
         &mut self,
-        owner: ProcessId,
-        object: CapabilityObject,
-        rights: CapabilityRights,
-    ) -> KernelResult<CapabilityId> {
-        self.security
-            .grant_capability(owner, object, rights)
-            .map_err(KernelError::SecurityViolation)
+        pid: ProcessId,
+        entry_point: u64,
+        priority: ProcessPriority,
+    ) -> KernelResult<Option<ThreadId>> {
+        self.locate_process(pid)?;
+        let request = PendingThreadSpawn {
+            process: pid,
+            entry_point,
+            priority,
+        };
+        // Queue behind earlier waiters instead of taking a slot they are owed.
+        if self.thread_waitlist.is_empty() {
+            match self.spawn_thread(pid, entry_point, priority) {
+                Err(KernelError::ThreadTableFull) => {}
+                result => return result.map(Some),
+            }
+        }
+        if self.thread_waitlist.push(request) {
+            Ok(None)
+        } else {
+            Err(KernelError::ThreadTableFull)
+        }
     }
 
-    pub fn revoke_task_capability(&mut self, capability: CapabilityId) -> KernelResult<()> {
+    pub fn pending_thread_spawns(&self) -> usize {
+        self.thread_waitlist.len()
+    }
+
+    /// Create waitlisted threads, oldest first, until the table fills again.
+    /// Requests that fail for any other reason are dropped.
+    fn service_thread_waitlist(&mut self) -> usize {
+        let mut spawned = 0;
+        while let Some(request) = self.thread_waitlist.front() {
+            match self.spawn_thread(request.process, request.entry_point, request.priority) {
+                Err(KernelError::ThreadTableFull) => break,
+                Ok(_) => spawned += 1,
+                Err(_) => {}
+            }
+            self.thread_waitlist.pop_front();
+        }
+        spawned
+    }
+
+    /// Swap the credentials of a live process without restarting it, so it
+    /// keeps its PID, IPC queue and service registrations. The requester
+    /// needs `CAP_KERNEL` and a label dominating both the old and new labels.
+    ///
+    /// The new task domain replaces the old one in a single registration and
+    /// governs every later authorization, including sends from the target.
+    /// Messages already queued for the target were authorized when they were
+    /// sent and are delivered as-is. The target is told about the change with
+    /// a [`CREDENTIAL_UPDATE_NOTICE`] message when its queue accepts one.
+    pub fn update_credentials(
+        &mut self,
+        requester: ProcessId,
+        target: ProcessId,
+        new_creds: Credentials,
+    ) -> KernelResult<()> {
         self.security
-            .revoke_capability(capability)
-            .map_err(KernelError::SecurityViolation)
+            .authorize_kernel_control(requester)
+            .map_err(KernelError::SecurityViolation)?;
+        let requester_label = self
+            .security
+            .task_domain(requester)
+            .map_err(KernelError::SecurityViolation)?
+            .label();
+        let index = self.locate_process(target)?;
+        let old_label = self
+            .security
+            .task_domain(target)
+            .map_err(KernelError::SecurityViolation)?
+            .label();
+        if !requester_label.dominates(&old_label) || !requester_label.dominates(&new_creds.label())
+        {
+            return Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation,
+            ));
+        }
+
+        self.register_security_domain(target, new_creds)?;
+        if let Some(pcb) = self.process_table[index].as_mut() {
+            pcb.update_credentials(new_creds);
+        }
+
+        let notice = MessagePayload::from_slice(SecurityClass::Public, CREDENTIAL_UPDATE_NOTICE);
+        let notified = self.send_message(requester, target, notice).is_ok();
+        self.last_credential_audit = Some(CredentialAudit {
+            requester,
+            target,
+            old_label,
+            new_label: new_creds.label(),
+            tick: KERNEL_TIME.uptime_ticks(),
+            notified,
+        });
+        Ok(())
     }
 
-    pub fn revoke_task_capabilities(&mut self, owner: ProcessId) {
-        self.security.revoke_all_capabilities(owner);
+    pub fn last_credential_audit(&self) -> Option<CredentialAudit> {
+        self.last_credential_audit
     }
 
-    pub fn revoke_service_owner(&mut self, owner: ProcessId) {
-        self.service_registry.revoke_owner(owner);
+    /// Terminate `pid` once `ticks` more ticks of uptime have elapsed,
+    /// replacing any timeout already armed.
+    pub fn exec_timeout(&mut self, pid: ProcessId, ticks: u64) -> KernelResult<()> {
+        let index = self.locate_process(pid)?;
+        let pcb = self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?;
+        pcb.execution_deadline = Some(KERNEL_TIME.uptime_ticks().saturating_add(ticks));
+        Ok(())
     }
 
-    pub fn check_service_control_capability(&self, pid: ProcessId) -> KernelResult<()> {
-        self.security
-            .authorize_service_control(pid)
-            .map_err(KernelError::SecurityViolation)
+    pub fn cancel_exec_timeout(&mut self, pid: ProcessId) -> KernelResult<()> {
+        let index = self.locate_process(pid)?;
+        self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?
+            .execution_deadline = None;
+        Ok(())
     }
 
-    pub fn check_service_registration_capability(
-        &self,
-        owner: ProcessId,
-        service: RegistryServiceId,
-    ) -> KernelResult<()> {
-        self.security
-            .authorize_service_registration(owner, service.security_class())
-            .map_err(KernelError::SecurityViolation)
+    /// Terminate every live process whose execution deadline is at or before
+    /// `now`. Returns how many were terminated.
+    fn expire_exec_deadlines(&mut self, now: u64) -> usize {
+        let mut expired = 0;
+        let mut idx = 0;
+        while idx < MAX_PROC {
+            let mut timed_out = None;
+            if let Some(pcb) = self.process_table[idx].as_mut() {
+                if !matches!(pcb.state, ProcessState::Zombie | ProcessState::Terminated)
+                    && pcb
+                        .execution_deadline
+                        .is_some_and(|deadline| deadline <= now)
+                {
+                    pcb.execution_deadline = None;
+                    timed_out = Some(pcb.pid);
+                }
+            }
+            if let Some(pid) = timed_out {
+                crate::kprintln!("process {} exceeded its execution deadline", pid.raw());
+                self.terminate_process(pid);
+                expired += 1;
+            }
+            idx += 1;
+        }
+        expired
     }
 
-    pub fn service_owner(&self, service: RegistryServiceId) -> Option<ProcessId> {
-        self.service_registry.owner(service)
+    pub fn terminate_process(&mut self, pid: ProcessId) {
+        self.exit_process(pid, ExitStatus::signaled(SIGTERM));
     }
 
-    pub fn send_service_message(
+    /// Start tearing `pid` down without waiting for its threads to come off
+    /// their cores. Threads dispatched later in the current tick are reaped
+    /// instead of run, and the exit with `status` completes at the start of
+    /// the next tick.
+    pub fn begin_process_termination(
         &mut self,
-        sender: ProcessId,
-        service: RegistryServiceId,
-        payload: MessagePayload,
+        pid: ProcessId,
+        status: ExitStatus,
     ) -> KernelResult<()> {
-        let receiver = self
-            .service_registry
-            .owner(service)
+        let index = self.locate_process(pid)?;
+        let pcb = self.process_table[index]
+            .as_mut()
             .ok_or(KernelError::UnknownProcess)?;
-        self.send_message(sender, receiver, payload)
+        if matches!(
+            pcb.state,
+            ProcessState::Terminating | ProcessState::Zombie | ProcessState::Terminated
+        ) {
+            return Err(KernelError::InvalidArgument);
+        }
+        pcb.mark_terminating(status);
+        Ok(())
     }
 
-    pub fn claim_service_device(
+    /// Complete the exit of every process left in `Terminating`.
+    fn finish_terminations(&mut self) {
+        let mut idx = 0usize;
+        while idx < MAX_PROC {
+            let terminating = self.process_table[idx]
+                .as_ref()
+                .filter(|pcb| pcb.state == ProcessState::Terminating)
+                .map(|pcb| (pcb.pid, pcb.exit_status));
+            if let Some((pid, status)) = terminating {
+                self.exit_process(pid, status.unwrap_or(ExitStatus::signaled(SIGKILL)));
+            }
+            idx += 1;
+        }
+    }
+
+    pub fn exit_process(
         &mut self,
-        owner: ProcessId,
+        pid: ProcessId,
+        status: ExitStatus,
+    ) -> Option<ProcessExitReport> {
+        if let Ok(index) = self.locate_process(pid) {
+            if let Some(pcb) = self.process_table[index].as_ref() {
+                if pcb.state == ProcessState::Zombie {
+                    return None;
+                }
+            }
+            if let Some(mut pcb) = self.process_table[index].take() {
+                self.release_process_file_table(&mut pcb.files);
+                pcb.mark_zombie(status);
+                self.process_table[index] = Some(pcb);
+            }
+            self.ipc_queues[index].clear();
+            self.message_spins[index] = AdaptiveSpin::new();
+            self.ipc_redirects.remove_process(pid);
+            self.ipc_credits.remove_process(pid);
+            self.ipc_endpoints.remove_process(pid);
+            self.device_reads[index] = None;
+            self.send_retries[index] = None;
+            self.send_retry_results[index] = None;
+            self.thread_waitlist.remove_process(pid);
+            if self
+                .mtss_scheduler
+                .terminate_task(Self::mtss_task_id(pid))
+                .is_ok()
+            {
+                let _ = self.mtss_scheduler.reap_task(Self::mtss_task_id(pid));
+            }
+            self.remove_threads_for_process(pid);
+            self.release_arenas(|arena| arena.owner() == pid);
+            if self.kernel_task_pid == Some(pid) {
+                self.release_kernel_tasks();
+            }
+            memory::release_process(pid);
+            if let Ok(namespace) = self.security.ipc_namespace(pid) {
+                self.exited_pids.record(pid, namespace);
+            }
+            self.security.revoke_task(pid);
+            self.timers.release_process(pid);
+            self.futexes.remove_owner(self.futex_owner_for_process(pid));
+            let _ = self.queue_signal_to_parent(pid, SIGCHLD);
+            let _ = self.wake_parent_child_waiters(pid);
+            return Some(ProcessExitReport { pid, status });
+        }
+        None
+    }
+
+    /// Remove `thread` from MTSS, futex queues, the cores and the thread
+    /// table, charging the removal to its process if that still exists. MTSS
+    /// and core state are cleaned up even when the thread table has already
+    /// lost the entry.
+    pub fn terminate_thread(&mut self, thread: ThreadId) {
+        let _ = self
+            .mtss_scheduler
+            .exit_thread(Self::mtss_thread_id(thread));
+        let _ = self
+            .mtss_scheduler
+            .reap_thread(Self::mtss_thread_id(thread));
+        self.futexes.remove_thread(thread);
+        self.remove_thread_from_cores(thread);
+        if let Ok(index) = self.locate_thread(thread) {
+            if let Some(tcb) = self.thread_table[index] {
+                self.thread_table.remove_at(index);
+                self.update_process_thread_count(tcb.process, false);
+            }
+        }
+    }
+
+    pub fn register_service(
+        &mut self,
+        authorizer: ProcessId,
         service: RegistryServiceId,
-        device: DeviceId,
+        owner: ProcessId,
     ) -> KernelResult<()> {
-        let descriptor = self
-            .devices
-            .descriptor(device)
-            .ok_or(KernelError::DeviceNotFound)?;
         self.security
-            .authorize_device_access(
-                owner,
-                CapabilityObject::PciDevice(descriptor.id.raw() as u64),
-                CapabilityRight::Control,
-                descriptor.security,
-            )
+            .authorize_service_control(authorizer)
             .map_err(KernelError::SecurityViolation)?;
+        self.security
+            .authorize_service_registration(owner, service.security_class())
+            .map_err(KernelError::SecurityViolation)?;
+        self.ensure_process_exists(owner)?;
         self.service_registry
-            .claim_device(service, owner, descriptor)
+            .register(service, owner)
             .map_err(map_service_registry_error)
     }
 
-    pub fn release_service_device(
-        &mut self,
-        owner: ProcessId,
-        service: RegistryServiceId,
-        device: DeviceId,
-    ) -> KernelResult<()> {
-        self.service_registry
-            .release_device(service, owner, device)
-            .map_err(map_service_registry_error)
+    /// Publish `name` so clients can look `pid` up with
+    /// [`Kernel::resolve_endpoint`]. Names are unique kernel-wide and are
+    /// dropped when their owner exits.
+    pub fn register_endpoint(&mut self, pid: ProcessId, name: &str) -> KernelResult<()> {
+        self.ensure_process_exists(pid)?;
+        self.ipc_endpoints
+            .register(name, pid)
+            .map_err(|error| match error {
+                EndpointTableError::Full => KernelError::ProcessTableFull,
+                EndpointTableError::InvalidName | EndpointTableError::NameTaken => {
+                    KernelError::InvalidArgument
+                }
+            })
     }
 
-    pub fn send_message(
-        &mut self,
-        sender: ProcessId,
-        receiver: ProcessId,
-        payload: MessagePayload,
-    ) -> KernelResult<()> {
-        self.security
-            .authorize_ipc(sender, receiver, payload.security_class)
-            .map_err(KernelError::SecurityViolation)?;
+    pub fn resolve_endpoint(&self, name: &str) -> Option<ProcessId> {
+        self.ipc_endpoints.resolve(name)
+    }
 
-        let message = Message::new(sender, receiver, self.next_message_sequence(), payload);
-        let queue_index = self.locate_process(receiver)?;
-        self.ipc_queues[queue_index]
-            .push(message)
-            .map_err(|MessageQueueError::Full| KernelError::MessageQueueFull)?;
+    pub fn revoke_task(&mut self, pid: ProcessId) {
+        self.security.revoke_task(pid);
+    }
 
-        let mut wake_threads = false;
-        if let Some(pcb) = self.process_table[queue_index].as_ref() {
-            if pcb.state == ProcessState::Blocked {
-                self.set_process_ready_via_mtss(receiver, queue_index)?;
-                wake_threads = true;
+    /// Cross-check the process table against the security kernel: every live
+    /// process has a task domain, every domain belongs to a process, and no
+    /// process carries a label its domain does not dominate.
+    pub fn assert_security_invariants(&self) -> KernelResult<()> {
+        let mut idx = 0;
+        while idx < MAX_PROC {
+            if let Some(pcb) = self.process_table[idx].as_ref() {
+                if !matches!(pcb.state, ProcessState::Zombie | ProcessState::Terminated) {
+                    let domain = self
+                        .security
+                        .credentials(pcb.pid)
+                        .map_err(KernelError::SecurityViolation)?;
+                    if !domain.label().dominates(&pcb.security_label) {
+                        return Err(KernelError::SecurityViolation(
+                            IsolationError::PolicyViolation,
+                        ));
+                    }
+                }
             }
+            idx += 1;
         }
 
-        if wake_threads {
-            if let Err(err) = self.make_threads_ready(receiver) {
-                // Sending to a blocked process is transactional: if the wakeup cannot be
-                // scheduled, the receiver stays blocked and the just-enqueued message is
-                // removed so callers can retry without duplicating delivery.
-                let _ = self.set_process_blocked_via_mtss(receiver, queue_index);
-                let _ = self.ipc_queues[queue_index].rollback_last_push();
-                return Err(err);
+        for pid in self.security.registered_tasks() {
+            if self.locate_process(pid).is_err() {
+                return Err(KernelError::SecurityViolation(IsolationError::UnknownTask));
             }
         }
-
         Ok(())
     }
 
-    pub fn receive_message(&mut self, pid: ProcessId) -> KernelResult<Message> {
-        let queue_index = self.locate_process(pid)?;
-        self.ipc_queues[queue_index]
-            .pop()
-            .ok_or(KernelError::MessageQueueEmpty)
-    }
-
-    pub fn receive_or_block(&mut self, pid: ProcessId) -> KernelResult<Option<Message>> {
-        let queue_index = self.locate_process(pid)?;
-        if let Some(message) = self.ipc_queues[queue_index].pop() {
-            return Ok(Some(message));
-        }
-
-        self.block_process_at_index(pid, queue_index);
-        Ok(None)
-    }
-
-    pub fn block_for_message(&mut self, pid: ProcessId) {
-        if let Ok(index) = self.locate_process(pid) {
-            self.block_process_at_index(pid, index);
+    /// `assert_security_invariants` plus scheduler bookkeeping: every thread
+    /// queued in MTSS must still have a thread table entry, and thread
+    /// accounting must pass `check_thread_accounting`.
+    pub fn consistency_check(&self) -> KernelResult<()> {
+        self.assert_security_invariants()?;
+        match self
+            .mtss_scheduler
+            .find_queued_thread(|thread| self.locate_thread(ThreadId::new(thread.raw())).is_err())
+        {
+            Some(_) => Err(KernelError::UnknownThread),
+            None => self.check_thread_accounting(),
         }
     }
 
-    pub fn wait(&mut self, parent: ProcessId, status: Option<&mut i32>) -> KernelResult<ProcessId> {
-        let status_ptr = status.map(|out| out as *mut i32 as u64).unwrap_or(0);
-        self.wait_for_child(parent, None, -1, status_ptr, 0)
-            .map(ProcessId::new)
+    /// Run [`Self::cross_check_scheduler`] at the start of every tick. On by
+    /// default with the `scheduler-cross-check` feature.
+    pub fn set_scheduler_cross_check(&mut self, enabled: bool) {
+        self.scheduler_cross_check = enabled;
     }
 
-    pub fn waitpid(
-        &mut self,
-        parent: ProcessId,
-        pid: i64,
-        status: Option<&mut i32>,
-        options: u64,
-    ) -> KernelResult<ProcessId> {
-        let status_ptr = status.map(|out| out as *mut i32 as u64).unwrap_or(0);
-        self.wait_for_child(parent, None, pid, status_ptr, options)
-            .map(ProcessId::new)
+    /// Removes and returns the oldest recoverable assertion report.
+    pub fn take_assertion(&mut self) -> Option<RecoverableAssertion> {
+        self.assertions.take()
     }
 
-    pub fn queue_thread_syscall(
-        &mut self,
-        thread: ThreadId,
-        number: u64,
-        args: [u64; syscall::SYSCALL_MAX_ARGS],
-    ) -> KernelResult<()> {
-        let index = self.locate_thread(thread)?;
-        if let Some(tcb) = self.thread_table[index].as_mut() {
-            tcb.prepare_syscall(number, args);
-            Ok(())
-        } else {
-            Err(KernelError::UnknownThread)
-        }
+    pub fn assertions(&self) -> &AssertionRing<ASSERTION_RING_CAPACITY> {
+        &self.assertions
     }
 
-    pub fn thread_context(&self, thread: ThreadId) -> KernelResult<CpuContext> {
-        let index = self.locate_thread(thread)?;
-        self.thread_table[index]
-            .map(|tcb| tcb.context)
-            .ok_or(KernelError::UnknownThread)
-    }
+    /// Cross-checks MTSS against the thread and process tables and reports
+    /// each divergence to the assertion ring:
+    ///
+    /// - every run queue entry names a live, `Ready` thread, once;
+    /// - every `Ready` thread is queued, current on a core or in MTSS, or
+    ///   the pending dispatch decision;
+    /// - every live process's `thread_count` matches its table entries.
+    ///
+    /// Membership is tracked in per-slot maps and processes are found by
+    /// binary search, so the cost is linear in the table sizes. Returns the
+    /// number of divergences found.
+    pub fn cross_check_scheduler(&mut self) -> usize {
+        let tick = self.mtss_ticks;
+        let mut reports = FixedVec::<RecoverableAssertion, ASSERTION_RING_CAPACITY>::new();
+        let mut found = 0usize;
+        let mut report = |assertion| {
+            found += 1;
+            let _ = reports.push(assertion);
+        };
 
-    pub fn handle_syscall(&mut self, number: u64, context: SyscallContext) -> KernelResult<u64> {
-        match SyscallNumber::from_raw(number).ok_or(KernelError::InvalidSyscall)? {
-            SyscallNumber::GetPid => Ok(context.caller.raw()),
-            SyscallNumber::Spawn => self.syscall_spawn(context),
-            SyscallNumber::SendIpc => self.syscall_send_ipc(context),
-            SyscallNumber::ReceiveIpc => self.syscall_receive_ipc(context),
-            SyscallNumber::ReceiveOrBlockIpc => self.syscall_receive_or_block_ipc(context),
-            SyscallNumber::BlockForIpc => {
-                self.security
-                    .authorize_ipc_receive(context.caller)
-                    .map_err(KernelError::SecurityViolation)?;
-                self.block_for_message(context.caller);
-                Ok(0)
+        let mut queued = [false; MAX_THREADS];
+        for entry in self.mtss_scheduler.queued_thread_ids() {
+            let thread = ThreadId::new(entry.raw());
+            let Ok(index) = self.locate_thread(thread) else {
+                report(RecoverableAssertion::thread(
+                    AssertionCode::QueuedThreadMissing,
+                    tick,
+                    thread,
+                    None,
+                    None,
+                    "thread table",
+                ));
+                continue;
+            };
+            let Some(tcb) = self.thread_table[index] else {
+                continue;
+            };
+            if queued[index] {
+                report(RecoverableAssertion::thread(
+                    AssertionCode::QueuedThreadDuplicated,
+                    tick,
+                    thread,
+                    Some(tcb.process),
+                    Some(tcb.state),
+                    "one run queue entry",
+                ));
+                continue;
+            }
+            queued[index] = true;
+            if tcb.state != ThreadState::Ready {
+                report(RecoverableAssertion::thread(
+                    AssertionCode::QueuedThreadNotReady,
+                    tick,
+                    thread,
+                    Some(tcb.process),
+                    Some(tcb.state),
+                    "Ready",
+                ));
             }
-            SyscallNumber::EnumerateDevices => self.syscall_enumerate_devices(context),
-            SyscallNumber::DeviceInfo => self.syscall_device_info(context),
-            SyscallNumber::DeviceRead => self.syscall_device_read(context),
-            SyscallNumber::DeviceWrite => self.syscall_device_write(context),
-            SyscallNumber::Mmap => self.syscall_mmap(context),
-            SyscallNumber::Munmap => self.syscall_munmap(context),
-            SyscallNumber::Malloc => self.syscall_malloc(context),
-            SyscallNumber::Free => self.syscall_free(context),
-            SyscallNumber::Realloc => self.syscall_realloc(context),
-            SyscallNumber::MallocAligned => self.syscall_malloc_aligned(context),
-            SyscallNumber::OpenAt => self.syscall_openat(context),
-            SyscallNumber::Close => self.syscall_close(context),
-            SyscallNumber::Read => self.syscall_read(context),
-            SyscallNumber::Write => self.syscall_write(context),
-            SyscallNumber::Pread64 => self.syscall_pread64(context),
-            SyscallNumber::Pwrite64 => self.syscall_pwrite64(context),
-            SyscallNumber::Lseek => self.syscall_lseek(context),
-            SyscallNumber::Statx => self.syscall_statx(context),
-            SyscallNumber::NewFstatAt => self.syscall_newfstatat(context),
-            SyscallNumber::Getdents64 => self.syscall_getdents64(context),
-            SyscallNumber::MkdirAt => self.syscall_mkdirat(context),
-            SyscallNumber::UnlinkAt => self.syscall_unlinkat(context),
-            SyscallNumber::RenameAt2 => self.syscall_renameat2(context),
-            SyscallNumber::Ftruncate => self.syscall_ftruncate(context),
-            SyscallNumber::Fsync => self.syscall_fsync(context),
-            SyscallNumber::Mount => self.syscall_mount(context),
-            SyscallNumber::Chdir => self.syscall_chdir(context),
-            SyscallNumber::Fchdir => self.syscall_fchdir(context),
-            SyscallNumber::Getcwd => self.syscall_getcwd(context),
-            SyscallNumber::Faccessat => self.syscall_faccessat(context),
-            SyscallNumber::Fchmodat => self.syscall_fchmodat(context),
-            SyscallNumber::Fchownat => self.syscall_fchownat(context),
-            SyscallNumber::Symlinkat => self.syscall_symlinkat(context),
-            SyscallNumber::Readlinkat => self.syscall_readlinkat(context),
-            SyscallNumber::Linkat => self.syscall_linkat(context),
-            SyscallNumber::RegisterService => self.syscall_register_service(context),
-            SyscallNumber::SendServiceIpc => self.syscall_send_service_ipc(context),
-            SyscallNumber::ClaimDevice => self.syscall_claim_device(context),
-            SyscallNumber::ReleaseDevice => self.syscall_release_device(context),
-            SyscallNumber::Fork => self.syscall_fork(context),
-            SyscallNumber::Execve => self.syscall_execve(context),
-            SyscallNumber::Exit => self.syscall_exit(context),
-            SyscallNumber::Wait4 => self.syscall_wait4(context),
-            SyscallNumber::GetPpid => self.syscall_getppid(context),
-            SyscallNumber::SetPgid => self.syscall_setpgid(context),
-            SyscallNumber::Setsid => self.syscall_setsid(context),
-            SyscallNumber::GetUid => self.syscall_getuid(context),
-            SyscallNumber::GetEuid => self.syscall_geteuid(context),
-            SyscallNumber::SetUid => self.syscall_setuid(context),
-            SyscallNumber::GetGid => self.syscall_getgid(context),
-            SyscallNumber::SetGid => self.syscall_setgid(context),
-            SyscallNumber::GetGroups => self.syscall_getgroups(context),
-            SyscallNumber::SetGroups => self.syscall_setgroups(context),
-            SyscallNumber::RtSigaction => self.syscall_rt_sigaction(context),
-            SyscallNumber::RtSigprocmask => self.syscall_rt_sigprocmask(context),
-            SyscallNumber::Kill => self.syscall_kill(context),
-            SyscallNumber::RtSigreturn => self.syscall_rt_sigreturn(context),
-            SyscallNumber::ClockGettime => self.syscall_clock_gettime(context),
-            SyscallNumber::Nanosleep => self.syscall_nanosleep(context),
-            SyscallNumber::TimerCreate => self.syscall_timer_create(context),
-            SyscallNumber::TimerSettime => self.syscall_timer_settime(context),
-            SyscallNumber::TimerGettime => self.syscall_timer_gettime(context),
-            SyscallNumber::TimerDelete => self.syscall_timer_delete(context),
-            SyscallNumber::Dup => self.syscall_dup(context),
-            SyscallNumber::Dup2 => self.syscall_dup2(context),
-            SyscallNumber::Dup3 => self.syscall_dup3(context),
-            SyscallNumber::Fcntl => self.syscall_fcntl(context),
-            SyscallNumber::Ioctl => self.syscall_ioctl(context),
-            SyscallNumber::Pipe2 => self.syscall_pipe2(context),
-            SyscallNumber::Poll => self.syscall_poll(context),
-            SyscallNumber::Pselect => self.syscall_pselect(context),
-            SyscallNumber::Eventfd => self.syscall_eventfd(context),
-            SyscallNumber::Socket => self.syscall_socket(context),
-            SyscallNumber::Bind => self.syscall_bind(context),
-            SyscallNumber::Listen => self.syscall_listen(context),
-            SyscallNumber::Accept => self.syscall_accept(context),
-            SyscallNumber::Connect => self.syscall_connect(context),
-            SyscallNumber::Sendmsg => self.syscall_sendmsg(context),
-            SyscallNumber::Recvmsg => self.syscall_recvmsg(context),
-            SyscallNumber::Clone => self.syscall_clone(context),
-            SyscallNumber::Futex => self.syscall_futex(context),
-            SyscallNumber::SetThreadArea => self.syscall_set_thread_area(context),
-            SyscallNumber::ArchPrctl => self.syscall_arch_prctl(context),
-            SyscallNumber::Yield => self.syscall_yield(context),
         }
-    }
 
-    fn syscall_yield(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.ensure_process_exists(context.caller)?;
-        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
-        let thread_index = self.locate_thread(thread)?;
-        let tcb = self.thread_table[thread_index]
-            .as_mut()
-            .ok_or(KernelError::UnknownThread)?;
-        if tcb.process != context.caller {
-            return Err(KernelError::SecurityViolation(
-                IsolationError::PolicyViolation,
-            ));
+        let mut dispatched = [false; MAX_THREADS];
+        let on_cores = self.core_states.iter().map(|core| core.current_thread);
+        let current = self
+            .mtss_scheduler
+            .current()
+            .map(|thread| ThreadId::new(thread.raw()));
+        let pending = self.pending_mtss_decision.map(|decision| decision.thread);
+        for thread in on_cores.chain([current, pending]).flatten() {
+            if let Ok(index) = self.locate_thread(thread) {
+                dispatched[index] = true;
+            }
         }
-        if tcb.state == ThreadState::Terminated {
-            return Err(KernelError::UnknownThread);
+        let mut owners = FixedVec::<(ProcessId, usize), MAX_PROC>::new();
+        for (index, pcb) in self.process_table.iter().enumerate() {
+            if let Some(pcb) = pcb {
+                let _ = owners.push((pcb.pid, index));
+            }
         }
-        if tcb.state == ThreadState::Running {
-            tcb.mark_ready();
+        owners
+            .as_mut_slice()
+            .sort_unstable_by_key(|(pid, _)| pid.raw());
+        let mut counted = [0u32; MAX_PROC];
+        for (index, tcb) in self.thread_table.iter().enumerate() {
+            let Some(tcb) = tcb else {
+                continue;
+            };
+            if let Ok(owner) = owners
+                .as_slice()
+                .binary_search_by_key(&tcb.process.raw(), |(pid, _)| pid.raw())
+            {
+                counted[owners.as_slice()[owner].1] += 1;
+            }
+            if tcb.state == ThreadState::Ready && !queued[index] && !dispatched[index] {
+                report(RecoverableAssertion::thread(
+                    AssertionCode::ReadyThreadUnscheduled,
+                    tick,
+                    tcb.id,
+                    Some(tcb.process),
+                    Some(tcb.state),
+                    "run queue, a core or the pending dispatch",
+                ));
+            }
+        }
+        for (index, pcb) in self.process_table.iter().enumerate() {
+            let Some(pcb) = pcb else {
+                continue;
+            };
+            if !matches!(pcb.state, ProcessState::Zombie | ProcessState::Terminated)
+                && u32::from(pcb.thread_count) != counted[index]
+            {
+                report(RecoverableAssertion {
+                    code: AssertionCode::ThreadCountMismatch,
+                    tick,
+                    thread: None,
+                    process: Some(pcb.pid),
+                    found: None,
+                    expected: "thread_count equal to owned thread table entries",
+                    counts: Some((u32::from(pcb.thread_count), counted[index])),
+                });
+            }
         }
-        Ok(0)
-    }
 
-    fn syscall_fork(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.fork_task(context.caller, context.thread)
-            .map(|pid| pid.raw())
+        for assertion in reports.iter() {
+            self.assertions.push(*assertion);
+        }
+        found
     }
 
-    fn syscall_execve(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let resolved = self.resolve_user_path(context.caller, AT_FDCWD as u64, context.arg(0))?;
-        let path = resolved.as_path()?;
-        let stat = self.root_fs.stat(path).map_err(KernelError::Filesystem)?;
-        if stat.kind != InodeKind::RegularFile {
-            return Err(KernelError::Filesystem(VfsError::PermissionDenied));
+    /// Confirm `bootstrap` left the kernel ready for init: no queued or
+    /// tabled threads, no processes or leftover IPC and device state, core 0
+    /// online and the clock calibrated.
+    pub fn post_bootstrap_check(&self) -> KernelResult<()> {
+        if self.mtss_scheduler.queued_threads() != 0
+            || self.thread_table.iter().flatten().next().is_some()
+            || !self.thread_waitlist.is_empty()
+        {
+            return Err(KernelError::UnknownThread);
         }
-        if !crate::kernel::fs::Permissions::new(stat.mode, stat.uid, stat.gid).allows(
-            self.fs_credentials_for(context.caller)?,
-            AccessMode::Execute,
-        ) {
-            return Err(KernelError::Filesystem(VfsError::PermissionDenied));
+        let mut idx = 0;
+        while idx < MAX_PROC {
+            if self.process_table[idx].is_some()
+                || self.ipc_queues[idx].len() != 0
+                || self.device_reads[idx].is_some()
+            {
+                return Err(KernelError::UnknownProcess);
+            }
+            idx += 1;
         }
-
-        let argv = exec_vector_metadata(context.arg(1), MAX_EXEC_ARGS)?;
-        let envp = exec_vector_metadata(context.arg(2), MAX_EXEC_ENVS)?;
-        if argv.truncated || envp.truncated {
+        let core_zero_online = self.core_states.first().is_some_and(|core| core.online);
+        let clock_ready =
+            clock::HARDWARE_CLOCK.is_calibrated() && KERNEL_TIME.now().frequency() != 0;
+        if !core_zero_online || !clock_ready {
             return Err(KernelError::InvalidArgument);
         }
-        let image = self.load_exec_image(context.caller, &resolved, stat, argv, envp)?;
-        let request = ExecRequest::new(
-            context.caller,
-            ProcessPath::from_path(path),
-            argv,
-            envp,
-            decode_credentials(context.arg(5))?,
-            image,
-        );
-
-        self.exec_task(request, context.thread)?;
-        Ok(0)
+        Ok(())
     }
 
-    fn syscall_exit(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let status = ExitStatus::exited(context.arg(0) as i32);
-        self.exit_task(context.caller, status);
-
-        if let Some(thread) = context.thread {
-            let _ = self
-                .mtss_scheduler
-                .exit_thread(Self::mtss_thread_id(thread));
-            self.remove_thread_from_cores(thread);
-        }
-        let _ = self
-            .mtss_scheduler
-            .terminate_task(Self::mtss_task_id(context.caller));
-
-        if context.caller.raw() == 1 {
-            crate::kprintln!("SPIDER-RS PID1 [FAILED: exited unexpectedly]");
+    /// Every thread table entry belongs to a live process, and every running
+    /// process's `thread_count` matches the entries it owns.
+    fn check_thread_accounting(&self) -> KernelResult<()> {
+        for tcb in self.thread_table.iter().flatten() {
+            self.locate_process(tcb.process)?;
         }
-
-        Ok(0)
+        let mut idx = 0;
+        while idx < MAX_PROC {
+            if let Some(pcb) = self.process_table[idx].as_ref() {
+                let owned = self
+                    .thread_table
+                    .iter()
+                    .flatten()
+                    .filter(|tcb| tcb.process == pcb.pid)
+                    .count();
+                if !matches!(pcb.state, ProcessState::Zombie | ProcessState::Terminated)
+                    && owned != pcb.thread_count as usize
+                {
+                    return Err(KernelError::UnknownThread);
+                }
+            }
+            idx += 1;
+        }
+        Ok(())
     }
 
-    fn syscall_wait4(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.wait_for_child(
-            context.caller,
-            context.thread,
-            context.arg(0) as i64,
-            context.arg(1),
-            context.arg(2),
-        )
+    pub fn grant_task_capability(
+        &mut self,
+        owner: ProcessId,
+        object: CapabilityObject,
+        rights: CapabilityRights,
+    ) -> KernelResult<CapabilityId> {
+        self.security
+            .grant_capability(owner, object, rights)
+            .map_err(KernelError::SecurityViolation)
     }
 
-    fn syscall_getppid(&self, context: SyscallContext) -> KernelResult<u64> {
-        let index = self.locate_process(context.caller)?;
-        Ok(self.process_table[index]
-            .as_ref()
-            .and_then(|pcb| pcb.parent)
-            .map(|pid| pid.raw())
-            .unwrap_or(0))
+    pub fn revoke_task_capability(&mut self, capability: CapabilityId) -> KernelResult<()> {
+        self.security
+            .revoke_capability(capability)
+            .map_err(KernelError::SecurityViolation)
     }
 
-    fn syscall_setpgid(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let target = if context.arg(0) == 0 {
-            context.caller
-        } else {
-            ProcessId::new(context.arg(0))
-        };
-        let pgid = if context.arg(1) == 0 {
-            ProcessGroupId::new(target.raw())
-        } else {
-            ProcessGroupId::new(context.arg(1))
-        };
-        let caller_index = self.locate_process(context.caller)?;
-        let caller_session = self.process_table[caller_index]
-            .as_ref()
-            .ok_or(KernelError::UnknownProcess)?
-            .session;
-        let target_index = self.locate_process(target)?;
-        let target_pcb = self.process_table[target_index]
-            .as_mut()
-            .ok_or(KernelError::UnknownProcess)?;
-        if target != context.caller && target_pcb.parent != Some(context.caller) {
-            return Err(KernelError::InvalidArgument);
-        }
-        if target_pcb.session != caller_session {
-            return Err(KernelError::InvalidArgument);
-        }
-        target_pcb.set_process_group(pgid);
-        Ok(0)
+    pub fn revoke_task_capabilities(&mut self, owner: ProcessId) {
+        self.security.revoke_all_capabilities(owner);
     }
 
-    fn syscall_setsid(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let index = self.locate_process(context.caller)?;
-        let sid = SessionId::new(context.caller.raw());
-        let pgid = ProcessGroupId::new(context.caller.raw());
-        let pcb = self.process_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownProcess)?;
-        if pcb.process_group.raw() == context.caller.raw() {
-            return Err(KernelError::InvalidArgument);
-        }
-        pcb.set_session(sid);
-        pcb.set_process_group(pgid);
-        Ok(sid.raw())
+    pub fn revoke_service_owner(&mut self, owner: ProcessId) {
+        self.service_registry.revoke_owner(owner);
     }
 
-    fn syscall_getuid(&self, context: SyscallContext) -> KernelResult<u64> {
-        Ok(self.process_credentials(context.caller)?.uid as u64)
+    pub fn check_service_control_capability(&self, pid: ProcessId) -> KernelResult<()> {
+        self.security
+            .authorize_service_control(pid)
+            .map_err(KernelError::SecurityViolation)
     }
 
-    fn syscall_geteuid(&self, context: SyscallContext) -> KernelResult<u64> {
-        Ok(self.process_credentials(context.caller)?.euid as u64)
+    pub fn check_service_registration_capability(
+        &self,
+        owner: ProcessId,
+        service: RegistryServiceId,
+    ) -> KernelResult<()> {
+        self.security
+            .authorize_service_registration(owner, service.security_class())
+            .map_err(KernelError::SecurityViolation)
     }
 
-    fn syscall_getgid(&self, context: SyscallContext) -> KernelResult<u64> {
-        Ok(self.process_credentials(context.caller)?.gid as u64)
+    pub fn service_owner(&self, service: RegistryServiceId) -> Option<ProcessId> {
+        self.service_registry.owner(service)
     }
 
-    fn syscall_setuid(&mut self, context: SyscallContext) -> KernelResult<u64> {
+    pub fn send_service_message(
+        &mut self,
+        sender: ProcessId,
+        service: RegistryServiceId,
+        payload: MessagePayload,
+    ) -> KernelResult<()> {
+        let receiver = self
+            .service_registry
+            .owner(service)
+            .ok_or(KernelError::UnknownProcess)?;
+        self.send_message(sender, receiver, payload)
+    }
+
+    pub fn claim_service_device(
+        &mut self,
+        owner: ProcessId,
+        service: RegistryServiceId,
+        device: DeviceId,
+    ) -> KernelResult<()> {
+        let descriptor = self
+            .devices
+            .descriptor(device)
+            .ok_or(KernelError::DeviceNotFound)?;
         self.security
-            .authorize_credential_update(context.caller)
+            .authorize_device_access(
+                owner,
+                CapabilityObject::PciDevice(descriptor.id.raw() as u64),
+                CapabilityRight::Control,
+                descriptor.security,
+            )
             .map_err(KernelError::SecurityViolation)?;
-        let uid = u16::try_from(context.arg(0)).map_err(|_| KernelError::InvalidArgument)?;
-        self.process_credentials_mut(context.caller)?.set_uid(uid);
-        Ok(0)
+        self.service_registry
+            .claim_device(service, owner, descriptor)
+            .map_err(map_service_registry_error)
     }
 
-    fn syscall_setgid(&mut self, context: SyscallContext) -> KernelResult<u64> {
+    pub fn release_service_device(
+        &mut self,
+        owner: ProcessId,
+        service: RegistryServiceId,
+        device: DeviceId,
+    ) -> KernelResult<()> {
+        self.service_registry
+            .release_device(service, owner, device)
+            .map_err(map_service_registry_error)
+    }
+
+    pub fn send_message(
+        &mut self,
+        sender: ProcessId,
+        receiver: ProcessId,
+        payload: MessagePayload,
+    ) -> KernelResult<()> {
+        self.ensure_receiver_live(sender, receiver)?;
+        self.ensure_ipc_visible(sender, receiver)?;
         self.security
-            .authorize_credential_update(context.caller)
+            .authorize_ipc(sender, receiver, payload.security_class)
             .map_err(KernelError::SecurityViolation)?;
-        let gid = u16::try_from(context.arg(0)).map_err(|_| KernelError::InvalidArgument)?;
-        self.process_credentials_mut(context.caller)?.set_gid(gid);
-        Ok(0)
+
+        // Redirects are applied after authorization so the sender is checked
+        // against the receiver it addressed and never learns about the proxy.
+        let receiver = self.ipc_redirects.lookup(receiver).unwrap_or(receiver);
+        let message = Message::new(sender, receiver, self.next_message_sequence(), payload);
+        self.enqueue_message(message)
     }
 
-    fn syscall_getgroups(&self, context: SyscallContext) -> KernelResult<u64> {
-        let credentials = self.process_credentials(context.caller)?;
-        let group_count = credentials.supplementary_group_count();
-        if context.arg(0) == 0 {
-            return Ok(group_count as u64);
-        }
-        let capacity = context.arg(0) as usize;
-        if capacity < group_count {
-            return Err(KernelError::InvalidArgument);
-        }
-        let groups = user_slice_mut_typed::<u32>(context.arg(1), capacity)?;
-        let stored = credentials.supplementary_groups();
-        let mut idx = 0usize;
-        while idx < group_count {
-            groups[idx] = stored[idx] as u32;
-            idx += 1;
+    /// Send `payload` stamped with `declared_class` instead of trusting the
+    /// class the payload carries. The declared class may raise the payload's
+    /// class but never lower it, and the sender must be cleared for it, so
+    /// data cannot be relabelled down to reach less trusted receivers.
+    pub fn send_message_classified(
+        &mut self,
+        sender: ProcessId,
+        receiver: ProcessId,
+        mut payload: MessagePayload,
+        declared_class: SecurityClass,
+    ) -> KernelResult<()> {
+        if !declared_class
+            .as_label()
+            .dominates(&payload.security_class.as_label())
+        {
+            return Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation,
+            ));
         }
-        Ok(group_count as u64)
+        payload.security_class = declared_class;
+        self.send_message(sender, receiver, payload)
     }
 
-    fn syscall_setgroups(&mut self, context: SyscallContext) -> KernelResult<u64> {
+    /// Install a filter consulted for every message after the security check,
+    /// including forwarded ones. Returning `false` rejects the message with
+    /// `SecurityViolation`. Replaces any previously installed hook.
+    pub fn set_message_filter_hook(&mut self, hook: fn(&Message) -> bool) {
+        ipc::set_message_filter_hook(hook);
+    }
+
+    pub fn clear_message_filter_hook(&mut self) {
+        ipc::clear_message_filter_hook();
+    }
+
+    /// Deliver messages addressed to `from` to `to` instead. Requires
+    /// `CAP_KERNEL`; an existing redirect for `from` is replaced.
+    pub fn add_ipc_redirect(
+        &mut self,
+        actor: ProcessId,
+        from: ProcessId,
+        to: ProcessId,
+    ) -> KernelResult<()> {
         self.security
-            .authorize_credential_update(context.caller)
+            .authorize_kernel_control(actor)
             .map_err(KernelError::SecurityViolation)?;
-        let count = context.arg(0) as usize;
-        if count > MAX_SUPPLEMENTARY_GROUPS {
+        if from == to {
             return Err(KernelError::InvalidArgument);
         }
-        let user_groups = user_slice_typed::<u32>(context.arg(1), count)?;
-        let mut groups = [0u16; MAX_SUPPLEMENTARY_GROUPS];
-        let mut idx = 0usize;
-        while idx < count {
-            groups[idx] =
-                u16::try_from(user_groups[idx]).map_err(|_| KernelError::InvalidArgument)?;
-            idx += 1;
-        }
-        self.process_credentials_mut(context.caller)?
-            .set_supplementary_groups(&groups[..count])
-            .map_err(|_| KernelError::InvalidArgument)?;
-        Ok(0)
+        self.locate_process(from)?;
+        self.locate_process(to)?;
+        self.ipc_redirects
+            .insert(IpcRedirect { from, to })
+            .map_err(|RedirectTableError::Full| KernelError::ProcessTableFull)
     }
 
-    fn syscall_rt_sigaction(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let signal = context.arg(0) as usize;
-        if signal == 0 || signal > crate::kernel::process::MAX_SIGNAL_NUMBER {
-            return Err(KernelError::InvalidArgument);
-        }
-        let new_action = context.arg(1) as *const SignalAction;
-        let old_action = context.arg(2) as *mut SignalAction;
-        let index = self.locate_process(context.caller)?;
-        let pcb = self.process_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownProcess)?;
-        if !old_action.is_null() {
-            unsafe { old_action.write(pcb.signal_actions[signal]) };
-        }
-        if !new_action.is_null() {
-            pcb.signal_actions[signal] = unsafe { new_action.read() };
-        }
-        Ok(0)
+    /// Stop redirecting messages addressed to `from`, returning the previous target.
+    pub fn remove_ipc_redirect(&mut self, from: ProcessId) -> Option<ProcessId> {
+        self.ipc_redirects.remove(from).map(|redirect| redirect.to)
     }
 
-    fn syscall_rt_sigprocmask(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let how = context.arg(0);
-        let new_mask = context.arg(1) as *const SignalMask;
-        let old_mask = context.arg(2) as *mut SignalMask;
-        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
-        let index = self.locate_thread(thread)?;
-        let tcb = self.thread_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownThread)?;
-        if !old_mask.is_null() {
-            unsafe { old_mask.write(tcb.signal_mask) };
+    /// `send_message` that keeps trying while the receiver's queue is full.
+    /// The first attempt is made now; if it finds the queue full and
+    /// `max_attempts` allows more, later attempts are made from `tick`,
+    /// `backoff_ticks` apart at first and doubling each time. The outcome of
+    /// a deferred send is collected with [`Kernel::take_send_retry_result`]:
+    /// `MessageQueueFull` if every attempt failed. A sender has at most one
+    /// send outstanding.
+    pub fn send_message_retry(
+        &mut self,
+        sender: ProcessId,
+        receiver: ProcessId,
+        payload: MessagePayload,
+        max_attempts: u32,
+        backoff_ticks: u64,
+    ) -> KernelResult<SendRetryOutcome> {
+        if max_attempts == 0 {
+            return Err(KernelError::InvalidArgument);
         }
-        if !new_mask.is_null() {
-            let requested = unsafe { new_mask.read() };
-            let mask = match how {
-                0 => SignalMask::from_bits(tcb.signal_mask.bits() | requested.bits()),
-                1 => SignalMask::from_bits(tcb.signal_mask.bits() & !requested.bits()),
-                2 => requested,
-                _ => return Err(KernelError::InvalidArgument),
-            };
-            tcb.set_signal_mask(mask);
+        let index = self.locate_process(sender)?;
+        if self.send_retries[index].is_some() {
+            return Err(KernelError::InvalidArgument);
+        }
+        match self.send_message(sender, receiver, payload) {
+            Ok(()) => Ok(SendRetryOutcome::Delivered),
+            Err(KernelError::MessageQueueFull) if max_attempts > 1 => {
+                self.send_retry_results[index] = None;
+                self.send_retries[index] = Some(RetrySend::after_first_attempt(
+                    receiver,
+                    payload,
+                    max_attempts,
+                    backoff_ticks,
+                    self.mtss_ticks,
+                ));
+                Ok(SendRetryOutcome::Pending)
+            }
+            Err(error) => Err(error),
         }
-        Ok(0)
     }
 
-    fn syscall_kill(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.send_signal(context.arg(0) as i64, context.arg(1) as u8)
+    /// Result of `sender`'s last deferred `send_message_retry`, once it has
+    /// either been delivered or run out of attempts.
+    pub fn take_send_retry_result(&mut self, sender: ProcessId) -> Option<KernelResult<()>> {
+        let index = self.locate_process(sender).ok()?;
+        self.send_retry_results[index].take()
     }
 
-    fn syscall_rt_sigreturn(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
-        let index = self.locate_thread(thread)?;
-        self.thread_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownThread)?
-            .finish_signal();
-        Ok(0)
+    /// Re-attempt every retried send whose backoff has elapsed.
+    fn retry_pending_sends(&mut self) {
+        let now = self.mtss_ticks;
+        let mut idx = 0;
+        while idx < MAX_PROC {
+            let due = self.send_retries[idx].filter(|retry| retry.is_due(now));
+            let sender = self.process_table[idx].as_ref().map(|pcb| pcb.pid);
+            if let (Some(mut retry), Some(sender)) = (due, sender) {
+                match self.send_message(sender, retry.receiver, retry.payload) {
+                    Err(KernelError::MessageQueueFull) if retry.record_failure(now) => {
+                        self.send_retries[idx] = Some(retry);
+                    }
+                    result => {
+                        self.send_retries[idx] = None;
+                        self.send_retry_results[idx] = Some(result);
+                    }
+                }
+            }
+            idx += 1;
+        }
     }
 
-    fn syscall_clock_gettime(&self, context: SyscallContext) -> KernelResult<u64> {
-        let out = user_out_ptr::<MirageTimespec>(context.arg(1))?;
-        let nanos = KERNEL_TIME.now().as_nanos();
-        unsafe {
-            out.write(MirageTimespec {
-                tv_sec: (nanos / 1_000_000_000) as i64,
-                tv_nsec: (nanos % 1_000_000_000) as i64,
-            });
+    /// Re-send a received message to `new_receiver` without rebuilding its
+    /// payload. Delivery is authorized under the forwarder's domain; the
+    /// receiver sees the forwarder as `sender` and the first sender as
+    /// `original_sender`.
+    pub fn forward_message(
+        &mut self,
+        forwarder: ProcessId,
+        original: Message,
+        new_receiver: ProcessId,
+    ) -> KernelResult<()> {
+        if original.receiver != forwarder {
+            return Err(KernelError::InvalidArgument);
         }
-        Ok(0)
+        self.ensure_ipc_visible(forwarder, new_receiver)?;
+        self.security
+            .authorize_ipc(forwarder, new_receiver, original.payload.security_class)
+            .map_err(KernelError::SecurityViolation)?;
+
+        let mut message = Message::new(
+            forwarder,
+            new_receiver,
+            self.next_message_sequence(),
+            original.payload,
+        );
+        message.original_sender = Some(original.origin());
+        self.enqueue_message(message)
     }
 
-    fn syscall_nanosleep(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let requested = read_user_value::<MirageTimespec>(context.arg(0))?;
-        if context.arg(1) != 0 {
-            let _ = user_out_ptr::<MirageTimespec>(context.arg(1))?;
+    fn enqueue_message(&mut self, message: Message) -> KernelResult<()> {
+        #[cfg(feature = "latency-metrics")]
+        let message = Message {
+            sent_tick: self.mtss_ticks,
+            ..message
+        };
+        if !ipc::message_filter_allows(&message) {
+            return Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation,
+            ));
         }
-        let duration_ns = timespec_to_nanos(requested)?;
-        if duration_ns == 0 {
-            return Ok(0);
+        let receiver = message.receiver;
+        let queue_index = self.locate_process(receiver)?;
+        if let Some(credits) = self.ipc_credits.get(message.sender, receiver) {
+            if credits.balance == 0 {
+                return Err(KernelError::NoCredits);
+            }
         }
+        self.ipc_queues[queue_index]
+            .push(message)
+            .map_err(|error| match error {
+                MessageQueueError::Full => KernelError::MessageQueueFull,
+                MessageQueueError::ClassQuotaExceeded => KernelError::ClassQuotaExceeded,
+                MessageQueueError::Corrupted { .. } => KernelError::MessageCorrupted,
+            })?;
 
-        let wake_deadline = KERNEL_TIME.now().as_nanos().saturating_add(duration_ns);
-        self.timers
-            .add_sleep(context.caller, context.thread, wake_deadline)
-            .map_err(map_timer_error)?;
-        let process_index = self.locate_process(context.caller)?;
-        self.block_process_at_index(context.caller, process_index);
+        let mut wake_threads = false;
+        if let Some(pcb) = self.process_table[queue_index].as_ref() {
+            if pcb.state == ProcessState::Blocked {
+                self.set_process_ready_via_mtss(receiver, queue_index)?;
+                wake_threads = true;
+            }
+        }
 
-        if context.arg(1) != 0 {
-            write_user_value(
-                context.arg(1),
-                MirageTimespec {
-                    tv_sec: 0,
-                    tv_nsec: 0,
-                },
-            )?;
+        if wake_threads {
+            if let Err(err) = self.make_threads_ready(receiver) {
+                // Sending to a blocked process is transactional: if the wakeup cannot be
+                // scheduled, the receiver stays blocked and the just-enqueued message is
+                // removed so callers can retry without duplicating delivery.
+                let _ = self.set_process_blocked_via_mtss(receiver, queue_index);
+                let _ = self.ipc_queues[queue_index].rollback_last_push();
+                return Err(err);
+            }
         }
+        self.message_spins[queue_index].finish(self.mtss_ticks);
 
-        Ok(0)
+        if let Some(credits) = self.ipc_credits.get_mut(message.sender, receiver) {
+            credits.balance -= 1;
+        }
+        Ok(())
     }
 
-    fn syscall_timer_create(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.ensure_process_exists(context.caller)?;
-        let timer_id_out = context.arg(2);
-        let _ = user_out_ptr::<u64>(timer_id_out)?;
-        let id = self
-            .timers
-            .create_timer(context.caller)
-            .map_err(map_timer_error)?;
-        write_user_value(timer_id_out, id)?;
-        Ok(0)
+    /// Give `sender` `count` more credits for messages to `receiver`. The
+    /// first grant puts the pair under flow control: from then on each send
+    /// spends a credit and fails with `NoCredits` once the balance is zero.
+    pub fn grant_credits(
+        &mut self,
+        receiver: ProcessId,
+        sender: ProcessId,
+        count: u32,
+    ) -> KernelResult<u32> {
+        self.locate_process(receiver)?;
+        self.locate_process(sender)?;
+        self.ipc_credits
+            .grant(sender, receiver, count)
+            .map_err(|CreditTableError::Full| KernelError::ProcessTableFull)
+    }
+
+    /// Return a credit to `sender` whenever `receiver` dequeues one of its
+    /// messages, keeping a pipeline of the granted depth topped up. The pair
+    /// must already be under flow control.
+    pub fn set_credit_auto_regrant(
+        &mut self,
+        receiver: ProcessId,
+        sender: ProcessId,
+        enabled: bool,
+    ) -> KernelResult<()> {
+        let credits = self
+            .ipc_credits
+            .get_mut(sender, receiver)
+            .ok_or(KernelError::InvalidArgument)?;
+        credits.auto_regrant = enabled;
+        Ok(())
     }
 
-    fn syscall_timer_settime(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        const TIMER_ABSTIME: u64 = 1;
-
-        let timer_id = context.arg(0);
-        let flags = context.arg(1);
-        if flags & !TIMER_ABSTIME != 0 {
+    /// Cap how many messages of `class` can wait in `receiver`'s queue, or
+    /// lift the cap with `None`; sends over the cap fail with
+    /// `ClassQuotaExceeded`. Only the receiver itself or a `CAP_KERNEL` task
+    /// may change its quotas.
+    pub fn set_class_quota(
+        &mut self,
+        requester: ProcessId,
+        receiver: ProcessId,
+        class: SecurityClass,
+        max_slots: Option<usize>,
+    ) -> KernelResult<()> {
+        if requester != receiver {
+            self.security
+                .authorize_kernel_control(requester)
+                .map_err(KernelError::SecurityViolation)?;
+        }
+        if max_slots.is_some_and(|slots| slots > MSG_DEPTH) {
             return Err(KernelError::InvalidArgument);
         }
+        let queue_index = self.locate_process(receiver)?;
+        self.ipc_queues[queue_index].set_class_quota(class, max_slots);
+        Ok(())
+    }
 
-        let requested = read_user_value::<MirageItimerspec>(context.arg(2))?;
-        let old_value_out = context.arg(3);
-        if old_value_out != 0 {
-            let _ = user_out_ptr::<MirageItimerspec>(old_value_out)?;
-        }
-        let value_ns = timespec_to_nanos(requested.it_value)?;
-        let interval_ns = timespec_to_nanos(requested.it_interval)?;
-        let now_ns = KERNEL_TIME.now().as_nanos();
-        let deadline = if value_ns == 0 {
-            None
-        } else if flags & TIMER_ABSTIME != 0 {
-            Some(value_ns)
-        } else {
-            Some(now_ns.saturating_add(value_ns))
-        };
+    /// Messages of `class` waiting for `pid`, with the class's quota.
+    pub fn queue_class_occupancy(
+        &self,
+        pid: ProcessId,
+        class: SecurityClass,
+    ) -> KernelResult<ClassOccupancy> {
+        let queue_index = self.locate_process(pid)?;
+        Ok(self.ipc_queues[queue_index].class_occupancy(class))
+    }
 
-        let previous = self
-            .timers
-            .set_timer(context.caller, timer_id, deadline, interval_ns)
-            .map_err(map_timer_error)?;
+    /// Enqueued, dequeued and dropped totals for `pid`'s receive queue.
+    pub fn queue_stats(&self, pid: ProcessId) -> KernelResult<QueueStats> {
+        let queue_index = self.locate_process(pid)?;
+        Ok(self.ipc_queues[queue_index].stats())
+    }
 
-        if old_value_out != 0 {
-            write_user_value(old_value_out, timer_to_itimerspec(previous, now_ns))?;
+    /// Credits `sender` has left for `receiver`, or `None` when the pair is
+    /// not flow controlled.
+    pub fn credit_balance(&self, sender: ProcessId, receiver: ProcessId) -> Option<u32> {
+        self.ipc_credits
+            .get(sender, receiver)
+            .map(|credits| credits.balance)
+    }
+
+    /// Copy `pid`'s pending messages into `out` in wire format, oldest first,
+    /// without dequeuing them. Returns the number of bytes written; fails
+    /// with `InvalidArgument` rather than truncating if `out` is too small.
+    pub fn export_queue(&self, pid: ProcessId, out: &mut [u8]) -> KernelResult<usize> {
+        let queue_index = self.locate_process(pid)?;
+        let queue = &self.ipc_queues[queue_index];
+        let needed = queue.len() * MESSAGE_WIRE_SIZE;
+        if out.len() < needed {
+            return Err(KernelError::InvalidArgument);
         }
+        let mut offset = 0;
+        while offset < queue.len() {
+            if let Some(message) = queue.peek_at(offset) {
+                let start = offset * MESSAGE_WIRE_SIZE;
+                message
+                    .encode(&mut out[start..start + MESSAGE_WIRE_SIZE])
+                    .map_err(|_| KernelError::InvalidArgument)?;
+            }
+            offset += 1;
+        }
+        Ok(needed)
+    }
 
-        Ok(0)
+    /// Inject one wire-format message as if its encoded sender had called
+    /// `send_message`; authorization, redirects, and credits all apply and
+    /// the message gets a fresh local sequence number.
+    pub fn import_message(&mut self, bytes: &[u8]) -> KernelResult<()> {
+        let message = Message::decode(bytes).map_err(|_| KernelError::InvalidArgument)?;
+        self.send_message(message.sender, message.receiver, message.payload)
     }
 
-    fn syscall_timer_gettime(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let _ = user_out_ptr::<MirageItimerspec>(context.arg(1))?;
-        let timer = self
-            .timers
-            .timer(context.caller, context.arg(0))
-            .map_err(map_timer_error)?;
-        write_user_value(
-            context.arg(1),
-            timer_to_itimerspec(timer, KERNEL_TIME.now().as_nanos()),
-        )?;
-        Ok(0)
+    fn note_message_received(&mut self, message: &Message) {
+        if let Some(credits) = self.ipc_credits.get_mut(message.sender, message.receiver) {
+            if credits.auto_regrant {
+                credits.balance = credits.balance.saturating_add(1);
+            }
+        }
     }
 
-    fn syscall_timer_delete(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.timers
-            .delete_timer(context.caller, context.arg(0))
-            .map_err(map_timer_error)?;
-        Ok(0)
+    /// Checksum every IPC message while it sits in a receive queue, so a
+    /// stray write into queue memory surfaces as
+    /// [`KernelError::MessageCorrupted`] instead of a silently altered
+    /// payload. Applies to messages queued from now on.
+    pub fn set_ipc_checksums(&mut self, enabled: bool) {
+        for queue in self.ipc_queues.iter_mut() {
+            queue.set_checksums(enabled);
+        }
     }
 
-    fn syscall_dup(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.duplicate_fd(
-            context.caller,
-            context.arg(0) as usize,
-            0,
-            DescriptorFlags::EMPTY,
-        )
+    fn pop_message(&mut self, queue_index: usize) -> KernelResult<Option<Message>> {
+        let popped = self.ipc_queues[queue_index].pop();
+        #[cfg(feature = "latency-metrics")]
+        if let Ok(Some(message)) = &popped {
+            self.latency
+                .ipc_delivery
+                .record(self.mtss_ticks.saturating_sub(message.sent_tick));
+        }
+        popped.map_err(|error| {
+            if let MessageQueueError::Corrupted { sequence } = error {
+                crate::kprintln!(
+                    "ipc queue {} dropped corrupted message {}",
+                    queue_index,
+                    sequence
+                );
+            }
+            KernelError::MessageCorrupted
+        })
     }
 
-    fn syscall_dup2(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.duplicate_fd_to(
-            context.caller,
-            context.arg(0) as usize,
-            context.arg(1) as usize,
-            DescriptorFlags::EMPTY,
-            false,
-        )
+    pub fn receive_message(&mut self, pid: ProcessId) -> KernelResult<Message> {
+        let queue_index = self.locate_process(pid)?;
+        let message = self
+            .pop_message(queue_index)?
+            .ok_or(KernelError::MessageQueueEmpty)?;
+        self.note_message_received(&message);
+        Ok(message)
     }
 
-    fn syscall_dup3(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let flags = context.arg(2);
-        if flags & !O_CLOEXEC_RAW != 0 || context.arg(0) == context.arg(1) {
-            return Err(KernelError::InvalidArgument);
+    pub fn receive_or_block(&mut self, pid: ProcessId) -> KernelResult<Option<Message>> {
+        let queue_index = self.locate_process(pid)?;
+        if let Some(message) = self.pop_message(queue_index)? {
+            self.note_message_received(&message);
+            return Ok(Some(message));
         }
-        let descriptor_flags = if flags & O_CLOEXEC_RAW != 0 {
-            DescriptorFlags::CLOSE_ON_EXEC
-        } else {
-            DescriptorFlags::EMPTY
+
+        self.park_process_at_index(pid, queue_index);
+        Ok(None)
+    }
+
+    /// Park `pid` until a message arrives. Returns `false` without blocking
+    /// when the process already has [`pending_work`](Self::pending_work),
+    /// since the wakeup for that work may already have been delivered.
+    ///
+    /// With a spin window set by [`Self::set_message_spin`], the process
+    /// first stays runnable for up to that many ticks and only blocks if no
+    /// message has arrived by then. Returns `true` while it spins.
+    pub fn block_for_message(&mut self, pid: ProcessId) -> bool {
+        let Ok(index) = self.locate_process(pid) else {
+            return false;
         };
-        self.duplicate_fd_to(
-            context.caller,
-            context.arg(0) as usize,
-            context.arg(1) as usize,
-            descriptor_flags,
-            true,
-        )
+        if self.pending_work(pid).is_ok_and(|work| !work.is_empty()) {
+            return false;
+        }
+        if self.message_spins[index].begin(self.mtss_ticks) {
+            return true;
+        }
+        self.park_process_at_index(pid, index)
     }
 
-    fn syscall_fcntl(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let fd = context.arg(0) as usize;
-        match context.arg(1) {
-            F_DUPFD => self.duplicate_fd(
-                context.caller,
-                fd,
-                context.arg(2) as usize,
-                DescriptorFlags::EMPTY,
-            ),
-            F_DUPFD_CLOEXEC => self.duplicate_fd(
-                context.caller,
-                fd,
-                context.arg(2) as usize,
-                DescriptorFlags::CLOSE_ON_EXEC,
-            ),
-            F_GETFD => Ok(self
-                .process_files(context.caller)?
-                .get(fd)
-                .map_err(map_process_file_table_error)?
-                .flags()
-                .bits() as u64),
-            F_SETFD => {
-                let flags = if context.arg(2) & FD_CLOEXEC != 0 {
-                    DescriptorFlags::CLOSE_ON_EXEC
-                } else {
-                    DescriptorFlags::EMPTY
+    /// Let `pid` spin for up to `max_cycles` scheduler ticks in
+    /// [`Self::block_for_message`] before blocking. The window then tunes
+    /// itself to recent wait times; zero turns spinning off.
+    pub fn set_message_spin(&mut self, pid: ProcessId, max_cycles: u32) -> KernelResult<()> {
+        let index = self.locate_process(pid)?;
+        self.message_spins[index].configure(max_cycles);
+        Ok(())
+    }
+
+    pub fn message_spin(&self, pid: ProcessId) -> KernelResult<AdaptiveSpin> {
+        let index = self.locate_process(pid)?;
+        Ok(self.message_spins[index])
+    }
+
+    /// Spend a cycle of every spinning receiver's window, blocking those
+    /// whose window has run out.
+    fn spin_message_waiters(&mut self) {
+        let mut index = 0usize;
+        while index < MAX_PROC {
+            if self.message_spins[index].spin_cycle() {
+                let Some(pid) = self.process_table[index].as_ref().map(|pcb| pcb.pid) else {
+                    index += 1;
+                    continue;
                 };
-                self.process_files_mut(context.caller)?
-                    .get_mut(fd)
-                    .map_err(map_process_file_table_error)?
-                    .set_flags(flags);
-                Ok(0)
+                // A decision taken while it spun must not dispatch it again.
+                if self.park_process_at_index(pid, index)
+                    && self
+                        .pending_mtss_decision
+                        .is_some_and(|decision| decision.process == pid)
+                {
+                    self.pending_mtss_decision = None;
+                }
             }
-            F_GETFL => {
-                let description = self.fd_description(context.caller, fd)?;
-                Ok(self
-                    .open_files
-                    .get(description)
-                    .map_err(map_file_table_error)?
-                    .flags()
-                    .bits() as u64)
-            }
-            _ => Err(KernelError::InvalidArgument),
+            index += 1;
         }
     }
 
-    fn syscall_ioctl(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let fd = context.arg(0) as usize;
-        let request = context.arg(1);
-        let arg = context.arg(2);
-        let object = self.fd_object(context.caller, fd)?;
-        match request {
-            FIONREAD => {
-                let out = user_out_ptr::<u32>(arg)?;
-                let available = match object {
-                    DescriptorObject::Regular(file) => {
-                        let stat = self.root_fs.fstat(&file).map_err(KernelError::Filesystem)?;
-                        stat.size.saturating_sub(file.cursor()).min(u32::MAX as u64) as u32
-                    }
-                    DescriptorObject::Pipe(endpoint) => self
-                        .pipes
-                        .get(endpoint.id().raw())
-                        .and_then(|entry| *entry)
-                        .map(|pipe| pipe.len as u32)
-                        .ok_or(KernelError::InvalidArgument)?,
-                    DescriptorObject::EventFd(id) => self
-                        .eventfds
-                        .get(id.raw())
-                        .and_then(|entry| *entry)
-                        .map(|eventfd| if eventfd.counter > 0 { 8 } else { 0 })
-                        .ok_or(KernelError::InvalidArgument)?,
-                    DescriptorObject::Device(_)
-                    | DescriptorObject::Socket(_)
-                    | DescriptorObject::Console(_) => 0,
-                };
-                unsafe { out.write(available) };
-                Ok(0)
-            }
-            BLKSSZGET => match object {
-                DescriptorObject::Device(handle) => {
-                    let out = user_out_ptr::<u32>(arg)?;
-                    let value = self
-                        .devices
-                        .sector_size(handle.id())
-                        .map_err(KernelError::DeviceFault)? as u32;
-                    unsafe { out.write(value) };
-                    Ok(0)
-                }
-                _ => Err(KernelError::InvalidArgument),
-            },
-            BLKGETSIZE64 => match object {
-                DescriptorObject::Device(handle) => {
-                    let out = user_out_ptr::<u64>(arg)?;
-                    let size = self
-                        .devices
-                        .sector_size(handle.id())
-                        .and_then(|sector_size| {
-                            self.devices
-                                .sector_count(handle.id())
-                                .map(|sectors| sectors.saturating_mul(sector_size as u64))
-                        })
-                        .map_err(KernelError::DeviceFault)?;
-                    unsafe { out.write(size) };
-                    Ok(0)
-                }
-                _ => Err(KernelError::InvalidArgument),
-            },
-            MIRAGE_IOCTL_DEVICE_INFO => match object {
-                DescriptorObject::Device(handle) => {
-                    let out = user_out_ptr::<MirageDeviceDescriptor>(arg)?;
-                    let descriptor = self
-                        .device_info(handle.id())
-                        .ok_or(KernelError::DeviceNotFound)?;
-                    unsafe { out.write(MirageDeviceDescriptor::from_descriptor(descriptor)) };
-                    Ok(0)
-                }
-                _ => Err(KernelError::InvalidArgument),
-            },
-            _ => Err(KernelError::InvalidArgument),
-        }
-    }
+    pub fn pending_work(&self, pid: ProcessId) -> KernelResult<PendingWork> {
+        let index = self.locate_process(pid)?;
+        let pcb = self.process_table[index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?;
 
-    fn syscall_pipe2(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let out = user_slice_mut_typed::<i32>(context.arg(0), 2)?;
-        let flags = context.arg(1);
-        if flags & !PIPE2_SUPPORTED_FLAGS != 0 {
-            return Err(KernelError::InvalidArgument);
-        }
-        let pipe_id = self.allocate_pipe()?;
-        let descriptor_flags = descriptor_flags_from_raw(flags);
-        let read_description = self
-            .open_files
-            .insert_object(DescriptorObject::Pipe(PipeEndpoint::new(
-                pipe_id,
-                PipeDirection::Read,
-            )))
-            .map_err(map_file_table_error)?;
-        let write_description =
-            match self
-                .open_files
-                .insert_object(DescriptorObject::Pipe(PipeEndpoint::new(
-                    pipe_id,
-                    PipeDirection::Write,
-                ))) {
-                Ok(description) => description,
-                Err(error) => {
-                    let _ = self.close_open_description(read_description);
-                    self.pipes[pipe_id.raw()] = None;
-                    return Err(map_file_table_error(error));
-                }
-            };
-        let read_fd = match self
-            .process_files_mut(context.caller)?
-            .open(read_description, descriptor_flags)
-        {
-            Ok(fd) => fd,
-            Err(error) => {
-                let _ = self.close_open_description(read_description);
-                let _ = self.close_open_description(write_description);
-                self.pipes[pipe_id.raw()] = None;
-                return Err(map_process_file_table_error(error));
-            }
-        };
-        let write_fd = match self
-            .process_files_mut(context.caller)?
-            .open(write_description, descriptor_flags)
-        {
-            Ok(fd) => fd,
-            Err(error) => {
-                let closed_read = self
-                    .process_files_mut(context.caller)
-                    .ok()
-                    .and_then(|files| files.close(read_fd).ok());
-                if let Some(descriptor) = closed_read {
-                    let _ = self.close_open_description(descriptor.description());
+        let mut blocked_everywhere = None;
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(tcb) = self.thread_table[idx] {
+                if tcb.process == pid && tcb.state != ThreadState::Terminated {
+                    let bits = blocked_everywhere.unwrap_or(u64::MAX);
+                    blocked_everywhere = Some(bits & tcb.signal_mask.bits());
                 }
-                let _ = self.close_open_description(write_description);
-                self.pipes[pipe_id.raw()] = None;
-                return Err(map_process_file_table_error(error));
             }
-        };
-        out[0] = read_fd as i32;
-        out[1] = write_fd as i32;
-        Ok(0)
+            idx += 1;
+        }
+        let mask = SignalMask::from_bits(blocked_everywhere.unwrap_or(0));
+
+        let lifecycle_events = self
+            .process_table
+            .iter()
+            .flatten()
+            .filter(|child| child.parent == Some(pid) && child.state == ProcessState::Zombie)
+            .count();
+
+        Ok(PendingWork {
+            messages: self.ipc_queues[index].len(),
+            expired_timers: self.timers.expired_for(pid, KERNEL_TIME.now().as_nanos()),
+            signals: pcb.pending_signals.count_unmasked(mask),
+            lifecycle_events,
+        })
     }
 
-    fn syscall_poll(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let fds = user_slice_mut_typed::<MiragePollFd>(context.arg(0), context.arg(1) as usize)?;
-        let timeout_ms = context.arg(2) as i32;
-        self.poll_descriptors(context.caller, context.thread, fds, timeout_ms)
+    pub fn wait(&mut self, parent: ProcessId, status: Option<&mut i32>) -> KernelResult<ProcessId> {
+        let status_ptr = status.map(|out| out as *mut i32 as u64).unwrap_or(0);
+        self.wait_for_child(parent, None, -1, status_ptr, 0)
+            .map(ProcessId::new)
     }
 
-    fn syscall_pselect(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let fds = user_slice_mut_typed::<MiragePollFd>(context.arg(0), context.arg(1) as usize)?;
-        let timeout_ms = if context.arg(2) == 0 {
-            -1
-        } else {
-            let timeout = read_user_value::<MirageTimespec>(context.arg(2))?;
-            let nanos = timespec_to_nanos(timeout)?;
-            let millis = (nanos / 1_000_000).min(i32::MAX as u128) as i32;
-            millis
-        };
-        self.poll_descriptors(context.caller, context.thread, fds, timeout_ms)
+    pub fn waitpid(
+        &mut self,
+        parent: ProcessId,
+        pid: i64,
+        status: Option<&mut i32>,
+        options: u64,
+    ) -> KernelResult<ProcessId> {
+        let status_ptr = status.map(|out| out as *mut i32 as u64).unwrap_or(0);
+        self.wait_for_child(parent, None, pid, status_ptr, options)
+            .map(ProcessId::new)
     }
 
-    fn syscall_eventfd(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let initial = context.arg(0) as u32 as u64;
-        let flags = context.arg(1);
-        if flags & !EVENTFD_SUPPORTED_FLAGS != 0 || flags & O_DIRECT_RAW != 0 {
-            return Err(KernelError::InvalidArgument);
-        }
-        let id = self.allocate_eventfd(initial, flags & EFD_SEMAPHORE != 0)?;
-        let description = match self.open_files.insert_object(DescriptorObject::EventFd(id)) {
-            Ok(description) => description,
-            Err(error) => {
-                self.eventfds[id.raw()] = None;
-                return Err(map_file_table_error(error));
-            }
-        };
-        match self
-            .process_files_mut(context.caller)?
-            .open(description, descriptor_flags_from_raw(flags))
-        {
-            Ok(fd) => Ok(fd as u64),
-            Err(error) => {
-                let _ = self.close_open_description(description);
-                self.eventfds[id.raw()] = None;
-                Err(map_process_file_table_error(error))
-            }
+    pub fn queue_thread_syscall(
+        &mut self,
+        thread: ThreadId,
+        number: u64,
+        args: [u64; syscall::SYSCALL_MAX_ARGS],
+    ) -> KernelResult<()> {
+        let index = self.locate_thread(thread)?;
+        if let Some(tcb) = self.thread_table.entry_mut(index) {
+            tcb.prepare_syscall(number, args);
+            Ok(())
+        } else {
+            Err(KernelError::UnknownThread)
         }
     }
 
-    fn syscall_socket(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let domain = i32::try_from(context.arg(0)).map_err(|_| KernelError::InvalidArgument)?;
-        let socket_type =
-            i32::try_from(context.arg(1)).map_err(|_| KernelError::InvalidArgument)?;
-        let protocol = i32::try_from(context.arg(2)).map_err(|_| KernelError::InvalidArgument)?;
-        if domain < 0 || socket_type < 0 {
-            return Err(KernelError::InvalidArgument);
-        }
+    pub fn thread_context(&self, thread: ThreadId) -> KernelResult<CpuContext> {
+        let index = self.locate_thread(thread)?;
+        self.thread_table[index]
+            .map(|tcb| tcb.context)
+            .ok_or(KernelError::UnknownThread)
+    }
 
-        let handle = self.allocate_socket_handle()?;
-        let description = match self
-            .open_files
-            .insert_object(DescriptorObject::Socket(handle))
-        {
-            Ok(description) => description,
-            Err(error) => return Err(map_file_table_error(error)),
-        };
-        let fd = match self
-            .process_files_mut(context.caller)?
-            .open(description, DescriptorFlags::EMPTY)
-        {
-            Ok(fd) => fd,
-            Err(error) => {
-                let _ = self.close_open_description(description);
-                return Err(map_process_file_table_error(error));
-            }
-        };
+    /// Single raw entry point: decode and validate `frame` for `caller`,
+    /// dispatch it, and return the packed result register.
+    pub fn syscall_raw(&mut self, caller: ProcessId, frame: &SyscallFrame) -> u64 {
+        let result = syscall::abi::decode(frame).and_then(|decoded| {
+            let frame = decoded.encode();
+            self.handle_syscall(frame.number, SyscallContext::new(caller, None, frame.args))
+        });
+        syscall::abi::encode_result(result)
+    }
 
-        let request = NetworkSocketRequest {
-            header: self.network_request_header(
-                NetworkOpcode::Socket,
-                context.caller,
-                socket_type as u32,
-            ),
-            socket_handle: handle.raw(),
-            domain,
-            socket_type,
-            protocol,
-            reserved: 0,
-        };
-        if let Err(error) = self.send_network_request(context.caller, &request) {
-            if let Ok(descriptor) = self
-                .process_files_mut(context.caller)
-                .and_then(|files| files.close(fd).map_err(map_process_file_table_error))
-            {
-                let _ = self.close_open_description(descriptor.description());
+    pub fn handle_syscall(&mut self, number: u64, context: SyscallContext) -> KernelResult<u64> {
+        match SyscallNumber::from_raw(number).ok_or(KernelError::InvalidSyscall)? {
+            SyscallNumber::GetPid => Ok(context.caller.raw()),
+            SyscallNumber::Spawn => self.syscall_spawn(context),
+            SyscallNumber::SendIpc => self.syscall_send_ipc(context),
+            SyscallNumber::ReceiveIpc => self.syscall_receive_ipc(context),
+            SyscallNumber::ReceiveOrBlockIpc => self.syscall_receive_or_block_ipc(context),
+            SyscallNumber::BlockForIpc => {
+                self.security
+                    .authorize_ipc_receive(context.caller)
+                    .map_err(KernelError::SecurityViolation)?;
+                self.block_for_message(context.caller);
+                Ok(0)
             }
-            return Err(error);
+            SyscallNumber::EnumerateDevices => self.syscall_enumerate_devices(context),
+            SyscallNumber::DeviceInfo => self.syscall_device_info(context),
+            SyscallNumber::DeviceRead => self.syscall_device_read(context),
+            SyscallNumber::DeviceWrite => self.syscall_device_write(context),
+            SyscallNumber::Mmap => self.syscall_mmap(context),
+            SyscallNumber::Munmap => self.syscall_munmap(context),
+            SyscallNumber::Malloc => self.syscall_malloc(context),
+            SyscallNumber::Free => self.syscall_free(context),
+            SyscallNumber::Realloc => self.syscall_realloc(context),
+            SyscallNumber::MallocAligned => self.syscall_malloc_aligned(context),
+            SyscallNumber::OpenAt => self.syscall_openat(context),
+            SyscallNumber::Close => self.syscall_close(context),
+            SyscallNumber::Read => self.syscall_read(context),
+            SyscallNumber::Write => self.syscall_write(context),
+            SyscallNumber::Pread64 => self.syscall_pread64(context),
+            SyscallNumber::Pwrite64 => self.syscall_pwrite64(context),
+            SyscallNumber::Lseek => self.syscall_lseek(context),
+            SyscallNumber::Statx => self.syscall_statx(context),
+            SyscallNumber::NewFstatAt => self.syscall_newfstatat(context),
+            SyscallNumber::Getdents64 => self.syscall_getdents64(context),
+            SyscallNumber::MkdirAt => self.syscall_mkdirat(context),
+            SyscallNumber::UnlinkAt => self.syscall_unlinkat(context),
+            SyscallNumber::RenameAt2 => self.syscall_renameat2(context),
+            SyscallNumber::Ftruncate => self.syscall_ftruncate(context),
+            SyscallNumber::Fsync => self.syscall_fsync(context),
+            SyscallNumber::Mount => self.syscall_mount(context),
+            SyscallNumber::Chdir => self.syscall_chdir(context),
+            SyscallNumber::Fchdir => self.syscall_fchdir(context),
+            SyscallNumber::Getcwd => self.syscall_getcwd(context),
+            SyscallNumber::Faccessat => self.syscall_faccessat(context),
+            SyscallNumber::Fchmodat => self.syscall_fchmodat(context),
+            SyscallNumber::Fchownat => self.syscall_fchownat(context),
+            SyscallNumber::Symlinkat => self.syscall_symlinkat(context),
+            SyscallNumber::Readlinkat => self.syscall_readlinkat(context),
+            SyscallNumber::Linkat => self.syscall_linkat(context),
+            SyscallNumber::RegisterService => self.syscall_register_service(context),
+            SyscallNumber::SendServiceIpc => self.syscall_send_service_ipc(context),
+            SyscallNumber::ClaimDevice => self.syscall_claim_device(context),
+            SyscallNumber::ReleaseDevice => self.syscall_release_device(context),
+            SyscallNumber::Fork => self.syscall_fork(context),
+            SyscallNumber::Execve => self.syscall_execve(context),
+            SyscallNumber::Exit => self.syscall_exit(context),
+            SyscallNumber::Wait4 => self.syscall_wait4(context),
+            SyscallNumber::GetPpid => self.syscall_getppid(context),
+            SyscallNumber::SetPgid => self.syscall_setpgid(context),
+            SyscallNumber::Setsid => self.syscall_setsid(context),
+            SyscallNumber::GetUid => self.syscall_getuid(context),
+            SyscallNumber::GetEuid => self.syscall_geteuid(context),
+            SyscallNumber::SetUid => self.syscall_setuid(context),
+            SyscallNumber::GetGid => self.syscall_getgid(context),
+            SyscallNumber::SetGid => self.syscall_setgid(context),
+            SyscallNumber::GetGroups => self.syscall_getgroups(context),
+            SyscallNumber::SetGroups => self.syscall_setgroups(context),
+            SyscallNumber::RtSigaction => self.syscall_rt_sigaction(context),
+            SyscallNumber::RtSigprocmask => self.syscall_rt_sigprocmask(context),
+            SyscallNumber::Kill => self.syscall_kill(context),
+            SyscallNumber::RtSigreturn => self.syscall_rt_sigreturn(context),
+            SyscallNumber::ClockGettime => self.syscall_clock_gettime(context),
+            SyscallNumber::Nanosleep => self.syscall_nanosleep(context),
+            SyscallNumber::TimerCreate => self.syscall_timer_create(context),
+            SyscallNumber::TimerSettime => self.syscall_timer_settime(context),
+            SyscallNumber::TimerGettime => self.syscall_timer_gettime(context),
+            SyscallNumber::TimerDelete => self.syscall_timer_delete(context),
+            SyscallNumber::Dup => self.syscall_dup(context),
+            SyscallNumber::Dup2 => self.syscall_dup2(context),
+            SyscallNumber::Dup3 => self.syscall_dup3(context),
+            SyscallNumber::Fcntl => self.syscall_fcntl(context),
+            SyscallNumber::Ioctl => self.syscall_ioctl(context),
+            SyscallNumber::Pipe2 => self.syscall_pipe2(context),
+            SyscallNumber::Poll => self.syscall_poll(context),
+            SyscallNumber::Pselect => self.syscall_pselect(context),
+            SyscallNumber::Eventfd => self.syscall_eventfd(context),
+            SyscallNumber::Socket => self.syscall_socket(context),
+            SyscallNumber::Bind => self.syscall_bind(context),
+            SyscallNumber::Listen => self.syscall_listen(context),
+            SyscallNumber::Accept => self.syscall_accept(context),
+            SyscallNumber::Connect => self.syscall_connect(context),
+            SyscallNumber::Sendmsg => self.syscall_sendmsg(context),
+            SyscallNumber::Recvmsg => self.syscall_recvmsg(context),
+            SyscallNumber::Clone => self.syscall_clone(context),
+            SyscallNumber::Futex => self.syscall_futex(context),
+            SyscallNumber::SetThreadArea => self.syscall_set_thread_area(context),
+            SyscallNumber::ArchPrctl => self.syscall_arch_prctl(context),
+            SyscallNumber::Yield => self.syscall_yield(context),
         }
-
-        Ok(fd as u64)
     }
 
-    fn syscall_bind(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
-        let addr_ptr = context.arg(1);
-        let addr_len = u32::try_from(context.arg(2)).map_err(|_| KernelError::InvalidArgument)?;
-        validate_user_range(addr_ptr, addr_len as usize)?;
-        let request = NetworkSockaddrRequest {
-            header: self.network_request_header(NetworkOpcode::Bind, context.caller, 0),
-            socket_handle: handle.raw(),
-            addr_ptr,
-            addr_len,
-            value: 0,
-            result_addr_len_ptr: 0,
-            accepted_socket_handle: 0,
-        };
-        self.send_network_request(context.caller, &request)?;
+    fn syscall_yield(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.ensure_process_exists(context.caller)?;
+        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
+        let thread_index = self.locate_thread(thread)?;
+        let tcb = self
+            .thread_table
+            .entry_mut(thread_index)
+            .ok_or(KernelError::UnknownThread)?;
+        if tcb.process != context.caller {
+            return Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation,
+            ));
+        }
+        if tcb.state == ThreadState::Terminated {
+            return Err(KernelError::UnknownThread);
+        }
+        if tcb.state == ThreadState::Running {
+            tcb.mark_ready_at(self.mtss_ticks);
+        }
         Ok(0)
     }
 
-    fn syscall_listen(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
-        let backlog = i32::try_from(context.arg(1)).map_err(|_| KernelError::InvalidArgument)?;
-        if backlog < 0 {
+    fn syscall_fork(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.fork_task(context.caller, context.thread)
+            .map(|pid| pid.raw())
+    }
+
+    fn syscall_execve(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let resolved = self.resolve_user_path(context.caller, AT_FDCWD as u64, context.arg(0))?;
+        let path = resolved.as_path()?;
+        let stat = self.root_fs.stat(path).map_err(KernelError::Filesystem)?;
+        if stat.kind != InodeKind::RegularFile {
+            return Err(KernelError::Filesystem(VfsError::PermissionDenied));
+        }
+        if !crate::kernel::fs::Permissions::new(stat.mode, stat.uid, stat.gid).allows(
+            self.fs_credentials_for(context.caller)?,
+            AccessMode::Execute,
+        ) {
+            return Err(KernelError::Filesystem(VfsError::PermissionDenied));
+        }
+
+        let argv = exec_vector_metadata(context.arg(1), MAX_EXEC_ARGS)?;
+        let envp = exec_vector_metadata(context.arg(2), MAX_EXEC_ENVS)?;
+        if argv.truncated || envp.truncated {
             return Err(KernelError::InvalidArgument);
         }
-        let request = NetworkSockaddrRequest {
-            header: self.network_request_header(NetworkOpcode::Listen, context.caller, 0),
-            socket_handle: handle.raw(),
-            addr_ptr: 0,
-            addr_len: 0,
-            value: backlog,
-            result_addr_len_ptr: 0,
-            accepted_socket_handle: 0,
-        };
-        self.send_network_request(context.caller, &request)?;
+        let image = self.load_exec_image(context.caller, &resolved, stat, argv, envp)?;
+        let request = ExecRequest::new(
+            context.caller,
+            ProcessPath::from_path(path),
+            argv,
+            envp,
+            decode_credentials(context.arg(5))?,
+            image,
+        );
+
+        self.exec_task(request, context.thread)?;
         Ok(0)
     }
 
-    fn syscall_accept(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let listener = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
-        let addr_ptr = context.arg(1);
-        let addr_len_ptr = context.arg(2);
-        if addr_ptr != 0 {
-            if addr_len_ptr == 0 {
-                return Err(KernelError::InvalidPointer);
-            }
-            let addr_len = read_user_value::<u32>(addr_len_ptr)?;
-            validate_user_range(addr_ptr, addr_len as usize)?;
-        } else if addr_len_ptr != 0 {
-            let _ = read_user_value::<u32>(addr_len_ptr)?;
+    fn syscall_exit(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let status = ExitStatus::exited(context.arg(0) as i32);
+        self.exit_task(context.caller, status);
+
+        if let Some(thread) = context.thread {
+            let _ = self
+                .mtss_scheduler
+                .exit_thread(Self::mtss_thread_id(thread));
+            self.remove_thread_from_cores(thread);
         }
-        let accepted = self.allocate_socket_handle()?;
-        let description = self
-            .open_files
-            .insert_object(DescriptorObject::Socket(accepted))
-            .map_err(map_file_table_error)?;
-        let fd = match self
-            .process_files_mut(context.caller)?
-            .open(description, DescriptorFlags::EMPTY)
-        {
-            Ok(fd) => fd,
-            Err(error) => {
-                let _ = self.close_open_description(description);
-                return Err(map_process_file_table_error(error));
-            }
-        };
-        let request = NetworkSockaddrRequest {
-            header: self.network_request_header(NetworkOpcode::Accept, context.caller, 0),
-            socket_handle: listener.raw(),
-            addr_ptr,
-            addr_len: 0,
-            value: 0,
-            result_addr_len_ptr: addr_len_ptr,
-            accepted_socket_handle: accepted.raw(),
-        };
-        if let Err(error) = self.send_network_request(context.caller, &request) {
-            if let Ok(descriptor) = self
-                .process_files_mut(context.caller)
-                .and_then(|files| files.close(fd).map_err(map_process_file_table_error))
-            {
-                let _ = self.close_open_description(descriptor.description());
-            }
-            return Err(error);
+        let _ = self
+            .mtss_scheduler
+            .terminate_task(Self::mtss_task_id(context.caller));
+
+        if context.caller.raw() == 1 {
+            crate::kprintln!("SPIDER-RS PID1 [FAILED: exited unexpectedly]");
         }
 
-        Ok(fd as u64)
+        Ok(0)
     }
 
-    fn syscall_connect(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
-        let addr_ptr = context.arg(1);
-        let addr_len = u32::try_from(context.arg(2)).map_err(|_| KernelError::InvalidArgument)?;
-        validate_user_range(addr_ptr, addr_len as usize)?;
-        let request = NetworkSockaddrRequest {
-            header: self.network_request_header(NetworkOpcode::Connect, context.caller, 0),
-            socket_handle: handle.raw(),
-            addr_ptr,
-            addr_len,
-            value: 0,
-            result_addr_len_ptr: 0,
-            accepted_socket_handle: 0,
-        };
-        self.send_network_request(context.caller, &request)?;
-        Ok(0)
+    fn syscall_wait4(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.wait_for_child(
+            context.caller,
+            context.thread,
+            context.arg(0) as i64,
+            context.arg(1),
+            context.arg(2),
+        )
     }
 
-    fn syscall_sendmsg(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
-        let message_ptr = context.arg(1);
-        validate_user_range(message_ptr, 1)?;
-        let request = NetworkSendmsgRequest {
-            header: self.network_request_header(NetworkOpcode::Sendmsg, context.caller, 0),
-            socket_handle: handle.raw(),
-            message_ptr,
-            flags: context.arg(2),
-            reserved: 0,
-        };
-        self.send_network_request(context.caller, &request)?;
-        Ok(0)
+    fn syscall_getppid(&self, context: SyscallContext) -> KernelResult<u64> {
+        let index = self.locate_process(context.caller)?;
+        Ok(self.process_table[index]
+            .as_ref()
+            .and_then(|pcb| pcb.parent)
+            .map(|pid| pid.raw())
+            .unwrap_or(0))
     }
 
-    fn syscall_recvmsg(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
-        let message_ptr = context.arg(1);
-        validate_user_range(message_ptr, 1)?;
-        let request = NetworkRecvmsgRequest {
-            header: self.network_request_header(NetworkOpcode::Recvmsg, context.caller, 0),
-            socket_handle: handle.raw(),
-            message_ptr,
-            flags: context.arg(2),
-            reserved: 0,
+    fn syscall_setpgid(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let target = if context.arg(0) == 0 {
+            context.caller
+        } else {
+            ProcessId::new(context.arg(0))
         };
-        self.send_network_request(context.caller, &request)?;
-        Ok(0)
-    }
-
-    fn syscall_clone(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let entry_point = context.arg(0);
-        let priority = decode_priority(context.arg(1))?;
-        let flags = context.arg(2);
-        let request = if flags == 0 && context.arg(3) == 0 && context.arg(4) == 0 {
-            CloneTaskRequest::legacy_thread(context.caller, context.thread, entry_point, priority)
+        let pgid = if context.arg(1) == 0 {
+            ProcessGroupId::new(target.raw())
         } else {
-            let tls_base = if context.arg(3) == 0 {
-                None
-            } else {
-                Some(context.arg(3))
-            };
-            let child_stack = if context.arg(4) == 0 {
-                None
-            } else {
-                Some(context.arg(4))
-            };
-            CloneTaskRequest::new(
-                context.caller,
-                context.thread,
-                entry_point,
-                priority,
-                child_stack,
-                tls_base,
-                flags,
-            )
+            ProcessGroupId::new(context.arg(1))
         };
-        self.clone_thread(request).map(|thread| thread.raw())
+        let caller_index = self.locate_process(context.caller)?;
+        let caller_session = self.process_table[caller_index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?
+            .session;
+        let target_index = self.locate_process(target)?;
+        let target_pcb = self.process_table[target_index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?;
+        if target != context.caller && target_pcb.parent != Some(context.caller) {
+            return Err(KernelError::InvalidArgument);
+        }
+        if target_pcb.session != caller_session {
+            return Err(KernelError::InvalidArgument);
+        }
+        target_pcb.set_process_group(pgid);
+        Ok(0)
     }
 
-    fn syscall_futex(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let uaddr = context.arg(0);
-        let operation = context.arg(1);
-        let value = context.arg(2) as i32;
-        let timeout_ptr = context.arg(3);
-        let command = operation & FUTEX_CMD_MASK;
-        let key = self.futex_key(context.caller, uaddr)?;
-
-        match command {
-            FUTEX_WAIT => {
-                let thread = context.thread.ok_or(KernelError::UnknownThread)?;
-                let observed = read_user_value::<i32>(uaddr)?;
-                if observed != value {
-                    return Err(KernelError::MessageQueueEmpty);
-                }
-                let deadline = if timeout_ptr == 0 {
-                    None
-                } else {
-                    let requested = read_user_value::<MirageTimespec>(timeout_ptr)?;
-                    let duration_ns = timespec_to_nanos(requested)?;
-                    Some(KERNEL_TIME.now().as_nanos().saturating_add(duration_ns))
-                };
-                self.futexes
-                    .enqueue(key, thread, deadline)
-                    .map_err(|_| KernelError::AllocationFailed)?;
-                self.block_thread(thread)?;
-                Ok(0)
-            }
-            FUTEX_WAKE => {
-                let limit = if value < 0 { 0 } else { value as usize };
-                let mut woken_threads = [None; MAX_THREADS];
-                let count = self.futexes.wake(key, limit, &mut woken_threads);
-                self.wake_futex_threads(&woken_threads, count, 0)?;
-                Ok(count as u64)
-            }
-            _ => Err(KernelError::InvalidArgument),
+    fn syscall_setsid(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let index = self.locate_process(context.caller)?;
+        let sid = SessionId::new(context.caller.raw());
+        let pgid = ProcessGroupId::new(context.caller.raw());
+        let pcb = self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?;
+        if pcb.process_group.raw() == context.caller.raw() {
+            return Err(KernelError::InvalidArgument);
         }
+        pcb.set_session(sid);
+        pcb.set_process_group(pgid);
+        Ok(sid.raw())
     }
 
-    fn syscall_set_thread_area(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
-        let base = context.arg(0);
-        validate_tls_base(base)?;
-        self.set_thread_fs_base(thread, base)
+    fn syscall_getuid(&self, context: SyscallContext) -> KernelResult<u64> {
+        Ok(self.process_credentials(context.caller)?.uid as u64)
     }
 
-    fn syscall_arch_prctl(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
-        match context.arg(0) {
-            ARCH_SET_FS => {
-                let base = context.arg(1);
-                validate_tls_base(base)?;
-                self.set_thread_fs_base(thread, base)
-            }
-            ARCH_SET_GS => {
-                let base = context.arg(1);
-                validate_tls_base(base)?;
-                self.set_thread_gs_base(thread, base)
-            }
-            ARCH_GET_FS => {
-                let base = self.thread_fs_base(thread)?;
-                write_user_value::<u64>(context.arg(1), base)?;
-                Ok(0)
-            }
-            ARCH_GET_GS => {
-                let base = self.thread_gs_base(thread)?;
-                write_user_value::<u64>(context.arg(1), base)?;
-                Ok(0)
-            }
-            _ => Err(KernelError::InvalidArgument),
-        }
+    fn syscall_geteuid(&self, context: SyscallContext) -> KernelResult<u64> {
+        Ok(self.process_credentials(context.caller)?.euid as u64)
     }
 
-    fn syscall_spawn(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let resolved = copy_user_path_with_len(context.arg(0), context.arg(1) as usize)?;
-        let path = resolved.as_path()?;
-        let stat = match self.root_fs.stat(path) {
-            Ok(stat) => stat,
-            #[cfg(test)]
-            Err(VfsError::NotFound) if is_test_spawn_path(resolved.as_str()) => {
-                return self
-                    .spawn_test_loaded_task(context.caller, resolved.as_str())
-                    .map(|pid| pid.raw());
-            }
-            Err(error) => return Err(KernelError::Filesystem(error)),
-        };
-        if stat.kind != InodeKind::RegularFile {
-            return Err(KernelError::Filesystem(VfsError::PermissionDenied));
-        }
-
-        let argv = exec_vector_metadata_with_count(
-            context.arg(2),
-            context.arg(3) as usize,
-            MAX_EXEC_ARGS,
-        )?;
-        let envp = exec_vector_metadata_with_count(
-            context.arg(4),
-            context.arg(5) as usize,
-            MAX_EXEC_ENVS,
-        )?;
-        self.spawn_loaded_task(
-            context.caller,
-            &resolved,
-            stat,
-            argv,
-            envp,
-            ProcessPriority::Normal,
-            self.current_credentials(context.caller)?,
-        )
-        .map(|pid| pid.raw())
+    fn syscall_getgid(&self, context: SyscallContext) -> KernelResult<u64> {
+        Ok(self.process_credentials(context.caller)?.gid as u64)
     }
 
-    #[cfg(test)]
-    fn spawn_test_loaded_task(&mut self, parent: ProcessId, path: &str) -> KernelResult<ProcessId> {
-        let _ = path;
-        let credentials = self.current_credentials(parent)?;
-        let child = self.spawn_task(SpawnTaskRequest {
-            parent: Some(parent),
-            entry_point: 0x400078,
-            priority: ProcessPriority::Normal,
-            credentials,
-        })?;
-        let index = self.locate_process(child)?;
-        if let Some(pcb) = self.process_table[index].as_mut() {
-            pcb.address_space_root = child.raw();
-        }
-        let thread = self
-            .first_thread_for_process(child)
-            .ok_or(KernelError::UnknownThread)?;
-        let thread_index = self.locate_thread(thread)?;
-        if let Some(tcb) = self.thread_table[thread_index].as_mut() {
-            tcb.replace_exec_image(0x400078, 0x0000_7fff_ffff_ffc0);
-        }
-        Ok(child)
+    fn syscall_setuid(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_credential_update(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let uid = u16::try_from(context.arg(0)).map_err(|_| KernelError::InvalidArgument)?;
+        self.process_credentials_mut(context.caller)?.set_uid(uid);
+        Ok(0)
     }
 
-    fn syscall_send_ipc(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let receiver = ProcessId::new(context.arg(0));
-        let data_ptr = context.arg(1) as *const u8;
-        let data_len = context.arg(2) as usize;
-        let security_class = decode_security_class(context.arg(3))?;
-        if data_len > 0 && data_ptr.is_null() {
-            return Err(KernelError::InvalidPointer);
-        }
-
-        let data = if data_len == 0 {
-            &[]
-        } else {
-            unsafe { core::slice::from_raw_parts(data_ptr, data_len) }
-        };
-        let payload = MessagePayload::from_slice(security_class, data);
-        self.send_message(context.caller, receiver, payload)?;
-        Ok(payload.length as u64)
-    }
-
-    fn syscall_receive_ipc(&mut self, context: SyscallContext) -> KernelResult<u64> {
+    fn syscall_setgid(&mut self, context: SyscallContext) -> KernelResult<u64> {
         self.security
-            .authorize_ipc_receive(context.caller)
+            .authorize_credential_update(context.caller)
             .map_err(KernelError::SecurityViolation)?;
-        let out = context.arg(0) as *mut Message;
-        if out.is_null() {
-            return Err(KernelError::InvalidPointer);
-        }
-        let message = self.receive_message(context.caller)?;
-        unsafe { out.write(message) };
-        Ok(message.payload.length as u64)
-    }
-
-    fn syscall_register_service(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let service = decode_registry_service_id(context.arg(0))?;
-        let owner = if context.arg(1) == 0 {
-            context.caller
-        } else {
-            ProcessId::new(context.arg(1))
-        };
-        self.register_service(context.caller, service, owner)?;
+        let gid = u16::try_from(context.arg(0)).map_err(|_| KernelError::InvalidArgument)?;
+        self.process_credentials_mut(context.caller)?.set_gid(gid);
         Ok(0)
     }
 
-    fn syscall_send_service_ipc(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let service = decode_registry_service_id(context.arg(0))?;
-        let data_ptr = context.arg(1) as *const u8;
-        let data_len = context.arg(2) as usize;
-        let security_class = decode_security_class(context.arg(3))?;
-        if data_len > 0 && data_ptr.is_null() {
-            return Err(KernelError::InvalidPointer);
+    fn syscall_getgroups(&self, context: SyscallContext) -> KernelResult<u64> {
+        let credentials = self.process_credentials(context.caller)?;
+        let group_count = credentials.supplementary_group_count();
+        if context.arg(0) == 0 {
+            return Ok(group_count as u64);
         }
-        let data = if data_len == 0 {
-            &[]
-        } else {
-            unsafe { core::slice::from_raw_parts(data_ptr, data_len) }
-        };
-        let payload = MessagePayload::from_slice(security_class, data);
-        self.send_service_message(context.caller, service, payload)?;
-        Ok(payload.length as u64)
-    }
-
-    fn syscall_claim_device(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let service = decode_registry_service_id(context.arg(0))?;
-        let device = DeviceId::new(context.arg(1) as u16);
-        self.claim_service_device(context.caller, service, device)?;
-        Ok(0)
-    }
-
-    fn syscall_release_device(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let service = decode_registry_service_id(context.arg(0))?;
-        let device = DeviceId::new(context.arg(1) as u16);
-        self.release_service_device(context.caller, service, device)?;
-        Ok(0)
-    }
-
-    fn syscall_receive_or_block_ipc(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        self.security
-            .authorize_ipc_receive(context.caller)
-            .map_err(KernelError::SecurityViolation)?;
-        let out = context.arg(0) as *mut Message;
-        if out.is_null() {
-            return Err(KernelError::InvalidPointer);
+        let capacity = context.arg(0) as usize;
+        if capacity < group_count {
+            return Err(KernelError::InvalidArgument);
         }
-
-        if let Some(message) = self.receive_or_block(context.caller)? {
-            unsafe { out.write(message) };
-            Ok(1)
-        } else {
-            Ok(0)
+        let groups = user_slice_mut_typed::<u32>(context.arg(1), capacity)?;
+        let stored = credentials.supplementary_groups();
+        let mut idx = 0usize;
+        while idx < group_count {
+            groups[idx] = stored[idx] as u32;
+            idx += 1;
         }
+        Ok(group_count as u64)
     }
 
-    fn syscall_enumerate_devices(&self, context: SyscallContext) -> KernelResult<u64> {
+    fn syscall_setgroups(&mut self, context: SyscallContext) -> KernelResult<u64> {
         self.security
-            .authorize_device_enumeration(context.caller)
+            .authorize_credential_update(context.caller)
             .map_err(KernelError::SecurityViolation)?;
-        let out = context.arg(0) as *mut MirageDeviceDescriptor;
-        let capacity = context.arg(1) as usize;
-        if capacity > 0 && out.is_null() {
-            return Err(KernelError::InvalidPointer);
+        let count = context.arg(0) as usize;
+        if count > MAX_SUPPLEMENTARY_GROUPS {
+            return Err(KernelError::InvalidArgument);
         }
-        let out_slice = if capacity == 0 {
-            &mut []
-        } else {
-            unsafe { core::slice::from_raw_parts_mut(out, capacity) }
-        };
-
-        let mut descriptors = [EMPTY_DEVICE_DESCRIPTOR; MAX_DEVICES];
-        let count = self.enumerate_devices(&mut descriptors[..min(capacity, MAX_DEVICES)]);
+        let user_groups = user_slice_typed::<u32>(context.arg(1), count)?;
+        let mut groups = [0u16; MAX_SUPPLEMENTARY_GROUPS];
         let mut idx = 0usize;
         while idx < count {
-            out_slice[idx] = MirageDeviceDescriptor::from_descriptor(descriptors[idx]);
+            groups[idx] =
+                u16::try_from(user_groups[idx]).map_err(|_| KernelError::InvalidArgument)?;
             idx += 1;
         }
-        Ok(count as u64)
+        self.process_credentials_mut(context.caller)?
+            .set_supplementary_groups(&groups[..count])
+            .map_err(|_| KernelError::InvalidArgument)?;
+        Ok(0)
     }
 
-    fn syscall_device_info(&self, context: SyscallContext) -> KernelResult<u64> {
-        self.security
-            .authorize_device_enumeration(context.caller)
-            .map_err(KernelError::SecurityViolation)?;
-        let id = DeviceId::new(context.arg(0) as u16);
-        let out = context.arg(1) as *mut MirageDeviceDescriptor;
-        if out.is_null() {
-            return Err(KernelError::InvalidPointer);
+    fn syscall_rt_sigaction(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let signal = context.arg(0) as usize;
+        if signal == 0 || signal > crate::kernel::process::MAX_SIGNAL_NUMBER {
+            return Err(KernelError::InvalidArgument);
         }
-        let descriptor = self.device_info(id).ok_or(KernelError::DeviceNotFound)?;
-        unsafe { out.write(MirageDeviceDescriptor::from_descriptor(descriptor)) };
-        Ok(1)
-    }
-
-    fn syscall_device_read(&self, context: SyscallContext) -> KernelResult<u64> {
-        let id = DeviceId::new(context.arg(0) as u16);
-        let buffer = context.arg(1) as *mut u8;
-        let len = context.arg(2) as usize;
-        if len > 0 && buffer.is_null() {
-            return Err(KernelError::InvalidPointer);
+        let new_action = context.arg(1) as *const SignalAction;
+        let old_action = context.arg(2) as *mut SignalAction;
+        let index = self.locate_process(context.caller)?;
+        let pcb = self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?;
+        if !old_action.is_null() {
+            unsafe { old_action.write(pcb.signal_actions[signal]) };
         }
-        let buffer = if len == 0 {
-            &mut []
-        } else {
-            unsafe { core::slice::from_raw_parts_mut(buffer, len) }
-        };
-        self.device_read(context.caller, id, buffer)
-            .map(|read| read as u64)
+        if !new_action.is_null() {
+            pcb.signal_actions[signal] = unsafe { new_action.read() };
+        }
+        Ok(0)
     }
 
-    fn syscall_device_write(&self, context: SyscallContext) -> KernelResult<u64> {
-        let id = DeviceId::new(context.arg(0) as u16);
-        let data = context.arg(1) as *const u8;
-        let len = context.arg(2) as usize;
-        if len > 0 && data.is_null() {
-            return Err(KernelError::InvalidPointer);
+    fn syscall_rt_sigprocmask(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let how = context.arg(0);
+        let new_mask = context.arg(1) as *const SignalMask;
+        let old_mask = context.arg(2) as *mut SignalMask;
+        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
+        let index = self.locate_thread(thread)?;
+        let current = self.thread_table[index]
+            .as_ref()
+            .ok_or(KernelError::UnknownThread)?
+            .signal_mask;
+        if !old_mask.is_null() {
+            unsafe { old_mask.write(current) };
         }
-        let data = if len == 0 {
-            &[]
-        } else {
-            unsafe { core::slice::from_raw_parts(data, len) }
-        };
-        self.device_write(context.caller, id, data)
-            .map(|written| written as u64)
+        if !new_mask.is_null() {
+            let requested = unsafe { new_mask.read() };
+            let mask = match how {
+                0 => SignalMask::from_bits(current.bits() | requested.bits()),
+                1 => SignalMask::from_bits(current.bits() & !requested.bits()),
+                2 => requested,
+                _ => return Err(KernelError::InvalidArgument),
+            };
+            self.set_signal_mask(thread, mask)?;
+        }
+        Ok(0)
     }
 
-    fn syscall_mmap(&self, context: SyscallContext) -> KernelResult<u64> {
-        let length = context.arg(0) as usize;
-        let protection = MemoryProtection::from_bits(context.arg(1) as u32);
-        self.security
-            .authorize_memory_mapping(context.caller, protection)
-            .map_err(KernelError::SecurityViolation)?;
-        memory::mmap_for(context.caller, length, protection)
-            .map(|region| region.as_ptr() as u64)
-            .ok_or(KernelError::AllocationFailed)
+    /// Replace `thread`'s blocked-signal mask, returning the previous one.
+    /// Masked signals stay pending on the process; any that this call unblocks
+    /// are delivered before it returns.
+    pub fn set_signal_mask(
+        &mut self,
+        thread: ThreadId,
+        mask: SignalMask,
+    ) -> KernelResult<SignalMask> {
+        let index = self.locate_thread(thread)?;
+        let tcb = self
+            .thread_table
+            .entry_mut(index)
+            .ok_or(KernelError::UnknownThread)?;
+        let process = tcb.process;
+        let previous = tcb.set_signal_mask(mask);
+        if previous.bits() & !mask.bits() != 0 {
+            self.deliver_signal_checkpoint(process, thread)?;
+        }
+        Ok(previous)
     }
 
-    fn syscall_munmap(&self, context: SyscallContext) -> KernelResult<u64> {
-        self.security
-            .authorize_memory_service(context.caller)
-            .map_err(KernelError::SecurityViolation)?;
-        let ptr = NonNull::new(context.arg(0) as *mut u8).ok_or(KernelError::InvalidPointer)?;
-        let length = context.arg(1) as usize;
-        if memory::munmap_ptr_for(context.caller, ptr, length) {
-            Ok(0)
-        } else {
-            Err(KernelError::InvalidArgument)
-        }
+    fn syscall_kill(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.send_signal(context.arg(0) as i64, context.arg(1) as u8)
     }
 
-    fn syscall_malloc(&self, context: SyscallContext) -> KernelResult<u64> {
-        self.security
-            .authorize_memory_service(context.caller)
-            .map_err(KernelError::SecurityViolation)?;
-        memory::malloc_for(context.caller, context.arg(0) as usize)
-            .map(|ptr| ptr.as_ptr() as u64)
-            .ok_or(KernelError::AllocationFailed)
+    fn syscall_rt_sigreturn(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
+        let index = self.locate_thread(thread)?;
+        self.thread_table
+            .entry_mut(index)
+            .ok_or(KernelError::UnknownThread)?
+            .finish_signal();
+        Ok(0)
     }
 
-    fn syscall_free(&self, context: SyscallContext) -> KernelResult<u64> {
-        self.security
-            .authorize_memory_service(context.caller)
-            .map_err(KernelError::SecurityViolation)?;
-        let ptr = NonNull::new(context.arg(0) as *mut u8).ok_or(KernelError::InvalidPointer)?;
-        if memory::free_for(context.caller, ptr) {
-            Ok(0)
-        } else {
-            Err(KernelError::InvalidArgument)
+    fn syscall_clock_gettime(&self, context: SyscallContext) -> KernelResult<u64> {
+        let out = user_out_ptr::<MirageTimespec>(context.arg(1))?;
+        let nanos = KERNEL_TIME.now().as_nanos();
+        unsafe {
+            out.write(MirageTimespec {
+                tv_sec: (nanos / 1_000_000_000) as i64,
+                tv_nsec: (nanos % 1_000_000_000) as i64,
+            });
         }
+        Ok(0)
     }
 
-    fn syscall_realloc(&self, context: SyscallContext) -> KernelResult<u64> {
-        self.security
-            .authorize_memory_service(context.caller)
-            .map_err(KernelError::SecurityViolation)?;
-        let ptr = NonNull::new(context.arg(0) as *mut u8);
-        memory::realloc_for(context.caller, ptr, context.arg(1) as usize)
-            .map(|ptr| ptr.as_ptr() as u64)
-            .ok_or(KernelError::AllocationFailed)
-    }
-
-    fn syscall_malloc_aligned(&self, context: SyscallContext) -> KernelResult<u64> {
-        self.security
-            .authorize_memory_service(context.caller)
-            .map_err(KernelError::SecurityViolation)?;
-        let alignment = context.arg(1) as usize;
-        if alignment == 0 || !alignment.is_power_of_two() {
-            return Err(KernelError::InvalidArgument);
+    fn syscall_nanosleep(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let requested = read_user_value::<MirageTimespec>(context.arg(0))?;
+        if context.arg(1) != 0 {
+            let _ = user_out_ptr::<MirageTimespec>(context.arg(1))?;
+        }
+        let duration_ns = timespec_to_nanos(requested)?;
+        if duration_ns == 0 {
+            return Ok(0);
         }
 
-        memory::malloc_aligned_for(context.caller, context.arg(0) as usize, alignment)
-            .map(|ptr| ptr.as_ptr() as u64)
-            .ok_or(KernelError::AllocationFailed)
-    }
+        let wake_deadline = KERNEL_TIME.now().as_nanos().saturating_add(duration_ns);
+        self.timers
+            .add_sleep(context.caller, context.thread, wake_deadline)
+            .map_err(map_timer_error)?;
+        let process_index = self.locate_process(context.caller)?;
+        self.block_process_at_index(context.caller, process_index);
 
-    fn syscall_openat(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        let path = path_buf.as_path()?;
-        let raw_flags = open_flags_from_libc(context.arg(2) as u32);
-        let descriptor_flags = DescriptorFlags::from_open_flags(raw_flags);
-        let credentials = self.fs_credentials_for(context.caller)?;
-        let file = self
-            .root_fs
-            .open(path, raw_flags.without_descriptor_flags(), credentials)
-            .map_err(KernelError::Filesystem)?;
-        let description = self.open_files.insert(file).map_err(map_file_table_error)?;
-        match self
-            .process_files_mut(context.caller)?
-            .open(description, descriptor_flags)
-        {
-            Ok(fd) => Ok(fd as u64),
-            Err(error) => {
-                self.close_open_description(description)?;
-                Err(map_process_file_table_error(error))
-            }
+        if context.arg(1) != 0 {
+            write_user_value(
+                context.arg(1),
+                MirageTimespec {
+                    tv_sec: 0,
+                    tv_nsec: 0,
+                },
+            )?;
         }
+
+        Ok(0)
     }
 
-    fn syscall_close(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let descriptor = self
-            .process_files_mut(context.caller)?
-            .close(context.arg(0) as usize)
-            .map_err(map_process_file_table_error)?;
-        self.close_open_description(descriptor.description())?;
+    fn syscall_timer_create(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.ensure_process_exists(context.caller)?;
+        let timer_id_out = context.arg(2);
+        let _ = user_out_ptr::<u64>(timer_id_out)?;
+        let id = self
+            .timers
+            .create_timer(context.caller)
+            .map_err(map_timer_error)?;
+        write_user_value(timer_id_out, id)?;
         Ok(0)
     }
 
-    fn syscall_read(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let buffer = user_slice_mut(context.arg(1), context.arg(2) as usize)?;
-        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
-        match self
-            .open_files
-            .get_object(description)
-            .map_err(map_file_table_error)?
-        {
-            DescriptorObject::Regular(_) => {
-                let file = self
-                    .open_files
-                    .get_mut(description)
-                    .map_err(map_file_table_error)?;
-                self.root_fs
-                    .read(file, buffer)
-                    .map(|read| read as u64)
-                    .map_err(KernelError::Filesystem)
-            }
-            DescriptorObject::Pipe(endpoint) => {
-                if endpoint.direction() != PipeDirection::Read {
-                    return Err(KernelError::InvalidArgument);
-                }
-                let pipe = self
-                    .pipes
-                    .get_mut(endpoint.id().raw())
-                    .and_then(Option::as_mut)
-                    .ok_or(KernelError::InvalidArgument)?;
-                Ok(pipe.read(buffer) as u64)
-            }
-            DescriptorObject::EventFd(id) => {
-                if buffer.len() < 8 {
-                    return Err(KernelError::InvalidArgument);
-                }
-                let eventfd = self
-                    .eventfds
-                    .get_mut(id.raw())
-                    .and_then(Option::as_mut)
-                    .ok_or(KernelError::InvalidArgument)?;
-                if eventfd.counter == 0 {
-                    return Ok(0);
-                }
-                let value = if eventfd.semaphore {
-                    eventfd.counter -= 1;
-                    1
-                } else {
-                    let value = eventfd.counter;
-                    eventfd.counter = 0;
-                    value
-                };
-                buffer[..8].copy_from_slice(&value.to_ne_bytes());
-                Ok(8)
-            }
-            DescriptorObject::Device(handle) => self
-                .device_read(context.caller, handle.id(), buffer)
-                .map(|read| read as u64),
-            DescriptorObject::Socket(_) | DescriptorObject::Console(_) => {
-                Err(KernelError::InvalidArgument)
-            }
+    fn syscall_timer_settime(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        const TIMER_ABSTIME: u64 = 1;
+
+        let timer_id = context.arg(0);
+        let flags = context.arg(1);
+        if flags & !TIMER_ABSTIME != 0 {
+            return Err(KernelError::InvalidArgument);
         }
-    }
 
-    fn syscall_write(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let data = user_slice(context.arg(1), context.arg(2) as usize)?;
-        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
-        match self
-            .open_files
-            .get_object(description)
-            .map_err(map_file_table_error)?
-        {
-            DescriptorObject::Regular(_) => {
-                let file = self
-                    .open_files
-                    .get_mut(description)
-                    .map_err(map_file_table_error)?;
-                self.root_fs
-                    .write(file, data)
-                    .map(|written| written as u64)
-                    .map_err(KernelError::Filesystem)
-            }
-            DescriptorObject::Pipe(endpoint) => {
-                if endpoint.direction() != PipeDirection::Write {
-                    return Err(KernelError::InvalidArgument);
-                }
-                let pipe = self
-                    .pipes
-                    .get_mut(endpoint.id().raw())
-                    .and_then(Option::as_mut)
-                    .ok_or(KernelError::InvalidArgument)?;
-                if pipe.readers == 0 {
-                    return Err(KernelError::InvalidArgument);
-                }
-                Ok(pipe.write(data) as u64)
-            }
-            DescriptorObject::EventFd(id) => {
-                if data.len() < 8 {
-                    return Err(KernelError::InvalidArgument);
-                }
-                let value = u64::from_ne_bytes([
-                    data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
-                ]);
-                if value == u64::MAX {
-                    return Err(KernelError::InvalidArgument);
-                }
-                let eventfd = self
-                    .eventfds
-                    .get_mut(id.raw())
-                    .and_then(Option::as_mut)
-                    .ok_or(KernelError::InvalidArgument)?;
-                eventfd.counter = eventfd
-                    .counter
-                    .checked_add(value)
-                    .ok_or(KernelError::InvalidArgument)?;
-                Ok(8)
-            }
-            DescriptorObject::Device(handle) => self
-                .device_write(context.caller, handle.id(), data)
-                .map(|written| written as u64),
-            DescriptorObject::Console(console) => {
-                self.write_console_descriptor(console, data);
-                Ok(data.len() as u64)
-            }
-            DescriptorObject::Socket(_) => Err(KernelError::InvalidArgument),
+        let requested = read_user_value::<MirageItimerspec>(context.arg(2))?;
+        let old_value_out = context.arg(3);
+        if old_value_out != 0 {
+            let _ = user_out_ptr::<MirageItimerspec>(old_value_out)?;
         }
-    }
+        let value_ns = timespec_to_nanos(requested.it_value)?;
+        let interval_ns = timespec_to_nanos(requested.it_interval)?;
+        let now_ns = KERNEL_TIME.now().as_nanos();
+        let deadline = if value_ns == 0 {
+            None
+        } else if flags & TIMER_ABSTIME != 0 {
+            Some(value_ns)
+        } else {
+            Some(now_ns.saturating_add(value_ns))
+        };
 
-    fn syscall_pread64(&self, context: SyscallContext) -> KernelResult<u64> {
-        let buffer = user_slice_mut(context.arg(1), context.arg(2) as usize)?;
-        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
-        let file = self
-            .open_files
-            .get(description)
-            .map_err(map_file_table_error)?;
-        self.root_fs
-            .pread(&file, buffer, context.arg(3))
-            .map(|read| read as u64)
-            .map_err(KernelError::Filesystem)
-    }
+        let previous = self
+            .timers
+            .set_timer(context.caller, timer_id, deadline, interval_ns)
+            .map_err(map_timer_error)?;
 
-    fn syscall_pwrite64(&self, context: SyscallContext) -> KernelResult<u64> {
-        let data = user_slice(context.arg(1), context.arg(2) as usize)?;
-        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
-        let file = self
-            .open_files
-            .get(description)
-            .map_err(map_file_table_error)?;
-        self.root_fs
-            .pwrite(&file, data, context.arg(3))
-            .map(|written| written as u64)
-            .map_err(KernelError::Filesystem)
+        if old_value_out != 0 {
+            write_user_value(old_value_out, timer_to_itimerspec(previous, now_ns))?;
+        }
+
+        Ok(0)
     }
 
-    fn syscall_lseek(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let fd = context.arg(0) as usize;
-        let offset = context.arg(1) as i64;
-        let whence = context.arg(2);
-        let description = self.fd_description(context.caller, fd)?;
-        let file = self
-            .open_files
-            .get(description)
-            .map_err(map_file_table_error)?;
-        let current = file.cursor() as i64;
-        let end = self
-            .root_fs
-            .fstat(&file)
-            .map_err(KernelError::Filesystem)?
-            .size as i64;
-        let base = match whence {
-            SEEK_SET => 0,
-            SEEK_CUR => current,
-            SEEK_END => end,
-            _ => return Err(KernelError::InvalidArgument),
-        };
-        let new_offset = base
-            .checked_add(offset)
-            .ok_or(KernelError::InvalidArgument)?;
-        if new_offset < 0 {
-            return Err(KernelError::InvalidArgument);
-        }
-        self.open_files
-            .get_mut(description)
-            .map_err(map_file_table_error)?
-            .seek(new_offset as u64);
-        Ok(new_offset as u64)
-    }
-
-    fn syscall_statx(&self, context: SyscallContext) -> KernelResult<u64> {
-        self.syscall_newfstatat(context)
+    fn syscall_timer_gettime(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let _ = user_out_ptr::<MirageItimerspec>(context.arg(1))?;
+        let timer = self
+            .timers
+            .timer(context.caller, context.arg(0))
+            .map_err(map_timer_error)?;
+        write_user_value(
+            context.arg(1),
+            timer_to_itimerspec(timer, KERNEL_TIME.now().as_nanos()),
+        )?;
+        Ok(0)
     }
 
-    fn syscall_newfstatat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let path_ptr = context.arg(1);
-        let out = user_out_ptr::<CStat>(context.arg(2))?;
-        let stat = if path_ptr == 0 {
-            let description = self.fd_description(context.caller, context.arg(0) as usize)?;
-            let file = self
-                .open_files
-                .get(description)
-                .map_err(map_file_table_error)?;
-            self.root_fs.fstat(&file)
-        } else {
-            let path_buf = self.resolve_user_path(context.caller, context.arg(0), path_ptr)?;
-            self.root_fs.stat(path_buf.as_path()?)
-        }
-        .map_err(KernelError::Filesystem)?;
-        unsafe { out.write(CStat::from_kernel(stat)) };
+    fn syscall_timer_delete(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.timers
+            .delete_timer(context.caller, context.arg(0))
+            .map_err(map_timer_error)?;
         Ok(0)
     }
 
-    fn syscall_getdents64(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let entries = user_slice_mut_typed::<CDirEntry>(context.arg(1), context.arg(2) as usize)?;
-        let fd = context.arg(0) as usize;
-        let description = self.fd_description(context.caller, fd)?;
-        let file = self
-            .open_files
-            .get(description)
-            .map_err(map_file_table_error)?;
-        let mut cursor = file.cursor() as usize;
-        let mut written = 0usize;
-        while written < entries.len() {
-            let mut kernel_entry = [DirEntry::empty(); 1];
-            let count = self
-                .root_fs
-                .readdir_inode(file.inode(), cursor, &mut kernel_entry)
-                .map_err(KernelError::Filesystem)?;
-            if count == 0 {
-                break;
-            }
-            cursor += count;
-            entries[written] = CDirEntry::from_kernel(&kernel_entry[0], cursor);
-            written += 1;
-        }
-        self.open_files
-            .get_mut(description)
-            .map_err(map_file_table_error)?
-            .advance(written);
-        Ok(written as u64)
+    fn syscall_dup(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.duplicate_fd(
+            context.caller,
+            context.arg(0) as usize,
+            0,
+            DescriptorFlags::EMPTY,
+        )
     }
 
-    fn syscall_mkdirat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        let path = path_buf.as_path()?;
-        let requested = context.arg(2) as u32;
-        let umask = self.process_files(context.caller)?.umask().bits() as u32;
-        let credentials = self.fs_credentials_for(context.caller)?;
-        let mode = permissions_from_libc_mode(requested & !umask, credentials.uid, credentials.gid);
-        self.root_fs
-            .mkdir(path, mode, credentials)
-            .map(|_| 0)
-            .map_err(KernelError::Filesystem)
+    fn syscall_dup2(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.duplicate_fd_to(
+            context.caller,
+            context.arg(0) as usize,
+            context.arg(1) as usize,
+            DescriptorFlags::EMPTY,
+            false,
+        )
     }
 
-    fn syscall_unlinkat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        let path = path_buf.as_path()?;
+    fn syscall_dup3(&mut self, context: SyscallContext) -> KernelResult<u64> {
         let flags = context.arg(2);
-        let result = if (flags & AT_REMOVEDIR) != 0 {
-            self.root_fs
-                .rmdir(path, self.fs_credentials_for(context.caller)?)
-        } else {
-            self.root_fs
-                .unlink(path, self.fs_credentials_for(context.caller)?)
-        };
-        result.map(|_| 0).map_err(KernelError::Filesystem)
-    }
-
-    fn syscall_renameat2(&self, context: SyscallContext) -> KernelResult<u64> {
-        let old_path_buf =
-            self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        let new_path_buf =
-            self.resolve_user_path(context.caller, context.arg(2), context.arg(3))?;
-        let flags = context.arg(4);
-        if flags & !RENAME_NOREPLACE != 0 {
+        if flags & !O_CLOEXEC_RAW != 0 || context.arg(0) == context.arg(1) {
             return Err(KernelError::InvalidArgument);
         }
-        if flags & RENAME_NOREPLACE != 0 && self.root_fs.stat(new_path_buf.as_path()?).is_ok() {
-            return Err(KernelError::Filesystem(VfsError::AlreadyExists));
-        }
-        self.root_fs
-            .rename(
-                old_path_buf.as_path()?,
-                new_path_buf.as_path()?,
-                self.fs_credentials_for(context.caller)?,
-            )
-            .map(|_| 0)
-            .map_err(KernelError::Filesystem)
-    }
-
-    fn syscall_ftruncate(&self, context: SyscallContext) -> KernelResult<u64> {
-        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
-        let file = self
-            .open_files
-            .get(description)
-            .map_err(map_file_table_error)?;
-        self.root_fs
-            .ftruncate(
-                &file,
-                context.arg(1),
-                self.fs_credentials_for(context.caller)?,
-            )
-            .map(|_| 0)
-            .map_err(KernelError::Filesystem)
-    }
-
-    fn syscall_fsync(&self, context: SyscallContext) -> KernelResult<u64> {
-        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
-        let file = self
-            .open_files
-            .get(description)
-            .map_err(map_file_table_error)?;
-        self.root_fs
-            .fsync(&file)
-            .map(|_| 0)
-            .map_err(KernelError::Filesystem)
-    }
-
-    fn syscall_mount(&self, context: SyscallContext) -> KernelResult<u64> {
-        if context.arg(0) != 0 {
-            let _source = user_cstr(context.arg(0))?;
-        }
-        let target = self.user_path(context.arg(1))?;
-        let filesystem_type = if context.arg(2) == 0 {
-            DEFAULT_ROOT_FILESYSTEM
+        let descriptor_flags = if flags & O_CLOEXEC_RAW != 0 {
+            DescriptorFlags::CLOSE_ON_EXEC
         } else {
-            user_cstr(context.arg(2))?
+            DescriptorFlags::EMPTY
         };
-        if !target.is_root() || !is_supported_root_filesystem(filesystem_type) {
-            return Err(KernelError::Filesystem(VfsError::Unsupported));
-        }
-        Ok(0)
+        self.duplicate_fd_to(
+            context.caller,
+            context.arg(0) as usize,
+            context.arg(1) as usize,
+            descriptor_flags,
+            true,
+        )
     }
 
-    fn syscall_chdir(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let path_buf = self.resolve_user_path(context.caller, AT_FDCWD as u64, context.arg(0))?;
-        let stat = self
-            .root_fs
-            .stat(path_buf.as_path()?)
-            .map_err(KernelError::Filesystem)?;
-        if stat.kind != crate::kernel::fs::inode::InodeKind::Directory {
-            return Err(KernelError::Filesystem(VfsError::NotDirectory));
+    fn syscall_fcntl(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let fd = context.arg(0) as usize;
+        match context.arg(1) {
+            F_DUPFD => self.duplicate_fd(
+                context.caller,
+                fd,
+                context.arg(2) as usize,
+                DescriptorFlags::EMPTY,
+            ),
+            F_DUPFD_CLOEXEC => self.duplicate_fd(
+                context.caller,
+                fd,
+                context.arg(2) as usize,
+                DescriptorFlags::CLOSE_ON_EXEC,
+            ),
+            F_GETFD => Ok(self
+                .process_files(context.caller)?
+                .get(fd)
+                .map_err(map_process_file_table_error)?
+                .flags()
+                .bits() as u64),
+            F_SETFD => {
+                let flags = if context.arg(2) & FD_CLOEXEC != 0 {
+                    DescriptorFlags::CLOSE_ON_EXEC
+                } else {
+                    DescriptorFlags::EMPTY
+                };
+                self.process_files_mut(context.caller)?
+                    .get_mut(fd)
+                    .map_err(map_process_file_table_error)?
+                    .set_flags(flags);
+                Ok(0)
+            }
+            F_GETFL => {
+                let description = self.fd_description(context.caller, fd)?;
+                Ok(self
+                    .open_files
+                    .get(description)
+                    .map_err(map_file_table_error)?
+                    .flags()
+                    .bits() as u64)
+            }
+            _ => Err(KernelError::InvalidArgument),
         }
-        self.process_files_mut(context.caller)?
-            .set_cwd(ProcessPath::from_path(path_buf.as_path()?));
-        Ok(0)
     }
 
-    fn syscall_fchdir(&mut self, context: SyscallContext) -> KernelResult<u64> {
-        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
-        let file = self
-            .open_files
-            .get(description)
-            .map_err(map_file_table_error)?;
-        let stat = self.root_fs.fstat(&file).map_err(KernelError::Filesystem)?;
-        if stat.kind != crate::kernel::fs::inode::InodeKind::Directory {
-            return Err(KernelError::Filesystem(VfsError::NotDirectory));
+    fn syscall_ioctl(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let fd = context.arg(0) as usize;
+        let request = context.arg(1);
+        let arg = context.arg(2);
+        let object = self.fd_object(context.caller, fd)?;
+        match request {
+            FIONREAD => {
+                let out = user_out_ptr::<u32>(arg)?;
+                let available = match object {
+                    DescriptorObject::Regular(file) => {
+                        let stat = self.root_fs.fstat(&file).map_err(KernelError::Filesystem)?;
+                        stat.size.saturating_sub(file.cursor()).min(u32::MAX as u64) as u32
+                    }
+                    DescriptorObject::Pipe(endpoint) => self
+                        .pipes
+                        .get(endpoint.id().raw())
+                        .and_then(|entry| *entry)
+                        .map(|pipe| pipe.len as u32)
+                        .ok_or(KernelError::InvalidArgument)?,
+                    DescriptorObject::EventFd(id) => self
+                        .eventfds
+                        .get(id.raw())
+                        .and_then(|entry| *entry)
+                        .map(|eventfd| if eventfd.counter > 0 { 8 } else { 0 })
+                        .ok_or(KernelError::InvalidArgument)?,
+                    DescriptorObject::Device(_)
+                    | DescriptorObject::Socket(_)
+                    | DescriptorObject::Console(_) => 0,
+                };
+                unsafe { out.write(available) };
+                Ok(0)
+            }
+            BLKSSZGET => match object {
+                DescriptorObject::Device(handle) => {
+                    let out = user_out_ptr::<u32>(arg)?;
+                    let value = self
+                        .devices
+                        .sector_size(handle.id())
+                        .map_err(KernelError::DeviceFault)? as u32;
+                    unsafe { out.write(value) };
+                    Ok(0)
+                }
+                _ => Err(KernelError::InvalidArgument),
+            },
+            BLKGETSIZE64 => match object {
+                DescriptorObject::Device(handle) => {
+                    let out = user_out_ptr::<u64>(arg)?;
+                    let size = self
+                        .devices
+                        .sector_size(handle.id())
+                        .and_then(|sector_size| {
+                            self.devices
+                                .sector_count(handle.id())
+                                .map(|sectors| sectors.saturating_mul(sector_size as u64))
+                        })
+                        .map_err(KernelError::DeviceFault)?;
+                    unsafe { out.write(size) };
+                    Ok(0)
+                }
+                _ => Err(KernelError::InvalidArgument),
+            },
+            MIRAGE_IOCTL_DEVICE_INFO => match object {
+                DescriptorObject::Device(handle) => {
+                    let out = user_out_ptr::<MirageDeviceDescriptor>(arg)?;
+                    let descriptor = self
+                        .device_info(handle.id())
+                        .ok_or(KernelError::DeviceNotFound)?;
+                    unsafe { out.write(MirageDeviceDescriptor::from_descriptor(descriptor)) };
+                    Ok(0)
+                }
+                _ => Err(KernelError::InvalidArgument),
+            },
+            _ => Err(KernelError::InvalidArgument),
         }
-        let path = Path::new(file.path()).map_err(map_path_error)?;
-        self.process_files_mut(context.caller)?
-            .set_cwd(ProcessPath::from_path(path));
-        Ok(0)
     }
 
-    fn syscall_getcwd(&self, context: SyscallContext) -> KernelResult<u64> {
-        let buffer = user_slice_mut(context.arg(0), context.arg(1) as usize)?;
-        let cwd = self.process_files(context.caller)?.cwd();
-        let raw = cwd.as_str().as_bytes();
-        if buffer.len() < raw.len() + 1 {
+    fn syscall_pipe2(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let out = user_slice_mut_typed::<i32>(context.arg(0), 2)?;
+        let flags = context.arg(1);
+        if flags & !PIPE2_SUPPORTED_FLAGS != 0 {
             return Err(KernelError::InvalidArgument);
         }
-        buffer[..raw.len()].copy_from_slice(raw);
-        buffer[raw.len()] = 0;
-        Ok(context.arg(0))
-    }
-
-    fn syscall_faccessat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        let mode = context.arg(2) as u32;
-        if mode
-            & !(crate::kernel::fs::stdlib::R_OK
-                | crate::kernel::fs::stdlib::W_OK
-                | crate::kernel::fs::stdlib::X_OK)
-            != 0
+        let pipe_id = self.allocate_pipe()?;
+        let descriptor_flags = descriptor_flags_from_raw(flags);
+        let read_description = self
+            .open_files
+            .insert_object(DescriptorObject::Pipe(PipeEndpoint::new(
+                pipe_id,
+                PipeDirection::Read,
+            )))
+            .map_err(map_file_table_error)?;
+        let write_description =
+            match self
+                .open_files
+                .insert_object(DescriptorObject::Pipe(PipeEndpoint::new(
+                    pipe_id,
+                    PipeDirection::Write,
+                ))) {
+                Ok(description) => description,
+                Err(error) => {
+                    let _ = self.close_open_description(read_description);
+                    self.pipes[pipe_id.raw()] = None;
+                    return Err(map_file_table_error(error));
+                }
+            };
+        let read_fd = match self
+            .process_files_mut(context.caller)?
+            .open(read_description, descriptor_flags)
         {
-            return Err(KernelError::InvalidArgument);
-        }
-        let stat = self
-            .root_fs
-            .stat(path_buf.as_path()?)
-            .map_err(KernelError::Filesystem)?;
-        if mode == crate::kernel::fs::stdlib::F_OK {
-            return Ok(0);
-        }
-        let permissions = crate::kernel::fs::Permissions::new(stat.mode, stat.uid, stat.gid);
-        let credentials = self.fs_credentials_for(context.caller)?;
-        let allowed = match (
-            mode & crate::kernel::fs::stdlib::R_OK != 0,
-            mode & crate::kernel::fs::stdlib::W_OK != 0,
-            mode & crate::kernel::fs::stdlib::X_OK != 0,
-        ) {
-            (true, true, true) => {
-                permissions.allows(credentials, AccessMode::ReadWrite)
-                    && permissions.allows(credentials, AccessMode::Execute)
-            }
-            (true, true, false) => permissions.allows(credentials, AccessMode::ReadWrite),
-            (true, false, false) => permissions.allows(credentials, AccessMode::Read),
-            (false, true, false) => permissions.allows(credentials, AccessMode::Write),
-            (false, false, true) => permissions.allows(credentials, AccessMode::Execute),
-            (true, false, true) => {
-                permissions.allows(credentials, AccessMode::Read)
-                    && permissions.allows(credentials, AccessMode::Execute)
+            Ok(fd) => fd,
+            Err(error) => {
+                let _ = self.close_open_description(read_description);
+                let _ = self.close_open_description(write_description);
+                self.pipes[pipe_id.raw()] = None;
+                return Err(map_process_file_table_error(error));
             }
-            (false, true, true) => {
-                permissions.allows(credentials, AccessMode::Write)
-                    && permissions.allows(credentials, AccessMode::Execute)
+        };
+        let write_fd = match self
+            .process_files_mut(context.caller)?
+            .open(write_description, descriptor_flags)
+        {
+            Ok(fd) => fd,
+            Err(error) => {
+                let closed_read = self
+                    .process_files_mut(context.caller)
+                    .ok()
+                    .and_then(|files| files.close(read_fd).ok());
+                if let Some(descriptor) = closed_read {
+                    let _ = self.close_open_description(descriptor.description());
+                }
+                let _ = self.close_open_description(write_description);
+                self.pipes[pipe_id.raw()] = None;
+                return Err(map_process_file_table_error(error));
             }
-            _ => true,
         };
-        if allowed {
-            Ok(0)
-        } else {
-            Err(KernelError::Filesystem(VfsError::PermissionDenied))
-        }
-    }
-
-    fn syscall_fchmodat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        self.root_fs
-            .chmod(
-                path_buf.as_path()?,
-                context.arg(2) as u16,
-                self.fs_credentials_for(context.caller)?,
-            )
-            .map(|_| 0)
-            .map_err(KernelError::Filesystem)
-    }
-
-    fn syscall_fchownat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        self.root_fs
-            .chown(
-                path_buf.as_path()?,
-                context.arg(2) as u16,
-                context.arg(3) as u16,
-                self.fs_credentials_for(context.caller)?,
-            )
-            .map(|_| 0)
-            .map_err(KernelError::Filesystem)
+        out[0] = read_fd as i32;
+        out[1] = write_fd as i32;
+        Ok(0)
     }
 
-    fn syscall_symlinkat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let target = self.user_path_str(context.arg(0))?;
-        Path::new_unchecked_rooted(target).map_err(map_path_error)?;
-        let link_path_buf =
-            self.resolve_user_path(context.caller, context.arg(1), context.arg(2))?;
-        self.root_fs
-            .symlink(
-                target,
-                link_path_buf.as_path()?,
-                self.fs_credentials_for(context.caller)?,
-            )
-            .map(|_| 0)
-            .map_err(KernelError::Filesystem)
+    fn syscall_poll(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let fds = user_slice_mut_typed::<MiragePollFd>(context.arg(0), context.arg(1) as usize)?;
+        let timeout_ms = context.arg(2) as i32;
+        self.poll_descriptors(context.caller, context.thread, fds, timeout_ms)
     }
 
-    fn syscall_readlinkat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        let buffer = user_slice_mut(context.arg(2), context.arg(3) as usize)?;
-        self.root_fs
-            .readlink(path_buf.as_path()?, buffer)
-            .map(|n| n as u64)
-            .map_err(KernelError::Filesystem)
+    fn syscall_pselect(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let fds = user_slice_mut_typed::<MiragePollFd>(context.arg(0), context.arg(1) as usize)?;
+        let timeout_ms = if context.arg(2) == 0 {
+            -1
+        } else {
+            let timeout = read_user_value::<MirageTimespec>(context.arg(2))?;
+            let nanos = timespec_to_nanos(timeout)?;
+            let millis = (nanos / 1_000_000).min(i32::MAX as u128) as i32;
+            millis
+        };
+        self.poll_descriptors(context.caller, context.thread, fds, timeout_ms)
     }
 
-    fn syscall_linkat(&self, context: SyscallContext) -> KernelResult<u64> {
-        let old_path_buf =
-            self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
-        let new_path_buf =
-            self.resolve_user_path(context.caller, context.arg(2), context.arg(3))?;
-        let flags = context.arg(4);
-        if flags & !AT_SYMLINK_FOLLOW != 0 {
+    fn syscall_eventfd(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let initial = context.arg(0) as u32 as u64;
+        let flags = context.arg(1);
+        if flags & !EVENTFD_SUPPORTED_FLAGS != 0 || flags & O_DIRECT_RAW != 0 {
             return Err(KernelError::InvalidArgument);
         }
-        self.root_fs
-            .link(
-                old_path_buf.as_path()?,
-                new_path_buf.as_path()?,
-                self.fs_credentials_for(context.caller)?,
-            )
-            .map(|_| 0)
-            .map_err(KernelError::Filesystem)
-    }
-
-    fn resolve_user_path(
-        &self,
-        pid: ProcessId,
-        dirfd: u64,
-        path_ptr: u64,
-    ) -> KernelResult<KernelPathBuf> {
-        let raw = self.user_path_str(path_ptr)?;
-        self.resolve_path_str(pid, dirfd, raw)
-    }
-
-    fn resolve_path_str(
-        &self,
-        pid: ProcessId,
-        dirfd: u64,
-        raw: &str,
-    ) -> KernelResult<KernelPathBuf> {
-        Path::new_unchecked_rooted(raw).map_err(map_path_error)?;
-        let files = self.process_files(pid)?;
-        let root = files.root();
-        let root_str = root.as_str();
-        let root_len = root.len();
-        let mut resolved = if raw.starts_with('/') {
-            KernelPathBuf::from_str(root_str)?
-        } else if dirfd as i32 == AT_FDCWD {
-            KernelPathBuf::from_str(files.cwd().as_str())?
-        } else {
-            let description = files
-                .get(dirfd as usize)
-                .map_err(map_process_file_table_error)?
-                .description();
-            let file = self
-                .open_files
-                .get(description)
-                .map_err(map_file_table_error)?;
-            let metadata = self.root_fs.fstat(&file).map_err(KernelError::Filesystem)?;
-            if metadata.kind != crate::kernel::fs::inode::InodeKind::Directory {
-                return Err(KernelError::Filesystem(VfsError::NotDirectory));
+        let id = self.allocate_eventfd(initial, flags & EFD_SEMAPHORE != 0)?;
+        let description = match self.open_files.insert_object(DescriptorObject::EventFd(id)) {
+            Ok(description) => description,
+            Err(error) => {
+                self.eventfds[id.raw()] = None;
+                return Err(map_file_table_error(error));
             }
-            KernelPathBuf::from_str(file.path())?
         };
-        for component in raw.split('/') {
-            let before = resolved.len;
-            resolved.push_component(component)?;
-            if component == ".." && resolved.len < root_len {
-                resolved.truncate_to_root(root_len);
-            } else if before < root_len {
-                resolved.truncate_to_root(root_len);
+        match self
+            .process_files_mut(context.caller)?
+            .open(description, descriptor_flags_from_raw(flags))
+        {
+            Ok(fd) => Ok(fd as u64),
+            Err(error) => {
+                let _ = self.close_open_description(description);
+                self.eventfds[id.raw()] = None;
+                Err(map_process_file_table_error(error))
             }
         }
-        resolved.as_path()?;
-        Ok(resolved)
-    }
-
-    fn user_path(&self, path_ptr: u64) -> KernelResult<Path<'_>> {
-        let raw = self.user_path_str(path_ptr)?;
-        Path::new(raw).map_err(map_path_error)
     }
 
-    fn user_path_str(&self, path_ptr: u64) -> KernelResult<&'_ str> {
-        let bytes = user_cstr(path_ptr)?;
-        core::str::from_utf8(bytes).map_err(|_| KernelError::InvalidArgument)
-    }
-
-    fn replace_process_image(
-        &mut self,
-        pid: ProcessId,
-        current_thread: Option<ThreadId>,
-        entry_point: u64,
-        stack_pointer: u64,
-        address_space_root: u64,
-    ) -> KernelResult<()> {
-        let index = self.locate_process(pid)?;
-        if let Some(pcb) = self.process_table[index].as_mut() {
-            pcb.set_exec_image(entry_point, address_space_root);
-            pcb.thread_count = 0;
+    fn syscall_socket(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let domain = i32::try_from(context.arg(0)).map_err(|_| KernelError::InvalidArgument)?;
+        let socket_type =
+            i32::try_from(context.arg(1)).map_err(|_| KernelError::InvalidArgument)?;
+        let protocol = i32::try_from(context.arg(2)).map_err(|_| KernelError::InvalidArgument)?;
+        if domain < 0 || socket_type < 0 {
+            return Err(KernelError::InvalidArgument);
         }
 
-        if self
-            .mtss_scheduler
-            .terminate_task(Self::mtss_task_id(pid))
-            .is_ok()
+        let handle = self.allocate_socket_handle()?;
+        let description = match self
+            .open_files
+            .insert_object(DescriptorObject::Socket(handle))
         {
-            let _ = self.mtss_scheduler.reap_task(Self::mtss_task_id(pid));
-        }
-        let mut kept_thread = current_thread;
-        if kept_thread.is_none() {
-            let mut idx = 0usize;
-            while idx < Self::THREAD_CAPACITY {
-                if let Some(thread) = self.thread_table[idx] {
-                    if thread.process == pid {
-                        kept_thread = Some(thread.id);
-                        break;
-                    }
-                }
-                idx += 1;
+            Ok(description) => description,
+            Err(error) => return Err(map_file_table_error(error)),
+        };
+        let fd = match self
+            .process_files_mut(context.caller)?
+            .open(description, DescriptorFlags::EMPTY)
+        {
+            Ok(fd) => fd,
+            Err(error) => {
+                let _ = self.close_open_description(description);
+                return Err(map_process_file_table_error(error));
             }
-        }
+        };
 
-        let mut idx = 0usize;
-        while idx < Self::THREAD_CAPACITY {
-            if let Some(thread) = self.thread_table[idx] {
-                if thread.process == pid && Some(thread.id) != kept_thread {
-                    self.thread_table[idx] = None;
-                }
+        let request = NetworkSocketRequest {
+            header: self.network_request_header(
+                NetworkOpcode::Socket,
+                context.caller,
+                socket_type as u32,
+            ),
+            socket_handle: handle.raw(),
+            domain,
+            socket_type,
+            protocol,
+            reserved: 0,
+        };
+        if let Err(error) = self.send_network_request(context.caller, &request) {
+            if let Ok(descriptor) = self
+                .process_files_mut(context.caller)
+                .and_then(|files| files.close(fd).map_err(map_process_file_table_error))
+            {
+                let _ = self.close_open_description(descriptor.description());
             }
-            idx += 1;
+            return Err(error);
         }
 
-        let thread_id = if let Some(thread_id) = kept_thread {
-            let thread_index = self.locate_thread(thread_id)?;
-            let priority = self.process_table[index]
-                .as_ref()
-                .ok_or(KernelError::UnknownProcess)?
-                .priority;
-            if let Some(tcb) = self.thread_table[thread_index].as_mut() {
-                tcb.replace_exec_image(entry_point, stack_pointer);
-                tcb.priority = priority;
-            }
-            thread_id
-        } else {
-            self.create_thread(
-                pid,
-                entry_point,
-                self.process_table[index]
-                    .as_ref()
-                    .ok_or(KernelError::UnknownProcess)?
-                    .priority,
-            )?
+        Ok(fd as u64)
+    }
+
+    fn syscall_bind(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
+        let addr_ptr = context.arg(1);
+        let addr_len = u32::try_from(context.arg(2)).map_err(|_| KernelError::InvalidArgument)?;
+        validate_user_range(addr_ptr, addr_len as usize)?;
+        let request = NetworkSockaddrRequest {
+            header: self.network_request_header(NetworkOpcode::Bind, context.caller, 0),
+            socket_handle: handle.raw(),
+            addr_ptr,
+            addr_len,
+            value: 0,
+            result_addr_len_ptr: 0,
+            accepted_socket_handle: 0,
         };
+        self.send_network_request(context.caller, &request)?;
+        Ok(0)
+    }
 
-        if let Some(pcb) = self.process_table[index].as_mut() {
-            pcb.thread_count = 1;
+    fn syscall_listen(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
+        let backlog = i32::try_from(context.arg(1)).map_err(|_| KernelError::InvalidArgument)?;
+        if backlog < 0 {
+            return Err(KernelError::InvalidArgument);
         }
-        let pcb = self.process_table[index]
-            .as_ref()
-            .ok_or(KernelError::UnknownProcess)?;
-        let priority = pcb.priority;
-        let parent = pcb.parent;
-        self.mtss_create_task(pid, parent, address_space_root, priority)
-            .and_then(|_| self.mtss_create_thread(pid, thread_id, priority))
-            .and_then(|_| self.mtss_enqueue_thread(thread_id))?;
-        self.set_process_ready_via_mtss(pid, index)
+        let request = NetworkSockaddrRequest {
+            header: self.network_request_header(NetworkOpcode::Listen, context.caller, 0),
+            socket_handle: handle.raw(),
+            addr_ptr: 0,
+            addr_len: 0,
+            value: backlog,
+            result_addr_len_ptr: 0,
+            accepted_socket_handle: 0,
+        };
+        self.send_network_request(context.caller, &request)?;
+        Ok(0)
     }
 
-    fn wait_for_child(
-        &mut self,
-        parent: ProcessId,
-        current_thread: Option<ThreadId>,
-        selector: i64,
-        status_ptr: u64,
-        options: u64,
-    ) -> KernelResult<u64> {
-        const WNOHANG: u64 = 1;
-        let parent_index = self.locate_process(parent)?;
-        let parent_pgid = self.process_table[parent_index]
-            .as_ref()
-            .ok_or(KernelError::UnknownProcess)?
-            .process_group;
-        let wait_selector = decode_child_wait_selector(selector, parent_pgid);
-        let mut saw_child = false;
-        let mut idx = 0usize;
-        while idx < MAX_PROC {
-            if let Some(pcb) = self.process_table[idx] {
-                if pcb.parent == Some(parent) && wait_selector.matches(pcb.pid, pcb.process_group) {
-                    saw_child = true;
-                    if pcb.state == ProcessState::Zombie {
-                        let status = pcb.exit_status.unwrap_or(ExitStatus::exited(0));
-                        if status_ptr != 0 {
-                            write_user_value::<i32>(status_ptr, status.raw())?;
-                        }
-                        let pid = pcb.pid;
-                        self.clear_child_wait(parent, current_thread);
-                        self.reap_process_at(idx);
-                        return Ok(pid.raw());
-                    }
-                }
-            }
-            idx += 1;
-        }
-        if saw_child && (options & WNOHANG) != 0 {
-            Ok(0)
-        } else if saw_child {
-            self.record_child_wait(parent, parent_index, current_thread, wait_selector);
-            Err(KernelError::MessageQueueEmpty)
-        } else {
-            Err(KernelError::UnknownProcess)
-        }
-    }
-
-    fn record_child_wait(
-        &mut self,
-        parent: ProcessId,
-        parent_index: usize,
-        current_thread: Option<ThreadId>,
-        selector: ChildWaitSelector,
-    ) {
-        if let Some(pcb) = self.process_table[parent_index].as_mut() {
-            pcb.wait_for_child(selector);
-        }
-        if let Some(thread) = current_thread {
-            if let Ok(thread_index) = self.locate_thread(thread) {
-                if let Some(tcb) = self.thread_table[thread_index].as_mut() {
-                    tcb.wait_for_child(selector);
-                    tcb.block();
-                    let _ = self
-                        .mtss_scheduler
-                        .block_thread(Self::mtss_thread_id(thread));
-                }
+    fn syscall_accept(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let listener = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
+        let addr_ptr = context.arg(1);
+        let addr_len_ptr = context.arg(2);
+        if addr_ptr != 0 {
+            if addr_len_ptr == 0 {
+                return Err(KernelError::InvalidPointer);
             }
-        } else {
-            self.block_threads_for_process(parent);
+            let addr_len = read_user_value::<u32>(addr_len_ptr)?;
+            validate_user_range(addr_ptr, addr_len as usize)?;
+        } else if addr_len_ptr != 0 {
+            let _ = read_user_value::<u32>(addr_len_ptr)?;
         }
-        self.block_process_at_index(parent, parent_index);
-    }
-
-    fn clear_child_wait(&mut self, parent: ProcessId, current_thread: Option<ThreadId>) {
-        if let Ok(parent_index) = self.locate_process(parent) {
-            if let Some(pcb) = self.process_table[parent_index].as_mut() {
-                pcb.clear_child_wait();
+        let accepted = self.allocate_socket_handle()?;
+        let description = self
+            .open_files
+            .insert_object(DescriptorObject::Socket(accepted))
+            .map_err(map_file_table_error)?;
+        let fd = match self
+            .process_files_mut(context.caller)?
+            .open(description, DescriptorFlags::EMPTY)
+        {
+            Ok(fd) => fd,
+            Err(error) => {
+                let _ = self.close_open_description(description);
+                return Err(map_process_file_table_error(error));
             }
-        }
-        if let Some(thread) = current_thread {
-            if let Ok(thread_index) = self.locate_thread(thread) {
-                if let Some(tcb) = self.thread_table[thread_index].as_mut() {
-                    tcb.clear_child_wait();
-                }
+        };
+        let request = NetworkSockaddrRequest {
+            header: self.network_request_header(NetworkOpcode::Accept, context.caller, 0),
+            socket_handle: listener.raw(),
+            addr_ptr,
+            addr_len: 0,
+            value: 0,
+            result_addr_len_ptr: addr_len_ptr,
+            accepted_socket_handle: accepted.raw(),
+        };
+        if let Err(error) = self.send_network_request(context.caller, &request) {
+            if let Ok(descriptor) = self
+                .process_files_mut(context.caller)
+                .and_then(|files| files.close(fd).map_err(map_process_file_table_error))
+            {
+                let _ = self.close_open_description(descriptor.description());
             }
-        } else {
-            self.clear_child_wait_threads(parent);
+            return Err(error);
         }
-    }
 
-    fn clear_child_wait_threads(&mut self, parent: ProcessId) {
-        let mut idx = 0usize;
-        while idx < Self::THREAD_CAPACITY {
-            if let Some(thread) = self.thread_table[idx].as_mut() {
-                if thread.process == parent {
-                    thread.clear_child_wait();
-                }
-            }
-            idx += 1;
-        }
+        Ok(fd as u64)
     }
 
-    fn wake_parent_child_waiters(&mut self, child: ProcessId) -> KernelResult<()> {
-        let child_index = self.locate_process(child)?;
-        let Some(child_pcb) = self.process_table[child_index] else {
-            return Err(KernelError::UnknownProcess);
-        };
-        let Some(parent) = child_pcb.parent else {
-            return Ok(());
+    fn syscall_connect(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
+        let addr_ptr = context.arg(1);
+        let addr_len = u32::try_from(context.arg(2)).map_err(|_| KernelError::InvalidArgument)?;
+        validate_user_range(addr_ptr, addr_len as usize)?;
+        let request = NetworkSockaddrRequest {
+            header: self.network_request_header(NetworkOpcode::Connect, context.caller, 0),
+            socket_handle: handle.raw(),
+            addr_ptr,
+            addr_len,
+            value: 0,
+            result_addr_len_ptr: 0,
+            accepted_socket_handle: 0,
         };
-        let parent_index = self.locate_process(parent)?;
-        let should_wake = self.process_table[parent_index]
-            .as_ref()
-            .and_then(|pcb| pcb.child_wait)
-            .map(|selector| selector.matches(child, child_pcb.process_group))
-            .unwrap_or(false);
-        if !should_wake {
-            return Ok(());
-        }
-        if let Some(parent_pcb) = self.process_table[parent_index].as_mut() {
-            parent_pcb.clear_child_wait();
-        }
-        self.clear_child_wait_threads(parent);
-        if self.process_table[parent_index]
-            .as_ref()
-            .map(|pcb| pcb.state == ProcessState::Blocked)
-            .unwrap_or(false)
-        {
-            self.set_process_ready_via_mtss(parent, parent_index)?;
-        }
-        self.make_threads_ready(parent)
-    }
-
-    fn reap_process_at(&mut self, index: usize) {
-        if let Some(pcb) = self.process_table[index] {
-            self.security.revoke_task(pcb.pid);
-            self.process_table[index] = None;
-            self.timers.release_process(pcb.pid);
-        }
+        self.send_network_request(context.caller, &request)?;
+        Ok(0)
     }
 
-    fn queue_signal_to_parent(&mut self, child: ProcessId, signal: u8) -> KernelResult<()> {
-        let child_index = self.locate_process(child)?;
-        if let Some(parent) = self.process_table[child_index]
-            .as_ref()
-            .and_then(|pcb| pcb.parent)
-        {
-            self.queue_signal(parent, signal)?;
-        }
-        Ok(())
+    fn syscall_sendmsg(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
+        let message_ptr = context.arg(1);
+        validate_user_range(message_ptr, 1)?;
+        let request = NetworkSendmsgRequest {
+            header: self.network_request_header(NetworkOpcode::Sendmsg, context.caller, 0),
+            socket_handle: handle.raw(),
+            message_ptr,
+            flags: context.arg(2),
+            reserved: 0,
+        };
+        self.send_network_request(context.caller, &request)?;
+        Ok(0)
     }
 
-    fn queue_signal(&mut self, pid: ProcessId, signal: u8) -> KernelResult<()> {
-        let index = self.locate_process(pid)?;
-        self.process_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownProcess)?
-            .queue_signal(signal)
-            .map_err(|_| KernelError::InvalidArgument)
+    fn syscall_recvmsg(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let handle = self.socket_handle_for_fd(context.caller, context.arg(0) as usize)?;
+        let message_ptr = context.arg(1);
+        validate_user_range(message_ptr, 1)?;
+        let request = NetworkRecvmsgRequest {
+            header: self.network_request_header(NetworkOpcode::Recvmsg, context.caller, 0),
+            socket_handle: handle.raw(),
+            message_ptr,
+            flags: context.arg(2),
+            reserved: 0,
+        };
+        self.send_network_request(context.caller, &request)?;
+        Ok(0)
     }
 
-    fn send_signal(&mut self, selector: i64, signal: u8) -> KernelResult<u64> {
-        if signal == 0 {
-            self.ensure_process_exists(ProcessId::new(selector as u64))?;
-            return Ok(0);
-        }
-        let mut delivered = 0u64;
-        if selector > 0 {
-            self.queue_signal(ProcessId::new(selector as u64), signal)?;
-            return Ok(0);
-        }
-        let target_group = if selector == 0 {
-            None
+    fn syscall_clone(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let entry_point = context.arg(0);
+        let priority = decode_priority(context.arg(1))?;
+        let flags = context.arg(2);
+        let request = if flags == 0 && context.arg(3) == 0 && context.arg(4) == 0 {
+            CloneTaskRequest::legacy_thread(context.caller, context.thread, entry_point, priority)
         } else {
-            Some(ProcessGroupId::new((-selector) as u64))
+            let tls_base = if context.arg(3) == 0 {
+                None
+            } else {
+                Some(context.arg(3))
+            };
+            let child_stack = if context.arg(4) == 0 {
+                None
+            } else {
+                Some(context.arg(4))
+            };
+            CloneTaskRequest::new(
+                context.caller,
+                context.thread,
+                entry_point,
+                priority,
+                child_stack,
+                tls_base,
+                flags,
+            )
         };
-        let mut idx = 0usize;
-        while idx < MAX_PROC {
-            if let Some(pcb) = self.process_table[idx] {
-                let matches = target_group
-                    .map(|pgid| pcb.process_group == pgid)
-                    .unwrap_or(true);
-                if matches && pcb.state != ProcessState::Zombie {
-                    if self.queue_signal(pcb.pid, signal).is_ok() {
-                        delivered += 1;
-                    }
-                }
-            }
-            idx += 1;
-        }
-        if delivered == 0 {
-            Err(KernelError::UnknownProcess)
-        } else {
-            Ok(0)
-        }
-    }
-
-    fn deliver_signal_checkpoint(&mut self, pid: ProcessId, thread: ThreadId) -> KernelResult<()> {
-        let thread_index = self.locate_thread(thread)?;
-        let mask = self.thread_table[thread_index]
-            .as_ref()
-            .ok_or(KernelError::UnknownThread)?
-            .signal_mask;
-        let process_index = self.locate_process(pid)?;
-        let Some(signal) = self.process_table[process_index]
-            .as_mut()
-            .ok_or(KernelError::UnknownProcess)?
-            .take_deliverable_signal(mask)
-        else {
-            return Ok(());
-        };
-        let action = self.process_table[process_index]
-            .as_ref()
-            .ok_or(KernelError::UnknownProcess)?
-            .signal_actions[signal as usize];
-        if action.handler == 0 && matches!(signal, SIGKILL | SIGTERM) {
-            self.exit_process(pid, ExitStatus::signaled(signal));
-            return Ok(());
-        }
-        if let Some(tcb) = self.thread_table[thread_index].as_mut() {
-            tcb.deliver_signal(signal, action.handler);
-        }
-        Ok(())
-    }
-
-    fn process_credentials(
-        &self,
-        pid: ProcessId,
-    ) -> KernelResult<&crate::kernel::process::ProcessCredentials> {
-        let index = self.locate_process(pid)?;
-        Ok(&self.process_table[index]
-            .as_ref()
-            .ok_or(KernelError::UnknownProcess)?
-            .credentials)
-    }
-
-    fn process_credentials_mut(
-        &mut self,
-        pid: ProcessId,
-    ) -> KernelResult<&mut crate::kernel::process::ProcessCredentials> {
-        let index = self.locate_process(pid)?;
-        Ok(&mut self.process_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownProcess)?
-            .credentials)
+        self.clone_thread(request).map(|thread| thread.raw())
     }
 
-    fn fs_credentials_for(&self, pid: ProcessId) -> KernelResult<FsCredentials> {
-        let credentials = self.process_credentials(pid)?;
-        Ok(FsCredentials::user_with_groups(
-            credentials.euid,
-            credentials.egid,
-            credentials.supplementary_groups(),
-            credentials.supplementary_group_count(),
-        ))
-    }
+    fn syscall_futex(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let uaddr = context.arg(0);
+        let operation = context.arg(1);
+        let value = context.arg(2) as i32;
+        let timeout_ptr = context.arg(3);
+        let command = operation & FUTEX_CMD_MASK;
+        let key = self.futex_key(context.caller, uaddr)?;
 
-    fn process_files(
-        &self,
-        pid: ProcessId,
-    ) -> KernelResult<&crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>> {
-        let index = self.locate_process(pid)?;
-        Ok(&self.process_table[index]
-            .as_ref()
-            .ok_or(KernelError::UnknownProcess)?
-            .files)
+        match command {
+            FUTEX_WAIT => {
+                let thread = context.thread.ok_or(KernelError::UnknownThread)?;
+                let observed = read_user_value::<i32>(uaddr)?;
+                if observed != value {
+                    return Err(KernelError::MessageQueueEmpty);
+                }
+                let deadline = if timeout_ptr == 0 {
+                    None
+                } else {
+                    let requested = read_user_value::<MirageTimespec>(timeout_ptr)?;
+                    let duration_ns = timespec_to_nanos(requested)?;
+                    Some(KERNEL_TIME.now().as_nanos().saturating_add(duration_ns))
+                };
+                self.futexes
+                    .enqueue(key, thread, deadline)
+                    .map_err(|_| KernelError::AllocationFailed)?;
+                self.block_thread(thread)?;
+                Ok(0)
+            }
+            FUTEX_WAKE => {
+                let limit = if value < 0 { 0 } else { value as usize };
+                let mut woken_threads = [None; MAX_THREADS];
+                let count = self.futexes.wake(key, limit, &mut woken_threads);
+                self.wake_futex_threads(&woken_threads, count, 0)?;
+                Ok(count as u64)
+            }
+            _ => Err(KernelError::InvalidArgument),
+        }
     }
 
-    fn process_files_mut(
-        &mut self,
-        pid: ProcessId,
-    ) -> KernelResult<&mut crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>> {
-        let index = self.locate_process(pid)?;
-        Ok(&mut self.process_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownProcess)?
-            .files)
+    fn syscall_set_thread_area(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
+        let base = context.arg(0);
+        validate_tls_base(base)?;
+        self.set_thread_fs_base(thread, base)
     }
 
-    fn duplicate_fd(
-        &mut self,
-        pid: ProcessId,
-        old_fd: usize,
-        min_fd: usize,
-        flags: DescriptorFlags,
-    ) -> KernelResult<u64> {
-        let description = self.fd_description(pid, old_fd)?;
-        self.open_files
-            .increment_ref_count(description)
-            .map_err(map_file_table_error)?;
-        match self
-            .process_files_mut(pid)?
-            .open_at_or_above(description, flags, min_fd)
-        {
-            Ok(fd) => Ok(fd as u64),
-            Err(error) => {
-                let _ = self.close_open_description(description);
-                Err(map_process_file_table_error(error))
+    fn syscall_arch_prctl(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let thread = context.thread.ok_or(KernelError::UnknownThread)?;
+        match context.arg(0) {
+            ARCH_SET_FS => {
+                let base = context.arg(1);
+                validate_tls_base(base)?;
+                self.set_thread_fs_base(thread, base)
+            }
+            ARCH_SET_GS => {
+                let base = context.arg(1);
+                validate_tls_base(base)?;
+                self.set_thread_gs_base(thread, base)
+            }
+            ARCH_GET_FS => {
+                let base = self.thread_fs_base(thread)?;
+                write_user_value::<u64>(context.arg(1), base)?;
+                Ok(0)
+            }
+            ARCH_GET_GS => {
+                let base = self.thread_gs_base(thread)?;
+                write_user_value::<u64>(context.arg(1), base)?;
+                Ok(0)
             }
+            _ => Err(KernelError::InvalidArgument),
         }
     }
 
-    fn duplicate_fd_to(
-        &mut self,
-        pid: ProcessId,
-        old_fd: usize,
-        new_fd: usize,
-        flags: DescriptorFlags,
-        reject_same: bool,
-    ) -> KernelResult<u64> {
-        let description = self.fd_description(pid, old_fd)?;
-        if old_fd == new_fd {
-            if reject_same {
-                return Err(KernelError::InvalidArgument);
-            }
-            return Ok(new_fd as u64);
-        }
-        self.open_files
-            .increment_ref_count(description)
-            .map_err(map_file_table_error)?;
-        let replaced = match self
-            .process_files_mut(pid)?
-            .duplicate_to(new_fd, description, flags)
-        {
-            Ok(previous) => previous,
-            Err(error) => {
-                let _ = self.close_open_description(description);
-                return Err(map_process_file_table_error(error));
+    fn syscall_spawn(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let resolved = copy_user_path_with_len(context.arg(0), context.arg(1) as usize)?;
+        let path = resolved.as_path()?;
+        let stat = match self.root_fs.stat(path) {
+            Ok(stat) => stat,
+            #[cfg(test)]
+            Err(VfsError::NotFound) if is_test_spawn_path(resolved.as_str()) => {
+                return self
+                    .spawn_test_loaded_task(context.caller, resolved.as_str())
+                    .map(|pid| pid.raw());
             }
+            Err(error) => return Err(KernelError::Filesystem(error)),
         };
-        if let Some(previous) = replaced {
-            self.close_open_description(previous.description())?;
+        if stat.kind != InodeKind::RegularFile {
+            return Err(KernelError::Filesystem(VfsError::PermissionDenied));
         }
-        Ok(new_fd as u64)
+
+        let argv = exec_vector_metadata_with_count(
+            context.arg(2),
+            context.arg(3) as usize,
+            MAX_EXEC_ARGS,
+        )?;
+        let envp = exec_vector_metadata_with_count(
+            context.arg(4),
+            context.arg(5) as usize,
+            MAX_EXEC_ENVS,
+        )?;
+        self.spawn_loaded_task(
+            context.caller,
+            &resolved,
+            stat,
+            argv,
+            envp,
+            ProcessPriority::Normal,
+            self.current_credentials(context.caller)?,
+        )
+        .map(|pid| pid.raw())
     }
 
-    fn fd_description(&self, pid: ProcessId, fd: usize) -> KernelResult<FileDescriptionId> {
-        self.process_files(pid)?
-            .get(fd)
-            .map(|descriptor| descriptor.description())
-            .map_err(map_process_file_table_error)
+    #[cfg(test)]
+    fn spawn_test_loaded_task(&mut self, parent: ProcessId, path: &str) -> KernelResult<ProcessId> {
+        let _ = path;
+        let credentials = self.current_credentials(parent)?;
+        let child = self.spawn_task(SpawnTaskRequest {
+            parent: Some(parent),
+            entry_point: 0x400078,
+            priority: ProcessPriority::Normal,
+            credentials,
+        })?;
+        let index = self.locate_process(child)?;
+        if let Some(pcb) = self.process_table[index].as_mut() {
+            pcb.address_space_root = child.raw();
+        }
+        let thread = self
+            .first_thread_for_process(child)
+            .ok_or(KernelError::UnknownThread)?;
+        let thread_index = self.locate_thread(thread)?;
+        if let Some(tcb) = self.thread_table.entry_mut(thread_index) {
+            tcb.replace_exec_image(0x400078, 0x0000_7fff_ffff_ffc0);
+        }
+        Ok(child)
     }
 
-    fn ensure_process_console_descriptors(
-        &mut self,
-        files: &mut crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>,
-    ) -> KernelResult<()> {
-        self.ensure_console_descriptor(files, 1, ConsoleDescriptor::Stdout)?;
-        self.ensure_console_descriptor(files, 2, ConsoleDescriptor::Stderr)
+    fn syscall_send_ipc(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let receiver = ProcessId::new(context.arg(0));
+        let data_ptr = context.arg(1) as *const u8;
+        let data_len = context.arg(2) as usize;
+        let security_class = decode_security_class(context.arg(3))?;
+        if data_len > 0 && data_ptr.is_null() {
+            return Err(KernelError::InvalidPointer);
+        }
+
+        let data = if data_len == 0 {
+            &[]
+        } else {
+            unsafe { core::slice::from_raw_parts(data_ptr, data_len) }
+        };
+        let payload = MessagePayload::from_slice(security_class, data);
+        self.send_message(context.caller, receiver, payload)?;
+        Ok(payload.length as u64)
     }
 
-    fn ensure_console_descriptor(
-        &mut self,
-        files: &mut crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>,
-        fd: usize,
-        console: ConsoleDescriptor,
-    ) -> KernelResult<()> {
-        if files.get(fd).is_ok() {
-            return Ok(());
-        }
-        let description = self
-            .open_files
-            .insert_object(DescriptorObject::Console(console))
-            .map_err(map_file_table_error)?;
-        match files.duplicate_to(fd, description, DescriptorFlags::EMPTY) {
-            Ok(None) => Ok(()),
-            Ok(Some(previous)) => {
-                let _ = self.close_open_description(previous.description());
-                Ok(())
-            }
-            Err(error) => {
-                let _ = self.close_open_description(description);
-                Err(map_process_file_table_error(error))
-            }
+    fn syscall_receive_ipc(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_ipc_receive(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let out = context.arg(0) as *mut Message;
+        if out.is_null() {
+            return Err(KernelError::InvalidPointer);
         }
+        let message = self.receive_message(context.caller)?;
+        unsafe { out.write(message) };
+        Ok(message.payload.length as u64)
     }
 
-    fn write_console_descriptor(&self, _console: ConsoleDescriptor, data: &[u8]) {
-        if data.is_empty() {
-            return;
-        }
-        let mut start = 0usize;
-        let mut idx = 0usize;
-        while idx < data.len() {
-            if data[idx].is_ascii() {
-                idx += 1;
-                continue;
-            }
-            if start < idx {
-                if let Ok(text) = core::str::from_utf8(&data[start..idx]) {
-                    crate::arch::x86_64::early_console::write_fmt(format_args!("{}", text));
-                }
-            }
-            crate::arch::x86_64::early_console::write_fmt(format_args!("�"));
-            idx += 1;
-            start = idx;
-        }
-        if start < data.len() {
-            if let Ok(text) = core::str::from_utf8(&data[start..]) {
-                crate::arch::x86_64::early_console::write_fmt(format_args!("{}", text));
-            }
-        }
+    fn syscall_register_service(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let service = decode_registry_service_id(context.arg(0))?;
+        let owner = if context.arg(1) == 0 {
+            context.caller
+        } else {
+            ProcessId::new(context.arg(1))
+        };
+        self.register_service(context.caller, service, owner)?;
+        Ok(0)
     }
 
-    fn inherit_process_file_table(
-        &mut self,
-        parent: ProcessId,
-    ) -> KernelResult<crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>> {
-        let inherited = *self.process_files(parent)?;
-        let mut retained = 0usize;
-        let descriptors = inherited.descriptors();
-        while retained < MAX_OPEN_FILES {
-            if let Some(descriptor) = descriptors[retained] {
-                if let Err(error) = self
-                    .open_files
-                    .increment_ref_count(descriptor.description())
-                {
-                    self.release_inherited_prefix(&inherited, retained);
-                    return Err(map_file_table_error(error));
-                }
-            }
-            retained += 1;
+    fn syscall_send_service_ipc(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let service = decode_registry_service_id(context.arg(0))?;
+        let data_ptr = context.arg(1) as *const u8;
+        let data_len = context.arg(2) as usize;
+        let security_class = decode_security_class(context.arg(3))?;
+        if data_len > 0 && data_ptr.is_null() {
+            return Err(KernelError::InvalidPointer);
         }
-        Ok(inherited)
+        let data = if data_len == 0 {
+            &[]
+        } else {
+            unsafe { core::slice::from_raw_parts(data_ptr, data_len) }
+        };
+        let payload = MessagePayload::from_slice(security_class, data);
+        self.send_service_message(context.caller, service, payload)?;
+        Ok(payload.length as u64)
     }
 
-    fn release_inherited_prefix(
-        &mut self,
-        table: &crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>,
-        count: usize,
-    ) {
-        let mut idx = 0usize;
-        while idx < count {
-            if let Some(descriptor) = table.descriptors()[idx] {
-                let _ = self.close_open_description(descriptor.description());
-            }
-            idx += 1;
-        }
+    fn syscall_claim_device(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let service = decode_registry_service_id(context.arg(0))?;
+        let device = DeviceId::new(context.arg(1) as u16);
+        self.claim_service_device(context.caller, service, device)?;
+        Ok(0)
     }
 
-    fn release_process_file_table(
-        &mut self,
-        table: &mut crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>,
-    ) {
-        let closed = table.clear();
-        self.release_description_ids(&closed);
+    fn syscall_release_device(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let service = decode_registry_service_id(context.arg(0))?;
+        let device = DeviceId::new(context.arg(1) as u16);
+        self.release_service_device(context.caller, service, device)?;
+        Ok(0)
     }
 
-    fn release_description_ids(
-        &mut self,
-        descriptions: &[Option<FileDescriptionId>; MAX_OPEN_FILES],
-    ) {
-        for description in descriptions.iter().flatten() {
-            let _ = self.close_open_description(*description);
+    fn syscall_receive_or_block_ipc(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_ipc_receive(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let out = context.arg(0) as *mut Message;
+        if out.is_null() {
+            return Err(KernelError::InvalidPointer);
         }
-    }
 
-    fn close_open_description(&mut self, description: FileDescriptionId) -> KernelResult<()> {
-        if let Some(object) = self
-            .open_files
-            .close(description)
-            .map_err(map_file_table_error)?
-        {
-            match object {
-                DescriptorObject::Regular(file) => {
-                    self.root_fs.close(file).map_err(KernelError::Filesystem)?;
-                }
-                DescriptorObject::Pipe(endpoint) => self.release_pipe_endpoint(endpoint),
-                DescriptorObject::EventFd(id) => self.release_eventfd(id),
-                DescriptorObject::Device(_)
-                | DescriptorObject::Socket(_)
-                | DescriptorObject::Console(_) => {}
-            }
+        if let Some(message) = self.receive_or_block(context.caller)? {
+            unsafe { out.write(message) };
+            Ok(1)
+        } else {
+            Ok(0)
         }
-        Ok(())
     }
 
-    fn fd_object(&self, pid: ProcessId, fd: usize) -> KernelResult<DescriptorObject> {
-        let description = self.fd_description(pid, fd)?;
-        self.open_files
-            .get_object(description)
-            .map_err(map_file_table_error)
-    }
+    fn syscall_enumerate_devices(&self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_device_enumeration(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let out = context.arg(0) as *mut MirageDeviceDescriptor;
+        let capacity = context.arg(1) as usize;
+        if capacity > 0 && out.is_null() {
+            return Err(KernelError::InvalidPointer);
+        }
+        let out_slice = if capacity == 0 {
+            &mut []
+        } else {
+            unsafe { core::slice::from_raw_parts_mut(out, capacity) }
+        };
 
-    fn socket_handle_for_fd(&self, pid: ProcessId, fd: usize) -> KernelResult<SocketHandle> {
-        match self.fd_object(pid, fd)? {
-            DescriptorObject::Socket(handle) => Ok(handle),
-            _ => Err(KernelError::Filesystem(VfsError::InvalidHandle)),
+        let descriptors = self.devices.descriptors();
+        let count = min(capacity, descriptors.len());
+        for (slot, descriptor) in out_slice.iter_mut().zip(descriptors.iter()) {
+            *slot = MirageDeviceDescriptor::from_descriptor(*descriptor);
         }
+        Ok(count as u64)
     }
 
-    fn allocate_socket_handle(&mut self) -> KernelResult<SocketHandle> {
-        let raw = self.next_socket_handle;
-        if raw == 0 {
-            return Err(KernelError::FileTableFull);
+    fn syscall_device_info(&self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_device_enumeration(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let id = DeviceId::new(context.arg(0) as u16);
+        let out = context.arg(1) as *mut MirageDeviceDescriptor;
+        if out.is_null() {
+            return Err(KernelError::InvalidPointer);
         }
-        self.next_socket_handle = self
-            .next_socket_handle
-            .checked_add(1)
-            .ok_or(KernelError::FileTableFull)?;
-        Ok(SocketHandle::new(raw))
+        let descriptor = self.device_info(id).ok_or(KernelError::DeviceNotFound)?;
+        unsafe { out.write(MirageDeviceDescriptor::from_descriptor(descriptor)) };
+        Ok(1)
     }
 
-    fn network_request_header(
-        &mut self,
-        opcode: NetworkOpcode,
-        client: ProcessId,
-        flags: u32,
-    ) -> NetworkRequestHeader {
-        NetworkRequestHeader::new(opcode, client, self.next_message_sequence(), flags)
+    fn syscall_device_read(&self, context: SyscallContext) -> KernelResult<u64> {
+        let id = DeviceId::new(context.arg(0) as u16);
+        let buffer = context.arg(1) as *mut u8;
+        let len = context.arg(2) as usize;
+        if len > 0 && buffer.is_null() {
+            return Err(KernelError::InvalidPointer);
+        }
+        let buffer = if len == 0 {
+            &mut []
+        } else {
+            unsafe { core::slice::from_raw_parts_mut(buffer, len) }
+        };
+        self.device_read(context.caller, id, buffer)
+            .map(|read| read as u64)
     }
 
-    fn send_network_request<T: NetworkIpcRequest>(
-        &mut self,
-        sender: ProcessId,
-        request: &T,
-    ) -> KernelResult<()> {
-        let receiver = self
-            .service_registry
-            .owner(RegistryServiceId::Networkd)
-            .ok_or(KernelError::UnknownProcess)?;
-        self.ensure_process_exists(receiver)?;
+    fn syscall_device_write(&self, context: SyscallContext) -> KernelResult<u64> {
+        let id = DeviceId::new(context.arg(0) as u16);
+        let data = context.arg(1) as *const u8;
+        let len = context.arg(2) as usize;
+        if len > 0 && data.is_null() {
+            return Err(KernelError::InvalidPointer);
+        }
+        let data = if len == 0 {
+            &[]
+        } else {
+            unsafe { core::slice::from_raw_parts(data, len) }
+        };
+        self.device_write(context.caller, id, data)
+            .map(|written| written as u64)
+    }
+
+    fn syscall_mmap(&self, context: SyscallContext) -> KernelResult<u64> {
+        let length = context.arg(0) as usize;
+        let protection = MemoryProtection::from_bits(context.arg(1) as u32);
+        let flags = MmapFlags::from_bits(context.arg(2) as u32).without(MmapFlags::FIXED);
         self.security
-            .authorize_ipc(
-                sender,
-                receiver,
-                RegistryServiceId::Networkd.security_class(),
-            )
+            .authorize_memory_mapping(context.caller, protection)
             .map_err(KernelError::SecurityViolation)?;
-        let payload = MessagePayload::from_slice(
-            RegistryServiceId::Networkd.security_class(),
-            request.as_bytes(),
-        );
-        self.send_message(sender, receiver, payload)
+        memory::mmap_with_flags_for(context.caller, length, protection, flags, None)
+            .map(|region| region.as_ptr() as u64)
+            .ok_or(KernelError::AllocationFailed)
     }
 
-    fn allocate_pipe(&mut self) -> KernelResult<PipeId> {
-        let mut idx = 0usize;
-        while idx < MAX_KERNEL_PIPES {
-            if self.pipes[idx].is_none() {
-                self.pipes[idx] = Some(PipeObject::new());
-                return Ok(PipeId::new(idx));
-            }
-            idx += 1;
+    fn syscall_munmap(&self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_memory_service(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let ptr = NonNull::new(context.arg(0) as *mut u8).ok_or(KernelError::InvalidPointer)?;
+        let length = context.arg(1) as usize;
+        if memory::munmap_ptr_for(context.caller, ptr, length) {
+            Ok(0)
+        } else {
+            Err(KernelError::InvalidArgument)
         }
-        Err(KernelError::FileTableFull)
     }
 
-    fn release_pipe_endpoint(&mut self, endpoint: PipeEndpoint) {
-        if let Some(pipe) = self
-            .pipes
-            .get_mut(endpoint.id().raw())
-            .and_then(Option::as_mut)
-        {
-            match endpoint.direction() {
-                PipeDirection::Read => pipe.readers = pipe.readers.saturating_sub(1),
-                PipeDirection::Write => pipe.writers = pipe.writers.saturating_sub(1),
-            }
-            if pipe.readers == 0 && pipe.writers == 0 {
-                self.pipes[endpoint.id().raw()] = None;
-            }
+    fn syscall_malloc(&self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_memory_service(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        memory::malloc_for(context.caller, context.arg(0) as usize)
+            .map(|ptr| ptr.as_ptr() as u64)
+            .ok_or(KernelError::AllocationFailed)
+    }
+
+    fn syscall_free(&self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_memory_service(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let ptr = NonNull::new(context.arg(0) as *mut u8).ok_or(KernelError::InvalidPointer)?;
+        if memory::free_for(context.caller, ptr) {
+            Ok(0)
+        } else {
+            Err(KernelError::InvalidArgument)
         }
     }
 
-    fn allocate_eventfd(&mut self, counter: u64, semaphore: bool) -> KernelResult<EventFdId> {
-        let mut idx = 0usize;
-        while idx < MAX_KERNEL_EVENTFDS {
-            if self.eventfds[idx].is_none() {
-                self.eventfds[idx] = Some(EventFdObject::new(counter, semaphore));
-                return Ok(EventFdId::new(idx));
-            }
-            idx += 1;
+    fn syscall_realloc(&self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_memory_service(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let ptr = NonNull::new(context.arg(0) as *mut u8);
+        memory::realloc_for(context.caller, ptr, context.arg(1) as usize)
+            .map(|ptr| ptr.as_ptr() as u64)
+            .ok_or(KernelError::AllocationFailed)
+    }
+
+    fn syscall_malloc_aligned(&self, context: SyscallContext) -> KernelResult<u64> {
+        self.security
+            .authorize_memory_service(context.caller)
+            .map_err(KernelError::SecurityViolation)?;
+        let alignment = context.arg(1) as usize;
+        if alignment == 0 || !alignment.is_power_of_two() {
+            return Err(KernelError::InvalidArgument);
         }
-        Err(KernelError::FileTableFull)
+
+        memory::malloc_aligned_for(context.caller, context.arg(0) as usize, alignment)
+            .map(|ptr| ptr.as_ptr() as u64)
+            .ok_or(KernelError::AllocationFailed)
     }
 
-    fn release_eventfd(&mut self, id: EventFdId) {
-        if id.raw() < MAX_KERNEL_EVENTFDS {
-            self.eventfds[id.raw()] = None;
+    fn syscall_openat(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        let path = path_buf.as_path()?;
+        let raw_flags = open_flags_from_libc(context.arg(2) as u32);
+        let descriptor_flags = DescriptorFlags::from_open_flags(raw_flags);
+        let credentials = self.fs_credentials_for(context.caller)?;
+        let file = self
+            .root_fs
+            .open(path, raw_flags.without_descriptor_flags(), credentials)
+            .map_err(KernelError::Filesystem)?;
+        let description = self.open_files.insert(file).map_err(map_file_table_error)?;
+        match self
+            .process_files_mut(context.caller)?
+            .open(description, descriptor_flags)
+        {
+            Ok(fd) => Ok(fd as u64),
+            Err(error) => {
+                self.close_open_description(description)?;
+                Err(map_process_file_table_error(error))
+            }
         }
     }
 
-    fn descriptor_readiness(&self, object: DescriptorObject, events: i16) -> i16 {
-        let mut revents = 0i16;
-        match object {
+    fn syscall_close(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let descriptor = self
+            .process_files_mut(context.caller)?
+            .close(context.arg(0) as usize)
+            .map_err(map_process_file_table_error)?;
+        self.close_open_description(descriptor.description())?;
+        Ok(0)
+    }
+
+    fn syscall_read(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let buffer = user_slice_mut(context.arg(1), context.arg(2) as usize)?;
+        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
+        match self
+            .open_files
+            .get_object(description)
+            .map_err(map_file_table_error)?
+        {
             DescriptorObject::Regular(_) => {
-                if events & (POLLIN | POLLPRI) != 0 {
-                    revents |= events & (POLLIN | POLLPRI);
-                }
-                if events & POLLOUT != 0 {
-                    revents |= POLLOUT;
-                }
+                let file = self
+                    .open_files
+                    .get_mut(description)
+                    .map_err(map_file_table_error)?;
+                self.root_fs
+                    .read(file, buffer)
+                    .map(|read| read as u64)
+                    .map_err(KernelError::Filesystem)
             }
             DescriptorObject::Pipe(endpoint) => {
-                match self.pipes.get(endpoint.id().raw()).and_then(|entry| *entry) {
-                    Some(pipe) => match endpoint.direction() {
-                        PipeDirection::Read => {
-                            if events & (POLLIN | POLLPRI) != 0 && pipe.is_readable() {
-                                revents |= events & (POLLIN | POLLPRI);
-                            }
-                            if pipe.writers == 0 {
-                                revents |= POLLHUP;
-                            }
-                        }
-                        PipeDirection::Write => {
-                            if events & POLLOUT != 0 && pipe.is_writable() {
-                                revents |= POLLOUT;
-                            }
-                            if pipe.readers == 0 {
-                                revents |= POLLERR;
-                            }
-                        }
-                    },
-                    None => revents |= POLLNVAL,
+                if endpoint.direction() != PipeDirection::Read {
+                    return Err(KernelError::InvalidArgument);
                 }
+                let pipe = self
+                    .pipes
+                    .get_mut(endpoint.id().raw())
+                    .and_then(Option::as_mut)
+                    .ok_or(KernelError::InvalidArgument)?;
+                Ok(pipe.read(buffer) as u64)
             }
             DescriptorObject::EventFd(id) => {
-                match self.eventfds.get(id.raw()).and_then(|entry| *entry) {
-                    Some(eventfd) => {
-                        if events & (POLLIN | POLLPRI) != 0 && eventfd.is_readable() {
-                            revents |= events & (POLLIN | POLLPRI);
-                        }
-                        if events & POLLOUT != 0 && eventfd.is_writable() {
-                            revents |= POLLOUT;
-                        }
-                    }
-                    None => revents |= POLLNVAL,
-                }
-            }
-            DescriptorObject::Device(_)
-            | DescriptorObject::Socket(_)
-            | DescriptorObject::Console(_) => {
-                if events & (POLLIN | POLLPRI) != 0 {
-                    revents |= events & (POLLIN | POLLPRI);
+                if buffer.len() < 8 {
+                    return Err(KernelError::InvalidArgument);
                 }
-                if events & POLLOUT != 0 {
-                    revents |= POLLOUT;
+                let eventfd = self
+                    .eventfds
+                    .get_mut(id.raw())
+                    .and_then(Option::as_mut)
+                    .ok_or(KernelError::InvalidArgument)?;
+                if eventfd.counter == 0 {
+                    return Ok(0);
                 }
+                let value = if eventfd.semaphore {
+                    eventfd.counter -= 1;
+                    1
+                } else {
+                    let value = eventfd.counter;
+                    eventfd.counter = 0;
+                    value
+                };
+                buffer[..8].copy_from_slice(&value.to_ne_bytes());
+                Ok(8)
+            }
+            DescriptorObject::Device(handle) => self
+                .device_read(context.caller, handle.id(), buffer)
+                .map(|read| read as u64),
+            DescriptorObject::Socket(_) | DescriptorObject::Console(_) => {
+                Err(KernelError::InvalidArgument)
             }
         }
-        revents
     }
 
-    fn poll_descriptors(
-        &mut self,
-        pid: ProcessId,
-        thread: Option<ThreadId>,
-        fds: &mut [MiragePollFd],
-        timeout_ms: i32,
-    ) -> KernelResult<u64> {
-        let mut ready = 0u64;
-        let mut idx = 0usize;
-        while idx < fds.len() {
-            fds[idx].revents = 0;
-            if fds[idx].fd >= 0 {
-                match self.fd_object(pid, fds[idx].fd as usize) {
-                    Ok(object) => {
-                        fds[idx].revents = self.descriptor_readiness(object, fds[idx].events)
-                    }
-                    Err(_) => fds[idx].revents = POLLNVAL,
+    fn syscall_write(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let data = user_slice(context.arg(1), context.arg(2) as usize)?;
+        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
+        match self
+            .open_files
+            .get_object(description)
+            .map_err(map_file_table_error)?
+        {
+            DescriptorObject::Regular(_) => {
+                let file = self
+                    .open_files
+                    .get_mut(description)
+                    .map_err(map_file_table_error)?;
+                self.root_fs
+                    .write(file, data)
+                    .map(|written| written as u64)
+                    .map_err(KernelError::Filesystem)
+            }
+            DescriptorObject::Pipe(endpoint) => {
+                if endpoint.direction() != PipeDirection::Write {
+                    return Err(KernelError::InvalidArgument);
                 }
-                if fds[idx].revents != 0 {
-                    ready += 1;
+                let pipe = self
+                    .pipes
+                    .get_mut(endpoint.id().raw())
+                    .and_then(Option::as_mut)
+                    .ok_or(KernelError::InvalidArgument)?;
+                if pipe.readers == 0 {
+                    return Err(KernelError::InvalidArgument);
                 }
+                Ok(pipe.write(data) as u64)
             }
-            idx += 1;
-        }
-        if ready == 0 && timeout_ms != 0 {
-            if timeout_ms > 0 {
-                let wake_deadline = KERNEL_TIME
-                    .now()
-                    .as_nanos()
-                    .saturating_add((timeout_ms as u128).saturating_mul(1_000_000));
-                self.timers
-                    .add_sleep(pid, thread, wake_deadline)
-                    .map_err(map_timer_error)?;
+            DescriptorObject::EventFd(id) => {
+                if data.len() < 8 {
+                    return Err(KernelError::InvalidArgument);
+                }
+                let value = u64::from_ne_bytes([
+                    data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
+                ]);
+                if value == u64::MAX {
+                    return Err(KernelError::InvalidArgument);
+                }
+                let eventfd = self
+                    .eventfds
+                    .get_mut(id.raw())
+                    .and_then(Option::as_mut)
+                    .ok_or(KernelError::InvalidArgument)?;
+                eventfd.counter = eventfd
+                    .counter
+                    .checked_add(value)
+                    .ok_or(KernelError::InvalidArgument)?;
+                Ok(8)
             }
-            let process_index = self.locate_process(pid)?;
-            self.block_process_at_index(pid, process_index);
+            DescriptorObject::Device(handle) => self
+                .device_write(context.caller, handle.id(), data)
+                .map(|written| written as u64),
+            DescriptorObject::Console(console) => {
+                self.write_console_descriptor(console, data);
+                Ok(data.len() as u64)
+            }
+            DescriptorObject::Socket(_) => Err(KernelError::InvalidArgument),
         }
-        Ok(ready)
     }
 
-    pub fn tick(&mut self) {
-        self.kernel_on_timer_tick();
-        device::system_timer().tick();
-        let timestamp = KERNEL_TIME.tick();
-        let now_ns = timestamp.as_nanos();
-        self.wake_expired_timeouts(now_ns);
-        self.wake_expired_futexes(now_ns);
-        let mut core_index = 0usize;
-        while core_index < cpu::MAX_CORES {
-            if self.core_states[core_index].online {
-                self.run_core(core_index);
-            }
-            core_index += 1;
-        }
+    fn syscall_pread64(&self, context: SyscallContext) -> KernelResult<u64> {
+        let buffer = user_slice_mut(context.arg(1), context.arg(2) as usize)?;
+        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
+        let file = self
+            .open_files
+            .get(description)
+            .map_err(map_file_table_error)?;
+        self.root_fs
+            .pread(&file, buffer, context.arg(3))
+            .map(|read| read as u64)
+            .map_err(KernelError::Filesystem)
     }
 
-    fn wake_expired_timeouts(&mut self, now_ns: u128) {
-        while let Some(expired) = self.timers.expire_sleep(now_ns) {
-            let _ = self.wake_process_for_timeout(expired.process);
-        }
+    fn syscall_pwrite64(&self, context: SyscallContext) -> KernelResult<u64> {
+        let data = user_slice(context.arg(1), context.arg(2) as usize)?;
+        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
+        let file = self
+            .open_files
+            .get(description)
+            .map_err(map_file_table_error)?;
+        self.root_fs
+            .pwrite(&file, data, context.arg(3))
+            .map(|written| written as u64)
+            .map_err(KernelError::Filesystem)
+    }
 
-        while let Some(expired) = self.timers.expire_timer(now_ns) {
-            let _ = self.wake_process_for_timeout(expired.owner);
+    fn syscall_lseek(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let fd = context.arg(0) as usize;
+        let offset = context.arg(1) as i64;
+        let whence = context.arg(2);
+        let description = self.fd_description(context.caller, fd)?;
+        let file = self
+            .open_files
+            .get(description)
+            .map_err(map_file_table_error)?;
+        let current = file.cursor() as i64;
+        let end = self
+            .root_fs
+            .fstat(&file)
+            .map_err(KernelError::Filesystem)?
+            .size as i64;
+        let base = match whence {
+            SEEK_SET => 0,
+            SEEK_CUR => current,
+            SEEK_END => end,
+            _ => return Err(KernelError::InvalidArgument),
+        };
+        let new_offset = base
+            .checked_add(offset)
+            .ok_or(KernelError::InvalidArgument)?;
+        if new_offset < 0 {
+            return Err(KernelError::InvalidArgument);
         }
+        self.open_files
+            .get_mut(description)
+            .map_err(map_file_table_error)?
+            .seek(new_offset as u64);
+        Ok(new_offset as u64)
     }
 
-    fn wake_expired_futexes(&mut self, now_ns: u128) {
-        let mut expired_threads = [None; MAX_THREADS];
-        let count = self.futexes.expire(now_ns, &mut expired_threads);
-        let _ = self.wake_futex_threads(
-            &expired_threads,
-            count,
-            encode_syscall_error(KernelError::TimedOut),
-        );
+    fn syscall_statx(&self, context: SyscallContext) -> KernelResult<u64> {
+        self.syscall_newfstatat(context)
     }
 
-    fn wake_process_for_timeout(&mut self, pid: ProcessId) -> KernelResult<()> {
-        let index = self.locate_process(pid)?;
-        let mut wake_threads = false;
-        if let Some(pcb) = self.process_table[index].as_ref() {
-            if pcb.state == ProcessState::Blocked {
-                self.set_process_ready_via_mtss(pid, index)?;
-                wake_threads = true;
-            }
-        }
-        if wake_threads {
-            self.make_threads_ready(pid)?;
+    fn syscall_newfstatat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let path_ptr = context.arg(1);
+        let out = user_out_ptr::<CStat>(context.arg(2))?;
+        let stat = if path_ptr == 0 {
+            let description = self.fd_description(context.caller, context.arg(0) as usize)?;
+            let file = self
+                .open_files
+                .get(description)
+                .map_err(map_file_table_error)?;
+            self.root_fs.fstat(&file)
+        } else {
+            let path_buf = self.resolve_user_path(context.caller, context.arg(0), path_ptr)?;
+            self.root_fs.stat(path_buf.as_path()?)
         }
-        Ok(())
+        .map_err(KernelError::Filesystem)?;
+        unsafe { out.write(CStat::from_kernel(stat)) };
+        Ok(0)
     }
 
-    fn run_core(&mut self, core_index: usize) {
-        if let Some(scheduled) = self.kernel_schedule_next() {
-            let thread_index = match self.locate_thread(scheduled.thread) {
-                Ok(idx) => idx,
-                Err(_) => {
-                    self.core_states[core_index].idle_cycle();
-                    return;
-                }
-            };
-
-            let process_index = match self.locate_process(scheduled.process) {
-                Ok(idx) => idx,
-                Err(_) => {
-                    self.thread_table[thread_index] = None;
-                    self.core_states[core_index].idle_cycle();
-                    return;
-                }
-            };
-
-            if let Err(reason) = self.security.enforce_isolation(scheduled.process) {
-                self.handle_isolation_fault(scheduled.process, reason);
-                return;
+    fn syscall_getdents64(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let entries = user_slice_mut_typed::<CDirEntry>(context.arg(1), context.arg(2) as usize)?;
+        let fd = context.arg(0) as usize;
+        let description = self.fd_description(context.caller, fd)?;
+        let file = self
+            .open_files
+            .get(description)
+            .map_err(map_file_table_error)?;
+        let mut cursor = file.cursor() as usize;
+        let mut written = 0usize;
+        while written < entries.len() {
+            let mut kernel_entry = [DirEntry::empty(); 1];
+            let count = self
+                .root_fs
+                .readdir_inode(file.inode(), cursor, &mut kernel_entry)
+                .map_err(KernelError::Filesystem)?;
+            if count == 0 {
+                break;
             }
+            cursor += count;
+            entries[written] = CDirEntry::from_kernel(&kernel_entry[0], cursor);
+            written += 1;
+        }
+        self.open_files
+            .get_mut(description)
+            .map_err(map_file_table_error)?
+            .advance(written);
+        Ok(written as u64)
+    }
 
-            let _ = self.deliver_signal_checkpoint(scheduled.process, scheduled.thread);
-            if self.locate_thread(scheduled.thread).is_err() {
-                self.core_states[core_index].idle_cycle();
-                return;
-            }
+    fn syscall_mkdirat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        let path = path_buf.as_path()?;
+        let requested = context.arg(2) as u32;
+        let umask = self.process_files(context.caller)?.umask().bits() as u32;
+        let credentials = self.fs_credentials_for(context.caller)?;
+        let mode = permissions_from_libc_mode(requested & !umask, credentials.uid, credentials.gid);
+        self.root_fs
+            .mkdir(path, mode, credentials)
+            .map(|_| 0)
+            .map_err(KernelError::Filesystem)
+    }
 
-            let address_space_root = self.process_table[process_index]
-                .as_ref()
-                .map(|pcb| pcb.address_space_root)
-                .unwrap_or(0);
-            if address_space_root == 0 {
-                self.handle_isolation_fault(scheduled.process, IsolationError::PolicyViolation);
-                return;
-            }
-
-            let kernel_stack_top = x86_64::kernel_stack_top(core_index);
-            self.core_states[core_index].set_kernel_stack_top(kernel_stack_top);
-            self.core_states[core_index].start_thread(scheduled.thread);
-
-            let mut terminated = false;
-            let mut run_outcome = ThreadRunOutcome::TimeSliceComplete;
-            if let Some(entry) = self.thread_table.get_mut(thread_index) {
-                if let Some(thread) = entry.as_mut() {
-                    if thread.state == ThreadState::Terminated {
-                        *entry = None;
-                        terminated = true;
-                    } else {
-                        run_outcome = x86_64::run_thread_slice(ThreadSliceRunContext {
-                            core_index,
-                            thread: scheduled.thread,
-                            process: scheduled.process,
-                            address_space_root,
-                            kernel_stack_top,
-                            context: &mut thread.context,
-                        });
-                        if run_outcome != ThreadRunOutcome::UserEntryInvalid {
-                            thread.mark_running();
-                            thread.accumulate_cpu_time(1);
-                        }
-                    }
-                }
-            }
-
-            if terminated {
-                self.update_process_thread_count(scheduled.process, false);
-                self.core_states[core_index].finish_cycle();
-                return;
-            }
+    fn syscall_unlinkat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        let path = path_buf.as_path()?;
+        let flags = context.arg(2);
+        let result = if (flags & AT_REMOVEDIR) != 0 {
+            self.root_fs
+                .rmdir(path, self.fs_credentials_for(context.caller)?)
+        } else {
+            self.root_fs
+                .unlink(path, self.fs_credentials_for(context.caller)?)
+        };
+        result.map(|_| 0).map_err(KernelError::Filesystem)
+    }
 
-            self.set_process_running_via_mtss(scheduled.process, process_index)
-                .unwrap_or_else(|_| {
-                    self.handle_isolation_fault(scheduled.process, IsolationError::PolicyViolation)
-                });
-            if let Some(pcb) = self.process_table[process_index].as_mut() {
-                pcb.cpu_time = pcb.cpu_time.saturating_add(1);
-            }
+    fn syscall_renameat2(&self, context: SyscallContext) -> KernelResult<u64> {
+        let old_path_buf =
+            self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        let new_path_buf =
+            self.resolve_user_path(context.caller, context.arg(2), context.arg(3))?;
+        let flags = context.arg(4);
+        if flags & !RENAME_NOREPLACE != 0 {
+            return Err(KernelError::InvalidArgument);
+        }
+        if flags & RENAME_NOREPLACE != 0 && self.root_fs.stat(new_path_buf.as_path()?).is_ok() {
+            return Err(KernelError::Filesystem(VfsError::AlreadyExists));
+        }
+        self.root_fs
+            .rename(
+                old_path_buf.as_path()?,
+                new_path_buf.as_path()?,
+                self.fs_credentials_for(context.caller)?,
+            )
+            .map(|_| 0)
+            .map_err(KernelError::Filesystem)
+    }
 
-            match run_outcome {
-                ThreadRunOutcome::Syscall(trap) => {
-                    let context =
-                        SyscallContext::new(scheduled.process, Some(trap.thread), trap.args);
-                    let result = self
-                        .handle_syscall(trap.number, context)
-                        .unwrap_or_else(encode_syscall_error);
-                    if !self.syscall_trap_target_can_resume(scheduled.process, trap.thread) {
-                        self.core_states[core_index].finish_cycle();
-                        if let Some(next) = self.kernel_schedule_next() {
-                            self.pending_mtss_decision = Some(next);
-                        } else {
-                            self.core_states[core_index].idle_cycle();
-                        }
-                        return;
-                    }
-                    self.write_thread_syscall_result(trap.thread, result);
-                    let _ = self.deliver_signal_checkpoint(scheduled.process, trap.thread);
-                    if !self.syscall_trap_target_can_resume(scheduled.process, trap.thread) {
-                        self.core_states[core_index].finish_cycle();
-                        if let Some(next) = self.kernel_schedule_next() {
-                            self.pending_mtss_decision = Some(next);
-                        } else {
-                            self.core_states[core_index].idle_cycle();
-                        }
-                        return;
-                    }
-                }
-                ThreadRunOutcome::TimerPreempted | ThreadRunOutcome::TimeSliceComplete => {}
-                ThreadRunOutcome::UserEntryInvalid => {
-                    self.handle_isolation_fault(scheduled.process, IsolationError::PolicyViolation);
-                }
-            }
+    fn syscall_ftruncate(&self, context: SyscallContext) -> KernelResult<u64> {
+        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
+        let file = self
+            .open_files
+            .get(description)
+            .map_err(map_file_table_error)?;
+        self.root_fs
+            .ftruncate(
+                &file,
+                context.arg(1),
+                self.fs_credentials_for(context.caller)?,
+            )
+            .map(|_| 0)
+            .map_err(KernelError::Filesystem)
+    }
 
-            let mut requeue_thread = false;
-            if let Some(entry) = self.thread_table.get_mut(thread_index) {
-                if let Some(thread) = entry.as_mut() {
-                    if thread.state == ThreadState::Running {
-                        thread.mark_ready();
-                    }
-                    requeue_thread = thread.state == ThreadState::Ready;
-                }
-            }
+    fn syscall_fsync(&self, context: SyscallContext) -> KernelResult<u64> {
+        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
+        let file = self
+            .open_files
+            .get(description)
+            .map_err(map_file_table_error)?;
+        self.root_fs
+            .fsync(&file)
+            .map(|_| 0)
+            .map_err(KernelError::Filesystem)
+    }
 
-            let process_has_runnable_threads = self.has_runnable_thread(scheduled.process);
-            if let Some(pcb) = self.process_table[process_index].as_ref() {
-                if pcb.state == ProcessState::Running {
-                    if process_has_runnable_threads {
-                        let _ = self.set_process_ready_via_mtss(scheduled.process, process_index);
-                    } else {
-                        let _ = self.set_process_blocked_via_mtss(scheduled.process, process_index);
-                    }
-                }
-            }
+    fn syscall_mount(&self, context: SyscallContext) -> KernelResult<u64> {
+        if context.arg(0) != 0 {
+            let _source = user_cstr(context.arg(0))?;
+        }
+        let target = self.user_path(context.arg(1))?;
+        let filesystem_type = if context.arg(2) == 0 {
+            DEFAULT_ROOT_FILESYSTEM
+        } else {
+            user_cstr(context.arg(2))?
+        };
+        if !target.is_root() || !is_supported_root_filesystem(filesystem_type) {
+            return Err(KernelError::Filesystem(VfsError::Unsupported));
+        }
+        Ok(0)
+    }
 
-            self.core_states[core_index].finish_cycle();
+    fn syscall_chdir(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let path_buf = self.resolve_user_path(context.caller, AT_FDCWD as u64, context.arg(0))?;
+        let stat = self
+            .root_fs
+            .stat(path_buf.as_path()?)
+            .map_err(KernelError::Filesystem)?;
+        if stat.kind != crate::kernel::fs::inode::InodeKind::Directory {
+            return Err(KernelError::Filesystem(VfsError::NotDirectory));
+        }
+        self.process_files_mut(context.caller)?
+            .set_cwd(ProcessPath::from_path(path_buf.as_path()?));
+        Ok(0)
+    }
 
-            if requeue_thread {
-                match self.kernel_yield_current(scheduled) {
-                    Ok(Some(next)) => {
-                        // MTSS has already selected the next runnable thread. The
-                        // single-slice core loop defers dispatching that exact MTSS
-                        // decision until the next scheduler tick, where
-                        // `kernel_schedule_next` will expose only MTSS-selected
-                        // threads to the architecture backend.
-                        self.pending_mtss_decision = Some(next);
-                    }
-                    Ok(None) => {}
-                    Err(_) => {
-                        self.core_states[core_index].idle_cycle();
-                    }
-                }
-            }
-        } else {
-            self.core_states[core_index].idle_cycle();
+    fn syscall_fchdir(&mut self, context: SyscallContext) -> KernelResult<u64> {
+        let description = self.fd_description(context.caller, context.arg(0) as usize)?;
+        let file = self
+            .open_files
+            .get(description)
+            .map_err(map_file_table_error)?;
+        let stat = self.root_fs.fstat(&file).map_err(KernelError::Filesystem)?;
+        if stat.kind != crate::kernel::fs::inode::InodeKind::Directory {
+            return Err(KernelError::Filesystem(VfsError::NotDirectory));
         }
+        let path = Path::new(file.path()).map_err(map_path_error)?;
+        self.process_files_mut(context.caller)?
+            .set_cwd(ProcessPath::from_path(path));
+        Ok(0)
     }
 
-    fn syscall_trap_target_can_resume(&self, pid: ProcessId, thread: ThreadId) -> bool {
-        let process_index = match self.locate_process(pid) {
-            Ok(index) => index,
-            Err(_) => return false,
-        };
-        if self.process_table[process_index]
-            .as_ref()
-            .map(|pcb| pcb.state == ProcessState::Zombie)
-            .unwrap_or(true)
-        {
-            return false;
+    fn syscall_getcwd(&self, context: SyscallContext) -> KernelResult<u64> {
+        let buffer = user_slice_mut(context.arg(0), context.arg(1) as usize)?;
+        let cwd = self.process_files(context.caller)?.cwd();
+        let raw = cwd.as_str().as_bytes();
+        if buffer.len() < raw.len() + 1 {
+            return Err(KernelError::InvalidArgument);
         }
-        let thread_index = match self.locate_thread(thread) {
-            Ok(index) => index,
-            Err(_) => return false,
-        };
-        self.thread_table[thread_index]
-            .as_ref()
-            .map(|tcb| tcb.process == pid && tcb.state != ThreadState::Terminated)
-            .unwrap_or(false)
+        buffer[..raw.len()].copy_from_slice(raw);
+        buffer[raw.len()] = 0;
+        Ok(context.arg(0))
     }
 
-    fn futex_owner_for_process(&self, pid: ProcessId) -> u64 {
-        if let Ok(process_index) = self.locate_process(pid) {
-            if let Some(pcb) = self.process_table[process_index].as_ref() {
-                if pcb.address_space_root != 0 {
-                    return pcb.address_space_root;
-                }
-            }
+    fn syscall_faccessat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        let mode = context.arg(2) as u32;
+        if mode
+            & !(crate::kernel::fs::stdlib::R_OK
+                | crate::kernel::fs::stdlib::W_OK
+                | crate::kernel::fs::stdlib::X_OK)
+            != 0
+        {
+            return Err(KernelError::InvalidArgument);
         }
-        pid.raw()
+        let stat = self
+            .root_fs
+            .stat(path_buf.as_path()?)
+            .map_err(KernelError::Filesystem)?;
+        if mode == crate::kernel::fs::stdlib::F_OK {
+            return Ok(0);
+        }
+        let permissions = crate::kernel::fs::Permissions::new(stat.mode, stat.uid, stat.gid);
+        let credentials = self.fs_credentials_for(context.caller)?;
+        let allowed = match (
+            mode & crate::kernel::fs::stdlib::R_OK != 0,
+            mode & crate::kernel::fs::stdlib::W_OK != 0,
+            mode & crate::kernel::fs::stdlib::X_OK != 0,
+        ) {
+            (true, true, true) => {
+                permissions.allows(credentials, AccessMode::ReadWrite)
+                    && permissions.allows(credentials, AccessMode::Execute)
+            }
+            (true, true, false) => permissions.allows(credentials, AccessMode::ReadWrite),
+            (true, false, false) => permissions.allows(credentials, AccessMode::Read),
+            (false, true, false) => permissions.allows(credentials, AccessMode::Write),
+            (false, false, true) => permissions.allows(credentials, AccessMode::Execute),
+            (true, false, true) => {
+                permissions.allows(credentials, AccessMode::Read)
+                    && permissions.allows(credentials, AccessMode::Execute)
+            }
+            (false, true, true) => {
+                permissions.allows(credentials, AccessMode::Write)
+                    && permissions.allows(credentials, AccessMode::Execute)
+            }
+            _ => true,
+        };
+        if allowed {
+            Ok(0)
+        } else {
+            Err(KernelError::Filesystem(VfsError::PermissionDenied))
+        }
+    }
+
+    fn syscall_fchmodat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        self.root_fs
+            .chmod(
+                path_buf.as_path()?,
+                context.arg(2) as u16,
+                self.fs_credentials_for(context.caller)?,
+            )
+            .map(|_| 0)
+            .map_err(KernelError::Filesystem)
+    }
+
+    fn syscall_fchownat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        self.root_fs
+            .chown(
+                path_buf.as_path()?,
+                context.arg(2) as u16,
+                context.arg(3) as u16,
+                self.fs_credentials_for(context.caller)?,
+            )
+            .map(|_| 0)
+            .map_err(KernelError::Filesystem)
+    }
+
+    fn syscall_symlinkat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let target = self.user_path_str(context.arg(0))?;
+        Path::new_unchecked_rooted(target).map_err(map_path_error)?;
+        let link_path_buf =
+            self.resolve_user_path(context.caller, context.arg(1), context.arg(2))?;
+        self.root_fs
+            .symlink(
+                target,
+                link_path_buf.as_path()?,
+                self.fs_credentials_for(context.caller)?,
+            )
+            .map(|_| 0)
+            .map_err(KernelError::Filesystem)
+    }
+
+    fn syscall_readlinkat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let path_buf = self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        let buffer = user_slice_mut(context.arg(2), context.arg(3) as usize)?;
+        self.root_fs
+            .readlink(path_buf.as_path()?, buffer)
+            .map(|n| n as u64)
+            .map_err(KernelError::Filesystem)
+    }
+
+    fn syscall_linkat(&self, context: SyscallContext) -> KernelResult<u64> {
+        let old_path_buf =
+            self.resolve_user_path(context.caller, context.arg(0), context.arg(1))?;
+        let new_path_buf =
+            self.resolve_user_path(context.caller, context.arg(2), context.arg(3))?;
+        let flags = context.arg(4);
+        if flags & !AT_SYMLINK_FOLLOW != 0 {
+            return Err(KernelError::InvalidArgument);
+        }
+        self.root_fs
+            .link(
+                old_path_buf.as_path()?,
+                new_path_buf.as_path()?,
+                self.fs_credentials_for(context.caller)?,
+            )
+            .map(|_| 0)
+            .map_err(KernelError::Filesystem)
+    }
+
+    fn resolve_user_path(
+        &self,
+        pid: ProcessId,
+        dirfd: u64,
+        path_ptr: u64,
+    ) -> KernelResult<KernelPathBuf> {
+        let raw = self.user_path_str(path_ptr)?;
+        self.resolve_path_str(pid, dirfd, raw)
+    }
+
+    fn resolve_path_str(
+        &self,
+        pid: ProcessId,
+        dirfd: u64,
+        raw: &str,
+    ) -> KernelResult<KernelPathBuf> {
+        Path::new_unchecked_rooted(raw).map_err(map_path_error)?;
+        let files = self.process_files(pid)?;
+        let root = files.root();
+        let root_str = root.as_str();
+        let root_len = root.len();
+        let mut resolved = if raw.starts_with('/') {
+            KernelPathBuf::from_str(root_str)?
+        } else if dirfd as i32 == AT_FDCWD {
+            KernelPathBuf::from_str(files.cwd().as_str())?
+        } else {
+            let description = files
+                .get(dirfd as usize)
+                .map_err(map_process_file_table_error)?
+                .description();
+            let file = self
+                .open_files
+                .get(description)
+                .map_err(map_file_table_error)?;
+            let metadata = self.root_fs.fstat(&file).map_err(KernelError::Filesystem)?;
+            if metadata.kind != crate::kernel::fs::inode::InodeKind::Directory {
+                return Err(KernelError::Filesystem(VfsError::NotDirectory));
+            }
+            KernelPathBuf::from_str(file.path())?
+        };
+        for component in raw.split('/') {
+            let before = resolved.len;
+            resolved.push_component(component)?;
+            if component == ".." && resolved.len < root_len {
+                resolved.truncate_to_root(root_len);
+            } else if before < root_len {
+                resolved.truncate_to_root(root_len);
+            }
+        }
+        resolved.as_path()?;
+        Ok(resolved)
+    }
+
+    fn user_path(&self, path_ptr: u64) -> KernelResult<Path<'_>> {
+        let raw = self.user_path_str(path_ptr)?;
+        Path::new(raw).map_err(map_path_error)
+    }
+
+    fn user_path_str(&self, path_ptr: u64) -> KernelResult<&'_ str> {
+        let bytes = user_cstr(path_ptr)?;
+        core::str::from_utf8(bytes).map_err(|_| KernelError::InvalidArgument)
+    }
+
+    fn replace_process_image(
+        &mut self,
+        pid: ProcessId,
+        current_thread: Option<ThreadId>,
+        entry_point: u64,
+        stack_pointer: u64,
+        address_space_root: u64,
+    ) -> KernelResult<()> {
+        let index = self.locate_process(pid)?;
+        if let Some(pcb) = self.process_table[index].as_mut() {
+            pcb.set_exec_image(entry_point, address_space_root);
+            pcb.thread_count = 0;
+        }
+
+        if self
+            .mtss_scheduler
+            .terminate_task(Self::mtss_task_id(pid))
+            .is_ok()
+        {
+            let _ = self.mtss_scheduler.reap_task(Self::mtss_task_id(pid));
+        }
+        let mut kept_thread = current_thread;
+        if kept_thread.is_none() {
+            let mut idx = 0usize;
+            while idx < Self::THREAD_CAPACITY {
+                if let Some(thread) = self.thread_table[idx] {
+                    if thread.process == pid {
+                        kept_thread = Some(thread.id);
+                        break;
+                    }
+                }
+                idx += 1;
+            }
+        }
+
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(thread) = self.thread_table[idx] {
+                if thread.process == pid && Some(thread.id) != kept_thread {
+                    self.thread_table.remove_at(idx);
+                }
+            }
+            idx += 1;
+        }
+
+        let thread_id = if let Some(thread_id) = kept_thread {
+            let thread_index = self.locate_thread(thread_id)?;
+            let priority = self.process_table[index]
+                .as_ref()
+                .ok_or(KernelError::UnknownProcess)?
+                .priority;
+            if let Some(tcb) = self.thread_table.entry_mut(thread_index) {
+                tcb.replace_exec_image(entry_point, stack_pointer);
+                tcb.priority = priority;
+            }
+            thread_id
+        } else {
+            self.create_thread(
+                pid,
+                entry_point,
+                self.process_table[index]
+                    .as_ref()
+                    .ok_or(KernelError::UnknownProcess)?
+                    .priority,
+            )?
+        };
+
+        if let Some(pcb) = self.process_table[index].as_mut() {
+            pcb.thread_count = 1;
+        }
+        let pcb = self.process_table[index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?;
+        let priority = pcb.priority;
+        let parent = pcb.parent;
+        self.mtss_create_task(pid, parent, address_space_root, priority)
+            .and_then(|_| self.mtss_create_thread(pid, thread_id, priority))
+            .and_then(|_| self.mtss_enqueue_thread(thread_id))?;
+        self.set_process_ready_via_mtss(pid, index)
+    }
+
+    fn wait_for_child(
+        &mut self,
+        parent: ProcessId,
+        current_thread: Option<ThreadId>,
+        selector: i64,
+        status_ptr: u64,
+        options: u64,
+    ) -> KernelResult<u64> {
+        const WNOHANG: u64 = 1;
+        let parent_index = self.locate_process(parent)?;
+        let parent_pgid = self.process_table[parent_index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?
+            .process_group;
+        let wait_selector = decode_child_wait_selector(selector, parent_pgid);
+        let mut saw_child = false;
+        let mut idx = 0usize;
+        while idx < MAX_PROC {
+            if let Some(pcb) = self.process_table[idx] {
+                if pcb.parent == Some(parent) && wait_selector.matches(pcb.pid, pcb.process_group) {
+                    saw_child = true;
+                    if pcb.state == ProcessState::Zombie {
+                        let status = pcb.exit_status.unwrap_or(ExitStatus::exited(0));
+                        if status_ptr != 0 {
+                            write_user_value::<i32>(status_ptr, status.raw())?;
+                        }
+                        let pid = pcb.pid;
+                        self.clear_child_wait(parent, current_thread);
+                        self.reap_process_at(idx);
+                        return Ok(pid.raw());
+                    }
+                }
+            }
+            idx += 1;
+        }
+        if saw_child && (options & WNOHANG) != 0 {
+            Ok(0)
+        } else if saw_child {
+            self.record_child_wait(parent, parent_index, current_thread, wait_selector);
+            Err(KernelError::MessageQueueEmpty)
+        } else {
+            Err(KernelError::UnknownProcess)
+        }
+    }
+
+    fn record_child_wait(
+        &mut self,
+        parent: ProcessId,
+        parent_index: usize,
+        current_thread: Option<ThreadId>,
+        selector: ChildWaitSelector,
+    ) {
+        if let Some(pcb) = self.process_table[parent_index].as_mut() {
+            pcb.wait_for_child(selector);
+        }
+        if let Some(thread) = current_thread {
+            if let Ok(thread_index) = self.locate_thread(thread) {
+                if let Some(tcb) = self.thread_table.entry_mut(thread_index) {
+                    tcb.wait_for_child(selector);
+                    tcb.block();
+                    let _ = self
+                        .mtss_scheduler
+                        .block_thread(Self::mtss_thread_id(thread));
+                }
+            }
+        } else {
+            self.block_threads_for_process(parent);
+        }
+        self.block_process_at_index(parent, parent_index);
+    }
+
+    fn clear_child_wait(&mut self, parent: ProcessId, current_thread: Option<ThreadId>) {
+        if let Ok(parent_index) = self.locate_process(parent) {
+            if let Some(pcb) = self.process_table[parent_index].as_mut() {
+                pcb.clear_child_wait();
+            }
+        }
+        if let Some(thread) = current_thread {
+            if let Ok(thread_index) = self.locate_thread(thread) {
+                if let Some(tcb) = self.thread_table.entry_mut(thread_index) {
+                    tcb.clear_child_wait();
+                }
+            }
+        } else {
+            self.clear_child_wait_threads(parent);
+        }
+    }
+
+    fn clear_child_wait_threads(&mut self, parent: ProcessId) {
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(thread) = self.thread_table.entry_mut(idx) {
+                if thread.process == parent {
+                    thread.clear_child_wait();
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    fn wake_parent_child_waiters(&mut self, child: ProcessId) -> KernelResult<()> {
+        let child_index = self.locate_process(child)?;
+        let Some(child_pcb) = self.process_table[child_index] else {
+            return Err(KernelError::UnknownProcess);
+        };
+        let Some(parent) = child_pcb.parent else {
+            return Ok(());
+        };
+        let parent_index = self.locate_process(parent)?;
+        let should_wake = self.process_table[parent_index]
+            .as_ref()
+            .and_then(|pcb| pcb.child_wait)
+            .map(|selector| selector.matches(child, child_pcb.process_group))
+            .unwrap_or(false);
+        if !should_wake {
+            return Ok(());
+        }
+        if let Some(parent_pcb) = self.process_table[parent_index].as_mut() {
+            parent_pcb.clear_child_wait();
+        }
+        self.clear_child_wait_threads(parent);
+        if self.process_table[parent_index]
+            .as_ref()
+            .map(|pcb| pcb.state == ProcessState::Blocked)
+            .unwrap_or(false)
+        {
+            self.set_process_ready_via_mtss(parent, parent_index)?;
+        }
+        self.make_threads_ready(parent)
+    }
+
+    fn reap_process_at(&mut self, index: usize) {
+        if let Some(pcb) = self.process_table[index] {
+            self.security.revoke_task(pcb.pid);
+            self.vacate_process(index);
+            self.timers.release_process(pcb.pid);
+        }
+    }
+
+    fn queue_signal_to_parent(&mut self, child: ProcessId, signal: u8) -> KernelResult<()> {
+        let child_index = self.locate_process(child)?;
+        if let Some(parent) = self.process_table[child_index]
+            .as_ref()
+            .and_then(|pcb| pcb.parent)
+        {
+            self.queue_signal(parent, signal)?;
+        }
+        Ok(())
+    }
+
+    fn queue_signal(&mut self, pid: ProcessId, signal: u8) -> KernelResult<()> {
+        let index = self.locate_process(pid)?;
+        self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?
+            .queue_signal(signal)
+            .map_err(|_| KernelError::InvalidArgument)
+    }
+
+    fn send_signal(&mut self, selector: i64, signal: u8) -> KernelResult<u64> {
+        if signal == 0 {
+            self.ensure_process_exists(ProcessId::new(selector as u64))?;
+            return Ok(0);
+        }
+        let mut delivered = 0u64;
+        if selector > 0 {
+            self.queue_signal(ProcessId::new(selector as u64), signal)?;
+            return Ok(0);
+        }
+        let target_group = if selector == 0 {
+            None
+        } else {
+            Some(ProcessGroupId::new((-selector) as u64))
+        };
+        let mut idx = 0usize;
+        while idx < MAX_PROC {
+            if let Some(pcb) = self.process_table[idx] {
+                let matches = target_group
+                    .map(|pgid| pcb.process_group == pgid)
+                    .unwrap_or(true);
+                if matches && pcb.state != ProcessState::Zombie {
+                    if self.queue_signal(pcb.pid, signal).is_ok() {
+                        delivered += 1;
+                    }
+                }
+            }
+            idx += 1;
+        }
+        if delivered == 0 {
+            Err(KernelError::UnknownProcess)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn deliver_signal_checkpoint(&mut self, pid: ProcessId, thread: ThreadId) -> KernelResult<()> {
+        let thread_index = self.locate_thread(thread)?;
+        let mask = self.thread_table[thread_index]
+            .as_ref()
+            .ok_or(KernelError::UnknownThread)?
+            .signal_mask;
+        let process_index = self.locate_process(pid)?;
+        let Some(signal) = self.process_table[process_index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?
+            .take_deliverable_signal(mask)
+        else {
+            return Ok(());
+        };
+        let action = self.process_table[process_index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?
+            .signal_actions[signal as usize];
+        if action.handler == 0 && matches!(signal, SIGKILL | SIGTERM) {
+            self.exit_process(pid, ExitStatus::signaled(signal));
+            return Ok(());
+        }
+        if let Some(tcb) = self.thread_table.entry_mut(thread_index) {
+            tcb.deliver_signal(signal, action.handler);
+        }
+        Ok(())
+    }
+
+    fn process_credentials(
+        &self,
+        pid: ProcessId,
+    ) -> KernelResult<&crate::kernel::process::ProcessCredentials> {
+        let index = self.locate_process(pid)?;
+        Ok(&self.process_table[index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?
+            .credentials)
+    }
+
+    fn process_credentials_mut(
+        &mut self,
+        pid: ProcessId,
+    ) -> KernelResult<&mut crate::kernel::process::ProcessCredentials> {
+        let index = self.locate_process(pid)?;
+        Ok(&mut self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?
+            .credentials)
+    }
+
+    fn fs_credentials_for(&self, pid: ProcessId) -> KernelResult<FsCredentials> {
+        let credentials = self.process_credentials(pid)?;
+        Ok(FsCredentials::user_with_groups(
+            credentials.euid,
+            credentials.egid,
+            credentials.supplementary_groups(),
+            credentials.supplementary_group_count(),
+        ))
+    }
+
+    fn process_files(
+        &self,
+        pid: ProcessId,
+    ) -> KernelResult<&crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>> {
+        let index = self.locate_process(pid)?;
+        Ok(&self.process_table[index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?
+            .files)
+    }
+
+    fn process_files_mut(
+        &mut self,
+        pid: ProcessId,
+    ) -> KernelResult<&mut crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>> {
+        let index = self.locate_process(pid)?;
+        Ok(&mut self.process_table[index]
+            .as_mut()
+            .ok_or(KernelError::UnknownProcess)?
+            .files)
+    }
+
+    fn duplicate_fd(
+        &mut self,
+        pid: ProcessId,
+        old_fd: usize,
+        min_fd: usize,
+        flags: DescriptorFlags,
+    ) -> KernelResult<u64> {
+        let description = self.fd_description(pid, old_fd)?;
+        self.open_files
+            .increment_ref_count(description)
+            .map_err(map_file_table_error)?;
+        match self
+            .process_files_mut(pid)?
+            .open_at_or_above(description, flags, min_fd)
+        {
+            Ok(fd) => Ok(fd as u64),
+            Err(error) => {
+                let _ = self.close_open_description(description);
+                Err(map_process_file_table_error(error))
+            }
+        }
+    }
+
+    fn duplicate_fd_to(
+        &mut self,
+        pid: ProcessId,
+        old_fd: usize,
+        new_fd: usize,
+        flags: DescriptorFlags,
+        reject_same: bool,
+    ) -> KernelResult<u64> {
+        let description = self.fd_description(pid, old_fd)?;
+        if old_fd == new_fd {
+            if reject_same {
+                return Err(KernelError::InvalidArgument);
+            }
+            return Ok(new_fd as u64);
+        }
+        self.open_files
+            .increment_ref_count(description)
+            .map_err(map_file_table_error)?;
+        let replaced = match self
+            .process_files_mut(pid)?
+            .duplicate_to(new_fd, description, flags)
+        {
+            Ok(previous) => previous,
+            Err(error) => {
+                let _ = self.close_open_description(description);
+                return Err(map_process_file_table_error(error));
+            }
+        };
+        if let Some(previous) = replaced {
+            self.close_open_description(previous.description())?;
+        }
+        Ok(new_fd as u64)
+    }
+
+    fn fd_description(&self, pid: ProcessId, fd: usize) -> KernelResult<FileDescriptionId> {
+        self.process_files(pid)?
+            .get(fd)
+            .map(|descriptor| descriptor.description())
+            .map_err(map_process_file_table_error)
+    }
+
+    fn ensure_process_console_descriptors(
+        &mut self,
+        files: &mut crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>,
+    ) -> KernelResult<()> {
+        self.ensure_console_descriptor(files, 1, ConsoleDescriptor::Stdout)?;
+        self.ensure_console_descriptor(files, 2, ConsoleDescriptor::Stderr)
+    }
+
+    fn ensure_console_descriptor(
+        &mut self,
+        files: &mut crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>,
+        fd: usize,
+        console: ConsoleDescriptor,
+    ) -> KernelResult<()> {
+        if files.get(fd).is_ok() {
+            return Ok(());
+        }
+        let description = self
+            .open_files
+            .insert_object(DescriptorObject::Console(console))
+            .map_err(map_file_table_error)?;
+        match files.duplicate_to(fd, description, DescriptorFlags::EMPTY) {
+            Ok(None) => Ok(()),
+            Ok(Some(previous)) => {
+                let _ = self.close_open_description(previous.description());
+                Ok(())
+            }
+            Err(error) => {
+                let _ = self.close_open_description(description);
+                Err(map_process_file_table_error(error))
+            }
+        }
+    }
+
+    fn write_console_descriptor(&self, _console: ConsoleDescriptor, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        let mut start = 0usize;
+        let mut idx = 0usize;
+        while idx < data.len() {
+            if data[idx].is_ascii() {
+                idx += 1;
+                continue;
+            }
+            if start < idx {
+                if let Ok(text) = core::str::from_utf8(&data[start..idx]) {
+                    crate::arch::x86_64::early_console::write_fmt(format_args!("{}", text));
+                }
+            }
+            crate::arch::x86_64::early_console::write_fmt(format_args!("�"));
+            idx += 1;
+            start = idx;
+        }
+        if start < data.len() {
+            if let Ok(text) = core::str::from_utf8(&data[start..]) {
+                crate::arch::x86_64::early_console::write_fmt(format_args!("{}", text));
+            }
+        }
+    }
+
+    fn inherit_process_file_table(
+        &mut self,
+        parent: ProcessId,
+    ) -> KernelResult<crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>> {
+        let inherited = *self.process_files(parent)?;
+        let mut retained = 0usize;
+        let descriptors = inherited.descriptors();
+        while retained < MAX_OPEN_FILES {
+            if let Some(descriptor) = descriptors[retained] {
+                if let Err(error) = self
+                    .open_files
+                    .increment_ref_count(descriptor.description())
+                {
+                    self.release_inherited_prefix(&inherited, retained);
+                    return Err(map_file_table_error(error));
+                }
+            }
+            retained += 1;
+        }
+        Ok(inherited)
+    }
+
+    fn release_inherited_prefix(
+        &mut self,
+        table: &crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>,
+        count: usize,
+    ) {
+        let mut idx = 0usize;
+        while idx < count {
+            if let Some(descriptor) = table.descriptors()[idx] {
+                let _ = self.close_open_description(descriptor.description());
+            }
+            idx += 1;
+        }
+    }
+
+    fn release_process_file_table(
+        &mut self,
+        table: &mut crate::kernel::process::ProcessFileTable<MAX_OPEN_FILES>,
+    ) {
+        let closed = table.clear();
+        self.release_description_ids(&closed);
+    }
+
+    fn release_description_ids(
+        &mut self,
+        descriptions: &[Option<FileDescriptionId>; MAX_OPEN_FILES],
+    ) {
+        for description in descriptions.iter().flatten() {
+            let _ = self.close_open_description(*description);
+        }
+    }
+
+    fn close_open_description(&mut self, description: FileDescriptionId) -> KernelResult<()> {
+        if let Some(object) = self
+            .open_files
+            .close(description)
+            .map_err(map_file_table_error)?
+        {
+            match object {
+                DescriptorObject::Regular(file) => {
+                    self.root_fs.close(file).map_err(KernelError::Filesystem)?;
+                }
+                DescriptorObject::Pipe(endpoint) => self.release_pipe_endpoint(endpoint),
+                DescriptorObject::EventFd(id) => self.release_eventfd(id),
+                DescriptorObject::Device(_)
+                | DescriptorObject::Socket(_)
+                | DescriptorObject::Console(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn fd_object(&self, pid: ProcessId, fd: usize) -> KernelResult<DescriptorObject> {
+        let description = self.fd_description(pid, fd)?;
+        self.open_files
+            .get_object(description)
+            .map_err(map_file_table_error)
+    }
+
+    fn socket_handle_for_fd(&self, pid: ProcessId, fd: usize) -> KernelResult<SocketHandle> {
+        match self.fd_object(pid, fd)? {
+            DescriptorObject::Socket(handle) => Ok(handle),
+            _ => Err(KernelError::Filesystem(VfsError::InvalidHandle)),
+        }
+    }
+
+    fn allocate_socket_handle(&mut self) -> KernelResult<SocketHandle> {
+        let raw = self.next_socket_handle;
+        if raw == 0 {
+            return Err(KernelError::FileTableFull);
+        }
+        self.next_socket_handle = self
+            .next_socket_handle
+            .checked_add(1)
+            .ok_or(KernelError::FileTableFull)?;
+        Ok(SocketHandle::new(raw))
+    }
+
+    fn network_request_header(
+        &mut self,
+        opcode: NetworkOpcode,
+        client: ProcessId,
+        flags: u32,
+    ) -> NetworkRequestHeader {
+        NetworkRequestHeader::new(opcode, client, self.next_message_sequence(), flags)
+    }
+
+    fn send_network_request<T: NetworkIpcRequest>(
+        &mut self,
+        sender: ProcessId,
+        request: &T,
+    ) -> KernelResult<()> {
+        let receiver = self
+            .service_registry
+            .owner(RegistryServiceId::Networkd)
+            .ok_or(KernelError::UnknownProcess)?;
+        self.ensure_process_exists(receiver)?;
+        self.security
+            .authorize_ipc(
+                sender,
+                receiver,
+                RegistryServiceId::Networkd.security_class(),
+            )
+            .map_err(KernelError::SecurityViolation)?;
+        let payload = MessagePayload::from_slice(
+            RegistryServiceId::Networkd.security_class(),
+            request.as_bytes(),
+        );
+        self.send_message(sender, receiver, payload)
+    }
+
+    fn allocate_pipe(&mut self) -> KernelResult<PipeId> {
+        let mut idx = 0usize;
+        while idx < MAX_KERNEL_PIPES {
+            if self.pipes[idx].is_none() {
+                self.pipes[idx] = Some(PipeObject::new());
+                return Ok(PipeId::new(idx));
+            }
+            idx += 1;
+        }
+        Err(KernelError::FileTableFull)
+    }
+
+    fn release_pipe_endpoint(&mut self, endpoint: PipeEndpoint) {
+        if let Some(pipe) = self
+            .pipes
+            .get_mut(endpoint.id().raw())
+            .and_then(Option::as_mut)
+        {
+            match endpoint.direction() {
+                PipeDirection::Read => pipe.readers = pipe.readers.saturating_sub(1),
+                PipeDirection::Write => pipe.writers = pipe.writers.saturating_sub(1),
+            }
+            if pipe.readers == 0 && pipe.writers == 0 {
+                self.pipes[endpoint.id().raw()] = None;
+            }
+        }
+    }
+
+    fn allocate_eventfd(&mut self, counter: u64, semaphore: bool) -> KernelResult<EventFdId> {
+        let mut idx = 0usize;
+        while idx < MAX_KERNEL_EVENTFDS {
+            if self.eventfds[idx].is_none() {
+                self.eventfds[idx] = Some(EventFdObject::new(counter, semaphore));
+                return Ok(EventFdId::new(idx));
+            }
+            idx += 1;
+        }
+        Err(KernelError::FileTableFull)
+    }
+
+    fn release_eventfd(&mut self, id: EventFdId) {
+        if id.raw() < MAX_KERNEL_EVENTFDS {
+            self.eventfds[id.raw()] = None;
+        }
+    }
+
+    fn descriptor_readiness(&self, object: DescriptorObject, events: i16) -> i16 {
+        let mut revents = 0i16;
+        match object {
+            DescriptorObject::Regular(_) => {
+                if events & (POLLIN | POLLPRI) != 0 {
+                    revents |= events & (POLLIN | POLLPRI);
+                }
+                if events & POLLOUT != 0 {
+                    revents |= POLLOUT;
+                }
+            }
+            DescriptorObject::Pipe(endpoint) => {
+                match self.pipes.get(endpoint.id().raw()).and_then(|entry| *entry) {
+                    Some(pipe) => match endpoint.direction() {
+                        PipeDirection::Read => {
+                            if events & (POLLIN | POLLPRI) != 0 && pipe.is_readable() {
+                                revents |= events & (POLLIN | POLLPRI);
+                            }
+                            if pipe.writers == 0 {
+                                revents |= POLLHUP;
+                            }
+                        }
+                        PipeDirection::Write => {
+                            if events & POLLOUT != 0 && pipe.is_writable() {
+                                revents |= POLLOUT;
+                            }
+                            if pipe.readers == 0 {
+                                revents |= POLLERR;
+                            }
+                        }
+                    },
+                    None => revents |= POLLNVAL,
+                }
+            }
+            DescriptorObject::EventFd(id) => {
+                match self.eventfds.get(id.raw()).and_then(|entry| *entry) {
+                    Some(eventfd) => {
+                        if events & (POLLIN | POLLPRI) != 0 && eventfd.is_readable() {
+                            revents |= events & (POLLIN | POLLPRI);
+                        }
+                        if events & POLLOUT != 0 && eventfd.is_writable() {
+                            revents |= POLLOUT;
+                        }
+                    }
+                    None => revents |= POLLNVAL,
+                }
+            }
+            DescriptorObject::Device(_)
+            | DescriptorObject::Socket(_)
+            | DescriptorObject::Console(_) => {
+                if events & (POLLIN | POLLPRI) != 0 {
+                    revents |= events & (POLLIN | POLLPRI);
+                }
+                if events & POLLOUT != 0 {
+                    revents |= POLLOUT;
+                }
+            }
+        }
+        revents
+    }
+
+    fn poll_descriptors(
+        &mut self,
+        pid: ProcessId,
+        thread: Option<ThreadId>,
+        fds: &mut [MiragePollFd],
+        timeout_ms: i32,
+    ) -> KernelResult<u64> {
+        let mut ready = 0u64;
+        let mut idx = 0usize;
+        while idx < fds.len() {
+            fds[idx].revents = 0;
+            if fds[idx].fd >= 0 {
+                match self.fd_object(pid, fds[idx].fd as usize) {
+                    Ok(object) => {
+                        fds[idx].revents = self.descriptor_readiness(object, fds[idx].events)
+                    }
+                    Err(_) => fds[idx].revents = POLLNVAL,
+                }
+                if fds[idx].revents != 0 {
+                    ready += 1;
+                }
+            }
+            idx += 1;
+        }
+        if ready == 0 && timeout_ms != 0 {
+            if timeout_ms > 0 {
+                let wake_deadline = KERNEL_TIME
+                    .now()
+                    .as_nanos()
+                    .saturating_add((timeout_ms as u128).saturating_mul(1_000_000));
+                self.timers
+                    .add_sleep(pid, thread, wake_deadline)
+                    .map_err(map_timer_error)?;
+            }
+            let process_index = self.locate_process(pid)?;
+            self.block_process_at_index(pid, process_index);
+        }
+        Ok(ready)
+    }
+
+    pub fn tick(&mut self) {
+        self.tick_prologue();
+        let mut core_index = 0usize;
+        while core_index < MAX_CORES {
+            if self.core_states[core_index].online {
+                self.run_core(core_index);
+            }
+            core_index += 1;
+        }
+    }
+
+    /// One tick that runs cores in `order` instead of by index, so tests can
+    /// replay a particular interleaving. Offline or out-of-range cores in
+    /// `order` are skipped; a core listed twice runs twice.
+    #[cfg(test)]
+    pub(crate) fn tick_with_order(&mut self, order: &[usize]) {
+        self.tick_prologue();
+        for &core_index in order {
+            if self
+                .core_states
+                .get(core_index)
+                .is_some_and(|core| core.online)
+            {
+                self.run_core(core_index);
+            }
+        }
+    }
+
+    /// Timekeeping and deferred work that runs before any core is scheduled.
+    fn tick_prologue(&mut self) {
+        for core in self.core_states.iter_mut() {
+            core.begin_tick();
+        }
+        if self.scheduler_cross_check && self.mtss_initialized {
+            self.cross_check_scheduler();
+        }
+        for arena in self.scratch_arenas.iter_mut() {
+            arena.reset();
+        }
+        self.finish_terminations();
+        self.kernel_on_timer_tick();
+        self.check_deadlines();
+        self.roll_over_cpu_quotas();
+        self.retry_pending_sends();
+        self.spin_message_waiters();
+        device::system_timer().tick();
+        if let (_, Some(jump)) = KERNEL_TIME.tick_checked() {
+            self.cross_clock_discontinuity(jump);
+        }
+        self.softirqs.raise(SoftirqKind::Timer);
+        if self.device_reads.iter().any(Option::is_some) {
+            self.softirqs.raise(SoftirqKind::DeviceReady);
+        }
+        self.run_softirqs();
+        self.service_thread_waitlist();
+        self.stack_guard_countdown = self.stack_guard_countdown.saturating_sub(1);
+        if self.stack_guard_countdown == 0 {
+            self.stack_guard_countdown = STACK_GUARD_CHECK_INTERVAL;
+            let _ = self.check_stack_guards();
+        }
+    }
+
+    /// Runs `n` ticks back to back, as if calling [`Self::tick`] `n` times.
+    /// Timers, futexes and exec deadlines are checked against every
+    /// intermediate timestamp, so the clock still advances one tick at a time.
+    /// Stops early once every process that was live on entry has exited;
+    /// returns the number of ticks actually run.
+    pub fn run_ticks(&mut self, n: u64) -> u64 {
+        let had_processes = self.process_table.iter().any(Option::is_some);
+        let mut ran = 0u64;
+        while ran < n {
+            self.tick();
+            ran += 1;
+            if had_processes && self.process_table.iter().all(Option::is_none) {
+                break;
+            }
+        }
+        ran
+    }
+
+    /// Ticks until a fixed point or until `max_ticks` have run. A tick is a
+    /// fixed point when it sent, delivered or queued no message, changed no
+    /// process or thread lifecycle state, left no softirq pending and no
+    /// staged syscall undispatched, and no sleep, timer or futex timeout is
+    /// due before the budget would run out.
+    pub fn quiesce(&mut self, max_ticks: u64) -> QuiesceResult {
+        let mut ticks = 0u64;
+        let mut fixed_point = false;
+        while ticks < max_ticks {
+            let before = self.activity_snapshot();
+            self.tick();
+            ticks += 1;
+            let now = KERNEL_TIME.now();
+            let horizon_ns = MonotonicTimestamp::new(
+                now.ticks().saturating_add(max_ticks - ticks),
+                now.frequency(),
+            )
+            .as_nanos();
+            if self.activity_snapshot() == before && !self.has_deferred_work(horizon_ns) {
+                fixed_point = true;
+                break;
+            }
+        }
+        let after = self.activity_snapshot();
+        QuiesceResult {
+            ticks,
+            fixed_point,
+            blocked_processes: after.blocked_processes,
+            queued_messages: after.queued_messages,
+        }
+    }
+
+    fn activity_snapshot(&self) -> ActivitySnapshot {
+        let processes = self.process_table.iter().flatten();
+        let threads = self.thread_table.iter().flatten();
+        ActivitySnapshot {
+            messages_sent: self.message_sequence,
+            queued_messages: self.ipc_queues.iter().map(|queue| queue.len()).sum(),
+            processes: processes.clone().count(),
+            blocked_processes: processes
+                .clone()
+                .filter(|pcb| pcb.state == ProcessState::Blocked)
+                .count(),
+            exited_processes: processes
+                .filter(|pcb| {
+                    matches!(
+                        pcb.state,
+                        ProcessState::Terminating | ProcessState::Zombie | ProcessState::Terminated
+                    )
+                })
+                .count(),
+            threads: threads.clone().count(),
+            blocked_threads: threads
+                .filter(|tcb| tcb.state == ThreadState::Blocked)
+                .count(),
+            pending_spawns: self.thread_waitlist.len(),
+        }
+    }
+
+    /// Whether work is already queued for a later tick: a pending softirq, a
+    /// runnable thread with a staged syscall, or a deadline at or before
+    /// `horizon_ns`.
+    fn has_deferred_work(&self, horizon_ns: u128) -> bool {
+        let softirq_pending = self.softirqs.is_pending(SoftirqKind::Timer)
+            || self.softirqs.is_pending(SoftirqKind::DeviceReady);
+        let staged_syscall = self.thread_table.iter().flatten().any(|tcb| {
+            tcb.state != ThreadState::Blocked
+                && tcb.state != ThreadState::Terminated
+                && tcb.context.trap_vector == SYSCALL_TRAP_VECTOR
+        });
+        let deadline_due = [self.timers.next_deadline(), self.futexes.next_deadline()]
+            .into_iter()
+            .flatten()
+            .any(|deadline| deadline <= horizon_ns);
+        softirq_pending || staged_syscall || deadline_due
+    }
+
+    /// Raises deferred work from interrupt context; it runs on the next tick.
+    pub fn raise_softirq(&self, kind: SoftirqKind) {
+        self.softirqs.raise(kind);
+    }
+
+    pub fn softirq_stats(&self, kind: SoftirqKind) -> SoftirqStats {
+        self.softirqs.stats(kind)
+    }
+
+    fn run_softirqs(&mut self) {
+        let mut budget = SOFTIRQ_BUDGET;
+        while let Some(kind) = self.softirqs.take_next(&mut budget) {
+            match kind {
+                SoftirqKind::Timer => self.expire_due_deadlines(),
+                SoftirqKind::DeviceReady => self.retry_device_reads(),
+            }
+        }
+    }
+
+    fn expire_due_deadlines(&mut self) {
+        let timestamp = KERNEL_TIME.now();
+        let now_ns = timestamp.as_nanos();
+        self.wake_expired_timeouts(now_ns);
+        self.wake_expired_futexes(now_ns);
+        self.expire_exec_deadlines(timestamp.ticks());
+    }
+
+    /// Move the hardware clock as `adjustment` says and carry every pending
+    /// deadline across the jump: sleeps, process timers, futex timeouts and
+    /// execution deadlines. Whatever the jump made due fires before this
+    /// returns; see [`rebase_deadline`] for where the rest land.
+    pub fn adjust_clock(&mut self, adjustment: ClockAdjustment) -> ClockDiscontinuity {
+        let old_ticks = clock::HARDWARE_CLOCK.now();
+        match adjustment {
+            ClockAdjustment::Resume { slept_ticks } => {
+                clock::HARDWARE_CLOCK.advance(slept_ticks);
+            }
+            ClockAdjustment::Reset => clock::HARDWARE_CLOCK.reset(),
+            ClockAdjustment::Set { ticks } => clock::HARDWARE_CLOCK.set(ticks),
+        }
+        let jump = KERNEL_TIME.notice_discontinuity(old_ticks, clock::HARDWARE_CLOCK.now());
+        self.cross_clock_discontinuity(jump);
+        self.expire_due_deadlines();
+        jump
+    }
+
+    pub fn clock_discontinuity_count(&self) -> u64 {
+        self.clock_discontinuities
+    }
+
+    pub fn last_clock_discontinuity(&self) -> Option<ClockDiscontinuity> {
+        self.last_clock_discontinuity
+    }
+
+    fn cross_clock_discontinuity(&mut self, jump: ClockDiscontinuity) {
+        let frequency = clock::HARDWARE_CLOCK.frequency();
+        let old_ns = MonotonicTimestamp::new(jump.old_ticks, frequency).as_nanos();
+        let new_ns = MonotonicTimestamp::new(jump.new_ticks, frequency).as_nanos();
+        self.timers.rebase(old_ns, new_ns);
+        self.futexes.rebase(old_ns, new_ns);
+        for pcb in self.process_table.iter_mut().flatten() {
+            if let Some(deadline) = pcb.execution_deadline.as_mut() {
+                *deadline = rebase_deadline(
+                    u128::from(*deadline),
+                    u128::from(jump.old_ticks),
+                    u128::from(jump.new_ticks),
+                ) as u64;
+            }
+        }
+        self.clock_discontinuities = self.clock_discontinuities.saturating_add(1);
+        self.last_clock_discontinuity = Some(jump);
+        crate::kprintln!(
+            "clock jumped from tick {} to {}{}",
+            jump.old_ticks,
+            jump.new_ticks,
+            if jump.mediated { "" } else { " without notice" }
+        );
+    }
+
+    /// Scans every live thread's guard page (the page directly below its
+    /// lowest mapped stack page). A non-zero first byte means the stack ran
+    /// into the guard, and is resolved like a fault on that page by
+    /// [`Self::handle_stack_fault`]: the stack grows if its policy allows,
+    /// otherwise only that thread is killed. Returns the number of hits found
+    /// by this scan.
+    pub fn check_stack_guards(&mut self) -> usize {
+        let mut hits = 0usize;
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            let Some(tcb) = self.thread_table[idx] else {
+                idx += 1;
+                continue;
+            };
+            if tcb.stack_guard_hit || tcb.state == ThreadState::Terminated {
+                idx += 1;
+                continue;
+            }
+            let root = self
+                .locate_process(tcb.process)
+                .ok()
+                .and_then(|index| self.process_table[index].as_ref())
+                .map(|pcb| pcb.address_space_root)
+                .unwrap_or(0);
+            let Some(guard) = tcb.stack_guard_page() else {
+                idx += 1;
+                continue;
+            };
+            let mut first_byte = [0u8; 1];
+            if root != 0
+                && memory::copy_from_user(root, guard, &mut first_byte)
+                && first_byte[0] != 0
+            {
+                let _ = self.handle_stack_fault(tcb.id, guard);
+                hits += 1;
+            }
+            idx += 1;
+        }
+        hits
+    }
+
+    /// Resolves a page fault at `address` on `thread`'s stack guard page
+    /// according to its [`StackPolicy`]. Growth maps the guard page into the
+    /// process and moves the guard one page down; an overflow, or growth that
+    /// hits its cap or cannot map a page, terminates only the faulting thread
+    /// and records a `StackOverflow` fault dump. Faults outside the guard
+    /// are `InvalidArgument`.
+    pub fn handle_stack_fault(
+        &mut self,
+        thread: ThreadId,
+        address: u64,
+    ) -> KernelResult<StackFaultOutcome> {
+        let index = self.locate_thread(thread)?;
+        let tcb = self.thread_table[index].ok_or(KernelError::UnknownThread)?;
+        let base = tcb.stack_base().ok_or(KernelError::InvalidArgument)?;
+        let guard = tcb.stack_guard_page().ok_or(KernelError::InvalidArgument)?;
+        if address >= base || base - address > tcb.stack_guard_size as u64 {
+            return Err(KernelError::InvalidArgument);
+        }
+
+        if let StackPolicy::Grow { max_bytes } = tcb.stack_policy {
+            let root = self
+                .locate_process(tcb.process)
+                .ok()
+                .and_then(|slot| self.process_table[slot].as_ref())
+                .map(|pcb| pcb.address_space_root)
+                .unwrap_or(0);
+            let within_cap = tcb.stack_grown + memory::PAGE_SIZE as u32 <= max_bytes;
+            if within_cap
+                && root != 0
+                && memory::map_user_stack_page(tcb.process, root, guard).is_some()
+            {
+                let Some(grown) = self.thread_table.entry_mut(index) else {
+                    return Err(KernelError::UnknownThread);
+                };
+                grown.stack_grown += memory::PAGE_SIZE as u32;
+                return Ok(StackFaultOutcome::Grown {
+                    guard: guard.saturating_sub(memory::PAGE_SIZE as u64),
+                });
+            }
+        }
+
+        self.overflow_thread(index, thread, tcb.process);
+        Ok(StackFaultOutcome::Overflow)
+    }
+
+    /// Flags `thread` as having overflowed its stack, records a
+    /// `StackOverflow` fault dump and terminates only that thread.
+    fn overflow_thread(&mut self, index: usize, thread: ThreadId, process: ProcessId) {
+        if let Some(faulted) = self.thread_table.entry_mut(index) {
+            faulted.stack_guard_hit = true;
+        }
+        if let Some(dump) = self.capture_fault_dump(process, IsolationError::StackOverflow) {
+            self.fault_dumps.push(dump);
+        }
+        self.terminate_thread(thread);
+    }
+
+    /// Expect `thread` to finish its current work by scheduler tick
+    /// `absolute_tick`. Blocking or exiting meets the deadline; if the thread
+    /// is still runnable once that tick has passed, a miss is counted on it.
+    /// Replaces any deadline already set.
+    pub fn set_deadline(&mut self, thread: ThreadId, absolute_tick: u64) -> KernelResult<()> {
+        let index = self.locate_thread(thread)?;
+        let tcb = self
+            .thread_table
+            .entry_mut(index)
+            .ok_or(KernelError::UnknownThread)?;
+        tcb.deadline = Some(absolute_tick);
+        Ok(())
+    }
+
+    /// Deadlines `thread` has missed since it was created.
+    pub fn deadline_misses(&self, thread: ThreadId) -> KernelResult<u32> {
+        let index = self.locate_thread(thread)?;
+        self.thread_table[index]
+            .map(|tcb| tcb.deadline_misses)
+            .ok_or(KernelError::UnknownThread)
+    }
+
+    /// Retire every deadline that has passed, counting a miss for threads
+    /// that are still runnable.
+    fn check_deadlines(&mut self) {
+        let now = self.mtss_ticks;
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(tcb) = self.thread_table.entry_mut(idx) {
+                if tcb.deadline.is_some_and(|deadline| deadline < now) {
+                    tcb.deadline = None;
+                    if matches!(tcb.state, ThreadState::Ready | ThreadState::Running) {
+                        tcb.deadline_misses = tcb.deadline_misses.saturating_add(1);
+                    }
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    /// Number of live threads whose stack guard page has been hit.
+    pub fn stack_overflow_count(&self) -> usize {
+        self.thread_table
+            .iter()
+            .flatten()
+            .filter(|tcb| tcb.stack_guard_hit)
+            .count()
+    }
+
+    /// Reserve `size` bytes of kernel heap as a bump arena owned by `pid`.
+    /// Objects carved with [`Self::arena_alloc`] are not tracked one by one;
+    /// the whole arena is freed by [`Self::destroy_arena`] or when `pid`
+    /// exits.
+    pub fn create_arena(&mut self, pid: ProcessId, size: usize) -> KernelResult<ArenaId> {
+        self.locate_process(pid)?;
+        if size == 0 {
+            return Err(KernelError::InvalidArgument);
+        }
+        let slot = self
+            .process_arenas
+            .find_free()
+            .ok_or(KernelError::AllocationFailed)?;
+        let base = memory::malloc_aligned_for(pid, size, ARENA_ALIGN)
+            .ok_or(KernelError::AllocationFailed)?;
+        let id = ArenaId::new(self.process_arenas.handle_for(slot).raw());
+        self.process_arenas
+            .insert_at(slot, ProcessArena::new(pid, base, size));
+        Ok(id)
+    }
+
+    /// Carve `len` bytes aligned to `align` from one of `pid`'s arenas.
+    pub fn arena_alloc(
+        &mut self,
+        pid: ProcessId,
+        arena: ArenaId,
+        len: usize,
+        align: usize,
+    ) -> KernelResult<NonNull<u8>> {
+        let arena = self
+            .process_arenas
+            .get_mut(Handle::from_raw(arena.raw()))
+            .filter(|arena| arena.owner() == pid)
+            .ok_or(KernelError::InvalidArgument)?;
+        arena.alloc(len, align).ok_or(KernelError::AllocationFailed)
+    }
+
+    /// Return one of `pid`'s arenas to the heap in a single free, along with
+    /// everything carved from it. Returns the arena's size in bytes.
+    pub fn destroy_arena(&mut self, pid: ProcessId, arena: ArenaId) -> KernelResult<usize> {
+        let handle = Handle::from_raw(arena.raw());
+        if self
+            .process_arenas
+            .get(handle)
+            .is_none_or(|arena| arena.owner() != pid)
+        {
+            return Err(KernelError::InvalidArgument);
+        }
+        let arena = self
+            .process_arenas
+            .remove(handle)
+            .ok_or(KernelError::InvalidArgument)?;
+        memory::free_for(pid, arena.base());
+        Ok(arena.size())
+    }
+
+    fn release_arenas(&mut self, mut matches: impl FnMut(&ProcessArena) -> bool) {
+        let mut idx = 0usize;
+        while idx < MAX_PROCESS_ARENAS {
+            if self.process_arenas[idx].is_some_and(|arena| matches(&arena)) {
+                if let Some(arena) = self.process_arenas.remove_at(idx) {
+                    memory::free_for(arena.owner(), arena.base());
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    /// Returns the scratch arena for `core_index`. Buffers handed out through
+    /// the context are reclaimed at the start of the next tick.
+    pub fn scratch_context(&mut self, core_index: usize) -> Option<ScratchCtx<'_>> {
+        self.scratch_arenas
+            .get_mut(core_index)
+            .map(|arena| ScratchCtx::new(core_index, arena))
+    }
+
+    pub fn scratch_high_water_mark(&self, core_index: usize) -> Option<usize> {
+        self.scratch_arenas
+            .get(core_index)
+            .map(ScratchArena::high_water_mark)
+    }
+
+    /// Core currently running a thread of `pid`, if any.
+    fn core_running(&self, pid: ProcessId) -> Option<usize> {
+        self.core_states.iter().position(|core| {
+            core.current_thread
+                .and_then(|thread| self.locate_thread(thread).ok())
+                .and_then(|index| self.thread_table[index].as_ref())
+                .is_some_and(|tcb| tcb.process == pid)
+        })
+    }
+
+    fn wake_expired_timeouts(&mut self, now_ns: u128) {
+        while let Some(expired) = self.timers.expire_sleep(now_ns) {
+            // Kernel tasks sleep one thread at a time, not the whole process.
+            match expired.thread {
+                Some(thread) if self.kernel_task_pid == Some(expired.process) => {
+                    let _ = self.wake_thread(thread);
+                }
+                _ => {
+                    let _ = self.wake_process_for_timeout(expired.process);
+                }
+            }
+        }
+
+        while let Some(expired) = self.timers.expire_timer(now_ns) {
+            let _ = self.wake_process_for_timeout(expired.owner);
+        }
+    }
+
+    fn wake_expired_futexes(&mut self, now_ns: u128) {
+        let mut expired_threads = [None; MAX_THREADS];
+        let count = self.futexes.expire(now_ns, &mut expired_threads);
+        let _ = self.wake_futex_threads(
+            &expired_threads,
+            count,
+            encode_syscall_error(KernelError::TimedOut),
+        );
+    }
+
+    fn wake_process_for_timeout(&mut self, pid: ProcessId) -> KernelResult<()> {
+        let index = self.locate_process(pid)?;
+        let mut wake_threads = false;
+        if let Some(pcb) = self.process_table[index].as_ref() {
+            if pcb.state == ProcessState::Blocked {
+                self.set_process_ready_via_mtss(pid, index)?;
+                wake_threads = true;
+            }
+        }
+        if wake_threads {
+            self.make_threads_ready(pid)?;
+        }
+        Ok(())
+    }
+
+    fn run_core(&mut self, core_index: usize) {
+        if let Some(scheduled) = self.schedule_for_core(core_index) {
+            // A dispatched thread whose table entry or process is gone is
+            // dropped everywhere it is still tracked, not just skipped.
+            let thread_index = match self.locate_thread(scheduled.thread) {
+                Ok(idx) => idx,
+                Err(_) => {
+                    self.terminate_thread(scheduled.thread);
+                    debug_assert!(self.check_thread_accounting().is_ok());
+                    self.core_states[core_index].idle_cycle();
+                    return;
+                }
+            };
+
+            let process_index = match self.locate_process(scheduled.process) {
+                Ok(idx) => idx,
+                Err(_) => {
+                    self.terminate_orphaned_threads(scheduled.process);
+                    debug_assert!(self.check_thread_accounting().is_ok());
+                    self.core_states[core_index].idle_cycle();
+                    return;
+                }
+            };
+            if self.process_table[process_index]
+                .as_ref()
+                .is_some_and(|pcb| pcb.state == ProcessState::Terminating)
+            {
+                self.terminate_thread(scheduled.thread);
+                self.core_states[core_index].idle_cycle();
+                return;
+            }
+
+            if self.kernel_task_pid == Some(scheduled.process) {
+                if let Some(slot) = self.kernel_task_slot(scheduled.thread) {
+                    self.run_kernel_task(core_index, slot, scheduled, thread_index, process_index);
+                    return;
+                }
+            }
+
+            if let Err(reason) = self.security.enforce_isolation(scheduled.process) {
+                self.handle_isolation_fault(scheduled.process, reason);
+                return;
+            }
+
+            let _ = self.deliver_signal_checkpoint(scheduled.process, scheduled.thread);
+            if self.locate_thread(scheduled.thread).is_err() {
+                self.core_states[core_index].idle_cycle();
+                return;
+            }
+
+            let address_space_root = self.process_table[process_index]
+                .as_ref()
+                .map(|pcb| pcb.address_space_root)
+                .unwrap_or(0);
+            if address_space_root == 0 {
+                self.handle_isolation_fault(scheduled.process, IsolationError::PolicyViolation);
+                return;
+            }
+
+            let saved_sp = self.thread_table[thread_index]
+                .as_ref()
+                .filter(|tcb| tcb.stack_size != 0)
+                .map(|tcb| tcb.context.rsp);
+            if let Some(sp) = saved_sp {
+                if self.check_stack(scheduled.thread, sp).is_err() {
+                    self.overflow_thread(thread_index, scheduled.thread, scheduled.process);
+                    self.core_states[core_index].idle_cycle();
+                    return;
+                }
+            }
+
+            let kernel_stack_top = x86_64::kernel_stack_top(core_index);
+            self.core_states[core_index].set_kernel_stack_top(kernel_stack_top);
+            self.core_states[core_index].start_thread(scheduled.thread);
+            self.record_dispatch_wait(core_index, thread_index);
+            #[cfg(feature = "latency-metrics")]
+            if let Some(woken) = self
+                .thread_table
+                .entry_mut(thread_index)
+                .and_then(|tcb| tcb.woken_tick.take())
+            {
+                self.latency
+                    .wake_to_run
+                    .record(self.mtss_ticks.saturating_sub(woken));
+            }
+            if let (Some(pcb), Some(tcb)) = (
+                self.process_table[process_index].as_ref(),
+                self.thread_table[thread_index].as_ref(),
+            ) {
+                PROFILER.sample_if_due(
+                    KERNEL_TIME.uptime_ticks(),
+                    &self.core_states[core_index],
+                    pcb,
+                    tcb,
+                );
+            }
+
+            let mut terminated = false;
+            let mut run_outcome = ThreadRunOutcome::TimeSliceComplete;
+            if let Some(thread) = self.thread_table.entry_mut(thread_index) {
+                if thread.state == ThreadState::Terminated {
+                    terminated = true;
+                } else {
+                    run_outcome = x86_64::run_thread_slice(ThreadSliceRunContext {
+                        core_index,
+                        thread: scheduled.thread,
+                        process: scheduled.process,
+                        address_space_root,
+                        kernel_stack_top,
+                        context: &mut thread.context,
+                    });
+                    if run_outcome != ThreadRunOutcome::UserEntryInvalid {
+                        thread.mark_running();
+                        thread.accumulate_cpu_time(1);
+                    }
+                }
+            }
+
+            if terminated {
+                self.terminate_thread(scheduled.thread);
+                self.core_states[core_index].finish_cycle();
+                return;
+            }
+
+            self.set_process_running_via_mtss(scheduled.process, process_index)
+                .unwrap_or_else(|_| {
+                    self.handle_isolation_fault(scheduled.process, IsolationError::PolicyViolation)
+                });
+            if let Some(pcb) = self.process_table[process_index].as_mut() {
+                pcb.cpu_time = pcb.cpu_time.saturating_add(1);
+            }
+            self.charge_cpu_quota(scheduled.process, process_index);
+
+            match run_outcome {
+                ThreadRunOutcome::Syscall(trap) => {
+                    let context =
+                        SyscallContext::new(scheduled.process, Some(trap.thread), trap.args);
+                    let result = self
+                        .handle_syscall(trap.number, context)
+                        .unwrap_or_else(encode_syscall_error);
+                    if !self.syscall_trap_target_can_resume(scheduled.process, trap.thread) {
+                        self.core_states[core_index].finish_cycle();
+                        if let Some(next) = self.kernel_schedule_next() {
+                            self.pending_mtss_decision = Some(next);
+                        } else {
+                            self.core_states[core_index].idle_cycle();
+                        }
+                        return;
+                    }
+                    self.write_thread_syscall_result(trap.thread, result);
+                    let _ = self.deliver_signal_checkpoint(scheduled.process, trap.thread);
+                    if !self.syscall_trap_target_can_resume(scheduled.process, trap.thread) {
+                        self.core_states[core_index].finish_cycle();
+                        if let Some(next) = self.kernel_schedule_next() {
+                            self.pending_mtss_decision = Some(next);
+                        } else {
+                            self.core_states[core_index].idle_cycle();
+                        }
+                        return;
+                    }
+                }
+                ThreadRunOutcome::TimerPreempted | ThreadRunOutcome::TimeSliceComplete => {}
+                ThreadRunOutcome::UserEntryInvalid => {
+                    self.handle_isolation_fault(scheduled.process, IsolationError::PolicyViolation);
+                }
+                ThreadRunOutcome::PageFault { address } => {
+                    // A guard-page hit grows the stack or kills the thread;
+                    // any other user fault is an isolation fault.
+                    if self.handle_stack_fault(scheduled.thread, address).is_err() {
+                        self.handle_isolation_fault(
+                            scheduled.process,
+                            IsolationError::PolicyViolation,
+                        );
+                    }
+                }
+            }
+
+            let mut requeue_thread = false;
+            if let Some(thread) = self.thread_table.entry_mut(thread_index) {
+                if thread.state == ThreadState::Running {
+                    thread.mark_ready_at(self.mtss_ticks);
+                }
+                requeue_thread = thread.state == ThreadState::Ready;
+            }
+
+            let process_has_runnable_threads = self.has_runnable_thread(scheduled.process);
+            if let Some(pcb) = self.process_table[process_index].as_ref() {
+                if pcb.state == ProcessState::Running {
+                    if process_has_runnable_threads {
+                        let _ = self.set_process_ready_via_mtss(scheduled.process, process_index);
+                    } else {
+                        let _ = self.set_process_blocked_via_mtss(scheduled.process, process_index);
+                    }
+                }
+            }
+
+            self.core_states[core_index].finish_cycle();
+
+            if requeue_thread {
+                match self.kernel_yield_current(scheduled) {
+                    Ok(Some(next)) => {
+                        // MTSS has already selected the next runnable thread. The
+                        // single-slice core loop defers dispatching that exact MTSS
+                        // decision until the next scheduler tick, where
+                        // `kernel_schedule_next` will expose only MTSS-selected
+                        // threads to the architecture backend.
+                        self.pending_mtss_decision = Some(next);
+                    }
+                    Ok(None) => {}
+                    Err(_) => {
+                        self.core_states[core_index].idle_cycle();
+                    }
+                }
+            }
+        } else {
+            self.core_states[core_index].idle_cycle();
+            memory::scrub_idle();
+        }
+    }
+
+    /// MTSS's next thread for `core_index`. MTSS keeps one current thread, so
+    /// its pick can be a thread another core already ran this tick; that
+    /// thread is handed back and the next one taken instead. When nothing
+    /// else can run, the pick stays pending for the next tick and the core
+    /// idles.
+    fn schedule_for_core(&mut self, core_index: usize) -> Option<KernelThreadScheduleRecord> {
+        let scheduled = self.kernel_schedule_next()?;
+        if !self.started_on_other_core(core_index, scheduled.thread) {
+            return Some(scheduled);
+        }
+        let next = match self.mtss_scheduler.pass_current() {
+            Ok(Some(decision)) => self.schedule_record_from_mtss(decision),
+            _ => Some(scheduled),
+        }?;
+        if self.started_on_other_core(core_index, next.thread) {
+            self.pending_mtss_decision = Some(next);
+            return None;
+        }
+        Some(next)
+    }
+
+    fn started_on_other_core(&self, core_index: usize, thread: ThreadId) -> bool {
+        self.core_states
+            .iter()
+            .enumerate()
+            .any(|(index, core)| index != core_index && core.started_this_tick == Some(thread))
+    }
+
+    /// Charge the wait of the thread just dispatched on `core_index` to that
+    /// core's latency histogram.
+    pub(super) fn record_dispatch_wait(&mut self, core_index: usize, thread_index: usize) {
+        let now = self.mtss_ticks;
+        if let Some(ready) = self
+            .thread_table
+            .entry_mut(thread_index)
+            .and_then(|tcb| tcb.ready_tick.take())
+        {
+            self.core_states[core_index]
+                .dispatch_latency
+                .record(now.saturating_sub(ready));
+        }
+    }
+
+    fn syscall_trap_target_can_resume(&self, pid: ProcessId, thread: ThreadId) -> bool {
+        let process_index = match self.locate_process(pid) {
+            Ok(index) => index,
+            Err(_) => return false,
+        };
+        if self.process_table[process_index]
+            .as_ref()
+            .map(|pcb| pcb.state == ProcessState::Zombie)
+            .unwrap_or(true)
+        {
+            return false;
+        }
+        let thread_index = match self.locate_thread(thread) {
+            Ok(index) => index,
+            Err(_) => return false,
+        };
+        self.thread_table[thread_index]
+            .as_ref()
+            .map(|tcb| tcb.process == pid && tcb.state != ThreadState::Terminated)
+            .unwrap_or(false)
+    }
+
+    fn futex_owner_for_process(&self, pid: ProcessId) -> u64 {
+        if let Ok(process_index) = self.locate_process(pid) {
+            if let Some(pcb) = self.process_table[process_index].as_ref() {
+                if pcb.address_space_root != 0 {
+                    return pcb.address_space_root;
+                }
+            }
+        }
+        pid.raw()
+    }
+
+    fn futex_key(&self, pid: ProcessId, user_address: u64) -> KernelResult<FutexKey> {
+        let _ = user_out_ptr::<i32>(user_address)?;
+        let process_index = self.locate_process(pid)?;
+        let owner = self.process_table[process_index]
+            .as_ref()
+            .ok_or(KernelError::UnknownProcess)?
+            .address_space_root;
+        let owner = if owner == 0 { pid.raw() } else { owner };
+        Ok(FutexKey::new(owner, user_address))
+    }
+
+    /// Block `pid` on the 64-bit word at `addr` if it still holds `expected`.
+    /// `addr` must be 8-byte aligned and inside one of `pid`'s readable
+    /// mappings. Kernel mappings share one address in every process, so any
+    /// process may release the waiter with [`Self::futex_wake`]. The wait is
+    /// made by `pid`'s first live thread, whose syscall result reads
+    /// `TimedOut` if `timeout_ns` elapses first.
+    pub fn futex_wait(
+        &mut self,
+        pid: ProcessId,
+        addr: usize,
+        expected: u64,
+        timeout_ns: Option<u64>,
+    ) -> KernelResult<FutexWaitOutcome> {
+        self.locate_process(pid)?;
+        if !addr.is_multiple_of(core::mem::align_of::<u64>()) {
+            return Err(KernelError::InvalidArgument);
+        }
+        if !memory::mapping_covers(pid, addr, core::mem::size_of::<u64>()) {
+            return Err(KernelError::InvalidPointer);
+        }
+        let thread = self
+            .thread_table
+            .iter()
+            .flatten()
+            .find(|tcb| tcb.process == pid && tcb.state != ThreadState::Terminated)
+            .map(|tcb| tcb.id)
+            .ok_or(KernelError::UnknownThread)?;
+        // SAFETY: the word lies inside a live mapping owned by `pid`.
+        let observed = unsafe { core::ptr::read_volatile(addr as *const u64) };
+        if observed != expected {
+            return Ok(FutexWaitOutcome::ValueChanged);
+        }
+        let deadline =
+            timeout_ns.map(|timeout| KERNEL_TIME.now().as_nanos().saturating_add(timeout as u128));
+        self.futexes
+            .enqueue(
+                FutexKey::new(SHARED_FUTEX_OWNER, addr as u64),
+                thread,
+                deadline,
+            )
+            .map_err(|_| KernelError::AllocationFailed)?;
+        self.block_thread(thread)?;
+        Ok(FutexWaitOutcome::Blocked)
+    }
+
+    /// Wake up to `count` waiters blocked by [`Self::futex_wait`] on `addr`,
+    /// returning how many were woken.
+    pub fn futex_wake(&mut self, addr: usize, count: usize) -> KernelResult<usize> {
+        let mut woken_threads = [None; MAX_THREADS];
+        let woken = self.futexes.wake(
+            FutexKey::new(SHARED_FUTEX_OWNER, addr as u64),
+            count,
+            &mut woken_threads,
+        );
+        self.wake_futex_threads(&woken_threads, woken, 0)?;
+        Ok(woken)
+    }
+
+    fn set_thread_fs_base(&mut self, thread: ThreadId, base: u64) -> KernelResult<u64> {
+        let index = self.locate_thread(thread)?;
+        let tcb = self
+            .thread_table
+            .entry_mut(index)
+            .ok_or(KernelError::UnknownThread)?;
+        tcb.set_fs_base(base);
+        Ok(0)
+    }
+
+    fn set_thread_gs_base(&mut self, thread: ThreadId, base: u64) -> KernelResult<u64> {
+        let index = self.locate_thread(thread)?;
+        let tcb = self
+            .thread_table
+            .entry_mut(index)
+            .ok_or(KernelError::UnknownThread)?;
+        tcb.set_gs_base(base);
+        Ok(0)
+    }
+
+    fn thread_fs_base(&self, thread: ThreadId) -> KernelResult<u64> {
+        let index = self.locate_thread(thread)?;
+        self.thread_table[index]
+            .map(|tcb| tcb.fs_base)
+            .ok_or(KernelError::UnknownThread)
+    }
+
+    fn thread_gs_base(&self, thread: ThreadId) -> KernelResult<u64> {
+        let index = self.locate_thread(thread)?;
+        self.thread_table[index]
+            .map(|tcb| tcb.gs_base)
+            .ok_or(KernelError::UnknownThread)
+    }
+
+    fn block_thread(&mut self, thread: ThreadId) -> KernelResult<()> {
+        let index = self.locate_thread(thread)?;
+        let process = self.thread_table[index]
+            .as_ref()
+            .ok_or(KernelError::UnknownThread)?
+            .process;
+        if let Some(tcb) = self.thread_table.entry_mut(index) {
+            tcb.block();
+        }
+        match self
+            .mtss_scheduler
+            .block_thread(Self::mtss_thread_id(thread))
+        {
+            Ok(()) | Err(MtssError::InvalidThread) => {}
+            Err(err) => return Err(map_mtss_error(err)),
+        }
+        // A decision already taken for this thread must not dispatch it.
+        if self
+            .pending_mtss_decision
+            .is_some_and(|decision| decision.thread == thread)
+        {
+            self.pending_mtss_decision = None;
+        }
+        if !self.has_runnable_thread(process) {
+            if let Ok(process_index) = self.locate_process(process) {
+                if let Some(pcb) = self.process_table[process_index].as_ref() {
+                    if pcb.state != ProcessState::Zombie {
+                        let _ = self.set_process_blocked_via_mtss(process, process_index);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn wake_thread(&mut self, thread: ThreadId) -> KernelResult<()> {
+        let index = self.locate_thread(thread)?;
+        let mut process = None;
+        if let Some(tcb) = self.thread_table.entry_mut(index) {
+            if tcb.state == ThreadState::Blocked {
+                tcb.mark_ready_at(self.mtss_ticks);
+                process = Some(tcb.process);
+            }
+        }
+        if let Some(process) = process {
+            match self
+                .mtss_scheduler
+                .wake_thread(Self::mtss_thread_id(thread))
+            {
+                Ok(()) | Err(MtssError::InvalidThread) => {}
+                Err(err) => return Err(map_mtss_error(err)),
+            }
+            if let Ok(process_index) = self.locate_process(process) {
+                if let Some(pcb) = self.process_table[process_index].as_ref() {
+                    if pcb.state == ProcessState::Blocked {
+                        self.set_process_ready_via_mtss(process, process_index)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn has_runnable_thread(&self, pid: ProcessId) -> bool {
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(thread) = self.thread_table[idx] {
+                if thread.process == pid
+                    && (thread.state == ThreadState::Ready || thread.state == ThreadState::Running)
+                {
+                    return true;
+                }
+            }
+            idx += 1;
+        }
+        false
+    }
+
+    fn wake_futex_threads(
+        &mut self,
+        threads: &[Option<ThreadId>],
+        count: usize,
+        result: u64,
+    ) -> KernelResult<()> {
+        let mut idx = 0usize;
+        while idx < count && idx < threads.len() {
+            if let Some(thread) = threads[idx] {
+                self.write_thread_syscall_result(thread, result);
+                self.wake_thread(thread)?;
+            }
+            idx += 1;
+        }
+        Ok(())
+    }
+
+    /// Block `pid` unless it has pending work, re-checked here so work that
+    /// arrived after the caller last looked is not stranded. Returns whether
+    /// the process was blocked.
+    fn park_process_at_index(&mut self, pid: ProcessId, index: usize) -> bool {
+        if self.pending_work(pid).is_ok_and(|work| !work.is_empty()) {
+            return false;
+        }
+        self.block_process_at_index(pid, index);
+        true
+    }
+
+    fn block_process_at_index(&mut self, pid: ProcessId, index: usize) {
+        let _ = self.set_process_blocked_via_mtss(pid, index);
+        self.block_threads_for_process(pid);
+    }
+
+    fn block_threads_for_process(&mut self, pid: ProcessId) {
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(thread) = self.thread_table.entry_mut(idx) {
+                if thread.process == pid {
+                    thread.block();
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    /// Wake every blocked thread of `pid`. The scan starts at the process's
+    /// rotating wake cursor so the first thread queued changes between wakes.
+    fn make_threads_ready(&mut self, pid: ProcessId) -> KernelResult<()> {
+        let process_index = self.locate_process(pid)?;
+        let start = self.process_table[process_index]
+            .as_ref()
+            .map(|pcb| pcb.wake_cursor % Self::THREAD_CAPACITY)
+            .unwrap_or(0);
+        let mut first_woken = None;
+        let mut step = 0usize;
+        while step < Self::THREAD_CAPACITY {
+            let idx = (start + step) % Self::THREAD_CAPACITY;
+            if let Some(thread) = self.thread_table.entry_mut(idx) {
+                if thread.process == pid && thread.state == ThreadState::Blocked {
+                    thread.mark_ready_at(self.mtss_ticks);
+                    if self
+                        .mtss_scheduler
+                        .wake_thread(Self::mtss_thread_id(thread.id))
+                        .is_err()
+                    {
+                        thread.block();
+                        self.rollback_ready_threads(pid, start, step);
+                        return Err(KernelError::SchedulerFull);
+                    }
+                    #[cfg(feature = "latency-metrics")]
+                    {
+                        thread.woken_tick = Some(self.mtss_ticks);
+                    }
+                    first_woken.get_or_insert(idx);
+                }
+            }
+            step += 1;
+        }
+        if let (Some(first), Some(pcb)) = (first_woken, self.process_table[process_index].as_mut())
+        {
+            pcb.wake_cursor = (first + 1) % Self::THREAD_CAPACITY;
+        }
+        Ok(())
+    }
+
+    /// Undo the first `steps` slots of a wake scan that began at `start`.
+    fn rollback_ready_threads(&mut self, pid: ProcessId, start: usize, steps: usize) {
+        let mut step = 0usize;
+        while step < steps {
+            let idx = (start + step) % Self::THREAD_CAPACITY;
+            if let Some(thread) = self.thread_table.entry_mut(idx) {
+                if thread.process == pid && thread.state == ThreadState::Ready {
+                    thread.block();
+                    let _ = self
+                        .mtss_scheduler
+                        .block_thread(Self::mtss_thread_id(thread.id));
+                }
+            }
+            step += 1;
+        }
+    }
+
+    fn remove_threads_for_process(&mut self, pid: ProcessId) {
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(thread) = self.thread_table[idx] {
+                if thread.process == pid {
+                    let _ = self
+                        .mtss_scheduler
+                        .exit_thread(Self::mtss_thread_id(thread.id));
+                    self.futexes.remove_thread(thread.id);
+                    self.remove_thread_from_cores(thread.id);
+                    self.thread_table.remove_at(idx);
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    /// Terminate every thread still owned by `pid` after the process itself
+    /// has disappeared.
+    fn terminate_orphaned_threads(&mut self, pid: ProcessId) {
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(thread) = self.thread_table[idx] {
+                if thread.process == pid {
+                    self.terminate_thread(thread.id);
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    fn remove_thread_from_cores(&mut self, thread: ThreadId) {
+        let mut idx = 0usize;
+        while idx < MAX_CORES {
+            self.core_states[idx].evict(thread);
+            idx += 1;
+        }
+    }
+
+    fn create_thread(
+        &mut self,
+        pid: ProcessId,
+        entry_point: u64,
+        priority: ProcessPriority,
+    ) -> KernelResult<ThreadId> {
+        let slot = self
+            .find_free_thread_slot()
+            .ok_or(KernelError::ThreadTableFull)?;
+        let id = self.allocate_thread_id(slot);
+        let stack_pointer = self.allocate_stack_pointer(slot);
+        let mut tcb = ThreadControlBlock::new(id, pid, entry_point, priority, stack_pointer);
+        tcb.ready_tick = Some(self.mtss_ticks);
+        self.thread_table.insert_at(slot, tcb);
+        self.update_process_thread_count(pid, true);
+        Ok(id)
+    }
+
+    fn rollback_thread_creation(&mut self, thread: ThreadId) {
+        if let Ok(index) = self.locate_thread(thread) {
+            if let Some(tcb) = self.thread_table[index] {
+                self.futexes.remove_thread(thread);
+                self.thread_table.remove_at(index);
+                self.update_process_thread_count(tcb.process, false);
+            }
+        }
+    }
+
+    fn write_thread_syscall_result(&mut self, thread: ThreadId, result: u64) {
+        if let Ok(index) = self.locate_thread(thread) {
+            if let Some(tcb) = self.thread_table.entry_mut(index) {
+                tcb.write_syscall_result(result);
+            }
+        }
+    }
+
+    fn allocate_stack_pointer(&self, slot: usize) -> u64 {
+        const USER_STACK_BASE: u64 = 0x0000_7000_0000_0000;
+        const USER_STACK_SIZE: u64 = 0x20_000;
+        let stack_slot = slot as u64 + 1;
+        USER_STACK_BASE.saturating_add(stack_slot.saturating_mul(USER_STACK_SIZE))
+    }
+
+    fn update_process_thread_count(&mut self, pid: ProcessId, increment: bool) {
+        if let Ok(index) = self.locate_process(pid) {
+            if let Some(pcb) = self.process_table[index].as_mut() {
+                if increment {
+                    pcb.increment_thread_count();
+                } else {
+                    pcb.decrement_thread_count();
+                }
+            }
+        }
+    }
+
+    fn ensure_process_exists(&self, pid: ProcessId) -> KernelResult<()> {
+        self.locate_process(pid).map(|_| ())
+    }
+
+    fn handle_isolation_fault(&mut self, pid: ProcessId, reason: IsolationError) {
+        if let Some(dump) = self.capture_fault_dump(pid, reason) {
+            self.fault_dumps.push(dump);
+        }
+        self.terminate_process(pid);
+    }
+
+    fn capture_fault_dump(&self, pid: ProcessId, reason: IsolationError) -> Option<FaultDump> {
+        let index = self.locate_process(pid).ok()?;
+        let pcb = self.process_table[index]?;
+        let mut dump = FaultDump::new(
+            KERNEL_TIME.uptime_ticks(),
+            reason,
+            pcb,
+            self.security.task_domain(pid).ok(),
+        );
+        let mut idx = 0usize;
+        while idx < Self::THREAD_CAPACITY {
+            if let Some(tcb) = self.thread_table[idx] {
+                if tcb.process == pid {
+                    dump.record_thread(tcb);
+                }
+            }
+            idx += 1;
+        }
+        let mut offset = 0usize;
+        while let Some(message) = self.ipc_queues[index].peek_at(offset) {
+            dump.record_message(message);
+            offset += 1;
+        }
+        Some(dump)
+    }
+
+    /// Lowest vacant `process_table` index.
+    fn find_free_slot(&self) -> Option<usize> {
+        self.process_slots.first_clear()
+    }
+
+    fn install_process(&mut self, slot: usize, pcb: ProcessControlBlock<MAX_OPEN_FILES>) {
+        self.process_table[slot] = Some(pcb);
+        self.process_slots.set(slot);
+    }
+
+    fn vacate_process(&mut self, slot: usize) -> Option<ProcessControlBlock<MAX_OPEN_FILES>> {
+        self.process_slots.clear(slot);
+        self.process_table[slot].take()
+    }
+
+    fn find_free_thread_slot(&self) -> Option<usize> {
+        self.thread_table.find_free()
+    }
+
+    fn locate_process(&self, pid: ProcessId) -> KernelResult<usize> {
+        let mut idx = 0;
+        while idx < MAX_PROC {
+            if let Some(pcb) = &self.process_table[idx] {
+                if pcb.pid == pid {
+                    return Ok(idx);
+                }
+            }
+            idx += 1;
+        }
+        Err(KernelError::UnknownProcess)
+    }
+
+    /// Slot of a live thread. Thread ids carry their slot's generation, so an
+    /// id kept after the thread exits is rejected even once the slot is reused.
+    fn locate_thread(&self, thread: ThreadId) -> KernelResult<usize> {
+        self.thread_table
+            .index_of(Handle::from_raw(thread.raw()))
+            .ok_or(KernelError::UnknownThread)
+    }
+
+    fn generate_pid_seed() -> u64 {
+        clock::HARDWARE_CLOCK.now() ^ PID_SEED_SALT
+    }
+
+    /// The first process after bootstrap is PID 1; later PIDs are drawn from
+    /// an LCG so they cannot be predicted from earlier ones. Candidates keep
+    /// the high 31 bits of the state, which are the well-mixed ones and keep
+    /// PIDs positive as a `pid_t`.
+    fn allocate_pid(&mut self) -> KernelResult<ProcessId> {
+        if !self.init_pid_issued {
+            self.init_pid_issued = true;
+            let init = ProcessId::new(1);
+            if self.locate_process(init).is_err() {
+                self.exited_pids.forget(init);
+                return Ok(init);
+            }
+        }
+        let mut attempt = 0;
+        while attempt < PID_ALLOCATION_ATTEMPTS {
+            self.pid_seed = self
+                .pid_seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let pid = ProcessId::new((self.pid_seed >> 33) | 1);
+            if pid.raw() != 1 && self.locate_process(pid).is_err() {
+                self.exited_pids.forget(pid);
+                return Ok(pid);
+            }
+            self.pid_collision_count = self.pid_collision_count.saturating_add(1);
+            attempt += 1;
+        }
+        Err(KernelError::ProcessTableFull)
+    }
+
+    /// Generated PIDs that were discarded because they were already taken.
+    pub fn pid_collision_count(&self) -> u32 {
+        self.pid_collision_count
+    }
+
+    /// Remove and return the oldest dump taken when a process was killed for
+    /// an isolation fault. Requires `CAP_KERNEL`.
+    pub fn take_fault_dump(&mut self, actor: ProcessId) -> KernelResult<Option<FaultDump>> {
+        self.security
+            .authorize_kernel_control(actor)
+            .map_err(KernelError::SecurityViolation)?;
+        Ok(self.fault_dumps.take())
+    }
+
+    /// Keep at most `limit` fault dumps, between one and
+    /// `FAULT_DUMP_CAPACITY`. Requires `CAP_KERNEL`.
+    pub fn set_fault_dump_limit(&mut self, actor: ProcessId, limit: usize) -> KernelResult<()> {
+        self.security
+            .authorize_kernel_control(actor)
+            .map_err(KernelError::SecurityViolation)?;
+        self.fault_dumps
+            .set_limit(limit)
+            .map_err(|_| KernelError::InvalidArgument)
+    }
+
+    fn allocate_thread_id(&self, slot: usize) -> ThreadId {
+        ThreadId::new(self.thread_table.handle_for(slot).raw())
+    }
+
+    fn next_message_sequence(&mut self) -> u64 {
+        let seq = self.message_sequence;
+        self.message_sequence = self.message_sequence.wrapping_add(1);
+        seq
+    }
+
+    pub fn enumerate_devices(&self, out: &mut [DeviceDescriptor]) -> usize {
+        self.devices.enumerate(out)
+    }
+
+    pub fn device_info(&self, id: DeviceId) -> Option<DeviceDescriptor> {
+        self.devices.descriptor(id)
+    }
+
+    pub fn devices_of_kind(&self, kind: DeviceKind, out: &mut [DeviceDescriptor]) -> usize {
+        self.devices.find_by_kind(kind, out)
+    }
+
+    pub fn set_default_device(&mut self, kind: DeviceKind, id: DeviceId) -> KernelResult<()> {
+        self.devices
+            .set_default(kind, id)
+            .map_err(KernelError::DeviceFault)
+    }
+
+    /// Console the kernel log sink should write to.
+    pub fn default_console(&self) -> Option<DeviceId> {
+        self.devices.default_for(DeviceKind::SerialConsole)
+    }
+
+    /// Block device filesystems should mount when none is named explicitly.
+    pub fn default_storage(&self) -> Option<DeviceId> {
+        self.devices.default_for(DeviceKind::BlockStorage)
+    }
+
+    pub fn device_read(
+        &self,
+        pid: ProcessId,
+        id: DeviceId,
+        buffer: &mut [u8],
+    ) -> KernelResult<usize> {
+        self.authorize_device_read(pid, id)?;
+        self.devices
+            .read(id, pid, &self.security, buffer)
+            .map_err(KernelError::DeviceFault)
+    }
+
+    /// Read from `id`, waiting up to `ticks` for input. When nothing is
+    /// buffered the caller is blocked and `Pending` is returned; every tick
+    /// wakes it to call again with the same arguments until data arrives or
+    /// the timeout runs out.
+    pub fn device_read_timeout(
+        &mut self,
+        pid: ProcessId,
+        id: DeviceId,
+        buffer: &mut [u8],
+        ticks: u64,
+    ) -> KernelResult<DeviceReadOutcome> {
+        self.authorize_device_read(pid, id)?;
+        let index = self.locate_process(pid)?;
+        if let Some(count) = self
+            .devices
+            .try_read(id, pid, &self.security, buffer)
+            .map_err(KernelError::DeviceFault)?
+        {
+            self.device_reads[index] = None;
+            return Ok(DeviceReadOutcome::Ready(count));
+        }
+
+        let remaining_ticks = match self.device_reads[index] {
+            Some(pending) if pending.device == id => pending.remaining_ticks,
+            _ => ticks,
+        };
+        if remaining_ticks == 0 {
+            self.device_reads[index] = None;
+            return Ok(DeviceReadOutcome::TimedOut);
+        }
+        self.device_reads[index] = Some(PendingDeviceRead {
+            device: id,
+            remaining_ticks,
+        });
+        self.block_process_at_index(pid, index);
+        Ok(DeviceReadOutcome::Pending)
+    }
+
+    fn authorize_device_read(&self, pid: ProcessId, id: DeviceId) -> KernelResult<()> {
+        let descriptor = self
+            .devices
+            .descriptor(id)
+            .ok_or(KernelError::DeviceNotFound)?;
+
+        self.security
+            .authorize_device_access(
+                pid,
+                CapabilityObject::PciDevice(descriptor.id.raw() as u64),
+                CapabilityRight::Read,
+                descriptor.security,
+            )
+            .map_err(KernelError::SecurityViolation)?;
+        if !self.service_registry.claimed_by(pid, id) {
+            return Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Charge a tick to every pending bounded device read and wake its
+    /// process so the read is retried.
+    fn retry_device_reads(&mut self) {
+        let mut idx = 0;
+        while idx < MAX_PROC {
+            if let Some(pending) = self.device_reads[idx].as_mut() {
+                pending.remaining_ticks = pending.remaining_ticks.saturating_sub(1);
+                if let Some(pid) = self.process_table[idx].as_ref().map(|pcb| pcb.pid) {
+                    let _ = self.wake_process_for_timeout(pid);
+                }
+            }
+            idx += 1;
+        }
+    }
+
+    pub fn device_write(&self, pid: ProcessId, id: DeviceId, data: &[u8]) -> KernelResult<usize> {
+        let descriptor = self
+            .devices
+            .descriptor(id)
+            .ok_or(KernelError::DeviceNotFound)?;
+
+        self.security
+            .authorize_device_access(
+                pid,
+                CapabilityObject::PciDevice(descriptor.id.raw() as u64),
+                CapabilityRight::Write,
+                descriptor.security,
+            )
+            .map_err(KernelError::SecurityViolation)?;
+        if !self.service_registry.claimed_by(pid, id) {
+            return Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation,
+            ));
+        }
+
+        self.devices
+            .write(id, pid, &self.security, data)
+            .map_err(KernelError::DeviceFault)
+    }
+}
+
+fn exec_vector_metadata(ptr: u64, max_entries: usize) -> KernelResult<ExecVectorMetadata> {
+    if ptr == 0 {
+        return Ok(ExecVectorMetadata::empty());
+    }
+
+    let entries = user_slice_typed::<u64>(ptr, max_entries + 1)?;
+    let mut count = 0usize;
+    while count < entries.len() {
+        if entries[count] == 0 {
+            return Ok(ExecVectorMetadata::new(ptr, count, false));
+        }
+        count += 1;
+    }
+
+    Ok(ExecVectorMetadata::new(ptr, max_entries, true))
+}
+
+fn signed_exec_manifest_for_path(
+    path: &str,
+) -> (Option<ExecServiceDaemon>, Option<ExecSignatureMetadata>) {
+    match path {
+        "/bin/displayd" | "/sbin/displayd" | "/displayd" => (
+            Some(ExecServiceDaemon::Display),
+            Some(ExecSignatureMetadata::new(
+                "mirage-service-root",
+                0x444953504c415944,
+            )),
+        ),
+        "/bin/networkd" | "/sbin/networkd" | "/networkd" => (
+            Some(ExecServiceDaemon::Network),
+            Some(ExecSignatureMetadata::new(
+                "mirage-service-root",
+                0x4e4554574f524b44,
+            )),
+        ),
+        "/bin/inputd" | "/sbin/inputd" | "/inputd" => (
+            Some(ExecServiceDaemon::Input),
+            Some(ExecSignatureMetadata::new(
+                "mirage-service-root",
+                0x494e50555444414d,
+            )),
+        ),
+        "/bin/l2-driverd" | "/sbin/l2-driverd" | "/l2-driverd" => (
+            Some(ExecServiceDaemon::L2Driver),
+            Some(ExecSignatureMetadata::new(
+                "mirage-driver-root",
+                0x4c32445249564552,
+            )),
+        ),
+        _ => (None, None),
+    }
+}
+
+fn map_file_table_error(error: FileTableError) -> KernelError {
+    match error {
+        FileTableError::Full => KernelError::FileTableFull,
+        FileTableError::InvalidDescriptor => KernelError::Filesystem(VfsError::InvalidHandle),
+    }
+}
+
+fn map_process_file_table_error(error: ProcessFileTableError) -> KernelError {
+    match error {
+        ProcessFileTableError::Full => KernelError::FileTableFull,
+        ProcessFileTableError::InvalidDescriptor => {
+            KernelError::Filesystem(VfsError::InvalidHandle)
+        }
+    }
+}
+
+fn map_path_error(error: PathError) -> KernelError {
+    KernelError::Filesystem(VfsError::InvalidPath(error))
+}
+
+fn map_service_registry_error(error: ServiceRegistryError) -> KernelError {
+    match error {
+        ServiceRegistryError::Full => KernelError::ProcessTableFull,
+        ServiceRegistryError::AlreadyRegistered
+        | ServiceRegistryError::DeviceAlreadyClaimed
+        | ServiceRegistryError::DeviceClassMismatch
+        | ServiceRegistryError::NotOwner => KernelError::InvalidArgument,
+        ServiceRegistryError::NotRegistered => KernelError::UnknownProcess,
+        ServiceRegistryError::DeviceNotClaimed => KernelError::DeviceNotFound,
+    }
+}
+
+fn decode_registry_service_id(raw: u64) -> KernelResult<RegistryServiceId> {
+    RegistryServiceId::from_raw(raw).ok_or(KernelError::InvalidArgument)
+}
+
+fn validate_tls_base(base: u64) -> KernelResult<()> {
+    if base < USER_CANONICAL_LIMIT {
+        Ok(())
+    } else {
+        Err(KernelError::InvalidArgument)
+    }
+}
+
+fn active_user_root() -> u64 {
+    x86_64::paging::current_address_space_root()
+}
+
+fn validate_user_range(ptr: u64, len: usize) -> KernelResult<()> {
+    validate_user_access(ptr, len, false)
+}
+
+fn validate_user_access(ptr: u64, len: usize, write: bool) -> KernelResult<()> {
+    if len == 0 {
+        return Ok(());
+    }
+    if ptr == 0 {
+        return Err(KernelError::InvalidPointer);
+    }
+    ptr.checked_add(len as u64)
+        .filter(|end| *end >= ptr && *end <= USER_CANONICAL_LIMIT)
+        .ok_or(KernelError::InvalidPointer)?;
+    if !x86_64::paging::installed() || active_user_root() == 0 {
+        return Ok(());
+    }
+    if !memory::validate_user_range(active_user_root(), ptr, len, write) {
+        return Err(KernelError::InvalidPointer);
+    }
+    Ok(())
+}
+
+fn user_translated_ptr(ptr: u64, len: usize, write: bool) -> KernelResult<NonNull<u8>> {
+    validate_user_access(ptr, len, write)?;
+    if len == 0 {
+        return Ok(NonNull::dangling());
+    }
+    if !x86_64::paging::installed() || active_user_root() == 0 {
+        return NonNull::new(ptr as *mut u8).ok_or(KernelError::InvalidPointer);
+    }
+    memory::active_translated_slice(active_user_root(), ptr, len, write)
+        .ok_or(KernelError::InvalidPointer)
+}
+
+fn user_slice(ptr: u64, len: usize) -> KernelResult<&'static [u8]> {
+    let translated = user_translated_ptr(ptr, len, false)?;
+    if len == 0 {
+        Ok(&[])
+    } else {
+        Ok(unsafe { core::slice::from_raw_parts(translated.as_ptr(), len) })
+    }
+}
+
+fn user_slice_mut(ptr: u64, len: usize) -> KernelResult<&'static mut [u8]> {
+    let translated = user_translated_ptr(ptr, len, true)?;
+    if len == 0 {
+        Ok(&mut [])
+    } else {
+        Ok(unsafe { core::slice::from_raw_parts_mut(translated.as_ptr(), len) })
+    }
+}
+
+fn user_slice_typed<T>(ptr: u64, count: usize) -> KernelResult<&'static [T]> {
+    let byte_len = count
+        .checked_mul(core::mem::size_of::<T>())
+        .ok_or(KernelError::InvalidPointer)?;
+    if count == 0 {
+        return Ok(&[]);
+    }
+    if !(ptr as *const T).is_aligned() {
+        return Err(KernelError::InvalidPointer);
+    }
+    let translated = user_translated_ptr(ptr, byte_len, false)?;
+    if !(translated.as_ptr() as *const T).is_aligned() {
+        return Err(KernelError::InvalidPointer);
+    }
+    Ok(unsafe { core::slice::from_raw_parts(translated.as_ptr() as *const T, count) })
+}
+
+fn user_slice_mut_typed<T>(ptr: u64, count: usize) -> KernelResult<&'static mut [T]> {
+    let byte_len = count
+        .checked_mul(core::mem::size_of::<T>())
+        .ok_or(KernelError::InvalidPointer)?;
+    if count == 0 {
+        return Ok(&mut []);
+    }
+    if !(ptr as *mut T).is_aligned() {
+        return Err(KernelError::InvalidPointer);
+    }
+    let translated = user_translated_ptr(ptr, byte_len, true)?;
+    if !(translated.as_ptr() as *mut T).is_aligned() {
+        return Err(KernelError::InvalidPointer);
+    }
+    Ok(unsafe { core::slice::from_raw_parts_mut(translated.as_ptr() as *mut T, count) })
+}
+
+fn user_out_ptr<T>(ptr: u64) -> KernelResult<*mut T> {
+    if !(ptr as *mut T).is_aligned() {
+        return Err(KernelError::InvalidPointer);
+    }
+    let translated = user_translated_ptr(ptr, core::mem::size_of::<T>(), true)?;
+    if !(translated.as_ptr() as *mut T).is_aligned() {
+        return Err(KernelError::InvalidPointer);
+    }
+    Ok(translated.as_ptr() as *mut T)
+}
+
+fn read_user_value<T: Copy>(ptr: u64) -> KernelResult<T> {
+    if !(ptr as *const T).is_aligned() {
+        return Err(KernelError::InvalidPointer);
+    }
+    let mut value = core::mem::MaybeUninit::<T>::uninit();
+    let out = unsafe {
+        core::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, core::mem::size_of::<T>())
+    };
+    if !x86_64::paging::installed() || active_user_root() == 0 {
+        unsafe { core::ptr::copy_nonoverlapping(ptr as *const u8, out.as_mut_ptr(), out.len()) };
+    } else if !memory::copy_from_user(active_user_root(), ptr, out) {
+        return Err(KernelError::InvalidPointer);
+    }
+    Ok(unsafe { value.assume_init() })
+}
+
+fn write_user_value<T: Copy>(ptr: u64, value: T) -> KernelResult<()> {
+    if !(ptr as *const T).is_aligned() {
+        return Err(KernelError::InvalidPointer);
+    }
+    let input = unsafe {
+        core::slice::from_raw_parts((&value as *const T) as *const u8, core::mem::size_of::<T>())
+    };
+    if !x86_64::paging::installed() || active_user_root() == 0 {
+        unsafe { core::ptr::copy_nonoverlapping(input.as_ptr(), ptr as *mut u8, input.len()) };
+    } else if !memory::copy_to_user(active_user_root(), ptr, input) {
+        return Err(KernelError::InvalidPointer);
+    }
+    Ok(())
+}
+
+fn timespec_to_nanos(timespec: MirageTimespec) -> KernelResult<u128> {
+    if timespec.tv_sec < 0 || timespec.tv_nsec < 0 || timespec.tv_nsec >= 1_000_000_000 {
+        return Err(KernelError::InvalidArgument);
+    }
+    let seconds = timespec.tv_sec as u128;
+    let nanos = timespec.tv_nsec as u128;
+    seconds
+        .checked_mul(1_000_000_000)
+        .and_then(|value| value.checked_add(nanos))
+        .ok_or(KernelError::InvalidArgument)
+}
+
+fn nanos_to_timespec(nanos: u128) -> MirageTimespec {
+    MirageTimespec {
+        tv_sec: (nanos / 1_000_000_000) as i64,
+        tv_nsec: (nanos % 1_000_000_000) as i64,
+    }
+}
+
+fn descriptor_flags_from_raw(flags: u64) -> DescriptorFlags {
+    if flags & (O_CLOEXEC_RAW | EFD_CLOEXEC) != 0 {
+        DescriptorFlags::CLOSE_ON_EXEC
+    } else {
+        DescriptorFlags::EMPTY
+    }
+}
+
+fn timer_to_itimerspec(
+    timer: crate::kernel::timer::ProcessTimer,
+    now_ns: u128,
+) -> MirageItimerspec {
+    let remaining = if timer.armed {
+        timer.wake_deadline_ns.saturating_sub(now_ns)
+    } else {
+        0
+    };
+    MirageItimerspec {
+        it_interval: nanos_to_timespec(timer.interval_ns),
+        it_value: nanos_to_timespec(remaining),
+    }
+}
+
+fn map_timer_error(error: TimerError) -> KernelError {
+    match error {
+        TimerError::Full => KernelError::AllocationFailed,
+        TimerError::InvalidTimer => KernelError::InvalidArgument,
+    }
+}
+
+#[cfg(test)]
+fn is_test_spawn_path(path: &str) -> bool {
+    matches!(path, "/spider-rt/sbin/spider-rsd" | "/usr/bin/m1-terminal")
+}
+
+fn copy_user_path_with_len(ptr: u64, len: usize) -> KernelResult<KernelPathBuf> {
+    if len == 0 || len > MAX_PATH_BYTES {
+        return Err(KernelError::Filesystem(VfsError::InvalidPath(
+            PathError::TooLong,
+        )));
+    }
+    validate_user_range(ptr, len)?;
+    let mut bytes = [0u8; MAX_PATH_BYTES];
+    if !x86_64::paging::installed() || active_user_root() == 0 {
+        unsafe { core::ptr::copy_nonoverlapping(ptr as *const u8, bytes.as_mut_ptr(), len) };
+    } else if !memory::copy_from_user(active_user_root(), ptr, &mut bytes[..len]) {
+        return Err(KernelError::InvalidPointer);
+    }
+    if bytes[..len].contains(&0) {
+        return Err(KernelError::Filesystem(VfsError::InvalidPath(
+            PathError::InvalidByte,
+        )));
+    }
+    let raw = core::str::from_utf8(&bytes[..len])
+        .map_err(|_| KernelError::Filesystem(VfsError::InvalidPath(PathError::InvalidByte)))?;
+    KernelPathBuf::from_str(raw)
+}
+
+fn exec_vector_metadata_with_count(
+    base: u64,
+    count: usize,
+    max: usize,
+) -> KernelResult<ExecVectorMetadata> {
+    if count > max {
+        return Err(KernelError::InvalidArgument);
+    }
+    if count == 0 {
+        return Ok(ExecVectorMetadata::empty());
+    }
+    let bytes = count
+        .checked_mul(core::mem::size_of::<u64>())
+        .ok_or(KernelError::InvalidArgument)?;
+    validate_user_range(base, bytes)?;
+    Ok(ExecVectorMetadata::new(base, count, false))
+}
+
+fn user_cstr(ptr: u64) -> KernelResult<&'static [u8]> {
+    validate_user_range(ptr, 1)?;
+    let mut len = 0usize;
+    while len <= MAX_PATH_BYTES {
+        let mut byte = [0u8; 1];
+        if !x86_64::paging::installed() || active_user_root() == 0 {
+            byte[0] = unsafe { ((ptr + len as u64) as *const u8).read() };
+        } else if !memory::copy_from_user(active_user_root(), ptr + len as u64, &mut byte) {
+            return Err(KernelError::InvalidPointer);
+        }
+        if byte[0] == 0 {
+            return user_slice(ptr, len);
+        }
+        len += 1;
+    }
+    Err(KernelError::Filesystem(VfsError::InvalidPath(
+        PathError::TooLong,
+    )))
+}
+
+fn encode_syscall_error(error: KernelError) -> u64 {
+    MIRAGE_SYSCALL_ERROR_BIT | syscall_error_code(error).raw()
+}
+
+fn syscall_error_code(error: KernelError) -> SyscallErrorCode {
+    match error {
+        KernelError::ProcessTableFull => SyscallErrorCode::ProcessTableFull,
+        KernelError::SchedulerFull => SyscallErrorCode::SchedulerFull,
+        KernelError::UnknownProcess | KernelError::ReceiverTerminated => {
+            SyscallErrorCode::NoSuchProcess
+        }
+        KernelError::UnknownThread => SyscallErrorCode::NoSuchThread,
+        KernelError::ThreadTableFull => SyscallErrorCode::ThreadTableFull,
+        KernelError::MessageQueueFull
+        | KernelError::NoCredits
+        | KernelError::ClassQuotaExceeded => SyscallErrorCode::QueueFull,
+        KernelError::MessageQueueEmpty => SyscallErrorCode::QueueEmpty,
+        KernelError::MessageCorrupted => SyscallErrorCode::DeviceFault,
+        KernelError::SecurityViolation(reason) => isolation_syscall_error_code(reason),
+        KernelError::SecurityTableFull => SyscallErrorCode::ProcessTableFull,
+        KernelError::IsolationFault(reason) => isolation_syscall_error_code(reason),
+        KernelError::DeviceNotFound => SyscallErrorCode::NoSuchDevice,
+        KernelError::DeviceFault(_) => SyscallErrorCode::DeviceFault,
+        KernelError::InvalidSyscall => SyscallErrorCode::InvalidSyscall,
+        KernelError::InvalidArgument => SyscallErrorCode::InvalidArgument,
+        KernelError::InvalidPointer => SyscallErrorCode::BadAddress,
+        KernelError::AllocationFailed => SyscallErrorCode::OutOfMemory,
+        KernelError::FileTableFull => SyscallErrorCode::OutOfMemory,
+        KernelError::Filesystem(error) => vfs_syscall_error_code(error),
+        KernelError::TimedOut => SyscallErrorCode::TimedOut,
+        KernelError::Loader(_) => SyscallErrorCode::InvalidArgument,
+    }
+}
+
+fn vfs_syscall_error_code(error: VfsError) -> SyscallErrorCode {
+    syscall_error_code_from_vfs(error)
+}
+
+fn isolation_syscall_error_code(reason: IsolationError) -> SyscallErrorCode {
+    match reason {
+        IsolationError::UnknownTask => SyscallErrorCode::NoSuchProcess,
+        IsolationError::PolicyViolation | IsolationError::CapabilityMissing => {
+            SyscallErrorCode::PermissionDenied
+        }
+        IsolationError::CapabilityTableFull => SyscallErrorCode::OutOfMemory,
+        IsolationError::DomainTableFull => SyscallErrorCode::ProcessTableFull,
+        IsolationError::StackOverflow => SyscallErrorCode::BadAddress,
+    }
+}
+
+fn decode_priority(raw: u64) -> KernelResult<ProcessPriority> {
+    match raw {
+        0 => Ok(ProcessPriority::Critical),
+        1 => Ok(ProcessPriority::High),
+        2 => Ok(ProcessPriority::Normal),
+        3 => Ok(ProcessPriority::Low),
+        _ => Err(KernelError::InvalidArgument),
+    }
+}
+
+fn decode_credentials(raw: u64) -> KernelResult<Credentials> {
+    match raw {
+        0 => Ok(Credentials::user()),
+        1 => Ok(Credentials::system()),
+        _ => Err(KernelError::InvalidArgument),
+    }
+}
+
+fn decode_security_class(raw: u64) -> KernelResult<SecurityClass> {
+    match raw {
+        0 => Ok(SecurityClass::Public),
+        1 => Ok(SecurityClass::Internal),
+        2 => Ok(SecurityClass::Confidential),
+        3 => Ok(SecurityClass::System),
+        _ => Err(KernelError::InvalidArgument),
+    }
+}
+
+#[cfg(all(test, not(feature = "qfs-std")))]
+mod tests {
+    use super::*;
+    use crate::kernel::ipc::SpinStats;
+    use crate::kernel::memory::{PROT_EXECUTE, PROT_READ, PROT_WRITE};
+    use crate::libc;
+    use crate::subkernel::{CapabilitySet, IsolationLevel, SecurityLabel};
+    use std::boxed::Box;
+
+    #[test]
+    fn boot_device_registry_has_headroom_for_real_and_core_drivers() {
+        // Architecture bring-up can discover serial, PS/2, USB-HID, ACPI EC,
+        // Limine module block media, and AHCI SATA/ATAPI drivers before the
+        // core logical devices are registered during BootInfoApplied.
+        const WORST_CASE_EARLY_REAL_DRIVERS: usize = 7;
+        const CORE_LOGICAL_DRIVERS: usize = 8;
+        assert!(MAX_DEVICES >= WORST_CASE_EARLY_REAL_DRIVERS + CORE_LOGICAL_DRIVERS);
+    }
+
+    #[test]
+    fn boot_info_applied_device_error_fatality_is_explicit() {
+        assert!(DriverError::RegistryFull.is_fatal_during_boot_info_applied());
+        assert!(DriverError::BufferTooSmall.is_fatal_during_boot_info_applied());
+        assert!(!DriverError::Busy.is_fatal_during_boot_info_applied());
+        assert!(!DriverError::Unsupported.is_fatal_during_boot_info_applied());
+        assert!(!DriverError::NotFound.is_fatal_during_boot_info_applied());
+    }
+
+    /// Boxed so that tests holding several kernels fit the default test
+    /// thread stack.
+    fn boot_kernel() -> Box<Kernel<16, 4>> {
+        let mut kernel = Box::new(Kernel::<16, 4>::new());
+        kernel.bootstrap();
+        kernel
+    }
+
+    fn process_state(kernel: &Kernel<16, 4>, pid: ProcessId) -> ProcessState {
+        let index = kernel.locate_process(pid).unwrap();
+        kernel.process_table[index].unwrap().state
+    }
+
+    /// Teardown check for tests that drive process and thread lifecycles.
+    fn assert_kernel_consistent(kernel: &Kernel<16, 4>) {
+        kernel.assert_security_invariants().unwrap();
+        kernel.consistency_check().unwrap();
+    }
+
+    fn first_thread(kernel: &Kernel<16, 4>, pid: ProcessId) -> ThreadId {
+        let mut idx = 0usize;
+        while idx < Kernel::<16, 4>::THREAD_CAPACITY {
+            if let Some(thread) = kernel.thread_table[idx] {
+                if thread.process == pid {
+                    return thread.id;
+                }
+            }
+            idx += 1;
+        }
+        panic!("process has no thread")
+    }
+
+    fn process_threads_blocked(kernel: &Kernel<16, 4>, pid: ProcessId) -> bool {
+        let mut saw_thread = false;
+        let mut idx = 0usize;
+        while idx < Kernel::<16, 4>::THREAD_CAPACITY {
+            if let Some(thread) = kernel.thread_table[idx] {
+                if thread.process == pid {
+                    saw_thread = true;
+                    if thread.state != ThreadState::Blocked {
+                        return false;
+                    }
+                }
+            }
+            idx += 1;
+        }
+        saw_thread
+    }
+
+    fn elf_image(entry: u64, ph_flags: u32) -> [u8; 128] {
+        let mut bytes = [0u8; 128];
+        bytes[0..4].copy_from_slice(b"\x7fELF");
+        bytes[4] = 2;
+        bytes[5] = 1;
+        bytes[6] = 1;
+        bytes[16..18].copy_from_slice(&2u16.to_le_bytes());
+        bytes[18..20].copy_from_slice(&62u16.to_le_bytes());
+        bytes[24..32].copy_from_slice(&entry.to_le_bytes());
+        bytes[32..40].copy_from_slice(&64u64.to_le_bytes());
+        bytes[54..56].copy_from_slice(&56u16.to_le_bytes());
+        bytes[56..58].copy_from_slice(&1u16.to_le_bytes());
+        let ph = 64usize;
+        bytes[ph..ph + 4].copy_from_slice(&1u32.to_le_bytes());
+        bytes[ph + 4..ph + 8].copy_from_slice(&ph_flags.to_le_bytes());
+        bytes[ph + 8..ph + 16].copy_from_slice(&120u64.to_le_bytes());
+        bytes[ph + 16..ph + 24].copy_from_slice(&0x400078u64.to_le_bytes());
+        bytes[ph + 32..ph + 40].copy_from_slice(&1u64.to_le_bytes());
+        bytes[ph + 40..ph + 48].copy_from_slice(&1u64.to_le_bytes());
+        bytes[ph + 48..ph + 56].copy_from_slice(&8u64.to_le_bytes());
+        bytes[120] = 0xc3;
+        bytes
+    }
+
+    fn use_in_memory_qfs(kernel: &mut Kernel<16, 4>) {
+        kernel.root_fs = RootFileSystem::Qfs(QfsFileSystem::new(false));
+    }
+
+    const fn mtss_report(
+        core_ready: bool,
+        scheduler_ready: bool,
+        timer_ready: bool,
+        preemption_ready: bool,
+        idle_ready: bool,
+        api_ready: bool,
+    ) -> MtssInitReport {
+        MtssInitReport {
+            core_ready,
+            scheduler_ready,
+            timer_ready,
+            preemption_ready,
+            idle_ready,
+            api_ready,
+        }
+    }
+
+    #[test]
+    fn mtss_readiness_offline_blocks_all_task_paths() {
+        let report = mtss_report(false, false, false, false, false, false);
+
+        assert_eq!(report.readiness(), MtssReadiness::Offline);
+        assert!(!report.core_ready());
+        assert!(!report.scheduler_ready());
+        assert!(!report.can_create_tasks());
+        assert!(!report.can_mark_runnable());
+        assert!(!report.can_cooperative_schedule());
+        assert!(!report.preemption_ready());
+        assert!(!report.is_fully_online());
+        assert!(!report.pid1_handoff_allowed(false));
+        assert_eq!(
+            report.pid1_handoff_blocker(false),
+            Some("MTSS core not ready")
+        );
+    }
+
+    #[test]
+    fn mtss_readiness_core_ready_still_blocks_scheduler_paths() {
+        let report = mtss_report(true, false, false, false, false, true);
+
+        assert_eq!(report.readiness(), MtssReadiness::CoreReady);
+        assert!(report.core_ready());
+        assert!(!report.scheduler_ready());
+        assert!(report.can_create_tasks());
+        assert!(!report.can_mark_runnable());
+        assert!(!report.can_cooperative_schedule());
+        assert!(!report.is_fully_online());
+        assert_eq!(
+            report.pid1_handoff_blocker(false),
+            Some("MTSS scheduler not ready")
+        );
+    }
+
+    #[test]
+    fn mtss_readiness_scheduler_without_idle_or_api_is_failed() {
+        let no_idle = mtss_report(true, true, false, false, false, true);
+        let no_api = mtss_report(true, true, false, false, true, false);
+
+        assert_eq!(
+            no_idle.readiness(),
+            MtssReadiness::Failed {
+                reason: "idle task unavailable"
+            }
+        );
+        assert!(!no_idle.can_mark_runnable());
+        assert!(!no_idle.can_cooperative_schedule());
+        assert_eq!(
+            no_idle.pid1_handoff_blocker(false),
+            Some("idle task unavailable")
+        );
+
+        assert_eq!(
+            no_api.readiness(),
+            MtssReadiness::Failed {
+                reason: "MTSS API unavailable"
+            }
+        );
+        assert!(!no_api.can_create_tasks());
+        assert!(!no_api.can_mark_runnable());
+        assert_eq!(
+            no_api.pid1_handoff_blocker(false),
+            Some("MTSS task API unavailable")
+        );
+    }
+
+    #[test]
+    fn mtss_cooperative_schedule_does_not_require_timer_or_preemption() {
+        let report = mtss_report(true, true, false, false, true, true);
+
+        assert_eq!(
+            report.readiness(),
+            MtssReadiness::Degraded {
+                reason: "timer backend pending"
+            }
+        );
+        assert!(report.can_create_tasks());
+        assert!(report.can_mark_runnable());
+        assert!(report.can_cooperative_schedule());
+        assert!(!report.preemption_ready());
+        assert!(!report.is_fully_online());
+        assert!(report.pid1_handoff_allowed(false));
+        assert_eq!(report.pid1_handoff_blocker(false), None);
+        assert!(!report.pid1_handoff_allowed(true));
+        assert_eq!(
+            report.pid1_handoff_blocker(true),
+            Some("policy requires timer/preemption before userspace")
+        );
+    }
+
+    #[test]
+    fn mtss_full_online_requires_timer_and_preemption() {
+        let missing_preemption = mtss_report(true, true, true, false, true, true);
+        let online = mtss_report(true, true, true, true, true, true);
+
+        assert_eq!(
+            missing_preemption.readiness(),
+            MtssReadiness::Degraded {
+                reason: "preemption backend pending"
+            }
+        );
+        assert!(missing_preemption.can_cooperative_schedule());
+        assert!(!missing_preemption.preemption_ready());
+        assert!(!missing_preemption.is_fully_online());
+        assert!(!missing_preemption.required_components_ready());
+
+        assert_eq!(online.readiness(), MtssReadiness::Online);
+        assert!(online.preemption_ready());
+        assert!(online.is_fully_online());
+        assert!(online.required_components_ready());
+        assert!(online.pid1_handoff_allowed(true));
+    }
+
+    #[test]
+    fn kernel_mtss_init_allows_pid1_handoff_when_preemption_not_required() {
+        let mut kernel = boot_kernel();
+        let report = kernel.kernel_mtss_init().unwrap();
+
+        assert!(report.core_ready());
+        assert!(report.scheduler_ready());
+        assert!(report.can_create_tasks());
+        assert!(report.can_mark_runnable());
+        assert!(report.can_cooperative_schedule());
+        assert!(!report.preemption_ready());
+        assert!(!report.is_fully_online());
+        assert!(report.pid1_handoff_allowed(false));
+        assert_eq!(report.pid1_handoff_blocker(false), None);
+        assert!(!report.pid1_handoff_allowed(true));
+    }
+
+    fn create_root_file(
+        kernel: &mut Kernel<16, 4>,
+        name: &str,
+        mode: crate::kernel::fs::Permissions,
+        data: &[u8],
+    ) {
+        match &kernel.root_fs {
+            RootFileSystem::Qfs(fs) => {
+                fs.create_test_file(fs.root_inode(), name, mode, data, FsCredentials::kernel())
+                    .unwrap();
+            }
+            RootFileSystem::Ext4(_) => panic!("tests expect qfs root filesystem"),
+        }
+    }
+
+    fn clear_process_address_space(kernel: &mut Kernel<16, 4>, pid: ProcessId) {
+        let index = kernel.locate_process(pid).unwrap();
+        let old = kernel.process_table[index].unwrap().address_space_root;
+        if old != 0 {
+            memory::destroy_user_address_space(old);
+            kernel.process_table[index]
+                .as_mut()
+                .unwrap()
+                .address_space_root = 0;
+        }
+    }
+
+    fn load_exec_for_path(
+        kernel: &mut Kernel<16, 4>,
+        pid: ProcessId,
+        raw: &str,
+    ) -> KernelResult<crate::kernel::process::ExecImageMetadata> {
+        let resolved = KernelPathBuf::from_str(raw)?;
+        let path = resolved.as_path()?;
+        let stat = kernel.root_fs.stat(path).map_err(KernelError::Filesystem)?;
+        kernel.load_exec_image(
+            pid,
+            &resolved,
+            stat,
+            ExecVectorMetadata::empty(),
+            ExecVectorMetadata::empty(),
+        )
+    }
+
+    #[test]
+    fn load_exec_image_rejects_invalid_elf() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        use_in_memory_qfs(&mut kernel);
+        clear_process_address_space(&mut kernel, pid);
+        create_root_file(
+            &mut kernel,
+            "bad-elf",
+            crate::kernel::fs::Permissions::executable(),
+            b"not-elf",
+        );
+
+        assert!(matches!(
+            load_exec_for_path(&mut kernel, pid, "/bad-elf"),
+            Err(KernelError::InvalidArgument)
+        ));
+    }
+
+    #[test]
+    fn load_exec_image_rejects_non_executable_path_and_unmapped_entry() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        use_in_memory_qfs(&mut kernel);
+        clear_process_address_space(&mut kernel, pid);
+        let valid = elf_image(0x400078, 0x5);
+        create_root_file(
+            &mut kernel,
+            "not-exec",
+            crate::kernel::fs::Permissions::read_write(),
+            &valid,
+        );
+        let unmapped = elf_image(0x400078, 0x4);
+        create_root_file(
+            &mut kernel,
+            "unmapped-entry",
+            crate::kernel::fs::Permissions::executable(),
+            &unmapped,
+        );
+
+        assert!(matches!(
+            load_exec_for_path(&mut kernel, pid, "/not-exec"),
+            Err(KernelError::Filesystem(VfsError::PermissionDenied))
+        ));
+        assert!(matches!(
+            load_exec_for_path(&mut kernel, pid, "/unmapped-entry"),
+            Err(_)
+        ));
+    }
+
+    #[test]
+    fn exec_task_success_preserves_pid_and_closes_cloexec_descriptors() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        use_in_memory_qfs(&mut kernel);
+        clear_process_address_space(&mut kernel, pid);
+        let thread = first_thread(&kernel, pid);
+        let image_bytes = elf_image(0x400078, 0x5);
+        create_root_file(
+            &mut kernel,
+            "exec-ok",
+            crate::kernel::fs::Permissions::executable(),
+            &image_bytes,
+        );
+        create_root_file(
+            &mut kernel,
+            "keep",
+            crate::kernel::fs::Permissions::read_write(),
+            b"keep",
+        );
+        create_root_file(
+            &mut kernel,
+            "drop",
+            crate::kernel::fs::Permissions::read_write(),
+            b"drop",
+        );
+
+        let keep_file = kernel
+            .root_fs
+            .open(
+                Path::new("/keep").unwrap(),
+                crate::kernel::fs::OpenFlags::RDONLY,
+                kernel.fs_credentials_for(pid).unwrap(),
+            )
+            .unwrap();
+        let drop_file = kernel
+            .root_fs
+            .open(
+                Path::new("/drop").unwrap(),
+                crate::kernel::fs::OpenFlags::RDONLY,
+                kernel.fs_credentials_for(pid).unwrap(),
+            )
+            .unwrap();
+        let keep_desc = kernel.open_files.insert(keep_file).unwrap();
+        let drop_desc = kernel.open_files.insert(drop_file).unwrap();
+        let keep_fd = kernel
+            .process_files_mut(pid)
+            .unwrap()
+            .open(keep_desc, DescriptorFlags::EMPTY)
+            .unwrap();
+        let drop_fd = kernel
+            .process_files_mut(pid)
+            .unwrap()
+            .open(drop_desc, DescriptorFlags::CLOSE_ON_EXEC)
+            .unwrap();
+
+        let resolved = KernelPathBuf::from_str("/exec-ok").unwrap();
+        let stat = kernel.root_fs.stat(resolved.as_path().unwrap()).unwrap();
+        let image = crate::kernel::process::ExecImageMetadata::new(
+            stat.inode.raw(),
+            stat.size,
+            stat.mode,
+            0x400078,
+            0x70000000,
+            0,
+            None,
+            None,
+        );
+        let request = ExecRequest::new(
+            pid,
+            ProcessPath::from_path(resolved.as_path().unwrap()),
+            ExecVectorMetadata::empty(),
+            ExecVectorMetadata::empty(),
+            Credentials::system(),
+            image,
+        );
+
+        kernel.exec_task(request, Some(thread)).unwrap();
+
+        assert_eq!(
+            kernel.process_table[kernel.locate_process(pid).unwrap()]
+                .unwrap()
+                .pid,
+            pid
+        );
+        assert!(kernel.process_files_mut(pid).unwrap().get(keep_fd).is_ok());
+        assert!(kernel.process_files_mut(pid).unwrap().get(drop_fd).is_err());
+        assert_eq!(
+            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
+                .unwrap()
+                .context
+                .rip,
+            0x400078
+        );
+        assert_kernel_consistent(&kernel);
+    }
+
+    #[test]
+    fn syscall_write_stdout_console_descriptor_accepts_bytes() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let bytes = b"stdout-console-test\n";
+
+        assert_eq!(
+            kernel
+                .handle_syscall(
+                    SyscallNumber::Write.raw(),
+                    SyscallContext::new(
+                        pid,
+                        None,
+                        [1, bytes.as_ptr() as u64, bytes.len() as u64, 0, 0, 0]
+                    ),
+                )
+                .unwrap(),
+            bytes.len() as u64
+        );
+    }
+
+    #[test]
+    fn syscall_write_stderr_console_descriptor_accepts_bytes() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let bytes = b"stderr-console-test\n";
+
+        assert_eq!(
+            kernel
+                .handle_syscall(
+                    SyscallNumber::Write.raw(),
+                    SyscallContext::new(
+                        pid,
+                        None,
+                        [2, bytes.as_ptr() as u64, bytes.len() as u64, 0, 0, 0]
+                    ),
+                )
+                .unwrap(),
+            bytes.len() as u64
+        );
+    }
+
+    #[test]
+    fn syscall_write_invalid_fd_returns_bad_file_descriptor() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let bytes = b"invalid-fd";
+
+        let error = kernel
+            .handle_syscall(
+                SyscallNumber::Write.raw(),
+                SyscallContext::new(
+                    pid,
+                    None,
+                    [99, bytes.as_ptr() as u64, bytes.len() as u64, 0, 0, 0],
+                ),
+            )
+            .unwrap_err();
+
+        assert_eq!(
+            syscall_error_code(error),
+            SyscallErrorCode::BadFileDescriptor
+        );
+    }
+
+    #[test]
+    fn syscall_write_zero_length_console_write_returns_zero() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+
+        assert_eq!(
+            kernel
+                .handle_syscall(
+                    SyscallNumber::Write.raw(),
+                    SyscallContext::new(pid, None, [1, 0, 0, 0, 0, 0]),
+                )
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn wait4_null_status_reaps_zombie_child() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+
+        kernel.exit_process(child, ExitStatus::exited(7)).unwrap();
+
+        let waited = kernel
+            .handle_syscall(
+                SyscallNumber::Wait4.raw(),
+                SyscallContext::new(parent, None, [child.raw(), 0, 0, 0, 0, 0]),
+            )
+            .unwrap();
+
+        assert_eq!(waited, child.raw());
+        assert!(matches!(
+            kernel.locate_process(child),
+            Err(KernelError::UnknownProcess)
+        ));
+    }
+
+    #[test]
+    fn wait4_writes_valid_status_pointer() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let mut status = 0i32;
+
+        kernel.exit_process(child, ExitStatus::exited(42)).unwrap();
+        let waited = kernel
+            .wait_for_child(
+                parent,
+                None,
+                child.raw() as i64,
+                &mut status as *mut i32 as u64,
+                0,
+            )
+            .unwrap();
+
+        assert_eq!(waited, child.raw());
+        assert_eq!(status, ExitStatus::exited(42).raw());
+    }
+
+    #[test]
+    fn wait4_rejects_invalid_status_pointer() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+
+        kernel.exit_process(child, ExitStatus::exited(1)).unwrap();
+        let error = kernel
+            .handle_syscall(
+                SyscallNumber::Wait4.raw(),
+                SyscallContext::new(parent, None, [child.raw(), 1, 0, 0, 0, 0]),
+            )
+            .unwrap_err();
+
+        assert!(matches!(error, KernelError::InvalidPointer));
+        assert_eq!(syscall_error_code(error), SyscallErrorCode::BadAddress);
+        assert_eq!(process_state(&kernel, child), ProcessState::Zombie);
+    }
+
+    #[test]
+    fn wait4_rejects_non_child_pid() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let grandchild = kernel
+            .spawn_child_process(child, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+
+        kernel
+            .exit_process(grandchild, ExitStatus::exited(0))
+            .unwrap();
+
+        assert!(matches!(
+            kernel.wait_for_child(parent, None, grandchild.raw() as i64, 0, 0),
+            Err(KernelError::UnknownProcess)
+        ));
+    }
+
+    #[test]
+    fn wait4_reports_no_children() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+
+        assert!(matches!(
+            kernel.wait_for_child(parent, None, -1, 0, 0),
+            Err(KernelError::UnknownProcess)
+        ));
+    }
+
+    #[test]
+    fn wait4_blocks_parent_until_child_exits() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let thread = first_thread(&kernel, parent);
+
+        assert!(matches!(
+            kernel.wait_for_child(parent, Some(thread), child.raw() as i64, 0, 0),
+            Err(KernelError::MessageQueueEmpty)
+        ));
+
+        assert_eq!(process_state(&kernel, parent), ProcessState::Blocked);
+        assert!(process_threads_blocked(&kernel, parent));
+        assert_eq!(
+            kernel.process_table[kernel.locate_process(parent).unwrap()]
+                .unwrap()
+                .child_wait,
+            Some(ChildWaitSelector::Pid(child))
+        );
+    }
+
+    #[test]
+    fn child_exit_wakes_matching_waiter_without_reaping_zombie() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let thread = first_thread(&kernel, parent);
+
+        assert!(matches!(
+            kernel.wait_for_child(parent, Some(thread), child.raw() as i64, 0, 0),
+            Err(KernelError::MessageQueueEmpty)
+        ));
+        kernel.exit_process(child, ExitStatus::exited(9)).unwrap();
+
+        assert_eq!(process_state(&kernel, parent), ProcessState::Ready);
+        assert_eq!(process_state(&kernel, child), ProcessState::Zombie);
+        assert_eq!(
+            kernel.process_table[kernel.locate_process(parent).unwrap()]
+                .unwrap()
+                .child_wait,
+            None
+        );
+    }
+
+    #[test]
+    fn wait_any_reaps_first_zombie_child() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let other = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+
+        kernel.exit_process(child, ExitStatus::exited(3)).unwrap();
+
+        assert_eq!(
+            kernel.wait_for_child(parent, None, -1, 0, 0).unwrap(),
+            child.raw()
+        );
+        assert!(matches!(
+            kernel.locate_process(child),
+            Err(KernelError::UnknownProcess)
+        ));
+        assert!(kernel.locate_process(other).is_ok());
+        assert_kernel_consistent(&kernel);
+    }
+
+    #[test]
+    fn wait_pid_ignores_other_zombie_children() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let first = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let second = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+
+        kernel.exit_process(first, ExitStatus::exited(1)).unwrap();
+        kernel.exit_process(second, ExitStatus::exited(2)).unwrap();
+
+        assert_eq!(
+            kernel
+                .wait_for_child(parent, None, second.raw() as i64, 0, 0)
+                .unwrap(),
+            second.raw()
+        );
+        assert_eq!(process_state(&kernel, first), ProcessState::Zombie);
+    }
+
+    #[test]
+    fn wait_nohang_returns_zero_without_blocking() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let thread = first_thread(&kernel, parent);
+
+        assert_eq!(
+            kernel
+                .wait_for_child(parent, Some(thread), child.raw() as i64, 0, 1)
+                .unwrap(),
+            0
+        );
+        assert_eq!(process_state(&kernel, parent), ProcessState::Ready);
+        assert_eq!(
+            kernel.process_table[kernel.locate_process(parent).unwrap()]
+                .unwrap()
+                .child_wait,
+            None
+        );
+    }
+
+    #[test]
+    fn syscall_exit_marks_zombie_releases_resources_and_wakes_waiter() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let parent_thread = first_thread(&kernel, parent);
+        let child_thread = first_thread(&kernel, child);
+        let fd = kernel
+            .handle_syscall(
+                SyscallNumber::Eventfd.raw(),
+                SyscallContext::new(child, Some(child_thread), [0, 0, 0, 0, 0, 0]),
+            )
+            .unwrap() as usize;
+        let description = kernel.fd_description(child, fd).unwrap();
+        kernel
+            .grant_task_capability(
+                child,
+                CapabilityObject::ServiceControl,
+                CapabilityRights::service_control(),
+            )
+            .unwrap();
+
+        assert!(matches!(
+            kernel.wait_for_child(parent, Some(parent_thread), child.raw() as i64, 0, 0),
+            Err(KernelError::MessageQueueEmpty)
+        ));
+
+        kernel
+            .handle_syscall(
+                SyscallNumber::Exit.raw(),
+                SyscallContext::new(child, Some(child_thread), [23, 0, 0, 0, 0, 0]),
+            )
+            .unwrap();
+
+        assert_eq!(process_state(&kernel, child), ProcessState::Zombie);
+        assert_eq!(process_state(&kernel, parent), ProcessState::Ready);
+        assert!(matches!(
+            kernel.locate_thread(child_thread),
+            Err(KernelError::UnknownThread)
+        ));
+        assert!(matches!(
+            kernel.open_files.ref_count(description),
+            Err(FileTableError::InvalidDescriptor)
+        ));
+        assert!(matches!(
+            kernel.check_service_control_capability(child),
+            Err(KernelError::SecurityViolation(
+                IsolationError::UnknownTask | IsolationError::CapabilityMissing
+            ))
+        ));
+        assert_eq!(
+            kernel.process_table[kernel.locate_process(parent).unwrap()]
+                .unwrap()
+                .child_wait,
+            None
+        );
+        assert_kernel_consistent(&kernel);
+    }
+
+    #[test]
+    fn syscall_trap_exit_does_not_resume_or_requeue_exited_thread() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Critical, Credentials::system())
+            .unwrap();
+        let child_thread = first_thread(&kernel, child);
+
+        let scheduled_parent = kernel.kernel_schedule_next().unwrap();
+        assert_eq!(scheduled_parent.process, parent);
+        let scheduled_child = kernel
+            .kernel_yield_current(scheduled_parent)
+            .unwrap()
+            .unwrap();
+        assert_eq!(scheduled_child.process, child);
+        kernel.pending_mtss_decision = Some(scheduled_child);
+
+        let child_index = kernel.locate_thread(child_thread).unwrap();
+        kernel
+            .thread_table
+            .entry_mut(child_index)
+            .unwrap()
+            .prepare_syscall(SyscallNumber::Exit.raw(), [17, 0, 0, 0, 0, 0]);
+
+        kernel.run_core(0);
+
+        assert_eq!(process_state(&kernel, child), ProcessState::Zombie);
+        assert!(matches!(
+            kernel.locate_thread(child_thread),
+            Err(KernelError::UnknownThread)
+        ));
+        assert!(kernel
+            .pending_mtss_decision
+            .map(|record| record.thread != child_thread && record.process != child)
+            .unwrap_or(true));
+        if let Some(record) = kernel.kernel_schedule_next() {
+            assert_ne!(record.thread, child_thread);
+            assert_ne!(record.process, child);
+        }
+    }
+
+    #[test]
+    fn kernel_yield_current_returns_and_defers_mtss_selected_thread() {
+        let mut kernel = boot_kernel();
+        let first = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let second = kernel
+            .spawn_child_process(first, 0, ProcessPriority::Critical, Credentials::system())
+            .unwrap();
+
+        let scheduled = kernel.kernel_schedule_next().unwrap();
+        assert_eq!(scheduled.process, first);
+
+        let next = kernel.kernel_yield_current(scheduled).unwrap().unwrap();
+        assert_eq!(next.process, second);
+
+        kernel.pending_mtss_decision = Some(next);
+        assert_eq!(kernel.kernel_schedule_next().unwrap().process, second);
+    }
+
+    #[test]
+    fn supervisor_starts_l2_before_device_daemons() {
+        let mut kernel = boot_kernel();
+        let supervisor = crate::supervisor::Supervisor::new();
+
+        let report = supervisor.bootstrap_services(&mut kernel);
+
+        assert!(report.all_running());
+        let l2 = report
+            .pid(crate::supervisor::ServiceId::L2Subkernel)
+            .unwrap();
+        assert_eq!(l2.raw(), 1);
+        assert_eq!(
+            report.state(crate::supervisor::ServiceId::Storaged),
+            Some(crate::supervisor::StartupState::Running)
+        );
+        assert_eq!(
+            report.state(crate::supervisor::ServiceId::Usbd),
+            Some(crate::supervisor::StartupState::Running)
+        );
+        assert_eq!(
+            report.state(crate::supervisor::ServiceId::Nvmed),
+            Some(crate::supervisor::StartupState::Running)
+        );
+        assert_eq!(
+            report.state(crate::supervisor::ServiceId::Ahcid),
+            Some(crate::supervisor::StartupState::Running)
+        );
+        assert_eq!(
+            report.state(crate::supervisor::ServiceId::Displayd),
+            Some(crate::supervisor::StartupState::Running)
+        );
+        assert_eq!(
+            report.state(crate::supervisor::ServiceId::AmdgpuDisplayd),
+            Some(crate::supervisor::StartupState::Running)
+        );
+        assert_eq!(
+            report.state(crate::supervisor::ServiceId::Networkd),
+            Some(crate::supervisor::StartupState::Running)
+        );
+        assert_eq!(
+            report.state(crate::supervisor::ServiceId::Inputd),
+            Some(crate::supervisor::StartupState::Running)
+        );
+
+        let mut idx = 0usize;
+        let mut child_count = 0usize;
+        while idx < Kernel::<16, 4>::THREAD_CAPACITY {
+            if idx < kernel.process_table.len() {
+                if let Some(pcb) = kernel.process_table[idx] {
+                    if pcb.pid != l2 {
+                        assert_eq!(pcb.parent, Some(l2));
+                        child_count += 1;
+                    }
+                }
+            }
+            idx += 1;
+        }
+        assert_eq!(child_count, 8);
+    }
+
+    #[test]
+    fn receive_or_block_returns_queued_message_without_blocking() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"ping");
+
+        kernel.send_message(pid, pid, payload).unwrap();
+
+        let message = kernel.receive_or_block(pid).unwrap().unwrap();
+        assert_eq!(message.sender, pid);
+        assert_eq!(message.receiver, pid);
+        assert_eq!(&message.payload.data[..message.payload.length], b"ping");
+        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+    }
+
+    #[test]
+    fn receive_or_block_atomically_blocks_empty_receiver() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+
+        let message = kernel.receive_or_block(pid).unwrap();
+
+        assert!(message.is_none());
+        assert_eq!(process_state(&kernel, pid), ProcessState::Blocked);
+        assert!(process_threads_blocked(&kernel, pid));
+    }
+
+    #[test]
+    fn libc_receive_uses_blocking_receive_syscall() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let thread = first_thread(&kernel, pid);
+        let mut out = Message::new(
+            ProcessId::new(0),
+            ProcessId::new(0),
+            0,
+            MessagePayload::empty(SecurityClass::Public),
+        );
+
+        let received =
+            libc::receive_ipc_or_block(&mut kernel, pid, Some(thread), &mut out).unwrap();
+
+        assert_eq!(received, None);
+        assert_eq!(process_state(&kernel, pid), ProcessState::Blocked);
+        assert!(process_threads_blocked(&kernel, pid));
+    }
+
+    #[test]
+    fn security_errors_preserve_isolation_reason() {
+        let mut kernel = boot_kernel();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let user = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let mut buffer = [0u8; 8];
+
+        assert!(matches!(
+            kernel.device_read(user, DeviceId::new(1), &mut buffer),
+            Err(KernelError::SecurityViolation(
+                IsolationError::CapabilityMissing
+            ))
+        ));
+
+        assert!(matches!(
+            kernel.send_message(
+                user,
+                ProcessId::new(999),
+                MessagePayload::empty(SecurityClass::Public)
+            ),
+            Err(KernelError::SecurityViolation(IsolationError::UnknownTask))
+        ));
+    }
+
+    #[test]
+    fn service_registry_routes_ipc_and_gates_raw_device_access() {
+        let mut kernel = boot_kernel();
+        let l2 = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let displayd = kernel
+            .spawn_child_process(
+                l2,
+                0,
+                ProcessPriority::High,
+                Credentials::new(
+                    SecurityLabel::internal(),
+                    CapabilitySet::ipc_io(),
+                    IsolationLevel::Process,
+                ),
+            )
+            .unwrap();
+        let user = kernel
+            .spawn_child_process(l2, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+
+        kernel
+            .register_service(l2, RegistryServiceId::Displayd, displayd)
+            .unwrap();
+
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"draw");
+        kernel
+            .send_service_message(user, RegistryServiceId::Displayd, payload)
+            .unwrap();
+
+        let message = kernel.receive_message(displayd).unwrap();
+        assert_eq!(message.sender, user);
+        assert_eq!(message.receiver, displayd);
+        assert_eq!(&message.payload.data[..message.payload.length], b"draw");
+
+        let mut buffer = [0u8; 64];
+        assert!(matches!(
+            kernel.device_read(displayd, DeviceId::new(5), &mut buffer),
+            Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation
+            ))
+        ));
+
+        kernel
+            .claim_service_device(displayd, RegistryServiceId::Displayd, DeviceId::new(5))
+            .unwrap();
+        assert!(kernel
+            .device_read(displayd, DeviceId::new(5), &mut buffer)
+            .is_ok());
+    }
+
+    #[test]
+    fn kernel_exit_reports_without_registry_policy_cleanup() {
+        let mut kernel = boot_kernel();
+        let l2 = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let networkd = kernel
+            .spawn_child_process(
+                l2,
+                0,
+                ProcessPriority::High,
+                Credentials::new(
+                    SecurityLabel::internal(),
+                    CapabilitySet::ipc_io(),
+                    IsolationLevel::Process,
+                ),
+            )
+            .unwrap();
+
+        kernel
+            .register_service(l2, RegistryServiceId::Networkd, networkd)
+            .unwrap();
+        kernel
+            .claim_service_device(networkd, RegistryServiceId::Networkd, DeviceId::new(6))
+            .unwrap();
+
+        let exit = kernel
+            .exit_process(networkd, ExitStatus::exited(0))
+            .unwrap();
+
+        assert_eq!(exit.pid, networkd);
+        assert_eq!(exit.status.raw(), ExitStatus::exited(0).raw());
+        assert_eq!(
+            kernel.service_owner(RegistryServiceId::Networkd),
+            Some(networkd)
+        );
+        assert!(matches!(
+            kernel.device_write(networkd, DeviceId::new(6), b"ping"),
+            Err(KernelError::SecurityViolation(IsolationError::UnknownTask))
+        ));
+    }
+
+    #[test]
+    fn syscall_error_encoding_maps_structured_security_reasons() {
+        assert_eq!(
+            encode_syscall_error(KernelError::SecurityViolation(
+                IsolationError::CapabilityMissing
+            )),
+            MIRAGE_SYSCALL_ERROR_BIT | SyscallErrorCode::PermissionDenied.raw()
+        );
+        assert_eq!(
+            encode_syscall_error(KernelError::SecurityViolation(IsolationError::UnknownTask)),
+            MIRAGE_SYSCALL_ERROR_BIT | SyscallErrorCode::NoSuchProcess.raw()
+        );
+        assert_eq!(
+            encode_syscall_error(KernelError::MessageQueueFull),
+            MIRAGE_SYSCALL_ERROR_BIT | SyscallErrorCode::QueueFull.raw()
+        );
+    }
+
+    #[test]
+    fn nanosleep_blocks_until_kernel_time_deadline() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let thread = first_thread(&kernel, pid);
+        let req = MirageTimespec {
+            tv_sec: 0,
+            tv_nsec: 1,
+        };
+
+        kernel
+            .handle_syscall(
+                SyscallNumber::Nanosleep.raw(),
+                SyscallContext::new(
+                    pid,
+                    Some(thread),
+                    [&req as *const MirageTimespec as u64, 0, 0, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(process_state(&kernel, pid), ProcessState::Blocked);
+        assert!(process_threads_blocked(&kernel, pid));
+
+        kernel.tick();
+
+        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+        assert_eq!(
+            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
+                .unwrap()
+                .state,
+            ThreadState::Ready
+        );
+    }
+
+    #[test]
+    fn tls_syscalls_record_fs_and_gs_bases() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let thread = first_thread(&kernel, pid);
+        let mut out = 0u64;
+
+        kernel
+            .handle_syscall(
+                SyscallNumber::SetThreadArea.raw(),
+                SyscallContext::new(pid, Some(thread), [0x7000, 0, 0, 0, 0, 0]),
+            )
+            .unwrap();
+        assert_eq!(kernel.thread_context(thread).unwrap().fs_base, 0x7000);
+
+        kernel
+            .handle_syscall(
+                SyscallNumber::ArchPrctl.raw(),
+                SyscallContext::new(pid, Some(thread), [ARCH_SET_FS, 0x8000, 0, 0, 0, 0]),
+            )
+            .unwrap();
+        kernel
+            .handle_syscall(
+                SyscallNumber::ArchPrctl.raw(),
+                SyscallContext::new(
+                    pid,
+                    Some(thread),
+                    [ARCH_GET_FS, &mut out as *mut u64 as u64, 0, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+        assert_eq!(out, 0x8000);
+
+        kernel
+            .handle_syscall(
+                SyscallNumber::ArchPrctl.raw(),
+                SyscallContext::new(pid, Some(thread), [ARCH_SET_GS, 0x9000, 0, 0, 0, 0]),
+            )
+            .unwrap();
+        kernel
+            .handle_syscall(
+                SyscallNumber::ArchPrctl.raw(),
+                SyscallContext::new(
+                    pid,
+                    Some(thread),
+                    [ARCH_GET_GS, &mut out as *mut u64 as u64, 0, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+        assert_eq!(out, 0x9000);
+    }
+
+    #[test]
+    fn futex_wait_blocks_and_wake_requeues_thread() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let thread = first_thread(&kernel, pid);
+        let word = 7i32;
+
+        kernel
+            .handle_syscall(
+                SyscallNumber::Futex.raw(),
+                SyscallContext::new(
+                    pid,
+                    Some(thread),
+                    [&word as *const i32 as u64, FUTEX_WAIT, 7, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+        assert_eq!(process_state(&kernel, pid), ProcessState::Blocked);
+        assert_eq!(
+            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
+                .unwrap()
+                .state,
+            ThreadState::Blocked
+        );
+
+        let woken = kernel
+            .handle_syscall(
+                SyscallNumber::Futex.raw(),
+                SyscallContext::new(
+                    pid,
+                    None,
+                    [&word as *const i32 as u64, FUTEX_WAKE, 1, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(woken, 1);
+        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+        assert_eq!(
+            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
+                .unwrap()
+                .state,
+            ThreadState::Ready
+        );
+        assert_kernel_consistent(&kernel);
     }
 
-    fn futex_key(&self, pid: ProcessId, user_address: u64) -> KernelResult<FutexKey> {
-        let _ = user_out_ptr::<i32>(user_address)?;
-        let process_index = self.locate_process(pid)?;
-        let owner = self.process_table[process_index]
-            .as_ref()
-            .ok_or(KernelError::UnknownProcess)?
-            .address_space_root;
-        let owner = if owner == 0 { pid.raw() } else { owner };
-        Ok(FutexKey::new(owner, user_address))
-    }
+    #[test]
+    fn shared_futex_wait_is_released_by_another_process() {
+        let mut kernel = boot_kernel();
+        let waiter = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let waker = kernel
+            .spawn_child_process(waiter, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let region =
+            memory::mmap_for(waiter, memory::PAGE_SIZE, MemoryProtection::read_write()).unwrap();
+        let addr = region.ptr.as_ptr() as usize;
+        unsafe { core::ptr::write_volatile(addr as *mut u64, 5) };
 
-    fn set_thread_fs_base(&mut self, thread: ThreadId, base: u64) -> KernelResult<u64> {
-        let index = self.locate_thread(thread)?;
-        let tcb = self.thread_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownThread)?;
-        tcb.set_fs_base(base);
-        Ok(0)
-    }
+        assert_eq!(
+            kernel.futex_wait(waiter, addr, 5, None).unwrap(),
+            FutexWaitOutcome::Blocked
+        );
+        assert_eq!(process_state(&kernel, waiter), ProcessState::Blocked);
 
-    fn set_thread_gs_base(&mut self, thread: ThreadId, base: u64) -> KernelResult<u64> {
-        let index = self.locate_thread(thread)?;
-        let tcb = self.thread_table[index]
-            .as_mut()
-            .ok_or(KernelError::UnknownThread)?;
-        tcb.set_gs_base(base);
-        Ok(0)
+        // The waker does not map the word itself; the address names it.
+        assert!(matches!(
+            kernel.futex_wait(waker, addr, 5, None),
+            Err(KernelError::InvalidPointer)
+        ));
+        assert_eq!(kernel.futex_wake(addr + 8, 1).unwrap(), 0);
+        assert_eq!(kernel.futex_wake(addr, 4).unwrap(), 1);
+        assert_eq!(process_state(&kernel, waiter), ProcessState::Ready);
+        assert_eq!(kernel.futex_wake(addr, 4).unwrap(), 0);
+        assert_kernel_consistent(&kernel);
+        assert!(memory::munmap(region));
     }
 
-    fn thread_fs_base(&self, thread: ThreadId) -> KernelResult<u64> {
-        let index = self.locate_thread(thread)?;
-        self.thread_table[index]
-            .map(|tcb| tcb.fs_base)
-            .ok_or(KernelError::UnknownThread)
-    }
+    #[test]
+    fn shared_futex_wait_returns_at_once_when_value_differs() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let region =
+            memory::mmap_for(pid, memory::PAGE_SIZE, MemoryProtection::read_write()).unwrap();
+        let addr = region.ptr.as_ptr() as usize;
+        unsafe { core::ptr::write_volatile(addr as *mut u64, 9) };
 
-    fn thread_gs_base(&self, thread: ThreadId) -> KernelResult<u64> {
-        let index = self.locate_thread(thread)?;
-        self.thread_table[index]
-            .map(|tcb| tcb.gs_base)
-            .ok_or(KernelError::UnknownThread)
+        assert_eq!(
+            kernel.futex_wait(pid, addr, 8, None).unwrap(),
+            FutexWaitOutcome::ValueChanged
+        );
+        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+        assert!(matches!(
+            kernel.futex_wait(pid, addr + 4, 9, None),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert_eq!(kernel.futex_wake(addr, 1).unwrap(), 0);
+        assert!(memory::munmap(region));
     }
 
-    fn block_thread(&mut self, thread: ThreadId) -> KernelResult<()> {
-        let index = self.locate_thread(thread)?;
-        let process = self.thread_table[index]
-            .as_ref()
-            .ok_or(KernelError::UnknownThread)?
-            .process;
-        if let Some(tcb) = self.thread_table[index].as_mut() {
-            tcb.block();
-        }
-        match self
-            .mtss_scheduler
-            .block_thread(Self::mtss_thread_id(thread))
-        {
-            Ok(()) | Err(MtssError::InvalidThread) => {}
-            Err(err) => return Err(map_mtss_error(err)),
-        }
-        if !self.has_runnable_thread(process) {
-            if let Ok(process_index) = self.locate_process(process) {
-                if let Some(pcb) = self.process_table[process_index].as_ref() {
-                    if pcb.state != ProcessState::Zombie {
-                        let _ = self.set_process_blocked_via_mtss(process, process_index);
-                    }
-                }
-            }
-        }
-        Ok(())
-    }
+    #[test]
+    fn futex_wait_timeout_wakes_on_tick_with_timed_out_result() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let thread = first_thread(&kernel, pid);
+        let word = 3i32;
+        let timeout = MirageTimespec {
+            tv_sec: 0,
+            tv_nsec: 1,
+        };
 
-    fn wake_thread(&mut self, thread: ThreadId) -> KernelResult<()> {
-        let index = self.locate_thread(thread)?;
-        let mut process = None;
-        if let Some(tcb) = self.thread_table[index].as_mut() {
-            if tcb.state == ThreadState::Blocked {
-                tcb.mark_ready();
-                process = Some(tcb.process);
-            }
-        }
-        if let Some(process) = process {
-            match self
-                .mtss_scheduler
-                .wake_thread(Self::mtss_thread_id(thread))
-            {
-                Ok(()) | Err(MtssError::InvalidThread) => {}
-                Err(err) => return Err(map_mtss_error(err)),
-            }
-            if let Ok(process_index) = self.locate_process(process) {
-                if let Some(pcb) = self.process_table[process_index].as_ref() {
-                    if pcb.state == ProcessState::Blocked {
-                        self.set_process_ready_via_mtss(process, process_index)?;
-                    }
-                }
-            }
-        }
-        Ok(())
-    }
+        kernel
+            .handle_syscall(
+                SyscallNumber::Futex.raw(),
+                SyscallContext::new(
+                    pid,
+                    Some(thread),
+                    [
+                        &word as *const i32 as u64,
+                        FUTEX_WAIT,
+                        3,
+                        &timeout as *const MirageTimespec as u64,
+                        0,
+                        0,
+                    ],
+                ),
+            )
+            .unwrap();
 
-    fn has_runnable_thread(&self, pid: ProcessId) -> bool {
-        let mut idx = 0usize;
-        while idx < Self::THREAD_CAPACITY {
-            if let Some(thread) = self.thread_table[idx] {
-                if thread.process == pid
-                    && (thread.state == ThreadState::Ready || thread.state == ThreadState::Running)
-                {
-                    return true;
-                }
-            }
-            idx += 1;
-        }
-        false
+        kernel.tick();
+
+        let context = kernel.thread_context(thread).unwrap();
+        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+        assert_eq!(context.rax, encode_syscall_error(KernelError::TimedOut));
     }
 
-    fn wake_futex_threads(
-        &mut self,
-        threads: &[Option<ThreadId>],
-        count: usize,
-        result: u64,
-    ) -> KernelResult<()> {
-        let mut idx = 0usize;
-        while idx < count && idx < threads.len() {
-            if let Some(thread) = threads[idx] {
-                self.write_thread_syscall_result(thread, result);
-                self.wake_thread(thread)?;
-            }
-            idx += 1;
-        }
-        Ok(())
+    #[test]
+    fn nanosleep_rejects_malformed_timespec() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let req = MirageTimespec {
+            tv_sec: 0,
+            tv_nsec: 1_000_000_000,
+        };
+
+        assert!(matches!(
+            kernel.handle_syscall(
+                SyscallNumber::Nanosleep.raw(),
+                SyscallContext::new(
+                    pid,
+                    None,
+                    [&req as *const MirageTimespec as u64, 0, 0, 0, 0, 0,],
+                ),
+            ),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
     }
 
-    fn block_process_at_index(&mut self, pid: ProcessId, index: usize) {
-        let _ = self.set_process_blocked_via_mtss(pid, index);
-        self.block_threads_for_process(pid);
+    #[test]
+    fn process_timers_are_owned_and_reject_bad_ids() {
+        let mut kernel = boot_kernel();
+        let owner = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let other = kernel
+            .spawn_child_process(owner, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let mut timer_id = 0u64;
+
+        kernel
+            .handle_syscall(
+                SyscallNumber::TimerCreate.raw(),
+                SyscallContext::new(
+                    owner,
+                    None,
+                    [0, 0, &mut timer_id as *mut u64 as u64, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+        assert_ne!(timer_id, 0);
+
+        assert!(matches!(
+            kernel.handle_syscall(
+                SyscallNumber::TimerGettime.raw(),
+                SyscallContext::new(
+                    other,
+                    None,
+                    [
+                        timer_id,
+                        &mut MirageItimerspec {
+                            it_interval: MirageTimespec {
+                                tv_sec: 0,
+                                tv_nsec: 0
+                            },
+                            it_value: MirageTimespec {
+                                tv_sec: 0,
+                                tv_nsec: 0
+                            },
+                        } as *mut MirageItimerspec as u64,
+                        0,
+                        0,
+                        0,
+                        0
+                    ]
+                ),
+            ),
+            Err(KernelError::InvalidArgument)
+        ));
     }
 
-    fn block_threads_for_process(&mut self, pid: ProcessId) {
-        let mut idx = 0usize;
-        while idx < Self::THREAD_CAPACITY {
-            if let Some(entry) = self.thread_table.get_mut(idx) {
-                if let Some(thread) = entry.as_mut() {
-                    if thread.process == pid {
-                        thread.block();
-                    }
-                }
-            }
-            idx += 1;
-        }
-    }
+    #[test]
+    fn timer_settime_arms_gettime_and_delete() {
+        let mut kernel = boot_kernel();
+        let owner = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let mut timer_id = 0u64;
+        let new_value = MirageItimerspec {
+            it_interval: MirageTimespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+            it_value: MirageTimespec {
+                tv_sec: 0,
+                tv_nsec: 1,
+            },
+        };
+        let mut current = MirageItimerspec {
+            it_interval: MirageTimespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+            it_value: MirageTimespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+        };
+
+        kernel
+            .handle_syscall(
+                SyscallNumber::TimerCreate.raw(),
+                SyscallContext::new(
+                    owner,
+                    None,
+                    [0, 0, &mut timer_id as *mut u64 as u64, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+        kernel
+            .handle_syscall(
+                SyscallNumber::TimerSettime.raw(),
+                SyscallContext::new(
+                    owner,
+                    None,
+                    [
+                        timer_id,
+                        0,
+                        &new_value as *const MirageItimerspec as u64,
+                        0,
+                        0,
+                        0,
+                    ],
+                ),
+            )
+            .unwrap();
+        kernel
+            .handle_syscall(
+                SyscallNumber::TimerGettime.raw(),
+                SyscallContext::new(
+                    owner,
+                    None,
+                    [
+                        timer_id,
+                        &mut current as *mut MirageItimerspec as u64,
+                        0,
+                        0,
+                        0,
+                        0,
+                    ],
+                ),
+            )
+            .unwrap();
+        assert!(current.it_value.tv_sec > 0 || current.it_value.tv_nsec > 0);
 
-    fn make_threads_ready(&mut self, pid: ProcessId) -> KernelResult<()> {
-        let mut idx = 0usize;
-        while idx < Self::THREAD_CAPACITY {
-            if let Some(entry) = self.thread_table.get_mut(idx) {
-                if let Some(thread) = entry.as_mut() {
-                    if thread.process == pid && thread.state == ThreadState::Blocked {
-                        thread.mark_ready();
-                        if self
-                            .mtss_scheduler
-                            .wake_thread(Self::mtss_thread_id(thread.id))
-                            .is_err()
-                        {
-                            thread.block();
-                            self.rollback_ready_threads(pid, idx);
-                            return Err(KernelError::SchedulerFull);
-                        }
-                    }
-                }
-            }
-            idx += 1;
-        }
-        Ok(())
+        kernel
+            .handle_syscall(
+                SyscallNumber::TimerDelete.raw(),
+                SyscallContext::new(owner, None, [timer_id, 0, 0, 0, 0, 0]),
+            )
+            .unwrap();
+        assert!(matches!(
+            kernel.handle_syscall(
+                SyscallNumber::TimerDelete.raw(),
+                SyscallContext::new(owner, None, [timer_id, 0, 0, 0, 0, 0]),
+            ),
+            Err(KernelError::InvalidArgument)
+        ));
     }
 
-    fn rollback_ready_threads(&mut self, pid: ProcessId, before_index: usize) {
-        let mut idx = 0usize;
-        while idx < before_index {
-            if let Some(entry) = self.thread_table.get_mut(idx) {
-                if let Some(thread) = entry.as_mut() {
-                    if thread.process == pid && thread.state == ThreadState::Ready {
-                        thread.block();
-                        let _ = self
-                            .mtss_scheduler
-                            .block_thread(Self::mtss_thread_id(thread.id));
-                    }
-                }
-            }
-            idx += 1;
-        }
-    }
+    #[test]
+    fn process_file_tables_share_inherited_open_descriptions() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let path = b"/fdshare\0";
+        let fd = kernel
+            .handle_syscall(
+                SyscallNumber::OpenAt.raw(),
+                SyscallContext::new(
+                    parent,
+                    None,
+                    [
+                        AT_FDCWD as u64,
+                        path.as_ptr() as u64,
+                        (crate::kernel::fs::O_RDWR
+                            | crate::kernel::fs::O_CREAT
+                            | crate::kernel::fs::O_CLOEXEC) as u64,
+                        0,
+                        0,
+                        0,
+                    ],
+                ),
+            )
+            .unwrap() as usize;
+        let description = kernel.fd_description(parent, fd).unwrap();
+        assert_eq!(kernel.open_files.ref_count(description).unwrap(), 1);
+        assert!(kernel
+            .process_files(parent)
+            .unwrap()
+            .get(fd)
+            .unwrap()
+            .close_on_exec());
 
-    fn remove_threads_for_process(&mut self, pid: ProcessId) {
-        let mut idx = 0usize;
-        while idx < Self::THREAD_CAPACITY {
-            if let Some(thread) = self.thread_table[idx] {
-                if thread.process == pid {
-                    let _ = self
-                        .mtss_scheduler
-                        .exit_thread(Self::mtss_thread_id(thread.id));
-                    self.futexes.remove_thread(thread.id);
-                    self.remove_thread_from_cores(thread.id);
-                    self.thread_table[idx] = None;
-                }
-            }
-            idx += 1;
-        }
-    }
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        assert_eq!(kernel.fd_description(child, fd).unwrap(), description);
+        assert_eq!(kernel.open_files.ref_count(description).unwrap(), 2);
 
-    fn remove_thread_from_cores(&mut self, thread: ThreadId) {
-        let mut idx = 0usize;
-        while idx < cpu::MAX_CORES {
-            self.core_states[idx].evict(thread);
-            idx += 1;
-        }
-    }
+        kernel
+            .handle_syscall(
+                SyscallNumber::Close.raw(),
+                SyscallContext::new(parent, None, [fd as u64, 0, 0, 0, 0, 0]),
+            )
+            .unwrap();
+        assert_eq!(kernel.open_files.ref_count(description).unwrap(), 1);
 
-    fn create_thread(
-        &mut self,
-        pid: ProcessId,
-        entry_point: u64,
-        priority: ProcessPriority,
-    ) -> KernelResult<ThreadId> {
-        let slot = self
-            .find_free_thread_slot()
-            .ok_or(KernelError::ThreadTableFull)?;
-        let id = self.allocate_thread_id();
-        let stack_pointer = self.allocate_stack_pointer(slot, id);
-        let tcb = ThreadControlBlock::new(id, pid, entry_point, priority, stack_pointer);
-        self.thread_table[slot] = Some(tcb);
-        self.update_process_thread_count(pid, true);
-        Ok(id)
+        kernel.terminate_process(child);
+        assert!(matches!(
+            kernel.open_files.ref_count(description),
+            Err(FileTableError::InvalidDescriptor)
+        ));
     }
 
-    fn rollback_thread_creation(&mut self, thread: ThreadId) {
-        if let Ok(index) = self.locate_thread(thread) {
-            if let Some(tcb) = self.thread_table[index] {
-                self.futexes.remove_thread(thread);
-                self.thread_table[index] = None;
-                self.update_process_thread_count(tcb.process, false);
-            }
-        }
-    }
+    #[test]
+    fn syscall_spawn_spider_rsd_records_parent_and_runnable_mtss() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let path = b"/spider-rt/sbin/spider-rsd\0";
+        let child = kernel
+            .handle_syscall(
+                SyscallNumber::Spawn.raw(),
+                SyscallContext::new(
+                    parent,
+                    None,
+                    [path.as_ptr() as u64, (path.len() - 1) as u64, 0, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+        let child_pid = ProcessId::new(child);
+        let child_index = kernel.locate_process(child_pid).unwrap();
+        let child_pcb = kernel.process_table[child_index].unwrap();
+        assert_eq!(child_pcb.parent, Some(parent));
+        assert_eq!(child_pcb.state, ProcessState::Ready);
+        assert!(child_pcb.address_space_root != 0);
 
-    fn write_thread_syscall_result(&mut self, thread: ThreadId, result: u64) {
-        if let Ok(index) = self.locate_thread(thread) {
-            if let Some(tcb) = self.thread_table[index].as_mut() {
-                tcb.write_syscall_result(result);
+        let mut saw_child = false;
+        let mut attempts = 0;
+        while attempts < 4 {
+            if let Some(record) = kernel.kernel_schedule_next() {
+                saw_child |= record.process == child_pid;
             }
+            attempts += 1;
         }
+        assert!(saw_child);
+        assert_kernel_consistent(&kernel);
     }
 
-    fn allocate_stack_pointer(&self, slot: usize, thread: ThreadId) -> u64 {
-        const USER_STACK_BASE: u64 = 0x0000_7000_0000_0000;
-        const USER_STACK_SIZE: u64 = 0x20_000;
-        let stack_slot = (slot as u64).saturating_add(thread.raw());
-        USER_STACK_BASE.saturating_add(stack_slot.saturating_mul(USER_STACK_SIZE))
+    #[test]
+    fn syscall_spawn_resolves_m1_terminal_via_root_vfs() {
+        let mut kernel = boot_kernel();
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let path = b"/usr/bin/m1-terminal\0";
+        let child = kernel
+            .handle_syscall(
+                SyscallNumber::Spawn.raw(),
+                SyscallContext::new(
+                    parent,
+                    None,
+                    [path.as_ptr() as u64, (path.len() - 1) as u64, 0, 0, 0, 0],
+                ),
+            )
+            .unwrap();
+        assert_eq!(
+            kernel.process_table[kernel.locate_process(ProcessId::new(child)).unwrap()]
+                .unwrap()
+                .parent,
+            Some(parent)
+        );
     }
 
-    fn update_process_thread_count(&mut self, pid: ProcessId, increment: bool) {
-        if let Ok(index) = self.locate_process(pid) {
-            if let Some(pcb) = self.process_table[index].as_mut() {
-                if increment {
-                    pcb.increment_thread_count();
-                } else {
-                    pcb.decrement_thread_count();
-                }
-            }
-        }
-    }
+    #[test]
+    fn memory_syscalls_are_process_owned() {
+        let mut kernel = boot_kernel();
+        let owner = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let other = kernel
+            .spawn_child_process(owner, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let ptr = libc::malloc(&mut kernel, owner, None, 64).unwrap();
 
-    fn ensure_process_exists(&self, pid: ProcessId) -> KernelResult<()> {
-        self.locate_process(pid).map(|_| ())
+        assert!(matches!(
+            libc::free(&mut kernel, other, None, ptr),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert!(libc::free(&mut kernel, owner, None, ptr).is_ok());
     }
 
-    fn handle_isolation_fault(&mut self, pid: ProcessId, _reason: IsolationError) {
-        self.terminate_process(pid);
-    }
+    #[test]
+    fn mmap_rejects_writable_executable_mapping() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let protection = MemoryProtection::from_bits(PROT_READ | PROT_WRITE | PROT_EXECUTE);
 
-    fn find_free_slot(&self) -> Option<usize> {
-        let mut idx = 0;
-        while idx < MAX_PROC {
-            if self.process_table[idx].is_none() {
-                return Some(idx);
-            }
-            idx += 1;
-        }
-        None
+        assert!(matches!(
+            libc::mmap(&mut kernel, pid, None, 4096, protection),
+            Err(KernelError::SecurityViolation(_))
+        ));
     }
 
-    fn find_free_thread_slot(&self) -> Option<usize> {
-        let mut idx = 0usize;
-        while idx < Self::THREAD_CAPACITY {
-            if self.thread_table[idx].is_none() {
-                return Some(idx);
-            }
-            idx += 1;
-        }
-        None
-    }
+    #[test]
+    fn mmap_rejects_executable_mapping_for_unprivileged_process() {
+        let mut kernel = boot_kernel();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let user = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let protection = MemoryProtection::from_bits(PROT_READ | PROT_EXECUTE);
 
-    fn locate_process(&self, pid: ProcessId) -> KernelResult<usize> {
-        let mut idx = 0;
-        while idx < MAX_PROC {
-            if let Some(pcb) = &self.process_table[idx] {
-                if pcb.pid == pid {
-                    return Ok(idx);
-                }
-            }
-            idx += 1;
-        }
-        Err(KernelError::UnknownProcess)
+        assert!(matches!(
+            libc::mmap(&mut kernel, user, None, 4096, protection),
+            Err(KernelError::SecurityViolation(_))
+        ));
     }
 
-    fn locate_thread(&self, thread: ThreadId) -> KernelResult<usize> {
-        let mut idx = 0usize;
-        while idx < Self::THREAD_CAPACITY {
-            if let Some(tcb) = &self.thread_table[idx] {
-                if tcb.id == thread {
-                    return Ok(idx);
-                }
-            }
-            idx += 1;
+    #[test]
+    fn scratch_arenas_reset_every_tick_and_keep_high_water_mark() {
+        let mut kernel = boot_kernel();
+        {
+            let mut scratch = kernel.scratch_context(0).expect("core 0 has an arena");
+            assert!(scratch.alloc(512, 8).is_some());
+            assert!(scratch.alloc(SCRATCH_ARENA_BYTES, 1).is_none());
         }
-        Err(KernelError::UnknownThread)
-    }
-
-    fn allocate_pid(&mut self) -> ProcessId {
-        let pid = ProcessId::new(self.next_pid);
-        self.next_pid += 1;
-        pid
-    }
+        assert!(kernel.scratch_context(cpu::MAX_CORES).is_none());
 
-    fn allocate_thread_id(&mut self) -> ThreadId {
-        let id = ThreadId::new(self.next_thread);
-        self.next_thread += 1;
-        id
-    }
+        kernel.tick();
 
-    fn next_message_sequence(&mut self) -> u64 {
-        let seq = self.message_sequence;
-        self.message_sequence = self.message_sequence.wrapping_add(1);
-        seq
+        let scratch = kernel.scratch_context(0).unwrap();
+        assert_eq!(scratch.remaining(), SCRATCH_ARENA_BYTES);
+        assert_eq!(kernel.scratch_high_water_mark(0), Some(512));
     }
 
-    pub fn enumerate_devices(&self, out: &mut [DeviceDescriptor]) -> usize {
-        self.devices.enumerate(out)
-    }
+    #[test]
+    fn undersized_security_table_reports_security_table_full() {
+        let mut kernel = boot_kernel();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let mut phantom = 10_000u64;
+        while kernel
+            .security
+            .register_task(ProcessId::new(phantom), Credentials::user())
+            .is_ok()
+        {
+            phantom += 1;
+        }
+        let free_slots_before = kernel
+            .process_table
+            .iter()
+            .filter(|slot| slot.is_none())
+            .count();
 
-    pub fn device_info(&self, id: DeviceId) -> Option<DeviceDescriptor> {
-        self.devices.descriptor(id)
+        assert!(matches!(
+            kernel.spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user()),
+            Err(KernelError::SecurityTableFull)
+        ));
+        assert_eq!(
+            kernel
+                .process_table
+                .iter()
+                .filter(|slot| slot.is_none())
+                .count(),
+            free_slots_before
+        );
+        assert_eq!(
+            syscall_error_code(KernelError::SecurityTableFull),
+            SyscallErrorCode::ProcessTableFull
+        );
     }
 
-    pub fn device_read(
-        &self,
-        pid: ProcessId,
-        id: DeviceId,
-        buffer: &mut [u8],
-    ) -> KernelResult<usize> {
-        let descriptor = self
-            .devices
-            .descriptor(id)
-            .ok_or(KernelError::DeviceNotFound)?;
-
-        self.security
-            .authorize_device_access(
+    #[test]
+    fn stack_guard_scan_resolves_hits_by_stack_policy() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let root = 0x5354_4b47_0000;
+        let _mappings = memory::TestUserMappings(root);
+        set_address_space_root(&mut kernel, pid, root);
+        let survivor = first_thread(&kernel, pid);
+        let faulting = kernel
+            .spawn_thread(pid, 0x1000, ProcessPriority::Normal)
+            .unwrap();
+        let growing = kernel
+            .spawn_thread_with_stack_policy(
                 pid,
-                CapabilityObject::PciDevice(descriptor.id.raw() as u64),
-                CapabilityRight::Read,
-                descriptor.security,
+                0x1000,
+                ProcessPriority::Normal,
+                StackPolicy::Grow {
+                    max_bytes: 4 * memory::PAGE_SIZE as u32,
+                },
             )
-            .map_err(KernelError::SecurityViolation)?;
-        if !self.service_registry.claimed_by(pid, id) {
-            return Err(KernelError::SecurityViolation(
-                IsolationError::PolicyViolation,
-            ));
-        }
-
-        self.devices
-            .read(id, buffer)
-            .map_err(KernelError::DeviceFault)
-    }
-
-    pub fn device_write(&self, pid: ProcessId, id: DeviceId, data: &[u8]) -> KernelResult<usize> {
-        let descriptor = self
-            .devices
-            .descriptor(id)
-            .ok_or(KernelError::DeviceNotFound)?;
-
-        self.security
-            .authorize_device_access(
+            .unwrap();
+        let guard_of = |kernel: &Kernel<16, 4>, thread: ThreadId| {
+            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
+                .unwrap()
+                .stack_guard_page()
+                .unwrap()
+        };
+        let guards = [faulting, growing].map(|thread| guard_of(&kernel, thread));
+        for guard in guards {
+            memory::install_test_user_mapping(
                 pid,
-                CapabilityObject::PciDevice(descriptor.id.raw() as u64),
-                CapabilityRight::Write,
-                descriptor.security,
+                root,
+                guard,
+                memory::PAGE_SIZE,
+                MemoryProtection::read_write(),
             )
-            .map_err(KernelError::SecurityViolation)?;
-        if !self.service_registry.claimed_by(pid, id) {
-            return Err(KernelError::SecurityViolation(
-                IsolationError::PolicyViolation,
-            ));
+            .expect("guard page maps");
+            assert!(memory::copy_to_user(root, guard, &[0]));
         }
+        assert_eq!(kernel.check_stack_guards(), 0);
 
-        self.devices
-            .write(id, data)
-            .map_err(KernelError::DeviceFault)
-    }
-}
+        for guard in guards {
+            assert!(memory::copy_to_user(root, guard, &[0x5a]));
+        }
+        assert_eq!(kernel.check_stack_guards(), 2);
+        assert_eq!(kernel.check_stack_guards(), 0);
 
-fn exec_vector_metadata(ptr: u64, max_entries: usize) -> KernelResult<ExecVectorMetadata> {
-    if ptr == 0 {
-        return Ok(ExecVectorMetadata::empty());
+        assert!(kernel.locate_thread(faulting).is_err());
+        assert!(kernel.locate_thread(survivor).is_ok());
+        assert_eq!(
+            guard_of(&kernel, growing),
+            guards[1] - memory::PAGE_SIZE as u64
+        );
+        let dump = kernel.fault_dumps.take().unwrap();
+        assert_eq!(dump.reason, IsolationError::StackOverflow);
+        assert!(dump.threads.iter().flatten().any(|t| t.id == faulting));
+        assert!(kernel.fault_dumps.is_empty());
+        let process_index = kernel.locate_process(pid).unwrap();
+        assert_eq!(
+            kernel.process_table[process_index]
+                .as_mut()
+                .unwrap()
+                .take_deliverable_signal(SignalMask::EMPTY),
+            None
+        );
+        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+        assert_kernel_consistent(&kernel);
     }
 
-    let entries = user_slice_typed::<u64>(ptr, max_entries + 1)?;
-    let mut count = 0usize;
-    while count < entries.len() {
-        if entries[count] == 0 {
-            return Ok(ExecVectorMetadata::new(ptr, count, false));
-        }
-        count += 1;
+    fn set_address_space_root<const P: usize, const D: usize>(
+        kernel: &mut Kernel<P, D>,
+        pid: ProcessId,
+        root: u64,
+    ) {
+        let index = kernel.locate_process(pid).unwrap();
+        kernel.process_table[index]
+            .as_mut()
+            .unwrap()
+            .address_space_root = root;
     }
 
-    Ok(ExecVectorMetadata::new(ptr, max_entries, true))
-}
+    #[test]
+    fn stack_fault_under_fault_policy_kills_only_that_thread() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        set_address_space_root(&mut kernel, pid, 0x5354_4b46_0000);
+        let survivor = first_thread(&kernel, pid);
+        let faulting = kernel
+            .spawn_thread(pid, 0x1000, ProcessPriority::Normal)
+            .unwrap();
+        let tcb = kernel.thread_table[kernel.locate_thread(faulting).unwrap()].unwrap();
+        assert_eq!(tcb.stack_policy, StackPolicy::Fault);
+        let guard = tcb.stack_guard_page().unwrap();
 
-fn signed_exec_manifest_for_path(
-    path: &str,
-) -> (Option<ExecServiceDaemon>, Option<ExecSignatureMetadata>) {
-    match path {
-        "/bin/displayd" | "/sbin/displayd" | "/displayd" => (
-            Some(ExecServiceDaemon::Display),
-            Some(ExecSignatureMetadata::new(
-                "mirage-service-root",
-                0x444953504c415944,
-            )),
-        ),
-        "/bin/networkd" | "/sbin/networkd" | "/networkd" => (
-            Some(ExecServiceDaemon::Network),
-            Some(ExecSignatureMetadata::new(
-                "mirage-service-root",
-                0x4e4554574f524b44,
-            )),
-        ),
-        "/bin/inputd" | "/sbin/inputd" | "/inputd" => (
-            Some(ExecServiceDaemon::Input),
-            Some(ExecSignatureMetadata::new(
-                "mirage-service-root",
-                0x494e50555444414d,
-            )),
-        ),
-        "/bin/l2-driverd" | "/sbin/l2-driverd" | "/l2-driverd" => (
-            Some(ExecServiceDaemon::L2Driver),
-            Some(ExecSignatureMetadata::new(
-                "mirage-driver-root",
-                0x4c32445249564552,
-            )),
-        ),
-        _ => (None, None),
+        assert_eq!(
+            kernel.handle_stack_fault(faulting, guard + 8).unwrap(),
+            StackFaultOutcome::Overflow
+        );
+
+        assert!(kernel.locate_thread(faulting).is_err());
+        assert!(kernel.locate_thread(survivor).is_ok());
+        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+        let dump = kernel.fault_dumps.take().unwrap();
+        assert_eq!(dump.process.pid, pid);
+        assert_eq!(dump.reason, IsolationError::StackOverflow);
+        assert!(dump.threads.iter().flatten().any(|t| t.id == faulting));
+        assert!(kernel.fault_dumps.is_empty());
+        assert_kernel_consistent(&kernel);
     }
-}
 
-fn map_file_table_error(error: FileTableError) -> KernelError {
-    match error {
-        FileTableError::Full => KernelError::FileTableFull,
-        FileTableError::InvalidDescriptor => KernelError::Filesystem(VfsError::InvalidHandle),
+    #[test]
+    fn grow_policy_extends_stack_page_by_page() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let root = 0x5354_4b47_1000;
+        let _mappings = memory::TestUserMappings(root);
+        set_address_space_root(&mut kernel, pid, root);
+        assert!(matches!(
+            kernel.spawn_thread_with_stack_policy(
+                pid,
+                0x1000,
+                ProcessPriority::Normal,
+                StackPolicy::Grow { max_bytes: 100 },
+            ),
+            Err(KernelError::InvalidArgument)
+        ));
+        let policy = StackPolicy::Grow {
+            max_bytes: 4 * memory::PAGE_SIZE as u32,
+        };
+        let thread = kernel
+            .spawn_thread_with_stack_policy(pid, 0x1000, ProcessPriority::Normal, policy)
+            .unwrap();
+        let index = kernel.locate_thread(thread).unwrap();
+        let top = kernel.thread_table[index].unwrap().stack_pointer;
+        assert!(matches!(
+            kernel.handle_stack_fault(thread, top),
+            Err(KernelError::InvalidArgument)
+        ));
+
+        let mut pages = 1u64;
+        while pages <= 3 {
+            let guard = kernel.thread_table[index]
+                .unwrap()
+                .stack_guard_page()
+                .unwrap();
+            assert_eq!(guard, top - pages * memory::PAGE_SIZE as u64);
+            assert_eq!(
+                kernel.handle_stack_fault(thread, guard).unwrap(),
+                StackFaultOutcome::Grown {
+                    guard: guard - memory::PAGE_SIZE as u64
+                }
+            );
+            let region = memory::find_user_mapping(root, guard, memory::PAGE_SIZE, true)
+                .expect("grown page is mapped writable");
+            let ptr = NonNull::new(region.as_ptr()).unwrap();
+            let info = memory::allocation_info_for(pid, ptr).unwrap();
+            assert_eq!((info.owner, info.size), (pid, memory::PAGE_SIZE));
+            pages += 1;
+        }
+
+        let tcb = kernel.thread_table[index].unwrap();
+        assert_eq!(tcb.stack_grown, 3 * memory::PAGE_SIZE as u32);
+        assert!(!tcb.stack_guard_hit);
     }
-}
 
-fn map_process_file_table_error(error: ProcessFileTableError) -> KernelError {
-    match error {
-        ProcessFileTableError::Full => KernelError::FileTableFull,
-        ProcessFileTableError::InvalidDescriptor => {
-            KernelError::Filesystem(VfsError::InvalidHandle)
+    #[test]
+    fn grow_policy_past_its_cap_falls_back_to_fault() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let root = 0x5354_4b47_2000;
+        let _mappings = memory::TestUserMappings(root);
+        set_address_space_root(&mut kernel, pid, root);
+        let thread = kernel
+            .spawn_thread_with_stack_policy(
+                pid,
+                0x1000,
+                ProcessPriority::Normal,
+                StackPolicy::Grow {
+                    max_bytes: 2 * memory::PAGE_SIZE as u32,
+                },
+            )
+            .unwrap();
+        let guard_of = |kernel: &Kernel<16, 4>| {
+            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
+                .unwrap()
+                .stack_guard_page()
+                .unwrap()
+        };
+
+        for _ in 0..2 {
+            let guard = guard_of(&kernel);
+            assert!(matches!(
+                kernel.handle_stack_fault(thread, guard),
+                Ok(StackFaultOutcome::Grown { .. })
+            ));
         }
+        let guard = guard_of(&kernel);
+        assert_eq!(
+            kernel.handle_stack_fault(thread, guard).unwrap(),
+            StackFaultOutcome::Overflow
+        );
+        assert!(kernel.locate_thread(thread).is_err());
+        assert!(memory::find_user_mapping(root, guard, memory::PAGE_SIZE, false).is_none());
     }
-}
 
-fn map_path_error(error: PathError) -> KernelError {
-    KernelError::Filesystem(VfsError::InvalidPath(error))
-}
+    #[test]
+    fn page_fault_trap_on_the_guard_page_runs_the_stack_policy() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let root = 0x5354_4b47_3000;
+        let _mappings = memory::TestUserMappings(root);
+        set_address_space_root(&mut kernel, pid, root);
+        let thread = first_thread(&kernel, pid);
+        let index = kernel.locate_thread(thread).unwrap();
+        kernel.thread_table.entry_mut(index).unwrap().stack_policy = StackPolicy::Grow {
+            max_bytes: memory::PAGE_SIZE as u32,
+        };
+        let fault_on_guard = |kernel: &mut Kernel<16, 4>| {
+            let tcb = kernel.thread_table.entry_mut(index).unwrap();
+            let guard = tcb.stack_guard_page().unwrap();
+            tcb.context.trap_vector = u64::from(x86_64::idt::PAGE_FAULT_VECTOR);
+            x86_64::idt::record_page_fault_address(guard + 8);
+            kernel.run_core(0);
+            guard
+        };
 
-fn map_service_registry_error(error: ServiceRegistryError) -> KernelError {
-    match error {
-        ServiceRegistryError::Full => KernelError::ProcessTableFull,
-        ServiceRegistryError::AlreadyRegistered
-        | ServiceRegistryError::DeviceAlreadyClaimed
-        | ServiceRegistryError::DeviceClassMismatch
-        | ServiceRegistryError::NotOwner => KernelError::InvalidArgument,
-        ServiceRegistryError::NotRegistered => KernelError::UnknownProcess,
-        ServiceRegistryError::DeviceNotClaimed => KernelError::DeviceNotFound,
-    }
-}
+        let guard = fault_on_guard(&mut kernel);
+        let tcb = kernel.thread_table[index].unwrap();
+        assert_eq!(tcb.stack_grown, memory::PAGE_SIZE as u32);
+        assert_eq!(tcb.context.trap_vector, 0);
+        assert!(memory::find_user_mapping(root, guard, memory::PAGE_SIZE, true).is_some());
 
-fn decode_registry_service_id(raw: u64) -> KernelResult<RegistryServiceId> {
-    RegistryServiceId::from_raw(raw).ok_or(KernelError::InvalidArgument)
-}
+        fault_on_guard(&mut kernel);
+        assert!(kernel.locate_thread(thread).is_err());
+        assert_kernel_consistent(&kernel);
+    }
 
-fn validate_tls_base(base: u64) -> KernelResult<()> {
-    if base < USER_CANONICAL_LIMIT {
-        Ok(())
-    } else {
-        Err(KernelError::InvalidArgument)
+    #[test]
+    fn check_stack_faults_in_the_guard_and_passes_inside_the_stack() {
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let thread = kernel
+            .spawn_thread(pid, 0x1000, ProcessPriority::Normal)
+            .unwrap();
+        let page = memory::PAGE_SIZE;
+        assert!(matches!(
+            kernel.set_stack_layout(thread, 4 * page, 0),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert!(matches!(
+            kernel.set_stack_layout(thread, 100, page),
+            Err(KernelError::InvalidArgument)
+        ));
+        kernel.set_stack_layout(thread, 4 * page, 2 * page).unwrap();
+        let top = kernel.thread_table[kernel.locate_thread(thread).unwrap()]
+            .unwrap()
+            .stack_pointer;
+        let base = top - 4 * page as u64;
+
+        for sp in [top, top - 16, base] {
+            assert!(kernel.check_stack(thread, sp).is_ok());
+        }
+        for sp in [
+            base - 8,
+            base - 2 * page as u64,
+            base - 3 * page as u64,
+            top + 8,
+        ] {
+            assert!(matches!(
+                kernel.check_stack(thread, sp),
+                Err(KernelError::IsolationFault(IsolationError::StackOverflow))
+            ));
+        }
+        // Faults anywhere in the wider guard are stack faults.
+        assert_eq!(
+            kernel
+                .handle_stack_fault(thread, base - 2 * page as u64)
+                .unwrap(),
+            StackFaultOutcome::Overflow
+        );
     }
-}
 
-fn active_user_root() -> u64 {
-    x86_64::paging::current_address_space_root()
-}
+    #[test]
+    fn dispatch_terminates_a_thread_whose_stack_pointer_is_in_the_guard() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        set_address_space_root(&mut kernel, pid, 0x5354_4b43_0000);
+        let healthy = first_thread(&kernel, pid);
+        let overflowed = kernel
+            .spawn_thread(pid, 0x1000, ProcessPriority::Critical)
+            .unwrap();
+        for thread in [healthy, overflowed] {
+            kernel
+                .set_stack_layout(thread, 8 * memory::PAGE_SIZE, memory::PAGE_SIZE)
+                .unwrap();
+        }
+        let index = kernel.locate_thread(overflowed).unwrap();
+        let tcb = kernel.thread_table.entry_mut(index).unwrap();
+        tcb.context.rsp = tcb.stack_base().unwrap() - 64;
 
-fn validate_user_range(ptr: u64, len: usize) -> KernelResult<()> {
-    validate_user_access(ptr, len, false)
-}
+        for _ in 0..8 {
+            kernel.run_core(0);
+        }
 
-fn validate_user_access(ptr: u64, len: usize, write: bool) -> KernelResult<()> {
-    if len == 0 {
-        return Ok(());
-    }
-    if ptr == 0 {
-        return Err(KernelError::InvalidPointer);
-    }
-    ptr.checked_add(len as u64)
-        .filter(|end| *end >= ptr && *end <= USER_CANONICAL_LIMIT)
-        .ok_or(KernelError::InvalidPointer)?;
-    if !x86_64::paging::installed() || active_user_root() == 0 {
-        return Ok(());
-    }
-    if !memory::validate_user_range(active_user_root(), ptr, len, write) {
-        return Err(KernelError::InvalidPointer);
+        assert!(kernel.locate_thread(overflowed).is_err());
+        assert!(kernel.locate_thread(healthy).is_ok());
+        assert_kernel_consistent(&kernel);
     }
-    Ok(())
-}
 
-fn user_translated_ptr(ptr: u64, len: usize, write: bool) -> KernelResult<NonNull<u8>> {
-    validate_user_access(ptr, len, write)?;
-    if len == 0 {
-        return Ok(NonNull::dangling());
-    }
-    if !x86_64::paging::installed() || active_user_root() == 0 {
-        return NonNull::new(ptr as *mut u8).ok_or(KernelError::InvalidPointer);
+    fn cross_checked_kernel() -> (Box<Kernel<16, 4>>, ProcessId, ThreadId) {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let worker = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let extra = kernel
+            .spawn_thread(worker, 0x1000, ProcessPriority::Normal)
+            .unwrap();
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = 0x5843_4b00_0000;
+        }
+        kernel.set_scheduler_cross_check(true);
+        (kernel, worker, extra)
     }
-    memory::active_translated_slice(active_user_root(), ptr, len, write)
-        .ok_or(KernelError::InvalidPointer)
-}
 
-fn user_slice(ptr: u64, len: usize) -> KernelResult<&'static [u8]> {
-    let translated = user_translated_ptr(ptr, len, false)?;
-    if len == 0 {
-        Ok(&[])
-    } else {
-        Ok(unsafe { core::slice::from_raw_parts(translated.as_ptr(), len) })
+    fn assertion_codes(kernel: &mut Kernel<16, 4>) -> Vec<AssertionCode> {
+        core::iter::from_fn(|| kernel.take_assertion())
+            .map(|assertion| assertion.code)
+            .collect()
     }
-}
 
-fn user_slice_mut(ptr: u64, len: usize) -> KernelResult<&'static mut [u8]> {
-    let translated = user_translated_ptr(ptr, len, true)?;
-    if len == 0 {
-        Ok(&mut [])
-    } else {
-        Ok(unsafe { core::slice::from_raw_parts_mut(translated.as_ptr(), len) })
-    }
-}
+    #[test]
+    fn scheduler_cross_check_is_quiet_on_a_healthy_kernel() {
+        let (mut kernel, worker, extra) = cross_checked_kernel();
+        kernel.run_ticks(20);
+        kernel.block_thread(extra).unwrap();
+        kernel.run_ticks(5);
+        kernel.wake_thread(extra).unwrap();
+        kernel.run_ticks(5);
+        kernel.exit_process(worker, ExitStatus::exited(0));
+        kernel.run_ticks(5);
 
-fn user_slice_typed<T>(ptr: u64, count: usize) -> KernelResult<&'static [T]> {
-    let byte_len = count
-        .checked_mul(core::mem::size_of::<T>())
-        .ok_or(KernelError::InvalidPointer)?;
-    if count == 0 {
-        return Ok(&[]);
-    }
-    if !(ptr as *const T).is_aligned() {
-        return Err(KernelError::InvalidPointer);
-    }
-    let translated = user_translated_ptr(ptr, byte_len, false)?;
-    if !(translated.as_ptr() as *const T).is_aligned() {
-        return Err(KernelError::InvalidPointer);
+        assert_eq!(kernel.cross_check_scheduler(), 0);
+        assert!(kernel.assertions().is_empty());
     }
-    Ok(unsafe { core::slice::from_raw_parts(translated.as_ptr() as *const T, count) })
-}
 
-fn user_slice_mut_typed<T>(ptr: u64, count: usize) -> KernelResult<&'static mut [T]> {
-    let byte_len = count
-        .checked_mul(core::mem::size_of::<T>())
-        .ok_or(KernelError::InvalidPointer)?;
-    if count == 0 {
-        return Ok(&mut []);
-    }
-    if !(ptr as *mut T).is_aligned() {
-        return Err(KernelError::InvalidPointer);
-    }
-    let translated = user_translated_ptr(ptr, byte_len, true)?;
-    if !(translated.as_ptr() as *mut T).is_aligned() {
-        return Err(KernelError::InvalidPointer);
-    }
-    Ok(unsafe { core::slice::from_raw_parts_mut(translated.as_ptr() as *mut T, count) })
-}
+    #[test]
+    fn scheduler_cross_check_catches_a_double_enqueue() {
+        let (mut kernel, _, _) = cross_checked_kernel();
+        kernel.run_ticks(3);
+        let queued = kernel.mtss_scheduler.queued_thread_ids().next().unwrap();
+        let thread = ThreadId::new(queued.raw());
+        kernel.mtss_enqueue_thread(thread).unwrap();
 
-fn user_out_ptr<T>(ptr: u64) -> KernelResult<*mut T> {
-    if !(ptr as *mut T).is_aligned() {
-        return Err(KernelError::InvalidPointer);
-    }
-    let translated = user_translated_ptr(ptr, core::mem::size_of::<T>(), true)?;
-    if !(translated.as_ptr() as *mut T).is_aligned() {
-        return Err(KernelError::InvalidPointer);
+        kernel.tick();
+        let assertion = kernel.take_assertion().unwrap();
+        assert_eq!(assertion.code, AssertionCode::QueuedThreadDuplicated);
+        assert_eq!(assertion.thread, Some(thread));
+        assert_eq!(assertion.found, Some(ThreadState::Ready));
     }
-    Ok(translated.as_ptr() as *mut T)
-}
 
-fn read_user_value<T: Copy>(ptr: u64) -> KernelResult<T> {
-    if !(ptr as *const T).is_aligned() {
-        return Err(KernelError::InvalidPointer);
-    }
-    let mut value = core::mem::MaybeUninit::<T>::uninit();
-    let out = unsafe {
-        core::slice::from_raw_parts_mut(value.as_mut_ptr() as *mut u8, core::mem::size_of::<T>())
-    };
-    if !x86_64::paging::installed() || active_user_root() == 0 {
-        unsafe { core::ptr::copy_nonoverlapping(ptr as *const u8, out.as_mut_ptr(), out.len()) };
-    } else if !memory::copy_from_user(active_user_root(), ptr, out) {
-        return Err(KernelError::InvalidPointer);
-    }
-    Ok(unsafe { value.assume_init() })
-}
+    #[test]
+    fn scheduler_cross_check_catches_a_lost_ready_thread() {
+        let (mut kernel, worker, extra) = cross_checked_kernel();
+        kernel.run_ticks(3);
+        // Pull the thread out of MTSS behind the thread table's back.
+        let _ = kernel
+            .mtss_scheduler
+            .block_thread(Kernel::<16, 4>::mtss_thread_id(extra));
+        if kernel
+            .pending_mtss_decision
+            .is_some_and(|d| d.thread == extra)
+        {
+            kernel.pending_mtss_decision = None;
+        }
 
-fn write_user_value<T: Copy>(ptr: u64, value: T) -> KernelResult<()> {
-    if !(ptr as *const T).is_aligned() {
-        return Err(KernelError::InvalidPointer);
+        kernel.tick();
+        let assertion = kernel.take_assertion().unwrap();
+        assert_eq!(assertion.code, AssertionCode::ReadyThreadUnscheduled);
+        assert_eq!(
+            (assertion.thread, assertion.process),
+            (Some(extra), Some(worker))
+        );
     }
-    let input = unsafe {
-        core::slice::from_raw_parts((&value as *const T) as *const u8, core::mem::size_of::<T>())
-    };
-    if !x86_64::paging::installed() || active_user_root() == 0 {
-        unsafe { core::ptr::copy_nonoverlapping(input.as_ptr(), ptr as *mut u8, input.len()) };
-    } else if !memory::copy_to_user(active_user_root(), ptr, input) {
-        return Err(KernelError::InvalidPointer);
+
+    #[test]
+    fn scheduler_cross_check_catches_queue_entries_for_dead_or_blocked_threads() {
+        let (mut kernel, worker, extra) = cross_checked_kernel();
+        let sibling = first_thread(&kernel, worker);
+
+        let index = kernel.locate_thread(sibling).unwrap();
+        kernel.thread_table.entry_mut(index).unwrap().block();
+        let index = kernel.locate_thread(extra).unwrap();
+        kernel.thread_table.remove_at(index);
+        let process = kernel.locate_process(worker).unwrap();
+        kernel.process_table[process]
+            .as_mut()
+            .unwrap()
+            .decrement_thread_count();
+
+        kernel.tick();
+        let codes = assertion_codes(&mut kernel);
+        assert!(codes.contains(&AssertionCode::QueuedThreadMissing));
+        assert!(codes.contains(&AssertionCode::QueuedThreadNotReady));
+        assert!(!codes.contains(&AssertionCode::ThreadCountMismatch));
     }
-    Ok(())
-}
 
-fn timespec_to_nanos(timespec: MirageTimespec) -> KernelResult<u128> {
-    if timespec.tv_sec < 0 || timespec.tv_nsec < 0 || timespec.tv_nsec >= 1_000_000_000 {
-        return Err(KernelError::InvalidArgument);
+    #[test]
+    fn scheduler_cross_check_catches_thread_count_drift() {
+        let (mut kernel, worker, _) = cross_checked_kernel();
+        let process = kernel.locate_process(worker).unwrap();
+        kernel.process_table[process]
+            .as_mut()
+            .unwrap()
+            .increment_thread_count();
+
+        kernel.tick();
+        let assertion = kernel.take_assertion().unwrap();
+        assert_eq!(assertion.code, AssertionCode::ThreadCountMismatch);
+        assert_eq!(assertion.process, Some(worker));
+        assert_eq!(assertion.counts, Some((3, 2)));
     }
-    let seconds = timespec.tv_sec as u128;
-    let nanos = timespec.tv_nsec as u128;
-    seconds
-        .checked_mul(1_000_000_000)
-        .and_then(|value| value.checked_add(nanos))
-        .ok_or(KernelError::InvalidArgument)
-}
 
-fn nanos_to_timespec(nanos: u128) -> MirageTimespec {
-    MirageTimespec {
-        tv_sec: (nanos / 1_000_000_000) as i64,
-        tv_nsec: (nanos % 1_000_000_000) as i64,
+    #[test]
+    fn scheduler_cross_check_is_a_no_op_when_disabled() {
+        let (mut kernel, worker, extra) = cross_checked_kernel();
+        kernel.set_scheduler_cross_check(false);
+        kernel.mtss_enqueue_thread(extra).unwrap();
+        let process = kernel.locate_process(worker).unwrap();
+        kernel.process_table[process]
+            .as_mut()
+            .unwrap()
+            .increment_thread_count();
+
+        kernel.tick();
+        assert!(kernel.assertions().is_empty());
     }
-}
 
-fn descriptor_flags_from_raw(flags: u64) -> DescriptorFlags {
-    if flags & (O_CLOEXEC_RAW | EFD_CLOEXEC) != 0 {
-        DescriptorFlags::CLOSE_ON_EXEC
-    } else {
-        DescriptorFlags::EMPTY
+    #[test]
+    fn runnable_thread_past_its_deadline_records_one_miss() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        set_address_space_root(&mut kernel, pid, 0x4544_4c00_0000);
+        let thread = first_thread(&kernel, pid);
+
+        // The thread never blocks, so it cannot finish within one tick.
+        kernel.set_deadline(thread, kernel.mtss_ticks + 1).unwrap();
+        kernel.run_ticks(5);
+        assert_eq!(kernel.deadline_misses(thread).unwrap(), 1);
+
+        kernel.set_deadline(thread, kernel.mtss_ticks).unwrap();
+        kernel.run_ticks(1);
+        assert_eq!(kernel.deadline_misses(thread).unwrap(), 2);
     }
-}
 
-fn timer_to_itimerspec(
-    timer: crate::kernel::timer::ProcessTimer,
-    now_ns: u128,
-) -> MirageItimerspec {
-    let remaining = if timer.armed {
-        timer.wake_deadline_ns.saturating_sub(now_ns)
-    } else {
-        0
-    };
-    MirageItimerspec {
-        it_interval: nanos_to_timespec(timer.interval_ns),
-        it_value: nanos_to_timespec(remaining),
+    #[test]
+    fn thread_that_blocks_before_its_deadline_records_no_miss() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        set_address_space_root(&mut kernel, pid, 0x4544_4c01_0000);
+        let thread = first_thread(&kernel, pid);
+
+        kernel.set_deadline(thread, kernel.mtss_ticks + 5).unwrap();
+        kernel.run_ticks(2);
+        kernel.block_thread(thread).unwrap();
+        kernel.run_ticks(10);
+
+        assert_eq!(kernel.deadline_misses(thread).unwrap(), 0);
+        kernel.terminate_thread(thread);
+        assert!(matches!(
+            kernel.set_deadline(thread, 1),
+            Err(KernelError::UnknownThread)
+        ));
     }
-}
 
-fn map_timer_error(error: TimerError) -> KernelError {
-    match error {
-        TimerError::Full => KernelError::AllocationFailed,
-        TimerError::InvalidTimer => KernelError::InvalidArgument,
+    #[test]
+    fn tick_runs_stack_guard_scan_on_interval() {
+        let mut kernel = boot_kernel();
+        kernel.stack_guard_countdown = 2;
+
+        kernel.tick();
+        assert_eq!(kernel.stack_guard_countdown, 1);
+        kernel.tick();
+        assert_eq!(kernel.stack_guard_countdown, STACK_GUARD_CHECK_INTERVAL);
     }
-}
 
-#[cfg(test)]
-fn is_test_spawn_path(path: &str) -> bool {
-    matches!(path, "/spider-rt/sbin/spider-rsd" | "/usr/bin/m1-terminal")
-}
+    #[test]
+    fn tick_drains_timer_softirq_once_per_tick() {
+        let mut kernel = boot_kernel();
 
-fn copy_user_path_with_len(ptr: u64, len: usize) -> KernelResult<KernelPathBuf> {
-    if len == 0 || len > MAX_PATH_BYTES {
-        return Err(KernelError::Filesystem(VfsError::InvalidPath(
-            PathError::TooLong,
-        )));
+        kernel.tick();
+        kernel.tick();
+
+        let timer = kernel.softirq_stats(SoftirqKind::Timer);
+        assert_eq!((timer.raised, timer.ran, timer.overran), (2, 2, 0));
+        assert_eq!(kernel.softirq_stats(SoftirqKind::DeviceReady).raised, 0);
+        assert!(!kernel.softirqs.is_pending(SoftirqKind::Timer));
     }
-    validate_user_range(ptr, len)?;
-    let mut bytes = [0u8; MAX_PATH_BYTES];
-    if !x86_64::paging::installed() || active_user_root() == 0 {
-        unsafe { core::ptr::copy_nonoverlapping(ptr as *const u8, bytes.as_mut_ptr(), len) };
-    } else if !memory::copy_from_user(active_user_root(), ptr, &mut bytes[..len]) {
-        return Err(KernelError::InvalidPointer);
+
+    fn clock_ns(ticks: u64) -> u128 {
+        MonotonicTimestamp::new(ticks, clock::HARDWARE_CLOCK.frequency()).as_nanos()
     }
-    if bytes[..len].contains(&0) {
-        return Err(KernelError::Filesystem(VfsError::InvalidPath(
-            PathError::InvalidByte,
-        )));
+
+    fn sleep_until(kernel: &mut Kernel<16, 4>, pid: ProcessId, deadline_ns: u128) {
+        kernel.timers.add_sleep(pid, None, deadline_ns).unwrap();
+        let index = kernel.locate_process(pid).unwrap();
+        kernel.block_process_at_index(pid, index);
     }
-    let raw = core::str::from_utf8(&bytes[..len])
-        .map_err(|_| KernelError::Filesystem(VfsError::InvalidPath(PathError::InvalidByte)))?;
-    KernelPathBuf::from_str(raw)
-}
 
-fn exec_vector_metadata_with_count(
-    base: u64,
-    count: usize,
-    max: usize,
-) -> KernelResult<ExecVectorMetadata> {
-    if count > max {
-        return Err(KernelError::InvalidArgument);
+    #[test]
+    fn forward_clock_jump_fires_due_sleeps_and_timers_once() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let sleeper = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let ticker = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let now = clock::HARDWARE_CLOCK.now();
+        sleep_until(&mut kernel, sleeper, clock_ns(now + 100));
+        let timer = kernel.timers.create_timer(ticker).unwrap();
+        kernel
+            .timers
+            .set_timer(ticker, timer, Some(clock_ns(now + 50)), clock_ns(10))
+            .unwrap();
+        let index = kernel.locate_process(ticker).unwrap();
+        kernel.block_process_at_index(ticker, index);
+
+        let jump = kernel.adjust_clock(ClockAdjustment::Resume { slept_ticks: 1_000 });
+
+        assert!(jump.mediated && !jump.is_backward());
+        assert_eq!(kernel.last_clock_discontinuity(), Some(jump));
+        assert_ne!(process_state(&kernel, sleeper), ProcessState::Blocked);
+        assert_ne!(process_state(&kernel, ticker), ProcessState::Blocked);
+        // The periodic timer fired once and re-armed one period after the
+        // jump instead of replaying every period it slept through.
+        let rearmed = kernel.timers.timer(ticker, timer).unwrap();
+        assert!(rearmed.armed);
+        assert!(rearmed.wake_deadline_ns > clock_ns(jump.new_ticks));
+        assert!(rearmed.wake_deadline_ns <= clock_ns(jump.new_ticks + 10));
+        assert_eq!(
+            kernel.timers.expired_for(ticker, clock_ns(jump.new_ticks)),
+            0
+        );
+        assert_eq!(kernel.timers.expired_for(sleeper, u128::MAX), 0);
     }
-    if count == 0 {
-        return Ok(ExecVectorMetadata::empty());
+
+    #[test]
+    fn backward_clock_jump_keeps_remaining_time_and_fires_overdue_work() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let sleeper = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let overdue = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let start = kernel
+            .adjust_clock(ClockAdjustment::Resume { slept_ticks: 1_000 })
+            .new_ticks;
+        sleep_until(&mut kernel, sleeper, clock_ns(start + 100));
+        // Due already, but the timer softirq has not run since.
+        sleep_until(&mut kernel, overdue, clock_ns(start));
+        kernel.exec_timeout(init, 300).unwrap();
+
+        let jump = kernel.adjust_clock(ClockAdjustment::Reset);
+
+        assert!(jump.is_backward());
+        assert_eq!(kernel.clock_discontinuity_count(), 2);
+        assert_ne!(process_state(&kernel, overdue), ProcessState::Blocked);
+        assert_eq!(process_state(&kernel, sleeper), ProcessState::Blocked);
+        let deadline = kernel.timers.next_deadline().unwrap();
+        assert!(deadline > clock_ns(jump.new_ticks));
+        assert!(deadline <= clock_ns(jump.new_ticks + 100));
+        let index = kernel.locate_process(init).unwrap();
+        let watchdog = kernel.process_table[index]
+            .unwrap()
+            .execution_deadline
+            .unwrap();
+        assert!(watchdog > jump.new_ticks && watchdog <= jump.new_ticks + 300);
+
+        kernel.adjust_clock(ClockAdjustment::Resume { slept_ticks: 100 });
+        assert_ne!(process_state(&kernel, sleeper), ProcessState::Blocked);
+        assert_eq!(kernel.timers.next_deadline(), None);
     }
-    let bytes = count
-        .checked_mul(core::mem::size_of::<u64>())
-        .ok_or(KernelError::InvalidArgument)?;
-    validate_user_range(base, bytes)?;
-    Ok(ExecVectorMetadata::new(base, count, false))
-}
 
-fn user_cstr(ptr: u64) -> KernelResult<&'static [u8]> {
-    validate_user_range(ptr, 1)?;
-    let mut len = 0usize;
-    while len <= MAX_PATH_BYTES {
-        let mut byte = [0u8; 1];
-        if !x86_64::paging::installed() || active_user_root() == 0 {
-            byte[0] = unsafe { ((ptr + len as u64) as *const u8).read() };
-        } else if !memory::copy_from_user(active_user_root(), ptr + len as u64, &mut byte) {
-            return Err(KernelError::InvalidPointer);
-        }
-        if byte[0] == 0 {
-            return user_slice(ptr, len);
+    #[test]
+    fn tick_with_order_changes_which_core_dispatches() {
+        // Returns the thread each core last dispatched, the init and low
+        // priority threads, and whether both ran exactly once.
+        fn run(order: &[usize]) -> ([Option<ThreadId>; 2], ThreadId, ThreadId, bool) {
+            let mut kernel = boot_kernel();
+            kernel.kernel_mtss_init().unwrap();
+            kernel.bring_up_secondary_cores(1);
+            let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+            let low = kernel
+                .spawn_child_process(init, 0, ProcessPriority::Low, Credentials::system())
+                .unwrap();
+            for pcb in kernel.process_table.iter_mut().flatten() {
+                pcb.address_space_root = 0x4f52_0000;
+            }
+            let thread_of = |pid| {
+                kernel
+                    .thread_table
+                    .iter()
+                    .flatten()
+                    .find(|tcb| tcb.process == pid)
+                    .unwrap()
+                    .id
+            };
+            let (init, low) = (thread_of(init), thread_of(low));
+
+            kernel.tick_with_order(order);
+
+            let ran_once = [init, low].iter().all(|thread| {
+                kernel
+                    .thread_table
+                    .iter()
+                    .flatten()
+                    .find(|tcb| tcb.id == *thread)
+                    .is_some_and(|tcb| tcb.cpu_time == 1)
+            });
+            let cores = &kernel.core_states;
+            (
+                [cores[0].last_thread, cores[1].last_thread],
+                init,
+                low,
+                ran_once,
+            )
         }
-        len += 1;
+
+        let (forward, init, low, ran_once) = run(&[0, 1]);
+        assert!(ran_once);
+        assert_eq!(forward, [Some(init), Some(low)]);
+
+        let (reverse, init, low, ran_once) = run(&[1, 0]);
+        assert!(ran_once);
+        assert_eq!(reverse, [Some(low), Some(init)]);
     }
-    Err(KernelError::Filesystem(VfsError::InvalidPath(
-        PathError::TooLong,
-    )))
-}
 
-fn encode_syscall_error(error: KernelError) -> u64 {
-    MIRAGE_SYSCALL_ERROR_BIT | syscall_error_code(error).raw()
-}
+    #[test]
+    fn run_ticks_matches_individual_ticks() {
+        // Builds and runs one kernel per call so only one is on the stack.
+        fn run(batched: bool) -> (Vec<(ProcessState, u128, u16, usize)>, u32) {
+            let mut kernel = boot_kernel();
+            kernel.kernel_mtss_init().unwrap();
+            kernel.bring_up_secondary_cores(1);
+            let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+            kernel
+                .spawn_child_process(init, 0, ProcessPriority::High, Credentials::system())
+                .unwrap();
+            kernel
+                .spawn_child_process(init, 0, ProcessPriority::Low, Credentials::user())
+                .unwrap();
+            for pcb in kernel.process_table.iter_mut().flatten() {
+                pcb.address_space_root = 0x5254_0000;
+            }
 
-fn syscall_error_code(error: KernelError) -> SyscallErrorCode {
-    match error {
-        KernelError::ProcessTableFull => SyscallErrorCode::ProcessTableFull,
-        KernelError::SchedulerFull => SyscallErrorCode::SchedulerFull,
-        KernelError::UnknownProcess => SyscallErrorCode::NoSuchProcess,
-        KernelError::UnknownThread => SyscallErrorCode::NoSuchThread,
-        KernelError::ThreadTableFull => SyscallErrorCode::ThreadTableFull,
-        KernelError::MessageQueueFull => SyscallErrorCode::QueueFull,
-        KernelError::MessageQueueEmpty => SyscallErrorCode::QueueEmpty,
-        KernelError::SecurityViolation(reason) => isolation_syscall_error_code(reason),
-        KernelError::IsolationFault(reason) => isolation_syscall_error_code(reason),
-        KernelError::DeviceNotFound => SyscallErrorCode::NoSuchDevice,
-        KernelError::DeviceFault(_) => SyscallErrorCode::DeviceFault,
-        KernelError::InvalidSyscall => SyscallErrorCode::InvalidSyscall,
-        KernelError::InvalidArgument => SyscallErrorCode::InvalidArgument,
-        KernelError::InvalidPointer => SyscallErrorCode::BadAddress,
-        KernelError::AllocationFailed => SyscallErrorCode::OutOfMemory,
-        KernelError::FileTableFull => SyscallErrorCode::OutOfMemory,
-        KernelError::Filesystem(error) => vfs_syscall_error_code(error),
-        KernelError::TimedOut => SyscallErrorCode::TimedOut,
-        KernelError::Loader(_) => SyscallErrorCode::InvalidArgument,
+            if batched {
+                assert_eq!(kernel.run_ticks(100), 100);
+            } else {
+                for _ in 0..100 {
+                    kernel.tick();
+                }
+            }
+
+            let state = kernel
+                .process_table
+                .iter()
+                .flatten()
+                .map(|pcb| {
+                    let threads = kernel
+                        .thread_table
+                        .iter()
+                        .flatten()
+                        .filter(|tcb| tcb.process == pcb.pid)
+                        .count();
+                    (pcb.state, pcb.cpu_time, pcb.thread_count, threads)
+                })
+                .collect();
+            (state, kernel.stack_guard_countdown)
+        }
+
+        let stepped = run(false);
+        let batched = run(true);
+
+        assert_eq!(batched, stepped);
+        assert!(batched.0.iter().all(|entry| entry.1 > 0));
+    }
+
+    /// Boots a kernel with a client (init) and a server process, both with an
+    /// address space so ticks dispatch them.
+    fn quiesce_pair() -> (Box<Kernel<16, 4>>, ProcessId, ProcessId) {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let client = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let server = kernel
+            .spawn_child_process(client, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = 0x5149_0000;
+        }
+        (kernel, client, server)
     }
-}
 
-fn vfs_syscall_error_code(error: VfsError) -> SyscallErrorCode {
-    syscall_error_code_from_vfs(error)
-}
+    /// Stages `number` on `pid`'s first thread; it runs when next dispatched.
+    fn stage_syscall(
+        kernel: &mut Kernel<16, 4>,
+        pid: ProcessId,
+        number: SyscallNumber,
+        args: [u64; 6],
+    ) {
+        let index = kernel.locate_thread(first_thread(kernel, pid)).unwrap();
+        kernel
+            .thread_table
+            .entry_mut(index)
+            .unwrap()
+            .prepare_syscall(number.raw(), args);
+    }
 
-fn isolation_syscall_error_code(reason: IsolationError) -> SyscallErrorCode {
-    match reason {
-        IsolationError::UnknownTask => SyscallErrorCode::NoSuchProcess,
-        IsolationError::PolicyViolation | IsolationError::CapabilityMissing => {
-            SyscallErrorCode::PermissionDenied
+    #[test]
+    fn ping_pong_protocol_quiesces_after_completion() {
+        let (mut kernel, client, server) = quiesce_pair();
+        let mut inbox = Message::new(
+            client,
+            server,
+            0,
+            MessagePayload::empty(SecurityClass::Public),
+        );
+        let inbox_ptr = &mut inbox as *mut Message as u64;
+        let send = |to: ProcessId, data: &'static [u8]| {
+            [to.raw(), data.as_ptr() as u64, data.len() as u64, 0, 0, 0]
+        };
+
+        // The server waits for a request and the client's ping wakes it; each
+        // step settles before the next is staged.
+        stage_syscall(
+            &mut kernel,
+            server,
+            SyscallNumber::ReceiveOrBlockIpc,
+            [inbox_ptr, 0, 0, 0, 0, 0],
+        );
+        let waiting = kernel.quiesce(32);
+        assert!(waiting.fixed_point);
+        assert_eq!(waiting.blocked_processes, 1);
+        assert!(!waiting.is_quiesced());
+
+        stage_syscall(
+            &mut kernel,
+            client,
+            SyscallNumber::SendIpc,
+            send(server, b"ping"),
+        );
+        assert_eq!(kernel.quiesce(32).queued_messages, 1);
+        stage_syscall(
+            &mut kernel,
+            server,
+            SyscallNumber::ReceiveIpc,
+            [inbox_ptr, 0, 0, 0, 0, 0],
+        );
+        assert!(kernel.quiesce(32).is_quiesced());
+        assert_eq!(&inbox.payload.data[..4], b"ping");
+
+        stage_syscall(
+            &mut kernel,
+            server,
+            SyscallNumber::SendIpc,
+            send(client, b"pong"),
+        );
+        assert_eq!(kernel.quiesce(32).queued_messages, 1);
+        stage_syscall(
+            &mut kernel,
+            client,
+            SyscallNumber::ReceiveIpc,
+            [inbox_ptr, 0, 0, 0, 0, 0],
+        );
+        let done = kernel.quiesce(32);
+        assert!(done.is_quiesced(), "{done:?}");
+        assert!(done.ticks < 32);
+        assert_eq!(&inbox.payload.data[..4], b"pong");
+    }
+
+    #[test]
+    fn deadlocked_pair_reaches_a_fixed_point_without_quiescing() {
+        let (mut kernel, client, server) = quiesce_pair();
+        let mut inbox = Message::new(
+            client,
+            server,
+            0,
+            MessagePayload::empty(SecurityClass::Public),
+        );
+        let inbox_ptr = &mut inbox as *mut Message as u64;
+        for pid in [client, server] {
+            stage_syscall(
+                &mut kernel,
+                pid,
+                SyscallNumber::ReceiveOrBlockIpc,
+                [inbox_ptr, 0, 0, 0, 0, 0],
+            );
         }
-        IsolationError::CapabilityTableFull => SyscallErrorCode::OutOfMemory,
+
+        let result = kernel.quiesce(32);
+
+        assert!(result.fixed_point);
+        assert_eq!(result.blocked_processes, 2);
+        assert_eq!(result.queued_messages, 0);
+        assert!(!result.is_quiesced());
     }
-}
 
-fn decode_priority(raw: u64) -> KernelResult<ProcessPriority> {
-    match raw {
-        0 => Ok(ProcessPriority::Critical),
-        1 => Ok(ProcessPriority::High),
-        2 => Ok(ProcessPriority::Normal),
-        3 => Ok(ProcessPriority::Low),
-        _ => Err(KernelError::InvalidArgument),
+    #[test]
+    fn quiesce_reports_an_exhausted_budget() {
+        let (mut kernel, client, server) = quiesce_pair();
+        stage_syscall(
+            &mut kernel,
+            client,
+            SyscallNumber::SendIpc,
+            [server.raw(), 0, 0, 0, 0, 0],
+        );
+
+        let cut_short = kernel.quiesce(1);
+        assert_eq!(cut_short.ticks, 1);
+        assert!(!cut_short.fixed_point);
+        assert!(!cut_short.is_quiesced());
+        assert_eq!(kernel.quiesce(0).ticks, 0);
+
+        let finished = kernel.quiesce(32);
+        assert!(finished.fixed_point);
+        assert_eq!(finished.queued_messages, 1);
     }
-}
 
-fn decode_credentials(raw: u64) -> KernelResult<Credentials> {
-    match raw {
-        0 => Ok(Credentials::user()),
-        1 => Ok(Credentials::system()),
-        _ => Err(KernelError::InvalidArgument),
+    #[test]
+    fn default_console_and_storage_resolve_core_devices() {
+        let mut kernel = boot_kernel();
+
+        let console = kernel.default_console().expect("serial console installed");
+        let storage = kernel.default_storage().expect("block storage installed");
+        assert_eq!(
+            kernel.device_info(console).map(|device| device.kind),
+            Some(DeviceKind::SerialConsole)
+        );
+        assert_eq!(
+            kernel.device_info(storage).map(|device| device.kind),
+            Some(DeviceKind::BlockStorage)
+        );
+        assert!(matches!(
+            kernel.set_default_device(DeviceKind::SerialConsole, storage),
+            Err(KernelError::DeviceFault(DriverError::Unsupported))
+        ));
+        assert_eq!(kernel.default_console(), Some(console));
     }
-}
 
-fn decode_security_class(raw: u64) -> KernelResult<SecurityClass> {
-    match raw {
-        0 => Ok(SecurityClass::Public),
-        1 => Ok(SecurityClass::Internal),
-        2 => Ok(SecurityClass::Confidential),
-        3 => Ok(SecurityClass::System),
-        _ => Err(KernelError::InvalidArgument),
+    #[test]
+    fn tiny_kernel_exhausts_thread_table_and_cores() {
+        let mut kernel = Kernel::<8, 4, 1, 4>::new();
+        kernel.bootstrap();
+        kernel.bring_up_secondary_cores(3);
+        assert_eq!(kernel.online_core_count(), 1);
+        assert_eq!(kernel.core_states.len(), 1);
+
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let mut last = None;
+        let mut spawned = 1usize;
+        while spawned < 4 {
+            last = Some(
+                kernel
+                    .spawn_thread(pid, 0x1000, ProcessPriority::Normal)
+                    .unwrap(),
+            );
+            spawned += 1;
+        }
+        assert!(matches!(
+            kernel.spawn_thread(pid, 0x1000, ProcessPriority::Normal),
+            Err(KernelError::ThreadTableFull)
+        ));
+        // The MTSS run queue is sized by the same limit, so every admitted
+        // thread stays schedulable.
+        assert_eq!(kernel.run_queue_position(last.unwrap()), Some(3));
+        assert!(matches!(
+            kernel.spawn_child_process(pid, 0x1000, ProcessPriority::Normal, Credentials::system()),
+            Err(KernelError::ThreadTableFull)
+        ));
     }
-}
 
-#[cfg(all(test, not(feature = "qfs-std")))]
-mod tests {
-    use super::*;
-    use crate::kernel::memory::{PROT_EXECUTE, PROT_READ, PROT_WRITE};
-    use crate::libc;
-    use crate::subkernel::{CapabilitySet, IsolationLevel, SecurityLabel};
+    #[test]
+    fn small_mtss_kernel_reports_overflow_at_its_own_limits() {
+        let mut kernel = Kernel::<2, 4, 1, 4>::new();
+        kernel.bootstrap();
+        kernel.kernel_mtss_init().unwrap();
+
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        for _ in 1..4 {
+            kernel
+                .spawn_thread(pid, 0x1000, ProcessPriority::Normal)
+                .unwrap();
+        }
+        let mut threads = [ThreadId::new(0); 4];
+        assert_eq!(kernel.threads_of(pid, &mut threads).unwrap(), 4);
+        assert!(matches!(
+            kernel.spawn_thread(pid, 0x1000, ProcessPriority::Normal),
+            Err(KernelError::ThreadTableFull)
+        ));
+        // Every admitted thread fits the MTSS run queue; none was turned away
+        // with `SchedulerFull`.
+        for (position, thread) in threads.iter().enumerate() {
+            assert_eq!(kernel.run_queue_position(*thread), Some(position));
+        }
+
+        // Freeing a thread makes room again, and the MTSS task table is sized
+        // by the kernel's process limit rather than the global one.
+        kernel.terminate_thread(threads[3]);
+        kernel.spawn_initial_process(Credentials::system()).unwrap();
+        assert!(matches!(
+            kernel.spawn_initial_process(Credentials::system()),
+            Err(KernelError::ProcessTableFull)
+        ));
+    }
 
     #[test]
-    fn boot_device_registry_has_headroom_for_real_and_core_drivers() {
-        // Architecture bring-up can discover serial, PS/2, USB-HID, ACPI EC,
-        // Limine module block media, and AHCI SATA/ATAPI drivers before the
-        // core logical devices are registered during BootInfoApplied.
-        const WORST_CASE_EARLY_REAL_DRIVERS: usize = 7;
-        const CORE_LOGICAL_DRIVERS: usize = 8;
-        assert!(MAX_DEVICES >= WORST_CASE_EARLY_REAL_DRIVERS + CORE_LOGICAL_DRIVERS);
+    fn small_core_counts_dispatch_one_thread_per_online_core() {
+        // Returns the CPU time of two runnable threads after each of two
+        // ticks.
+        fn run<const CORES: usize>() -> [[u128; 2]; 2] {
+            let mut kernel = Kernel::<16, 4, CORES>::new();
+            kernel.bootstrap();
+            kernel.kernel_mtss_init().unwrap();
+            kernel.bring_up_secondary_cores(CORES + 1);
+            assert_eq!(kernel.online_core_count(), CORES);
+            assert_eq!(kernel.core_states.len(), CORES);
+
+            let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+            let child = kernel
+                .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+                .unwrap();
+            for pcb in kernel.process_table.iter_mut().flatten() {
+                pcb.address_space_root = 0x434f_0000;
+            }
+
+            let mut seen = [[0; 2]; 2];
+            for times in seen.iter_mut() {
+                kernel.tick();
+                *times = [init, child].map(|pid| {
+                    kernel
+                        .thread_table
+                        .iter()
+                        .flatten()
+                        .find(|tcb| tcb.process == pid)
+                        .unwrap()
+                        .cpu_time
+                });
+            }
+            seen
+        }
+
+        let [first, second] = run::<1>();
+        assert_eq!(first.iter().sum::<u128>(), 1);
+        assert_eq!(second.iter().sum::<u128>(), 2);
+
+        let [first, second] = run::<2>();
+        assert_eq!(first, [1, 1]);
+        assert_eq!(second, [2, 2]);
     }
 
     #[test]
-    fn boot_info_applied_device_error_fatality_is_explicit() {
-        assert!(DriverError::RegistryFull.is_fatal_during_boot_info_applied());
-        assert!(DriverError::BufferTooSmall.is_fatal_during_boot_info_applied());
-        assert!(!DriverError::Busy.is_fatal_during_boot_info_applied());
-        assert!(!DriverError::Unsupported.is_fatal_during_boot_info_applied());
-        assert!(!DriverError::NotFound.is_fatal_during_boot_info_applied());
+    fn a_thread_starts_on_at_most_one_core_per_tick() {
+        let mut kernel = Kernel::<16, 4, 2>::new();
+        kernel.bootstrap();
+        kernel.kernel_mtss_init().unwrap();
+        kernel.bring_up_secondary_cores(2);
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = 0x4f4e_0000;
+        }
+        let thread = kernel
+            .thread_table
+            .iter()
+            .flatten()
+            .find(|tcb| tcb.process == init)
+            .unwrap()
+            .id;
+        let cpu_time = |kernel: &Kernel<16, 4, 2>| {
+            kernel
+                .thread_table
+                .iter()
+                .flatten()
+                .find(|tcb| tcb.id == thread)
+                .unwrap()
+                .cpu_time
+        };
+
+        for tick in 1..=4u128 {
+            kernel.tick();
+            assert_eq!(cpu_time(&kernel), tick, "tick {tick}");
+        }
     }
 
-    fn boot_kernel() -> Kernel<16, 4> {
-        let mut kernel = Kernel::<16, 4>::new();
+    #[test]
+    fn threads_that_keep_using_up_slices_sink_below_fresh_work() {
+        // Children clone the parent's page tables, which the fake root set
+        // below does not have, so roots are only filled in while running.
+        fn set_roots(kernel: &mut Kernel<16, 4, 1>, root: u64) {
+            for pcb in kernel.process_table.iter_mut().flatten() {
+                pcb.address_space_root = root;
+            }
+        }
+        let mut kernel = Kernel::<16, 4, 1>::new();
         kernel.bootstrap();
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
         kernel
-    }
+            .spawn_child_process(init, 0, ProcessPriority::Critical, Credentials::system())
+            .unwrap();
+        set_roots(&mut kernel, 0x4d4c_0000);
+        let depths = |kernel: &Kernel<16, 4, 1>| {
+            [
+                ProcessPriority::Critical,
+                ProcessPriority::High,
+                ProcessPriority::Normal,
+                ProcessPriority::Low,
+            ]
+            .map(|priority| kernel.queue_depth_for_priority(priority))
+        };
 
-    fn process_state(kernel: &Kernel<16, 4>, pid: ProcessId) -> ProcessState {
-        let index = kernel.locate_process(pid).unwrap();
-        kernel.process_table[index].unwrap().state
+        kernel.tick();
+        assert_eq!(depths(&kernel), [2, 0, 0, 0]);
+        // Two spinning threads each use up a four-tick slice every eight
+        // ticks and drop a band each time.
+        for _ in 0..15 {
+            kernel.tick();
+        }
+        assert_eq!(depths(&kernel), [0, 0, 1, 0]);
+
+        set_roots(&mut kernel, 0);
+        let fresh = kernel
+            .spawn_child_process(init, 0, ProcessPriority::High, Credentials::system())
+            .unwrap();
+        set_roots(&mut kernel, 0x4d4c_0000);
+        // The fresh High thread outranks both sunken Critical threads and
+        // holds the core from the next tick on.
+        kernel.tick();
+        kernel.tick();
+        let fresh_thread = kernel
+            .thread_table
+            .iter()
+            .flatten()
+            .find(|tcb| tcb.process == fresh)
+            .unwrap();
+        assert_eq!(fresh_thread.cpu_time, 2);
     }
 
-    fn first_thread(kernel: &Kernel<16, 4>, pid: ProcessId) -> ThreadId {
-        let mut idx = 0usize;
-        while idx < Kernel::<16, 4>::THREAD_CAPACITY {
-            if let Some(thread) = kernel.thread_table[idx] {
-                if thread.process == pid {
-                    return thread.id;
-                }
+    #[test]
+    fn critical_process_spawned_after_normal_ones_runs_on_the_next_tick() {
+        fn set_roots(kernel: &mut Kernel<16, 4, 1>, root: u64) {
+            for pcb in kernel.process_table.iter_mut().flatten() {
+                pcb.address_space_root = root;
             }
-            idx += 1;
         }
-        panic!("process has no thread")
-    }
-
-    fn process_threads_blocked(kernel: &Kernel<16, 4>, pid: ProcessId) -> bool {
-        let mut saw_thread = false;
-        let mut idx = 0usize;
-        while idx < Kernel::<16, 4>::THREAD_CAPACITY {
-            if let Some(thread) = kernel.thread_table[idx] {
-                if thread.process == pid {
-                    saw_thread = true;
-                    if thread.state != ThreadState::Blocked {
-                        return false;
-                    }
-                }
+        // Spawn at every point of the running Normal thread's slice.
+        for warmup in 1..=8 {
+            let mut kernel = Kernel::<16, 4, 1>::new();
+            kernel.bootstrap();
+            kernel.kernel_mtss_init().unwrap();
+            let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+            for _ in 0..3 {
+                kernel
+                    .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+                    .unwrap();
             }
-            idx += 1;
+            // init starts Critical; run it at Normal alongside its children.
+            let index = kernel.locate_process(init).unwrap();
+            kernel.process_table[index].as_mut().unwrap().priority = ProcessPriority::Normal;
+            kernel
+                .apply_thread_priority(init, ProcessPriority::Normal)
+                .unwrap();
+            set_roots(&mut kernel, 0x4e43_0000);
+            for _ in 0..warmup {
+                kernel.tick();
+            }
+
+            set_roots(&mut kernel, 0);
+            let critical = kernel
+                .spawn_child_process(init, 0, ProcessPriority::Critical, Credentials::system())
+                .unwrap();
+            set_roots(&mut kernel, 0x4e43_0000);
+            kernel.tick();
+            let critical_thread = kernel
+                .thread_table
+                .iter()
+                .flatten()
+                .find(|tcb| tcb.process == critical)
+                .unwrap();
+            assert_eq!(critical_thread.cpu_time, 1, "after {warmup} ticks");
         }
-        saw_thread
     }
 
-    fn elf_image(entry: u64, ph_flags: u32) -> [u8; 128] {
-        let mut bytes = [0u8; 128];
-        bytes[0..4].copy_from_slice(b"\x7fELF");
-        bytes[4] = 2;
-        bytes[5] = 1;
-        bytes[6] = 1;
-        bytes[16..18].copy_from_slice(&2u16.to_le_bytes());
-        bytes[18..20].copy_from_slice(&62u16.to_le_bytes());
-        bytes[24..32].copy_from_slice(&entry.to_le_bytes());
-        bytes[32..40].copy_from_slice(&64u64.to_le_bytes());
-        bytes[54..56].copy_from_slice(&56u16.to_le_bytes());
-        bytes[56..58].copy_from_slice(&1u16.to_le_bytes());
-        let ph = 64usize;
-        bytes[ph..ph + 4].copy_from_slice(&1u32.to_le_bytes());
-        bytes[ph + 4..ph + 8].copy_from_slice(&ph_flags.to_le_bytes());
-        bytes[ph + 8..ph + 16].copy_from_slice(&120u64.to_le_bytes());
-        bytes[ph + 16..ph + 24].copy_from_slice(&0x400078u64.to_le_bytes());
-        bytes[ph + 32..ph + 40].copy_from_slice(&1u64.to_le_bytes());
-        bytes[ph + 40..ph + 48].copy_from_slice(&1u64.to_le_bytes());
-        bytes[ph + 48..ph + 56].copy_from_slice(&8u64.to_le_bytes());
-        bytes[120] = 0xc3;
-        bytes
-    }
+    #[test]
+    fn offline_core_keeps_threads_running_on_remaining_cores() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        kernel.bring_up_secondary_cores(3);
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let mut spawned = 0;
+        while spawned < 3 {
+            kernel
+                .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+                .unwrap();
+            spawned += 1;
+        }
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = 0x4f46_0000;
+        }
+        let threads = kernel.thread_table.iter().flatten().count();
 
-    fn use_in_memory_qfs(kernel: &mut Kernel<16, 4>) {
-        kernel.root_fs = RootFileSystem::Qfs(QfsFileSystem::new(false));
-    }
+        let report = kernel.offline_core(2).unwrap();
+        assert_eq!(report.core, 2);
+        assert_eq!(report.migrated_threads, 0);
+        assert_eq!(report.online_cores, 3);
+        assert!(matches!(
+            kernel.offline_core(2),
+            Err(KernelError::InvalidArgument)
+        ));
 
-    const fn mtss_report(
-        core_ready: bool,
-        scheduler_ready: bool,
-        timer_ready: bool,
-        preemption_ready: bool,
-        idle_ready: bool,
-        api_ready: bool,
-    ) -> MtssInitReport {
-        MtssInitReport {
-            core_ready,
-            scheduler_ready,
-            timer_ready,
-            preemption_ready,
-            idle_ready,
-            api_ready,
+        let parked = kernel.core_states[2];
+        let before = kernel.core_states[0].local_ticks + kernel.core_states[0].idle_ticks;
+        for _ in 0..8 {
+            kernel.tick();
         }
+        assert_eq!(kernel.thread_table.iter().flatten().count(), threads);
+        assert!(kernel
+            .process_table
+            .iter()
+            .flatten()
+            .all(|pcb| pcb.cpu_time > 0));
+        assert_eq!(kernel.core_states[2].local_ticks, parked.local_ticks);
+        assert_eq!(kernel.core_states[2].idle_ticks, parked.idle_ticks);
+        assert!(kernel.core_states[0].local_ticks + kernel.core_states[0].idle_ticks > before);
+
+        kernel.online_core(2).unwrap();
+        assert_eq!(kernel.online_core_count(), 4);
+        kernel.tick();
+        assert!(
+            kernel.core_states[2].local_ticks + kernel.core_states[2].idle_ticks
+                > parked.local_ticks + parked.idle_ticks
+        );
     }
 
     #[test]
-    fn mtss_readiness_offline_blocks_all_task_paths() {
-        let report = mtss_report(false, false, false, false, false, false);
+    fn last_online_core_cannot_be_taken_down() {
+        let mut kernel = boot_kernel();
+        kernel.bring_up_secondary_cores(1);
+        kernel.offline_core(0).unwrap();
+        assert!(matches!(
+            kernel.offline_core(1),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert_eq!(kernel.online_core_count(), 1);
+        assert!(matches!(
+            kernel.online_core(cpu::MAX_CORES),
+            Err(KernelError::InvalidArgument)
+        ));
+        kernel.online_core(0).unwrap();
+        kernel.offline_core(1).unwrap();
+    }
 
-        assert_eq!(report.readiness(), MtssReadiness::Offline);
-        assert!(!report.core_ready());
-        assert!(!report.scheduler_ready());
-        assert!(!report.can_create_tasks());
-        assert!(!report.can_mark_runnable());
-        assert!(!report.can_cooperative_schedule());
-        assert!(!report.preemption_ready());
-        assert!(!report.is_fully_online());
-        assert!(!report.pid1_handoff_allowed(false));
-        assert_eq!(
-            report.pid1_handoff_blocker(false),
-            Some("MTSS core not ready")
-        );
+    #[test]
+    fn resource_snapshot_counts_live_objects_within_limits() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        kernel.bring_up_secondary_cores(1);
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        kernel
+            .spawn_thread(child, 0x1000, ProcessPriority::Normal)
+            .unwrap();
+
+        let snapshot = kernel.resource_accounting_snapshot();
+        assert_eq!(snapshot.processes_live, 2);
+        assert_eq!(snapshot.processes_max, 16);
+        assert_eq!(snapshot.threads_live, 3);
+        assert_eq!(snapshot.threads_max, Kernel::<16, 4>::THREAD_CAPACITY);
+        assert_eq!(snapshot.scheduler_depth, 3);
+        assert_eq!(snapshot.security_domains, 2);
+        assert_eq!(snapshot.online_cores, 2);
+        assert_eq!(snapshot.utilization_percent(), 12);
+        assert!(snapshot.memory_used <= snapshot.memory_peak);
+        assert!(snapshot.processes_live <= snapshot.processes_max);
+        assert!(snapshot.threads_live <= snapshot.threads_max);
+        assert!(snapshot.scheduler_depth <= snapshot.threads_max);
+        assert!(snapshot.online_cores <= cpu::MAX_CORES);
     }
 
     #[test]
-    fn mtss_readiness_core_ready_still_blocks_scheduler_paths() {
-        let report = mtss_report(true, false, false, false, false, true);
+    fn update_credentials_requires_kernel_cap_and_dominating_label() {
+        let mut kernel = boot_kernel();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let target = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let user = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let internal_admin = kernel
+            .spawn_child_process(
+                init,
+                0,
+                ProcessPriority::Normal,
+                Credentials::new(
+                    SecurityLabel::internal(),
+                    CapabilitySet::full(),
+                    IsolationLevel::Process,
+                ),
+            )
+            .unwrap();
 
-        assert_eq!(report.readiness(), MtssReadiness::CoreReady);
-        assert!(report.core_ready());
-        assert!(!report.scheduler_ready());
-        assert!(report.can_create_tasks());
-        assert!(!report.can_mark_runnable());
-        assert!(!report.can_cooperative_schedule());
-        assert!(!report.is_fully_online());
+        assert!(matches!(
+            kernel.update_credentials(user, target, Credentials::user()),
+            Err(KernelError::SecurityViolation(
+                IsolationError::CapabilityMissing
+            ))
+        ));
+        assert!(matches!(
+            kernel.update_credentials(internal_admin, target, Credentials::system()),
+            Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation
+            ))
+        ));
+        assert_eq!(kernel.last_credential_audit(), None);
+
+        let confidential = MessagePayload::from_slice(SecurityClass::Confidential, b"secret");
+        assert!(kernel.send_message(target, init, confidential).is_err());
+        kernel
+            .update_credentials(init, target, Credentials::system())
+            .unwrap();
+        kernel.send_message(target, init, confidential).unwrap();
+        let index = kernel.locate_process(target).unwrap();
         assert_eq!(
-            report.pid1_handoff_blocker(false),
-            Some("MTSS scheduler not ready")
+            kernel.process_table[index].unwrap().security_label,
+            SecurityLabel::system()
         );
     }
 
     #[test]
-    fn mtss_readiness_scheduler_without_idle_or_api_is_failed() {
-        let no_idle = mtss_report(true, true, false, false, false, true);
-        let no_api = mtss_report(true, true, false, false, true, false);
+    fn update_credentials_keeps_queued_messages_and_audits_the_change() {
+        let mut kernel = boot_kernel();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let target = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let internal = MessagePayload::from_slice(SecurityClass::Internal, b"queued");
+        kernel.send_message(init, target, internal).unwrap();
 
-        assert_eq!(
-            no_idle.readiness(),
-            MtssReadiness::Failed {
-                reason: "idle task unavailable"
-            }
-        );
-        assert!(!no_idle.can_mark_runnable());
-        assert!(!no_idle.can_cooperative_schedule());
-        assert_eq!(
-            no_idle.pid1_handoff_blocker(false),
-            Some("idle task unavailable")
+        let downgraded = Credentials::new(
+            SecurityLabel::public(),
+            CapabilitySet::ipc(),
+            IsolationLevel::Process,
         );
+        kernel.update_credentials(init, target, downgraded).unwrap();
 
         assert_eq!(
-            no_api.readiness(),
-            MtssReadiness::Failed {
-                reason: "MTSS API unavailable"
-            }
+            kernel.last_credential_audit(),
+            Some(CredentialAudit {
+                requester: init,
+                target,
+                old_label: SecurityLabel::internal(),
+                new_label: SecurityLabel::public(),
+                tick: KERNEL_TIME.uptime_ticks(),
+                notified: true,
+            })
         );
-        assert!(!no_api.can_create_tasks());
-        assert!(!no_api.can_mark_runnable());
+        assert!(matches!(
+            kernel.send_message(init, target, internal),
+            Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation
+            ))
+        ));
+
+        let queued = kernel.receive_message(target).unwrap();
+        assert_eq!(&queued.payload.data[..queued.payload.length], b"queued");
+        let notice = kernel.receive_message(target).unwrap();
+        assert_eq!(notice.sender, init);
         assert_eq!(
-            no_api.pid1_handoff_blocker(false),
-            Some("MTSS task API unavailable")
+            &notice.payload.data[..notice.payload.length],
+            CREDENTIAL_UPDATE_NOTICE
         );
     }
 
     #[test]
-    fn mtss_cooperative_schedule_does_not_require_timer_or_preemption() {
-        let report = mtss_report(true, true, false, false, true, true);
-
-        assert_eq!(
-            report.readiness(),
-            MtssReadiness::Degraded {
-                reason: "timer backend pending"
-            }
-        );
-        assert!(report.can_create_tasks());
-        assert!(report.can_mark_runnable());
-        assert!(report.can_cooperative_schedule());
-        assert!(!report.preemption_ready());
-        assert!(!report.is_fully_online());
-        assert!(report.pid1_handoff_allowed(false));
-        assert_eq!(report.pid1_handoff_blocker(false), None);
-        assert!(!report.pid1_handoff_allowed(true));
-        assert_eq!(
-            report.pid1_handoff_blocker(true),
-            Some("policy requires timer/preemption before userspace")
-        );
+    fn classified_send_rejects_under_classification() {
+        let mut kernel = boot_kernel();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let sender = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let receiver = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let internal = MessagePayload::from_slice(SecurityClass::Internal, b"bulk");
+
+        assert!(matches!(
+            kernel.send_message_classified(sender, receiver, internal, SecurityClass::Public),
+            Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation
+            ))
+        ));
+        assert!(matches!(
+            kernel.send_message_classified(sender, receiver, internal, SecurityClass::Confidential),
+            Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation
+            ))
+        ));
+        assert!(matches!(
+            kernel.receive_message(receiver),
+            Err(KernelError::MessageQueueEmpty)
+        ));
+
+        let public = MessagePayload::from_slice(SecurityClass::Public, b"note");
+        kernel
+            .send_message_classified(sender, receiver, public, SecurityClass::Internal)
+            .unwrap();
+        let message = kernel.receive_message(receiver).unwrap();
+        assert_eq!(message.payload.security_class, SecurityClass::Internal);
     }
 
     #[test]
-    fn mtss_full_online_requires_timer_and_preemption() {
-        let missing_preemption = mtss_report(true, true, true, false, true, true);
-        let online = mtss_report(true, true, true, true, true, true);
+    fn terminating_process_threads_are_reaped_instead_of_dispatched() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        kernel.bring_up_secondary_cores(3);
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let victim = kernel
+            .spawn_child_process(init, 0, ProcessPriority::High, Credentials::system())
+            .unwrap();
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = 0x5445_0000;
+        }
+        let victim_index = kernel.locate_process(victim).unwrap();
+        let victim_thread = kernel
+            .thread_table
+            .iter()
+            .flatten()
+            .find(|tcb| tcb.process == victim)
+            .unwrap()
+            .id;
 
-        assert_eq!(
-            missing_preemption.readiness(),
-            MtssReadiness::Degraded {
-                reason: "preemption backend pending"
-            }
-        );
-        assert!(missing_preemption.can_cooperative_schedule());
-        assert!(!missing_preemption.preemption_ready());
-        assert!(!missing_preemption.is_fully_online());
-        assert!(!missing_preemption.required_components_ready());
+        kernel
+            .begin_process_termination(victim, ExitStatus::exited(3))
+            .unwrap();
+        assert!(matches!(
+            kernel.begin_process_termination(victim, ExitStatus::exited(4)),
+            Err(KernelError::InvalidArgument)
+        ));
 
-        assert_eq!(online.readiness(), MtssReadiness::Online);
-        assert!(online.preemption_ready());
-        assert!(online.is_fully_online());
-        assert!(online.required_components_ready());
-        assert!(online.pid1_handoff_allowed(true));
+        // Mid-tick: every core gets a chance to dispatch the victim.
+        for core in 0..4 {
+            kernel.run_core(core);
+        }
+        let pcb = kernel.process_table[victim_index].unwrap();
+        assert_eq!(pcb.state, ProcessState::Terminating);
+        assert_eq!(pcb.cpu_time, 0);
+        assert!(kernel.locate_thread(victim_thread).is_err());
+        assert!(kernel
+            .core_states
+            .iter()
+            .all(|core| core.last_thread != Some(victim_thread)));
+
+        kernel.tick();
+        let pcb = kernel.process_table[victim_index].unwrap();
+        assert_eq!(pcb.state, ProcessState::Zombie);
+        assert_eq!(pcb.exit_status, Some(ExitStatus::exited(3)));
+        assert_eq!(pcb.cpu_time, 0);
+        assert!(kernel.check_thread_accounting().is_ok());
+        assert!(
+            kernel.process_table[kernel.locate_process(init).unwrap()]
+                .unwrap()
+                .cpu_time
+                > 0
+        );
     }
 
     #[test]
-    fn kernel_mtss_init_allows_pid1_handoff_when_preemption_not_required() {
+    fn run_core_drops_threads_whose_process_vanished() {
         let mut kernel = boot_kernel();
-        let report = kernel.kernel_mtss_init().unwrap();
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let survivor = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Critical, Credentials::system())
+            .unwrap();
+        let doomed = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Critical, Credentials::system())
+            .unwrap();
+        kernel
+            .spawn_thread(doomed, 0x1000, ProcessPriority::Critical)
+            .unwrap();
+        kernel
+            .spawn_thread(survivor, 0x1000, ProcessPriority::Critical)
+            .unwrap();
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = 0x5245_0000;
+        }
+        let doomed_thread = first_thread(&kernel, doomed);
 
-        assert!(report.core_ready());
-        assert!(report.scheduler_ready());
-        assert!(report.can_create_tasks());
-        assert!(report.can_mark_runnable());
-        assert!(report.can_cooperative_schedule());
-        assert!(!report.preemption_ready());
-        assert!(!report.is_fully_online());
-        assert!(report.pid1_handoff_allowed(false));
-        assert_eq!(report.pid1_handoff_blocker(false), None);
-        assert!(!report.pid1_handoff_allowed(true));
-    }
+        // Pull the process out from under its queued threads.
+        let index = kernel.locate_process(doomed).unwrap();
+        kernel.vacate_process(index);
+        kernel.security.revoke_task(doomed);
+        assert!(kernel.consistency_check().is_err());
 
-    fn create_root_file(
-        kernel: &mut Kernel<16, 4>,
-        name: &str,
-        mode: crate::kernel::fs::Permissions,
-        data: &[u8],
-    ) {
-        match &kernel.root_fs {
-            RootFileSystem::Qfs(fs) => {
-                fs.create_test_file(fs.root_inode(), name, mode, data, FsCredentials::kernel())
-                    .unwrap();
-            }
-            RootFileSystem::Ext4(_) => panic!("tests expect qfs root filesystem"),
+        for _ in 0..8 {
+            kernel.run_core(0);
         }
-    }
 
-    fn clear_process_address_space(kernel: &mut Kernel<16, 4>, pid: ProcessId) {
-        let index = kernel.locate_process(pid).unwrap();
-        let old = kernel.process_table[index].unwrap().address_space_root;
-        if old != 0 {
-            memory::destroy_user_address_space(old);
-            kernel.process_table[index]
-                .as_mut()
-                .unwrap()
-                .address_space_root = 0;
-        }
+        assert!(kernel.locate_thread(doomed_thread).is_err());
+        assert!(kernel
+            .thread_table
+            .iter()
+            .flatten()
+            .all(|tcb| tcb.process != doomed));
+        assert_eq!(kernel.run_queue_position(doomed_thread), None);
+        let index = kernel.locate_process(survivor).unwrap();
+        assert_eq!(kernel.process_table[index].unwrap().thread_count, 2);
+        assert_kernel_consistent(&kernel);
     }
 
-    fn load_exec_for_path(
-        kernel: &mut Kernel<16, 4>,
-        pid: ProcessId,
-        raw: &str,
-    ) -> KernelResult<crate::kernel::process::ExecImageMetadata> {
-        let resolved = KernelPathBuf::from_str(raw)?;
-        let path = resolved.as_path()?;
-        let stat = kernel.root_fs.stat(path).map_err(KernelError::Filesystem)?;
-        kernel.load_exec_image(
-            pid,
-            &resolved,
-            stat,
-            ExecVectorMetadata::empty(),
-            ExecVectorMetadata::empty(),
-        )
+    #[test]
+    fn default_kernel_alias_uses_platform_limits() {
+        assert_eq!(DefaultKernel::THREAD_CAPACITY, thread::MAX_THREADS);
+        let mut kernel = Kernel::<16, 4>::new();
+        kernel.bootstrap();
+        kernel.bring_up_secondary_cores(cpu::MAX_CORES - 1);
+        assert_eq!(kernel.online_core_count(), cpu::MAX_CORES);
+        assert_eq!(kernel.core_states.len(), cpu::MAX_CORES);
     }
 
     #[test]
-    fn load_exec_image_rejects_invalid_elf() {
+    fn forward_message_preserves_original_sender() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        use_in_memory_qfs(&mut kernel);
-        clear_process_address_space(&mut kernel, pid);
-        create_root_file(
-            &mut kernel,
-            "bad-elf",
-            crate::kernel::fs::Permissions::executable(),
-            b"not-elf",
-        );
+        let client = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let router = kernel
+            .spawn_child_process(client, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let server = kernel
+            .spawn_child_process(client, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"route me");
 
+        kernel.send_message(client, router, payload).unwrap();
+        let received = kernel.receive_message(router).unwrap();
+        assert_eq!(received.original_sender, None);
         assert!(matches!(
-            load_exec_for_path(&mut kernel, pid, "/bad-elf"),
+            kernel.forward_message(server, received, client),
             Err(KernelError::InvalidArgument)
         ));
-    }
+        kernel.forward_message(router, received, server).unwrap();
 
-    #[test]
-    fn load_exec_image_rejects_non_executable_path_and_unmapped_entry() {
-        let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        use_in_memory_qfs(&mut kernel);
-        clear_process_address_space(&mut kernel, pid);
-        let valid = elf_image(0x400078, 0x5);
-        create_root_file(
-            &mut kernel,
-            "not-exec",
-            crate::kernel::fs::Permissions::read_write(),
-            &valid,
-        );
-        let unmapped = elf_image(0x400078, 0x4);
-        create_root_file(
-            &mut kernel,
-            "unmapped-entry",
-            crate::kernel::fs::Permissions::executable(),
-            &unmapped,
-        );
+        let delivered = kernel.receive_message(server).unwrap();
+        assert_eq!(delivered.sender, router);
+        assert_eq!(delivered.receiver, server);
+        assert_eq!(delivered.original_sender, Some(client));
+        assert_eq!(delivered.origin(), client);
+        assert_eq!(delivered.payload, payload);
+        assert!(delivered.sequence > received.sequence);
 
-        assert!(matches!(
-            load_exec_for_path(&mut kernel, pid, "/not-exec"),
-            Err(KernelError::Filesystem(VfsError::PermissionDenied))
-        ));
-        assert!(matches!(
-            load_exec_for_path(&mut kernel, pid, "/unmapped-entry"),
-            Err(_)
-        ));
+        kernel.forward_message(server, delivered, router).unwrap();
+        let bounced = kernel.receive_message(router).unwrap();
+        assert_eq!(bounced.sender, server);
+        assert_eq!(bounced.original_sender, Some(client));
     }
 
     #[test]
-    fn exec_task_success_preserves_pid_and_closes_cloexec_descriptors() {
+    fn ipc_redirect_delivers_to_proxy_without_changing_sender() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        use_in_memory_qfs(&mut kernel);
-        clear_process_address_space(&mut kernel, pid);
-        let thread = first_thread(&kernel, pid);
-        let image_bytes = elf_image(0x400078, 0x5);
-        create_root_file(
-            &mut kernel,
-            "exec-ok",
-            crate::kernel::fs::Permissions::executable(),
-            &image_bytes,
-        );
-        create_root_file(
-            &mut kernel,
-            "keep",
-            crate::kernel::fs::Permissions::read_write(),
-            b"keep",
-        );
-        create_root_file(
-            &mut kernel,
-            "drop",
-            crate::kernel::fs::Permissions::read_write(),
-            b"drop",
-        );
-
-        let keep_file = kernel
-            .root_fs
-            .open(
-                Path::new("/keep").unwrap(),
-                crate::kernel::fs::OpenFlags::RDONLY,
-                kernel.fs_credentials_for(pid).unwrap(),
-            )
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let client = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let drop_file = kernel
-            .root_fs
-            .open(
-                Path::new("/drop").unwrap(),
-                crate::kernel::fs::OpenFlags::RDONLY,
-                kernel.fs_credentials_for(pid).unwrap(),
-            )
+        let service = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let keep_desc = kernel.open_files.insert(keep_file).unwrap();
-        let drop_desc = kernel.open_files.insert(drop_file).unwrap();
-        let keep_fd = kernel
-            .process_files_mut(pid)
-            .unwrap()
-            .open(keep_desc, DescriptorFlags::EMPTY)
+        let proxy = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let drop_fd = kernel
-            .process_files_mut(pid)
-            .unwrap()
-            .open(drop_desc, DescriptorFlags::CLOSE_ON_EXEC)
+        let user = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
             .unwrap();
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"hello");
 
-        let resolved = KernelPathBuf::from_str("/exec-ok").unwrap();
-        let stat = kernel.root_fs.stat(resolved.as_path().unwrap()).unwrap();
-        let image = crate::kernel::process::ExecImageMetadata::new(
-            stat.inode.raw(),
-            stat.size,
-            stat.mode,
-            0x400078,
-            0x70000000,
-            0,
-            None,
-            None,
-        );
-        let request = ExecRequest::new(
-            pid,
-            ProcessPath::from_path(resolved.as_path().unwrap()),
-            ExecVectorMetadata::empty(),
-            ExecVectorMetadata::empty(),
-            Credentials::system(),
-            image,
-        );
+        assert!(matches!(
+            kernel.add_ipc_redirect(user, service, proxy),
+            Err(KernelError::SecurityViolation(
+                IsolationError::CapabilityMissing
+            ))
+        ));
+        kernel.add_ipc_redirect(init, service, proxy).unwrap();
 
-        kernel.exec_task(request, Some(thread)).unwrap();
+        kernel.send_message(client, service, payload).unwrap();
+        assert!(matches!(
+            kernel.receive_message(service),
+            Err(KernelError::MessageQueueEmpty)
+        ));
+        let intercepted = kernel.receive_message(proxy).unwrap();
+        assert_eq!(intercepted.sender, client);
+        assert_eq!(intercepted.receiver, proxy);
+        assert_eq!(intercepted.payload, payload);
 
-        assert_eq!(
-            kernel.process_table[kernel.locate_process(pid).unwrap()]
-                .unwrap()
-                .pid,
-            pid
-        );
-        assert!(kernel.process_files_mut(pid).unwrap().get(keep_fd).is_ok());
-        assert!(kernel.process_files_mut(pid).unwrap().get(drop_fd).is_err());
-        assert_eq!(
-            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
-                .unwrap()
-                .context
-                .rip,
-            0x400078
-        );
+        assert_eq!(kernel.remove_ipc_redirect(service), Some(proxy));
+        kernel.send_message(client, service, payload).unwrap();
+        assert_eq!(kernel.receive_message(service).unwrap().sender, client);
+
+        kernel.add_ipc_redirect(init, service, proxy).unwrap();
+        kernel.terminate_process(proxy);
+        assert_eq!(kernel.remove_ipc_redirect(service), None);
     }
 
     #[test]
-    fn syscall_write_stdout_console_descriptor_accepts_bytes() {
+    fn send_to_exited_receiver_is_told_apart_from_unknown_pid() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let bytes = b"stdout-console-test\n";
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let receiver = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"late");
 
-        assert_eq!(
-            kernel
-                .handle_syscall(
-                    SyscallNumber::Write.raw(),
-                    SyscallContext::new(
-                        pid,
-                        None,
-                        [1, bytes.as_ptr() as u64, bytes.len() as u64, 0, 0, 0]
-                    ),
-                )
-                .unwrap(),
-            bytes.len() as u64
-        );
+        kernel.terminate_process(receiver);
+        let zombie = kernel.send_message(init, receiver, payload);
+        assert!(matches!(zombie, Err(KernelError::ReceiverTerminated)));
+        assert_eq!(kernel.wait(init, None).unwrap(), receiver);
+        let reaped = kernel.send_message(init, receiver, payload);
+        assert!(matches!(reaped, Err(KernelError::ReceiverTerminated)));
+
+        // A PID that was never issued keeps failing the security check.
+        assert!(matches!(
+            kernel.send_message(init, ProcessId::new(0xdead), payload),
+            Err(KernelError::SecurityViolation(IsolationError::UnknownTask))
+        ));
     }
 
     #[test]
-    fn syscall_write_stderr_console_descriptor_accepts_bytes() {
+    fn threads_of_lists_every_thread_of_a_process() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let bytes = b"stderr-console-test\n";
-
-        assert_eq!(
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let worker = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let mut expected = [
+            first_thread(&kernel, worker),
             kernel
-                .handle_syscall(
-                    SyscallNumber::Write.raw(),
-                    SyscallContext::new(
-                        pid,
-                        None,
-                        [2, bytes.as_ptr() as u64, bytes.len() as u64, 0, 0, 0]
-                    ),
-                )
+                .spawn_thread(worker, 0x1000, ProcessPriority::Normal)
                 .unwrap(),
-            bytes.len() as u64
-        );
+            kernel
+                .spawn_thread(worker, 0x2000, ProcessPriority::Normal)
+                .unwrap(),
+        ];
+
+        let mut out = [ThreadId::new(0); 8];
+        let count = kernel.threads_of(worker, &mut out).unwrap();
+        let listed = &mut out[..count];
+        listed.sort_by_key(|thread| thread.raw());
+        expected.sort_by_key(|thread| thread.raw());
+        assert_eq!(listed, &expected[..]);
+
+        assert_eq!(kernel.threads_of(init, &mut out).unwrap(), 1);
+        assert_eq!(out[0], first_thread(&kernel, init));
+        assert_eq!(kernel.threads_of(worker, &mut out[..2]).unwrap(), 2);
+        assert!(matches!(
+            kernel.threads_of(ProcessId::new(0xdead), &mut out),
+            Err(KernelError::UnknownProcess)
+        ));
     }
 
     #[test]
-    fn syscall_write_invalid_fd_returns_bad_file_descriptor() {
+    fn process_snapshot_is_unchanged_by_later_kernel_changes() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let bytes = b"invalid-fd";
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let worker = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
 
-        let error = kernel
-            .handle_syscall(
-                SyscallNumber::Write.raw(),
-                SyscallContext::new(
-                    pid,
-                    None,
-                    [99, bytes.as_ptr() as u64, bytes.len() as u64, 0, 0, 0],
-                ),
-            )
-            .unwrap_err();
+        let snapshot = kernel.snapshot_processes();
+        let before = snapshot;
+        let captured = *snapshot.get(worker).unwrap();
+        assert_eq!(captured.parent, Some(init));
+        assert_eq!(captured.thread_count, 1);
+        assert!(snapshot.get(init).is_some());
 
-        assert_eq!(
-            syscall_error_code(error),
-            SyscallErrorCode::BadFileDescriptor
-        );
+        let late = kernel
+            .spawn_child_process(init, 0, ProcessPriority::High, Credentials::system())
+            .unwrap();
+        kernel
+            .spawn_thread(worker, 0x1000, ProcessPriority::Normal)
+            .unwrap();
+        kernel.exit_process(worker, ExitStatus::exited(0)).unwrap();
+
+        assert_eq!(snapshot, before);
+        assert!(snapshot.get(late).is_none());
+        assert_eq!(snapshot.get(worker), Some(&captured));
+
+        let current = kernel.snapshot_processes();
+        assert_eq!(current.len(), snapshot.len() + 1);
+        assert_eq!(current.get(late).unwrap().priority, ProcessPriority::High);
+        assert_eq!(current.get(worker).unwrap().state, ProcessState::Zombie);
     }
 
     #[test]
-    fn syscall_write_zero_length_console_write_returns_zero() {
+    fn retried_send_is_delivered_once_the_queue_drains() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let [sender, receiver] = core::array::from_fn(|_| {
+            kernel
+                .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+                .unwrap()
+        });
+        for pid in [init, sender, receiver] {
+            set_address_space_root(&mut kernel, pid, 0x1000);
+        }
+        let filler = MessagePayload::from_slice(SecurityClass::Public, b"fill");
+        let retried = MessagePayload::from_slice(SecurityClass::Public, b"retry");
+        while kernel.send_message(init, receiver, filler).is_ok() {}
 
+        assert!(matches!(
+            kernel.send_message_retry(sender, receiver, retried, 0, 1),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert!(matches!(
+            kernel.send_message_retry(sender, receiver, retried, 1, 1),
+            Err(KernelError::MessageQueueFull)
+        ));
         assert_eq!(
             kernel
-                .handle_syscall(
-                    SyscallNumber::Write.raw(),
-                    SyscallContext::new(pid, None, [1, 0, 0, 0, 0, 0]),
-                )
+                .send_message_retry(sender, receiver, retried, 4, 1)
                 .unwrap(),
-            0
+            SendRetryOutcome::Pending
         );
+
+        // The second attempt fails one tick later and backs off two more.
+        kernel.tick();
+        assert!(kernel.take_send_retry_result(sender).is_none());
+        assert_eq!(kernel.receive_message(receiver).unwrap().payload, filler);
+        kernel.tick();
+        assert!(kernel.take_send_retry_result(sender).is_none());
+        kernel.tick();
+        assert!(matches!(
+            kernel.take_send_retry_result(sender),
+            Some(Ok(()))
+        ));
+        let mut last = None;
+        while let Ok(message) = kernel.receive_message(receiver) {
+            last = Some(message);
+        }
+        let last = last.unwrap();
+        assert_eq!((last.sender, last.payload), (sender, retried));
     }
 
     #[test]
-    fn wait4_null_status_reaps_zombie_child() {
+    fn retried_send_reports_queue_full_after_its_last_attempt() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let receiver = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
+        for pid in [init, receiver] {
+            set_address_space_root(&mut kernel, pid, 0x1000);
+        }
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"full");
+        while kernel.send_message(init, receiver, payload).is_ok() {}
 
-        kernel.exit_process(child, ExitStatus::exited(7)).unwrap();
-
-        let waited = kernel
-            .handle_syscall(
-                SyscallNumber::Wait4.raw(),
-                SyscallContext::new(parent, None, [child.raw(), 0, 0, 0, 0, 0]),
-            )
+        kernel
+            .send_message_retry(init, receiver, payload, 3, 1)
             .unwrap();
-
-        assert_eq!(waited, child.raw());
+        for _ in 0..2 {
+            assert!(kernel.take_send_retry_result(init).is_none());
+            kernel.tick();
+        }
+        // Attempts ran at ticks 0, 1 and 3.
+        assert!(kernel.take_send_retry_result(init).is_none());
+        kernel.tick();
         assert!(matches!(
-            kernel.locate_process(child),
-            Err(KernelError::UnknownProcess)
+            kernel.take_send_retry_result(init),
+            Some(Err(KernelError::MessageQueueFull))
         ));
+        assert!(kernel.take_send_retry_result(init).is_none());
+    }
+
+    fn assert_slot_bitmaps_match_tables(kernel: &Kernel<16, 4>) {
+        for (idx, pcb) in kernel.process_table.iter().enumerate() {
+            assert_eq!(kernel.process_slots.is_set(idx), pcb.is_some());
+        }
+        assert_eq!(
+            kernel.find_free_slot(),
+            kernel.process_table.iter().position(Option::is_none)
+        );
+        assert_eq!(
+            kernel.find_free_thread_slot(),
+            kernel.thread_table.iter().position(Option::is_none)
+        );
     }
 
     #[test]
-    fn wait4_writes_valid_status_pointer() {
+    fn free_slot_bitmaps_track_spawns_and_reaps() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+        assert_eq!(kernel.find_free_slot(), Some(0));
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let children: [ProcessId; 3] = core::array::from_fn(|_| {
+            kernel
+                .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+                .unwrap()
+        });
+        assert_slot_bitmaps_match_tables(&kernel);
+        let reaped_slot = kernel.locate_process(children[1]).unwrap();
+        let thread_slot = kernel
+            .locate_thread(first_thread(&kernel, children[1]))
             .unwrap();
-        let mut status = 0i32;
 
-        kernel.exit_process(child, ExitStatus::exited(42)).unwrap();
-        let waited = kernel
-            .wait_for_child(
-                parent,
-                None,
-                child.raw() as i64,
-                &mut status as *mut i32 as u64,
-                0,
-            )
+        // A zombie keeps its process slot until it is reaped; its threads go
+        // at once.
+        kernel.terminate_process(children[1]);
+        assert_slot_bitmaps_match_tables(&kernel);
+        assert_eq!(kernel.find_free_thread_slot(), Some(thread_slot));
+        assert_eq!(kernel.wait(init, None).unwrap(), children[1]);
+        assert_slot_bitmaps_match_tables(&kernel);
+        assert_eq!(kernel.find_free_slot(), Some(reaped_slot));
+
+        let replacement = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
+        assert_eq!(kernel.locate_process(replacement).unwrap(), reaped_slot);
+        assert_slot_bitmaps_match_tables(&kernel);
 
-        assert_eq!(waited, child.raw());
-        assert_eq!(status, ExitStatus::exited(42).raw());
+        kernel.bootstrap();
+        assert_eq!(kernel.find_free_slot(), Some(0));
+        assert_slot_bitmaps_match_tables(&kernel);
     }
 
     #[test]
-    fn wait4_rejects_invalid_status_pointer() {
+    fn named_endpoints_resolve_to_owner_until_it_exits() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let client = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let service = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"hello");
 
-        kernel.exit_process(child, ExitStatus::exited(1)).unwrap();
-        let error = kernel
-            .handle_syscall(
-                SyscallNumber::Wait4.raw(),
-                SyscallContext::new(parent, None, [child.raw(), 1, 0, 0, 0, 0]),
-            )
-            .unwrap_err();
+        kernel.register_endpoint(service, "echo").unwrap();
+        assert!(matches!(
+            kernel.register_endpoint(client, "echo"),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert!(matches!(
+            kernel.register_endpoint(client, ""),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert_eq!(kernel.resolve_endpoint("missing"), None);
 
-        assert!(matches!(error, KernelError::InvalidPointer));
-        assert_eq!(syscall_error_code(error), SyscallErrorCode::BadAddress);
-        assert_eq!(process_state(&kernel, child), ProcessState::Zombie);
+        let target = kernel.resolve_endpoint("echo").unwrap();
+        assert_eq!(target, service);
+        kernel.send_message(client, target, payload).unwrap();
+        assert_eq!(kernel.receive_message(service).unwrap().sender, client);
+
+        kernel.terminate_process(service);
+        assert_eq!(kernel.resolve_endpoint("echo"), None);
+        kernel.register_endpoint(client, "echo").unwrap();
+        assert_eq!(kernel.resolve_endpoint("echo"), Some(client));
     }
 
     #[test]
-    fn wait4_rejects_non_child_pid() {
+    fn stale_thread_id_is_rejected_after_slot_reuse() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
-            .unwrap();
-        let grandchild = kernel
-            .spawn_child_process(child, 0, ProcessPriority::Normal, Credentials::system())
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let stale = kernel
+            .spawn_thread(pid, 0x1000, ProcessPriority::Normal)
             .unwrap();
+        let stale_slot = kernel.locate_thread(stale).unwrap();
 
-        kernel
-            .exit_process(grandchild, ExitStatus::exited(0))
-            .unwrap();
+        kernel.terminate_thread(stale);
+        assert!(matches!(
+            kernel.thread_context(stale),
+            Err(KernelError::UnknownThread)
+        ));
 
+        let replacement = kernel
+            .spawn_thread(pid, 0x2000, ProcessPriority::Normal)
+            .unwrap();
+        assert_eq!(kernel.locate_thread(replacement).unwrap(), stale_slot);
+        assert_ne!(replacement, stale);
+        // Thread ids double as TIDs, so a reused slot must still fit 32 bits.
+        assert!(replacement.raw() <= u64::from(u32::MAX));
         assert!(matches!(
-            kernel.wait_for_child(parent, None, grandchild.raw() as i64, 0, 0),
-            Err(KernelError::UnknownProcess)
+            kernel.thread_context(stale),
+            Err(KernelError::UnknownThread)
         ));
+        assert_eq!(kernel.thread_context(replacement).unwrap().rip, 0x2000);
+        assert_kernel_consistent(&kernel);
     }
 
     #[test]
-    fn wait4_reports_no_children() {
+    fn profiler_samples_land_on_interval_ticks() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-
         assert!(matches!(
-            kernel.wait_for_child(parent, None, -1, 0, 0),
-            Err(KernelError::UnknownProcess)
+            kernel.profiler_enable(0),
+            Err(KernelError::InvalidArgument)
         ));
+        kernel.profiler_enable(2).expect("enable profiler");
+        let mut round = 0;
+        while round < 8 {
+            kernel.tick();
+            round += 1;
+        }
+        kernel.profiler_disable();
+
+        let mut samples = [ProfileSample::empty(); 64];
+        let count = kernel.profiler_drain(&mut samples);
+        let mut idx = 0;
+        while idx < count {
+            assert!(samples[idx].tick.is_multiple_of(2));
+            idx += 1;
+        }
     }
 
     #[test]
-    fn wait4_blocks_parent_until_child_exits() {
+    fn message_filter_hook_rejects_matching_messages() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static SEEN: AtomicUsize = AtomicUsize::new(0);
+        static SEEN_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+        // The hook is global, so it only rejects payloads tagged by this test
+        // to keep concurrently running IPC tests unaffected.
+        fn deny_confidential(message: &Message) -> bool {
+            let tagged = message.payload.data.starts_with(b"lsm-hook");
+            if tagged {
+                SEEN.fetch_add(1, Ordering::SeqCst);
+                SEEN_SEQUENCE.store(message.sequence as usize, Ordering::SeqCst);
+            }
+            !(tagged && message.payload.security_class == SecurityClass::Confidential)
+        }
+
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let peer = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let thread = first_thread(&kernel, parent);
+        let denied = MessagePayload::from_slice(SecurityClass::Confidential, b"lsm-hook");
+        let allowed = MessagePayload::from_slice(SecurityClass::Public, b"lsm-hook");
+
+        kernel.set_message_filter_hook(deny_confidential);
+        let result = kernel.send_message(init, peer, denied);
+        let seen_after_deny = SEEN.load(Ordering::SeqCst);
+        let denied_sequence = SEEN_SEQUENCE.load(Ordering::SeqCst);
+        let allowed_result = kernel.send_message(init, peer, allowed);
+        kernel.clear_message_filter_hook();
 
         assert!(matches!(
-            kernel.wait_for_child(parent, Some(thread), child.raw() as i64, 0, 0),
-            Err(KernelError::MessageQueueEmpty)
+            result,
+            Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation
+            ))
         ));
+        assert_eq!(seen_after_deny, 1);
+        allowed_result.unwrap();
+        let delivered = kernel.receive_message(peer).unwrap();
+        assert_eq!(delivered.payload, allowed);
+        assert!(delivered.sequence > denied_sequence as u64);
+
+        kernel.send_message(init, peer, denied).unwrap();
+        assert_eq!(SEEN.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn masked_signal_is_deferred_until_unmasked() {
+        const SIGUSR1: u8 = 10;
+        let mut kernel = boot_kernel();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let thread = kernel
+            .spawn_thread(pid, 0x1000, ProcessPriority::Normal)
+            .unwrap();
+        let mut mask = SignalMask::EMPTY;
+        mask.insert(SIGUSR1);
 
-        assert_eq!(process_state(&kernel, parent), ProcessState::Blocked);
-        assert!(process_threads_blocked(&kernel, parent));
         assert_eq!(
-            kernel.process_table[kernel.locate_process(parent).unwrap()]
-                .unwrap()
-                .child_wait,
-            Some(ChildWaitSelector::Pid(child))
+            kernel.set_signal_mask(thread, mask).unwrap(),
+            SignalMask::EMPTY
+        );
+        kernel.queue_signal(pid, SIGUSR1).unwrap();
+        kernel.deliver_signal_checkpoint(pid, thread).unwrap();
+        let index = kernel.locate_thread(thread).unwrap();
+        assert_eq!(kernel.thread_table[index].unwrap().active_signal, None);
+
+        assert_eq!(
+            kernel.set_signal_mask(thread, SignalMask::EMPTY).unwrap(),
+            mask
+        );
+        assert_eq!(
+            kernel.thread_table[index].unwrap().active_signal,
+            Some(SIGUSR1)
         );
     }
 
     #[test]
-    fn child_exit_wakes_matching_waiter_without_reaping_zombie() {
+    fn queue_stats_track_each_process_separately() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let consumer = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let thread = first_thread(&kernel, parent);
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"tick");
+
+        for _ in 0..4 {
+            kernel.send_message(init, consumer, payload).unwrap();
+        }
+        assert!(kernel.send_message(init, consumer, payload).is_err());
+        kernel.receive_message(consumer).unwrap();
+        kernel.receive_message(consumer).unwrap();
+        kernel.send_message(consumer, init, payload).unwrap();
+
+        assert_eq!(
+            kernel.queue_stats(consumer).unwrap(),
+            QueueStats {
+                enqueued: 4,
+                dequeued: 2,
+                dropped: 1,
+            }
+        );
+        assert_eq!(
+            kernel.queue_stats(init).unwrap(),
+            QueueStats {
+                enqueued: 1,
+                dequeued: 0,
+                dropped: 0,
+            }
+        );
+    }
 
+    #[test]
+    fn destroying_an_arena_frees_every_object_in_one_step() {
+        let mut kernel = boot_kernel();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let owner = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let arena = kernel.create_arena(owner, 1024).unwrap();
+
+        let objects: [NonNull<u8>; 4] =
+            core::array::from_fn(|_| kernel.arena_alloc(owner, arena, 100, 8).unwrap());
+        let base = objects[0];
+        for object in &objects[1..] {
+            assert!(memory::allocation_info_for(owner, *object).is_none());
+        }
+        assert_eq!(
+            memory::allocation_info_for(owner, base).map(|info| info.size),
+            Some(1024)
+        );
         assert!(matches!(
-            kernel.wait_for_child(parent, Some(thread), child.raw() as i64, 0, 0),
-            Err(KernelError::MessageQueueEmpty)
+            kernel.arena_alloc(init, arena, 8, 8),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert!(matches!(
+            kernel.destroy_arena(init, arena),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert!(matches!(
+            kernel.arena_alloc(owner, arena, 1024, 8),
+            Err(KernelError::AllocationFailed)
         ));
-        kernel.exit_process(child, ExitStatus::exited(9)).unwrap();
 
-        assert_eq!(process_state(&kernel, parent), ProcessState::Ready);
-        assert_eq!(process_state(&kernel, child), ProcessState::Zombie);
-        assert_eq!(
-            kernel.process_table[kernel.locate_process(parent).unwrap()]
-                .unwrap()
-                .child_wait,
-            None
-        );
+        assert_eq!(kernel.destroy_arena(owner, arena).unwrap(), 1024);
+        assert!(memory::allocation_info_for(owner, base).is_none());
+        assert!(matches!(
+            kernel.arena_alloc(owner, arena, 8, 8),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert!(matches!(
+            kernel.destroy_arena(owner, arena),
+            Err(KernelError::InvalidArgument)
+        ));
     }
 
     #[test]
-    fn wait_any_reaps_first_zombie_child() {
+    fn terminating_a_process_frees_its_arenas() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
-            .unwrap();
-        let other = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let owner = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
+        let arena = kernel.create_arena(owner, 512).unwrap();
+        let object = kernel.arena_alloc(owner, arena, 64, 16).unwrap();
+        assert!(memory::allocation_info_for(owner, object).is_some());
 
-        kernel.exit_process(child, ExitStatus::exited(3)).unwrap();
+        kernel.terminate_process(owner);
 
-        assert_eq!(
-            kernel.wait_for_child(parent, None, -1, 0, 0).unwrap(),
-            child.raw()
-        );
+        assert!(memory::allocation_info_for(owner, object).is_none());
         assert!(matches!(
-            kernel.locate_process(child),
-            Err(KernelError::UnknownProcess)
+            kernel.arena_alloc(owner, arena, 8, 8),
+            Err(KernelError::InvalidArgument)
         ));
-        assert!(kernel.locate_process(other).is_ok());
     }
 
     #[test]
-    fn wait_pid_ignores_other_zombie_children() {
+    fn class_quota_keeps_room_for_other_classes() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let first = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let consumer = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let second = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+        let user = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
             .unwrap();
+        let bulk = MessagePayload::from_slice(SecurityClass::Confidential, b"bulk");
+        let ping = MessagePayload::from_slice(SecurityClass::Public, b"ping");
 
-        kernel.exit_process(first, ExitStatus::exited(1)).unwrap();
-        kernel.exit_process(second, ExitStatus::exited(2)).unwrap();
+        assert!(matches!(
+            kernel.set_class_quota(user, consumer, SecurityClass::Confidential, Some(1)),
+            Err(KernelError::SecurityViolation(
+                IsolationError::CapabilityMissing
+            ))
+        ));
+        kernel
+            .set_class_quota(consumer, consumer, SecurityClass::Confidential, Some(2))
+            .unwrap();
+        kernel.send_message(init, consumer, bulk).unwrap();
+        kernel.send_message(init, consumer, bulk).unwrap();
+        assert!(matches!(
+            kernel.send_message(init, consumer, bulk),
+            Err(KernelError::ClassQuotaExceeded)
+        ));
+        kernel.send_message(init, consumer, ping).unwrap();
 
+        // Raising the cap at runtime admits more of the class.
+        kernel
+            .set_class_quota(init, consumer, SecurityClass::Confidential, Some(3))
+            .unwrap();
+        kernel.send_message(init, consumer, bulk).unwrap();
         assert_eq!(
             kernel
-                .wait_for_child(parent, None, second.raw() as i64, 0, 0)
+                .queue_class_occupancy(consumer, SecurityClass::Confidential)
                 .unwrap(),
-            second.raw()
+            ClassOccupancy {
+                queued: 3,
+                quota: Some(3),
+            }
         );
-        assert_eq!(process_state(&kernel, first), ProcessState::Zombie);
-    }
-
-    #[test]
-    fn wait_nohang_returns_zero_without_blocking() {
-        let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
-            .unwrap();
-        let thread = first_thread(&kernel, parent);
 
+        while kernel.receive_message(consumer).is_ok() {}
         assert_eq!(
             kernel
-                .wait_for_child(parent, Some(thread), child.raw() as i64, 0, 1)
-                .unwrap(),
-            0
-        );
-        assert_eq!(process_state(&kernel, parent), ProcessState::Ready);
-        assert_eq!(
-            kernel.process_table[kernel.locate_process(parent).unwrap()]
+                .queue_class_occupancy(consumer, SecurityClass::Confidential)
                 .unwrap()
-                .child_wait,
-            None
+                .queued,
+            0
         );
+        assert!(matches!(
+            kernel.set_class_quota(consumer, consumer, SecurityClass::Public, Some(5)),
+            Err(KernelError::InvalidArgument)
+        ));
     }
 
     #[test]
-    fn syscall_exit_marks_zombie_releases_resources_and_wakes_waiter() {
+    fn ipc_credits_gate_sends_per_pair() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::user())
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let producer = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let parent_thread = first_thread(&kernel, parent);
-        let child_thread = first_thread(&kernel, child);
-        let fd = kernel
-            .handle_syscall(
-                SyscallNumber::Eventfd.raw(),
-                SyscallContext::new(child, Some(child_thread), [0, 0, 0, 0, 0, 0]),
-            )
-            .unwrap() as usize;
-        let description = kernel.fd_description(child, fd).unwrap();
-        kernel
-            .grant_task_capability(
-                child,
-                CapabilityObject::ServiceControl,
-                CapabilityRights::service_control(),
-            )
+        let consumer = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"item");
 
+        assert_eq!(kernel.credit_balance(producer, consumer), None);
         assert!(matches!(
-            kernel.wait_for_child(parent, Some(parent_thread), child.raw() as i64, 0, 0),
-            Err(KernelError::MessageQueueEmpty)
+            kernel.set_credit_auto_regrant(consumer, producer, true),
+            Err(KernelError::InvalidArgument)
         ));
+        assert_eq!(kernel.grant_credits(consumer, producer, 2).unwrap(), 2);
+        kernel.send_message(producer, consumer, payload).unwrap();
+        kernel.send_message(producer, consumer, payload).unwrap();
+        assert_eq!(kernel.credit_balance(producer, consumer), Some(0));
+        assert!(matches!(
+            kernel.send_message(producer, consumer, payload),
+            Err(KernelError::NoCredits)
+        ));
+
+        // Other senders to the same receiver are not flow controlled.
+        kernel.send_message(init, consumer, payload).unwrap();
+
+        // Draining without auto-regrant leaves the balance at zero.
+        while kernel.receive_message(consumer).is_ok() {}
+        assert_eq!(kernel.credit_balance(producer, consumer), Some(0));
+        assert_eq!(kernel.grant_credits(consumer, producer, 1).unwrap(), 1);
+        kernel.send_message(producer, consumer, payload).unwrap();
+        kernel.receive_message(consumer).unwrap();
 
+        // With auto-regrant a depth-2 pipeline keeps flowing indefinitely.
+        kernel.grant_credits(consumer, producer, 2).unwrap();
         kernel
-            .handle_syscall(
-                SyscallNumber::Exit.raw(),
-                SyscallContext::new(child, Some(child_thread), [23, 0, 0, 0, 0, 0]),
-            )
+            .set_credit_auto_regrant(consumer, producer, true)
             .unwrap();
+        kernel.send_message(producer, consumer, payload).unwrap();
+        let mut round = 0;
+        while round < 10 {
+            kernel.send_message(producer, consumer, payload).unwrap();
+            assert!(matches!(
+                kernel.send_message(producer, consumer, payload),
+                Err(KernelError::NoCredits)
+            ));
+            kernel.receive_message(consumer).unwrap();
+            assert_eq!(kernel.credit_balance(producer, consumer), Some(1));
+            round += 1;
+        }
 
-        assert_eq!(process_state(&kernel, child), ProcessState::Zombie);
-        assert_eq!(process_state(&kernel, parent), ProcessState::Ready);
-        assert!(matches!(
-            kernel.locate_thread(child_thread),
-            Err(KernelError::UnknownThread)
-        ));
-        assert!(matches!(
-            kernel.open_files.ref_count(description),
-            Err(FileTableError::InvalidDescriptor)
-        ));
-        assert!(matches!(
-            kernel.check_service_control_capability(child),
-            Err(KernelError::SecurityViolation(
-                IsolationError::UnknownTask | IsolationError::CapabilityMissing
-            ))
-        ));
-        assert_eq!(
-            kernel.process_table[kernel.locate_process(parent).unwrap()]
-                .unwrap()
-                .child_wait,
-            None
-        );
+        kernel.terminate_process(consumer);
+        assert_eq!(kernel.credit_balance(producer, consumer), None);
     }
 
     #[test]
-    fn syscall_trap_exit_does_not_resume_or_requeue_exited_thread() {
-        let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
+    fn exported_queue_imports_into_another_kernel() {
+        let mut source = boot_kernel();
+        let init = source.spawn_initial_process(Credentials::system()).unwrap();
+        let peer = source
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let child_thread = first_thread(&kernel, child);
+        let first = MessagePayload::from_slice(SecurityClass::Public, b"one");
+        let second = MessagePayload::from_slice(SecurityClass::Internal, b"two");
+        source.send_message(init, peer, first).unwrap();
+        source.send_message(init, peer, second).unwrap();
 
-        let scheduled_parent = kernel.kernel_schedule_next().unwrap();
-        assert_eq!(scheduled_parent.process, parent);
-        let scheduled_child = kernel
-            .kernel_yield_current(scheduled_parent)
-            .unwrap()
-            .unwrap();
-        assert_eq!(scheduled_child.process, child);
-        kernel.pending_mtss_decision = Some(scheduled_child);
+        let mut wire = [0u8; MESSAGE_WIRE_SIZE * 2];
+        assert!(matches!(
+            source.export_queue(peer, &mut wire[..MESSAGE_WIRE_SIZE]),
+            Err(KernelError::InvalidArgument)
+        ));
+        assert_eq!(
+            source.export_queue(peer, &mut wire).unwrap(),
+            MESSAGE_WIRE_SIZE * 2
+        );
+        assert_eq!(source.receive_message(peer).unwrap().payload, first);
 
-        let child_index = kernel.locate_thread(child_thread).unwrap();
-        kernel.thread_table[child_index]
-            .as_mut()
-            .unwrap()
-            .prepare_syscall(SyscallNumber::Exit.raw(), [17, 0, 0, 0, 0, 0]);
+        let mut target = boot_kernel();
+        let target_init = target.spawn_initial_process(Credentials::system()).unwrap();
+        let target_peer = target
+            .spawn_child_process(
+                target_init,
+                0,
+                ProcessPriority::Normal,
+                Credentials::system(),
+            )
+            .unwrap();
+        assert_eq!((target_init, target_peer), (init, peer));
 
-        kernel.run_core(0);
+        target.import_message(&wire[..MESSAGE_WIRE_SIZE]).unwrap();
+        target.import_message(&wire[MESSAGE_WIRE_SIZE..]).unwrap();
+        let imported = target.receive_message(target_peer).unwrap();
+        assert_eq!(imported.sender, init);
+        assert_eq!(imported.payload, first);
+        assert_eq!(target.receive_message(target_peer).unwrap().payload, second);
 
-        assert_eq!(process_state(&kernel, child), ProcessState::Zombie);
+        wire[8..16].copy_from_slice(&999u64.to_le_bytes());
         assert!(matches!(
-            kernel.locate_thread(child_thread),
-            Err(KernelError::UnknownThread)
+            target.import_message(&wire[..MESSAGE_WIRE_SIZE]),
+            Err(KernelError::SecurityViolation(_))
+        ));
+        wire[0] = 0;
+        assert!(matches!(
+            target.import_message(&wire[..MESSAGE_WIRE_SIZE]),
+            Err(KernelError::InvalidArgument)
         ));
-        assert!(kernel
-            .pending_mtss_decision
-            .map(|record| record.thread != child_thread && record.process != child)
-            .unwrap_or(true));
-        if let Some(record) = kernel.kernel_schedule_next() {
-            assert_ne!(record.thread, child_thread);
-            assert_ne!(record.process, child);
-        }
     }
 
     #[test]
-    fn kernel_yield_current_returns_and_defers_mtss_selected_thread() {
+    fn urgent_messages_are_received_before_bulk() {
+        use crate::kernel::ipc::MessagePriority;
+
         let mut kernel = boot_kernel();
-        let first = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let second = kernel
-            .spawn_child_process(first, 0, ProcessPriority::Normal, Credentials::system())
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let peer = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
+        let bulk = |tag: &[u8]| {
+            MessagePayload::from_slice(SecurityClass::System, tag)
+                .with_priority(MessagePriority::Bulk)
+        };
+        let urgent = |tag: &[u8]| {
+            MessagePayload::from_slice(SecurityClass::Public, tag)
+                .with_priority(MessagePriority::Urgent)
+        };
 
-        let scheduled = kernel.kernel_schedule_next().unwrap();
-        assert_eq!(scheduled.process, first);
-
-        let next = kernel.kernel_yield_current(scheduled).unwrap().unwrap();
-        assert_eq!(next.process, second);
+        kernel.send_message(init, peer, bulk(b"b1")).unwrap();
+        kernel.send_message(init, peer, urgent(b"u1")).unwrap();
+        kernel.send_message(init, peer, bulk(b"b2")).unwrap();
+        kernel.send_message(init, peer, urgent(b"u2")).unwrap();
 
-        kernel.pending_mtss_decision = Some(next);
-        assert_eq!(kernel.kernel_schedule_next().unwrap().process, second);
+        let mut order = [[0u8; 2]; 4];
+        let mut idx = 0;
+        while idx < order.len() {
+            let message = kernel.receive_message(peer).unwrap();
+            order[idx].copy_from_slice(&message.payload.data[..2]);
+            idx += 1;
+        }
+        assert_eq!(order, [*b"u1", *b"u2", *b"b1", *b"b2"]);
     }
 
     #[test]
-    fn supervisor_starts_l2_before_device_daemons() {
+    fn corrupted_queued_message_is_reported_and_dropped() {
         let mut kernel = boot_kernel();
-        let supervisor = crate::supervisor::Supervisor::new();
-
-        let report = supervisor.bootstrap_services(&mut kernel);
+        kernel.set_ipc_checksums(true);
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let payload = |tag: &[u8]| MessagePayload::from_slice(SecurityClass::Public, tag);
+        kernel.send_message(pid, pid, payload(b"one")).unwrap();
+        kernel.send_message(pid, pid, payload(b"two")).unwrap();
 
-        assert!(report.all_running());
-        let l2 = report
-            .pid(crate::supervisor::ServiceId::L2Subkernel)
-            .unwrap();
-        assert_eq!(l2.raw(), 1);
-        assert_eq!(
-            report.state(crate::supervisor::ServiceId::Storaged),
-            Some(crate::supervisor::StartupState::Running)
-        );
-        assert_eq!(
-            report.state(crate::supervisor::ServiceId::Usbd),
-            Some(crate::supervisor::StartupState::Running)
-        );
-        assert_eq!(
-            report.state(crate::supervisor::ServiceId::Nvmed),
-            Some(crate::supervisor::StartupState::Running)
-        );
-        assert_eq!(
-            report.state(crate::supervisor::ServiceId::Ahcid),
-            Some(crate::supervisor::StartupState::Running)
-        );
-        assert_eq!(
-            report.state(crate::supervisor::ServiceId::Displayd),
-            Some(crate::supervisor::StartupState::Running)
-        );
-        assert_eq!(
-            report.state(crate::supervisor::ServiceId::AmdgpuDisplayd),
-            Some(crate::supervisor::StartupState::Running)
-        );
-        assert_eq!(
-            report.state(crate::supervisor::ServiceId::Networkd),
-            Some(crate::supervisor::StartupState::Running)
-        );
-        assert_eq!(
-            report.state(crate::supervisor::ServiceId::Inputd),
-            Some(crate::supervisor::StartupState::Running)
-        );
+        let index = kernel.locate_process(pid).unwrap();
+        kernel.ipc_queues[index]
+            .message_at_mut(0)
+            .unwrap()
+            .payload
+            .length = 64;
 
-        let mut idx = 0usize;
-        let mut child_count = 0usize;
-        while idx < Kernel::<16, 4>::THREAD_CAPACITY {
-            if idx < kernel.process_table.len() {
-                if let Some(pcb) = kernel.process_table[idx] {
-                    if pcb.pid != l2 {
-                        assert_eq!(pcb.parent, Some(l2));
-                        child_count += 1;
-                    }
-                }
-            }
-            idx += 1;
-        }
-        assert_eq!(child_count, 8);
+        assert!(matches!(
+            kernel.receive_message(pid),
+            Err(KernelError::MessageCorrupted)
+        ));
+        let message = kernel.receive_message(pid).unwrap();
+        assert_eq!(&message.payload.data[..message.payload.length], b"two");
+        assert!(matches!(
+            kernel.receive_message(pid),
+            Err(KernelError::MessageQueueEmpty)
+        ));
     }
 
     #[test]
-    fn receive_or_block_returns_queued_message_without_blocking() {
+    fn exec_timeout_terminates_process_at_deadline() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let payload = MessagePayload::from_slice(SecurityClass::Public, b"ping");
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let worker = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let spared = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let state = |kernel: &Kernel<16, 4>, pid| {
+            let index = kernel.locate_process(pid).unwrap();
+            kernel.process_table[index].as_ref().unwrap().state
+        };
 
-        kernel.send_message(pid, pid, payload).unwrap();
+        kernel.exec_timeout(worker, 5).unwrap();
+        kernel.exec_timeout(spared, 5).unwrap();
+        kernel.cancel_exec_timeout(spared).unwrap();
+        let deadline = {
+            let index = kernel.locate_process(worker).unwrap();
+            kernel.process_table[index]
+                .as_ref()
+                .unwrap()
+                .execution_deadline
+                .unwrap()
+        };
 
-        let message = kernel.receive_or_block(pid).unwrap().unwrap();
-        assert_eq!(message.sender, pid);
-        assert_eq!(message.receiver, pid);
-        assert_eq!(&message.payload.data[..message.payload.length], b"ping");
-        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+        assert_eq!(kernel.expire_exec_deadlines(deadline - 1), 0);
+        assert_ne!(state(&kernel, worker), ProcessState::Zombie);
+        assert_eq!(kernel.expire_exec_deadlines(deadline), 1);
+        assert_eq!(state(&kernel, worker), ProcessState::Zombie);
+        assert_eq!(kernel.expire_exec_deadlines(deadline + 100), 0);
+        assert_ne!(state(&kernel, spared), ProcessState::Zombie);
     }
 
     #[test]
-    fn receive_or_block_atomically_blocks_empty_receiver() {
+    fn wake_all_rotates_first_queued_thread() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-
-        let message = kernel.receive_or_block(pid).unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let server = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        let threads = [
+            first_thread(&kernel, server),
+            kernel
+                .spawn_thread(server, 0x1000, ProcessPriority::Normal)
+                .unwrap(),
+            kernel
+                .spawn_thread(server, 0x2000, ProcessPriority::Normal)
+                .unwrap(),
+        ];
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"wake");
+
+        let mut firsts = [ThreadId::new(0); 3];
+        let mut round = 0;
+        while round < firsts.len() {
+            kernel.block_for_message(server);
+            for thread in threads {
+                kernel
+                    .mtss_scheduler
+                    .block_thread(Kernel::<16, 4>::mtss_thread_id(thread))
+                    .unwrap();
+            }
+            kernel.send_message(init, server, payload).unwrap();
+            kernel.receive_message(server).unwrap();
 
-        assert!(message.is_none());
-        assert_eq!(process_state(&kernel, pid), ProcessState::Blocked);
-        assert!(process_threads_blocked(&kernel, pid));
+            firsts[round] = *threads
+                .iter()
+                .min_by_key(|thread| kernel.run_queue_position(**thread).unwrap())
+                .unwrap();
+            round += 1;
+        }
+        assert_eq!(firsts, threads);
     }
 
     #[test]
-    fn libc_receive_uses_blocking_receive_syscall() {
+    fn freshly_bootstrapped_kernel_passes_post_bootstrap_check() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let thread = first_thread(&kernel, pid);
-        let mut out = Message::new(
-            ProcessId::new(0),
-            ProcessId::new(0),
-            0,
-            MessagePayload::empty(SecurityClass::Public),
-        );
-
-        let received =
-            libc::receive_ipc_or_block(&mut kernel, pid, Some(thread), &mut out).unwrap();
+        kernel.post_bootstrap_check().unwrap();
 
-        assert_eq!(received, None);
-        assert_eq!(process_state(&kernel, pid), ProcessState::Blocked);
-        assert!(process_threads_blocked(&kernel, pid));
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        kernel.bootstrap();
+        kernel.post_bootstrap_check().unwrap();
+        assert!(kernel.locate_process(init).is_err());
     }
 
     #[test]
-    fn security_errors_preserve_isolation_reason() {
+    fn corrupted_kernel_fails_post_bootstrap_check() {
         let mut kernel = boot_kernel();
-        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let user = kernel
-            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
-            .unwrap();
-        let mut buffer = [0u8; 8];
+        kernel.spawn_initial_process(Credentials::system()).unwrap();
+        assert!(matches!(
+            kernel.post_bootstrap_check(),
+            Err(KernelError::UnknownThread)
+        ));
 
+        kernel.bootstrap();
+        kernel.install_process(
+            0,
+            ProcessControlBlock::new(ProcessId::new(9), 0, ProcessPriority::Normal, None),
+        );
         assert!(matches!(
-            kernel.device_read(user, DeviceId::new(1), &mut buffer),
-            Err(KernelError::SecurityViolation(
-                IsolationError::CapabilityMissing
-            ))
+            kernel.post_bootstrap_check(),
+            Err(KernelError::UnknownProcess)
         ));
 
+        kernel.bootstrap();
+        kernel.core_states[0].offline();
         assert!(matches!(
-            kernel.send_message(
-                user,
-                ProcessId::new(999),
-                MessagePayload::empty(SecurityClass::Public)
-            ),
-            Err(KernelError::SecurityViolation(IsolationError::UnknownTask))
+            kernel.post_bootstrap_check(),
+            Err(KernelError::InvalidArgument)
         ));
     }
 
     #[test]
-    fn service_registry_routes_ipc_and_gates_raw_device_access() {
+    fn raw_syscalls_decode_dispatch_and_pack_errors() {
         let mut kernel = boot_kernel();
-        let l2 = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let displayd = kernel
-            .spawn_child_process(
-                l2,
-                0,
-                ProcessPriority::High,
-                Credentials::new(
-                    SecurityLabel::internal(),
-                    CapabilitySet::ipc_io(),
-                    IsolationLevel::Process,
-                ),
-            )
-            .unwrap();
-        let user = kernel
-            .spawn_child_process(l2, 0, ProcessPriority::Normal, Credentials::user())
-            .unwrap();
-
-        kernel
-            .register_service(l2, RegistryServiceId::Displayd, displayd)
-            .unwrap();
-
-        let payload = MessagePayload::from_slice(SecurityClass::Public, b"draw");
-        kernel
-            .send_service_message(user, RegistryServiceId::Displayd, payload)
-            .unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
 
-        let message = kernel.receive_message(displayd).unwrap();
-        assert_eq!(message.sender, user);
-        assert_eq!(message.receiver, displayd);
-        assert_eq!(&message.payload.data[..message.payload.length], b"draw");
+        let get_pid = SyscallFrame {
+            number: SyscallNumber::GetPid.raw(),
+            args: [0; 6],
+        };
+        assert_eq!(kernel.syscall_raw(init, &get_pid), init.raw());
 
-        let mut buffer = [0u8; 64];
-        assert!(matches!(
-            kernel.device_read(displayd, DeviceId::new(5), &mut buffer),
-            Err(KernelError::SecurityViolation(
-                IsolationError::PolicyViolation
-            ))
-        ));
+        let unknown = SyscallFrame {
+            number: 99,
+            args: [0; 6],
+        };
+        assert_eq!(
+            kernel.syscall_raw(init, &unknown),
+            MIRAGE_SYSCALL_ERROR_BIT | SyscallErrorCode::InvalidSyscall.raw()
+        );
 
-        kernel
-            .claim_service_device(displayd, RegistryServiceId::Displayd, DeviceId::new(5))
-            .unwrap();
-        assert!(kernel
-            .device_read(displayd, DeviceId::new(5), &mut buffer)
-            .is_ok());
+        let stray_register = SyscallFrame {
+            number: SyscallNumber::GetPid.raw(),
+            args: [0, 0, 0, 0, 0, 1],
+        };
+        assert_eq!(
+            kernel.syscall_raw(init, &stray_register),
+            MIRAGE_SYSCALL_ERROR_BIT | SyscallErrorCode::InvalidArgument.raw()
+        );
     }
 
     #[test]
-    fn kernel_exit_reports_without_registry_policy_cleanup() {
+    fn message_sent_before_block_is_not_stranded() {
         let mut kernel = boot_kernel();
-        let l2 = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let networkd = kernel
-            .spawn_child_process(
-                l2,
-                0,
-                ProcessPriority::High,
-                Credentials::new(
-                    SecurityLabel::internal(),
-                    CapabilitySet::ipc_io(),
-                    IsolationLevel::Process,
-                ),
-            )
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let server = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = 0x504e_0000;
+        }
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"early");
 
-        kernel
-            .register_service(l2, RegistryServiceId::Networkd, networkd)
-            .unwrap();
-        kernel
-            .claim_service_device(networkd, RegistryServiceId::Networkd, DeviceId::new(6))
-            .unwrap();
+        kernel.send_message(init, server, payload).unwrap();
+        assert!(!kernel.block_for_message(server));
+        kernel.tick();
 
-        let exit = kernel
-            .exit_process(networkd, ExitStatus::exited(0))
+        assert_ne!(process_state(&kernel, server), ProcessState::Blocked);
+        assert_eq!(kernel.receive_message(server).unwrap().sender, init);
+        assert!(kernel.block_for_message(server));
+        assert_eq!(process_state(&kernel, server), ProcessState::Blocked);
+    }
+
+    fn spinning_server(root: u64) -> (Box<Kernel<16, 4>>, ProcessId, ProcessId) {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let server = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = root;
+        }
+        kernel.set_message_spin(server, 4).unwrap();
+        (kernel, init, server)
+    }
 
-        assert_eq!(exit.pid, networkd);
-        assert_eq!(exit.status.raw(), ExitStatus::exited(0).raw());
-        assert_eq!(
-            kernel.service_owner(RegistryServiceId::Networkd),
-            Some(networkd)
-        );
-        assert!(matches!(
-            kernel.device_write(networkd, DeviceId::new(6), b"ping"),
-            Err(KernelError::SecurityViolation(IsolationError::UnknownTask))
-        ));
+    #[test]
+    fn message_within_the_spin_window_skips_the_block() {
+        let (mut kernel, init, server) = spinning_server(0x5350_0000);
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"soon");
+
+        assert!(kernel.block_for_message(server));
+        assert!(kernel.message_spin(server).unwrap().is_spinning());
+        for _ in 0..2 {
+            kernel.tick();
+            assert_ne!(process_state(&kernel, server), ProcessState::Blocked);
+        }
+        kernel.send_message(init, server, payload).unwrap();
+        kernel.run_ticks(4);
+
+        assert_ne!(process_state(&kernel, server), ProcessState::Blocked);
+        let spin = kernel.message_spin(server).unwrap();
+        assert!(!spin.is_spinning());
+        assert_eq!(spin.stats(), SpinStats { hits: 1, misses: 0 });
+        assert_eq!(kernel.receive_message(server).unwrap().sender, init);
     }
 
     #[test]
-    fn syscall_error_encoding_maps_structured_security_reasons() {
-        assert_eq!(
-            encode_syscall_error(KernelError::SecurityViolation(
-                IsolationError::CapabilityMissing
-            )),
-            MIRAGE_SYSCALL_ERROR_BIT | SyscallErrorCode::PermissionDenied.raw()
-        );
+    fn message_after_the_spin_window_blocks_as_before() {
+        let (mut kernel, init, server) = spinning_server(0x5350_1000);
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"late");
+
+        assert!(kernel.block_for_message(server));
+        kernel.run_ticks(4);
+        assert_eq!(process_state(&kernel, server), ProcessState::Blocked);
         assert_eq!(
-            encode_syscall_error(KernelError::SecurityViolation(IsolationError::UnknownTask)),
-            MIRAGE_SYSCALL_ERROR_BIT | SyscallErrorCode::NoSuchProcess.raw()
+            kernel.message_spin(server).unwrap().stats(),
+            SpinStats { hits: 0, misses: 1 }
         );
+
+        kernel.run_ticks(6);
+        kernel.send_message(init, server, payload).unwrap();
+        assert_ne!(process_state(&kernel, server), ProcessState::Blocked);
+        kernel.receive_message(server).unwrap();
+
+        // Once waits average past the four-tick cap, the next wait blocks
+        // without spinning.
+        assert!(kernel.block_for_message(server));
+        kernel.run_ticks(10);
+        kernel.send_message(init, server, payload).unwrap();
+        kernel.receive_message(server).unwrap();
+        assert_eq!(kernel.message_spin(server).unwrap().window(), 0);
+        assert!(kernel.block_for_message(server));
+        assert_eq!(process_state(&kernel, server), ProcessState::Blocked);
         assert_eq!(
-            encode_syscall_error(KernelError::MessageQueueFull),
-            MIRAGE_SYSCALL_ERROR_BIT | SyscallErrorCode::QueueFull.raw()
+            kernel.message_spin(server).unwrap().stats(),
+            SpinStats { hits: 0, misses: 2 }
         );
     }
 
     #[test]
-    fn nanosleep_blocks_until_kernel_time_deadline() {
+    fn pending_work_counts_every_kind_of_deliverable_work() {
+        const SIGUSR1: u8 = 10;
+        const SIGUSR2: u8 = 12;
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let thread = first_thread(&kernel, pid);
-        let req = MirageTimespec {
-            tv_sec: 0,
-            tv_nsec: 1,
-        };
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let server = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        assert!(kernel.pending_work(server).unwrap().is_empty());
 
+        let child = kernel
+            .spawn_child_process(server, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        kernel.exit_process(child, ExitStatus::exited(0));
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"work");
+        kernel.send_message(init, server, payload).unwrap();
+        kernel.timers.add_sleep(server, None, 0).unwrap();
+        let mut mask = SignalMask::EMPTY;
+        mask.insert(SIGUSR2);
         kernel
-            .handle_syscall(
-                SyscallNumber::Nanosleep.raw(),
-                SyscallContext::new(
-                    pid,
-                    Some(thread),
-                    [&req as *const MirageTimespec as u64, 0, 0, 0, 0, 0],
-                ),
-            )
+            .set_signal_mask(first_thread(&kernel, server), mask)
             .unwrap();
+        kernel.queue_signal(server, SIGUSR1).unwrap();
+        kernel.queue_signal(server, SIGUSR2).unwrap();
 
-        assert_eq!(process_state(&kernel, pid), ProcessState::Blocked);
-        assert!(process_threads_blocked(&kernel, pid));
-
-        kernel.tick();
-
-        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
         assert_eq!(
-            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
-                .unwrap()
-                .state,
-            ThreadState::Ready
+            kernel.pending_work(server).unwrap(),
+            PendingWork {
+                messages: 1,
+                expired_timers: 1,
+                // SIGCHLD from the exited child plus SIGUSR1.
+                signals: 2,
+                lifecycle_events: 1,
+            }
         );
+        assert!(!kernel.block_for_message(server));
+        assert!(matches!(
+            kernel.receive_or_block(server),
+            Ok(Some(message)) if message.sender == init
+        ));
+        assert_eq!(kernel.receive_or_block(server).unwrap(), None);
+        assert_ne!(process_state(&kernel, server), ProcessState::Blocked);
     }
 
     #[test]
-    fn tls_syscalls_record_fs_and_gs_bases() {
-        let mut kernel = boot_kernel();
+    fn waitlisted_thread_spawns_once_a_slot_frees() {
+        let mut kernel = Kernel::<8, 4, 1, 4>::new();
+        kernel.bootstrap();
         let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let thread = first_thread(&kernel, pid);
-        let mut out = 0u64;
+        let mut victim = None;
+        while kernel.pending_thread_spawns() == 0 {
+            match kernel
+                .spawn_thread_waitlisted(pid, 0x1000, ProcessPriority::Normal)
+                .unwrap()
+            {
+                Some(thread) => victim = Some(thread),
+                None => break,
+            }
+        }
+        assert_eq!(kernel.pending_thread_spawns(), 1);
+        assert!(kernel
+            .spawn_thread_waitlisted(pid, 0x2000, ProcessPriority::Normal)
+            .unwrap()
+            .is_none());
 
-        kernel
-            .handle_syscall(
-                SyscallNumber::SetThreadArea.raw(),
-                SyscallContext::new(pid, Some(thread), [0x7000, 0, 0, 0, 0, 0]),
-            )
-            .unwrap();
-        assert_eq!(kernel.thread_context(thread).unwrap().fs_base, 0x7000);
+        // `tick` runs this after its timer work; calling it directly keeps the
+        // scripted threads from being dispatched.
+        assert_eq!(kernel.service_thread_waitlist(), 0);
+        assert_eq!(kernel.pending_thread_spawns(), 2);
+
+        kernel.terminate_thread(victim.unwrap());
+        assert_eq!(kernel.service_thread_waitlist(), 1);
+        assert_eq!(kernel.pending_thread_spawns(), 1);
+        let mut entries = [0u64; 4];
+        let mut idx = 0;
+        while idx < 4 {
+            entries[idx] = kernel.thread_table[idx].unwrap().entry_point;
+            idx += 1;
+        }
+        assert_eq!(entries.iter().filter(|entry| **entry == 0x1000).count(), 3);
+        assert!(!entries.contains(&0x2000));
+    }
+
+    #[test]
+    fn pids_after_init_are_odd_and_non_sequential() {
+        let mut kernel = Kernel::<8, 4, 1, 16>::new();
+        kernel.bootstrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        assert_eq!(init.raw(), 1);
+
+        let mut pids = [0u64; 4];
+        let mut idx = 0;
+        while idx < pids.len() {
+            pids[idx] = kernel
+                .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+                .unwrap()
+                .raw();
+            idx += 1;
+        }
+        let mut sequential = true;
+        idx = 0;
+        while idx < pids.len() {
+            assert_eq!(pids[idx] & 1, 1);
+            assert!(pids[idx] <= i32::MAX as u64);
+            assert!(!pids[..idx].contains(&pids[idx]));
+            if idx > 0 && pids[idx] != pids[idx - 1] + 1 {
+                sequential = false;
+            }
+            idx += 1;
+        }
+        assert!(!sequential);
+    }
 
-        kernel
-            .handle_syscall(
-                SyscallNumber::ArchPrctl.raw(),
-                SyscallContext::new(pid, Some(thread), [ARCH_SET_FS, 0x8000, 0, 0, 0, 0]),
-            )
-            .unwrap();
-        kernel
-            .handle_syscall(
-                SyscallNumber::ArchPrctl.raw(),
-                SyscallContext::new(
-                    pid,
-                    Some(thread),
-                    [ARCH_GET_FS, &mut out as *mut u64 as u64, 0, 0, 0, 0],
-                ),
-            )
+    #[test]
+    fn colliding_pid_candidate_is_retried_and_counted() {
+        let mut kernel = Kernel::<8, 4, 1, 16>::new();
+        kernel.bootstrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let seed = kernel.pid_seed;
+        let first = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        assert_eq!(out, 0x8000);
 
-        kernel
-            .handle_syscall(
-                SyscallNumber::ArchPrctl.raw(),
-                SyscallContext::new(pid, Some(thread), [ARCH_SET_GS, 0x9000, 0, 0, 0, 0]),
-            )
-            .unwrap();
-        kernel
-            .handle_syscall(
-                SyscallNumber::ArchPrctl.raw(),
-                SyscallContext::new(
-                    pid,
-                    Some(thread),
-                    [ARCH_GET_GS, &mut out as *mut u64 as u64, 0, 0, 0, 0],
-                ),
-            )
+        kernel.pid_seed = seed;
+        let second = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        assert_eq!(out, 0x9000);
+        assert_ne!(first, second);
+        assert_eq!(kernel.pid_collision_count(), 1);
     }
 
     #[test]
-    fn futex_wait_blocks_and_wake_requeues_thread() {
+    fn security_invariants_catch_missing_and_orphaned_domains() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let thread = first_thread(&kernel, pid);
-        let word = 7i32;
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        assert_kernel_consistent(&kernel);
 
+        kernel.security.revoke_task(child);
+        assert!(matches!(
+            kernel.assert_security_invariants(),
+            Err(KernelError::SecurityViolation(IsolationError::UnknownTask))
+        ));
         kernel
-            .handle_syscall(
-                SyscallNumber::Futex.raw(),
-                SyscallContext::new(
-                    pid,
-                    Some(thread),
-                    [&word as *const i32 as u64, FUTEX_WAIT, 7, 0, 0, 0],
-                ),
-            )
+            .security
+            .register_task(child, Credentials::user())
             .unwrap();
-        assert_eq!(process_state(&kernel, pid), ProcessState::Blocked);
-        assert_eq!(
-            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
-                .unwrap()
-                .state,
-            ThreadState::Blocked
-        );
 
-        let woken = kernel
-            .handle_syscall(
-                SyscallNumber::Futex.raw(),
-                SyscallContext::new(
-                    pid,
-                    None,
-                    [&word as *const i32 as u64, FUTEX_WAKE, 1, 0, 0, 0],
-                ),
-            )
+        let index = kernel.locate_process(child).unwrap();
+        kernel.process_table[index].as_mut().unwrap().security_label = SecurityLabel::system();
+        assert!(matches!(
+            kernel.assert_security_invariants(),
+            Err(KernelError::SecurityViolation(
+                IsolationError::PolicyViolation
+            ))
+        ));
+        kernel.process_table[index].as_mut().unwrap().security_label = SecurityLabel::internal();
+
+        kernel
+            .security
+            .register_task(ProcessId::new(u32::MAX as u64), Credentials::user())
             .unwrap();
+        assert!(matches!(
+            kernel.consistency_check(),
+            Err(KernelError::SecurityViolation(IsolationError::UnknownTask))
+        ));
+    }
 
-        assert_eq!(woken, 1);
-        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
-        assert_eq!(
-            kernel.thread_table[kernel.locate_thread(thread).unwrap()]
-                .unwrap()
-                .state,
-            ThreadState::Ready
-        );
+    struct DelayedInput {
+        byte: core::sync::atomic::AtomicU8,
     }
 
-    #[test]
-    fn futex_wait_timeout_wakes_on_tick_with_timed_out_result() {
-        let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let thread = first_thread(&kernel, pid);
-        let word = 3i32;
-        let timeout = MirageTimespec {
-            tv_sec: 0,
-            tv_nsec: 1,
-        };
+    impl crate::kernel::device::DeviceDriver for DelayedInput {
+        fn kind(&self) -> DeviceKind {
+            DeviceKind::SerialConsole
+        }
 
-        kernel
-            .handle_syscall(
-                SyscallNumber::Futex.raw(),
-                SyscallContext::new(
-                    pid,
-                    Some(thread),
-                    [
-                        &word as *const i32 as u64,
-                        FUTEX_WAIT,
-                        3,
-                        &timeout as *const MirageTimespec as u64,
-                        0,
-                        0,
-                    ],
-                ),
-            )
-            .unwrap();
+        fn name(&self) -> &'static str {
+            "delayed-input"
+        }
 
-        kernel.tick();
+        fn security(&self) -> crate::subkernel::DeviceSecurity {
+            crate::subkernel::DeviceSecurity::new(SecurityClass::Internal, false)
+        }
 
-        let context = kernel.thread_context(thread).unwrap();
-        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
-        assert_eq!(context.rax, encode_syscall_error(KernelError::TimedOut));
+        fn read(&self, buffer: &mut [u8]) -> Result<usize, DriverError> {
+            match self.byte.swap(0, core::sync::atomic::Ordering::SeqCst) {
+                0 => Ok(0),
+                byte => {
+                    buffer[0] = byte;
+                    Ok(1)
+                }
+            }
+        }
     }
 
+    static DELAYED_INPUT: DelayedInput = DelayedInput {
+        byte: core::sync::atomic::AtomicU8::new(0),
+    };
+
     #[test]
-    fn nanosleep_rejects_malformed_timespec() {
+    fn device_read_timeout_waits_for_input_then_times_out() {
         let mut kernel = boot_kernel();
-        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let req = MirageTimespec {
-            tv_sec: 0,
-            tv_nsec: 1_000_000_000,
-        };
-
-        assert!(matches!(
-            kernel.handle_syscall(
-                SyscallNumber::Nanosleep.raw(),
-                SyscallContext::new(
-                    pid,
-                    None,
-                    [&req as *const MirageTimespec as u64, 0, 0, 0, 0, 0,],
+        let l2 = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let consoled = kernel
+            .spawn_child_process(
+                l2,
+                0,
+                ProcessPriority::High,
+                Credentials::new(
+                    SecurityLabel::internal(),
+                    CapabilitySet::ipc_io(),
+                    IsolationLevel::Process,
                 ),
-            ),
-            Err(KernelError::InvalidArgument)
-        ));
-        assert_eq!(process_state(&kernel, pid), ProcessState::Ready);
+            )
+            .unwrap();
+        kernel
+            .register_service(l2, RegistryServiceId::Console, consoled)
+            .unwrap();
+        let input = kernel.devices.register_driver(&DELAYED_INPUT).unwrap().id;
+        kernel
+            .claim_service_device(consoled, RegistryServiceId::Console, input)
+            .unwrap();
+        let mut buffer = [0u8; 8];
+
+        assert_eq!(
+            kernel
+                .device_read_timeout(consoled, input, &mut buffer, 3)
+                .unwrap(),
+            DeviceReadOutcome::Pending
+        );
+        assert_eq!(process_state(&kernel, consoled), ProcessState::Blocked);
+        kernel.retry_device_reads();
+        assert_ne!(process_state(&kernel, consoled), ProcessState::Blocked);
+        assert_eq!(
+            kernel
+                .device_read_timeout(consoled, input, &mut buffer, 3)
+                .unwrap(),
+            DeviceReadOutcome::Pending
+        );
+
+        DELAYED_INPUT
+            .byte
+            .store(b'x', core::sync::atomic::Ordering::SeqCst);
+        kernel.retry_device_reads();
+        assert_eq!(
+            kernel
+                .device_read_timeout(consoled, input, &mut buffer, 3)
+                .unwrap(),
+            DeviceReadOutcome::Ready(1)
+        );
+        assert_eq!(buffer[0], b'x');
+
+        let mut outcome = kernel
+            .device_read_timeout(consoled, input, &mut buffer, 2)
+            .unwrap();
+        let mut ticks = 0;
+        while outcome == DeviceReadOutcome::Pending {
+            kernel.retry_device_reads();
+            ticks += 1;
+            outcome = kernel
+                .device_read_timeout(consoled, input, &mut buffer, 2)
+                .unwrap();
+        }
+        assert_eq!(outcome, DeviceReadOutcome::TimedOut);
+        assert_eq!(ticks, 2);
+        assert_kernel_consistent(&kernel);
     }
 
     #[test]
-    fn process_timers_are_owned_and_reject_bad_ids() {
+    fn realtime_child_is_dispatched_ahead_of_batch_parent() {
         let mut kernel = boot_kernel();
-        let owner = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let other = kernel
-            .spawn_child_process(owner, 0, ProcessPriority::Normal, Credentials::user())
+        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let child = kernel
+            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let mut timer_id = 0u64;
+        assert_eq!(kernel.sched_class(child).unwrap(), SchedClass::Interactive);
 
-        kernel
-            .handle_syscall(
-                SyscallNumber::TimerCreate.raw(),
-                SyscallContext::new(
-                    owner,
-                    None,
-                    [0, 0, &mut timer_id as *mut u64 as u64, 0, 0, 0],
-                ),
-            )
-            .unwrap();
-        assert_ne!(timer_id, 0);
+        kernel.set_sched_class(parent, SchedClass::Batch).unwrap();
+        kernel.set_sched_class(child, SchedClass::Realtime).unwrap();
+        assert_eq!(kernel.sched_class(parent).unwrap(), SchedClass::Batch);
+        assert_eq!(kernel.sched_class(child).unwrap(), SchedClass::Realtime);
 
+        let scheduled = kernel.kernel_schedule_next().unwrap();
+        assert_eq!(scheduled.process, child);
+        assert_eq!(scheduled.thread, first_thread(&kernel, child));
         assert!(matches!(
-            kernel.handle_syscall(
-                SyscallNumber::TimerGettime.raw(),
-                SyscallContext::new(
-                    other,
-                    None,
-                    [
-                        timer_id,
-                        &mut MirageItimerspec {
-                            it_interval: MirageTimespec {
-                                tv_sec: 0,
-                                tv_nsec: 0
-                            },
-                            it_value: MirageTimespec {
-                                tv_sec: 0,
-                                tv_nsec: 0
-                            },
-                        } as *mut MirageItimerspec as u64,
-                        0,
-                        0,
-                        0,
-                        0
-                    ]
-                ),
-            ),
-            Err(KernelError::InvalidArgument)
+            kernel.set_sched_class(ProcessId::new(0x7fff_fff1), SchedClass::Batch),
+            Err(KernelError::UnknownProcess)
         ));
     }
 
     #[test]
-    fn timer_settime_arms_gettime_and_delete() {
+    fn cpu_quota_throttles_busy_process_until_window_rolls_over() {
         let mut kernel = boot_kernel();
-        let owner = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let mut timer_id = 0u64;
-        let new_value = MirageItimerspec {
-            it_interval: MirageTimespec {
-                tv_sec: 0,
-                tv_nsec: 0,
-            },
-            it_value: MirageTimespec {
-                tv_sec: 0,
-                tv_nsec: 1,
-            },
-        };
-        let mut current = MirageItimerspec {
-            it_interval: MirageTimespec {
-                tv_sec: 0,
-                tv_nsec: 0,
-            },
-            it_value: MirageTimespec {
-                tv_sec: 0,
-                tv_nsec: 0,
-            },
+        kernel.kernel_mtss_init().unwrap();
+        let busy = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        // A realtime rival is never demoted for using up its slices, so only
+        // the throttle decides whether it outranks the busy process.
+        let rival = kernel
+            .spawn_child_process(busy, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        kernel.set_sched_class(rival, SchedClass::Realtime).unwrap();
+        set_address_space_root(&mut kernel, busy, 0x4f52_0000);
+        set_address_space_root(&mut kernel, rival, 0x4f52_0000);
+        let cpu_time = |kernel: &Kernel<16, 4>, pid: ProcessId| {
+            kernel.process_table[kernel.locate_process(pid).unwrap()]
+                .as_ref()
+                .unwrap()
+                .cpu_time
         };
+        assert!(matches!(
+            kernel.set_cpu_quota(busy, 2, 0),
+            Err(KernelError::InvalidArgument)
+        ));
+        kernel.set_cpu_quota(busy, 2, 10).unwrap();
+        let own = kernel.effective_priority(busy).unwrap();
+        assert_ne!(own, ProcessPriority::Low);
 
-        kernel
-            .handle_syscall(
-                SyscallNumber::TimerCreate.raw(),
-                SyscallContext::new(
-                    owner,
-                    None,
-                    [0, 0, &mut timer_id as *mut u64 as u64, 0, 0, 0],
-                ),
-            )
+        kernel.run_ticks(2);
+        assert_eq!(kernel.cpu_quota(busy).unwrap().unwrap().used(), 2);
+        assert_eq!(kernel.effective_priority(busy).unwrap(), own);
+        assert_eq!(cpu_time(&kernel, rival), 0);
+
+        kernel.run_ticks(1);
+        assert_eq!(
+            kernel.effective_priority(busy).unwrap(),
+            ProcessPriority::Low
+        );
+        // Throttled, the busy process loses the core to its rival for the
+        // rest of the window.
+        kernel.run_ticks(6);
+        assert_eq!(
+            kernel.effective_priority(busy).unwrap(),
+            ProcessPriority::Low
+        );
+        assert_eq!(cpu_time(&kernel, busy), 3);
+        assert_eq!(cpu_time(&kernel, rival), 6);
+        assert_eq!(kernel.cpu_quota(busy).unwrap().unwrap().used(), 3);
+
+        // Tick 10 opens the next window, and the busy process takes the core
+        // back from the tick after.
+        kernel.run_ticks(1);
+        assert_eq!(kernel.effective_priority(busy).unwrap(), own);
+        assert_eq!(kernel.cpu_quota(busy).unwrap().unwrap().used(), 0);
+        kernel.run_ticks(2);
+        assert_eq!(cpu_time(&kernel, busy), 5);
+        assert_eq!(cpu_time(&kernel, rival), 7);
+        assert_eq!(kernel.cpu_quota(busy).unwrap().unwrap().used(), 2);
+
+        kernel.run_ticks(1);
+        assert_eq!(
+            kernel.effective_priority(busy).unwrap(),
+            ProcessPriority::Low
+        );
+        kernel.clear_cpu_quota(busy).unwrap();
+        assert_eq!(kernel.effective_priority(busy).unwrap(), own);
+    }
+
+    #[test]
+    fn fault_dump_captures_process_threads_queue_and_domain() {
+        let mut kernel = boot_kernel();
+        let l2 = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let victim = kernel
+            .spawn_child_process(l2, 0, ProcessPriority::Normal, Credentials::user())
             .unwrap();
+        let victim_thread = first_thread(&kernel, victim);
         kernel
-            .handle_syscall(
-                SyscallNumber::TimerSettime.raw(),
-                SyscallContext::new(
-                    owner,
-                    None,
-                    [
-                        timer_id,
-                        0,
-                        &new_value as *const MirageItimerspec as u64,
-                        0,
-                        0,
-                        0,
-                    ],
-                ),
+            .send_message(
+                l2,
+                victim,
+                MessagePayload::from_slice(SecurityClass::Public, b"ping"),
             )
             .unwrap();
         kernel
-            .handle_syscall(
-                SyscallNumber::TimerGettime.raw(),
-                SyscallContext::new(
-                    owner,
-                    None,
-                    [
-                        timer_id,
-                        &mut current as *mut MirageItimerspec as u64,
-                        0,
-                        0,
-                        0,
-                        0,
-                    ],
-                ),
+            .send_message(
+                l2,
+                victim,
+                MessagePayload::from_slice(SecurityClass::Internal, b"note"),
             )
             .unwrap();
-        assert!(current.it_value.tv_sec > 0 || current.it_value.tv_nsec > 0);
 
-        kernel
-            .handle_syscall(
-                SyscallNumber::TimerDelete.raw(),
-                SyscallContext::new(owner, None, [timer_id, 0, 0, 0, 0, 0]),
-            )
+        let dump = kernel
+            .capture_fault_dump(victim, IsolationError::PolicyViolation)
             .unwrap();
-        assert!(matches!(
-            kernel.handle_syscall(
-                SyscallNumber::TimerDelete.raw(),
-                SyscallContext::new(owner, None, [timer_id, 0, 0, 0, 0, 0]),
-            ),
-            Err(KernelError::InvalidArgument)
-        ));
+        assert_eq!(dump.reason, IsolationError::PolicyViolation);
+        assert_eq!(dump.process.pid, victim);
+        assert_eq!(dump.thread_count, 1);
+        assert_eq!(dump.threads[0].unwrap().id, victim_thread);
+        assert_eq!(dump.queue_depth, 2);
+        let first = dump.messages[0].unwrap();
+        assert_eq!(first.sender, l2);
+        assert!(!first.redacted);
+        assert_eq!(&first.data[..first.length], b"ping");
+        assert_eq!(dump.domain.unwrap().label(), SecurityLabel::internal());
     }
 
     #[test]
-    fn process_file_tables_share_inherited_open_descriptions() {
+    fn fault_dump_of_confidential_victim_redacts_payloads() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let path = b"/fdshare\0";
-        let fd = kernel
-            .handle_syscall(
-                SyscallNumber::OpenAt.raw(),
-                SyscallContext::new(
-                    parent,
-                    None,
-                    [
-                        AT_FDCWD as u64,
-                        path.as_ptr() as u64,
-                        (crate::kernel::fs::O_RDWR
-                            | crate::kernel::fs::O_CREAT
-                            | crate::kernel::fs::O_CLOEXEC) as u64,
-                        0,
-                        0,
-                        0,
-                    ],
+        let l2 = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let victim = kernel
+            .spawn_child_process(
+                l2,
+                0,
+                ProcessPriority::Normal,
+                Credentials::new(
+                    SecurityLabel::confidential(),
+                    CapabilitySet::ipc(),
+                    IsolationLevel::Process,
                 ),
             )
-            .unwrap() as usize;
-        let description = kernel.fd_description(parent, fd).unwrap();
-        assert_eq!(kernel.open_files.ref_count(description).unwrap(), 1);
-        assert!(kernel
-            .process_files(parent)
-            .unwrap()
-            .get(fd)
-            .unwrap()
-            .close_on_exec());
-
-        let child = kernel
-            .spawn_child_process(parent, 0, ProcessPriority::Normal, Credentials::user())
             .unwrap();
-        assert_eq!(kernel.fd_description(child, fd).unwrap(), description);
-        assert_eq!(kernel.open_files.ref_count(description).unwrap(), 2);
-
         kernel
-            .handle_syscall(
-                SyscallNumber::Close.raw(),
-                SyscallContext::new(parent, None, [fd as u64, 0, 0, 0, 0, 0]),
+            .send_message(
+                l2,
+                victim,
+                MessagePayload::from_slice(SecurityClass::Public, b"ping"),
             )
             .unwrap();
-        assert_eq!(kernel.open_files.ref_count(description).unwrap(), 1);
 
-        kernel.terminate_process(child);
+        let dump = kernel
+            .capture_fault_dump(victim, IsolationError::PolicyViolation)
+            .unwrap();
+        let summary = dump.messages[0].unwrap();
+        assert!(summary.redacted);
+        assert_eq!(summary.length, 4);
+        assert_eq!(summary.data, [0; 64]);
+    }
+
+    #[test]
+    fn isolation_fault_still_terminates_and_publishes_dump() {
+        let mut kernel = boot_kernel();
+        let l2 = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let victim = kernel
+            .spawn_child_process(l2, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
         assert!(matches!(
-            kernel.open_files.ref_count(description),
-            Err(FileTableError::InvalidDescriptor)
+            kernel.set_fault_dump_limit(l2, 0),
+            Err(KernelError::InvalidArgument)
+        ));
+
+        kernel.handle_isolation_fault(victim, IsolationError::PolicyViolation);
+
+        assert_eq!(process_state(&kernel, victim), ProcessState::Zombie);
+        assert!(matches!(
+            kernel.take_fault_dump(victim),
+            Err(KernelError::SecurityViolation(_))
         ));
+        let dump = kernel.take_fault_dump(l2).unwrap().unwrap();
+        assert_eq!(dump.process.pid, victim);
+        assert!(kernel.take_fault_dump(l2).unwrap().is_none());
     }
 
     #[test]
-    fn syscall_spawn_spider_rsd_records_parent_and_runnable_mtss() {
+    fn stride_mode_gives_critical_four_times_the_cpu_of_low() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let path = b"/spider-rt/sbin/spider-rsd\0";
-        let child = kernel
-            .handle_syscall(
-                SyscallNumber::Spawn.raw(),
-                SyscallContext::new(
-                    parent,
-                    None,
-                    [path.as_ptr() as u64, (path.len() - 1) as u64, 0, 0, 0, 0],
-                ),
-            )
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let critical = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Critical, Credentials::system())
             .unwrap();
-        let child_pid = ProcessId::new(child);
-        let child_index = kernel.locate_process(child_pid).unwrap();
-        let child_pcb = kernel.process_table[child_index].unwrap();
-        assert_eq!(child_pcb.parent, Some(parent));
-        assert_eq!(child_pcb.state, ProcessState::Ready);
-        assert!(child_pcb.address_space_root != 0);
-
-        let mut saw_child = false;
-        let mut attempts = 0;
-        while attempts < 4 {
-            if let Some(record) = kernel.kernel_schedule_next() {
-                saw_child |= record.process == child_pid;
+        let low = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Low, Credentials::system())
+            .unwrap();
+        let critical_thread = Kernel::<16, 4>::mtss_thread_id(first_thread(&kernel, critical));
+        let low_thread = Kernel::<16, 4>::mtss_thread_id(first_thread(&kernel, low));
+
+        kernel.set_scheduling_mode(SchedulingMode::Stride);
+        kernel.kernel_schedule_next().unwrap();
+        let mut critical_ticks = 0u32;
+        let mut low_ticks = 0u32;
+        for _ in 0..1000 {
+            let current = kernel.mtss_scheduler.current();
+            if current == Some(critical_thread) {
+                critical_ticks += 1;
+            } else if current == Some(low_thread) {
+                low_ticks += 1;
             }
-            attempts += 1;
+            kernel.kernel_on_timer_tick();
         }
-        assert!(saw_child);
+
+        assert!(low_ticks > 0);
+        assert!(
+            critical_ticks * 2 >= low_ticks * 7 && critical_ticks * 2 <= low_ticks * 9,
+            "critical {critical_ticks} low {low_ticks}"
+        );
     }
 
     #[test]
-    fn syscall_spawn_resolves_m1_terminal_via_root_vfs() {
+    fn process_fair_mode_gives_processes_equal_cpu_regardless_of_thread_count() {
         let mut kernel = boot_kernel();
-        let parent = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let path = b"/usr/bin/m1-terminal\0";
-        let child = kernel
-            .handle_syscall(
-                SyscallNumber::Spawn.raw(),
-                SyscallContext::new(
-                    parent,
-                    None,
-                    [path.as_ptr() as u64, (path.len() - 1) as u64, 0, 0, 0, 0],
-                ),
-            )
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let loner = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        assert_eq!(
-            kernel.process_table[kernel.locate_process(ProcessId::new(child)).unwrap()]
-                .unwrap()
-                .parent,
-            Some(parent)
+        let crowd = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
+            .unwrap();
+        for _ in 0..3 {
+            kernel
+                .spawn_thread(crowd, 0x1000, ProcessPriority::Normal)
+                .unwrap();
+        }
+        let loner_thread = Kernel::<16, 4>::mtss_thread_id(first_thread(&kernel, loner));
+        let crowd_threads: Vec<_> = kernel
+            .thread_table
+            .iter()
+            .flatten()
+            .filter(|tcb| tcb.process == crowd)
+            .map(|tcb| Kernel::<16, 4>::mtss_thread_id(tcb.id))
+            .collect();
+        assert_eq!(crowd_threads.len(), 4);
+
+        kernel.set_scheduling_mode(SchedulingMode::ProcessFair);
+        kernel.kernel_schedule_next().unwrap();
+        let mut loner_ticks = 0u32;
+        let mut crowd_ticks = 0u32;
+        for _ in 0..1200 {
+            match kernel.mtss_scheduler.current() {
+                Some(current) if current == loner_thread => loner_ticks += 1,
+                Some(current) if crowd_threads.contains(&current) => crowd_ticks += 1,
+                _ => {}
+            }
+            kernel.kernel_on_timer_tick();
+        }
+
+        assert!(loner_ticks > 0);
+        assert!(
+            crowd_ticks * 10 >= loner_ticks * 9 && crowd_ticks * 10 <= loner_ticks * 11,
+            "crowd {crowd_ticks} loner {loner_ticks}"
         );
     }
 
+    #[cfg(feature = "latency-metrics")]
     #[test]
-    fn memory_syscalls_are_process_owned() {
+    fn latency_report_buckets_ipc_delivery_delays() {
         let mut kernel = boot_kernel();
-        let owner = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let other = kernel
-            .spawn_child_process(owner, 0, ProcessPriority::Normal, Credentials::user())
+        kernel.kernel_mtss_init().unwrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let peer = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::system())
             .unwrap();
-        let ptr = libc::malloc(&mut kernel, owner, None, 64).unwrap();
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"lat");
 
-        assert!(matches!(
-            libc::free(&mut kernel, other, None, ptr),
-            Err(KernelError::InvalidArgument)
-        ));
-        assert!(libc::free(&mut kernel, owner, None, ptr).is_ok());
+        for delay in [0, 1, 2, 3, 5, 9] {
+            kernel.send_message(init, peer, payload).unwrap();
+            for _ in 0..delay {
+                kernel.kernel_on_timer_tick();
+            }
+            kernel.receive_message(peer).unwrap();
+        }
+
+        let report = kernel.latency_report();
+        let counts: [u64; 6] = core::array::from_fn(|index| report.ipc_delivery.bucket(index));
+        assert_eq!(counts, [1, 1, 2, 1, 1, 0]);
+        assert_eq!(report.ipc_delivery.percentile(50), Some(3));
+        assert_eq!(report.wake_to_run.count(), 0);
     }
 
+    #[cfg(feature = "latency-metrics")]
     #[test]
-    fn mmap_rejects_writable_executable_mapping() {
+    fn latency_report_measures_wake_to_first_dispatch() {
         let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
         let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let protection = MemoryProtection::from_bits(PROT_READ | PROT_WRITE | PROT_EXECUTE);
+        set_address_space_root(&mut kernel, pid, 0x1000);
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"wake");
+
+        for idle in [0, 2] {
+            assert_eq!(kernel.receive_or_block(pid).unwrap(), None);
+            kernel.send_message(pid, pid, payload).unwrap();
+            for _ in 0..idle {
+                kernel.kernel_on_timer_tick();
+            }
+            kernel.tick();
+            kernel.receive_message(pid).unwrap();
+        }
+
+        // Each tick advances the clock before dispatching, so the waits were
+        // one and three ticks.
+        let wake = kernel.latency_report().wake_to_run;
+        assert_eq!(wake.count(), 2);
+        assert_eq!(wake.bucket(1), 1);
+        assert_eq!(wake.bucket(2), 1);
+    }
+
+    #[test]
+    fn core_latency_histogram_buckets_injected_dispatch_waits() {
+        let mut kernel = boot_kernel();
+        kernel.kernel_mtss_init().unwrap();
+        let pid = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        set_address_space_root(&mut kernel, pid, 0x1000);
+
+        // The thread became runnable at tick 0; three idle ticks plus the
+        // dispatching tick make its first wait four ticks.
+        for _ in 0..3 {
+            kernel.kernel_on_timer_tick();
+        }
+        kernel.tick();
+        // Requeued at the end of its slice, it is redispatched one tick later.
+        kernel.tick();
+        kernel.tick();
+
+        let histogram = kernel.core_latency_histogram(0).unwrap();
+        assert_eq!(histogram.count(), 3);
+        assert_eq!(histogram.bucket(3), 1);
+        assert_eq!(histogram.bucket(1), 2);
+        assert!(kernel.core_latency_histogram(cpu::MAX_CORES).is_none());
+
+        kernel.kernel_mtss_init().unwrap();
+        assert_eq!(kernel.core_latency_histogram(0).unwrap().count(), 0);
+    }
+
+    fn spawn_tenant(
+        kernel: &mut Kernel<16, 4>,
+        supervisor: ProcessId,
+        namespace: u16,
+    ) -> ProcessId {
+        let tenant = Credentials::new(
+            SecurityLabel::internal(),
+            CapabilitySet::new(crate::subkernel::CAP_IPC | crate::subkernel::CAP_SPAWN),
+            IsolationLevel::None,
+        );
+        kernel
+            .spawn_child_process_in_namespace(
+                supervisor,
+                0,
+                ProcessPriority::Normal,
+                tenant,
+                IpcNamespace::new(namespace),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn cross_namespace_processes_look_unknown_to_each_other() {
+        let mut kernel = boot_kernel();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let alpha = spawn_tenant(&mut kernel, init, 1);
+        let beta = spawn_tenant(&mut kernel, init, 2);
+        let alpha_worker = kernel
+            .spawn_child_process(alpha, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"hi");
 
         assert!(matches!(
-            libc::mmap(&mut kernel, pid, None, 4096, protection),
-            Err(KernelError::SecurityViolation(_))
+            kernel.send_message(alpha, beta, payload),
+            Err(KernelError::UnknownProcess)
+        ));
+        assert!(matches!(
+            kernel.ipc_namespace(beta, alpha_worker),
+            Err(KernelError::UnknownProcess)
+        ));
+        // Children inherit their parent's namespace.
+        assert_eq!(
+            kernel.ipc_namespace(alpha, alpha_worker).unwrap(),
+            IpcNamespace::new(1)
+        );
+        kernel.send_message(alpha, alpha_worker, payload).unwrap();
+        assert_eq!(kernel.receive_message(alpha_worker).unwrap().sender, alpha);
+
+        // Only CAP_KERNEL may place a child in another namespace.
+        assert!(matches!(
+            kernel.spawn_child_process_in_namespace(
+                alpha,
+                0,
+                ProcessPriority::Normal,
+                Credentials::user(),
+                IpcNamespace::new(2),
+            ),
+            Err(KernelError::SecurityViolation(
+                IsolationError::CapabilityMissing
+            ))
         ));
     }
 
     #[test]
-    fn mmap_rejects_executable_mapping_for_unprivileged_process() {
+    fn global_supervisor_bridges_tenant_namespaces() {
         let mut kernel = boot_kernel();
         let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
-        let user = kernel
-            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
-            .unwrap();
-        let protection = MemoryProtection::from_bits(PROT_READ | PROT_EXECUTE);
+        let alpha = spawn_tenant(&mut kernel, init, 1);
+        let beta = spawn_tenant(&mut kernel, init, 2);
+        assert_eq!(
+            kernel.ipc_namespace(init, beta).unwrap(),
+            IpcNamespace::new(2)
+        );
+        assert_eq!(
+            kernel.ipc_namespace(alpha, init).unwrap(),
+            IpcNamespace::GLOBAL
+        );
 
+        let payload = MessagePayload::from_slice(SecurityClass::Public, b"relay");
+        kernel.send_message(alpha, init, payload).unwrap();
+        let request = kernel.receive_message(init).unwrap();
+        kernel.forward_message(init, request, beta).unwrap();
+        let delivered = kernel.receive_message(beta).unwrap();
+        assert_eq!(delivered.origin(), alpha);
+        assert_eq!(delivered.payload, payload);
+
+        // A tenant cannot forward across namespaces itself.
+        kernel.send_message(init, alpha, payload).unwrap();
+        let message = kernel.receive_message(alpha).unwrap();
         assert!(matches!(
-            libc::mmap(&mut kernel, user, None, 4096, protection),
-            Err(KernelError::SecurityViolation(_))
+            kernel.forward_message(alpha, message, beta),
+            Err(KernelError::UnknownProcess)
         ));
     }
 }
@@ -7335,7 +13135,7 @@ fn push_user_u64(
 
 fn map_mtss_error(error: MtssError) -> KernelError {
     match error {
-        MtssError::RunQueueFull => KernelError::SchedulerFull,
+        MtssError::RunQueueFull | MtssError::AdmissionRejected => KernelError::SchedulerFull,
         MtssError::EmptyRunQueue => KernelError::UnknownThread,
         MtssError::InvalidTask => KernelError::UnknownProcess,
         MtssError::InvalidThread => KernelError::UnknownThread,
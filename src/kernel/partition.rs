@@ -1,6 +1,16 @@
-//! MBR/GPT partition parsing above the generic block layer.
+//! MBR/GPT partition parsing above the generic block layer, plus Mirage's own
+//! partition table for sharing one device between kernel subsystems.
+//!
+//! The Mirage table lives in block 0 and holds up to
+//! [`MIRAGE_TABLE_ENTRIES`] non-overlapping ranges. [`PartitionManager`] reads
+//! and rewrites it; [`PartitionHandle`] exposes one range as a block device
+//! whose I/O cannot leave the partition.
 
-use crate::kernel::block::{BlockDevice, BlockError};
+use crate::kernel::block::{
+    validate_transfer, BlockDevice, BlockDeviceInfo, BlockDeviceKind, BlockError,
+};
+use crate::kernel::process::ProcessId;
+use crate::subkernel::SecurityKernel;
 
 pub const MAX_PARTITIONS: usize = 16;
 
@@ -8,6 +18,7 @@ pub const MAX_PARTITIONS: usize = 16;
 pub enum PartitionTableKind {
     Mbr,
     Gpt,
+    Mirage,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -18,6 +29,8 @@ pub struct PartitionInfo {
     pub type_code: u8,
     pub bootable: bool,
     pub name: [u8; 36],
+    /// `PARTITION_FLAG_*` bits; always zero for MBR and GPT entries.
+    pub flags: u32,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -102,6 +115,7 @@ pub fn parse_mbr_sector(
             type_code,
             bootable: sector[off] == 0x80,
             name: [0; 36],
+            flags: 0,
         });
     }
     Ok(table)
@@ -183,11 +197,310 @@ pub fn parse_gpt_header_and_entries(
             type_code: 0xee,
             bootable: false,
             name,
+            flags: 0,
         });
     }
     Ok(table)
 }
 
+pub const MIRAGE_TABLE_MAGIC: [u8; 8] = *b"MIRAGEPT";
+pub const MIRAGE_TABLE_VERSION: u16 = 1;
+pub const MIRAGE_TABLE_ENTRIES: usize = 8;
+pub const PARTITION_FLAG_BOOTABLE: u32 = 1 << 0;
+pub const PARTITION_FLAG_READONLY: u32 = 1 << 1;
+
+const MIRAGE_HEADER_BYTES: usize = 16;
+const MIRAGE_ENTRY_BYTES: usize = 32;
+const MIRAGE_TABLE_BYTES: usize = MIRAGE_HEADER_BYTES + MIRAGE_TABLE_ENTRIES * MIRAGE_ENTRY_BYTES;
+const MAX_TABLE_BLOCK_SIZE: usize = 4096;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PartitionError {
+    Block(BlockError),
+    /// The entry shares blocks with another partition.
+    Overlap,
+    TableFull,
+    NotFound,
+    /// Rewriting the table requires `CAP_KERNEL`.
+    PermissionDenied,
+}
+
+impl From<BlockError> for PartitionError {
+    fn from(error: BlockError) -> Self {
+        PartitionError::Block(error)
+    }
+}
+
+/// A partition to add to a Mirage table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PartitionSpec {
+    pub first_lba: u64,
+    pub block_count: u64,
+    pub label: [u8; 8],
+    pub flags: u32,
+}
+
+/// Reads and rewrites the Mirage partition table in block 0.
+pub struct PartitionManager;
+
+impl PartitionManager {
+    pub fn scan(device: &dyn BlockDevice) -> Result<PartitionTable, PartitionError> {
+        let info = device.info();
+        let mut block = [0u8; MAX_TABLE_BLOCK_SIZE];
+        let block = read_table_block(device, &mut block)?;
+        let table = decode_mirage_table(block)?;
+        validate_entries(&table, info.block_count)?;
+        Ok(table)
+    }
+
+    /// Write an empty table over block 0, discarding whatever was there.
+    pub fn initialize<const PROCS: usize>(
+        device: &dyn BlockDevice,
+        caller: ProcessId,
+        security: &SecurityKernel<PROCS>,
+    ) -> Result<(), PartitionError> {
+        authorize(caller, security)?;
+        let table = PartitionTable::empty(PartitionTableKind::Mirage, false);
+        write_mirage_table(device, &table)
+    }
+
+    /// Add `spec` in the first free entry and rewrite the table.
+    pub fn create<const PROCS: usize>(
+        device: &dyn BlockDevice,
+        caller: ProcessId,
+        security: &SecurityKernel<PROCS>,
+        spec: PartitionSpec,
+    ) -> Result<PartitionInfo, PartitionError> {
+        authorize(caller, security)?;
+        let mut table = Self::scan(device)?;
+        let slot = (0..MIRAGE_TABLE_ENTRIES)
+            .find(|slot| table.partitions[*slot].is_none())
+            .ok_or(PartitionError::TableFull)?;
+        let mut name = [0u8; 36];
+        name[..8].copy_from_slice(&spec.label);
+        let partition = PartitionInfo {
+            index: slot as u8 + 1,
+            first_lba: spec.first_lba,
+            block_count: spec.block_count,
+            type_code: 0,
+            bootable: spec.flags & PARTITION_FLAG_BOOTABLE != 0,
+            name,
+            flags: spec.flags,
+        };
+        table.partitions[slot] = Some(partition);
+        table.count += 1;
+        validate_entries(&table, device.info().block_count)?;
+        write_mirage_table(device, &table)?;
+        Ok(partition)
+    }
+
+    /// Remove the partition with table `index` and rewrite the table. The
+    /// partition's blocks are left as they are.
+    pub fn delete<const PROCS: usize>(
+        device: &dyn BlockDevice,
+        caller: ProcessId,
+        security: &SecurityKernel<PROCS>,
+        index: u8,
+    ) -> Result<(), PartitionError> {
+        authorize(caller, security)?;
+        let mut table = Self::scan(device)?;
+        let slot = table
+            .partitions
+            .iter()
+            .position(|entry| entry.is_some_and(|partition| partition.index == index))
+            .ok_or(PartitionError::NotFound)?;
+        table.partitions[slot] = None;
+        table.count -= 1;
+        write_mirage_table(device, &table)
+    }
+
+    /// Open the partition with table `index` as a block device named `name`.
+    pub fn open<'a>(
+        device: &'a dyn BlockDevice,
+        index: u8,
+        name: &'static str,
+    ) -> Result<PartitionHandle<'a>, PartitionError> {
+        let partition = Self::scan(device)?
+            .partitions
+            .iter()
+            .flatten()
+            .find(|partition| partition.index == index)
+            .copied()
+            .ok_or(PartitionError::NotFound)?;
+        Ok(PartitionHandle {
+            device,
+            name,
+            partition,
+        })
+    }
+}
+
+/// One partition of a device, addressed from LBA 0. Transfers are bounds
+/// checked against the partition before being translated to device LBAs.
+pub struct PartitionHandle<'a> {
+    device: &'a dyn BlockDevice,
+    name: &'static str,
+    partition: PartitionInfo,
+}
+
+impl PartitionHandle<'_> {
+    pub fn partition(&self) -> PartitionInfo {
+        self.partition
+    }
+}
+
+impl BlockDevice for PartitionHandle<'_> {
+    fn info(&self) -> BlockDeviceInfo {
+        let parent = self.device.info();
+        BlockDeviceInfo {
+            id: 0,
+            name: self.name,
+            kind: BlockDeviceKind::Partition,
+            block_size: parent.block_size,
+            block_count: self.partition.block_count,
+            readonly: parent.readonly || self.partition.flags & PARTITION_FLAG_READONLY != 0,
+        }
+    }
+
+    fn read_blocks(&self, lba: u64, count: u32, buffer: &mut [u8]) -> Result<(), BlockError> {
+        validate_transfer(self.info(), lba, count, buffer.len())?;
+        self.device
+            .read_blocks(self.partition.first_lba + lba, count, buffer)
+    }
+
+    fn write_blocks(&self, lba: u64, count: u32, buffer: &[u8]) -> Result<(), BlockError> {
+        let info = self.info();
+        validate_transfer(info, lba, count, buffer.len())?;
+        if info.readonly {
+            return Err(BlockError::ReadOnly);
+        }
+        self.device
+            .write_blocks(self.partition.first_lba + lba, count, buffer)
+    }
+
+    fn flush(&self) -> Result<(), BlockError> {
+        self.device.flush()
+    }
+}
+
+fn authorize<const PROCS: usize>(
+    caller: ProcessId,
+    security: &SecurityKernel<PROCS>,
+) -> Result<(), PartitionError> {
+    security
+        .authorize_kernel_control(caller)
+        .map_err(|_| PartitionError::PermissionDenied)
+}
+
+fn read_table_block<'b>(
+    device: &dyn BlockDevice,
+    block: &'b mut [u8; MAX_TABLE_BLOCK_SIZE],
+) -> Result<&'b mut [u8], BlockError> {
+    let block_size = device.info().block_size as usize;
+    if !(MIRAGE_TABLE_BYTES..=MAX_TABLE_BLOCK_SIZE).contains(&block_size) {
+        return Err(BlockError::Unsupported);
+    }
+    let block = &mut block[..block_size];
+    device.read_blocks(0, 1, block)?;
+    Ok(block)
+}
+
+fn decode_mirage_table(block: &[u8]) -> Result<PartitionTable, BlockError> {
+    if block[0..8] != MIRAGE_TABLE_MAGIC {
+        return Err(BlockError::InvalidSignature);
+    }
+    if u16::from_le_bytes([block[8], block[9]]) != MIRAGE_TABLE_VERSION {
+        return Err(BlockError::Unsupported);
+    }
+    if le_u32(block, 12) != mirage_table_crc(block) {
+        return Err(BlockError::Crc);
+    }
+    let mut table = PartitionTable::empty(PartitionTableKind::Mirage, false);
+    for slot in 0..MIRAGE_TABLE_ENTRIES {
+        let off = MIRAGE_HEADER_BYTES + slot * MIRAGE_ENTRY_BYTES;
+        let block_count = le_u64(block, off + 8);
+        if block_count == 0 {
+            continue;
+        }
+        let flags = le_u32(block, off + 24);
+        let mut name = [0u8; 36];
+        name[..8].copy_from_slice(&block[off + 16..off + 24]);
+        table.partitions[slot] = Some(PartitionInfo {
+            index: slot as u8 + 1,
+            first_lba: le_u64(block, off),
+            block_count,
+            type_code: 0,
+            bootable: flags & PARTITION_FLAG_BOOTABLE != 0,
+            name,
+            flags,
+        });
+        table.count += 1;
+    }
+    Ok(table)
+}
+
+fn write_mirage_table(
+    device: &dyn BlockDevice,
+    table: &PartitionTable,
+) -> Result<(), PartitionError> {
+    let block_size = device.info().block_size as usize;
+    if !(MIRAGE_TABLE_BYTES..=MAX_TABLE_BLOCK_SIZE).contains(&block_size) {
+        return Err(BlockError::Unsupported.into());
+    }
+    let mut block = [0u8; MAX_TABLE_BLOCK_SIZE];
+    let block = &mut block[..block_size];
+    block[0..8].copy_from_slice(&MIRAGE_TABLE_MAGIC);
+    block[8..10].copy_from_slice(&MIRAGE_TABLE_VERSION.to_le_bytes());
+    block[10..12].copy_from_slice(&(MIRAGE_TABLE_ENTRIES as u16).to_le_bytes());
+    for (slot, partition) in table.partitions[..MIRAGE_TABLE_ENTRIES].iter().enumerate() {
+        let Some(partition) = partition else {
+            continue;
+        };
+        let off = MIRAGE_HEADER_BYTES + slot * MIRAGE_ENTRY_BYTES;
+        block[off..off + 8].copy_from_slice(&partition.first_lba.to_le_bytes());
+        block[off + 8..off + 16].copy_from_slice(&partition.block_count.to_le_bytes());
+        block[off + 16..off + 24].copy_from_slice(&partition.name[..8]);
+        block[off + 24..off + 28].copy_from_slice(&partition.flags.to_le_bytes());
+    }
+    let crc = mirage_table_crc(block);
+    block[12..16].copy_from_slice(&crc.to_le_bytes());
+    device.write_blocks(0, 1, block)?;
+    device.flush()?;
+    Ok(())
+}
+
+/// CRC of the table with its own CRC field zeroed.
+fn mirage_table_crc(block: &[u8]) -> u32 {
+    let mut bytes = [0u8; MIRAGE_TABLE_BYTES];
+    bytes.copy_from_slice(&block[..MIRAGE_TABLE_BYTES]);
+    bytes[12..16].fill(0);
+    crc32(&bytes)
+}
+
+/// Entries must sit past block 0, inside the device, and apart from each
+/// other.
+fn validate_entries(table: &PartitionTable, device_blocks: u64) -> Result<(), PartitionError> {
+    let entries = &table.partitions[..MIRAGE_TABLE_ENTRIES];
+    for (slot, partition) in entries.iter().enumerate() {
+        let Some(partition) = partition else {
+            continue;
+        };
+        let end = partition
+            .first_lba
+            .checked_add(partition.block_count)
+            .ok_or(BlockError::OutOfBounds)?;
+        if partition.first_lba == 0 || partition.block_count == 0 || end > device_blocks {
+            return Err(BlockError::OutOfBounds.into());
+        }
+        for other in entries[slot + 1..].iter().flatten() {
+            if partition.first_lba < other.first_lba + other.block_count && other.first_lba < end {
+                return Err(PartitionError::Overlap);
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn crc32(bytes: &[u8]) -> u32 {
     let mut crc = 0xffff_ffffu32;
     for byte in bytes {
@@ -234,7 +547,8 @@ fn le_u64(bytes: &[u8], off: usize) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::kernel::block::{BlockDeviceInfo, BlockDeviceKind};
+    use crate::kernel::sync::SpinLock;
+    use crate::subkernel::Credentials;
 
     struct Disk {
         data: [u8; 34 * 512],
@@ -284,6 +598,184 @@ mod tests {
         assert_eq!(table.partitions[0].unwrap().first_lba, 1);
     }
 
+    const MEM_BLOCKS: usize = 32;
+
+    /// Writable in-memory disk whose contents can be exported and loaded into
+    /// a fresh instance, as if the machine had rebooted.
+    struct MemDisk {
+        data: SpinLock<[u8; MEM_BLOCKS * 512]>,
+    }
+
+    impl MemDisk {
+        fn new() -> Self {
+            Self::from_image([0; MEM_BLOCKS * 512])
+        }
+
+        fn from_image(image: [u8; MEM_BLOCKS * 512]) -> Self {
+            Self {
+                data: SpinLock::new(image),
+            }
+        }
+
+        fn export(&self) -> [u8; MEM_BLOCKS * 512] {
+            *self.data.lock()
+        }
+    }
+
+    impl BlockDevice for MemDisk {
+        fn info(&self) -> BlockDeviceInfo {
+            BlockDeviceInfo {
+                id: 2,
+                name: "mem0",
+                kind: BlockDeviceKind::RamDisk,
+                block_size: 512,
+                block_count: MEM_BLOCKS as u64,
+                readonly: false,
+            }
+        }
+        fn read_blocks(&self, lba: u64, count: u32, buffer: &mut [u8]) -> Result<(), BlockError> {
+            let len = validate_transfer(self.info(), lba, count, buffer.len())?;
+            let start = lba as usize * 512;
+            buffer.copy_from_slice(&self.data.lock()[start..start + len]);
+            Ok(())
+        }
+        fn write_blocks(&self, lba: u64, count: u32, buffer: &[u8]) -> Result<(), BlockError> {
+            let len = validate_transfer(self.info(), lba, count, buffer.len())?;
+            let start = lba as usize * 512;
+            self.data.lock()[start..start + len].copy_from_slice(buffer);
+            Ok(())
+        }
+        fn flush(&self) -> Result<(), BlockError> {
+            Ok(())
+        }
+    }
+
+    fn security() -> SecurityKernel<4> {
+        let mut security = SecurityKernel::new();
+        security
+            .register_task(ProcessId::new(1), Credentials::system())
+            .unwrap();
+        security
+            .register_task(ProcessId::new(2), Credentials::user())
+            .unwrap();
+        security
+    }
+
+    fn spec(first_lba: u64, block_count: u64, label: &[u8; 8]) -> PartitionSpec {
+        PartitionSpec {
+            first_lba,
+            block_count,
+            label: *label,
+            flags: 0,
+        }
+    }
+
+    fn partitioned_disk(security: &SecurityKernel<4>) -> MemDisk {
+        let disk = MemDisk::new();
+        let kernel = ProcessId::new(1);
+        PartitionManager::initialize(&disk, kernel, security).unwrap();
+        PartitionManager::create(&disk, kernel, security, spec(1, 8, b"logs\0\0\0\0")).unwrap();
+        PartitionManager::create(&disk, kernel, security, spec(9, 16, b"cache\0\0\0")).unwrap();
+        disk
+    }
+
+    #[test]
+    fn mirage_partitions_are_isolated_from_each_other() {
+        let security = security();
+        let disk = partitioned_disk(&security);
+        let logs = PartitionManager::open(&disk, 1, "mem0p1").unwrap();
+        let cache = PartitionManager::open(&disk, 2, "mem0p2").unwrap();
+        assert_eq!(logs.info().block_count, 8);
+        assert_eq!(logs.info().kind, BlockDeviceKind::Partition);
+
+        logs.write_blocks(7, 1, &[0xaa; 512]).unwrap();
+        cache.write_blocks(0, 1, &[0xbb; 512]).unwrap();
+        assert_eq!(
+            logs.write_blocks(8, 1, &[0xcc; 512]),
+            Err(BlockError::OutOfBounds)
+        );
+        let mut two = [0u8; 1024];
+        assert_eq!(
+            logs.read_blocks(7, 2, &mut two),
+            Err(BlockError::OutOfBounds)
+        );
+
+        let mut block = [0u8; 512];
+        logs.read_blocks(7, 1, &mut block).unwrap();
+        assert_eq!(block, [0xaa; 512]);
+        cache.read_blocks(0, 1, &mut block).unwrap();
+        assert_eq!(block, [0xbb; 512]);
+        disk.read_blocks(9, 1, &mut block).unwrap();
+        assert_eq!(block, [0xbb; 512]);
+    }
+
+    #[test]
+    fn overlapping_partitions_are_rejected() {
+        let security = security();
+        let disk = partitioned_disk(&security);
+        let kernel = ProcessId::new(1);
+        assert_eq!(
+            PartitionManager::create(&disk, kernel, &security, spec(20, 8, b"overlap\0")),
+            Err(PartitionError::Overlap)
+        );
+        assert_eq!(
+            PartitionManager::create(&disk, kernel, &security, spec(0, 1, b"boot\0\0\0\0")),
+            Err(PartitionError::Block(BlockError::OutOfBounds))
+        );
+        assert_eq!(
+            PartitionManager::create(
+                &disk,
+                ProcessId::new(2),
+                &security,
+                spec(25, 2, b"user\0\0\0\0")
+            ),
+            Err(PartitionError::PermissionDenied)
+        );
+        assert_eq!(PartitionManager::scan(&disk).unwrap().count, 2);
+
+        // A hand-edited table with overlapping entries fails to scan.
+        let mut block = [0u8; 512];
+        disk.read_blocks(0, 1, &mut block).unwrap();
+        let second = MIRAGE_HEADER_BYTES + MIRAGE_ENTRY_BYTES;
+        block[second..second + 8].copy_from_slice(&4u64.to_le_bytes());
+        let crc = mirage_table_crc(&block);
+        block[12..16].copy_from_slice(&crc.to_le_bytes());
+        disk.write_blocks(0, 1, &block).unwrap();
+        assert_eq!(PartitionManager::scan(&disk), Err(PartitionError::Overlap));
+    }
+
+    #[test]
+    fn mirage_table_survives_reboot_and_delete() {
+        let security = security();
+        let image = {
+            let disk = partitioned_disk(&security);
+            let cache = PartitionManager::open(&disk, 2, "mem0p2").unwrap();
+            cache.write_blocks(15, 1, &[0x5a; 512]).unwrap();
+            disk.export()
+        };
+
+        let disk = MemDisk::from_image(image);
+        let table = PartitionManager::scan(&disk).unwrap();
+        assert_eq!(table.kind, PartitionTableKind::Mirage);
+        assert_eq!(table.count, 2);
+        let cache = table.partitions[1].unwrap();
+        assert_eq!((cache.first_lba, cache.block_count), (9, 16));
+        assert_eq!(&cache.name[..5], b"cache");
+        let mut block = [0u8; 512];
+        PartitionManager::open(&disk, 2, "mem0p2")
+            .unwrap()
+            .read_blocks(15, 1, &mut block)
+            .unwrap();
+        assert_eq!(block, [0x5a; 512]);
+
+        PartitionManager::delete(&disk, ProcessId::new(1), &security, 1).unwrap();
+        assert_eq!(PartitionManager::scan(&disk).unwrap().count, 1);
+        assert!(matches!(
+            PartitionManager::open(&disk, 1, "mem0p1"),
+            Err(PartitionError::NotFound)
+        ));
+    }
+
     #[test]
     fn gpt_crc_and_partition_entry_parsing() {
         assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
@@ -1,12 +1,14 @@
 //! Process control structures for the Mirage kernel.
 
+use crate::kernel::collections::FixedString;
 use crate::kernel::fs::{DescriptorFlags, FileDescriptionId, Path, Permissions, MAX_PATH_BYTES};
-use crate::subkernel::{Credentials, SecurityLabel};
+use crate::subkernel::{Credentials, IpcNamespace, SecurityLabel};
 
 pub const MAX_PENDING_SIGNALS: usize = 32;
 pub const MAX_SUPPLEMENTARY_GROUPS: usize = 16;
 pub const MAX_SIGNAL_NUMBER: usize = 64;
 pub const SIGKILL: u8 = 9;
+pub const SIGSEGV: u8 = 11;
 pub const SIGTERM: u8 = 15;
 pub const SIGCHLD: u8 = 17;
 
@@ -258,6 +260,9 @@ pub enum ProcessState {
     Ready,
     Running,
     Blocked,
+    /// Teardown has begun: its threads are reaped instead of dispatched and
+    /// the exit completes at the next tick.
+    Terminating,
     /// The process has exited but is still waitable by its parent.
     Zombie,
     Terminated,
@@ -299,6 +304,231 @@ impl ProcessPriority {
     }
 }
 
+impl mirage_mtss::PriorityBand for ProcessPriority {
+    fn band(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Scheduling discipline for all threads of a process.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchedClass {
+    /// Dispatched from its priority band ahead of other classes in it, and
+    /// never aged, demoted or overtaken by aged threads.
+    Realtime,
+    /// Aged, and boosted one priority band when woken.
+    #[default]
+    Interactive,
+    /// Queued in the lowest band, with longer time slices.
+    Batch,
+}
+
+/// The most recent `N` exited PIDs and the IPC namespace each lived in, so
+/// a send to one can be told apart from a send to a PID never issued.
+/// Older exits are forgotten first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExitedPids<const N: usize> {
+    entries: [Option<(ProcessId, IpcNamespace)>; N],
+    next: usize,
+}
+
+impl<const N: usize> ExitedPids<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; N],
+            next: 0,
+        }
+    }
+
+    pub fn record(&mut self, pid: ProcessId, namespace: IpcNamespace) {
+        if N == 0 {
+            return;
+        }
+        self.forget(pid);
+        self.entries[self.next] = Some((pid, namespace));
+        self.next = (self.next + 1) % N;
+    }
+
+    /// Namespace `pid` exited from, if it is still remembered.
+    pub fn namespace_of(&self, pid: ProcessId) -> Option<IpcNamespace> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|(exited, _)| *exited == pid)
+            .map(|&(_, namespace)| namespace)
+    }
+
+    /// Drop `pid`, for when it is issued again.
+    pub fn forget(&mut self, pid: ProcessId) {
+        for entry in self.entries.iter_mut() {
+            if entry.is_some_and(|(exited, _)| exited == pid) {
+                *entry = None;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const N: usize> Default for ExitedPids<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Copied scheduling view of one process, as captured by a
+/// [`ProcessSnapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProcessSummary {
+    pub pid: ProcessId,
+    pub parent: Option<ProcessId>,
+    pub state: ProcessState,
+    pub priority: ProcessPriority,
+    pub sched_class: SchedClass,
+    pub thread_count: u16,
+    pub cpu_time: u128,
+}
+
+impl ProcessSummary {
+    pub fn capture<const MAX_FD: usize>(pcb: &ProcessControlBlock<MAX_FD>) -> Self {
+        Self {
+            pid: pcb.pid,
+            parent: pcb.parent,
+            state: pcb.state,
+            priority: pcb.priority,
+            sched_class: pcb.sched_class,
+            thread_count: pcb.thread_count,
+            cpu_time: pcb.cpu_time,
+        }
+    }
+}
+
+/// Summaries of up to `N` processes taken in one pass over the process
+/// table. It owns its copies, so later changes to the kernel do not show
+/// through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProcessSnapshot<const N: usize> {
+    entries: [Option<ProcessSummary>; N],
+    len: usize,
+}
+
+impl<const N: usize> ProcessSnapshot<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; N],
+            len: 0,
+        }
+    }
+
+    /// Appends `summary`, returning `false` once the snapshot is full.
+    pub fn push(&mut self, summary: ProcessSummary) -> bool {
+        if self.len == N {
+            return false;
+        }
+        self.entries[self.len] = Some(summary);
+        self.len += 1;
+        true
+    }
+
+    pub fn get(&self, pid: ProcessId) -> Option<&ProcessSummary> {
+        self.iter().find(|summary| summary.pid == pid)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ProcessSummary> + '_ {
+        self.entries[..self.len].iter().flatten()
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> Default for ProcessSnapshot<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Soft CPU limit. A process that runs for more than `ticks_per_window`
+/// ticks within one `window` is throttled to [`ProcessPriority::Low`] until
+/// the window rolls over; it is never killed for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CpuQuota {
+    pub ticks_per_window: u64,
+    pub window: u64,
+    window_start: u64,
+    used: u64,
+    throttled: bool,
+}
+
+impl CpuQuota {
+    /// A quota whose first window opens at tick `now`. `window` must be
+    /// nonzero.
+    pub const fn new(ticks_per_window: u64, window: u64, now: u64) -> Self {
+        Self {
+            ticks_per_window,
+            window,
+            window_start: now,
+            used: 0,
+            throttled: false,
+        }
+    }
+
+    /// Ticks charged in the current window.
+    pub const fn used(&self) -> u64 {
+        self.used
+    }
+
+    pub const fn is_throttled(&self) -> bool {
+        self.throttled
+    }
+
+    /// Start a fresh window if `now` is past the current one. Returns true
+    /// when that lifts a throttle.
+    pub fn roll_over(&mut self, now: u64) -> bool {
+        let elapsed = now.saturating_sub(self.window_start);
+        if elapsed < self.window {
+            return false;
+        }
+        self.window_start += elapsed - elapsed % self.window;
+        self.used = 0;
+        core::mem::replace(&mut self.throttled, false)
+    }
+
+    /// Charge one tick of CPU at `now`. Returns true when this tick
+    /// exceeds the quota.
+    pub fn charge(&mut self, now: u64) -> bool {
+        self.roll_over(now);
+        self.used = self.used.saturating_add(1);
+        if self.throttled || self.used <= self.ticks_per_window {
+            return false;
+        }
+        self.throttled = true;
+        true
+    }
+}
+
+/// Kernel-wide dispatch policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchedulingMode {
+    /// Order threads by [`SchedClass`] and priority.
+    #[default]
+    Classed,
+    /// Share CPU time in proportion to priority: Critical, High, Normal and
+    /// Low weigh 8, 6, 4 and 2.
+    Stride,
+    /// Serve runnable processes round-robin, running one of the process's
+    /// threads per turn, so each process gets an equal share regardless of
+    /// how many threads it runs.
+    ProcessFair,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ProcessFileTableError {
     Full,
@@ -308,38 +538,33 @@ pub enum ProcessFileTableError {
 /// Owned absolute path snapshot used for per-process `cwd` and `root`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ProcessPath {
-    bytes: [u8; MAX_PATH_BYTES],
-    len: usize,
+    path: FixedString<MAX_PATH_BYTES>,
 }
 
 impl ProcessPath {
     pub const fn root() -> Self {
-        let mut bytes = [0u8; MAX_PATH_BYTES];
-        bytes[0] = b'/';
-        Self { bytes, len: 1 }
+        Self {
+            path: FixedString::from_str_truncated("/"),
+        }
     }
 
-    pub const fn as_bytes(self) -> [u8; MAX_PATH_BYTES] {
-        self.bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.path.as_bytes()
     }
 
     pub const fn len(self) -> usize {
-        self.len
+        self.path.len()
     }
 
     pub fn from_path(path: Path<'_>) -> Self {
-        let mut bytes = [0u8; MAX_PATH_BYTES];
-        let raw = path.as_str().as_bytes();
-        bytes[..raw.len()].copy_from_slice(raw);
+        // `Path` already enforces MAX_PATH_BYTES, so nothing is truncated.
         Self {
-            bytes,
-            len: raw.len(),
+            path: FixedString::from_str_truncated(path.as_str()),
         }
     }
 
     pub fn as_str(&self) -> &str {
-        // ProcessPath is only built from validated UTF-8 kernel paths.
-        unsafe { core::str::from_utf8_unchecked(&self.bytes[..self.len]) }
+        self.path.as_str()
     }
 }
 
@@ -631,6 +856,15 @@ impl PendingSignalQueue {
         None
     }
 
+    /// Queued signals that `take_unmasked(mask)` would eventually return.
+    pub fn count_unmasked(&self, mask: SignalMask) -> usize {
+        self.signals
+            .iter()
+            .flatten()
+            .filter(|signal| **signal == SIGKILL || !mask.contains(**signal))
+            .count()
+    }
+
     pub fn clear(&mut self) {
         let mut idx = 0usize;
         while idx < MAX_PENDING_SIGNALS {
@@ -659,6 +893,16 @@ pub struct ProcessControlBlock<const MAX_FD: usize> {
     pub signal_actions: [SignalAction; MAX_SIGNAL_NUMBER + 1],
     pub pending_signals: PendingSignalQueue,
     pub child_wait: Option<ChildWaitSelector>,
+    /// Uptime tick at which the kernel terminates the process, if a
+    /// watchdog timeout is armed.
+    pub execution_deadline: Option<u64>,
+    /// Thread-table slot the next wake-all starts scanning from, rotated so
+    /// no single thread is always queued first.
+    pub wake_cursor: usize,
+    pub sched_class: SchedClass,
+    /// Set on the reserved process that hosts kernel task threads.
+    pub kernel_tasks: bool,
+    pub cpu_quota: Option<CpuQuota>,
 }
 
 impl<const MAX_FD: usize> ProcessControlBlock<MAX_FD> {
@@ -689,6 +933,11 @@ impl<const MAX_FD: usize> ProcessControlBlock<MAX_FD> {
             signal_actions: [SignalAction::DEFAULT; MAX_SIGNAL_NUMBER + 1],
             pending_signals: PendingSignalQueue::new(),
             child_wait: None,
+            execution_deadline: None,
+            wake_cursor: 0,
+            sched_class: SchedClass::Interactive,
+            kernel_tasks: false,
+            cpu_quota: None,
         }
     }
 
@@ -705,6 +954,11 @@ impl<const MAX_FD: usize> ProcessControlBlock<MAX_FD> {
         self.thread_count = self.thread_count.saturating_add(1);
     }
 
+    pub fn mark_terminating(&mut self, status: ExitStatus) {
+        self.state = ProcessState::Terminating;
+        self.exit_status = Some(status);
+    }
+
     pub fn mark_zombie(&mut self, status: ExitStatus) {
         self.state = ProcessState::Zombie;
         self.exit_status = Some(status);
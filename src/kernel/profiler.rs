@@ -0,0 +1,203 @@
+//! Tick-driven sampling profiler.
+//!
+//! While enabled, the scheduler records which thread a core dispatched and the
+//! instruction pointer it resumed at on every `interval`-th tick. Samples land
+//! in a fixed ring; once it fills, the oldest samples are overwritten and
+//! counted as dropped until a consumer drains them.
+
+use crate::kernel::cpu::CpuCoreState;
+use crate::kernel::process::{ProcessControlBlock, ProcessId};
+use crate::kernel::sync::SpinLock;
+use crate::kernel::thread::{ThreadControlBlock, ThreadId};
+
+pub const PROFILER_CAPACITY: usize = 512;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProfileSample {
+    pub tick: u64,
+    pub pid: ProcessId,
+    pub thread: ThreadId,
+    pub rip: u64,
+}
+
+impl ProfileSample {
+    pub const fn empty() -> Self {
+        Self {
+            tick: 0,
+            pid: ProcessId::new(0),
+            thread: ThreadId::new(0),
+            rip: 0,
+        }
+    }
+}
+
+struct ProfilerState<const N: usize> {
+    samples: [ProfileSample; N],
+    next: usize,
+    len: usize,
+    /// Sampling period in ticks; zero while the profiler is disabled.
+    interval: u64,
+    dropped: u64,
+}
+
+impl<const N: usize> ProfilerState<N> {
+    fn push(&mut self, sample: ProfileSample) {
+        if self.len == N {
+            self.dropped = self.dropped.saturating_add(1);
+        } else {
+            self.len += 1;
+        }
+        self.samples[self.next] = sample;
+        self.next = (self.next + 1) % N;
+    }
+}
+
+pub struct Profiler<const N: usize> {
+    state: SpinLock<ProfilerState<N>>,
+}
+
+impl<const N: usize> Profiler<N> {
+    pub const fn new() -> Self {
+        Self {
+            state: SpinLock::new(ProfilerState {
+                samples: [ProfileSample::empty(); N],
+                next: 0,
+                len: 0,
+                interval: 0,
+                dropped: 0,
+            }),
+        }
+    }
+
+    /// Start sampling every `interval` ticks. An interval of zero disables
+    /// the profiler.
+    pub fn enable(&self, interval: u64) {
+        self.state.lock().interval = interval;
+    }
+
+    pub fn disable(&self) {
+        self.state.lock().interval = 0;
+    }
+
+    pub fn interval(&self) -> Option<u64> {
+        match self.state.lock().interval {
+            0 => None,
+            interval => Some(interval),
+        }
+    }
+
+    /// Record `tcb` running on `core` if `tick` falls on the sampling
+    /// interval. Returns whether a sample was taken.
+    pub fn sample_if_due<const MAX_FD: usize>(
+        &self,
+        tick: u64,
+        core: &CpuCoreState,
+        pcb: &ProcessControlBlock<MAX_FD>,
+        tcb: &ThreadControlBlock,
+    ) -> bool {
+        let mut state = self.state.lock();
+        if state.interval == 0 || !core.online || !tick.is_multiple_of(state.interval) {
+            return false;
+        }
+        state.push(ProfileSample {
+            tick,
+            pid: pcb.pid,
+            thread: tcb.id,
+            rip: tcb.context.rip,
+        });
+        true
+    }
+
+    /// Move buffered samples into `out`, oldest first. Samples that do not
+    /// fit stay buffered for the next call.
+    pub fn drain(&self, out: &mut [ProfileSample]) -> usize {
+        let mut state = self.state.lock();
+        let count = core::cmp::min(out.len(), state.len);
+        let start = (state.next + N - state.len) % N;
+        let mut idx = 0usize;
+        while idx < count {
+            out[idx] = state.samples[(start + idx) % N];
+            idx += 1;
+        }
+        state.len -= count;
+        count
+    }
+
+    /// Samples overwritten before they were drained.
+    pub fn dropped(&self) -> u64 {
+        self.state.lock().dropped
+    }
+}
+
+impl<const N: usize> Default for Profiler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub static PROFILER: Profiler<PROFILER_CAPACITY> = Profiler::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::process::ProcessPriority;
+
+    fn fixtures() -> (CpuCoreState, ProcessControlBlock<4>, ThreadControlBlock) {
+        let mut core = CpuCoreState::new();
+        core.online();
+        let pid = ProcessId::new(3);
+        let pcb = ProcessControlBlock::new(pid, 0x4000, ProcessPriority::Normal, None);
+        let tcb = ThreadControlBlock::new(
+            ThreadId::new(9),
+            pid,
+            0x4000,
+            ProcessPriority::Normal,
+            0x7fff_f000,
+        );
+        (core, pcb, tcb)
+    }
+
+    #[test]
+    fn samples_only_on_interval_ticks() {
+        let profiler: Profiler<8> = Profiler::new();
+        let (core, pcb, tcb) = fixtures();
+        assert!(!profiler.sample_if_due(3, &core, &pcb, &tcb));
+
+        profiler.enable(3);
+        let mut tick = 1;
+        while tick <= 10 {
+            profiler.sample_if_due(tick, &core, &pcb, &tcb);
+            tick += 1;
+        }
+
+        let mut out = [ProfileSample::empty(); 8];
+        assert_eq!(profiler.drain(&mut out), 3);
+        assert_eq!(out[0].tick, 3);
+        assert_eq!(out[1].tick, 6);
+        assert_eq!(out[2].tick, 9);
+        assert_eq!(out[0].pid, pcb.pid);
+        assert_eq!(out[0].thread, tcb.id);
+        assert_eq!(out[0].rip, 0x4000);
+        assert_eq!(profiler.drain(&mut out), 0);
+
+        profiler.disable();
+        assert!(!profiler.sample_if_due(12, &core, &pcb, &tcb));
+    }
+
+    #[test]
+    fn full_ring_overwrites_oldest_and_partial_drain_keeps_rest() {
+        let profiler: Profiler<2> = Profiler::new();
+        let (core, pcb, tcb) = fixtures();
+        profiler.enable(1);
+        profiler.sample_if_due(1, &core, &pcb, &tcb);
+        profiler.sample_if_due(2, &core, &pcb, &tcb);
+        profiler.sample_if_due(3, &core, &pcb, &tcb);
+        assert_eq!(profiler.dropped(), 1);
+
+        let mut out = [ProfileSample::empty(); 1];
+        assert_eq!(profiler.drain(&mut out), 1);
+        assert_eq!(out[0].tick, 2);
+        assert_eq!(profiler.drain(&mut out), 1);
+        assert_eq!(out[0].tick, 3);
+    }
+}
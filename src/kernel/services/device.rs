@@ -20,7 +20,13 @@ pub trait DeviceService {
     fn device_write(&self, caller: ProcessId, id: DeviceId, data: &[u8]) -> KernelResult<usize>;
 }
 
-impl<const MAX_PROC: usize, const MSG_DEPTH: usize> DeviceService for Kernel<MAX_PROC, MSG_DEPTH> {
+impl<
+        const MAX_PROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > DeviceService for Kernel<MAX_PROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
     fn enumerate_devices(&self, out: &mut [DeviceDescriptor]) -> usize {
         Kernel::enumerate_devices(self, out)
     }
@@ -116,8 +116,12 @@ pub trait FileSystemService {
     ) -> KernelResult<usize>;
 }
 
-impl<const MAX_PROC: usize, const MSG_DEPTH: usize> FileSystemService
-    for Kernel<MAX_PROC, MSG_DEPTH>
+impl<
+        const MAX_PROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > FileSystemService for Kernel<MAX_PROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
 {
     fn openat(
         &mut self,
@@ -367,8 +371,13 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> FileSystemService
     }
 }
 
-fn service_syscall<const MAX_PROC: usize, const MSG_DEPTH: usize>(
-    kernel: &mut Kernel<MAX_PROC, MSG_DEPTH>,
+fn service_syscall<
+    const MAX_PROC: usize,
+    const MSG_DEPTH: usize,
+    const MAX_CORES: usize,
+    const MAX_THREADS: usize,
+>(
+    kernel: &mut Kernel<MAX_PROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>,
     caller: ProcessId,
     thread: Option<ThreadId>,
     number: SyscallNumber,
@@ -78,7 +78,13 @@ pub trait ProcessService {
     ) -> KernelResult<()>;
 }
 
-impl<const MAX_PROC: usize, const MSG_DEPTH: usize> ProcessService for Kernel<MAX_PROC, MSG_DEPTH> {
+impl<
+        const MAX_PROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > ProcessService for Kernel<MAX_PROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
     fn spawn_initial_process(&mut self, creds: Credentials) -> KernelResult<ProcessId> {
         Kernel::spawn_initial_process(self, creds)
     }
@@ -192,8 +198,13 @@ impl<const MAX_PROC: usize, const MSG_DEPTH: usize> ProcessService for Kernel<MA
     }
 }
 
-fn service_syscall<const MAX_PROC: usize, const MSG_DEPTH: usize>(
-    kernel: &mut Kernel<MAX_PROC, MSG_DEPTH>,
+fn service_syscall<
+    const MAX_PROC: usize,
+    const MSG_DEPTH: usize,
+    const MAX_CORES: usize,
+    const MAX_THREADS: usize,
+>(
+    kernel: &mut Kernel<MAX_PROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>,
     caller: ProcessId,
     thread: Option<ThreadId>,
     number: SyscallNumber,
@@ -21,7 +21,13 @@ pub trait TimeService {
     fn monotonic_now(&self) -> MonotonicTimestamp;
 }
 
-impl<const MAX_PROC: usize, const MSG_DEPTH: usize> TimeService for Kernel<MAX_PROC, MSG_DEPTH> {
+impl<
+        const MAX_PROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > TimeService for Kernel<MAX_PROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
     fn tick(&mut self) {
         Kernel::tick(self);
     }
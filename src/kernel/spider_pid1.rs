@@ -9,7 +9,13 @@
 use crate::kernel::process::ProcessId;
 use crate::kernel::{Kernel, KernelError};
 
-impl<const NPROC: usize, const MSG_DEPTH: usize> Kernel<NPROC, MSG_DEPTH> {
+impl<
+        const NPROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > Kernel<NPROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
     /// Admit Spider-rs as the initial userspace task through the MTSS-owned PID 1 path.
     ///
     /// The existing `bootstrap_spider_rs_pid1_from_image` path is kept as the
@@ -0,0 +1,476 @@
+//! One-shot, human-readable kernel status for the serial console.
+//!
+//! [`Kernel::status_report`] gathers the figures and [`StatusReport::render`]
+//! formats them into a caller buffer through [`BoundedWriter`], a line at a
+//! time: a line that does not fit is dropped along with everything after it,
+//! so truncated output always ends on a line boundary. The console dump renders
+//! into the calling core's scratch arena rather than a stack buffer.
+
+use core::fmt::{self, Write};
+
+use crate::kernel::cpu;
+use crate::kernel::memory::{self, ScratchCtx};
+use crate::kernel::process::ProcessId;
+use crate::kernel::{Kernel, KernelError, KernelResult, ResourceSnapshot};
+use crate::subkernel::IsolationError;
+
+/// `Kernel::console_control` op that writes the status report to the default
+/// console.
+pub const CONSOLE_STATUS_DUMP: u32 = 1;
+/// Largest report `CONSOLE_STATUS_DUMP` writes.
+pub const STATUS_REPORT_BYTES: usize = 512;
+pub const STATUS_TOP_PROCESSES: usize = 3;
+
+/// `fmt::Write` sink over a fixed byte buffer that never allocates.
+pub struct BoundedWriter<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+    truncated: bool,
+}
+
+impl<'a> BoundedWriter<'a> {
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether some output was dropped for lack of space.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn written(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Write `args` and a newline, or nothing at all if the whole line does
+    /// not fit. Once a line has been dropped every later line is dropped too.
+    pub fn line(&mut self, args: fmt::Arguments<'_>) -> bool {
+        if self.truncated {
+            return false;
+        }
+        let start = self.len;
+        if self.write_fmt(args).is_err() || self.write_str("\n").is_err() {
+            self.len = start;
+            return false;
+        }
+        true
+    }
+}
+
+impl Write for BoundedWriter<'_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let bytes = text.as_bytes();
+        let end = self.len + bytes.len();
+        if end > self.buffer.len() {
+            self.truncated = true;
+            return Err(fmt::Error);
+        }
+        self.buffer[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CoreUsage {
+    pub online: bool,
+    pub busy_ticks: u64,
+    pub idle_ticks: u64,
+}
+
+impl CoreUsage {
+    pub const fn utilization_percent(&self) -> u64 {
+        let total = self.busy_ticks + self.idle_ticks;
+        if total == 0 {
+            return 0;
+        }
+        self.busy_ticks * 100 / total
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FaultSummary {
+    pub tick: u64,
+    pub pid: ProcessId,
+    pub reason: IsolationError,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatusReport<const MAX_CORES: usize = { cpu::MAX_CORES }> {
+    pub resources: ResourceSnapshot,
+    pub memory_free: usize,
+    pub cores: [CoreUsage; MAX_CORES],
+    /// Live processes with the most CPU time, busiest first.
+    pub top: [Option<(ProcessId, u128)>; STATUS_TOP_PROCESSES],
+    /// Process hosting kernel task threads, marked `[kernel]` in the list.
+    pub kernel_tasks: Option<ProcessId>,
+    pub messages_queued: usize,
+    pub messages_sent: u64,
+    /// Most recent isolation fault still held in the dump ring.
+    pub last_fault: Option<FaultSummary>,
+}
+
+impl<const MAX_CORES: usize> StatusReport<MAX_CORES> {
+    /// Format the report into `out`, returning the bytes written.
+    pub fn render(&self, out: &mut [u8]) -> usize {
+        let mut writer = BoundedWriter::new(out);
+        let resources = &self.resources;
+        writer.line(format_args!("uptime {} ticks", resources.uptime_ticks));
+        writer.line(format_args!(
+            "cores {}/{} online",
+            resources.online_cores,
+            self.cores.len()
+        ));
+        for (index, core) in self.cores.iter().enumerate() {
+            if core.online {
+                writer.line(format_args!(
+                    " cpu{} {}% busy",
+                    index,
+                    core.utilization_percent()
+                ));
+            }
+        }
+        writer.line(format_args!(
+            "procs {}/{}",
+            resources.processes_live, resources.processes_max
+        ));
+        for (pid, cpu_time) in self.top.iter().flatten() {
+            let marker = if self.kernel_tasks == Some(*pid) {
+                " [kernel]"
+            } else {
+                ""
+            };
+            writer.line(format_args!(
+                " pid {} cpu {}{}",
+                pid.raw(),
+                cpu_time,
+                marker
+            ));
+        }
+        writer.line(format_args!(
+            "sched {} queued, threads {}/{}",
+            resources.scheduler_depth, resources.threads_live, resources.threads_max
+        ));
+        writer.line(format_args!(
+            "mem used {} peak {} free {}",
+            resources.memory_used, resources.memory_peak, self.memory_free
+        ));
+        writer.line(format_args!(
+            "msgs {} queued, {} sent",
+            self.messages_queued, self.messages_sent
+        ));
+        match self.last_fault {
+            Some(fault) => writer.line(format_args!(
+                "fault pid {} {:?} at {}",
+                fault.pid.raw(),
+                fault.reason,
+                fault.tick
+            )),
+            None => writer.line(format_args!("fault none")),
+        };
+        writer.len()
+    }
+
+    /// Format the report into a [`STATUS_REPORT_BYTES`] buffer carved from
+    /// `scratch` and return the rendered bytes, or `None` when the arena is
+    /// exhausted for this tick.
+    pub fn render_scratch<'s>(&self, scratch: &'s mut ScratchCtx<'_>) -> Option<&'s [u8]> {
+        let buffer = scratch.alloc(STATUS_REPORT_BYTES, 1)?;
+        let len = self.render(buffer);
+        Some(&buffer[..len])
+    }
+}
+
+impl<
+        const NPROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > Kernel<NPROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
+{
+    pub fn status_report(&self) -> StatusReport<MAX_CORES> {
+        let mut cores = [CoreUsage::default(); MAX_CORES];
+        for (usage, state) in cores.iter_mut().zip(self.core_states.iter()) {
+            *usage = CoreUsage {
+                online: state.online,
+                busy_ticks: state.local_ticks,
+                idle_ticks: state.idle_ticks,
+            };
+        }
+
+        let mut top: [Option<(ProcessId, u128)>; STATUS_TOP_PROCESSES] =
+            [None; STATUS_TOP_PROCESSES];
+        for pcb in self.process_table.iter().flatten() {
+            let position = top
+                .iter()
+                .position(|entry| entry.is_none_or(|(_, cpu_time)| cpu_time < pcb.cpu_time));
+            if let Some(position) = position {
+                top.copy_within(position..STATUS_TOP_PROCESSES - 1, position + 1);
+                top[position] = Some((pcb.pid, pcb.cpu_time));
+            }
+        }
+
+        StatusReport {
+            resources: self.resource_accounting_snapshot(),
+            memory_free: memory::full_stats().free_bytes,
+            cores,
+            top,
+            kernel_tasks: self.kernel_task_pid,
+            messages_queued: self.ipc_queues.iter().map(|queue| queue.len()).sum(),
+            messages_sent: self.message_sequence,
+            last_fault: self.fault_dumps.newest().map(|dump| FaultSummary {
+                tick: dump.tick,
+                pid: dump.process.pid,
+                reason: dump.reason,
+            }),
+        }
+    }
+
+    /// Render [`Self::status_report`] into `out`; see [`StatusReport::render`].
+    pub fn render_status(&self, out: &mut [u8]) -> usize {
+        self.status_report().render(out)
+    }
+
+    /// Console control operations. [`CONSOLE_STATUS_DUMP`] writes the status
+    /// report to the default console, where a host harness can read it back;
+    /// returns the bytes the console accepted. The report is rendered in the
+    /// scratch arena of the core running `caller`, or of the boot core, and
+    /// fails with `AllocationFailed` once that arena is spent for the tick.
+    /// Requires `CAP_KERNEL`.
+    pub fn console_control(&mut self, caller: ProcessId, op: u32) -> KernelResult<usize> {
+        self.security
+            .authorize_kernel_control(caller)
+            .map_err(KernelError::SecurityViolation)?;
+        match op {
+            CONSOLE_STATUS_DUMP => {
+                let console = self.default_console().ok_or(KernelError::DeviceNotFound)?;
+                let report = self.status_report();
+                let core = self.core_running(caller).unwrap_or(0);
+                let mut scratch = ScratchCtx::new(core, &mut self.scratch_arenas[core]);
+                let rendered = report
+                    .render_scratch(&mut scratch)
+                    .ok_or(KernelError::AllocationFailed)?;
+                self.devices
+                    .write(console, caller, &self.security, rendered)
+                    .map_err(KernelError::DeviceFault)
+            }
+            _ => Err(KernelError::InvalidArgument),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::device::DeviceKind;
+    use crate::kernel::process::ProcessPriority;
+    use crate::subkernel::Credentials;
+
+    const GOLDEN: &str = "uptime 42 ticks
+cores 2/4 online
+ cpu0 75% busy
+ cpu1 0% busy
+procs 3/16
+ pid 1 cpu 3
+ pid 9 cpu 1
+ pid 5 cpu 0
+sched 2 queued, threads 3/64
+mem used 4096 peak 8192 free 1024
+msgs 1 queued, 5 sent
+fault none
+";
+
+    fn deterministic_report() -> StatusReport {
+        let mut cores = [CoreUsage::default(); cpu::MAX_CORES];
+        cores[0] = CoreUsage {
+            online: true,
+            busy_ticks: 3,
+            idle_ticks: 1,
+        };
+        cores[1] = CoreUsage {
+            online: true,
+            busy_ticks: 0,
+            idle_ticks: 0,
+        };
+        StatusReport {
+            resources: ResourceSnapshot {
+                processes_live: 3,
+                processes_max: 16,
+                threads_live: 3,
+                threads_max: 64,
+                memory_used: 4096,
+                memory_peak: 8192,
+                scheduler_depth: 2,
+                security_domains: 3,
+                uptime_ticks: 42,
+                online_cores: 2,
+            },
+            memory_free: 1024,
+            cores,
+            top: [
+                Some((ProcessId::new(1), 3)),
+                Some((ProcessId::new(9), 1)),
+                Some((ProcessId::new(5), 0)),
+            ],
+            kernel_tasks: None,
+            messages_queued: 1,
+            messages_sent: 5,
+            last_fault: None,
+        }
+    }
+
+    #[test]
+    fn status_report_matches_golden_format() {
+        let mut report = deterministic_report();
+        let mut out = [0u8; STATUS_REPORT_BYTES];
+        let len = report.render(&mut out);
+        assert_eq!(core::str::from_utf8(&out[..len]).unwrap(), GOLDEN);
+
+        report.kernel_tasks = Some(ProcessId::new(9));
+        let len = report.render(&mut out);
+        assert!(core::str::from_utf8(&out[..len])
+            .unwrap()
+            .contains("\n pid 9 cpu 1 [kernel]\n pid 5 cpu 0\n"));
+
+        report.last_fault = Some(FaultSummary {
+            tick: 40,
+            pid: ProcessId::new(9),
+            reason: IsolationError::PolicyViolation,
+        });
+        let len = report.render(&mut out);
+        assert!(core::str::from_utf8(&out[..len])
+            .unwrap()
+            .ends_with("msgs 1 queued, 5 sent\nfault pid 9 PolicyViolation at 40\n"));
+    }
+
+    #[test]
+    fn truncated_report_ends_on_a_line_boundary() {
+        let report = deterministic_report();
+        let mut out = [0u8; 40];
+        let len = report.render(&mut out);
+        assert_eq!(
+            core::str::from_utf8(&out[..len]).unwrap(),
+            "uptime 42 ticks\ncores 2/4 online\n"
+        );
+
+        let mut tiny = [0u8; 4];
+        assert_eq!(report.render(&mut tiny), 0);
+    }
+
+    #[test]
+    fn report_covers_the_kernels_own_core_count() {
+        let mut kernel = Kernel::<16, 4, 2>::new();
+        kernel.bootstrap();
+        kernel.bring_up_secondary_cores(1);
+
+        let report = kernel.status_report();
+        assert_eq!(report.cores.len(), 2);
+        assert!(report.cores.iter().all(|core| core.online));
+        let mut out = [0u8; STATUS_REPORT_BYTES];
+        let len = report.render(&mut out);
+        assert!(core::str::from_utf8(&out[..len])
+            .unwrap()
+            .contains("cores 2/2 online\n"));
+    }
+
+    #[test]
+    fn status_dump_control_op_writes_report_to_console() {
+        let mut kernel = Kernel::<16, 4>::new();
+        kernel.bootstrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let user = kernel
+            .spawn_child_process(init, 0, ProcessPriority::Normal, Credentials::user())
+            .unwrap();
+        assert!(matches!(
+            kernel.console_control(user, CONSOLE_STATUS_DUMP),
+            Err(KernelError::SecurityViolation(_))
+        ));
+        assert!(matches!(
+            kernel.console_control(init, 0xdead),
+            Err(KernelError::InvalidArgument)
+        ));
+
+        // Route the console to the buffered fallback so the report can be
+        // read back, as a host harness would.
+        let default = kernel.default_console().unwrap();
+        let mut consoles = [kernel.device_info(default).unwrap(); 4];
+        let count = kernel.devices_of_kind(DeviceKind::SerialConsole, &mut consoles);
+        let console = consoles[..count]
+            .iter()
+            .find(|device| device.name == "serial-buffer-fallback")
+            .unwrap()
+            .id;
+        kernel
+            .set_default_device(DeviceKind::SerialConsole, console)
+            .unwrap();
+
+        let written = kernel.console_control(init, CONSOLE_STATUS_DUMP).unwrap();
+        assert!(written > 0);
+        let mut drained = [0u8; 2048];
+        let mut len = 0;
+        while let Ok(count) =
+            kernel
+                .devices
+                .read(console, init, &kernel.security, &mut drained[len..])
+        {
+            if count == 0 {
+                break;
+            }
+            len += count;
+        }
+        let text = core::str::from_utf8(&drained[..len]).unwrap();
+        assert!(text.contains("uptime "));
+        assert!(text.contains("procs 2/16\n"));
+        assert!(text.contains(&format!("\n pid {} cpu 0\n", init.raw())));
+        assert!(text.contains("\nfault "));
+    }
+
+    #[test]
+    fn status_dump_renders_in_the_boot_core_scratch_arena() {
+        let mut kernel = Kernel::<16, 4>::new();
+        kernel.bootstrap();
+        let init = kernel.spawn_initial_process(Credentials::system()).unwrap();
+        let default = kernel.default_console().unwrap();
+        let mut consoles = [kernel.device_info(default).unwrap(); 4];
+        let count = kernel.devices_of_kind(DeviceKind::SerialConsole, &mut consoles);
+        let console = consoles[..count]
+            .iter()
+            .find(|device| device.name == "serial-buffer-fallback")
+            .unwrap()
+            .id;
+        kernel
+            .set_default_device(DeviceKind::SerialConsole, console)
+            .unwrap();
+
+        kernel.console_control(init, CONSOLE_STATUS_DUMP).unwrap();
+        assert_eq!(kernel.scratch_high_water_mark(0), Some(STATUS_REPORT_BYTES));
+
+        // Spend the rest of the arena; the dump fails until the next tick.
+        {
+            let mut scratch = kernel.scratch_context(0).unwrap();
+            let rest = scratch.remaining();
+            assert!(scratch.alloc(rest, 1).is_some());
+        }
+        assert!(matches!(
+            kernel.console_control(init, CONSOLE_STATUS_DUMP),
+            Err(KernelError::AllocationFailed)
+        ));
+        for pcb in kernel.process_table.iter_mut().flatten() {
+            pcb.address_space_root = 0x5354_0000;
+        }
+        kernel.tick();
+        assert!(kernel.console_control(init, CONSOLE_STATUS_DUMP).unwrap() > 0);
+    }
+}
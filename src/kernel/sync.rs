@@ -52,6 +52,13 @@ impl<T> SpinLock<T> {
         }
     }
 
+    /// Whether the lock is held right now. Advisory only: the answer may be
+    /// stale by the time it is read, so use it for diagnostics and test
+    /// assertions, never to decide whether to lock.
+    pub fn is_locked(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
     fn unlock(&self) {
         self.flag.store(false, Ordering::Release);
     }
@@ -80,3 +87,25 @@ impl<'a, T> Drop for SpinLockGuard<'a, T> {
         self.lock.unlock();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_locked_tracks_guard_lifetime() {
+        let lock = SpinLock::new(0u32);
+        assert!(!lock.is_locked());
+
+        let guard = lock.lock();
+        assert!(lock.is_locked());
+        assert!(lock.try_lock().is_none());
+        drop(guard);
+        assert!(!lock.is_locked());
+
+        let guard = lock.try_lock().unwrap();
+        assert!(lock.is_locked());
+        drop(guard);
+        assert!(!lock.is_locked());
+    }
+}
@@ -3,7 +3,9 @@
 //! The table in [`SyscallNumber`] is append-only: existing numeric assignments
 //! are treated as ABI and must not be reused for a different operation.
 
-use crate::kernel::memory::{self, MemoryProtection};
+pub mod abi;
+
+use crate::kernel::memory::{self, MemoryProtection, MmapFlags};
 use crate::kernel::process::ProcessId;
 use crate::kernel::thread::{CpuContext, ThreadId};
 
@@ -58,7 +60,8 @@ pub fn dispatch_kernel_memory_syscall(number: SyscallNumber, context: SyscallCon
         SyscallNumber::Mmap => {
             let length = context.arg(0) as usize;
             let protection = MemoryProtection::from_bits(context.arg(1) as u32);
-            memory::mmap_for(context.caller, length, protection)
+            let flags = MmapFlags::from_bits(context.arg(2) as u32).without(MmapFlags::FIXED);
+            memory::mmap_with_flags_for(context.caller, length, protection, flags, None)
                 .map(|region| region.as_ptr() as u64)
                 .unwrap_or(0)
         }
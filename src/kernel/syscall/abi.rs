@@ -0,0 +1,722 @@
+//! Typed decoding of raw syscall frames.
+//!
+//! Numeric ids come from [`SyscallNumber`], whose assignments are frozen by the
+//! golden table in `mirage-abi`. [`decode`] turns a [`SyscallFrame`] into a
+//! [`Syscall`] and rejects unknown numbers, argument values that do not fit
+//! their field, flag bits Mirage does not define, non-zero unused registers and
+//! user pointers outside the caller's address space. [`Syscall::encode`] is its
+//! inverse.
+//!
+//! Mirage-native calls get typed variants. POSIX-facing calls keep their Linux
+//! register layouts, which their handlers already validate, so they decode to
+//! [`Syscall::Posix`] with the registers untouched.
+
+use core::mem::size_of;
+
+use super::{SyscallFrame, SyscallNumber, SYSCALL_MAX_ARGS};
+use crate::kernel::device::{DeviceId, MirageDeviceDescriptor};
+use crate::kernel::ipc::Message;
+use crate::kernel::memory::{MemoryProtection, MmapFlags, PROT_EXECUTE, PROT_READ, PROT_WRITE};
+use crate::kernel::process::ProcessId;
+use crate::kernel::services::registry::ServiceId;
+use crate::kernel::{
+    decode_security_class, encode_syscall_error, validate_user_access, validate_user_range,
+    KernelError, KernelResult,
+};
+use crate::subkernel::SecurityClass;
+
+const PROTECTION_BITS: u64 = (PROT_READ | PROT_WRITE | PROT_EXECUTE) as u64;
+const MMAP_FLAG_BITS: u64 = (MmapFlags::ANONYMOUS.bits() | MmapFlags::POPULATE.bits()) as u64;
+
+/// A user buffer named by a pointer and length register pair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserBuffer {
+    pub ptr: u64,
+    pub len: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Syscall {
+    GetPid,
+    Spawn {
+        path: UserBuffer,
+        argv: UserBuffer,
+        envp: UserBuffer,
+    },
+    SendIpc {
+        receiver: ProcessId,
+        data: UserBuffer,
+        class: SecurityClass,
+    },
+    ReceiveIpc {
+        out: u64,
+    },
+    BlockForIpc,
+    ReceiveOrBlockIpc {
+        out: u64,
+    },
+    EnumerateDevices {
+        out: u64,
+        capacity: usize,
+    },
+    DeviceInfo {
+        device: DeviceId,
+        out: u64,
+    },
+    DeviceRead {
+        device: DeviceId,
+        buffer: UserBuffer,
+    },
+    DeviceWrite {
+        device: DeviceId,
+        data: UserBuffer,
+    },
+    Mmap {
+        length: usize,
+        protection: MemoryProtection,
+        flags: MmapFlags,
+    },
+    Munmap {
+        addr: u64,
+        length: usize,
+    },
+    Malloc {
+        size: usize,
+    },
+    Free {
+        ptr: u64,
+    },
+    Realloc {
+        ptr: u64,
+        size: usize,
+    },
+    MallocAligned {
+        size: usize,
+        alignment: usize,
+    },
+    RegisterService {
+        service: ServiceId,
+        /// `None` registers the caller.
+        owner: Option<ProcessId>,
+    },
+    SendServiceIpc {
+        service: ServiceId,
+        data: UserBuffer,
+        class: SecurityClass,
+    },
+    ClaimDevice {
+        service: ServiceId,
+        device: DeviceId,
+    },
+    ReleaseDevice {
+        service: ServiceId,
+        device: DeviceId,
+    },
+    Exit {
+        status: i32,
+    },
+    Yield,
+    Posix {
+        number: SyscallNumber,
+        args: [u64; SYSCALL_MAX_ARGS],
+    },
+}
+
+pub fn decode(frame: &SyscallFrame) -> KernelResult<Syscall> {
+    let number = SyscallNumber::from_raw(frame.number).ok_or(KernelError::InvalidSyscall)?;
+    let args = &frame.args;
+    let syscall = match number {
+        SyscallNumber::GetPid => Syscall::GetPid,
+        SyscallNumber::Spawn => Syscall::Spawn {
+            path: user_buffer(args[0], args[1])?,
+            argv: user_buffer(args[2], args[3])?,
+            envp: user_buffer(args[4], args[5])?,
+        },
+        SyscallNumber::SendIpc => Syscall::SendIpc {
+            receiver: ProcessId::new(args[0]),
+            data: user_buffer(args[1], args[2])?,
+            class: decode_security_class(args[3])?,
+        },
+        SyscallNumber::ReceiveIpc => Syscall::ReceiveIpc {
+            out: user_out::<Message>(args[0])?,
+        },
+        SyscallNumber::BlockForIpc => Syscall::BlockForIpc,
+        SyscallNumber::ReceiveOrBlockIpc => Syscall::ReceiveOrBlockIpc {
+            out: user_out::<Message>(args[0])?,
+        },
+        SyscallNumber::EnumerateDevices => {
+            let capacity = usize_arg(args[1])?;
+            let bytes = capacity
+                .checked_mul(size_of::<MirageDeviceDescriptor>())
+                .ok_or(KernelError::InvalidArgument)?;
+            validate_user_access(args[0], bytes, true)?;
+            Syscall::EnumerateDevices {
+                out: args[0],
+                capacity,
+            }
+        }
+        SyscallNumber::DeviceInfo => Syscall::DeviceInfo {
+            device: device_arg(args[0])?,
+            out: user_out::<MirageDeviceDescriptor>(args[1])?,
+        },
+        SyscallNumber::DeviceRead => {
+            let buffer = user_buffer(args[1], args[2])?;
+            validate_user_access(buffer.ptr, buffer.len, true)?;
+            Syscall::DeviceRead {
+                device: device_arg(args[0])?,
+                buffer,
+            }
+        }
+        SyscallNumber::DeviceWrite => Syscall::DeviceWrite {
+            device: device_arg(args[0])?,
+            data: user_buffer(args[1], args[2])?,
+        },
+        SyscallNumber::Mmap => Syscall::Mmap {
+            length: usize_arg(args[0])?,
+            protection: MemoryProtection::from_bits(flag_arg(args[1], PROTECTION_BITS)?),
+            flags: MmapFlags::from_bits(flag_arg(args[2], MMAP_FLAG_BITS)?),
+        },
+        SyscallNumber::Munmap => Syscall::Munmap {
+            addr: non_null(args[0])?,
+            length: usize_arg(args[1])?,
+        },
+        SyscallNumber::Malloc => Syscall::Malloc {
+            size: usize_arg(args[0])?,
+        },
+        SyscallNumber::Free => Syscall::Free {
+            ptr: non_null(args[0])?,
+        },
+        SyscallNumber::Realloc => Syscall::Realloc {
+            ptr: args[0],
+            size: usize_arg(args[1])?,
+        },
+        SyscallNumber::MallocAligned => {
+            let alignment = usize_arg(args[1])?;
+            if !alignment.is_power_of_two() {
+                return Err(KernelError::InvalidArgument);
+            }
+            Syscall::MallocAligned {
+                size: usize_arg(args[0])?,
+                alignment,
+            }
+        }
+        SyscallNumber::RegisterService => Syscall::RegisterService {
+            service: service_arg(args[0])?,
+            owner: (args[1] != 0).then(|| ProcessId::new(args[1])),
+        },
+        SyscallNumber::SendServiceIpc => Syscall::SendServiceIpc {
+            service: service_arg(args[0])?,
+            data: user_buffer(args[1], args[2])?,
+            class: decode_security_class(args[3])?,
+        },
+        SyscallNumber::ClaimDevice => Syscall::ClaimDevice {
+            service: service_arg(args[0])?,
+            device: device_arg(args[1])?,
+        },
+        SyscallNumber::ReleaseDevice => Syscall::ReleaseDevice {
+            service: service_arg(args[0])?,
+            device: device_arg(args[1])?,
+        },
+        SyscallNumber::Exit => Syscall::Exit {
+            status: i32::try_from(args[0] as i64).map_err(|_| KernelError::InvalidArgument)?,
+        },
+        SyscallNumber::Yield => Syscall::Yield,
+        _ => {
+            return Ok(Syscall::Posix {
+                number,
+                args: frame.args,
+            })
+        }
+    };
+    // Registers a native call does not use must be zero so they stay free
+    // for later extension.
+    if syscall.encode().args != frame.args {
+        return Err(KernelError::InvalidArgument);
+    }
+    Ok(syscall)
+}
+
+impl Syscall {
+    pub fn number(&self) -> SyscallNumber {
+        match self {
+            Syscall::GetPid => SyscallNumber::GetPid,
+            Syscall::Spawn { .. } => SyscallNumber::Spawn,
+            Syscall::SendIpc { .. } => SyscallNumber::SendIpc,
+            Syscall::ReceiveIpc { .. } => SyscallNumber::ReceiveIpc,
+            Syscall::BlockForIpc => SyscallNumber::BlockForIpc,
+            Syscall::ReceiveOrBlockIpc { .. } => SyscallNumber::ReceiveOrBlockIpc,
+            Syscall::EnumerateDevices { .. } => SyscallNumber::EnumerateDevices,
+            Syscall::DeviceInfo { .. } => SyscallNumber::DeviceInfo,
+            Syscall::DeviceRead { .. } => SyscallNumber::DeviceRead,
+            Syscall::DeviceWrite { .. } => SyscallNumber::DeviceWrite,
+            Syscall::Mmap { .. } => SyscallNumber::Mmap,
+            Syscall::Munmap { .. } => SyscallNumber::Munmap,
+            Syscall::Malloc { .. } => SyscallNumber::Malloc,
+            Syscall::Free { .. } => SyscallNumber::Free,
+            Syscall::Realloc { .. } => SyscallNumber::Realloc,
+            Syscall::MallocAligned { .. } => SyscallNumber::MallocAligned,
+            Syscall::RegisterService { .. } => SyscallNumber::RegisterService,
+            Syscall::SendServiceIpc { .. } => SyscallNumber::SendServiceIpc,
+            Syscall::ClaimDevice { .. } => SyscallNumber::ClaimDevice,
+            Syscall::ReleaseDevice { .. } => SyscallNumber::ReleaseDevice,
+            Syscall::Exit { .. } => SyscallNumber::Exit,
+            Syscall::Yield => SyscallNumber::Yield,
+            Syscall::Posix { number, .. } => *number,
+        }
+    }
+
+    pub fn encode(&self) -> SyscallFrame {
+        let args = match *self {
+            Syscall::GetPid | Syscall::BlockForIpc | Syscall::Yield => [0; SYSCALL_MAX_ARGS],
+            Syscall::Spawn { path, argv, envp } => [
+                path.ptr,
+                path.len as u64,
+                argv.ptr,
+                argv.len as u64,
+                envp.ptr,
+                envp.len as u64,
+            ],
+            Syscall::SendIpc {
+                receiver,
+                data,
+                class,
+            } => [
+                receiver.raw(),
+                data.ptr,
+                data.len as u64,
+                security_class_raw(class),
+                0,
+                0,
+            ],
+            Syscall::ReceiveIpc { out } | Syscall::ReceiveOrBlockIpc { out } => {
+                [out, 0, 0, 0, 0, 0]
+            }
+            Syscall::EnumerateDevices { out, capacity } => [out, capacity as u64, 0, 0, 0, 0],
+            Syscall::DeviceInfo { device, out } => [device.raw() as u64, out, 0, 0, 0, 0],
+            Syscall::DeviceRead {
+                device,
+                buffer: data,
+            }
+            | Syscall::DeviceWrite { device, data } => {
+                [device.raw() as u64, data.ptr, data.len as u64, 0, 0, 0]
+            }
+            Syscall::Mmap {
+                length,
+                protection,
+                flags,
+            } => [
+                length as u64,
+                protection.bits() as u64,
+                flags.bits() as u64,
+                0,
+                0,
+                0,
+            ],
+            Syscall::Munmap { addr, length } => [addr, length as u64, 0, 0, 0, 0],
+            Syscall::Malloc { size } => [size as u64, 0, 0, 0, 0, 0],
+            Syscall::Free { ptr } => [ptr, 0, 0, 0, 0, 0],
+            Syscall::Realloc { ptr, size } => [ptr, size as u64, 0, 0, 0, 0],
+            Syscall::MallocAligned { size, alignment } => {
+                [size as u64, alignment as u64, 0, 0, 0, 0]
+            }
+            Syscall::RegisterService { service, owner } => {
+                [service.raw(), owner.map_or(0, |pid| pid.raw()), 0, 0, 0, 0]
+            }
+            Syscall::SendServiceIpc {
+                service,
+                data,
+                class,
+            } => [
+                service.raw(),
+                data.ptr,
+                data.len as u64,
+                security_class_raw(class),
+                0,
+                0,
+            ],
+            Syscall::ClaimDevice { service, device }
+            | Syscall::ReleaseDevice { service, device } => {
+                [service.raw(), device.raw() as u64, 0, 0, 0, 0]
+            }
+            Syscall::Exit { status } => [status as i64 as u64, 0, 0, 0, 0, 0],
+            Syscall::Posix { args, .. } => args,
+        };
+        SyscallFrame {
+            number: self.number().raw(),
+            args,
+        }
+    }
+}
+
+/// Pack a handler result into the trap return register: the value itself on
+/// success, or [`MIRAGE_SYSCALL_ERROR_BIT`](super::MIRAGE_SYSCALL_ERROR_BIT)
+/// plus the error code on failure.
+pub fn encode_result(result: KernelResult<u64>) -> u64 {
+    result.unwrap_or_else(encode_syscall_error)
+}
+
+fn usize_arg(raw: u64) -> KernelResult<usize> {
+    usize::try_from(raw).map_err(|_| KernelError::InvalidArgument)
+}
+
+fn flag_arg(raw: u64, known: u64) -> KernelResult<u32> {
+    if raw & !known != 0 {
+        return Err(KernelError::InvalidArgument);
+    }
+    Ok(raw as u32)
+}
+
+fn device_arg(raw: u64) -> KernelResult<DeviceId> {
+    u16::try_from(raw)
+        .map(DeviceId::new)
+        .map_err(|_| KernelError::InvalidArgument)
+}
+
+fn service_arg(raw: u64) -> KernelResult<ServiceId> {
+    ServiceId::from_raw(raw).ok_or(KernelError::InvalidArgument)
+}
+
+fn user_buffer(ptr: u64, len: u64) -> KernelResult<UserBuffer> {
+    let len = usize_arg(len)?;
+    validate_user_range(ptr, len)?;
+    Ok(UserBuffer { ptr, len })
+}
+
+fn user_out<T>(ptr: u64) -> KernelResult<u64> {
+    validate_user_access(ptr, size_of::<T>(), true)?;
+    Ok(ptr)
+}
+
+/// Heap and mapping addresses come from the kernel allocator rather than
+/// the caller's address space, so only null is rejected up front.
+fn non_null(ptr: u64) -> KernelResult<u64> {
+    if ptr == 0 {
+        return Err(KernelError::InvalidPointer);
+    }
+    Ok(ptr)
+}
+
+const fn security_class_raw(class: SecurityClass) -> u64 {
+    match class {
+        SecurityClass::Public => 0,
+        SecurityClass::Internal => 1,
+        SecurityClass::Confidential => 2,
+        SecurityClass::System => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kernel::device::DeviceError;
+    use crate::kernel::fs::VfsError;
+    use crate::kernel::syscall::{SyscallErrorCode, MIRAGE_SYSCALL_ERROR_BIT};
+    use crate::kernel::userspace::LoadError;
+    use crate::subkernel::IsolationError;
+    use mirage_abi::syscall::*;
+
+    const USER_BUF: u64 = 0x40_0000;
+
+    fn frame(number: SyscallNumber, args: [u64; SYSCALL_MAX_ARGS]) -> SyscallFrame {
+        SyscallFrame {
+            number: number.raw(),
+            args,
+        }
+    }
+
+    /// A frame for `number` that passes validation.
+    fn valid_frame(number: SyscallNumber) -> SyscallFrame {
+        let args = match number {
+            SyscallNumber::Spawn => [USER_BUF, 8, USER_BUF + 0x100, 16, 0, 0],
+            SyscallNumber::SendIpc | SyscallNumber::SendServiceIpc => [2, USER_BUF, 4, 1, 0, 0],
+            SyscallNumber::ReceiveIpc | SyscallNumber::ReceiveOrBlockIpc => {
+                [USER_BUF, 0, 0, 0, 0, 0]
+            }
+            SyscallNumber::EnumerateDevices => [USER_BUF, 4, 0, 0, 0, 0],
+            SyscallNumber::DeviceInfo => [3, USER_BUF, 0, 0, 0, 0],
+            SyscallNumber::DeviceRead | SyscallNumber::DeviceWrite => [3, USER_BUF, 64, 0, 0, 0],
+            SyscallNumber::Mmap => [4096, 3, 1, 0, 0, 0],
+            SyscallNumber::Munmap | SyscallNumber::Realloc => [USER_BUF, 4096, 0, 0, 0, 0],
+            SyscallNumber::Malloc | SyscallNumber::Free => [USER_BUF, 0, 0, 0, 0, 0],
+            SyscallNumber::MallocAligned => [128, 64, 0, 0, 0, 0],
+            SyscallNumber::RegisterService => [2, 7, 0, 0, 0, 0],
+            SyscallNumber::ClaimDevice | SyscallNumber::ReleaseDevice => [2, 3, 0, 0, 0, 0],
+            SyscallNumber::Exit => [(-3i64) as u64, 0, 0, 0, 0, 0],
+            SyscallNumber::GetPid | SyscallNumber::BlockForIpc | SyscallNumber::Yield => [0; 6],
+            _ => [u64::MAX, 1, 2, 3, 4, 5],
+        };
+        frame(number, args)
+    }
+
+    #[test]
+    fn every_syscall_round_trips_through_decode_and_encode() {
+        for number in SyscallNumber::ALL {
+            let raw = valid_frame(number);
+            let decoded = decode(&raw).unwrap_or_else(|error| panic!("{number:?}: {error:?}"));
+            assert_eq!(decoded.number(), number);
+            assert_eq!(decoded.encode(), raw, "{number:?}");
+            assert_eq!(decode(&decoded.encode()).unwrap(), decoded);
+        }
+    }
+
+    #[test]
+    fn typed_fields_decode_to_their_values() {
+        assert_eq!(
+            decode(&valid_frame(SyscallNumber::SendIpc)).unwrap(),
+            Syscall::SendIpc {
+                receiver: ProcessId::new(2),
+                data: UserBuffer {
+                    ptr: USER_BUF,
+                    len: 4,
+                },
+                class: SecurityClass::Internal,
+            }
+        );
+        assert_eq!(
+            decode(&valid_frame(SyscallNumber::Exit)).unwrap(),
+            Syscall::Exit { status: -3 }
+        );
+        assert_eq!(
+            decode(&frame(SyscallNumber::RegisterService, [2, 0, 0, 0, 0, 0])).unwrap(),
+            Syscall::RegisterService {
+                service: ServiceId::Networkd,
+                owner: None,
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_numbers_are_invalid_syscalls() {
+        let raw = SyscallFrame {
+            number: 99,
+            args: [0; SYSCALL_MAX_ARGS],
+        };
+        assert!(matches!(decode(&raw), Err(KernelError::InvalidSyscall)));
+    }
+
+    #[test]
+    fn each_field_rejects_out_of_range_values() {
+        let cases: [(SyscallNumber, [u64; SYSCALL_MAX_ARGS], KernelError); 15] = [
+            // Pointers outside user space or null with a length.
+            (
+                SyscallNumber::Spawn,
+                [0, 8, 0, 0, 0, 0],
+                KernelError::InvalidPointer,
+            ),
+            (
+                SyscallNumber::Spawn,
+                [USER_BUF, 8, u64::MAX - 4, 16, 0, 0],
+                KernelError::InvalidPointer,
+            ),
+            (
+                SyscallNumber::ReceiveIpc,
+                [0; 6],
+                KernelError::InvalidPointer,
+            ),
+            (
+                SyscallNumber::DeviceInfo,
+                [3, 1 << 47, 0, 0, 0, 0],
+                KernelError::InvalidPointer,
+            ),
+            (
+                SyscallNumber::EnumerateDevices,
+                [USER_BUF, u64::MAX, 0, 0, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+            (SyscallNumber::Free, [0; 6], KernelError::InvalidPointer),
+            // Values that do not fit their field.
+            (
+                SyscallNumber::SendIpc,
+                [2, USER_BUF, 4, 4, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+            (
+                SyscallNumber::DeviceRead,
+                [0x1_0000, USER_BUF, 64, 0, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+            (
+                SyscallNumber::ClaimDevice,
+                [0, 3, 0, 0, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+            (
+                SyscallNumber::MallocAligned,
+                [128, 48, 0, 0, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+            (
+                SyscallNumber::Exit,
+                [1 << 40, 0, 0, 0, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+            // Undefined flag bits; FIXED is not accepted from user space.
+            (
+                SyscallNumber::Mmap,
+                [4096, 8, 0, 0, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+            (
+                SyscallNumber::Mmap,
+                [4096, 1, MmapFlags::FIXED.bits() as u64, 0, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+            // Unused registers must be zero.
+            (
+                SyscallNumber::GetPid,
+                [0, 0, 0, 0, 0, 1],
+                KernelError::InvalidArgument,
+            ),
+            (
+                SyscallNumber::Malloc,
+                [64, 1, 0, 0, 0, 0],
+                KernelError::InvalidArgument,
+            ),
+        ];
+        for (number, args, expected) in cases {
+            let result = decode(&frame(number, args));
+            assert_eq!(
+                result.map_err(encode_syscall_error),
+                Err(encode_syscall_error(expected)),
+                "{number:?} {args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn results_pack_values_and_error_codes() {
+        assert_eq!(encode_result(Ok(42)), 42);
+        let cases: [(KernelError, SyscallErrorCode, i32); 25] = [
+            (
+                KernelError::ProcessTableFull,
+                SyscallErrorCode::ProcessTableFull,
+                MIRAGE_ENOMEM,
+            ),
+            (
+                KernelError::SchedulerFull,
+                SyscallErrorCode::SchedulerFull,
+                MIRAGE_ENOMEM,
+            ),
+            (
+                KernelError::UnknownProcess,
+                SyscallErrorCode::NoSuchProcess,
+                MIRAGE_ESRCH,
+            ),
+            (
+                KernelError::ReceiverTerminated,
+                SyscallErrorCode::NoSuchProcess,
+                MIRAGE_ESRCH,
+            ),
+            (
+                KernelError::UnknownThread,
+                SyscallErrorCode::NoSuchThread,
+                MIRAGE_ESRCH,
+            ),
+            (
+                KernelError::ThreadTableFull,
+                SyscallErrorCode::ThreadTableFull,
+                MIRAGE_ENOMEM,
+            ),
+            (
+                KernelError::MessageQueueFull,
+                SyscallErrorCode::QueueFull,
+                MIRAGE_ENOBUFS,
+            ),
+            (
+                KernelError::MessageQueueEmpty,
+                SyscallErrorCode::QueueEmpty,
+                MIRAGE_EAGAIN,
+            ),
+            (
+                KernelError::MessageCorrupted,
+                SyscallErrorCode::DeviceFault,
+                MIRAGE_EIO,
+            ),
+            (
+                KernelError::NoCredits,
+                SyscallErrorCode::QueueFull,
+                MIRAGE_ENOBUFS,
+            ),
+            (
+                KernelError::ClassQuotaExceeded,
+                SyscallErrorCode::QueueFull,
+                MIRAGE_ENOBUFS,
+            ),
+            (
+                KernelError::SecurityViolation(IsolationError::CapabilityMissing),
+                SyscallErrorCode::PermissionDenied,
+                MIRAGE_EACCES,
+            ),
+            (
+                KernelError::SecurityTableFull,
+                SyscallErrorCode::ProcessTableFull,
+                MIRAGE_ENOMEM,
+            ),
+            (
+                KernelError::IsolationFault(IsolationError::UnknownTask),
+                SyscallErrorCode::NoSuchProcess,
+                MIRAGE_ESRCH,
+            ),
+            (
+                KernelError::DeviceNotFound,
+                SyscallErrorCode::NoSuchDevice,
+                MIRAGE_ENODEV,
+            ),
+            (
+                KernelError::DeviceFault(DeviceError::Busy),
+                SyscallErrorCode::DeviceFault,
+                MIRAGE_EIO,
+            ),
+            (
+                KernelError::InvalidSyscall,
+                SyscallErrorCode::InvalidSyscall,
+                MIRAGE_ENOSYS,
+            ),
+            (
+                KernelError::InvalidArgument,
+                SyscallErrorCode::InvalidArgument,
+                MIRAGE_EINVAL,
+            ),
+            (
+                KernelError::InvalidPointer,
+                SyscallErrorCode::BadAddress,
+                MIRAGE_EFAULT,
+            ),
+            (
+                KernelError::AllocationFailed,
+                SyscallErrorCode::OutOfMemory,
+                MIRAGE_ENOMEM,
+            ),
+            (
+                KernelError::FileTableFull,
+                SyscallErrorCode::OutOfMemory,
+                MIRAGE_ENOMEM,
+            ),
+            (
+                KernelError::Filesystem(VfsError::NotFound),
+                SyscallErrorCode::FileNotFound,
+                MIRAGE_ENOENT,
+            ),
+            (
+                KernelError::TimedOut,
+                SyscallErrorCode::TimedOut,
+                MIRAGE_ETIMEDOUT,
+            ),
+            (
+                KernelError::Loader(LoadError::BadMagic),
+                SyscallErrorCode::InvalidArgument,
+                MIRAGE_EINVAL,
+            ),
+            (
+                KernelError::SecurityViolation(IsolationError::DomainTableFull),
+                SyscallErrorCode::ProcessTableFull,
+                MIRAGE_ENOMEM,
+            ),
+        ];
+        for (error, code, errno) in cases {
+            let packed = encode_result(Err(error));
+            assert_eq!(packed, MIRAGE_SYSCALL_ERROR_BIT | code.raw(), "{error:?}");
+            assert_eq!(code.linux_errno(), errno, "{error:?}");
+        }
+    }
+}
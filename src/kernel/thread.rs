@@ -1,5 +1,6 @@
 //! Thread management primitives used by the Mirage kernel scheduler.
 
+use crate::kernel::memory::PAGE_SIZE;
 use crate::kernel::process::{ChildWaitSelector, ProcessId, ProcessPriority, SignalMask};
 use crate::kernel::syscall::SYSCALL_MAX_ARGS;
 
@@ -223,6 +224,27 @@ impl CpuContext {
     }
 }
 
+/// What touching the guard page below a thread's stack does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StackPolicy {
+    /// Raise a stack overflow fault that terminates the thread.
+    #[default]
+    Fault,
+    /// Map the guard page as stack and move the guard one page down, until
+    /// the stack has grown by `max_bytes`. Past the cap, or when no page can
+    /// be mapped, the fault is handled as under [`StackPolicy::Fault`].
+    Grow { max_bytes: u32 },
+}
+
+/// How the kernel resolved a fault on a thread's stack guard page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackFaultOutcome {
+    /// The stack was extended; `guard` is the new guard page.
+    Grown { guard: u64 },
+    /// The thread was terminated and a fault dump recorded.
+    Overflow,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct ThreadControlBlock {
     pub id: ThreadId,
@@ -242,6 +264,26 @@ pub struct ThreadControlBlock {
     pub shares_address_space: bool,
     pub shares_descriptor_table: bool,
     pub child_wait: Option<ChildWaitSelector>,
+    pub stack_guard_hit: bool,
+    pub stack_policy: StackPolicy,
+    /// Bytes mapped below the initial stack by [`StackPolicy::Grow`].
+    pub stack_grown: u32,
+    /// Bytes reserved for the stack below `stack_pointer`, not counting
+    /// growth. Zero until a layout is set, in which case the stack pointer
+    /// is not checked on dispatch.
+    pub stack_size: usize,
+    /// Unmapped bytes directly below the stack that catch overflows.
+    pub stack_guard_size: usize,
+    /// Scheduler tick by which the thread should have stopped being runnable.
+    pub deadline: Option<u64>,
+    /// Deadlines that passed while the thread was still runnable.
+    pub deadline_misses: u32,
+    /// Kernel tick at which the thread was last woken and has not run since.
+    #[cfg(feature = "latency-metrics")]
+    pub woken_tick: Option<u64>,
+    /// Kernel tick at which the thread last became runnable, cleared when it
+    /// is dispatched.
+    pub ready_tick: Option<u64>,
 }
 
 impl ThreadControlBlock {
@@ -272,9 +314,31 @@ impl ThreadControlBlock {
             shares_address_space: false,
             shares_descriptor_table: false,
             child_wait: None,
+            stack_guard_hit: false,
+            stack_policy: StackPolicy::Fault,
+            stack_grown: 0,
+            stack_size: 0,
+            stack_guard_size: PAGE_SIZE,
+            deadline: None,
+            deadline_misses: 0,
+            #[cfg(feature = "latency-metrics")]
+            woken_tick: None,
+            ready_tick: None,
         }
     }
 
+    /// Lowest mapped stack address, including growth.
+    pub fn stack_base(&self) -> Option<u64> {
+        self.stack_pointer
+            .checked_sub(self.stack_size as u64)?
+            .checked_sub(u64::from(self.stack_grown))
+    }
+
+    /// Page directly below the lowest mapped stack page.
+    pub fn stack_guard_page(&self) -> Option<u64> {
+        self.stack_base()?.checked_sub(PAGE_SIZE as u64)
+    }
+
     pub fn prepare_syscall(&mut self, number: u64, args: [u64; SYSCALL_MAX_ARGS]) {
         self.context.stage_syscall_trap(number, args);
     }
@@ -291,8 +355,16 @@ impl ThreadControlBlock {
         self.state = ThreadState::Ready;
     }
 
+    /// `mark_ready`, noting `tick` as the start of the wait for a core.
+    pub fn mark_ready_at(&mut self, tick: u64) {
+        self.mark_ready();
+        self.ready_tick = Some(tick);
+    }
+
+    /// Blocking completes the thread's current work, so any deadline is met.
     pub fn block(&mut self) {
         self.state = ThreadState::Blocked;
+        self.deadline = None;
     }
 
     pub fn wait_for_child(&mut self, selector: ChildWaitSelector) {
@@ -319,6 +391,11 @@ impl ThreadControlBlock {
         self.gs_base = 0;
         self.state = ThreadState::Ready;
         self.active_signal = None;
+        self.stack_guard_hit = false;
+        self.stack_grown = 0;
+        self.stack_size = 0;
+        self.stack_guard_size = PAGE_SIZE;
+        self.deadline = None;
     }
 
     pub fn configure_clone_semantics(
@@ -373,3 +450,89 @@ impl ThreadControlBlock {
 const fn is_canonical_user_address(address: u64) -> bool {
     address < 0x0000_8000_0000_0000
 }
+
+/// Thread creation deferred until a thread-table slot frees up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingThreadSpawn {
+    pub process: ProcessId,
+    pub entry_point: u64,
+    pub priority: ProcessPriority,
+}
+
+/// FIFO of deferred thread spawns, serviced oldest first.
+#[derive(Clone, Copy)]
+pub struct ThreadWaitlist<const N: usize> {
+    entries: [Option<PendingThreadSpawn>; N],
+    len: usize,
+}
+
+impl<const N: usize> ThreadWaitlist<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; N],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, request: PendingThreadSpawn) -> bool {
+        if self.len == N {
+            return false;
+        }
+        self.entries[self.len] = Some(request);
+        self.len += 1;
+        true
+    }
+
+    pub fn front(&self) -> Option<PendingThreadSpawn> {
+        if self.len == 0 {
+            return None;
+        }
+        self.entries[0]
+    }
+
+    pub fn pop_front(&mut self) -> Option<PendingThreadSpawn> {
+        let front = self.front()?;
+        self.remove_at(0);
+        Some(front)
+    }
+
+    /// Drop every pending spawn for `process`.
+    pub fn remove_process(&mut self, process: ProcessId) {
+        let mut idx = 0;
+        while idx < self.len {
+            match self.entries[idx] {
+                Some(request) if request.process == process => self.remove_at(idx),
+                _ => idx += 1,
+            }
+        }
+    }
+
+    fn remove_at(&mut self, index: usize) {
+        let mut idx = index;
+        while idx + 1 < self.len {
+            self.entries[idx] = self.entries[idx + 1];
+            idx += 1;
+        }
+        self.len -= 1;
+        self.entries[self.len] = None;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for ThreadWaitlist<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -42,14 +42,109 @@ impl MonotonicTimestamp {
     }
 }
 
+/// Calendar date and time of day, as used in log timestamps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WallTimestamp {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl WallTimestamp {
+    const SECONDS_PER_DAY: i64 = 86_400;
+
+    /// Convert seconds since the Unix epoch to a proleptic Gregorian date.
+    /// Times before the epoch clamp to 1970-01-01 00:00:00.
+    pub fn from_unix_seconds(seconds: i64) -> Self {
+        let seconds = seconds.max(0);
+        let days = seconds / Self::SECONDS_PER_DAY;
+        let time_of_day = seconds % Self::SECONDS_PER_DAY;
+
+        // Civil-from-days: count in 400-year eras starting on 0000-03-01 so
+        // the leap day falls at the end of each computed year.
+        let shifted = days + 719_468;
+        let era = shifted / 146_097;
+        let day_of_era = shifted - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year: year.min(u16::MAX as i64) as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (time_of_day / 3_600) as u8,
+            minute: (time_of_day % 3_600 / 60) as u8,
+            second: (time_of_day % 60) as u8,
+        }
+    }
+}
+
+/// A deliberate change to the hardware tick counter, applied through
+/// [`Kernel::adjust_clock`](crate::kernel::Kernel::adjust_clock).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockAdjustment {
+    /// The host was suspended for `slept_ticks`; the counter jumps forward.
+    Resume { slept_ticks: u64 },
+    /// The counter goes back to zero.
+    Reset,
+    /// The counter is set to `ticks`.
+    Set { ticks: u64 },
+}
+
+/// A jump in the tick counter that was not the clock advancing normally.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockDiscontinuity {
+    pub old_ticks: u64,
+    pub new_ticks: u64,
+    /// Whether the kernel was told about the jump, rather than catching the
+    /// counter running backwards on its own.
+    pub mediated: bool,
+}
+
+impl ClockDiscontinuity {
+    pub const fn is_backward(&self) -> bool {
+        self.new_ticks < self.old_ticks
+    }
+}
+
+/// Where a deadline armed before the clock jumped from `old` to `new`
+/// belongs afterwards. A forward jump leaves it alone, so anything that
+/// became due fires. A backward jump keeps the time it had left, clamped to
+/// `new` so a deadline that had already passed fires instead of re-arming.
+pub fn rebase_deadline(deadline: u128, old: u128, new: u128) -> u128 {
+    if new >= old {
+        return deadline;
+    }
+    deadline.saturating_sub(old - new).max(new)
+}
+
 pub struct KernelTime {
     last_tick: AtomicU64,
+    /// Unix time, in seconds, at tick zero.
+    base_unix_time: AtomicU64,
+    discontinuities: AtomicU64,
+    unmediated_discontinuities: AtomicU64,
 }
 
 impl KernelTime {
     pub const fn new() -> Self {
         Self {
             last_tick: AtomicU64::new(0),
+            base_unix_time: AtomicU64::new(0),
+            discontinuities: AtomicU64::new(0),
+            unmediated_discontinuities: AtomicU64::new(0),
         }
     }
 
@@ -61,9 +156,58 @@ impl KernelTime {
     }
 
     pub fn tick(&self) -> MonotonicTimestamp {
+        self.tick_checked().0
+    }
+
+    /// Like [`Self::tick`], also reporting a counter that ran backwards
+    /// since the last tick without going through
+    /// [`Self::notice_discontinuity`].
+    pub fn tick_checked(&self) -> (MonotonicTimestamp, Option<ClockDiscontinuity>) {
         let ticks = HARDWARE_CLOCK.tick();
-        self.last_tick.store(ticks, Ordering::SeqCst);
-        MonotonicTimestamp::new(ticks, HARDWARE_CLOCK.frequency())
+        let jump = self.observe(ticks);
+        (
+            MonotonicTimestamp::new(ticks, HARDWARE_CLOCK.frequency()),
+            jump,
+        )
+    }
+
+    /// Record `ticks` as the latest counter reading. The counter only moves
+    /// forward in normal operation, so a reading below the previous one
+    /// means it was reset behind the kernel's back.
+    pub fn observe(&self, ticks: u64) -> Option<ClockDiscontinuity> {
+        let previous = self.last_tick.swap(ticks, Ordering::SeqCst);
+        if ticks >= previous {
+            return None;
+        }
+        self.discontinuities.fetch_add(1, Ordering::SeqCst);
+        self.unmediated_discontinuities
+            .fetch_add(1, Ordering::SeqCst);
+        Some(ClockDiscontinuity {
+            old_ticks: previous,
+            new_ticks: ticks,
+            mediated: false,
+        })
+    }
+
+    /// Accept a deliberate jump of the counter from `old_ticks` to
+    /// `new_ticks`, so the monotonic guard does not report it.
+    pub fn notice_discontinuity(&self, old_ticks: u64, new_ticks: u64) -> ClockDiscontinuity {
+        self.last_tick.store(new_ticks, Ordering::SeqCst);
+        self.discontinuities.fetch_add(1, Ordering::SeqCst);
+        ClockDiscontinuity {
+            old_ticks,
+            new_ticks,
+            mediated: true,
+        }
+    }
+
+    /// Jumps seen so far, mediated or not.
+    pub fn discontinuity_count(&self) -> u64 {
+        self.discontinuities.load(Ordering::SeqCst)
+    }
+
+    pub fn unmediated_discontinuity_count(&self) -> u64 {
+        self.unmediated_discontinuities.load(Ordering::SeqCst)
     }
 
     pub fn advance_ticks(&self, ticks: u64) -> MonotonicTimestamp {
@@ -80,6 +224,114 @@ impl KernelTime {
     pub fn uptime_ticks(&self) -> u64 {
         HARDWARE_CLOCK.now()
     }
+
+    /// Anchor tick zero to `ts` seconds since the Unix epoch.
+    pub fn set_base_time(&self, ts: u64) {
+        self.base_unix_time.store(ts, Ordering::SeqCst);
+    }
+
+    pub fn base_time(&self) -> u64 {
+        self.base_unix_time.load(Ordering::SeqCst)
+    }
+
+    /// Calendar time `ticks` after the base time, shifted by `offset_seconds`
+    /// for the local time zone.
+    pub fn wall_time(&self, ticks: u64, offset_seconds: i64) -> WallTimestamp {
+        let frequency = HARDWARE_CLOCK.frequency();
+        let uptime_seconds = ticks.checked_div(frequency).unwrap_or(0);
+        let unix_seconds = self
+            .base_time()
+            .saturating_add(uptime_seconds)
+            .min(i64::MAX as u64) as i64;
+        WallTimestamp::from_unix_seconds(unix_seconds.saturating_add(offset_seconds))
+    }
 }
 
 pub static KERNEL_TIME: KernelTime = KernelTime::new();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEW_YEAR_2024: u64 = 1_704_067_200;
+
+    const fn at(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> WallTimestamp {
+        WallTimestamp {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        }
+    }
+
+    #[test]
+    fn wall_time_applies_time_zone_offsets_to_base_time() {
+        let time = KernelTime::new();
+        time.set_base_time(NEW_YEAR_2024);
+
+        assert_eq!(time.wall_time(0, 0), at(2024, 1, 1, 0, 0, 0));
+        assert_eq!(
+            time.wall_time(0, 5 * 3_600 + 30 * 60),
+            at(2024, 1, 1, 5, 30, 0)
+        );
+        assert_eq!(time.wall_time(0, -8 * 3_600), at(2023, 12, 31, 16, 0, 0));
+        assert_eq!(time.wall_time(0, 14 * 3_600), at(2024, 1, 1, 14, 0, 0));
+    }
+
+    #[test]
+    fn backward_reading_is_reported_unless_noticed() {
+        let time = KernelTime::new();
+        assert_eq!(time.observe(10), None);
+        assert_eq!(time.observe(10), None);
+        assert_eq!(
+            time.observe(3),
+            Some(ClockDiscontinuity {
+                old_ticks: 10,
+                new_ticks: 3,
+                mediated: false,
+            })
+        );
+        assert_eq!(time.unmediated_discontinuity_count(), 1);
+
+        let jump = time.notice_discontinuity(4, 1);
+        assert!(jump.is_backward() && jump.mediated);
+        assert_eq!(time.observe(2), None);
+        assert_eq!(time.discontinuity_count(), 2);
+        assert_eq!(time.unmediated_discontinuity_count(), 1);
+    }
+
+    #[test]
+    fn rebased_deadlines_keep_their_remaining_time() {
+        // Forward: untouched, so the first two are now due.
+        assert_eq!(rebase_deadline(150, 100, 1_000), 150);
+        assert_eq!(rebase_deadline(5_000, 100, 1_000), 5_000);
+        // Backward: shifted by the jump, and never before the new now.
+        assert_eq!(rebase_deadline(150, 1_000, 100), 100);
+        assert_eq!(rebase_deadline(1_050, 1_000, 100), 150);
+        assert_eq!(rebase_deadline(1_050, 1_000, 0), 50);
+    }
+
+    #[test]
+    fn unix_seconds_convert_across_leap_days() {
+        assert_eq!(WallTimestamp::from_unix_seconds(0), at(1970, 1, 1, 0, 0, 0));
+        assert_eq!(
+            WallTimestamp::from_unix_seconds(-1),
+            at(1970, 1, 1, 0, 0, 0)
+        );
+        let feb_29 = NEW_YEAR_2024 as i64 + 59 * 86_400 + 3_661;
+        assert_eq!(
+            WallTimestamp::from_unix_seconds(feb_29),
+            at(2024, 2, 29, 1, 1, 1)
+        );
+        assert_eq!(
+            WallTimestamp::from_unix_seconds(feb_29 + 86_400),
+            at(2024, 3, 1, 1, 1, 1)
+        );
+        assert_eq!(
+            WallTimestamp::from_unix_seconds(951_782_400),
+            at(2000, 2, 29, 0, 0, 0)
+        );
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::kernel::process::ProcessId;
 use crate::kernel::thread::ThreadId;
+use crate::kernel::time::rebase_deadline;
 
 pub const MAX_SLEEP_ENTRIES: usize = crate::kernel::thread::MAX_THREADS;
 pub const MAX_PROCESS_TIMERS: usize = crate::kernel::MAX_PROCESSES * 4;
@@ -208,6 +209,53 @@ impl<const SLEEP_CAP: usize, const TIMER_CAP: usize> TimerManager<SLEEP_CAP, TIM
         None
     }
 
+    /// Move every pending deadline across a clock jump from `old_ns` to
+    /// `new_ns`; see [`rebase_deadline`].
+    pub fn rebase(&mut self, old_ns: u128, new_ns: u128) {
+        for entry in self.sleeps.iter_mut().flatten() {
+            entry.wake_deadline_ns = rebase_deadline(entry.wake_deadline_ns, old_ns, new_ns);
+        }
+        for timer in self.timers.iter_mut().flatten() {
+            if timer.armed {
+                timer.wake_deadline_ns = rebase_deadline(timer.wake_deadline_ns, old_ns, new_ns);
+            }
+        }
+    }
+
+    /// Sleeps and armed timers of `owner` whose deadline has passed but that
+    /// have not been expired yet.
+    pub fn expired_for(&self, owner: ProcessId, now_ns: u128) -> usize {
+        let sleeps = self
+            .sleeps
+            .iter()
+            .flatten()
+            .filter(|entry| entry.process == owner && entry.wake_deadline_ns <= now_ns)
+            .count();
+        let timers = self
+            .timers
+            .iter()
+            .flatten()
+            .filter(|timer| timer.owner == owner && timer.armed && timer.wake_deadline_ns <= now_ns)
+            .count();
+        sleeps + timers
+    }
+
+    /// Earliest deadline among pending sleeps and armed timers.
+    pub fn next_deadline(&self) -> Option<u128> {
+        let sleeps = self
+            .sleeps
+            .iter()
+            .flatten()
+            .map(|entry| entry.wake_deadline_ns);
+        let timers = self
+            .timers
+            .iter()
+            .flatten()
+            .filter(|timer| timer.armed)
+            .map(|timer| timer.wake_deadline_ns);
+        sleeps.chain(timers).min()
+    }
+
     fn locate_timer(&self, owner: ProcessId, id: u64) -> Result<usize, TimerError> {
         let mut idx = 0usize;
         while idx < TIMER_CAP {
@@ -47,10 +47,16 @@ pub(super) fn libc_errno(error: KernelError) -> i32 {
         | KernelError::SchedulerFull
         | KernelError::ThreadTableFull
         | KernelError::AllocationFailed
-        | KernelError::FileTableFull => MIRAGE_ENOMEM,
-        KernelError::UnknownProcess | KernelError::UnknownThread => MIRAGE_ESRCH,
-        KernelError::MessageQueueFull => MIRAGE_ENOBUFS,
+        | KernelError::FileTableFull
+        | KernelError::SecurityTableFull => MIRAGE_ENOMEM,
+        KernelError::UnknownProcess
+        | KernelError::ReceiverTerminated
+        | KernelError::UnknownThread => MIRAGE_ESRCH,
+        KernelError::MessageQueueFull
+        | KernelError::NoCredits
+        | KernelError::ClassQuotaExceeded => MIRAGE_ENOBUFS,
         KernelError::MessageQueueEmpty => MIRAGE_EAGAIN,
+        KernelError::MessageCorrupted => MIRAGE_EIO,
         KernelError::SecurityViolation(IsolationError::UnknownTask)
         | KernelError::IsolationFault(IsolationError::UnknownTask) => MIRAGE_ESRCH,
         KernelError::SecurityViolation(
@@ -59,8 +65,14 @@ pub(super) fn libc_errno(error: KernelError) -> i32 {
         | KernelError::IsolationFault(
             IsolationError::CapabilityMissing | IsolationError::PolicyViolation,
         ) => MIRAGE_EACCES,
-        KernelError::SecurityViolation(IsolationError::CapabilityTableFull)
-        | KernelError::IsolationFault(IsolationError::CapabilityTableFull) => MIRAGE_ENOMEM,
+        KernelError::SecurityViolation(
+            IsolationError::CapabilityTableFull | IsolationError::DomainTableFull,
+        )
+        | KernelError::IsolationFault(
+            IsolationError::CapabilityTableFull | IsolationError::DomainTableFull,
+        ) => MIRAGE_ENOMEM,
+        KernelError::SecurityViolation(IsolationError::StackOverflow)
+        | KernelError::IsolationFault(IsolationError::StackOverflow) => MIRAGE_EFAULT,
         KernelError::DeviceNotFound => MIRAGE_ESRCH,
         KernelError::DeviceFault(_) => MIRAGE_EIO,
         KernelError::InvalidSyscall => MIRAGE_ENOSYS,
@@ -0,0 +1,192 @@
+//! 128-bit integer helpers LLVM may emit calls to in no_std x86_64 builds.
+//!
+//! The arithmetic is always compiled so it can be tested; the `__*ti*`
+//! symbols themselves are only exported with the `compiler-builtins-shims`
+//! feature, since they clash with a linked `compiler_builtins`. Nothing here
+//! may use `/`, `%` or checked multiplication on 128-bit operands, as those
+//! lower to the very calls being provided.
+
+/// Unsigned 128-bit quotient and remainder by shift-subtract. A zero divisor
+/// yields quotient zero with `n` as the remainder.
+pub fn udivmod_u128(n: u128, d: u128) -> (u128, u128) {
+    if d == 0 || d > n {
+        return (0, n);
+    }
+    let shift = d.leading_zeros() - n.leading_zeros();
+    let mut divisor = d << shift;
+    let mut quotient = 0u128;
+    let mut remainder = n;
+    let mut step = 0;
+    while step <= shift {
+        quotient <<= 1;
+        if remainder >= divisor {
+            remainder -= divisor;
+            quotient |= 1;
+        }
+        divisor >>= 1;
+        step += 1;
+    }
+    (quotient, remainder)
+}
+
+/// Signed 128-bit quotient and remainder, truncating toward zero. The
+/// remainder takes the sign of `a`; `i128::MIN / -1` wraps to `i128::MIN`.
+pub fn idivmod_i128(a: i128, b: i128) -> (i128, i128) {
+    let (quotient, remainder) = udivmod_u128(a.unsigned_abs(), b.unsigned_abs());
+    let quotient = if (a < 0) != (b < 0) {
+        (quotient as i128).wrapping_neg()
+    } else {
+        quotient as i128
+    };
+    let remainder = if a < 0 {
+        (remainder as i128).wrapping_neg()
+    } else {
+        remainder as i128
+    };
+    (quotient, remainder)
+}
+
+/// Wrapping signed 128-bit product and whether it overflowed.
+pub fn mul_overflow_i128(a: i128, b: i128) -> (i128, bool) {
+    let product = (a as u128).wrapping_mul(b as u128) as i128;
+    let (ua, ub) = (a.unsigned_abs(), b.unsigned_abs());
+    let (a_hi, a_lo) = ((ua >> 64) as u64, ua as u64);
+    let (b_hi, b_lo) = ((ub >> 64) as u64, ub as u64);
+    if a_hi != 0 && b_hi != 0 {
+        return (product, true);
+    }
+    // At most one high half is non-zero, so the cross term is a single
+    // 64x64 product and must fit in 64 bits to survive the shift.
+    let cross = (a_hi as u128) * (b_lo as u128) + (a_lo as u128) * (b_hi as u128);
+    if cross >> 64 != 0 {
+        return (product, true);
+    }
+    let (magnitude, carried) = ((a_lo as u128) * (b_lo as u128)).overflowing_add(cross << 64);
+    let limit = if (a < 0) != (b < 0) {
+        i128::MIN.unsigned_abs()
+    } else {
+        i128::MAX as u128
+    };
+    (product, carried || magnitude > limit)
+}
+
+#[cfg(feature = "compiler-builtins-shims")]
+mod exports {
+    use core::ffi::c_int;
+
+    use super::{idivmod_i128, mul_overflow_i128, udivmod_u128};
+
+    #[cfg_attr(not(test), no_mangle)]
+    pub extern "C" fn __udivti3(n: u128, d: u128) -> u128 {
+        udivmod_u128(n, d).0
+    }
+
+    #[cfg_attr(not(test), no_mangle)]
+    pub extern "C" fn __umodti3(n: u128, d: u128) -> u128 {
+        udivmod_u128(n, d).1
+    }
+
+    #[cfg_attr(not(test), no_mangle)]
+    pub unsafe extern "C" fn __udivmodti4(n: u128, d: u128, rem: *mut u128) -> u128 {
+        let (quotient, remainder) = udivmod_u128(n, d);
+        if !rem.is_null() {
+            *rem = remainder;
+        }
+        quotient
+    }
+
+    #[cfg_attr(not(test), no_mangle)]
+    pub extern "C" fn __divti3(a: i128, b: i128) -> i128 {
+        idivmod_i128(a, b).0
+    }
+
+    #[cfg_attr(not(test), no_mangle)]
+    pub extern "C" fn __modti3(a: i128, b: i128) -> i128 {
+        idivmod_i128(a, b).1
+    }
+
+    #[cfg_attr(not(test), no_mangle)]
+    pub unsafe extern "C" fn __muloti4(a: i128, b: i128, overflow: *mut c_int) -> i128 {
+        let (product, overflowed) = mul_overflow_i128(a, b);
+        if !overflow.is_null() {
+            *overflow = overflowed as c_int;
+        }
+        product
+    }
+}
+
+#[cfg(feature = "compiler-builtins-shims")]
+pub use exports::{__divti3, __modti3, __muloti4, __udivmodti4, __udivti3, __umodti3};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// Operands of assorted widths so both short and long divisions run.
+        fn next_u128(&mut self) -> u128 {
+            let value = ((self.next() as u128) << 64) | self.next() as u128;
+            value >> (self.next() % 128)
+        }
+    }
+
+    #[test]
+    fn unsigned_division_satisfies_quotient_identity() {
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+        let mut round = 0;
+        while round < 2000 {
+            let n = rng.next_u128();
+            let d = rng.next_u128().max(1);
+            let (q, r) = udivmod_u128(n, d);
+            assert!(r < d, "{n} / {d}");
+            assert_eq!(q.wrapping_mul(d).wrapping_add(r), n, "{n} / {d}");
+            assert_eq!((q, r), (n / d, n % d));
+            round += 1;
+        }
+        assert_eq!(udivmod_u128(u128::MAX, 1), (u128::MAX, 0));
+        assert_eq!(udivmod_u128(7, 0), (0, 7));
+    }
+
+    #[test]
+    fn signed_division_truncates_toward_zero() {
+        assert_eq!(idivmod_i128(-7, 2), (-3, -1));
+        assert_eq!(idivmod_i128(7, -2), (-3, 1));
+        assert_eq!(idivmod_i128(i128::MIN, -1), (i128::MIN, 0));
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        let mut round = 0;
+        while round < 2000 {
+            let a = rng.next_u128() as i128;
+            let b = (rng.next_u128() as i128).max(1) * if rng.next() & 1 == 0 { 1 } else { -1 };
+            let (q, r) = idivmod_i128(a, b);
+            assert_eq!(q.wrapping_mul(b).wrapping_add(r), a, "{a} / {b}");
+            assert_eq!((q, r), (a.wrapping_div(b), a.wrapping_rem(b)));
+            round += 1;
+        }
+    }
+
+    #[test]
+    fn signed_multiply_reports_overflow() {
+        assert_eq!(mul_overflow_i128(i128::MAX, 1), (i128::MAX, false));
+        assert_eq!(mul_overflow_i128(i128::MIN, 1), (i128::MIN, false));
+        assert_eq!(mul_overflow_i128(i128::MIN, -1), (i128::MIN, true));
+        assert_eq!(mul_overflow_i128(1 << 64, -(1 << 63)), (i128::MIN, false));
+        assert!(mul_overflow_i128(1 << 64, 1 << 63).1);
+        let mut rng = XorShift(0xdead_beef_cafe_f00d);
+        let mut round = 0;
+        while round < 2000 {
+            let a = rng.next_u128() as i128;
+            let b = rng.next_u128() as i128;
+            assert_eq!(mul_overflow_i128(a, b), a.overflowing_mul(b), "{a} * {b}");
+            round += 1;
+        }
+    }
+}
@@ -17,6 +17,7 @@ use crate::subkernel::SecurityClass;
 pub mod dirent;
 pub mod errno;
 pub mod fcntl;
+pub mod intrinsics;
 pub mod pthread;
 pub mod socket;
 pub mod stdlib;
@@ -38,8 +39,7 @@ pub use unistd::{
     setgid, setuid, write,
 };
 
-pub(super) type DefaultKernel =
-    Kernel<{ crate::kernel::MAX_PROCESSES }, { crate::kernel::MESSAGE_DEPTH }>;
+pub(super) use crate::kernel::DefaultKernel;
 
 pub(super) const MIRAGE_AT_FDCWD: i32 = -100;
 
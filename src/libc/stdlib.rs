@@ -1,10 +1,10 @@
 //! C allocation, conversion, process termination, and environment runtime exports.
 
-use core::ffi::{c_int, c_void};
+use core::ffi::{c_int, c_long, c_longlong, c_void};
 use core::mem;
 use core::ptr;
 
-use crate::kernel::memory::{MemoryProtection, KERNEL_PROCESS_ID};
+use crate::kernel::memory::{MemoryProtection, MmapFlags, KERNEL_PROCESS_ID};
 use crate::kernel::syscall::{
     dispatch_kernel_memory_syscall, SyscallContext, SyscallNumber, SYSCALL_MAX_ARGS,
 };
@@ -39,10 +39,17 @@ fn syscall_malloc_aligned(size: usize, alignment: usize) -> *mut c_void {
     ) as *mut c_void
 }
 
-fn syscall_mmap(length: usize, protection: MemoryProtection) -> *mut c_void {
+fn syscall_mmap(length: usize, protection: MemoryProtection, flags: MmapFlags) -> *mut c_void {
     memory_syscall(
         SyscallNumber::Mmap,
-        [length as u64, protection.bits() as u64, 0, 0, 0, 0],
+        [
+            length as u64,
+            protection.bits() as u64,
+            flags.bits() as u64,
+            0,
+            0,
+            0,
+        ],
     ) as *mut c_void
 }
 
@@ -161,12 +168,15 @@ pub unsafe extern "C" fn mmap(
     _addr: *mut c_void,
     length: usize,
     prot: c_int,
-    _flags: c_int,
+    flags: c_int,
     _fd: c_int,
     _offset: usize,
 ) -> *mut c_void {
     let protection = MemoryProtection::from_bits(prot as u32);
-    syscall_mmap(length, protection)
+    // Only anonymous/populate semantics are forwarded; the kernel heap does not
+    // honour caller-chosen addresses through this shim.
+    let flags = MmapFlags::from_linux_bits(flags as u32).without(MmapFlags::FIXED);
+    syscall_mmap(length, protection, flags)
 }
 
 #[cfg_attr(not(test), no_mangle)]
@@ -177,3 +187,93 @@ pub unsafe extern "C" fn munmap(addr: *mut c_void, length: usize) -> c_int {
         syscall_munmap(addr, length)
     }
 }
+
+/// C `div_t`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DivT {
+    pub quot: c_int,
+    pub rem: c_int,
+}
+
+/// C `ldiv_t`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LdivT {
+    pub quot: c_long,
+    pub rem: c_long,
+}
+
+/// C `lldiv_t`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LldivT {
+    pub quot: c_longlong,
+    pub rem: c_longlong,
+}
+
+// C leaves `abs(INT_MIN)`, `INT_MIN / -1` and division by zero undefined.
+// These shims saturate instead of trapping: the absolute value of the minimum
+// is the maximum, the overflowing quotient is the maximum with remainder
+// zero, and a zero divisor yields quotient zero with the numerator as
+// remainder.
+
+#[cfg_attr(not(test), no_mangle)]
+pub extern "C" fn abs(value: c_int) -> c_int {
+    value.saturating_abs()
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub extern "C" fn labs(value: c_long) -> c_long {
+    value.saturating_abs()
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub extern "C" fn llabs(value: c_longlong) -> c_longlong {
+    value.saturating_abs()
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub extern "C" fn div(numer: c_int, denom: c_int) -> DivT {
+    match (numer.checked_div(denom), numer.checked_rem(denom)) {
+        (Some(quot), Some(rem)) => DivT { quot, rem },
+        _ if denom == 0 => DivT {
+            quot: 0,
+            rem: numer,
+        },
+        _ => DivT {
+            quot: c_int::MAX,
+            rem: 0,
+        },
+    }
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub extern "C" fn ldiv(numer: c_long, denom: c_long) -> LdivT {
+    match (numer.checked_div(denom), numer.checked_rem(denom)) {
+        (Some(quot), Some(rem)) => LdivT { quot, rem },
+        _ if denom == 0 => LdivT {
+            quot: 0,
+            rem: numer,
+        },
+        _ => LdivT {
+            quot: c_long::MAX,
+            rem: 0,
+        },
+    }
+}
+
+#[cfg_attr(not(test), no_mangle)]
+pub extern "C" fn lldiv(numer: c_longlong, denom: c_longlong) -> LldivT {
+    match (numer.checked_div(denom), numer.checked_rem(denom)) {
+        (Some(quot), Some(rem)) => LldivT { quot, rem },
+        _ if denom == 0 => LldivT {
+            quot: 0,
+            rem: numer,
+        },
+        _ => LldivT {
+            quot: c_longlong::MAX,
+            rem: 0,
+        },
+    }
+}
@@ -1,8 +1,8 @@
 //! Backward-compatible facade for Rust runtime C ABI exports.
 
 pub use crate::libc::stdlib::{
-    aligned_alloc, calloc, free, malloc, memalign, mmap, munmap, posix_memalign, realloc,
-    reallocarray,
+    abs, aligned_alloc, calloc, div, free, labs, ldiv, llabs, lldiv, malloc, memalign, mmap,
+    munmap, posix_memalign, realloc, reallocarray, DivT, LdivT, LldivT,
 };
 pub use crate::libc::string::{
     bcmp, bcopy, bzero, memchr, memcmp, memcpy, memmove, memset, strcat, strchr, strcmp, strcpy,
@@ -12,7 +12,7 @@ pub use crate::libc::string::{
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::kernel::memory::{PROT_READ, PROT_WRITE};
+    use crate::kernel::memory::{MAP_ANONYMOUS, MAP_POPULATE, PROT_READ, PROT_WRITE};
     use core::ffi::{c_char, c_int, c_void};
     use core::ptr;
     use std::vec::Vec;
@@ -95,6 +95,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn mmap_populate_returns_zeroed_region() {
+        unsafe {
+            let prot = (PROT_READ | PROT_WRITE) as c_int;
+            let flags = (MAP_ANONYMOUS | MAP_POPULATE) as c_int;
+            let region = mmap(ptr::null_mut(), 8192, prot, flags, -1, 0) as *mut u8;
+            assert!(!region.is_null());
+            for i in 0..8192 {
+                assert_eq!(*region.add(i), 0);
+            }
+            assert_eq!(munmap(region as *mut c_void, 8192), 0);
+        }
+    }
+
     #[test]
     fn calloc_zeroes_memory() {
         unsafe {
@@ -178,4 +192,41 @@ mod tests {
             free(dup as *mut c_void);
         }
     }
+
+    #[test]
+    fn abs_saturates_at_the_minimum() {
+        assert_eq!(abs(-5), 5);
+        assert_eq!(abs(c_int::MIN), c_int::MAX);
+        assert_eq!(labs(core::ffi::c_long::MIN), core::ffi::c_long::MAX);
+        assert_eq!(
+            llabs(core::ffi::c_longlong::MIN + 1),
+            core::ffi::c_longlong::MAX
+        );
+    }
+
+    #[test]
+    fn div_results_round_trip_through_the_c_abi() {
+        let div_fn: extern "C" fn(c_int, c_int) -> DivT = div;
+        let ldiv_fn: extern "C" fn(core::ffi::c_long, core::ffi::c_long) -> LdivT = ldiv;
+        let lldiv_fn: extern "C" fn(core::ffi::c_longlong, core::ffi::c_longlong) -> LldivT = lldiv;
+
+        assert_eq!(div_fn(-7, 2), DivT { quot: -3, rem: -1 });
+        assert_eq!(ldiv_fn(7, -2), LdivT { quot: -3, rem: 1 });
+        let wide = lldiv_fn(1 << 40, 3);
+        assert_eq!(wide.quot * 3 + wide.rem, 1 << 40);
+
+        assert_eq!(
+            div_fn(c_int::MIN, -1),
+            DivT {
+                quot: c_int::MAX,
+                rem: 0
+            }
+        );
+        assert_eq!(div_fn(9, 0), DivT { quot: 0, rem: 9 });
+        assert_eq!(
+            core::mem::size_of::<DivT>(),
+            2 * core::mem::size_of::<c_int>()
+        );
+        assert_eq!(core::mem::size_of::<LldivT>(), 16);
+    }
 }
@@ -15,16 +15,20 @@ use mirage::kernel::boot_phase::{
     boot_phase_validate_no_unresolved, BootPhase,
 };
 #[cfg(all(not(feature = "emergency-boot"), not(feature = "full-boot")))]
+use mirage::kernel::boot_tasks::{BootTask, SecurityPolicy};
+#[cfg(all(not(feature = "emergency-boot"), not(feature = "full-boot")))]
 use mirage::kernel::ipc::MessagePayload;
 #[cfg(not(feature = "emergency-boot"))]
 use mirage::kernel::kso::{
     kso_transition, maybe_retry_pid1_handoff_after_mtss_change, BootContinueResult,
     BootRuntimeDeps, KsoBootNode, KsoContext, KsoState,
 };
+#[cfg(all(not(feature = "emergency-boot"), not(feature = "full-boot")))]
+use mirage::kernel::process::ProcessPriority;
 #[cfg(not(feature = "emergency-boot"))]
 use mirage::kernel::{cpu, debug_shell, Kernel, MAX_PROCESSES, MESSAGE_DEPTH};
 #[cfg(all(not(feature = "emergency-boot"), not(feature = "full-boot")))]
-use mirage::subkernel::{Credentials, SecurityClass};
+use mirage::subkernel::SecurityClass;
 #[cfg(all(not(feature = "emergency-boot"), not(feature = "full-boot")))]
 use mirage::supervisor::mock_service::{
     MockManifestCapability, MockManifestService, ECHO_IPC_ENDPOINT, ECHO_SERVICE_IMAGE,
@@ -32,6 +36,17 @@ use mirage::supervisor::mock_service::{
 };
 use mirage::supervisor::Supervisor;
 
+/// Processes started before the echo smoke check. The first entry is init.
+#[cfg(all(not(feature = "emergency-boot"), not(feature = "full-boot")))]
+const BOOT_TASKS: [BootTask; 1] = [BootTask {
+    name: "echo-client",
+    entry_point: 0,
+    priority: ProcessPriority::Critical,
+    role: "system",
+    args: &[],
+    critical: true,
+}];
+
 #[cfg(not(feature = "emergency-boot"))]
 fn bootflow(seq: u8, phase: &'static str, status: &'static str) {
     mirage::kprintln!("[bootflow {}] phase={} {}", seq, phase, status);
@@ -115,6 +130,16 @@ pub extern "Rust" fn kernel_main(boot_info: BootInfo) -> ! {
         #[cfg(any(feature = "bootdiag-serial", feature = "bootdiag-verbose"))]
         mirage::kprintln!("[bootdiag] boot info apply returned");
         mirage::kprintln!("[bootflow 3.9] boot_info_applied: bootstrap_with_boot_info returned ok");
+        if let Err(error) = kernel.post_bootstrap_check() {
+            boot_phase_failed(BootPhase::BootInfoApplied, "post-bootstrap check failed");
+            bootflow(
+                3,
+                "boot_info_applied",
+                "failed: post-bootstrap check failed",
+            );
+            mirage::kprintln!("post-bootstrap check failed: {:?}", error);
+            mirage::arch::x86_64::panic_halt();
+        }
         boot_phase_ok(BootPhase::BootInfoApplied);
         bootflow(3, "boot_info_applied", "ok");
         mirage::kprintln!("boot info applied");
@@ -249,8 +274,10 @@ pub extern "Rust" fn kernel_main(boot_info: BootInfo) -> ! {
             match supervisor.launch_mock_manifest_service(kernel, echo_service) {
                 Ok(echo_report) => {
                     mirage::kprintln!("service running: echo-service");
-                    match kernel.spawn_initial_process(Credentials::system()) {
-                        Ok(caller) => {
+                    let boot_report =
+                        kernel.spawn_boot_tasks(&BOOT_TASKS, &SecurityPolicy::default_roles());
+                    match boot_report.outcome(0).map(|outcome| outcome.result) {
+                        Some(Ok(caller)) => {
                             let payload = MessagePayload::from_slice(
                                 SecurityClass::Internal,
                                 b"mirage echo smoke",
@@ -274,12 +301,15 @@ pub extern "Rust" fn kernel_main(boot_info: BootInfo) -> ! {
                                 }
                             }
                         }
-                        Err(error) => {
+                        Some(Err(error)) => {
                             mirage::kprintln!(
                                 "echo-service IPC check failed: caller spawn error: {:?}",
                                 error
                             );
                         }
+                        None => {
+                            mirage::kprintln!("echo-service IPC check failed: no boot tasks");
+                        }
                     }
                 }
                 Err(error) => {
@@ -3,7 +3,7 @@
 use crate::kernel::memory::MemoryProtection;
 use crate::kernel::process::{ExecRequest, ProcessId, MAX_SUPPLEMENTARY_GROUPS};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SecurityLevel {
     Public = 0,
     Internal = 1,
@@ -11,7 +11,7 @@ pub enum SecurityLevel {
     System = 3,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct SecurityLabel {
     level: SecurityLevel,
     categories: u32,
@@ -71,7 +71,7 @@ impl SecurityClass {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum IsolationLevel {
     None,
     Process,
@@ -265,7 +265,7 @@ impl CapabilityRecord {
 
 pub const MAX_CAPABILITY_RECORDS: usize = 256;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct CapabilitySet {
     flags: u32,
 }
@@ -317,7 +317,7 @@ impl CapabilitySet {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Credentials {
     label: SecurityLabel,
     capabilities: CapabilitySet,
@@ -442,6 +442,24 @@ impl Credentials {
     }
 }
 
+/// IPC visibility scope of a task. Tasks in different namespaces cannot see
+/// or message each other; namespace 0 is the global one every task starts
+/// in unless a `CAP_KERNEL` parent spawns it elsewhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IpcNamespace(u16);
+
+impl IpcNamespace {
+    pub const GLOBAL: Self = Self(0);
+
+    pub const fn new(raw: u16) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(&self) -> u16 {
+        self.0
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct TaskDomain {
     pid: ProcessId,
@@ -455,6 +473,7 @@ pub struct TaskDomain {
     supplementary_groups: [u16; MAX_SUPPLEMENTARY_GROUPS],
     supplementary_group_count: usize,
     quarantine_events: u32,
+    namespace: IpcNamespace,
 }
 
 impl TaskDomain {
@@ -471,9 +490,46 @@ impl TaskDomain {
             supplementary_groups: creds.supplementary_groups(),
             supplementary_group_count: creds.supplementary_group_count(),
             quarantine_events: 0,
+            namespace: IpcNamespace::GLOBAL,
         }
     }
 
+    pub const fn pid(&self) -> ProcessId {
+        self.pid
+    }
+
+    pub const fn label(&self) -> SecurityLabel {
+        self.label
+    }
+
+    pub const fn capabilities(&self) -> CapabilitySet {
+        self.capabilities
+    }
+
+    pub const fn isolation(&self) -> IsolationLevel {
+        self.isolation
+    }
+
+    pub const fn quarantine_events(&self) -> u32 {
+        self.quarantine_events
+    }
+
+    pub const fn namespace(&self) -> IpcNamespace {
+        self.namespace
+    }
+
+    /// A System-level task in the global namespace supervises every
+    /// namespace: it sees all tasks, and all tasks see it.
+    fn supervises_namespaces(&self) -> bool {
+        self.namespace == IpcNamespace::GLOBAL && self.label.level() == SecurityLevel::System
+    }
+
+    fn sees(&self, other: &TaskDomain) -> bool {
+        self.namespace == other.namespace
+            || self.supervises_namespaces()
+            || other.supervises_namespaces()
+    }
+
     pub fn can_transmit(&self, class: SecurityClass) -> bool {
         self.capabilities.allows_ipc() && self.label.dominates(&class.as_label())
     }
@@ -521,6 +577,9 @@ pub enum IsolationError {
     PolicyViolation,
     CapabilityMissing,
     CapabilityTableFull,
+    DomainTableFull,
+    /// A thread ran past the end of its stack.
+    StackOverflow,
 }
 
 #[derive(Clone, Copy)]
@@ -561,7 +620,11 @@ impl<const MAX: usize> SecurityKernel<MAX> {
     ) -> Result<(), IsolationError> {
         if let Some(idx) = self.find_domain_index(pid) {
             let previous = self.domains[idx];
-            self.domains[idx] = Some(TaskDomain::from_credentials(pid, creds));
+            let mut domain = TaskDomain::from_credentials(pid, creds);
+            if let Some(previous) = previous {
+                domain.namespace = previous.namespace;
+            }
+            self.domains[idx] = Some(domain);
             self.revoke_all_capabilities(pid);
             if let Err(err) = self.seed_initial_capabilities(pid, creds) {
                 self.revoke_all_capabilities(pid);
@@ -571,20 +634,68 @@ impl<const MAX: usize> SecurityKernel<MAX> {
             return Ok(());
         }
 
-        let mut idx = 0;
+        let idx = self
+            .free_domain_index(0)
+            .ok_or(IsolationError::DomainTableFull)?;
+        self.insert_domain(idx, pid, creds)
+    }
+
+    /// Register every `(pid, credentials)` pair in order, as `register_task`
+    /// would, and return how many were registered. The batch stops at the
+    /// first entry that finds the domain or capability table full; entries
+    /// before it stay registered. Free domain slots are searched once across
+    /// the whole batch rather than from the start for each entry.
+    pub fn register_bulk(
+        &mut self,
+        entries: &[(ProcessId, Credentials)],
+    ) -> Result<usize, IsolationError> {
+        let mut registered = 0usize;
+        let mut cursor = 0usize;
+        for &(pid, creds) in entries {
+            let result = if self.find_domain_index(pid).is_some() {
+                self.register_task(pid, creds)
+            } else {
+                match self.free_domain_index(cursor) {
+                    Some(idx) => {
+                        cursor = idx + 1;
+                        self.insert_domain(idx, pid, creds)
+                    }
+                    None => Err(IsolationError::DomainTableFull),
+                }
+            };
+            match result {
+                Ok(()) => registered += 1,
+                Err(IsolationError::DomainTableFull | IsolationError::CapabilityTableFull) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        debug_assert!(self.verify_integrity());
+        Ok(registered)
+    }
+
+    fn free_domain_index(&self, from: usize) -> Option<usize> {
+        let mut idx = from;
         while idx < MAX {
             if self.domains[idx].is_none() {
-                self.domains[idx] = Some(TaskDomain::from_credentials(pid, creds));
-                if let Err(err) = self.seed_initial_capabilities(pid, creds) {
-                    self.domains[idx] = None;
-                    return Err(err);
-                }
-                return Ok(());
+                return Some(idx);
             }
             idx += 1;
         }
+        None
+    }
 
-        Err(IsolationError::PolicyViolation)
+    fn insert_domain(
+        &mut self,
+        idx: usize,
+        pid: ProcessId,
+        creds: Credentials,
+    ) -> Result<(), IsolationError> {
+        self.domains[idx] = Some(TaskDomain::from_credentials(pid, creds));
+        if let Err(err) = self.seed_initial_capabilities(pid, creds) {
+            self.domains[idx] = None;
+            return Err(err);
+        }
+        Ok(())
     }
 
     pub fn revoke_task(&mut self, pid: ProcessId) {
@@ -592,6 +703,48 @@ impl<const MAX: usize> SecurityKernel<MAX> {
             self.domains[idx] = None;
         }
         self.revoke_all_capabilities(pid);
+        debug_assert!(self.verify_integrity());
+    }
+
+    /// Self-check the domain and capability tables.
+    ///
+    /// Domains are stored in a linear array, so the invariants are that no
+    /// PID owns more than one domain slot, every live capability belongs to a
+    /// registered domain, and capability ids are non-zero and unique. Derived
+    /// capabilities may outlive their parent record, so lineage is not checked.
+    pub fn verify_integrity(&self) -> bool {
+        let mut idx = 0;
+        while idx < MAX {
+            if let Some(domain) = self.domains[idx] {
+                let mut other = idx + 1;
+                while other < MAX {
+                    if matches!(self.domains[other], Some(dup) if dup.pid == domain.pid) {
+                        return false;
+                    }
+                    other += 1;
+                }
+            }
+            idx += 1;
+        }
+
+        let mut idx = 0;
+        while idx < MAX_CAPABILITY_RECORDS {
+            if let Some(record) = self.capabilities[idx] {
+                if record.id.raw() == 0 || self.find_domain_index(record.owner).is_none() {
+                    return false;
+                }
+                let mut other = idx + 1;
+                while other < MAX_CAPABILITY_RECORDS {
+                    if matches!(self.capabilities[other], Some(dup) if dup.id == record.id) {
+                        return false;
+                    }
+                    other += 1;
+                }
+            }
+            idx += 1;
+        }
+
+        true
     }
 
     pub fn grant_capability(
@@ -709,6 +862,9 @@ impl<const MAX: usize> SecurityKernel<MAX> {
     ) -> Result<(), IsolationError> {
         let sender_domain = self.domain(sender)?;
         let receiver_domain = self.domain(receiver)?;
+        if !sender_domain.sees(&receiver_domain) {
+            return Err(IsolationError::UnknownTask);
+        }
 
         self.check_capability(
             sender,
@@ -729,6 +885,34 @@ impl<const MAX: usize> SecurityKernel<MAX> {
         Ok(())
     }
 
+    /// Whether IPC namespaces let `viewer` see `target`. Unregistered tasks
+    /// are not hidden here; the other checks report them.
+    pub fn ipc_visible(&self, viewer: ProcessId, target: ProcessId) -> bool {
+        match (self.domain(viewer), self.domain(target)) {
+            (Ok(viewer), Ok(target)) => viewer.sees(&target),
+            _ => true,
+        }
+    }
+
+    pub fn ipc_namespace(&self, pid: ProcessId) -> Result<IpcNamespace, IsolationError> {
+        Ok(self.domain(pid)?.namespace)
+    }
+
+    /// Move `pid` into `namespace`. Callers gate this to spawn time.
+    pub fn set_ipc_namespace(
+        &mut self,
+        pid: ProcessId,
+        namespace: IpcNamespace,
+    ) -> Result<(), IsolationError> {
+        let idx = self
+            .find_domain_index(pid)
+            .ok_or(IsolationError::UnknownTask)?;
+        if let Some(domain) = self.domains[idx].as_mut() {
+            domain.namespace = namespace;
+        }
+        Ok(())
+    }
+
     pub fn authorize_device_access(
         &self,
         pid: ProcessId,
@@ -770,6 +954,15 @@ impl<const MAX: usize> SecurityKernel<MAX> {
         )
     }
 
+    /// Authorize kernel-wide configuration changes, which require `CAP_KERNEL`.
+    pub fn authorize_kernel_control(&self, pid: ProcessId) -> Result<(), IsolationError> {
+        if self.domain(pid)?.capabilities.allows_kernel_access() {
+            Ok(())
+        } else {
+            Err(IsolationError::CapabilityMissing)
+        }
+    }
+
     /// Authorize changes to mutable Unix credential state (uid/gid/groups).
     pub fn authorize_credential_update(&self, pid: ProcessId) -> Result<(), IsolationError> {
         self.domain(pid)?;
@@ -890,6 +1083,31 @@ impl<const MAX: usize> SecurityKernel<MAX> {
         ))
     }
 
+    /// Copy of the domain `pid` runs under, for diagnostics.
+    pub fn task_domain(&self, pid: ProcessId) -> Result<TaskDomain, IsolationError> {
+        self.domain(pid)
+    }
+
+    /// PIDs that currently own a task domain.
+    pub fn registered_tasks(&self) -> impl Iterator<Item = ProcessId> + '_ {
+        self.domains.iter().flatten().map(|domain| domain.pid)
+    }
+
+    /// Copies each registered task's PID and capability set into `out`, in
+    /// domain table order. Returns the number of entries written; tasks that
+    /// do not fit are left out.
+    pub fn capability_report(&self, out: &mut [(ProcessId, CapabilitySet)]) -> usize {
+        let mut written = 0;
+        for domain in self.domains.iter().flatten() {
+            let Some(slot) = out.get_mut(written) else {
+                break;
+            };
+            *slot = (domain.pid, domain.capabilities);
+            written += 1;
+        }
+        written
+    }
+
     pub fn enforce_isolation(&self, pid: ProcessId) -> Result<(), IsolationError> {
         let domain = self.domain(pid)?;
         match domain.isolation {
@@ -1168,6 +1386,145 @@ mod tests {
         )
     }
 
+    fn hash_of(creds: &Credentials) -> u64 {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        creds.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn credentials_compare_and_hash_by_every_field() {
+        let mut groups = [0; MAX_SUPPLEMENTARY_GROUPS];
+        groups[..2].copy_from_slice(&[10, 20]);
+        let mut other_groups = groups;
+        other_groups[1] = 21;
+        let build = |egid, groups| {
+            Credentials::with_unix_credentials(
+                SecurityLabel::new(SecurityLevel::Internal, 0b11),
+                CapabilitySet::ipc_io(),
+                IsolationLevel::Process,
+                1000,
+                1000,
+                100,
+                egid,
+                groups,
+                2,
+            )
+        };
+        let creds = build(100, groups);
+        assert_eq!(creds, build(100, groups));
+        assert_eq!(hash_of(&creds), hash_of(&build(100, groups)));
+        assert_eq!(Credentials::user(), Credentials::user());
+
+        assert_ne!(creds, build(101, groups));
+        assert_ne!(creds, build(100, other_groups));
+        assert_ne!(Credentials::system(), Credentials::user());
+        assert_ne!(
+            Credentials::new(
+                SecurityLabel::internal(),
+                CapabilitySet::ipc(),
+                IsolationLevel::None
+            ),
+            Credentials::new(
+                SecurityLabel::internal(),
+                CapabilitySet::ipc_io(),
+                IsolationLevel::None
+            )
+        );
+    }
+
+    #[test]
+    fn bulk_registration_fills_to_capacity_and_stops() {
+        let mut security: SecurityKernel<8> = SecurityKernel::new();
+        security
+            .register_task(pid(3), Credentials::system())
+            .unwrap();
+        let batch: [(ProcessId, Credentials); 10] = core::array::from_fn(|idx| {
+            let creds = if idx % 2 == 0 {
+                Credentials::system()
+            } else {
+                Credentials::user()
+            };
+            (pid(idx as u64 + 1), creds)
+        });
+
+        // pid 3 is already registered, so the batch fits eight of its
+        // entries: pids 1 to 8.
+        assert_eq!(security.register_bulk(&batch), Ok(8));
+        for &(pid, creds) in &batch[..8] {
+            assert_eq!(security.credentials(pid), Ok(creds));
+        }
+        for &(pid, _) in &batch[8..] {
+            assert_eq!(security.credentials(pid), Err(IsolationError::UnknownTask));
+        }
+        assert!(security.verify_integrity());
+        assert_eq!(security.register_bulk(&batch[8..]), Ok(0));
+        assert_eq!(security.register_bulk(&[]), Ok(0));
+    }
+
+    #[test]
+    fn ipc_namespaces_hide_tasks_except_the_global_supervisor() {
+        let mut security: SecurityKernel<4> = SecurityKernel::new();
+        security
+            .register_task(pid(1), Credentials::system())
+            .unwrap();
+        security.register_task(pid(2), Credentials::user()).unwrap();
+        security.register_task(pid(3), Credentials::user()).unwrap();
+        security
+            .set_ipc_namespace(pid(2), IpcNamespace::new(7))
+            .unwrap();
+
+        assert!(!security.ipc_visible(pid(2), pid(3)));
+        assert_eq!(
+            security.authorize_ipc(pid(3), pid(2), SecurityClass::Public),
+            Err(IsolationError::UnknownTask)
+        );
+        assert!(security.ipc_visible(pid(1), pid(2)));
+        assert!(security.ipc_visible(pid(2), pid(1)));
+        security
+            .authorize_ipc(pid(1), pid(2), SecurityClass::Public)
+            .unwrap();
+
+        // Re-registering credentials keeps the task in its namespace.
+        security.register_task(pid(2), Credentials::user()).unwrap();
+        assert_eq!(security.ipc_namespace(pid(2)), Ok(IpcNamespace::new(7)));
+    }
+
+    #[test]
+    fn verify_integrity_holds_across_registration_and_removal() {
+        let mut security: SecurityKernel<4> = SecurityKernel::new();
+        assert!(security.verify_integrity());
+
+        security
+            .register_task(pid(1), Credentials::system())
+            .unwrap();
+        security.register_task(pid(2), Credentials::user()).unwrap();
+        security.register_task(pid(3), Credentials::user()).unwrap();
+        security
+            .derive_inherited_child_capabilities(pid(1), pid(2))
+            .unwrap();
+        assert!(security.verify_integrity());
+
+        security.revoke_task(pid(2));
+        assert!(security.verify_integrity());
+        security.register_task(pid(4), Credentials::user()).unwrap();
+        security
+            .register_task(pid(3), Credentials::system())
+            .unwrap();
+        assert!(security.verify_integrity());
+
+        // Children keep inherited capabilities after the parent exits.
+        security
+            .derive_inherited_child_capabilities(pid(1), pid(4))
+            .unwrap();
+        security.revoke_task(pid(1));
+        assert!(security.verify_integrity());
+
+        security.domains[0] = security.domains[1];
+        assert!(!security.verify_integrity());
+    }
+
     #[test]
     fn authorize_exec_allows_same_credentials_without_spawn_capability() {
         let mut security: SecurityKernel<4> = SecurityKernel::new();
@@ -1404,4 +1761,46 @@ mod tests {
             Ok(())
         );
     }
+
+    #[test]
+    fn register_task_reports_full_domain_table_precisely() {
+        let mut security: SecurityKernel<2> = SecurityKernel::new();
+        security.register_task(pid(1), Credentials::user()).unwrap();
+        security.register_task(pid(2), Credentials::user()).unwrap();
+
+        assert_eq!(
+            security.register_task(pid(3), Credentials::user()),
+            Err(IsolationError::DomainTableFull)
+        );
+        assert_eq!(
+            security.register_task(pid(2), Credentials::system()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn capability_report_lists_each_task_with_its_flags() {
+        let mut security: SecurityKernel<4> = SecurityKernel::new();
+        security
+            .register_task(pid(1), Credentials::system())
+            .unwrap();
+        security.register_task(pid(2), Credentials::user()).unwrap();
+        let io = Credentials::new(
+            SecurityLabel::internal(),
+            CapabilitySet::ipc_io(),
+            IsolationLevel::Process,
+        );
+        security.register_task(pid(3), io).unwrap();
+        security.revoke_task(pid(2));
+
+        let mut out = [(pid(0), CapabilitySet::none()); 4];
+        assert_eq!(security.capability_report(&mut out), 2);
+        assert_eq!(out[0], (pid(1), Credentials::system().capabilities()));
+        assert_eq!(out[1], (pid(3), CapabilitySet::new(CAP_IPC | CAP_IO)));
+        assert!(out[1].1.allows_io() && !out[1].1.allows_spawn());
+
+        let mut short = [(pid(0), CapabilitySet::none()); 1];
+        assert_eq!(security.capability_report(&mut short), 1);
+        assert_eq!(short[0].0, pid(1));
+    }
 }
@@ -273,7 +273,7 @@ pub(crate) fn launch_echo_service_from_validated_manifest<
     let mut echo = EchoService::new();
     echo.receive_endpoint_capability(endpoint_capability)?;
     echo.register_endpoint(RegistryServiceId::EchoIpc)?;
-    kernel.register_endpoint(supervisor_pid, RegistryServiceId::EchoIpc, service_pid)?;
+    kernel.register_service(supervisor_pid, RegistryServiceId::EchoIpc, service_pid)?;
 
     Ok(MockServiceLaunchReport {
         supervisor_pid,
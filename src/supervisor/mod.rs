@@ -315,8 +315,12 @@ pub trait SupervisorMtssBoundary {
     ) -> Result<(), KernelError>;
 }
 
-impl<const NPROC: usize, const MSG_DEPTH: usize> SupervisorMtssBoundary
-    for Kernel<NPROC, MSG_DEPTH>
+impl<
+        const NPROC: usize,
+        const MSG_DEPTH: usize,
+        const MAX_CORES: usize,
+        const MAX_THREADS: usize,
+    > SupervisorMtssBoundary for Kernel<NPROC, MSG_DEPTH, MAX_CORES, MAX_THREADS>
 {
     fn mtss_spawn_task(&mut self, request: SpawnTaskRequest) -> Result<ProcessId, KernelError> {
         self.spawn_task(request)
@@ -755,7 +759,7 @@ impl<const CAP: usize> ServiceStartupReport<CAP> {
             while endpoint_idx < MAX_SERVICE_ENDPOINTS {
                 if let Some(endpoint) = record.claimed_endpoints[endpoint_idx] {
                     if let Some(authorizer) = authorizer {
-                        kernel.register_endpoint(authorizer, endpoint.service, pid)?;
+                        kernel.register_service(authorizer, endpoint.service, pid)?;
                     }
                 }
                 endpoint_idx += 1;
@@ -1685,7 +1689,7 @@ impl Supervisor {
         let mut idx = 0usize;
         while idx < MINIMAL_CORE_SERVICES.len() {
             let service = MINIMAL_CORE_SERVICES[idx];
-            match kernel.register_endpoint(supervisor_pid, service, supervisor_pid) {
+            match kernel.register_service(supervisor_pid, service, supervisor_pid) {
                 Ok(()) => {
                     crate::kprintln!("supervisor: registered core service '{}'", service.name());
                     report.push_registration(service, supervisor_pid);
@@ -1806,7 +1810,7 @@ impl Supervisor {
                                                 registry_service,
                                             );
                                             if let Some(authorizer) = parent {
-                                                let _ = kernel.register_endpoint(
+                                                let _ = kernel.register_service(
                                                     authorizer,
                                                     registry_service,
                                                     pid,
@@ -2247,7 +2251,7 @@ mod tests {
         kernel.revoke_task_capabilities(service_pid);
 
         assert!(matches!(
-            kernel.register_endpoint(supervisor_pid, RegistryServiceId::EchoIpc, service_pid),
+            kernel.register_service(supervisor_pid, RegistryServiceId::EchoIpc, service_pid),
             Err(KernelError::SecurityViolation(
                 IsolationError::CapabilityMissing
             ))
@@ -2278,7 +2282,7 @@ mod tests {
             .unwrap();
 
         kernel
-            .register_endpoint(supervisor_pid, RegistryServiceId::EchoIpc, service_pid)
+            .register_service(supervisor_pid, RegistryServiceId::EchoIpc, service_pid)
             .unwrap();
         assert_eq!(
             kernel.service_owner(RegistryServiceId::EchoIpc),